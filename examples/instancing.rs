@@ -28,14 +28,14 @@ impl Stage {
              0.0,   r, 0.0,       1.0, 0.0, 1.0, 1.0
         ];
         // vertex buffer for static geometry
-        let geometry_vertex_buffer = Buffer::immutable(ctx, BufferType::VertexBuffer, &vertices);
+        let geometry_vertex_buffer = Buffer::immutable(ctx, BufferType::VertexBuffer, vertices);
 
         #[rustfmt::skip]
         let indices: &[u16] = &[
             0, 1, 2,    0, 2, 3,    0, 3, 4,    0, 4, 1,
             5, 1, 2,    5, 2, 3,    5, 3, 4,    5, 4, 1
         ];
-        let index_buffer = Buffer::immutable(ctx, BufferType::IndexBuffer, &indices);
+        let index_buffer = Buffer::immutable(ctx, BufferType::IndexBuffer, indices);
 
         // empty, dynamic instance-data vertex buffer
         let positions_vertex_buffer = Buffer::stream(
@@ -46,7 +46,7 @@ impl Stage {
 
         let bindings = Bindings {
             vertex_buffers: vec![geometry_vertex_buffer, positions_vertex_buffer],
-            index_buffer: index_buffer,
+            index_buffer,
             images: vec![],
         };
 
@@ -176,6 +176,7 @@ mod shader {
         uniforms: UniformBlockLayout {
             uniforms: &[("mvp", UniformType::Mat4)],
         },
+        shared_uniforms: &[],
     };
 
     #[repr(C)]
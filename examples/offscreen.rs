@@ -63,7 +63,7 @@ impl Stage {
              1.0,  1.0, -1.0,    1.0, 0.0, 0.5, 1.0,     0.0, 1.0
         ];
 
-        let vertex_buffer = Buffer::immutable(ctx, BufferType::VertexBuffer, &vertices);
+        let vertex_buffer = Buffer::immutable(ctx, BufferType::VertexBuffer, vertices);
 
         #[rustfmt::skip]
         let indices: &[u16] = &[
@@ -75,17 +75,17 @@ impl Stage {
             22, 21, 20,  23, 22, 20
         ];
 
-        let index_buffer = Buffer::immutable(ctx, BufferType::IndexBuffer, &indices);
+        let index_buffer = Buffer::immutable(ctx, BufferType::IndexBuffer, indices);
 
         let offscreen_bind = Bindings {
-            vertex_buffers: vec![vertex_buffer.clone()],
-            index_buffer: index_buffer.clone(),
+            vertex_buffers: vec![vertex_buffer],
+            index_buffer,
             images: vec![],
         };
 
         let display_bind = Bindings {
             vertex_buffers: vec![vertex_buffer],
-            index_buffer: index_buffer,
+            index_buffer,
             images: vec![color_img],
         };
 
@@ -233,6 +233,7 @@ mod display_shader {
         uniforms: UniformBlockLayout {
             uniforms: &[("mvp", UniformType::Mat4)],
         },
+        shared_uniforms: &[],
     };
 
     #[repr(C)]
@@ -271,5 +272,6 @@ mod offscreen_shader {
         uniforms: UniformBlockLayout {
             uniforms: &[("mvp", UniformType::Mat4)],
         },
+        shared_uniforms: &[],
     };
 }
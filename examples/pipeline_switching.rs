@@ -0,0 +1,173 @@
+use miniquad::*;
+
+// Two pipelines reading the same vertex buffer, alternated for every draw.
+// They give the same attributes different formats and offsets: with an attribute
+// set up for the wrong pipeline the triangles show up garbled or with wrong colors.
+
+const GRID: usize = 12;
+
+struct Stage {
+    float_color: Pipeline,
+    byte_color: Pipeline,
+    bindings: Bindings,
+}
+
+/// Stride of both vertex formats, the buffer holds 3 vertices of each
+const STRIDE: usize = 24;
+
+fn float_color_vertex(bytes: &mut Vec<u8>, pos: [f32; 2], color: [f32; 4]) {
+    // pos Float2, color0 Float4
+    for value in pos.iter().chain(color.iter()) {
+        bytes.extend_from_slice(&value.to_ne_bytes());
+    }
+}
+
+fn byte_color_vertex(bytes: &mut Vec<u8>, pos: [f32; 2], color: [u8; 4]) {
+    // color0 Byte4, pos Float2, padding
+    let start = bytes.len();
+    bytes.extend_from_slice(&color);
+    for value in pos.iter() {
+        bytes.extend_from_slice(&value.to_ne_bytes());
+    }
+    bytes.resize(start + STRIDE, 0);
+}
+
+impl Stage {
+    pub fn new(ctx: &mut Context) -> Stage {
+        let mut vertices = vec![];
+        float_color_vertex(&mut vertices, [-0.4, -0.4], [1., 0., 0., 1.]);
+        float_color_vertex(&mut vertices, [0.4, -0.4], [0., 1., 0., 1.]);
+        float_color_vertex(&mut vertices, [0., 0.4], [0., 0., 1., 1.]);
+        byte_color_vertex(&mut vertices, [-0.4, 0.4], [255, 255, 0, 255]);
+        byte_color_vertex(&mut vertices, [0.4, 0.4], [0, 255, 255, 255]);
+        byte_color_vertex(&mut vertices, [0., -0.4], [255, 0, 255, 255]);
+        let vertex_buffer = Buffer::immutable(ctx, BufferType::VertexBuffer, &vertices);
+        let index_buffer = Buffer::immutable(ctx, BufferType::IndexBuffer, &[0u16, 1, 2, 3, 4, 5]);
+        let bindings = Bindings {
+            vertex_buffers: vec![vertex_buffer],
+            index_buffer,
+            images: vec![],
+        };
+
+        let layout = [BufferLayout {
+            stride: STRIDE as i32,
+            ..Default::default()
+        }];
+        let shader = Shader::new(ctx, shader::VERTEX, shader::FRAGMENT, shader::META);
+        let float_color = Pipeline::new(
+            ctx,
+            &layout,
+            &[
+                VertexAttribute::new("pos", VertexFormat::Float2),
+                VertexAttribute::new("color0", VertexFormat::Float4),
+            ],
+            shader,
+        );
+        let shader = Shader::new(ctx, shader::VERTEX_BYTES, shader::FRAGMENT, shader::META);
+        let byte_color = Pipeline::new(
+            ctx,
+            &layout,
+            &[
+                VertexAttribute::new("color0", VertexFormat::Byte4),
+                VertexAttribute::new("pos", VertexFormat::Float2),
+            ],
+            shader,
+        );
+
+        Stage {
+            float_color,
+            byte_color,
+            bindings,
+        }
+    }
+}
+
+impl EventHandler for Stage {
+    fn update(&mut self, _ctx: &mut Context) {}
+
+    fn draw(&mut self, ctx: &mut Context) {
+        ctx.begin_default_pass(PassAction::clear_color(0.1, 0.1, 0.1, 1.));
+        let cell = 2. / GRID as f32;
+        for n in 0..GRID * GRID {
+            let (x, y) = ((n % GRID) as f32, (n / GRID) as f32);
+            let uniforms = shader::Uniforms {
+                offset: (-1. + (x + 0.5) * cell, -1. + (y + 0.5) * cell),
+                scale: cell,
+            };
+            if n % 2 == 0 {
+                ctx.apply_pipeline(&self.float_color);
+                ctx.apply_bindings(&self.bindings);
+                ctx.apply_uniforms(&uniforms);
+                ctx.draw(0, 3, 1);
+            } else {
+                ctx.apply_pipeline(&self.byte_color);
+                ctx.apply_bindings(&self.bindings);
+                ctx.apply_uniforms(&uniforms);
+                ctx.draw(3, 3, 1);
+            }
+        }
+        ctx.end_render_pass();
+
+        ctx.commit_frame();
+    }
+}
+
+fn main() {
+    miniquad::start(conf::Conf::default(), |ctx| Box::new(Stage::new(ctx)));
+}
+
+mod shader {
+    use miniquad::*;
+
+    pub const VERTEX: &str = r#"#version 100
+    attribute vec2 pos;
+    attribute vec4 color0;
+
+    uniform vec2 offset;
+    uniform float scale;
+
+    varying lowp vec4 color;
+
+    void main() {
+        gl_Position = vec4(pos * scale + offset, 0, 1);
+        color = color0;
+    }"#;
+
+    pub const VERTEX_BYTES: &str = r#"#version 100
+    attribute vec4 color0;
+    attribute vec2 pos;
+
+    uniform vec2 offset;
+    uniform float scale;
+
+    varying lowp vec4 color;
+
+    void main() {
+        gl_Position = vec4(pos * scale + offset, 0, 1);
+        color = color0 / 255.;
+    }"#;
+
+    pub const FRAGMENT: &str = r#"#version 100
+    varying lowp vec4 color;
+
+    void main() {
+        gl_FragColor = color;
+    }"#;
+
+    pub const META: ShaderMeta = ShaderMeta {
+        images: &[],
+        uniforms: UniformBlockLayout {
+            uniforms: &[
+                ("offset", UniformType::Float2),
+                ("scale", UniformType::Float1),
+            ],
+        },
+        shared_uniforms: &[],
+    };
+
+    #[repr(C)]
+    pub struct Uniforms {
+        pub offset: (f32, f32),
+        pub scale: f32,
+    }
+}
@@ -0,0 +1,94 @@
+use miniquad::*;
+
+struct Stage {
+    pipeline: Pipeline,
+    bindings: Bindings,
+}
+
+impl Stage {
+    pub fn new(ctx: &mut Context) -> Stage {
+        #[rustfmt::skip]
+        let vertices: &[f32] = &[
+            /* pos         color */
+            -0.5, -0.5,    1.0, 0.0, 0.0,
+             0.5, -0.5,    0.0, 1.0, 0.0,
+             0.0,  0.5,    0.0, 0.0, 1.0,
+        ];
+        let vertex_buffer = Buffer::immutable(ctx, BufferType::VertexBuffer, vertices);
+        let index_buffer = Buffer::immutable(ctx, BufferType::IndexBuffer, &[0u16, 1, 2]);
+        let bindings = Bindings {
+            vertex_buffers: vec![vertex_buffer],
+            index_buffer,
+            images: vec![],
+        };
+
+        let shader = Shader::new(ctx, shader::VERTEX, shader::FRAGMENT, shader::META);
+        let pipeline = Pipeline::new(
+            ctx,
+            &[BufferLayout::default()],
+            &[
+                VertexAttribute::new("pos", VertexFormat::Float2),
+                VertexAttribute::new("color0", VertexFormat::Float3),
+            ],
+            shader,
+        );
+
+        Stage { pipeline, bindings }
+    }
+}
+
+impl EventHandler for Stage {
+    fn update(&mut self, _ctx: &mut Context) {}
+
+    fn draw(&mut self, ctx: &mut Context) {
+        // draws into the 160x90 canvas, scaled up to the window after draw
+        ctx.begin_default_pass(PassAction::clear_color(0.1, 0.1, 0.1, 1.));
+        ctx.apply_pipeline(&self.pipeline);
+        ctx.apply_bindings(&self.bindings);
+        ctx.draw(0, 3, 1);
+        ctx.end_render_pass();
+
+        ctx.commit_frame();
+    }
+}
+
+fn main() {
+    let conf = conf::Conf {
+        pixel_art: Some(conf::PixelArt {
+            width: 160,
+            height: 90,
+            scaling: conf::PixelScaling::Integer,
+            letterbox_color: Color::BLACK,
+        }),
+        ..Default::default()
+    };
+    miniquad::start(conf, |ctx| Box::new(Stage::new(ctx)));
+}
+
+mod shader {
+    use miniquad::*;
+
+    pub const VERTEX: &str = r#"#version 100
+    attribute vec2 pos;
+    attribute vec3 color0;
+
+    varying lowp vec3 color;
+
+    void main() {
+        gl_Position = vec4(pos, 0, 1);
+        color = color0;
+    }"#;
+
+    pub const FRAGMENT: &str = r#"#version 100
+    varying lowp vec3 color;
+
+    void main() {
+        gl_FragColor = vec4(color, 1);
+    }"#;
+
+    pub const META: ShaderMeta = ShaderMeta {
+        images: &[],
+        uniforms: UniformBlockLayout { uniforms: &[] },
+        shared_uniforms: &[],
+    };
+}
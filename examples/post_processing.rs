@@ -0,0 +1,160 @@
+use miniquad::*;
+
+struct Stage {
+    offscreen_pass: RenderPass,
+    color_img: Texture,
+    triangle_pipeline: Pipeline,
+    triangle_bind: Bindings,
+    post_process: PostProcess,
+}
+
+impl Stage {
+    pub fn new(ctx: &mut Context) -> Stage {
+        let color_img = Texture::new_render_texture(RenderTextureParams {
+            width: 512,
+            height: 512,
+            format: PixelFormat::RGBA8,
+            ..Default::default()
+        });
+        let depth_img = Texture::new_render_texture(RenderTextureParams {
+            width: 512,
+            height: 512,
+            format: PixelFormat::Depth,
+            ..Default::default()
+        });
+        let offscreen_pass = RenderPass::new(ctx, color_img, depth_img);
+
+        #[rustfmt::skip]
+        let vertices: &[f32] = &[
+            /* pos         color */
+            -0.5, -0.5,    1.0, 0.0, 0.0,
+             0.5, -0.5,    0.0, 1.0, 0.0,
+             0.0,  0.5,    0.0, 0.0, 1.0,
+        ];
+        let vertex_buffer = Buffer::immutable(ctx, BufferType::VertexBuffer, vertices);
+        let index_buffer = Buffer::immutable(ctx, BufferType::IndexBuffer, &[0u16, 1, 2]);
+        let triangle_bind = Bindings {
+            vertex_buffers: vec![vertex_buffer],
+            index_buffer,
+            images: vec![],
+        };
+
+        let shader = Shader::new(
+            ctx,
+            triangle_shader::VERTEX,
+            triangle_shader::FRAGMENT,
+            triangle_shader::META,
+        );
+        let triangle_pipeline = Pipeline::new(
+            ctx,
+            &[BufferLayout::default()],
+            &[
+                VertexAttribute::new("pos", VertexFormat::Float2),
+                VertexAttribute::new("color0", VertexFormat::Float3),
+            ],
+            shader,
+        );
+
+        let post_process = PostProcess::new(ctx, post_shader::FRAGMENT, post_shader::META);
+
+        Stage {
+            offscreen_pass,
+            color_img,
+            triangle_pipeline,
+            triangle_bind,
+            post_process,
+        }
+    }
+}
+
+impl EventHandler for Stage {
+    fn update(&mut self, _ctx: &mut Context) {}
+
+    fn draw(&mut self, ctx: &mut Context) {
+        ctx.begin_pass(self.offscreen_pass, PassAction::clear_color(0.1, 0.1, 0.1, 1.));
+        ctx.apply_pipeline(&self.triangle_pipeline);
+        ctx.apply_bindings(&self.triangle_bind);
+        ctx.draw(0, 3, 1);
+        ctx.end_render_pass();
+
+        ctx.begin_default_pass(PassAction::Nothing);
+        self.post_process.draw_with_uniforms(
+            ctx,
+            self.color_img,
+            &post_shader::Uniforms {
+                time: date::now() as f32,
+                _pad: 0.,
+            },
+        );
+        ctx.end_render_pass();
+
+        ctx.commit_frame();
+    }
+}
+
+fn main() {
+    miniquad::start(conf::Conf::default(), |ctx| Box::new(Stage::new(ctx)));
+}
+
+mod triangle_shader {
+    use miniquad::*;
+
+    pub const VERTEX: &str = r#"#version 100
+    attribute vec2 pos;
+    attribute vec3 color0;
+
+    varying lowp vec3 color;
+
+    void main() {
+        gl_Position = vec4(pos, 0, 1);
+        color = color0;
+    }"#;
+
+    pub const FRAGMENT: &str = r#"#version 100
+    varying lowp vec3 color;
+
+    void main() {
+        gl_FragColor = vec4(color, 1);
+    }"#;
+
+    pub const META: ShaderMeta = ShaderMeta {
+        images: &[],
+        uniforms: UniformBlockLayout { uniforms: &[] },
+        shared_uniforms: &[],
+    };
+}
+
+mod post_shader {
+    use miniquad::*;
+
+    // wobbly vignette
+    pub const FRAGMENT: &str = r#"#version 100
+    precision mediump float;
+
+    varying vec2 uv;
+
+    uniform sampler2D source;
+    uniform float time;
+
+    void main() {
+        vec2 wobble = vec2(sin(uv.y * 20.0 + time * 3.0), 0.0) * 0.01;
+        vec3 color = texture2D(source, uv + wobble).rgb;
+        float vignette = 1.0 - length(uv - 0.5) * 1.2;
+        gl_FragColor = vec4(color * vignette, 1);
+    }"#;
+
+    pub const META: ShaderMeta = ShaderMeta {
+        images: &["source"],
+        uniforms: UniformBlockLayout {
+            uniforms: &[("time", UniformType::Float1)],
+        },
+        shared_uniforms: &[],
+    };
+
+    #[repr(C)]
+    pub struct Uniforms {
+        pub time: f32,
+        // apply_uniforms expects the uniform struct to be larger than its last float
+        pub _pad: f32,
+    }
+}
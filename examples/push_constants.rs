@@ -0,0 +1,115 @@
+use miniquad::*;
+
+const QUADS: usize = 5000;
+
+struct Stage {
+    pipeline: Pipeline,
+    bindings: Bindings,
+}
+
+impl Stage {
+    pub fn new(ctx: &mut Context) -> Stage {
+        #[rustfmt::skip]
+        let vertices: [f32; 8] = [
+            -1., -1.,
+             1., -1.,
+             1.,  1.,
+            -1.,  1.,
+        ];
+        let vertex_buffer = Buffer::immutable(ctx, BufferType::VertexBuffer, &vertices);
+
+        let indices: [u16; 6] = [0, 1, 2, 0, 2, 3];
+        let index_buffer = Buffer::immutable(ctx, BufferType::IndexBuffer, &indices);
+
+        let bindings = Bindings {
+            vertex_buffers: vec![vertex_buffer],
+            index_buffer,
+            images: vec![],
+        };
+
+        let vertex = format!(
+            "{}{}{}",
+            shader::HEADER,
+            PUSH_CONSTANTS_GLSL,
+            shader::VERTEX
+        );
+        let fragment = format!(
+            "{}{}{}",
+            shader::HEADER,
+            PUSH_CONSTANTS_GLSL,
+            shader::FRAGMENT
+        );
+        let shader = Shader::new(ctx, &vertex, &fragment, shader::META);
+
+        let pipeline = Pipeline::new(
+            ctx,
+            &[BufferLayout::default()],
+            &[VertexAttribute::new("pos", VertexFormat::Float2)],
+            shader,
+        );
+
+        Stage { pipeline, bindings }
+    }
+}
+
+impl EventHandler for Stage {
+    fn update(&mut self, _ctx: &mut Context) {}
+
+    fn draw(&mut self, ctx: &mut Context) {
+        let t = date::now() as f32;
+
+        ctx.begin_default_pass(Default::default());
+
+        ctx.apply_pipeline(&self.pipeline);
+        ctx.apply_bindings(&self.bindings);
+        for i in 0..QUADS {
+            let f = i as f32 / QUADS as f32;
+            let angle = f * 97. + t * 0.2;
+            let radius = f * 0.9;
+            ctx.apply_push_constants(&shader::PushConstants {
+                // x, y, scale, unused
+                transform: [angle.cos() * radius, angle.sin() * radius, 0.01, 0.],
+                tint: [f, 1. - f, (t + f * 6.).sin() * 0.5 + 0.5, 1.],
+            });
+            ctx.draw(0, 6, 1);
+        }
+        ctx.end_render_pass();
+
+        ctx.commit_frame();
+    }
+}
+
+fn main() {
+    miniquad::start(conf::Conf::default(), |ctx| Box::new(Stage::new(ctx)));
+}
+
+mod shader {
+    use miniquad::*;
+
+    pub const HEADER: &str = "#version 100\n";
+
+    pub const VERTEX: &str = r#"
+    attribute vec2 pos;
+
+    void main() {
+        vec4 transform = push_constants[0];
+        gl_Position = vec4(pos * transform.z + transform.xy, 0, 1);
+    }"#;
+
+    pub const FRAGMENT: &str = r#"
+    void main() {
+        gl_FragColor = push_constants[1];
+    }"#;
+
+    pub const META: ShaderMeta = ShaderMeta {
+        images: &[],
+        uniforms: UniformBlockLayout { uniforms: &[] },
+        shared_uniforms: &[],
+    };
+
+    #[repr(C)]
+    pub struct PushConstants {
+        pub transform: [f32; 4],
+        pub tint: [f32; 4],
+    }
+}
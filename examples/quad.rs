@@ -41,7 +41,7 @@ impl Stage {
 
         let bindings = Bindings {
             vertex_buffers: vec![vertex_buffer],
-            index_buffer: index_buffer,
+            index_buffer,
             images: vec![texture],
         };
 
@@ -119,6 +119,7 @@ mod shader {
         uniforms: UniformBlockLayout {
             uniforms: &[("offset", UniformType::Float2)],
         },
+        shared_uniforms: &[],
     };
 
     #[repr(C)]
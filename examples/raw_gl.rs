@@ -0,0 +1,116 @@
+use miniquad::*;
+
+// A line strip, which miniquad doesn't draw, with GL calls of our own
+// between miniquad draws.
+
+const POINTS: usize = 200;
+
+struct Stage {
+    pipeline: Pipeline,
+    line: Buffer,
+    quad: Bindings,
+}
+
+impl Stage {
+    pub fn new(ctx: &mut Context) -> Stage {
+        let line: Vec<f32> = (0..POINTS)
+            .flat_map(|n| {
+                let x = n as f32 / (POINTS - 1) as f32 * 2. - 1.;
+                vec![x, (x * 10.).sin() * 0.5]
+            })
+            .collect();
+        let line = Buffer::immutable(ctx, BufferType::VertexBuffer, &line);
+
+        let quad: [f32; 8] = [-0.9, -0.1, 0.9, -0.1, 0.9, 0.1, -0.9, 0.1];
+        let quad = Bindings {
+            vertex_buffers: vec![Buffer::immutable(ctx, BufferType::VertexBuffer, &quad)],
+            index_buffer: Buffer::immutable(ctx, BufferType::IndexBuffer, &[0u16, 1, 2, 0, 2, 3]),
+            images: vec![],
+        };
+
+        let shader = Shader::new(ctx, shader::VERTEX, shader::FRAGMENT, shader::META);
+        let pipeline = Pipeline::new(
+            ctx,
+            &[BufferLayout::default()],
+            &[VertexAttribute::new("pos", VertexFormat::Float2)],
+            shader,
+        );
+
+        Stage {
+            pipeline,
+            line,
+            quad,
+        }
+    }
+}
+
+impl EventHandler for Stage {
+    fn update(&mut self, _ctx: &mut Context) {}
+
+    fn draw(&mut self, ctx: &mut Context) {
+        ctx.begin_default_pass(PassAction::clear_color(0.1, 0.1, 0.1, 1.));
+        ctx.apply_pipeline(&self.pipeline);
+        ctx.apply_bindings(&self.quad);
+        ctx.apply_uniforms(&shader::Uniforms {
+            color: (0.3, 0.3, 0.6, 1.),
+        });
+        ctx.draw(0, 6, 1);
+
+        let (pipeline, line) = (self.pipeline, self.line);
+        ctx.with_raw_gl(|raw| unsafe {
+            use miniquad::gl::*;
+
+            let program = raw.program(&pipeline);
+            let pos = glGetAttribLocation(program, b"pos\0".as_ptr() as *const _) as GLuint;
+            glBindBuffer(GL_ARRAY_BUFFER, raw.buffer(&line));
+            glVertexAttribPointer(pos, 2, GL_FLOAT, GL_FALSE as u8, 0, std::ptr::null_mut());
+            glEnableVertexAttribArray(pos);
+            glDrawArrays(GL_LINE_STRIP, 0, POINTS as i32);
+        });
+
+        // the pipeline is applied again, the bindings are not
+        ctx.apply_bindings(&self.quad);
+        ctx.apply_uniforms(&shader::Uniforms {
+            color: (0.9, 0.6, 0.2, 1.),
+        });
+        ctx.draw(3, 3, 1);
+        ctx.end_render_pass();
+
+        ctx.commit_frame();
+    }
+}
+
+fn main() {
+    miniquad::start(conf::Conf::default(), |ctx| Box::new(Stage::new(ctx)));
+}
+
+mod shader {
+    use miniquad::*;
+
+    pub const VERTEX: &str = r#"#version 100
+    attribute vec2 pos;
+
+    void main() {
+        gl_Position = vec4(pos, 0, 1);
+    }"#;
+
+    pub const FRAGMENT: &str = r#"#version 100
+    uniform lowp vec4 color;
+
+    void main() {
+        gl_FragColor = color;
+    }"#;
+
+    pub const META: ShaderMeta = ShaderMeta {
+        images: &[],
+        uniforms: UniformBlockLayout {
+            uniforms: &[("color", UniformType::Float4)],
+        },
+        shared_uniforms: &[],
+    };
+
+    #[repr(C)]
+    pub struct Uniforms {
+        pub color: (f32, f32, f32, f32),
+    }
+}
@@ -0,0 +1,244 @@
+use glam::{Mat4, Vec3};
+use miniquad::*;
+
+// A spinning cube casting its shadow on the ground, in two passes: depths seen
+// from the light into a ShadowMap, then the scene compared against them.
+
+const LIGHT_DIR: [f32; 3] = [-0.5, -1., -0.3];
+const GROUND_INDICES: i32 = 6;
+const CUBE_INDICES: i32 = 36;
+
+struct Stage {
+    shadow_map: ShadowMap,
+    depth_pipeline: Pipeline,
+    lit_pipeline: Pipeline,
+    depth_bindings: Bindings,
+    lit_bindings: Bindings,
+}
+
+/// Quad facing `u` x `v`, `u` and `v` its half sides
+fn face(vertices: &mut Vec<f32>, indices: &mut Vec<u16>, center: Vec3, u: Vec3, v: Vec3) {
+    let normal = u.cross(v).normalize();
+    let first = (vertices.len() / 6) as u16;
+    for (su, sv) in [(-1., -1.), (1., -1.), (1., 1.), (-1., 1.)].iter() {
+        let pos = center + u * *su + v * *sv;
+        vertices.extend_from_slice(&[pos.x(), pos.y(), pos.z()]);
+        vertices.extend_from_slice(&[normal.x(), normal.y(), normal.z()]);
+    }
+    indices.extend_from_slice(&[first, first + 1, first + 2, first, first + 2, first + 3]);
+}
+
+impl Stage {
+    pub fn new(ctx: &mut Context) -> Stage {
+        let (mut vertices, mut indices) = (vec![], vec![]);
+        let (x, y, z) = (Vec3::unit_x(), Vec3::unit_y(), Vec3::unit_z());
+        face(&mut vertices, &mut indices, Vec3::zero(), z * 4., x * 4.);
+        // cube faces, u x v points out
+        for (u, v) in [(y, z), (z, x), (x, y)].iter() {
+            let (u, v) = (*u * 0.5, *v * 0.5);
+            let axis = u.cross(v) * 2.;
+            face(&mut vertices, &mut indices, axis * 0.5, u, v);
+            face(&mut vertices, &mut indices, axis * -0.5, v, u);
+        }
+        let vertex_buffer = Buffer::immutable(ctx, BufferType::VertexBuffer, &vertices);
+        let index_buffer = Buffer::immutable(ctx, BufferType::IndexBuffer, &indices);
+
+        let shadow_map = ShadowMap::new(ctx, 1024);
+
+        let layout = [BufferLayout {
+            stride: 24,
+            ..Default::default()
+        }];
+        let shader = Shader::new(
+            ctx,
+            shader::DEPTH_VERTEX,
+            ShadowMap::DEPTH_FRAGMENT,
+            shader::DEPTH_META,
+        );
+        let depth_pipeline = Pipeline::with_params(
+            ctx,
+            &layout,
+            &[VertexAttribute::new("pos", VertexFormat::Float3)],
+            shader,
+            ShadowMap::pipeline_params(),
+        );
+
+        let fragment = format!(
+            "{}{}{}",
+            shader::LIT_FRAGMENT_HEADER,
+            ShadowMap::SAMPLE_GLSL,
+            shader::LIT_FRAGMENT_MAIN
+        );
+        let shader = Shader::new(ctx, shader::LIT_VERTEX, &fragment, shader::LIT_META);
+        let lit_pipeline = Pipeline::with_params(
+            ctx,
+            &layout,
+            &[
+                VertexAttribute::new("pos", VertexFormat::Float3),
+                VertexAttribute::new("normal", VertexFormat::Float3),
+            ],
+            shader,
+            PipelineParams {
+                depth_test: Comparison::LessOrEqual,
+                depth_write: true,
+                ..Default::default()
+            },
+        );
+
+        Stage {
+            depth_bindings: Bindings {
+                vertex_buffers: vec![vertex_buffer],
+                index_buffer,
+                images: vec![],
+            },
+            lit_bindings: Bindings {
+                vertex_buffers: vec![vertex_buffer],
+                index_buffer,
+                images: vec![shadow_map.texture()],
+            },
+            shadow_map,
+            depth_pipeline,
+            lit_pipeline,
+        }
+    }
+}
+
+impl EventHandler for Stage {
+    fn update(&mut self, _ctx: &mut Context) {}
+
+    fn draw(&mut self, ctx: &mut Context) {
+        let t = date::now();
+        let cube = Mat4::from_translation(Vec3::new(0., 1.2, 0.))
+            * Mat4::from_rotation_y((t % 100.) as f32)
+            * Mat4::from_rotation_x(0.5);
+        let models = [
+            (Mat4::identity(), 0, GROUND_INDICES),
+            (cube, GROUND_INDICES, CUBE_INDICES),
+        ];
+
+        self.shadow_map
+            .set_directional_light(LIGHT_DIR, [0., 0., 0.], 4.);
+        let light = Mat4::from_cols_array(&self.shadow_map.light_view_proj);
+        self.shadow_map.begin_pass(ctx);
+        ctx.apply_pipeline(&self.depth_pipeline);
+        ctx.apply_bindings(&self.depth_bindings);
+        for (model, start, count) in models.iter() {
+            ctx.apply_uniforms(&shader::DepthUniforms {
+                light_mvp: (light * *model).to_cols_array(),
+            });
+            ctx.draw(*start, *count, 1);
+        }
+        ctx.end_render_pass();
+
+        let (width, height) = ctx.screen_size();
+        let proj = Mat4::perspective_rh_gl(60f32.to_radians(), width / height, 0.1, 20.);
+        let view = Mat4::look_at_rh(Vec3::new(3., 3., 4.), Vec3::zero(), Vec3::unit_y());
+        let shadow = Mat4::from_cols_array(&self.shadow_map.shadow_matrix(ctx));
+
+        ctx.begin_default_pass(PassAction::clear_color(0.6, 0.7, 0.8, 1.));
+        ctx.apply_pipeline(&self.lit_pipeline);
+        ctx.apply_bindings(&self.lit_bindings);
+        for (model, start, count) in models.iter() {
+            ctx.apply_uniforms(&shader::LitUniforms {
+                mvp: (proj * view * *model).to_cols_array(),
+                model: model.to_cols_array(),
+                shadow_mvp: (shadow * *model).to_cols_array(),
+                light_dir: LIGHT_DIR,
+                shadow_map_size: self.shadow_map.size(),
+            });
+            ctx.draw(*start, *count, 1);
+        }
+        ctx.end_render_pass();
+
+        // what the light sees, in the corner
+        ctx.debug_show_texture(self.shadow_map.texture(), (10., 10., 200., 200.));
+
+        ctx.commit_frame();
+    }
+}
+
+fn main() {
+    miniquad::start(conf::Conf::default(), |ctx| Box::new(Stage::new(ctx)));
+}
+
+mod shader {
+    use miniquad::*;
+
+    pub const DEPTH_VERTEX: &str = r#"#version 100
+    attribute vec3 pos;
+
+    uniform mat4 light_mvp;
+
+    void main() {
+        gl_Position = light_mvp * vec4(pos, 1);
+    }"#;
+
+    pub const DEPTH_META: ShaderMeta = ShaderMeta {
+        images: &[],
+        uniforms: UniformBlockLayout {
+            uniforms: &[("light_mvp", UniformType::Mat4)],
+        },
+        shared_uniforms: &[],
+    };
+
+    #[repr(C)]
+    pub struct DepthUniforms {
+        pub light_mvp: [f32; 16],
+    }
+
+    pub const LIT_VERTEX: &str = r#"#version 100
+    attribute vec3 pos;
+    attribute vec3 normal;
+
+    uniform mat4 mvp;
+    uniform mat4 model;
+    uniform mat4 shadow_mvp;
+
+    varying vec3 world_normal;
+    varying vec4 shadow_pos;
+
+    void main() {
+        gl_Position = mvp * vec4(pos, 1);
+        world_normal = (model * vec4(normal, 0)).xyz;
+        shadow_pos = shadow_mvp * vec4(pos, 1);
+    }"#;
+
+    pub const LIT_FRAGMENT_HEADER: &str = r#"#version 100
+    precision mediump float;
+
+    varying vec3 world_normal;
+    varying vec4 shadow_pos;
+
+    uniform vec3 light_dir;
+    "#;
+
+    pub const LIT_FRAGMENT_MAIN: &str = r#"
+    void main() {
+        float diffuse = max(dot(normalize(world_normal), -normalize(light_dir)), 0.0);
+        float light = 0.3 + 0.7 * diffuse * shadow(shadow_pos);
+        gl_FragColor = vec4(vec3(0.9, 0.8, 0.6) * light, 1);
+    }"#;
+
+    pub const LIT_META: ShaderMeta = ShaderMeta {
+        images: &["shadow_map"],
+        uniforms: UniformBlockLayout {
+            uniforms: &[
+                ("mvp", UniformType::Mat4),
+                ("model", UniformType::Mat4),
+                ("shadow_mvp", UniformType::Mat4),
+                ("light_dir", UniformType::Float3),
+                ("shadow_map_size", UniformType::Float1),
+            ],
+        },
+        shared_uniforms: &[],
+    };
+
+    #[repr(C)]
+    pub struct LitUniforms {
+        pub mvp: [f32; 16],
+        pub model: [f32; 16],
+        pub shadow_mvp: [f32; 16],
+        pub light_dir: [f32; 3],
+        pub shadow_map_size: f32,
+    }
+}
@@ -0,0 +1,212 @@
+use glam::{Mat4, Vec3};
+use miniquad::*;
+
+// A strip bent by a chain of bones, the matrices in a float texture when the
+// GPU can read one from vertex shaders, otherwise in a uniform array.
+
+const BONES: usize = 4;
+const SEGMENTS: usize = 32;
+const BOTTOM: f32 = -0.8;
+const BONE_LEN: f32 = 1.6 / BONES as f32;
+
+#[repr(C)]
+struct Vertex {
+    pos: [f32; 2],
+    joints: [u16; 4],
+    weights: [f32; 4],
+}
+
+enum Bones {
+    Texture(skinning::BoneTexture),
+    Uniforms,
+}
+
+struct Stage {
+    pipeline: Pipeline,
+    bindings: Bindings,
+    bones: Bones,
+}
+
+impl Stage {
+    pub fn new(ctx: &mut Context) -> Stage {
+        let mut vertices = vec![];
+        let mut indices: Vec<u16> = vec![];
+        for n in 0..=SEGMENTS {
+            // position along the chain in bones, blended with the neighbour
+            // bone around each joint
+            let along = n as f32 / SEGMENTS as f32 * BONES as f32;
+            let bone = (along as usize).min(BONES - 1);
+            let f = along - bone as f32;
+            let (joints, weights) = if f < 0.5 && bone > 0 {
+                ([bone, bone - 1], [0.5 + f, 0.5 - f])
+            } else if f >= 0.5 && bone < BONES - 1 {
+                ([bone, bone + 1], [1.5 - f, f - 0.5])
+            } else {
+                ([bone, bone], [1., 0.])
+            };
+            let y = BOTTOM + along * BONE_LEN;
+            for x in [-0.05, 0.05].iter() {
+                vertices.push(Vertex {
+                    pos: [*x, y],
+                    joints: [joints[0] as u16, joints[1] as u16, 0, 0],
+                    weights: [weights[0], weights[1], 0., 0.],
+                });
+            }
+            if n < SEGMENTS {
+                let i = n as u16 * 2;
+                indices.extend_from_slice(&[i, i + 1, i + 2, i + 1, i + 3, i + 2]);
+            }
+        }
+
+        let bones = match skinning::BoneTexture::new(ctx, BONES as u32) {
+            Some(texture) => Bones::Texture(texture),
+            None => Bones::Uniforms,
+        };
+        let (vertex, meta, images) = match &bones {
+            Bones::Texture(texture) => (
+                format!(
+                    "{}{}{}",
+                    shader::HEADER,
+                    skinning::TEXTURE_GLSL,
+                    shader::MAIN
+                ),
+                shader::TEXTURE_META,
+                vec![texture.texture()],
+            ),
+            Bones::Uniforms => (
+                format!(
+                    "{}#define MAX_BONES {}\n{}{}",
+                    shader::HEADER,
+                    BONES,
+                    skinning::UNIFORMS_GLSL,
+                    shader::MAIN
+                ),
+                shader::UNIFORMS_META,
+                vec![],
+            ),
+        };
+
+        let shader = Shader::new(ctx, &vertex, shader::FRAGMENT, meta);
+        let pipeline = Pipeline::new(
+            ctx,
+            &[BufferLayout::default()],
+            &[
+                VertexAttribute::new("pos", VertexFormat::Float2),
+                VertexAttribute::new("joints", VertexFormat::Short4),
+                VertexAttribute::new("weights", VertexFormat::Float4),
+            ],
+            shader,
+        );
+        let bindings = Bindings {
+            vertex_buffers: vec![Buffer::immutable(ctx, BufferType::VertexBuffer, &vertices)],
+            index_buffer: Buffer::immutable(ctx, BufferType::IndexBuffer, &indices),
+            images,
+        };
+
+        Stage {
+            pipeline,
+            bindings,
+            bones,
+        }
+    }
+
+    /// Bone matrices of the pose at `time`, from the rest pose to the bent one
+    fn pose(time: f64) -> [[f32; 16]; BONES] {
+        let mut matrices = [[0.; 16]; BONES];
+        let mut parent = Mat4::identity();
+        for (n, matrix) in matrices.iter_mut().enumerate() {
+            let rest = BOTTOM + n as f32 * BONE_LEN;
+            let to_joint = if n == 0 {
+                Mat4::from_translation(Vec3::new(0., rest, 0.))
+            } else {
+                Mat4::from_translation(Vec3::new(0., BONE_LEN, 0.))
+            };
+            let angle = (time * 1.5 + n as f64).sin() as f32 * 0.4;
+            parent = parent * to_joint * Mat4::from_rotation_z(angle);
+            // from the rest position of the bone to its posed one
+            let inverse_rest = Mat4::from_translation(Vec3::new(0., -rest, 0.));
+            *matrix = (parent * inverse_rest).to_cols_array();
+        }
+        matrices
+    }
+}
+
+impl EventHandler for Stage {
+    fn update(&mut self, _ctx: &mut Context) {}
+
+    fn draw(&mut self, ctx: &mut Context) {
+        let pose = Stage::pose(date::now());
+
+        ctx.begin_default_pass(PassAction::clear_color(0.1, 0.1, 0.1, 1.));
+        ctx.apply_pipeline(&self.pipeline);
+        ctx.apply_bindings(&self.bindings);
+        match &self.bones {
+            Bones::Texture(texture) => {
+                texture.update(0, &pose);
+                ctx.apply_uniforms(&shader::TextureUniforms {
+                    bone_texture_height: texture.height(),
+                });
+            }
+            Bones::Uniforms => ctx.apply_uniforms(&shader::Uniforms { bones: pose }),
+        }
+        ctx.draw(0, SEGMENTS as i32 * 6, 1);
+        ctx.end_render_pass();
+
+        ctx.commit_frame();
+    }
+}
+
+fn main() {
+    miniquad::start(conf::Conf::default(), |ctx| Box::new(Stage::new(ctx)));
+}
+
+mod shader {
+    use miniquad::*;
+
+    pub const HEADER: &str = r#"#version 100
+    attribute vec2 pos;
+    attribute vec4 joints;
+    attribute vec4 weights;
+
+    varying lowp vec4 color;
+    "#;
+
+    pub const MAIN: &str = r#"
+    void main() {
+        gl_Position = skin_matrix(joints, weights) * vec4(pos, 0, 1);
+        color = vec4(weights.x, joints.x / 4., 1. - weights.x, 1);
+    }"#;
+
+    pub const FRAGMENT: &str = r#"#version 100
+    varying lowp vec4 color;
+
+    void main() {
+        gl_FragColor = color;
+    }"#;
+
+    pub const TEXTURE_META: ShaderMeta = ShaderMeta {
+        images: &["bone_texture"],
+        uniforms: UniformBlockLayout {
+            uniforms: &[("bone_texture_height", UniformType::Float1)],
+        },
+        shared_uniforms: &[],
+    };
+
+    pub const UNIFORMS_META: ShaderMeta = ShaderMeta {
+        images: &[],
+        uniforms: UniformBlockLayout {
+            uniforms: &[("bones[4]", UniformType::Mat4)],
+        },
+        shared_uniforms: &[],
+    };
+
+    #[repr(C)]
+    pub struct TextureUniforms {
+        pub bone_texture_height: f32,
+    }
+
+    #[repr(C)]
+    pub struct Uniforms {
+        pub bones: [[f32; 16]; super::BONES],
+    }
+}
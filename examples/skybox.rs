@@ -0,0 +1,141 @@
+use miniquad::*;
+
+use glam::{Mat4, Vec3};
+
+const WIDTH: usize = 1024;
+const HEIGHT: usize = 512;
+
+struct Stage {
+    pipeline: Pipeline,
+    bindings: Bindings,
+}
+
+/// Sky above the horizon, ground below, a sun along +X and lines every 30 degrees
+fn panorama() -> Vec<u8> {
+    let mut pixels = Vec::with_capacity(WIDTH * HEIGHT * 4);
+    for y in 0..HEIGHT {
+        for x in 0..WIDTH {
+            let u = x as f32 / WIDTH as f32;
+            let v = y as f32 / HEIGHT as f32;
+            let mut color = if v < 0.5 {
+                [0.3 + v * 0.8, 0.5 + v * 0.8, 1.]
+            } else {
+                [0.4, 0.3, 0.2]
+            };
+            let sun = (u - 0.5).hypot(v - 0.4);
+            if sun < 0.02 {
+                color = [1., 1., 0.8];
+            }
+            if (u * 12.).fract() < 0.004 * 12. || (v * 6.).fract() < 0.004 * 12. {
+                color = [1., 1., 1.];
+            }
+            pixels.extend(color.iter().map(|c| (c * 255.) as u8));
+            pixels.push(255);
+        }
+    }
+    pixels
+}
+
+impl Stage {
+    pub fn new(ctx: &mut Context) -> Stage {
+        let equirect = Texture::from_rgba8(WIDTH as u16, HEIGHT as u16, &panorama());
+        let mut converter = EquirectToCubemap::new(ctx);
+        let sky = converter.convert(ctx, equirect, 512, PixelFormat::RGBA8);
+        equirect.delete();
+
+        #[rustfmt::skip]
+        let vertices: [f32; 6] = [
+            -1., -1.,
+             3., -1.,
+            -1.,  3.,
+        ];
+        let vertex_buffer = Buffer::immutable(ctx, BufferType::VertexBuffer, &vertices);
+        let index_buffer = Buffer::immutable(ctx, BufferType::IndexBuffer, &[0u16, 1, 2]);
+
+        let bindings = Bindings {
+            vertex_buffers: vec![vertex_buffer],
+            index_buffer,
+            images: vec![sky],
+        };
+
+        let shader = Shader::new(ctx, shader::VERTEX, shader::FRAGMENT, shader::META);
+        let pipeline = Pipeline::new(
+            ctx,
+            &[BufferLayout::default()],
+            &[VertexAttribute::new("pos", VertexFormat::Float2)],
+            shader,
+        );
+
+        Stage { pipeline, bindings }
+    }
+}
+
+impl EventHandler for Stage {
+    fn update(&mut self, _ctx: &mut Context) {}
+
+    fn draw(&mut self, ctx: &mut Context) {
+        let t = date::now() as f32;
+        let (width, height) = ctx.screen_size();
+        let proj = Mat4::perspective_rh_gl(60.0f32.to_radians(), width / height, 0.1, 10.);
+        let view = Mat4::look_at_rh(
+            Vec3::zero(),
+            Vec3::new((t * 0.2).cos(), (t * 0.3).sin() * 0.5, (t * 0.2).sin()),
+            Vec3::unit_y(),
+        );
+
+        ctx.begin_default_pass(PassAction::Nothing);
+        ctx.apply_pipeline(&self.pipeline);
+        ctx.apply_bindings(&self.bindings);
+        ctx.apply_uniforms(&shader::Uniforms {
+            inv_view_proj: (proj * view).inverse().to_cols_array(),
+        });
+        ctx.draw(0, 3, 1);
+        ctx.end_render_pass();
+
+        ctx.commit_frame();
+    }
+}
+
+fn main() {
+    miniquad::start(conf::Conf::default(), |ctx| Box::new(Stage::new(ctx)));
+}
+
+mod shader {
+    use miniquad::*;
+
+    pub const VERTEX: &str = r#"#version 100
+    attribute vec2 pos;
+
+    varying vec2 ndc;
+
+    void main() {
+        gl_Position = vec4(pos, 0, 1);
+        ndc = pos;
+    }"#;
+
+    pub const FRAGMENT: &str = r#"#version 100
+    precision mediump float;
+
+    varying vec2 ndc;
+
+    uniform samplerCube sky;
+    uniform mat4 inv_view_proj;
+
+    void main() {
+        vec4 far = inv_view_proj * vec4(ndc, 1, 1);
+        gl_FragColor = textureCube(sky, far.xyz / far.w);
+    }"#;
+
+    pub const META: ShaderMeta = ShaderMeta {
+        images: &["sky"],
+        uniforms: UniformBlockLayout {
+            uniforms: &[("inv_view_proj", UniformType::Mat4)],
+        },
+        shared_uniforms: &[],
+    };
+
+    #[repr(C)]
+    pub struct Uniforms {
+        pub inv_view_proj: [f32; 16],
+    }
+}
@@ -19,7 +19,14 @@ pub use rand::*;
 
 pub type sapp_event_type = libc::c_uint;
 pub const sapp_event_type__SAPP_EVENTTYPE_FORCE_U32: sapp_event_type = 2147483647;
-pub const sapp_event_type__SAPP_EVENTTYPE_NUM: sapp_event_type = 21;
+pub const sapp_event_type__SAPP_EVENTTYPE_NUM: sapp_event_type = 28;
+pub const sapp_event_type_SAPP_EVENTTYPE_CONTEXT_LOST: sapp_event_type = 27;
+pub const sapp_event_type_SAPP_EVENTTYPE_DPI_CHANGED: sapp_event_type = 26;
+pub const sapp_event_type_SAPP_EVENTTYPE_EXPOSED: sapp_event_type = 25;
+pub const sapp_event_type_SAPP_EVENTTYPE_CONTEXT_RESTORED: sapp_event_type = 24;
+pub const sapp_event_type_SAPP_EVENTTYPE_ORIENTATION_CHANGED: sapp_event_type = 23;
+pub const sapp_event_type_SAPP_EVENTTYPE_SAFE_AREA_CHANGED: sapp_event_type = 22;
+pub const sapp_event_type_SAPP_EVENTTYPE_THEME_CHANGED: sapp_event_type = 21;
 pub const sapp_event_type_SAPP_EVENTTYPE_QUIT_REQUESTED: sapp_event_type = 20;
 pub const sapp_event_type_SAPP_EVENTTYPE_UPDATE_CURSOR: sapp_event_type = 19;
 pub const sapp_event_type_SAPP_EVENTTYPE_RESUMED: sapp_event_type = 18;
@@ -205,6 +212,7 @@ pub struct sapp_event {
     pub window_height: libc::c_int,
     pub framebuffer_width: libc::c_int,
     pub framebuffer_height: libc::c_int,
+    pub timestamp: f64,
 }
 #[derive(Copy, Clone)]
 #[repr(C)]
@@ -238,6 +246,9 @@ pub struct sapp_desc {
     pub html5_ask_leave_site: bool,
     pub ios_keyboard_resizes_canvas: bool,
     pub gl_force_gles2: bool,
+    pub depth_bits: libc::c_int,
+    pub stencil_bits: libc::c_int,
+    pub robust_buffer_access: bool,
 }
 #[derive(Copy, Clone)]
 #[repr(C)]
@@ -339,3 +350,169 @@ pub unsafe extern "C" fn sapp_width() -> libc::c_int {
 pub unsafe extern "C" fn sapp_isvalid() -> bool {
     true
 }
+#[no_mangle]
+pub unsafe extern "C" fn sapp_set_window_opacity(mut opacity: libc::c_float) {}
+#[no_mangle]
+pub unsafe extern "C" fn sapp_set_input_passthrough(mut passthrough: bool) {}
+#[no_mangle]
+pub unsafe extern "C" fn sapp_request_user_attention() {}
+#[no_mangle]
+pub unsafe extern "C" fn sapp_set_taskbar_progress(mut progress: libc::c_float) {}
+#[no_mangle]
+pub unsafe extern "C" fn sapp_vibrate(mut duration_ms: libc::c_int) {}
+#[no_mangle]
+pub unsafe extern "C" fn sapp_double_click_time() -> libc::c_int {
+    500
+}
+#[no_mangle]
+pub unsafe extern "C" fn sapp_key_char(_key: sapp_keycode) -> u32 {
+    0
+}
+#[no_mangle]
+pub unsafe extern "C" fn sapp_set_clipboard_image(
+    _width: libc::c_int,
+    _height: libc::c_int,
+    _pixels: *const u8,
+) -> bool {
+    false
+}
+#[no_mangle]
+pub unsafe extern "C" fn sapp_system_locale() -> *const libc::c_char {
+    b"en-US\0".as_ptr() as *const libc::c_char
+}
+#[no_mangle]
+pub unsafe extern "C" fn sapp_prefers_dark_theme() -> bool {
+    false
+}
+#[no_mangle]
+pub unsafe extern "C" fn sapp_accent_color(
+    _r: *mut libc::c_float,
+    _g: *mut libc::c_float,
+    _b: *mut libc::c_float,
+) -> bool {
+    false
+}
+#[no_mangle]
+pub unsafe extern "C" fn sapp_high_contrast() -> bool {
+    false
+}
+#[no_mangle]
+pub unsafe extern "C" fn sapp_battery_status(
+    mut level: *mut libc::c_float,
+    mut charging: *mut bool,
+    mut power_saving: *mut bool,
+) -> bool {
+    false
+}
+#[no_mangle]
+pub unsafe extern "C" fn sapp_refresh_rate() -> libc::c_int {
+    0
+}
+#[no_mangle]
+pub unsafe extern "C" fn sapp_poll_events() {}
+#[no_mangle]
+pub unsafe extern "C" fn sapp_set_swap_interval(mut interval: libc::c_int) {}
+#[no_mangle]
+pub unsafe extern "C" fn sapp_gl_context_handles(
+    mut display: *mut *mut libc::c_void,
+    mut context: *mut *mut libc::c_void,
+    mut config: *mut *mut libc::c_void,
+    mut drawable: *mut u64,
+    mut visual_id: *mut u64,
+) -> libc::c_int {
+    0
+}
+#[no_mangle]
+pub unsafe extern "C" fn sapp_graphics_reset_status() -> libc::c_int {
+    0
+}
+#[no_mangle]
+pub unsafe extern "C" fn sapp_recreate_context() {}
+#[no_mangle]
+pub unsafe extern "C" fn sapp_set_capture_protection(mut protect: bool) -> bool {
+    false
+}
+#[no_mangle]
+pub unsafe extern "C" fn sapp_keep_awake(mut awake: bool) -> bool {
+    false
+}
+#[no_mangle]
+pub unsafe extern "C" fn sapp_capture_shortcuts(mut capture: bool) -> bool {
+    false
+}
+#[no_mangle]
+pub unsafe extern "C" fn sapp_present_stats(
+    mut present_count: *mut u64,
+    mut refresh_count: *mut u64,
+    mut time: *mut libc::c_double,
+) -> libc::c_int {
+    0
+}
+#[no_mangle]
+pub unsafe extern "C" fn sapp_timestamp() -> libc::c_double {
+    let mut time: libc::timespec = std::mem::zeroed();
+    libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut time);
+    time.tv_sec as libc::c_double + time.tv_nsec as libc::c_double / 1e9
+}
+#[no_mangle]
+pub unsafe extern "C" fn sapp_sleep(mut seconds: libc::c_double) {
+    if seconds > 0. {
+        let time = libc::timespec {
+            tv_sec: seconds as libc::time_t,
+            tv_nsec: (seconds.fract() * 1e9) as libc::c_long,
+        };
+        libc::nanosleep(&time, std::ptr::null_mut());
+    }
+}
+#[no_mangle]
+pub unsafe extern "C" fn sapp_display_mode_count() -> libc::c_int {
+    0
+}
+#[no_mangle]
+pub unsafe extern "C" fn sapp_display_mode(
+    _index: libc::c_int,
+    _width: *mut libc::c_int,
+    _height: *mut libc::c_int,
+    _refresh_rate: *mut libc::c_int,
+) -> bool {
+    false
+}
+#[no_mangle]
+pub unsafe extern "C" fn sapp_set_exclusive_fullscreen(
+    width: libc::c_int,
+    _height: libc::c_int,
+    _refresh_rate: libc::c_int,
+) -> bool {
+    width <= 0
+}
+#[no_mangle]
+pub unsafe extern "C" fn sapp_depth_bits() -> libc::c_int {
+    0
+}
+#[no_mangle]
+pub unsafe extern "C" fn sapp_stencil_bits() -> libc::c_int {
+    0
+}
+#[no_mangle]
+pub unsafe extern "C" fn sapp_safe_area_insets(
+    mut top: *mut libc::c_float,
+    mut right: *mut libc::c_float,
+    mut bottom: *mut libc::c_float,
+    mut left: *mut libc::c_float,
+) {
+    *top = 0.0;
+    *right = 0.0;
+    *bottom = 0.0;
+    *left = 0.0;
+}
+#[no_mangle]
+pub unsafe extern "C" fn sapp_set_orientation_lock(mut orientation: libc::c_int) {}
+#[no_mangle]
+pub unsafe extern "C" fn sapp_confine_cursor(
+    _confinement: libc::c_int,
+    _x: libc::c_float,
+    _y: libc::c_float,
+    _width: libc::c_float,
+    _height: libc::c_float,
+) {
+}
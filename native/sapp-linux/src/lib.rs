@@ -22,7 +22,14 @@ use crate::x::*;
 
 pub type sapp_event_type = libc::c_uint;
 pub const sapp_event_type__SAPP_EVENTTYPE_FORCE_U32: sapp_event_type = 2147483647;
-pub const sapp_event_type__SAPP_EVENTTYPE_NUM: sapp_event_type = 21;
+pub const sapp_event_type__SAPP_EVENTTYPE_NUM: sapp_event_type = 28;
+pub const sapp_event_type_SAPP_EVENTTYPE_CONTEXT_LOST: sapp_event_type = 27;
+pub const sapp_event_type_SAPP_EVENTTYPE_DPI_CHANGED: sapp_event_type = 26;
+pub const sapp_event_type_SAPP_EVENTTYPE_EXPOSED: sapp_event_type = 25;
+pub const sapp_event_type_SAPP_EVENTTYPE_CONTEXT_RESTORED: sapp_event_type = 24;
+pub const sapp_event_type_SAPP_EVENTTYPE_ORIENTATION_CHANGED: sapp_event_type = 23;
+pub const sapp_event_type_SAPP_EVENTTYPE_SAFE_AREA_CHANGED: sapp_event_type = 22;
+pub const sapp_event_type_SAPP_EVENTTYPE_THEME_CHANGED: sapp_event_type = 21;
 pub const sapp_event_type_SAPP_EVENTTYPE_QUIT_REQUESTED: sapp_event_type = 20;
 pub const sapp_event_type_SAPP_EVENTTYPE_UPDATE_CURSOR: sapp_event_type = 19;
 pub const sapp_event_type_SAPP_EVENTTYPE_RESUMED: sapp_event_type = 18;
@@ -208,6 +215,7 @@ pub struct sapp_event {
     pub window_height: libc::c_int,
     pub framebuffer_width: libc::c_int,
     pub framebuffer_height: libc::c_int,
+    pub timestamp: f64,
 }
 #[derive(Copy, Clone)]
 #[repr(C)]
@@ -241,6 +249,9 @@ pub struct sapp_desc {
     pub html5_ask_leave_site: bool,
     pub ios_keyboard_resizes_canvas: bool,
     pub gl_force_gles2: bool,
+    pub depth_bits: libc::c_int,
+    pub stencil_bits: libc::c_int,
+    pub robust_buffer_access: bool,
 }
 #[derive(Copy, Clone)]
 #[repr(C)]
@@ -252,6 +263,8 @@ pub struct _sapp_state {
     pub framebuffer_height: libc::c_int,
     pub sample_count: libc::c_int,
     pub swap_interval: libc::c_int,
+    pub depth_bits: libc::c_int,
+    pub stencil_bits: libc::c_int,
     pub dpi_scale: libc::c_float,
     pub gles2_fallback: bool,
     pub first_frame: bool,
@@ -301,6 +314,25 @@ impl _sapp_x11_codepair {
 pub type PFNGLXMAKECURRENTPROC =
     Option<unsafe extern "C" fn(_: *mut Display, _: GLXDrawable, _: GLXContext) -> libc::c_int>;
 pub type PFNGLXSWAPINTERVALMESAPROC = Option<unsafe extern "C" fn(_: libc::c_int) -> libc::c_int>;
+pub type PFNGLXGETSYNCVALUESOMLPROC = Option<
+    unsafe extern "C" fn(
+        _: *mut Display,
+        _: GLXDrawable,
+        _: *mut i64,
+        _: *mut i64,
+        _: *mut i64,
+    ) -> libc::c_int,
+>;
+pub type PFNGLXWAITFORSBCOMLPROC = Option<
+    unsafe extern "C" fn(
+        _: *mut Display,
+        _: GLXDrawable,
+        _: i64,
+        _: *mut i64,
+        _: *mut i64,
+        _: *mut i64,
+    ) -> libc::c_int,
+>;
 pub type PFNGLXSWAPINTERVALEXTPROC =
     Option<unsafe extern "C" fn(_: *mut Display, _: GLXDrawable, _: libc::c_int) -> ()>;
 pub type GLXFBConfig = *mut __GLXFBConfig;
@@ -582,6 +614,11 @@ pub unsafe extern "C" fn _sapp_x11_init_extensions() {
         b"_NET_WM_ICON_NAME\x00" as *const u8 as *const libc::c_char,
         false as _,
     );
+    _sapp_x11_NET_WM_WINDOW_OPACITY = XInternAtom(
+        _sapp_x11_display,
+        b"_NET_WM_WINDOW_OPACITY\x00" as *const u8 as *const libc::c_char,
+        false as _,
+    );
 }
 pub static mut _sapp_glx_CreateNewContext: PFNGLXCREATENEWCONTEXTPROC = None;
 pub static mut _sapp_glx_QueryExtension: PFNGLXQUERYEXTENSIONPROC = None;
@@ -738,6 +775,12 @@ pub unsafe extern "C" fn _sapp_glx_init() {
         _sapp_glx_SwapIntervalMESA = _sapp_glx_getprocaddr(b"glXSwapIntervalMESA\x00");
         _sapp_glx_MESA_swap_control = _sapp_glx_SwapIntervalMESA.is_some()
     }
+    if _sapp_glx_extsupported(b"GLX_OML_sync_control\x00", exts) {
+        _sapp_glx_GetSyncValuesOML = _sapp_glx_getprocaddr(b"glXGetSyncValuesOML\x00");
+        _sapp_glx_WaitForSbcOML = _sapp_glx_getprocaddr(b"glXWaitForSbcOML\x00");
+        _sapp_glx_OML_sync_control =
+            _sapp_glx_GetSyncValuesOML.is_some() && _sapp_glx_WaitForSbcOML.is_some()
+    }
     _sapp_glx_ARB_multisample = _sapp_glx_extsupported(b"GLX_ARB_multisample\x00", exts);
     _sapp_glx_ARB_framebuffer_sRGB = _sapp_glx_extsupported(b"GLX_ARB_framebuffer_sRGB\x00", exts);
     _sapp_glx_EXT_framebuffer_sRGB = _sapp_glx_extsupported(b"GLX_EXT_framebuffer_sRGB\x00", exts);
@@ -748,6 +791,8 @@ pub unsafe extern "C" fn _sapp_glx_init() {
     }
     _sapp_glx_ARB_create_context_profile =
         _sapp_glx_extsupported(b"GLX_ARB_create_context_profile\x00", exts);
+    _sapp_glx_ARB_create_context_robustness =
+        _sapp_glx_extsupported(b"GLX_ARB_create_context_robustness\x00", exts);
 }
 pub static mut _sapp_glx_GetVisualFromFBConfig: PFNGLXGETVISUALFROMFBCONFIGPROC = None;
 pub unsafe extern "C" fn _sapp_glx_choose_visual(
@@ -772,12 +817,14 @@ pub unsafe extern "C" fn _sapp_glx_choose_visual(
     }
     *visual = (*result).visual;
     *depth = (*result).depth;
+    _sapp_glx_visualid = (*result).visualid as u64;
     XFree(result as *mut libc::c_void);
 }
 pub static mut _sapp_x11_root: Window = 0;
 pub static mut _sapp_x11_NET_WM_NAME: Atom = 0;
 pub static mut _sapp_x11_NET_WM_ICON_NAME: Atom = 0;
 pub static mut _sapp_x11_UTF8_STRING: Atom = 0;
+pub static mut _sapp_x11_NET_WM_WINDOW_OPACITY: Atom = 0;
 pub unsafe extern "C" fn _sapp_x11_update_window_title() {
     Xutf8SetWMProperties(
         _sapp_x11_display,
@@ -846,6 +893,10 @@ pub unsafe extern "C" fn _sapp_x11_query_window_size() {
 }
 pub static mut _sapp_glx_ARB_create_context: bool = false;
 pub static mut _sapp_glx_ARB_create_context_profile: bool = false;
+pub static mut _sapp_glx_ARB_create_context_robustness: bool = false;
+/* set when the context was created with reset notification */
+pub static mut _sapp_glx_GetGraphicsResetStatusARB: Option<unsafe extern "C" fn() -> GLenum> =
+    None;
 pub static mut _sapp_x11_error_code: libc::c_uchar = 0;
 pub unsafe extern "C" fn _sapp_x11_error_handler(
     mut display: *mut Display,
@@ -868,6 +919,9 @@ pub const GLX_CONTEXT_PROFILE_MASK_ARB: libc::c_int = 0x9126 as libc::c_int;
 pub const GLX_CONTEXT_CORE_PROFILE_BIT_ARB: libc::c_int = 0x1 as libc::c_int;
 pub const GLX_CONTEXT_FLAGS_ARB: libc::c_int = 0x2094 as libc::c_int;
 pub const GLX_CONTEXT_FORWARD_COMPATIBLE_BIT_ARB: libc::c_int = 0x2 as libc::c_int;
+pub const GLX_CONTEXT_RESET_NOTIFICATION_STRATEGY_ARB: libc::c_int = 0x8256 as libc::c_int;
+pub const GLX_LOSE_CONTEXT_ON_RESET_ARB: libc::c_int = 0x8252 as libc::c_int;
+pub const GLX_CONTEXT_ROBUST_ACCESS_BIT_ARB: libc::c_int = 0x4 as libc::c_int;
 pub unsafe extern "C" fn _sapp_x11_release_error_handler() {
     XSync(_sapp_x11_display, false as _);
     XSetErrorHandler(None);
@@ -983,7 +1037,7 @@ pub unsafe extern "C" fn _sapp_gl_choose_fbconfig(
                     * ((*desired).depth_bits - (*current).depth_bits);
             }
             if (*desired).stencil_bits != -1 {
-                extra_diff = ((*desired).stencil_bits - (*current).stencil_bits)
+                extra_diff += ((*desired).stencil_bits - (*current).stencil_bits)
                     * ((*desired).stencil_bits - (*current).stencil_bits);
             }
             if (*desired).samples != -1 {
@@ -1075,8 +1129,8 @@ pub unsafe extern "C" fn _sapp_glx_choosefbconfig() -> GLXFBConfig {
     desired.green_bits = 8;
     desired.blue_bits = 8;
     desired.alpha_bits = 8;
-    desired.depth_bits = 24;
-    desired.stencil_bits = 8;
+    desired.depth_bits = _sapp.desc.depth_bits;
+    desired.stencil_bits = _sapp.desc.stencil_bits;
     desired.doublebuffer = true;
     desired.samples = if _sapp.sample_count > 1 {
         _sapp.sample_count
@@ -1090,7 +1144,9 @@ pub unsafe extern "C" fn _sapp_glx_choosefbconfig() -> GLXFBConfig {
     );
     let mut result = 0 as GLXFBConfig;
     if !closest.is_null() {
-        result = (*closest).handle as GLXFBConfig
+        result = (*closest).handle as GLXFBConfig;
+        _sapp.depth_bits = (*closest).depth_bits;
+        _sapp.stencil_bits = (*closest).stencil_bits
     }
     XFree(native_configs as *mut libc::c_void);
     return result;
@@ -1131,7 +1187,8 @@ pub unsafe extern "C" fn _sapp_glx_create_context() {
         );
     }
     _sapp_x11_grab_error_handler();
-    let attribs: [libc::c_int; 10] = [
+    /* robust contexts report GPU resets instead of hanging or drawing garbage after them */
+    let mut attribs: [libc::c_int; 12] = [
         GLX_CONTEXT_MAJOR_VERSION_ARB,
         3,
         GLX_CONTEXT_MINOR_VERSION_ARB,
@@ -1140,9 +1197,17 @@ pub unsafe extern "C" fn _sapp_glx_create_context() {
         GLX_CONTEXT_CORE_PROFILE_BIT_ARB,
         GLX_CONTEXT_FLAGS_ARB,
         GLX_CONTEXT_FORWARD_COMPATIBLE_BIT_ARB,
+        GLX_CONTEXT_RESET_NOTIFICATION_STRATEGY_ARB,
+        GLX_LOSE_CONTEXT_ON_RESET_ARB,
         0,
         0,
     ];
+    if !_sapp_glx_ARB_create_context_robustness {
+        attribs[8] = 0;
+        attribs[9] = 0;
+    } else if _sapp.desc.robust_buffer_access {
+        attribs[7] |= GLX_CONTEXT_ROBUST_ACCESS_BIT_ARB;
+    }
     _sapp_glx_ctx = _sapp_glx_CreateContextAttribsARB.expect("non-null function pointer")(
         _sapp_x11_display,
         native,
@@ -1150,10 +1215,28 @@ pub unsafe extern "C" fn _sapp_glx_create_context() {
         true as _,
         attribs.as_ptr(),
     );
+    _sapp_glx_GetGraphicsResetStatusARB = None;
+    if _sapp_glx_ctx.is_null() && _sapp_glx_ARB_create_context_robustness {
+        /* the extension may be there without a robust context for this config */
+        attribs[7] = GLX_CONTEXT_FORWARD_COMPATIBLE_BIT_ARB;
+        attribs[8] = 0;
+        attribs[9] = 0;
+        _sapp_glx_ctx = _sapp_glx_CreateContextAttribsARB.expect("non-null function pointer")(
+            _sapp_x11_display,
+            native,
+            std::ptr::null_mut(),
+            true as _,
+            attribs.as_ptr(),
+        );
+    } else if _sapp_glx_ARB_create_context_robustness {
+        _sapp_glx_GetGraphicsResetStatusARB =
+            _sapp_glx_getprocaddr(b"glGetGraphicsResetStatusARB\x00");
+    }
     if _sapp_glx_ctx.is_null() {
         _sapp_fail(b"GLX: failed to create GL context\x00" as *const u8 as *const libc::c_char);
     }
     _sapp_x11_release_error_handler();
+    _sapp_glx_fbconfig = native;
     _sapp_glx_window = _sapp_glx_CreateWindow.expect("non-null function pointer")(
         _sapp_x11_display,
         native,
@@ -1204,6 +1287,9 @@ pub static mut _sapp_glx_EXT_swap_control: bool = false;
 pub static mut _sapp_glx_SwapIntervalEXT: PFNGLXSWAPINTERVALEXTPROC = None;
 pub static mut _sapp_glx_MESA_swap_control: bool = false;
 pub static mut _sapp_glx_SwapIntervalMESA: PFNGLXSWAPINTERVALMESAPROC = None;
+pub static mut _sapp_glx_OML_sync_control: bool = false;
+pub static mut _sapp_glx_GetSyncValuesOML: PFNGLXGETSYNCVALUESOMLPROC = None;
+pub static mut _sapp_glx_WaitForSbcOML: PFNGLXWAITFORSBCOMLPROC = None;
 pub unsafe extern "C" fn _sapp_glx_swapinterval(mut interval: libc::c_int) {
     _sapp_glx_make_current();
     if _sapp_glx_EXT_swap_control {
@@ -2108,7 +2194,93 @@ pub unsafe extern "C" fn _sapp_x11_key_event(
         _sapp_call_event(&mut _sapp.event);
     };
 }
+/* keys typing characters, by position: X keycodes are evdev scancodes + 8 */
+pub unsafe extern "C" fn _sapp_x11_physical_key(mut scancode: libc::c_int) -> sapp_keycode {
+    match scancode {
+        10 => return sapp_keycode_SAPP_KEYCODE_1,
+        11 => return sapp_keycode_SAPP_KEYCODE_2,
+        12 => return sapp_keycode_SAPP_KEYCODE_3,
+        13 => return sapp_keycode_SAPP_KEYCODE_4,
+        14 => return sapp_keycode_SAPP_KEYCODE_5,
+        15 => return sapp_keycode_SAPP_KEYCODE_6,
+        16 => return sapp_keycode_SAPP_KEYCODE_7,
+        17 => return sapp_keycode_SAPP_KEYCODE_8,
+        18 => return sapp_keycode_SAPP_KEYCODE_9,
+        19 => return sapp_keycode_SAPP_KEYCODE_0,
+        20 => return sapp_keycode_SAPP_KEYCODE_MINUS,
+        21 => return sapp_keycode_SAPP_KEYCODE_EQUAL,
+        24 => return sapp_keycode_SAPP_KEYCODE_Q,
+        25 => return sapp_keycode_SAPP_KEYCODE_W,
+        26 => return sapp_keycode_SAPP_KEYCODE_E,
+        27 => return sapp_keycode_SAPP_KEYCODE_R,
+        28 => return sapp_keycode_SAPP_KEYCODE_T,
+        29 => return sapp_keycode_SAPP_KEYCODE_Y,
+        30 => return sapp_keycode_SAPP_KEYCODE_U,
+        31 => return sapp_keycode_SAPP_KEYCODE_I,
+        32 => return sapp_keycode_SAPP_KEYCODE_O,
+        33 => return sapp_keycode_SAPP_KEYCODE_P,
+        34 => return sapp_keycode_SAPP_KEYCODE_LEFT_BRACKET,
+        35 => return sapp_keycode_SAPP_KEYCODE_RIGHT_BRACKET,
+        38 => return sapp_keycode_SAPP_KEYCODE_A,
+        39 => return sapp_keycode_SAPP_KEYCODE_S,
+        40 => return sapp_keycode_SAPP_KEYCODE_D,
+        41 => return sapp_keycode_SAPP_KEYCODE_F,
+        42 => return sapp_keycode_SAPP_KEYCODE_G,
+        43 => return sapp_keycode_SAPP_KEYCODE_H,
+        44 => return sapp_keycode_SAPP_KEYCODE_J,
+        45 => return sapp_keycode_SAPP_KEYCODE_K,
+        46 => return sapp_keycode_SAPP_KEYCODE_L,
+        47 => return sapp_keycode_SAPP_KEYCODE_SEMICOLON,
+        48 => return sapp_keycode_SAPP_KEYCODE_APOSTROPHE,
+        49 => return sapp_keycode_SAPP_KEYCODE_GRAVE_ACCENT,
+        51 => return sapp_keycode_SAPP_KEYCODE_BACKSLASH,
+        52 => return sapp_keycode_SAPP_KEYCODE_Z,
+        53 => return sapp_keycode_SAPP_KEYCODE_X,
+        54 => return sapp_keycode_SAPP_KEYCODE_C,
+        55 => return sapp_keycode_SAPP_KEYCODE_V,
+        56 => return sapp_keycode_SAPP_KEYCODE_B,
+        57 => return sapp_keycode_SAPP_KEYCODE_N,
+        58 => return sapp_keycode_SAPP_KEYCODE_M,
+        59 => return sapp_keycode_SAPP_KEYCODE_COMMA,
+        60 => return sapp_keycode_SAPP_KEYCODE_PERIOD,
+        61 => return sapp_keycode_SAPP_KEYCODE_SLASH,
+        94 => return sapp_keycode_SAPP_KEYCODE_WORLD_1,
+        _ => return sapp_keycode_SAPP_KEYCODE_INVALID,
+    };
+}
+/* X11 has no double click setting, 500 ms is the usual desktop default */
+#[no_mangle]
+pub unsafe extern "C" fn sapp_double_click_time() -> libc::c_int {
+    500
+}
+/* character the key types without modifiers on the current layout, 0 if none.
+   Latin-1 keysyms are their own code point, others carry it with 0x01000000 */
+#[no_mangle]
+pub unsafe extern "C" fn sapp_key_char(mut key: sapp_keycode) -> u32 {
+    for scancode in 8..256 {
+        if _sapp_x11_physical_key(scancode) != key {
+            continue;
+        }
+        let mut dummy: libc::c_int = 0;
+        let mut keysyms = XGetKeyboardMapping(_sapp_x11_display, scancode as KeyCode, 1, &mut dummy);
+        if keysyms.is_null() {
+            return 0;
+        }
+        let mut keysym = *keysyms.offset(0);
+        XFree(keysyms as *mut libc::c_void);
+        return match keysym {
+            0x20..=0x7e | 0xa0..=0xff => keysym as u32,
+            0x1000100..=0x110ffff => (keysym - 0x1000000) as u32,
+            _ => 0,
+        };
+    }
+    0
+}
 pub unsafe extern "C" fn _sapp_x11_translate_key(mut scancode: libc::c_int) -> sapp_keycode {
+    let physical = _sapp_x11_physical_key(scancode);
+    if physical != sapp_keycode_SAPP_KEYCODE_INVALID {
+        return physical;
+    }
     let mut dummy: libc::c_int = 0;
     let mut keysyms = XGetKeyboardMapping(
         _sapp_x11_display,
@@ -2345,7 +2517,27 @@ pub unsafe extern "C" fn _sapp_x11_get_window_state() -> libc::c_int {
 }
 pub static mut _sapp_x11_WM_PROTOCOLS: Atom = 0;
 pub static mut _sapp_x11_WM_DELETE_WINDOW: Atom = 0;
+/* time of the X input event being processed, 0 for other events */
+pub static mut _sapp_x11_event_time: libc::c_double = 0.;
+/* X server timestamps are milliseconds of the server's CLOCK_MONOTONIC, wrapping at 32 bits.
+   When the event time does not look like it comes from our clock, e.g. with a remote
+   X server, the time it was received at is used instead */
+pub unsafe extern "C" fn _sapp_x11_timestamp(mut time: libc::c_ulong) -> libc::c_double {
+    let mut now = sapp_timestamp();
+    let mut age = ((now * 1000.) as u64 as u32).wrapping_sub(time as u32);
+    if age < 1000 {
+        now - age as libc::c_double / 1000.
+    } else {
+        now
+    }
+}
 pub unsafe extern "C" fn _sapp_x11_process_event(mut event: *mut XEvent) {
+    _sapp_x11_event_time = match (*event).type_0 {
+        /* KeyPress, KeyRelease, ButtonPress, ButtonRelease, MotionNotify:
+           time is at the same offset in all of them */
+        2..=6 => _sapp_x11_timestamp((*event).xkey.time),
+        _ => 0.,
+    };
     match (*event).type_0 {
         2 => {
             let mut keycode = (*event).xkey.keycode as libc::c_int;
@@ -2464,6 +2656,27 @@ pub unsafe extern "C" fn _sapp_x11_process_event(mut event: *mut XEvent) {
                 }
             }
         }
+        9 => {
+            _sapp_x11_set_urgency(false);
+            if _sapp_x11_confinement != 0 {
+                _sapp_x11_apply_confinement();
+            }
+            if _sapp_x11_shortcuts_captured {
+                _sapp_x11_grab_keyboard();
+            }
+        }
+        10 => {
+            if _sapp_x11_confinement != 0 {
+                XUngrabPointer(_sapp_x11_display, CurrentTime);
+            }
+        }
+        12 => {
+            /* Expose, only for the last of a series; the frame is drawn
+               right after the pending events are processed */
+            if (*event).xexpose.count == 0 {
+                _sapp_x11_app_event(sapp_event_type_SAPP_EVENTTYPE_EXPOSED);
+            }
+        }
         33 => {
             if (*event).xclient.message_type == _sapp_x11_WM_PROTOCOLS {
                 let protocol = (*event).xclient.data.l[0 as libc::c_int as usize] as Atom;
@@ -2527,6 +2740,11 @@ pub unsafe extern "C" fn _sapp_init_event(mut type_: sapp_event_type) {
     _sapp.event.window_height = _sapp.window_height;
     _sapp.event.framebuffer_width = _sapp.framebuffer_width;
     _sapp.event.framebuffer_height = _sapp.framebuffer_height;
+    _sapp.event.timestamp = if _sapp_x11_event_time > 0. {
+        _sapp_x11_event_time
+    } else {
+        sapp_timestamp()
+    };
 }
 pub unsafe extern "C" fn _sapp_call_event(mut e: *const sapp_event) {
     if !_sapp.cleanup_called {
@@ -2563,6 +2781,8 @@ pub static mut _sapp_glx_DestroyWindow: PFNGLXDESTROYWINDOWPROC = None;
 pub static mut _sapp_glx_window: GLXWindow = 0;
 pub static mut _sapp_glx_DestroyContext: PFNGLXDESTROYCONTEXTPROC = None;
 pub static mut _sapp_glx_ctx: GLXContext = 0 as *const __GLXcontext as *mut __GLXcontext;
+pub static mut _sapp_glx_fbconfig: GLXFBConfig = 0 as *const __GLXFBConfig as *mut __GLXFBConfig;
+pub static mut _sapp_glx_visualid: u64 = 0;
 pub unsafe extern "C" fn _sapp_glx_destroy_context() {
     if _sapp_glx_window != 0 {
         _sapp_glx_DestroyWindow.expect("non-null function pointer")(
@@ -2637,6 +2857,7 @@ pub unsafe extern "C" fn _sapp_run(mut desc: *const sapp_desc) {
         }
         _sapp_frame();
         _sapp_glx_swap_buffers();
+        _sapp_x11_keep_awake_tick();
         XFlush(_sapp_x11_display);
         if _sapp.quit_requested as libc::c_int != 0 && !_sapp.quit_ordered {
             _sapp_x11_app_event(sapp_event_type_SAPP_EVENTTYPE_QUIT_REQUESTED);
@@ -2690,6 +2911,55 @@ pub unsafe extern "C" fn sapp_show_keyboard(mut shown: bool) {}
 pub unsafe extern "C" fn sapp_dpi_scale() -> libc::c_float {
     return _sapp.dpi_scale;
 }
+/* seconds of CLOCK_MONOTONIC, the clock of sapp_event.timestamp */
+#[no_mangle]
+pub unsafe extern "C" fn sapp_timestamp() -> libc::c_double {
+    let mut time: libc::timespec = std::mem::zeroed();
+    libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut time);
+    time.tv_sec as libc::c_double + time.tv_nsec as libc::c_double / 1e9
+}
+/* nanosleep wakes up within some tens of microseconds, timer slack included */
+#[no_mangle]
+pub unsafe extern "C" fn sapp_sleep(mut seconds: libc::c_double) {
+    if seconds > 0. {
+        let time = libc::timespec {
+            tv_sec: seconds as libc::time_t,
+            tv_nsec: (seconds.fract() * 1e9) as libc::c_long,
+        };
+        libc::nanosleep(&time, std::ptr::null_mut());
+    }
+}
+/* display modes are not switched on X11, exclusive fullscreen is windows only */
+#[no_mangle]
+pub unsafe extern "C" fn sapp_display_mode_count() -> libc::c_int {
+    0
+}
+#[no_mangle]
+pub unsafe extern "C" fn sapp_display_mode(
+    _index: libc::c_int,
+    _width: *mut libc::c_int,
+    _height: *mut libc::c_int,
+    _refresh_rate: *mut libc::c_int,
+) -> bool {
+    false
+}
+#[no_mangle]
+pub unsafe extern "C" fn sapp_set_exclusive_fullscreen(
+    width: libc::c_int,
+    _height: libc::c_int,
+    _refresh_rate: libc::c_int,
+) -> bool {
+    width <= 0
+}
+/* bits of the default framebuffer's depth and stencil buffers, as picked by the GLXFBConfig */
+#[no_mangle]
+pub unsafe extern "C" fn sapp_depth_bits() -> libc::c_int {
+    return _sapp.depth_bits;
+}
+#[no_mangle]
+pub unsafe extern "C" fn sapp_stencil_bits() -> libc::c_int {
+    return _sapp.stencil_bits;
+}
 #[no_mangle]
 pub unsafe extern "C" fn sapp_high_dpi() -> bool {
     return _sapp.desc.high_dpi && _sapp.dpi_scale > 1.5f32;
@@ -2718,6 +2988,8 @@ pub static mut _sapp: _sapp_state = _sapp_state {
     framebuffer_height: 0,
     sample_count: 0,
     swap_interval: 0,
+    depth_bits: 0,
+    stencil_bits: 0,
     dpi_scale: 0.,
     gles2_fallback: false,
     first_frame: false,
@@ -2757,6 +3029,7 @@ pub static mut _sapp: _sapp_state = _sapp_state {
         window_height: 0,
         framebuffer_width: 0,
         framebuffer_height: 0,
+        timestamp: 0.,
     },
     desc: sapp_desc {
         init_cb: None,
@@ -2786,6 +3059,9 @@ pub static mut _sapp: _sapp_state = _sapp_state {
         html5_ask_leave_site: false,
         ios_keyboard_resizes_canvas: false,
         gl_force_gles2: false,
+        depth_bits: 0,
+        stencil_bits: 0,
+        robust_buffer_access: false,
     },
     keycodes: [sapp_keycode_SAPP_KEYCODE_INVALID; 512],
 };
@@ -2793,3 +3069,420 @@ pub static mut _sapp: _sapp_state = _sapp_state {
 pub unsafe extern "C" fn sapp_isvalid() -> bool {
     return _sapp.valid;
 }
+pub unsafe extern "C" fn _sapp_x11_set_urgency(mut urgent: bool) {
+    let mut hints = XGetWMHints(_sapp_x11_display, _sapp_x11_window);
+    if hints.is_null() {
+        hints = XAllocWMHints();
+    }
+    if hints.is_null() {
+        return;
+    }
+    if urgent {
+        (*hints).flags |= XUrgencyHint;
+    } else {
+        (*hints).flags &= !XUrgencyHint;
+    }
+    XSetWMHints(_sapp_x11_display, _sapp_x11_window, hints);
+    XFree(hints as *mut libc::c_void);
+    XFlush(_sapp_x11_display);
+}
+#[no_mangle]
+pub unsafe extern "C" fn sapp_request_user_attention() {
+    // the window manager is expected to highlight the window until it is focused,
+    // urgency hint is cleared on FocusIn
+    _sapp_x11_set_urgency(true);
+}
+#[no_mangle]
+pub unsafe extern "C" fn sapp_set_taskbar_progress(mut progress: libc::c_float) {}
+#[no_mangle]
+pub unsafe extern "C" fn sapp_vibrate(mut duration_ms: libc::c_int) {}
+/* no X11 selection handling yet, so nothing to serve an image with */
+#[no_mangle]
+pub unsafe extern "C" fn sapp_set_clipboard_image(
+    _width: libc::c_int,
+    _height: libc::c_int,
+    _pixels: *const u8,
+) -> bool {
+    false
+}
+static mut _sapp_locale: [libc::c_char; 64] = [0; 64];
+/* BCP 47 tag built from the POSIX locale environment, "en_US.UTF-8" becomes "en-US" */
+#[no_mangle]
+pub unsafe extern "C" fn sapp_system_locale() -> *const libc::c_char {
+    let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty())
+        .unwrap_or_default();
+    let mut tag: String = locale
+        .split(|c| c == '.' || c == '@')
+        .next()
+        .unwrap_or("")
+        .replace('_', "-");
+    if tag.is_empty() || tag == "C" || tag == "POSIX" {
+        tag = "en-US".to_string();
+    }
+    let len = tag.len().min(_sapp_locale.len() - 1);
+    for (i, b) in tag.bytes().take(len).enumerate() {
+        _sapp_locale[i] = b as libc::c_char;
+    }
+    _sapp_locale[len] = 0 as libc::c_char;
+    return _sapp_locale.as_ptr();
+}
+/* there is no common dark mode setting on X11, GTK_THEME like "Adwaita:dark" is the closest */
+#[no_mangle]
+pub unsafe extern "C" fn sapp_prefers_dark_theme() -> bool {
+    return std::env::var("GTK_THEME")
+        .map(|theme| theme.to_lowercase().ends_with(":dark"))
+        .unwrap_or(false);
+}
+pub static mut _sapp_x11_confinement: libc::c_int = 0;
+pub static mut _sapp_x11_confine_rect: [libc::c_int; 4] = [0; 4];
+/* X11 confines pointers to windows, a rect gets an InputOnly child window.
+   Input events go through it to the main window, it selects none. */
+pub static mut _sapp_x11_confine_window: Window = 0;
+/* the grab is dropped while the window is not focused, see FocusIn and FocusOut */
+pub unsafe extern "C" fn _sapp_x11_apply_confinement() {
+    XUngrabPointer(_sapp_x11_display, CurrentTime);
+    if _sapp_x11_confinement != 2 && _sapp_x11_confine_window != 0 {
+        XDestroyWindow(_sapp_x11_display, _sapp_x11_confine_window);
+        _sapp_x11_confine_window = 0;
+    }
+    if _sapp_x11_confinement == 0 {
+        return;
+    }
+    let mut confine_to = _sapp_x11_window;
+    if _sapp_x11_confinement == 2 {
+        let [x, y, width, height] = _sapp_x11_confine_rect;
+        if _sapp_x11_confine_window == 0 {
+            _sapp_x11_confine_window = XCreateWindow(
+                _sapp_x11_display,
+                _sapp_x11_window,
+                x,
+                y,
+                width.max(1) as libc::c_uint,
+                height.max(1) as libc::c_uint,
+                0,
+                0,
+                InputOnly as libc::c_uint,
+                std::ptr::null_mut(),
+                0,
+                std::ptr::null_mut(),
+            );
+            XMapWindow(_sapp_x11_display, _sapp_x11_confine_window);
+        } else {
+            XMoveResizeWindow(
+                _sapp_x11_display,
+                _sapp_x11_confine_window,
+                x,
+                y,
+                width.max(1) as libc::c_uint,
+                height.max(1) as libc::c_uint,
+            );
+        }
+        confine_to = _sapp_x11_confine_window;
+    }
+    XGrabPointer(
+        _sapp_x11_display,
+        _sapp_x11_window,
+        1,
+        (ButtonPressMask | ButtonReleaseMask | PointerMotionMask) as libc::c_uint,
+        GrabModeAsync,
+        GrabModeAsync,
+        confine_to,
+        0,
+        CurrentTime,
+    );
+    XFlush(_sapp_x11_display);
+}
+#[no_mangle]
+pub unsafe extern "C" fn sapp_confine_cursor(
+    mut confinement: libc::c_int,
+    mut x: libc::c_float,
+    mut y: libc::c_float,
+    mut width: libc::c_float,
+    mut height: libc::c_float,
+) {
+    _sapp_x11_confinement = confinement;
+    _sapp_x11_confine_rect = [x as libc::c_int, y as libc::c_int, width as libc::c_int, height as libc::c_int];
+    _sapp_x11_apply_confinement();
+}
+/* no desktop-independent accent color on X11 */
+#[no_mangle]
+pub unsafe extern "C" fn sapp_accent_color(
+    _r: *mut libc::c_float,
+    _g: *mut libc::c_float,
+    _b: *mut libc::c_float,
+) -> bool {
+    false
+}
+#[no_mangle]
+pub unsafe extern "C" fn sapp_high_contrast() -> bool {
+    return std::env::var("GTK_THEME")
+        .map(|theme| theme.to_lowercase().contains("highcontrast"))
+        .unwrap_or(false);
+}
+/* X11 has no orientation lock */
+#[no_mangle]
+pub unsafe extern "C" fn sapp_set_orientation_lock(mut orientation: libc::c_int) {}
+/* desktop windows have no unsafe areas */
+#[no_mangle]
+pub unsafe extern "C" fn sapp_safe_area_insets(
+    mut top: *mut libc::c_float,
+    mut right: *mut libc::c_float,
+    mut bottom: *mut libc::c_float,
+    mut left: *mut libc::c_float,
+) {
+    *top = 0.0f32;
+    *right = 0.0f32;
+    *bottom = 0.0f32;
+    *left = 0.0f32;
+}
+pub static mut _sapp_x11_libxrandr: *mut libc::c_void = 0 as *const libc::c_void as *mut libc::c_void;
+/* libXrandr is loaded on demand, so it is not a hard dependency */
+#[no_mangle]
+pub unsafe extern "C" fn sapp_refresh_rate() -> libc::c_int {
+    type XRRGetScreenInfo = unsafe extern "C" fn(*mut Display, Window) -> *mut libc::c_void;
+    type XRRConfigCurrentRate = unsafe extern "C" fn(*mut libc::c_void) -> libc::c_short;
+    type XRRFreeScreenConfigInfo = unsafe extern "C" fn(*mut libc::c_void);
+
+    if _sapp_x11_libxrandr.is_null() {
+        _sapp_x11_libxrandr = dlopen(
+            b"libXrandr.so.2\x00" as *const u8 as *const libc::c_char,
+            RTLD_LAZY | RTLD_GLOBAL,
+        );
+        if _sapp_x11_libxrandr.is_null() {
+            return 0 as libc::c_int;
+        }
+    }
+    let get_screen_info = dlsym(_sapp_x11_libxrandr, b"XRRGetScreenInfo\x00".as_ptr() as _);
+    let current_rate = dlsym(_sapp_x11_libxrandr, b"XRRConfigCurrentRate\x00".as_ptr() as _);
+    let free_info = dlsym(_sapp_x11_libxrandr, b"XRRFreeScreenConfigInfo\x00".as_ptr() as _);
+    if get_screen_info.is_null() || current_rate.is_null() || free_info.is_null() {
+        return 0 as libc::c_int;
+    }
+    let get_screen_info: XRRGetScreenInfo = std::mem::transmute(get_screen_info);
+    let current_rate: XRRConfigCurrentRate = std::mem::transmute(current_rate);
+    let free_info: XRRFreeScreenConfigInfo = std::mem::transmute(free_info);
+
+    let mut config = get_screen_info(_sapp_x11_display, _sapp_x11_root);
+    if config.is_null() {
+        return 0 as libc::c_int;
+    }
+    let mut rate = current_rate(config) as libc::c_int;
+    free_info(config);
+    return rate;
+}
+/* first battery in /sys/class/power_supply, power saving from ACPI platform profile */
+#[no_mangle]
+pub unsafe extern "C" fn sapp_battery_status(
+    mut level: *mut libc::c_float,
+    mut charging: *mut bool,
+    mut power_saving: *mut bool,
+) -> bool {
+    let read = |path: &std::path::Path| {
+        std::fs::read_to_string(path)
+            .ok()
+            .map(|value| value.trim().to_string())
+    };
+    let battery = match std::fs::read_dir("/sys/class/power_supply").ok().and_then(|dir| {
+        dir.filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .find(|path| read(&path.join("type")).as_deref() == Some("Battery"))
+    }) {
+        Some(battery) => battery,
+        None => return false,
+    };
+    let capacity = match read(&battery.join("capacity")).and_then(|c| c.parse::<f32>().ok()) {
+        Some(capacity) => capacity,
+        None => return false,
+    };
+    *level = capacity / 100.0;
+    *charging = read(&battery.join("status")).as_deref() != Some("Discharging");
+    *power_saving = read(std::path::Path::new("/sys/firmware/acpi/platform_profile")).as_deref()
+        == Some("low-power");
+    return true;
+}
+#[no_mangle]
+pub unsafe extern "C" fn sapp_set_window_opacity(mut opacity: libc::c_float) {
+    // _NET_WM_WINDOW_OPACITY is a 32 bit CARDINAL, 0xffffffff means fully opaque
+    let mut value = (opacity.max(0.).min(1.) as libc::c_double * 0xffffffffu32 as libc::c_double)
+        as libc::c_ulong;
+    XChangeProperty(
+        _sapp_x11_display,
+        _sapp_x11_window,
+        _sapp_x11_NET_WM_WINDOW_OPACITY,
+        XA_CARDINAL,
+        32 as libc::c_int,
+        PropModeReplace,
+        &mut value as *mut libc::c_ulong as *mut libc::c_uchar,
+        1 as libc::c_int,
+    );
+    XFlush(_sapp_x11_display);
+}
+#[no_mangle]
+pub unsafe extern "C" fn sapp_set_input_passthrough(mut passthrough: bool) {
+    if passthrough {
+        // empty input region: all the pointer events go to the windows below
+        XShapeCombineRectangles(
+            _sapp_x11_display,
+            _sapp_x11_window,
+            ShapeInput,
+            0 as libc::c_int,
+            0 as libc::c_int,
+            std::ptr::null_mut(),
+            0 as libc::c_int,
+            ShapeSet,
+            Unsorted,
+        );
+    } else {
+        // None mask resets the input region back to the whole window
+        XShapeCombineMask(
+            _sapp_x11_display,
+            _sapp_x11_window,
+            ShapeInput,
+            0 as libc::c_int,
+            0 as libc::c_int,
+            0 as Pixmap,
+            ShapeSet,
+        );
+    }
+    XFlush(_sapp_x11_display);
+}
+/* all the pending X events, input and others alike */
+#[no_mangle]
+pub unsafe extern "C" fn sapp_poll_events() {
+    let mut count = XPending(_sapp_x11_display);
+    while count > 0 {
+        let mut event = _XEvent { type_0: 0 };
+        XNextEvent(_sapp_x11_display, &mut event);
+        _sapp_x11_process_event(&mut event);
+        count -= 1;
+    }
+}
+#[no_mangle]
+pub unsafe extern "C" fn sapp_set_swap_interval(mut interval: libc::c_int) {
+    _sapp.swap_interval = interval;
+    _sapp_glx_swapinterval(interval);
+}
+/* 2 for GLX, see sokol_app.h */
+#[no_mangle]
+pub unsafe extern "C" fn sapp_gl_context_handles(
+    mut display: *mut *mut libc::c_void,
+    mut context: *mut *mut libc::c_void,
+    mut config: *mut *mut libc::c_void,
+    mut drawable: *mut u64,
+    mut visual_id: *mut u64,
+) -> libc::c_int {
+    *display = _sapp_x11_display as *mut libc::c_void;
+    *context = _sapp_glx_ctx as *mut libc::c_void;
+    *config = _sapp_glx_fbconfig as *mut libc::c_void;
+    *drawable = _sapp_glx_window as u64;
+    *visual_id = _sapp_glx_visualid;
+    2
+}
+/* 1 with GLX_OML_sync_control, see sokol_app.h. UST is in microseconds of CLOCK_MONOTONIC
+   with Mesa, the clock of sapp_timestamp */
+#[no_mangle]
+pub unsafe extern "C" fn sapp_present_stats(
+    mut present_count: *mut u64,
+    mut refresh_count: *mut u64,
+    mut time: *mut libc::c_double,
+) -> libc::c_int {
+    if !_sapp_glx_OML_sync_control {
+        return 0;
+    }
+    let (mut ust, mut msc, mut sbc) = (0i64, 0i64, 0i64);
+    if _sapp_glx_GetSyncValuesOML.expect("non-null function pointer")(
+        _sapp_x11_display,
+        _sapp_glx_window,
+        &mut ust,
+        &mut msc,
+        &mut sbc,
+    ) == 0
+        || sbc <= 0
+    {
+        return 0;
+    }
+    /* swap sbc is done already, so this does not wait: it tells when that swap was shown */
+    if _sapp_glx_WaitForSbcOML.expect("non-null function pointer")(
+        _sapp_x11_display,
+        _sapp_glx_window,
+        sbc,
+        &mut ust,
+        &mut msc,
+        &mut sbc,
+    ) == 0
+    {
+        return 0;
+    }
+    *present_count = sbc as u64;
+    *refresh_count = msc as u64;
+    *time = ust as libc::c_double / 1e6;
+    1
+}
+/* GL_NO_ERROR, or GL_GUILTY_CONTEXT_RESET, GL_INNOCENT_CONTEXT_RESET or
+   GL_UNKNOWN_CONTEXT_RESET once the GPU was reset, see sokol_app.h */
+#[no_mangle]
+pub unsafe extern "C" fn sapp_graphics_reset_status() -> libc::c_int {
+    match _sapp_glx_GetGraphicsResetStatusARB {
+        Some(get_status) => get_status() as libc::c_int,
+        None => 0,
+    }
+}
+#[no_mangle]
+pub unsafe extern "C" fn sapp_recreate_context() {
+    _sapp_glx_destroy_context();
+    _sapp_glx_create_context();
+    _sapp_glx_make_current();
+    _sapp_glx_swapinterval(_sapp.swap_interval);
+}
+/* X11 lets any client read the window's pixels, there is nothing to opt out of */
+#[no_mangle]
+pub unsafe extern "C" fn sapp_set_capture_protection(mut protect: bool) -> bool {
+    false
+}
+/* XResetScreenSaver restarts the idle timers of both the X screensaver and DPMS,
+   a D-Bus org.freedesktop.ScreenSaver inhibition would need libdbus */
+pub static mut _sapp_x11_keep_awake: bool = false;
+pub static mut _sapp_x11_keep_awake_time: libc::c_double = 0.;
+pub unsafe extern "C" fn _sapp_x11_keep_awake_tick() {
+    if !_sapp_x11_keep_awake {
+        return;
+    }
+    let now = sapp_timestamp();
+    if now - _sapp_x11_keep_awake_time > 30. {
+        XResetScreenSaver(_sapp_x11_display);
+        _sapp_x11_keep_awake_time = now;
+    }
+}
+#[no_mangle]
+pub unsafe extern "C" fn sapp_keep_awake(mut awake: bool) -> bool {
+    _sapp_x11_keep_awake = awake;
+    _sapp_x11_keep_awake_time = 0.;
+    true
+}
+/* an active keyboard grab sends every key to the window, window manager shortcuts included;
+   it fails while the window is not viewable and is tried again when it gets the focus */
+pub static mut _sapp_x11_shortcuts_captured: bool = false;
+pub unsafe extern "C" fn _sapp_x11_grab_keyboard() -> bool {
+    XGrabKeyboard(
+        _sapp_x11_display,
+        _sapp_x11_window,
+        1,
+        GrabModeAsync,
+        GrabModeAsync,
+        CurrentTime,
+    ) == GrabSuccess
+}
+#[no_mangle]
+pub unsafe extern "C" fn sapp_capture_shortcuts(mut capture: bool) -> bool {
+    _sapp_x11_shortcuts_captured = capture;
+    if capture {
+        _sapp_x11_grab_keyboard();
+    } else {
+        XUngrabKeyboard(_sapp_x11_display, CurrentTime);
+    }
+    XFlush(_sapp_x11_display);
+    true
+}
@@ -5,27 +5,31 @@ pub use string_h::{memset, strcmp, strlen, strstr};
 pub use XKBlib_h::XkbSetDetectableAutoRepeat;
 pub use X_h::{
     AllocNone, Atom, ButtonPressMask, ButtonReleaseMask, CWBorderPixel, CWColormap, CWEventMask,
-    Colormap, ControlMask, Cursor, EnterWindowMask, ExposureMask, FocusChangeMask, InputOutput,
+    Colormap, ControlMask, CurrentTime, Cursor, EnterWindowMask, ExposureMask, FocusChangeMask,
+    GrabModeAsync, GrabSuccess, InputOnly, InputOutput,
     IsViewable, KeyCode, KeyPressMask, KeyReleaseMask, KeySym, LeaveWindowMask, Mod1Mask, Mod4Mask,
     Pixmap, PointerMotionMask, PropModeReplace, PropertyChangeMask, PropertyNewValue, ShiftMask,
-    StaticGravity, StructureNotifyMask, Success, VisibilityChangeMask, Window, XID,
+    StaticGravity, StructureNotifyMask, Success, VisibilityChangeMask, Window, XA_CARDINAL, XID,
 };
 pub use Xlib_h::{
     Display, Screen, Visual, XChangeProperty, XCloseDisplay, XCreateColormap, XCreateWindow,
     XDestroyWindow, XErrorEvent, XErrorHandler, XEvent, XFlush, XFree, XFreeColormap,
     XGetKeyboardMapping, XGetWindowAttributes, XGetWindowProperty, XInitThreads, XInternAtom,
-    XKeyEvent, XMapWindow, XNextEvent, XOpenDisplay, XPending, XPointer, XRaiseWindow,
-    XResourceManagerString, XSetErrorHandler, XSetWMProtocols, XSetWindowAttributes, XSync,
-    XUnmapWindow, XWindowAttributes, XrmInitialize, _XEvent, _XPrivDisplay, _XrmHashBucketRec,
+    XGrabKeyboard, XGrabPointer, XKeyEvent, XMapWindow, XMoveResizeWindow, XNextEvent, XOpenDisplay, XPending, XPointer, XRaiseWindow,
+    XResetScreenSaver, XResourceManagerString, XSetErrorHandler, XSetWMProtocols, XSetWindowAttributes, XSync,
+    XUngrabKeyboard, XUngrabPointer, XUnmapWindow, XWindowAttributes, XrmInitialize, _XEvent, _XPrivDisplay, _XrmHashBucketRec,
+};
+pub use shape_h::{
+    ShapeInput, ShapeSet, Unsorted, XRectangle, XShapeCombineMask, XShapeCombineRectangles,
 };
 pub use Xmd_h::CARD32;
 pub use Xresource_h::{
     XrmDatabase, XrmDestroyDatabase, XrmGetResource, XrmGetStringDatabase, XrmValue,
 };
 pub use Xutil_h::{
-    IconicState, NormalState, PWinGravity, WithdrawnState, XAllocSizeHints, XClassHint,
-    XComposeStatus, XLookupString, XSetWMNormalHints, XSizeHints, XVisualInfo, XWMHints,
-    Xutf8SetWMProperties,
+    IconicState, NormalState, PWinGravity, WithdrawnState, XAllocSizeHints, XAllocWMHints,
+    XClassHint, XComposeStatus, XGetWMHints, XLookupString, XSetWMHints, XSetWMNormalHints,
+    XSizeHints, XUrgencyHint, XVisualInfo, XWMHints, Xutf8SetWMProperties,
 };
 pub type __GLXcontext = ();
 pub type __GLXFBConfig = ();
@@ -773,6 +777,42 @@ pub mod Xlib_h {
         #[no_mangle]
         pub fn XUnmapWindow(_: *mut Display, _: Window) -> libc::c_int;
         #[no_mangle]
+        pub fn XMoveResizeWindow(
+            _: *mut Display,
+            _: Window,
+            _: libc::c_int,
+            _: libc::c_int,
+            _: libc::c_uint,
+            _: libc::c_uint,
+        ) -> libc::c_int;
+        #[no_mangle]
+        pub fn XGrabPointer(
+            _: *mut Display,
+            _: Window,
+            _: libc::c_int,
+            _: libc::c_uint,
+            _: libc::c_int,
+            _: libc::c_int,
+            _: Window,
+            _: Cursor,
+            _: Time,
+        ) -> libc::c_int;
+        #[no_mangle]
+        pub fn XUngrabPointer(_: *mut Display, _: Time) -> libc::c_int;
+        #[no_mangle]
+        pub fn XGrabKeyboard(
+            _: *mut Display,
+            _: Window,
+            _: libc::c_int,
+            _: libc::c_int,
+            _: libc::c_int,
+            _: Time,
+        ) -> libc::c_int;
+        #[no_mangle]
+        pub fn XUngrabKeyboard(_: *mut Display, _: Time) -> libc::c_int;
+        #[no_mangle]
+        pub fn XResetScreenSaver(_: *mut Display) -> libc::c_int;
+        #[no_mangle]
         pub fn XDestroyWindow(_: *mut Display, _: Window) -> libc::c_int;
         #[no_mangle]
         pub fn XFreeColormap(_: *mut Display, _: Colormap) -> libc::c_int;
@@ -808,6 +848,10 @@ pub mod X_h {
     pub const LeaveWindowMask: libc::c_long = (1 as libc::c_long) << 5 as libc::c_int;
     pub const PropertyChangeMask: libc::c_long = (1 as libc::c_long) << 22 as libc::c_int;
     pub const InputOutput: libc::c_int = 1 as libc::c_int;
+    pub const InputOnly: libc::c_int = 2 as libc::c_int;
+    pub const GrabModeAsync: libc::c_int = 1 as libc::c_int;
+    pub const GrabSuccess: libc::c_int = 0 as libc::c_int;
+    pub const CurrentTime: Time = 0 as Time;
     pub const CWBorderPixel: libc::c_long = (1 as libc::c_long) << 3 as libc::c_int;
     pub const CWColormap: libc::c_long = (1 as libc::c_long) << 13 as libc::c_int;
     pub const CWEventMask: libc::c_long = (1 as libc::c_long) << 11 as libc::c_int;
@@ -820,6 +864,7 @@ pub mod X_h {
     pub const Mod1Mask: libc::c_int = (1 as libc::c_int) << 3 as libc::c_int;
     pub const Mod4Mask: libc::c_int = (1 as libc::c_int) << 6 as libc::c_int;
     pub const PropertyNewValue: libc::c_int = 0 as libc::c_int;
+    pub const XA_CARDINAL: Atom = 6 as Atom;
 }
 pub mod Xmd_h {
     pub type CARD32 = libc::c_uint;
@@ -892,6 +937,7 @@ pub mod Xutil_h {
         pub bits_per_rgb: libc::c_int,
     }
     pub const PWinGravity: libc::c_long = (1 as libc::c_long) << 9 as libc::c_int;
+    pub const XUrgencyHint: libc::c_long = (1 as libc::c_long) << 8 as libc::c_int;
     pub const IconicState: libc::c_int = 3 as libc::c_int;
     pub const WithdrawnState: libc::c_int = 0 as libc::c_int;
     pub const NormalState: libc::c_int = 1 as libc::c_int;
@@ -903,6 +949,12 @@ pub mod Xutil_h {
         #[no_mangle]
         pub fn XAllocSizeHints() -> *mut XSizeHints;
         #[no_mangle]
+        pub fn XAllocWMHints() -> *mut XWMHints;
+        #[no_mangle]
+        pub fn XGetWMHints(_: *mut Display, _: Window) -> *mut XWMHints;
+        #[no_mangle]
+        pub fn XSetWMHints(_: *mut Display, _: Window, _: *mut XWMHints) -> libc::c_int;
+        #[no_mangle]
         pub fn Xutf8SetWMProperties(
             _: *mut Display,
             _: Window,
@@ -959,6 +1011,45 @@ pub mod XKBlib_h {
         ) -> libc::c_int;
     }
 }
+pub mod shape_h {
+    #[derive(Copy, Clone)]
+    #[repr(C)]
+    pub struct XRectangle {
+        pub x: libc::c_short,
+        pub y: libc::c_short,
+        pub width: libc::c_ushort,
+        pub height: libc::c_ushort,
+    }
+    pub const ShapeInput: libc::c_int = 2 as libc::c_int;
+    pub const ShapeSet: libc::c_int = 0 as libc::c_int;
+    pub const Unsorted: libc::c_int = 0 as libc::c_int;
+    use super::X_h::{Pixmap, Window};
+    use super::Xlib_h::Display;
+    extern "C" {
+        #[no_mangle]
+        pub fn XShapeCombineRectangles(
+            _: *mut Display,
+            _: Window,
+            _: libc::c_int,
+            _: libc::c_int,
+            _: libc::c_int,
+            _: *mut XRectangle,
+            _: libc::c_int,
+            _: libc::c_int,
+            _: libc::c_int,
+        );
+        #[no_mangle]
+        pub fn XShapeCombineMask(
+            _: *mut Display,
+            _: Window,
+            _: libc::c_int,
+            _: libc::c_int,
+            _: libc::c_int,
+            _: Pixmap,
+            _: libc::c_int,
+        );
+    }
+}
 pub mod stdlib_h {
     extern "C" {
         #[no_mangle]
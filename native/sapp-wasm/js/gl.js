@@ -7,10 +7,8 @@
 // TODO: split to gl.js and loader.js 
 
 const canvas = document.querySelector("#glcanvas");
-const gl = canvas.getContext("webgl");
-if (gl === null) {
-    alert("Unable to initialize WebGL. Your browser or machine may not support it.");
-}
+// created in init_opengl, once the context attributes requested by the app are known
+var gl = null;
 
 function assert(flag, message) {
     if (flag == false) {
@@ -43,18 +41,55 @@ function acquireInstancedArraysExtension(ctx) {
     }
 }
 
-acquireVertexArrayObjectExtension(gl);
-acquireInstancedArraysExtension(gl);
+// extension objects belong to a context, they are acquired again when it is restored
+function init_webgl_extensions() {
+    acquireVertexArrayObjectExtension(gl);
+    acquireInstancedArraysExtension(gl);
+    // u32 indices, core in WebGL 2
+    gl.getExtension('OES_element_index_uint');
+    // RGBA32F textures, for data like bone matrices
+    gl.getExtension('OES_texture_float');
+
+    // https://developer.mozilla.org/en-US/docs/Web/API/WEBGL_depth_texture
+    if (gl.getExtension('WEBGL_depth_texture') == null) {
+        alert("Cant initialize WEBGL_depth_texture extension");
+    }
+}
+
+var context_lost = false;
+// after sapp_quit, as pages can not be closed by scripts
+var frames_stopped = false;
+
+function init_webgl(attributes) {
+    gl = canvas.getContext("webgl", attributes);
+    if (gl === null) {
+        alert("Unable to initialize WebGL. Your browser or machine may not support it.");
+    }
+    init_webgl_extensions();
 
-// https://developer.mozilla.org/en-US/docs/Web/API/WEBGL_depth_texture
-if (gl.getExtension('WEBGL_depth_texture') == null) {
-    alert("Cant initialize WEBGL_depth_texture extension");
+    // GPU reset, driver update or too many contexts on the page.
+    // Without preventDefault the browser would never restore the context.
+    canvas.addEventListener("webglcontextlost", function (event) {
+        event.preventDefault();
+        context_lost = true;
+        wasm_exports.context_lost();
+    }, false);
+    canvas.addEventListener("webglcontextrestored", function () {
+        context_lost = false;
+        init_webgl_extensions();
+        wasm_exports.context_restored();
+    }, false);
 }
 
 function getArray(ptr, arr, n) {
     return new arr(memory.buffer, ptr, n);
 }
 
+// n components of texture data, WebGL wants the typed array to match the type
+function getTexels(ptr, type, n) {
+    return type == 0x1406 /* GL_FLOAT */ ? getArray(ptr, Float32Array, n) : getArray(ptr, Uint8Array, n);
+}
+
 function UTF8ToString(ptr, len) {
     let mem = new Uint8Array(memory.buffer);
     string = '';
@@ -182,6 +217,17 @@ _glGenObject = function (n, buffers, createFunction, objectTable, functionName)
     }
 }
 
+_glDeleteObjects = function (n, ids, deleteFunction, objectTable) {
+    for (var i = 0; i < n; i++) {
+        var id = getArray(ids + i * 4, Uint32Array, 1)[0];
+        var object = objectTable[id];
+        if (!object) continue;
+        gl[deleteFunction](object);
+        object.name = 0;
+        objectTable[id] = null;
+    }
+}
+
 _webglGet = function (name_, p, type) {
     // Guard against user passing a null pointer.
     // Note that GLES2 spec does not say anything about how passing a null pointer should be treated.
@@ -215,7 +261,7 @@ _webglGet = function (name_, p, type) {
             ret = formats ? formats.length : 0;
             break;
         case 0x821D: // GL_NUM_EXTENSIONS
-            assert(false, "unimplemented");
+            ret = (gl.getSupportedExtensions() || []).length;
             break;
         case 0x821B: // GL_MAJOR_VERSION
         case 0x821C: // GL_MINOR_VERSION
@@ -297,9 +343,36 @@ _webglGet = function (name_, p, type) {
 var Module;
 var wasm_exports;
 
+// canvas pixels per CSS pixel, above 1 only when high dpi rendering was asked for
+var high_dpi = false;
+var dpi_scale = 1;
+
+function current_dpi_scale() {
+    return high_dpi ? (window.devicePixelRatio || 1) : 1;
+}
+
+// devicePixelRatio changes with the browser zoom or when the window moves to another monitor
+function watch_dpi_scale() {
+    if (!window.matchMedia) {
+        return;
+    }
+    var query = window.matchMedia("(resolution: " + window.devicePixelRatio + "dppx)");
+    var on_change = function () {
+        query.removeListener(on_change);
+        var scale = current_dpi_scale();
+        if (scale != dpi_scale) {
+            dpi_scale = scale;
+            wasm_exports.dpi_changed();
+            resize(canvas, wasm_exports.resize);
+        }
+        watch_dpi_scale();
+    };
+    query.addListener(on_change);
+}
+
 function resize(canvas, on_resize) {
-    var displayWidth = canvas.clientWidth;
-    var displayHeight = canvas.clientHeight;
+    var displayWidth = Math.floor(canvas.clientWidth * dpi_scale);
+    var displayHeight = Math.floor(canvas.clientHeight * dpi_scale);
 
     if (canvas.width != displayWidth ||
         canvas.height != displayHeight) {
@@ -310,26 +383,256 @@ function resize(canvas, on_resize) {
     }
 }
 
-animation = function () {
-    wasm_exports.frame();
+animation = function (timestamp) {
+    var interval = timestamp - last_animation_frame;
+    last_animation_frame = timestamp;
+    if (first_frame_done) {
+        presented_frames += 1;
+    }
+    // ignore long pauses, like from a hidden tab
+    if (interval > 0.0 && interval < 100.0) {
+        frame_interval = frame_interval == 0.0 ? interval : frame_interval * 0.95 + interval * 0.05;
+    }
+    // nothing could be drawn until the context is restored
+    if (!context_lost && !frames_stopped) {
+        wasm_exports.frame();
+    }
+    if (!first_frame_done) {
+        first_frame_done = true;
+        report_first_frame();
+    }
     window.requestAnimationFrame(animation);
 }
 
-into_sapp_keycode = function (key_code) {
-    switch (event.code) {
-        case "KeyA": return 65;
-        case "KeyS": return 83;
-        case "KeyD": return 68;
-        case "KeyW": return 87;
+// KeyboardEvent.code names the physical key, as on a US layout, just like sapp keycodes
+into_sapp_keycode = function (code) {
+    if (/^Key[A-Z]$/.test(code)) {
+        return code.charCodeAt(3);
+    }
+    if (/^Digit[0-9]$/.test(code)) {
+        return code.charCodeAt(5);
+    }
+    if (/^Numpad[0-9]$/.test(code)) {
+        return 320 + code.charCodeAt(6) - 48;
+    }
+    if (/^F[0-9]+$/.test(code) && +code.substring(1) <= 25) {
+        return 289 + +code.substring(1);
+    }
+    switch (code) {
+        case "Space": return 32;
+        case "Quote": return 39;
+        case "Comma": return 44;
+        case "Minus": return 45;
+        case "Period": return 46;
+        case "Slash": return 47;
+        case "Semicolon": return 59;
+        case "Equal": return 61;
+        case "BracketLeft": return 91;
+        case "Backslash": return 92;
+        case "BracketRight": return 93;
+        case "Backquote": return 96;
+        case "IntlBackslash": return 161;
+        case "Escape": return 256;
+        case "Enter": return 257;
+        case "Tab": return 258;
+        case "Backspace": return 259;
+        case "Insert": return 260;
+        case "Delete": return 261;
         case "ArrowRight": return 262;
         case "ArrowLeft": return 263;
         case "ArrowDown": return 264;
         case "ArrowUp": return 265;
-        case "Space": return 32;
+        case "PageUp": return 266;
+        case "PageDown": return 267;
+        case "Home": return 268;
+        case "End": return 269;
+        case "CapsLock": return 280;
+        case "ScrollLock": return 281;
+        case "NumLock": return 282;
+        case "PrintScreen": return 283;
+        case "Pause": return 284;
+        case "NumpadDecimal": return 330;
+        case "NumpadDivide": return 331;
+        case "NumpadMultiply": return 332;
+        case "NumpadSubtract": return 333;
+        case "NumpadAdd": return 334;
+        case "NumpadEnter": return 335;
+        case "NumpadEqual": return 336;
+        case "ShiftLeft": return 340;
+        case "ControlLeft": return 341;
+        case "AltLeft": return 342;
+        case "MetaLeft": case "OSLeft": return 343;
+        case "ShiftRight": return 344;
+        case "ControlRight": return 345;
+        case "AltRight": return 346;
+        case "MetaRight": case "OSRight": return 347;
+        case "ContextMenu": return 348;
     }
+    return 0;
 }
 
 var emscripten_shaders_hack = false;
+var websockets = [];
+var http_requests = [];
+// video inputs, labels are empty until the page may use a camera
+var camera_devices = [];
+// camera states: 0 waiting for the permission, 1 running, -1 stopped
+var cameras = [];
+
+function camera_enumerate() {
+    navigator.mediaDevices.enumerateDevices().then(function (devices) {
+        camera_devices = devices.filter(function (device) {
+            return device.kind == "videoinput";
+        });
+    });
+}
+
+if (navigator.mediaDevices && navigator.mediaDevices.enumerateDevices) {
+    camera_enumerate();
+    navigator.mediaDevices.addEventListener("devicechange", camera_enumerate);
+}
+var battery = null;
+var safe_area_probe = null;
+var last_safe_area = [0, 0, 0, 0];
+var canvas_snapshot = null;
+var wake_lock = null;
+var wake_lock_wanted = false;
+// unmodified character typed by each sapp keycode on the current layout
+var key_chars = {};
+
+function init_key_chars() {
+    if (navigator.keyboard && navigator.keyboard.getLayoutMap) {
+        navigator.keyboard.getLayoutMap().then(function (layout) {
+            layout.forEach(function (key, code) {
+                key_chars[into_sapp_keycode(code)] = key;
+            });
+        });
+    }
+}
+
+function learn_key_char(event, sapp_key_code) {
+    if (event.key.length == 1 && !event.shiftKey && !event.ctrlKey && !event.altKey && !event.metaKey) {
+        key_chars[sapp_key_code] = event.key;
+    }
+}
+// timeStamp of the DOM input event being handled, in seconds
+var last_event_timestamp = 0.0;
+var text_input = null;
+
+// Mobile browsers show their on-screen keyboard only for a focused text field,
+// so a hidden one is focused while the keyboard should be shown and its text
+// is forwarded as char events.
+function init_text_input() {
+    text_input = document.createElement("input");
+    text_input.type = "text";
+    text_input.setAttribute("autocomplete", "off");
+    text_input.setAttribute("autocorrect", "off");
+    text_input.setAttribute("autocapitalize", "off");
+    text_input.style.position = "fixed";
+    text_input.style.left = "0px";
+    text_input.style.top = "0px";
+    text_input.style.width = "1px";
+    text_input.style.height = "1px";
+    text_input.style.opacity = "0";
+    text_input.style.pointerEvents = "none";
+    // ios zooms into focused fields with a smaller font
+    text_input.style.fontSize = "16px";
+    text_input.oninput = function (event) {
+        last_event_timestamp = event.timeStamp / 1000.0;
+        var text = text_input.value;
+        text_input.value = "";
+        for (const character of text) {
+            wasm_exports.char_input(character.codePointAt(0));
+        }
+    };
+    // key events go to the focused field instead of the canvas
+    text_input.onkeydown = canvas.onkeydown;
+    text_input.onkeyup = canvas.onkeyup;
+    document.body.appendChild(text_input);
+}
+
+// the browser releases the lock whenever the page is hidden, it is asked
+// for again when the page comes back
+function request_wake_lock() {
+    navigator.wakeLock.request("screen")
+        .then(function (lock) {
+            if (wake_lock_wanted) {
+                wake_lock = lock;
+            } else {
+                lock.release();
+            }
+        })
+        .catch(function (e) { console.log("Wake lock failed: " + e); });
+}
+
+document.addEventListener("visibilitychange", function () {
+    if (wake_lock_wanted && document.visibilityState == "visible") {
+        request_wake_lock();
+    }
+});
+
+// env() values are not readable from JS directly, so they are applied
+// to a hidden element and read back from its computed style
+function get_safe_area_insets() {
+    if (safe_area_probe == null) {
+        safe_area_probe = document.createElement("div");
+        var style = safe_area_probe.style;
+        style.position = "fixed";
+        style.visibility = "hidden";
+        style.pointerEvents = "none";
+        style.paddingTop = "env(safe-area-inset-top, 0px)";
+        style.paddingRight = "env(safe-area-inset-right, 0px)";
+        style.paddingBottom = "env(safe-area-inset-bottom, 0px)";
+        style.paddingLeft = "env(safe-area-inset-left, 0px)";
+        document.body.appendChild(safe_area_probe);
+    }
+    var computed = window.getComputedStyle(safe_area_probe);
+    return [
+        parseFloat(computed.paddingTop) || 0,
+        parseFloat(computed.paddingRight) || 0,
+        parseFloat(computed.paddingBottom) || 0,
+        parseFloat(computed.paddingLeft) || 0
+    ];
+}
+
+function check_safe_area() {
+    var insets = get_safe_area_insets();
+    for (var i = 0; i < 4; i++) {
+        if (insets[i] != last_safe_area[i]) {
+            last_safe_area = insets;
+            wasm_exports.safe_area_changed();
+            return;
+        }
+    }
+}
+
+// running average of requestAnimationFrame intervals, in ms
+var frame_interval = 0.0;
+var last_animation_frame = 0.0;
+// frames drawn before the current animation frame, shown by the time it began
+var presented_frames = 0;
+
+if (navigator.getBattery) {
+    navigator.getBattery().then(function (b) { battery = b; });
+}
+
+const STORAGE_OK = 0;
+const STORAGE_QUOTA_EXCEEDED = -1;
+const STORAGE_UNAVAILABLE = -2;
+const STORAGE_NOT_FOUND = -3;
+
+// accessing window.localStorage throws when storage is disabled by the browser
+function get_local_storage() {
+    try {
+        return window.localStorage;
+    } catch (e) {
+        return null;
+    }
+}
+
+function storage_key(ptr, len) {
+    return "miniquad:" + new TextDecoder().decode(getArray(ptr, Uint8Array, len));
+}
 var start;
 var importObject = {
     env: {
@@ -346,10 +649,369 @@ var importObject = {
             return (Date.now() - start) / 1000.0;
         },
         canvas_width: function () {
-            return Math.floor(canvas.clientWidth);
+            return Math.floor(canvas.clientWidth * dpi_scale);
         },
         canvas_height: function () {
-            return Math.floor(canvas.clientHeight);
+            return Math.floor(canvas.clientHeight * dpi_scale);
+        },
+        set_high_dpi: function (enabled) {
+            high_dpi = !!enabled;
+            dpi_scale = current_dpi_scale();
+            resize(canvas);
+            if (high_dpi) {
+                watch_dpi_scale();
+            }
+        },
+        dpi_scale: function () {
+            return dpi_scale;
+        },
+        set_window_opacity: function (opacity) {
+            canvas.style.opacity = Math.min(Math.max(opacity, 0.0), 1.0);
+        },
+        set_input_passthrough: function (passthrough) {
+            canvas.style.pointerEvents = passthrough ? "none" : "auto";
+        },
+        keep_awake: function (awake) {
+            if (!navigator.wakeLock) {
+                return false;
+            }
+            wake_lock_wanted = awake;
+            if (awake) {
+                request_wake_lock();
+            } else if (wake_lock != null) {
+                wake_lock.release();
+                wake_lock = null;
+            }
+            return true;
+        },
+        capture_shortcuts: function (capture) {
+            if (!navigator.keyboard || !navigator.keyboard.lock) {
+                return false;
+            }
+            if (capture) {
+                // rejected outside of fullscreen on some browsers, a no-op on others
+                navigator.keyboard.lock()
+                    .catch(function (e) { console.log("Keyboard lock failed: " + e); });
+            } else {
+                navigator.keyboard.unlock();
+            }
+            return true;
+        },
+        system_locale: function (ptr, max_len) {
+            var locale = new TextEncoder().encode(navigator.language || "en-US");
+            var len = Math.min(locale.length, max_len);
+            getArray(ptr, Uint8Array, len).set(locale.subarray(0, len));
+            return len;
+        },
+        set_orientation_lock: function (orientation) {
+            if (!screen.orientation || !screen.orientation.lock) {
+                return;
+            }
+            if (orientation == 0) {
+                screen.orientation.unlock();
+                return;
+            }
+            // rejected when not in fullscreen, nothing to do about it
+            screen.orientation.lock(orientation == 1 ? "landscape" : "portrait")
+                .catch(function (e) { console.log("Orientation lock failed: " + e); });
+        },
+        safe_area_insets: function (ptr) {
+            getArray(ptr, Float32Array, 4).set(get_safe_area_insets());
+        },
+        canvas_snapshot: function () {
+            canvas_snapshot = new TextEncoder().encode(canvas.toDataURL("image/png"));
+            return canvas_snapshot.length;
+        },
+        canvas_take_snapshot: function (ptr) {
+            getArray(ptr, Uint8Array, canvas_snapshot.length).set(canvas_snapshot);
+            canvas_snapshot = null;
+        },
+        key_char: function (key) {
+            var key_char = key_chars[key];
+            return key_char ? key_char.codePointAt(0) : 0;
+        },
+        set_clipboard_image: function (width, height, pixels) {
+            if (!navigator.clipboard || !navigator.clipboard.write || typeof ClipboardItem === "undefined") {
+                return false;
+            }
+            var image = new ImageData(new Uint8ClampedArray(getArray(pixels, Uint8Array, width * height * 4)), width, height);
+            var copy = document.createElement("canvas");
+            copy.width = width;
+            copy.height = height;
+            copy.getContext("2d").putImageData(image, 0, 0);
+            // a promise rather than a blob: safari wants the item within the user gesture
+            var png = new Promise(function (resolve) { copy.toBlob(resolve, "image/png"); });
+            navigator.clipboard.write([new ClipboardItem({ "image/png": png })])
+                .catch(function (e) { console.log("Copying the image failed: " + e); });
+            return true;
+        },
+        vibrate: function (duration_ms) {
+            if (navigator.vibrate) {
+                navigator.vibrate(duration_ms);
+            }
+        },
+        show_keyboard: function (shown) {
+            if (shown) {
+                text_input.focus();
+            } else {
+                text_input.blur();
+                canvas.focus();
+            }
+        },
+        keyboard_shown: function () {
+            return document.activeElement === text_input;
+        },
+        timestamp: function () {
+            return performance.now() / 1000.0;
+        },
+        event_timestamp: function () {
+            return last_event_timestamp;
+        },
+        stop_frames: function () {
+            frames_stopped = true;
+        },
+        presented_frames: function () {
+            return presented_frames;
+        },
+        present_timestamp: function () {
+            return last_animation_frame / 1000.0;
+        },
+        refresh_rate: function () {
+            if (frame_interval <= 0.0) {
+                return 0;
+            }
+            return Math.round(1000.0 / frame_interval);
+        },
+        battery_level: function () {
+            return battery == null ? -1.0 : battery.level;
+        },
+        battery_charging: function () {
+            return battery != null && battery.charging;
+        },
+        prefers_dark_theme: function () {
+            return window.matchMedia != undefined &&
+                window.matchMedia("(prefers-color-scheme: dark)").matches;
+        },
+        accent_color: function (ptr) {
+            if (!window.CSS || !CSS.supports("color", "AccentColor")) {
+                return false;
+            }
+            var probe = document.createElement("div");
+            probe.style.color = "AccentColor";
+            probe.style.display = "none";
+            document.body.appendChild(probe);
+            var rgb = getComputedStyle(probe).color.match(/[0-9.]+/g);
+            document.body.removeChild(probe);
+            if (!rgb || rgb.length < 3) {
+                return false;
+            }
+            getArray(ptr, Float32Array, 3).set([rgb[0] / 255, rgb[1] / 255, rgb[2] / 255]);
+            return true;
+        },
+        high_contrast: function () {
+            return window.matchMedia != undefined &&
+                (window.matchMedia("(prefers-contrast: more)").matches ||
+                    window.matchMedia("(forced-colors: active)").matches);
+        },
+        ws_connect: function (ptr, len) {
+            var url = UTF8ToString(ptr, len);
+            var socket;
+            try {
+                socket = new WebSocket(url);
+            } catch (e) {
+                console.error(e);
+                return -1;
+            }
+            socket.binaryType = "arraybuffer";
+            var entry = { socket: socket, messages: [], failed: false };
+            socket.onmessage = function (event) {
+                if (typeof event.data === "string") {
+                    entry.messages.push({ data: new TextEncoder().encode(event.data), text: true });
+                } else {
+                    entry.messages.push({ data: new Uint8Array(event.data), text: false });
+                }
+            };
+            // followed by onclose, the state then tells failed from closed
+            socket.onerror = function () {
+                entry.failed = true;
+            };
+            websockets.push(entry);
+            return websockets.length - 1;
+        },
+        // 0 connecting, 1 open, 2 closed, 3 failed
+        ws_state: function (id) {
+            var entry = websockets[id];
+            switch (entry.socket.readyState) {
+                case WebSocket.CONNECTING:
+                    return 0;
+                case WebSocket.OPEN:
+                    return 1;
+                default:
+                    return entry.failed ? 3 : 2;
+            }
+        },
+        ws_send: function (id, ptr, len, text) {
+            var data = getArray(ptr, Uint8Array, len).slice();
+            if (text) {
+                websockets[id].socket.send(new TextDecoder().decode(data));
+            } else {
+                websockets[id].socket.send(data);
+            }
+        },
+        ws_next_message_len: function (id) {
+            var messages = websockets[id].messages;
+            return messages.length == 0 ? -1 : messages[0].data.length;
+        },
+        ws_next_message_is_text: function (id) {
+            return websockets[id].messages[0].text;
+        },
+        ws_take_message: function (id, ptr) {
+            var message = websockets[id].messages.shift().data;
+            getArray(ptr, Uint8Array, message.length).set(message);
+        },
+        ws_close: function (id) {
+            websockets[id].socket.close();
+            websockets[id].messages = [];
+        },
+        http_get: function (ptr, len) {
+            var url = UTF8ToString(ptr, len);
+            var id = http_requests.length;
+            http_requests.push(null);
+            fetch(url)
+                .then(function (response) {
+                    return response.arrayBuffer().then(function (body) {
+                        http_requests[id] = new Uint8Array(body);
+                        wasm_exports.http_on_complete(id, response.status, body.byteLength);
+                    });
+                })
+                .catch(function (e) {
+                    console.error(e);
+                    wasm_exports.http_on_complete(id, 0, 0);
+                });
+            return id;
+        },
+        http_take_response: function (id, ptr) {
+            var body = http_requests[id];
+            getArray(ptr, Uint8Array, body.length).set(body);
+            http_requests[id] = null;
+        },
+        camera_device_count: function () {
+            return camera_devices.length;
+        },
+        camera_device_name: function (index, ptr, max_len) {
+            var name = new TextEncoder().encode(camera_devices[index].label || "Camera " + (index + 1));
+            var len = Math.min(name.length, max_len);
+            getArray(ptr, Uint8Array, len).set(name.subarray(0, len));
+            return len;
+        },
+        camera_open: function (index, width, height) {
+            var camera = { state: 0, video: null, stream: null, time: -1 };
+            cameras.push(camera);
+            if (!navigator.mediaDevices || !navigator.mediaDevices.getUserMedia) {
+                camera.state = -1;
+                return cameras.length - 1;
+            }
+            var constraints = { width: { ideal: width }, height: { ideal: height } };
+            if (index < camera_devices.length && camera_devices[index].deviceId) {
+                constraints.deviceId = { exact: camera_devices[index].deviceId };
+            }
+            navigator.mediaDevices.getUserMedia({ video: constraints, audio: false })
+                .then(function (stream) {
+                    if (camera.state == -1) {
+                        // closed while asking
+                        stream.getTracks().forEach(function (track) { track.stop(); });
+                        return;
+                    }
+                    camera.stream = stream;
+                    stream.getVideoTracks()[0].onended = function () {
+                        camera.state = -1;
+                    };
+                    var video = document.createElement("video");
+                    video.muted = true;
+                    video.playsInline = true;
+                    video.srcObject = stream;
+                    video.onloadedmetadata = function () {
+                        video.play();
+                        camera.video = video;
+                        camera.state = 1;
+                        // the labels are known now
+                        camera_enumerate();
+                    };
+                })
+                .catch(function (e) {
+                    console.error(e);
+                    camera.state = -1;
+                });
+            return cameras.length - 1;
+        },
+        camera_state: function (id) {
+            return cameras[id].state;
+        },
+        camera_width: function (id) {
+            return cameras[id].video ? cameras[id].video.videoWidth : 0;
+        },
+        camera_height: function (id) {
+            return cameras[id].video ? cameras[id].video.videoHeight : 0;
+        },
+        camera_update: function (id, texture) {
+            var camera = cameras[id];
+            if (camera.state != 1 || camera.video.readyState < 2 || camera.video.currentTime == camera.time) {
+                return false;
+            }
+            camera.time = camera.video.currentTime;
+            gl.bindTexture(gl.TEXTURE_2D, GL.textures[texture]);
+            gl.texImage2D(gl.TEXTURE_2D, 0, gl.RGBA, gl.RGBA, gl.UNSIGNED_BYTE, camera.video);
+            return true;
+        },
+        camera_close: function (id) {
+            var camera = cameras[id];
+            if (camera.stream) {
+                camera.stream.getTracks().forEach(function (track) { track.stop(); });
+            }
+            camera.state = -1;
+            camera.video = null;
+            camera.stream = null;
+        },
+        // LocalStorage keeps strings only, so each byte is stored as one UTF-16 char
+        storage_set: function (key_ptr, key_len, ptr, len) {
+            var storage = get_local_storage();
+            if (storage == null) {
+                return STORAGE_UNAVAILABLE;
+            }
+            var data = getArray(ptr, Uint8Array, len);
+            var value = "";
+            for (var i = 0; i < len; i += 4096) {
+                value += String.fromCharCode.apply(null, data.subarray(i, Math.min(i + 4096, len)));
+            }
+            try {
+                storage.setItem(storage_key(key_ptr, key_len), value);
+            } catch (e) {
+                return STORAGE_QUOTA_EXCEEDED;
+            }
+            return STORAGE_OK;
+        },
+        storage_len: function (key_ptr, key_len) {
+            var storage = get_local_storage();
+            if (storage == null) {
+                return STORAGE_UNAVAILABLE;
+            }
+            var value = storage.getItem(storage_key(key_ptr, key_len));
+            return value == null ? STORAGE_NOT_FOUND : value.length;
+        },
+        storage_get: function (key_ptr, key_len, ptr) {
+            var value = get_local_storage().getItem(storage_key(key_ptr, key_len));
+            var data = getArray(ptr, Uint8Array, value.length);
+            for (var i = 0; i < value.length; i++) {
+                data[i] = value.charCodeAt(i);
+            }
+        },
+        storage_remove: function (key_ptr, key_len) {
+            var storage = get_local_storage();
+            if (storage == null) {
+                return STORAGE_UNAVAILABLE;
+            }
+            storage.removeItem(storage_key(key_ptr, key_len));
+            return STORAGE_OK;
         },
         glClearDepthf: function (depth) {
             gl.clearDepth(depth);
@@ -357,6 +1019,9 @@ var importObject = {
         glClearColor: function (r, g, b, a) {
             gl.clearColor(r, g, b, a);
         },
+        glColorMask: function (red, green, blue, alpha) {
+            gl.colorMask(red, green, blue, alpha);
+        },
         glClearStencil: function (s) {
             gl.clearColorStencil(s);
         },
@@ -369,6 +1034,13 @@ var importObject = {
         glGenTextures: function (n, textures) {
             _glGenObject(n, textures, "createTexture", GL.textures, "glGenTextures")
         },
+        glDeleteTextures: function (n, textures) {
+            _glDeleteObjects(n, textures, "deleteTexture", GL.textures);
+        },
+        glReadPixels: function (x, y, width, height, format, type, pixels) {
+            assert(format == 0x1908 && type == 0x1401, "glReadPixels: only RGBA/UNSIGNED_BYTE is supported");
+            gl.readPixels(x, y, width, height, format, type, getArray(pixels, Uint8Array, width * height * 4));
+        },
         glActiveTexture: function (texture) {
             gl.activeTexture(texture)
         },
@@ -378,7 +1050,26 @@ var importObject = {
         },
         glTexImage2D: function (target, level, internalFormat, width, height, border, format, type, pixels) {
             gl.texImage2D(target, level, internalFormat, width, height, border, format, type,
-                pixels ? getArray(pixels, Uint8Array, width * height * 4) : null);
+                pixels ? getTexels(pixels, type, width * height * 4) : null);
+        },
+        glTexSubImage2D: function (target, level, xoffset, yoffset, width, height, format, type, pixels) {
+            gl.texSubImage2D(target, level, xoffset, yoffset, width, height, format, type,
+                pixels ? getTexels(pixels, type, width * height * 4) : null);
+        },
+        glCompressedTexImage2D: function (target, level, internalFormat, width, height, border, imageSize, data) {
+            gl.compressedTexImage2D(target, level, internalFormat, width, height, border,
+                getArray(data, Uint8Array, imageSize));
+        },
+        webgl_extension_name: function (index, ptr, max_len) {
+            var name = (gl.getSupportedExtensions() || [])[index];
+            if (name === undefined) {
+                return 0;
+            }
+            gl.getExtension(name);
+            name = new TextEncoder().encode(name);
+            var len = Math.min(name.length, max_len);
+            getArray(ptr, Uint8Array, len).set(name.subarray(0, len));
+            return len;
         },
         glTexParameteri: function (target, pname, param) {
             gl.texParameteri(target, pname, param);
@@ -386,25 +1077,25 @@ var importObject = {
         glUniform1fv: function (location, count, value) {
             GL.validateGLObjectID(GL.uniforms, location, 'glUniform1fv', 'location');
             assert((value & 3) == 0, 'Pointer to float data passed to glUniform1fv must be aligned to four bytes!');
-            var view = getArray(value, Float32Array, 1);
+            var view = getArray(value, Float32Array, 1 * count);
             gl.uniform1fv(GL.uniforms[location], view);
         },
         glUniform2fv: function (location, count, value) {
             GL.validateGLObjectID(GL.uniforms, location, 'glUniform2fv', 'location');
             assert((value & 3) == 0, 'Pointer to float data passed to glUniform2fv must be aligned to four bytes!');
-            var view = getArray(value, Float32Array, 2);
+            var view = getArray(value, Float32Array, 2 * count);
             gl.uniform2fv(GL.uniforms[location], view);
         },
         glUniform3fv: function (location, count, value) {
             GL.validateGLObjectID(GL.uniforms, location, 'glUniform3fv', 'location');
             assert((value & 3) == 0, 'Pointer to float data passed to glUniform3fv must be aligned to four bytes!');
-            var view = getArray(value, Float32Array, 3);
+            var view = getArray(value, Float32Array, 3 * count);
             gl.uniform3fv(GL.uniforms[location], view);
         },
         glUniform4fv: function (location, count, value) {
             GL.validateGLObjectID(GL.uniforms, location, 'glUniform4fv', 'location');
             assert((value & 3) == 0, 'Pointer to float data passed to glUniform4fv must be aligned to four bytes!');
-            var view = getArray(value, Float32Array, 4);
+            var view = getArray(value, Float32Array, 4 * count);
             gl.uniform4fv(GL.uniforms[location], view);
         },
         glBlendFunc: function (sfactor, dfactor) {
@@ -419,6 +1110,9 @@ var importObject = {
         glDrawElements: function (mode, count, type, indices) {
             gl.drawElements(mode, count, type, indices);
         },
+        glGetError: function () {
+            return gl.getError();
+        },
         glGetIntegerv: function (name_, p) {
             _webglGet(name_, p, 'EM_FUNC_SIG_PARAM_I');
         },
@@ -463,7 +1157,7 @@ var importObject = {
         glUniformMatrix4fv: function (location, count, transpose, value) {
             GL.validateGLObjectID(GL.uniforms, location, 'glUniformMatrix4fv', 'location');
             assert((value & 3) == 0, 'Pointer to float data passed to glUniformMatrix4fv must be aligned to four bytes!');
-            var view = getArray(value, Float32Array, 16);
+            var view = getArray(value, Float32Array, 16 * count);
             gl.uniformMatrix4fv(GL.uniforms[location], !!transpose, view);
         },
         glUseProgram: function (program) {
@@ -473,7 +1167,7 @@ var importObject = {
         glUniform4fv: function (location, count, value) {
             GL.validateGLObjectID(GL.uniforms, location, 'glUniformMatrix4fv', 'location');
             assert((value & 3) == 0, 'Pointer to float data passed to glUniformMatrix4fv must be aligned to four bytes!');
-            var view = getArray(value, Float32Array, 4);
+            var view = getArray(value, Float32Array, 4 * count);
             gl.uniform4fv(GL.uniforms[location], view);
         },
         glGenVertexArrays: function (n, arrays) {
@@ -482,6 +1176,9 @@ var importObject = {
         glGenFramebuffers: function (n, ids) {
             _glGenObject(n, ids, 'createFramebuffer', GL.framebuffers, 'glGenFramebuffers');
         },
+        glDeleteFramebuffers: function (n, ids) {
+            _glDeleteObjects(n, ids, "deleteFramebuffer", GL.framebuffers);
+        },
         glBindVertexArray: function (vao) {
             gl.bindVertexArray(GL.vaos[vao]);
         },
@@ -510,6 +1207,9 @@ var importObject = {
         glDepthFunc: function (func) {
             gl.depthFunc(func);
         },
+        glPolygonOffset: function (factor, units) {
+            gl.polygonOffset(factor, units);
+        },
         glBlendFuncSeparate: function (sfactorRGB, dfactorRGB, sfactorAlpha, dfactorAlpha) {
             gl.blendFuncSeparate(sfactorRGB, dfactorRGB, sfactorAlpha, dfactorAlpha);
         },
@@ -657,53 +1357,261 @@ var importObject = {
         glDrawElementsInstanced: function (mode, count, type, indices, primcount) {
             gl.drawElementsInstanced(mode, count, type, indices, primcount);
         },
+        glDeleteProgram: function (program) {
+            GL.validateGLObjectID(GL.programs, program, 'glDeleteProgram', 'program');
+            gl.deleteProgram(GL.programs[program]);
+            GL.programs[program] = null;
+        },
         glDeleteShader: function () { },
-        init_opengl: function (ptr) {
+        // WebGL 1 has no uniform blocks, GL_INVALID_INDEX for every name
+        glGetUniformBlockIndex: function (program, name) {
+            return -1;
+        },
+        glUniformBlockBinding: function (program, index, binding) { },
+        glBindBufferRange: function (target, index, buffer, offset, size) { },
+        init_opengl: function (depth, stencil, alpha, premultiplied_alpha, preserve_drawing_buffer, antialias, desynchronized) {
+            init_webgl({
+                depth: !!depth,
+                stencil: !!stencil,
+                alpha: !!alpha,
+                premultipliedAlpha: !!premultiplied_alpha,
+                preserveDrawingBuffer: !!preserve_drawing_buffer,
+                antialias: !!antialias,
+                desynchronized: !!desynchronized
+            });
             start = Date.now();
             canvas.onmousemove = function (event) {
-                var x = event.clientX;
-                var y = event.clientY;
+                last_event_timestamp = event.timeStamp / 1000.0;
+                var x = event.clientX * dpi_scale;
+                var y = event.clientY * dpi_scale;
                 wasm_exports.mouse_move(Math.floor(x), Math.floor(y));
             };
             canvas.onmousedown = function (event) {
-                var x = event.clientX;
-                var y = event.clientY;
+                last_event_timestamp = event.timeStamp / 1000.0;
+                var x = event.clientX * dpi_scale;
+                var y = event.clientY * dpi_scale;
                 var btn = event.button;
                 wasm_exports.mouse_down(x, y, btn);
             };
             canvas.onmouseup = function (event) {
-                var x = event.clientX;
-                var y = event.clientY;
+                last_event_timestamp = event.timeStamp / 1000.0;
+                var x = event.clientX * dpi_scale;
+                var y = event.clientY * dpi_scale;
                 var btn = event.button;
                 wasm_exports.mouse_up(x, y, btn);
             };
+            canvas.addEventListener("wheel", function (event) {
+                last_event_timestamp = event.timeStamp / 1000.0;
+                // about 4 per mouse wheel notch, like on windows
+                var scale = event.deltaMode == 0 ? 1 / 25 : event.deltaMode == 1 ? 4 / 3 : 40;
+                wasm_exports.mouse_wheel(-event.deltaX * scale, -event.deltaY * scale, event.ctrlKey);
+                // Ctrl + wheel is a trackpad pinch, keep the browser from zooming the page
+                if (event.ctrlKey) {
+                    event.preventDefault();
+                }
+            }, { passive: false });
+            var on_touch = function (phase) {
+                return function (event) {
+                    last_event_timestamp = event.timeStamp / 1000.0;
+                    for (var i = 0; i < event.changedTouches.length; i++) {
+                        var touch = event.changedTouches[i];
+                        wasm_exports.touch(phase, touch.identifier,
+                            touch.clientX * dpi_scale, touch.clientY * dpi_scale);
+                    }
+                    // two fingers pinch the app, not the page
+                    if (event.touches.length >= 2) {
+                        event.preventDefault();
+                    }
+                };
+            };
+            canvas.addEventListener("touchstart", on_touch(10), { passive: false });
+            canvas.addEventListener("touchmove", on_touch(11), { passive: false });
+            canvas.addEventListener("touchend", on_touch(12), { passive: false });
+            canvas.addEventListener("touchcancel", on_touch(13), { passive: false });
             canvas.onkeydown = function (event) {
-                var sapp_key_code = into_sapp_keycode()
+                last_event_timestamp = event.timeStamp / 1000.0;
+                var sapp_key_code = into_sapp_keycode(event.code);
+                learn_key_char(event, sapp_key_code);
                 wasm_exports.key_down(sapp_key_code);
             };
             canvas.onkeyup = function (event) {
-                var sapp_key_code = into_sapp_keycode()
+                last_event_timestamp = event.timeStamp / 1000.0;
+                var sapp_key_code = into_sapp_keycode(event.code);
                 wasm_exports.key_up(sapp_key_code);
             };
+            init_key_chars();
+            init_text_input();
 
             window.onresize = function () {
                 resize(canvas, wasm_exports.resize);
+                check_safe_area();
             };
+            last_safe_area = get_safe_area_insets();
+            window.addEventListener("orientationchange", check_safe_area);
+            if (screen.orientation) {
+                screen.orientation.addEventListener("change", function () {
+                    wasm_exports.orientation_changed();
+                });
+            }
+            if (window.matchMedia) {
+                var on_theme_change = function () {
+                    wasm_exports.theme_changed();
+                };
+                ["(prefers-color-scheme: dark)", "(prefers-contrast: more)", "(forced-colors: active)"]
+                    .forEach(function (query) {
+                        var media = window.matchMedia(query);
+                        // addListener is the only option on older safari
+                        if (media.addEventListener) {
+                            media.addEventListener("change", on_theme_change);
+                        } else if (media.addListener) {
+                            media.addListener(on_theme_change);
+                        }
+                    });
+            }
             window.requestAnimationFrame(animation);
         }
     }
 };
 
+// Crates built on top of miniquad may ship their own JS next to gl.js.
+// A plugin is an object like:
+// {
+//     name: "sound",
+//     version: "0.1.0",
+//     // called before the wasm module is instantiated,
+//     // add the functions declared in rust's `extern "C"` blocks to importObject.env
+//     register_plugin: function (importObject) { importObject.env.sound_play = function (id) { ... }; },
+//     // called after instantiation, right before main(), `wasm_exports` and `memory` are available
+//     on_init: function () { ... }
+// }
+// Plugins should be added with miniquad_add_plugin before load() is called.
+var plugins = [];
+
+function miniquad_add_plugin(plugin) {
+    assert(plugin.name != undefined, "Plugin should have a name");
+    plugins.push(plugin);
+}
+
+function register_plugins(plugins) {
+    for (var i = 0; i < plugins.length; i++) {
+        if (plugins[i].register_plugin != undefined) {
+            var env_before = Object.keys(importObject.env).length;
+            plugins[i].register_plugin(importObject);
+            if (Object.keys(importObject.env).length == env_before) {
+                console.log("Plugin " + plugins[i].name + " did not register any functions");
+            }
+        }
+    }
+}
+
+function init_plugins(plugins) {
+    for (var i = 0; i < plugins.length; i++) {
+        if (plugins[i].on_init != undefined) {
+            plugins[i].on_init();
+        }
+    }
+}
 
-function load(wasm_path) {
+// Optional loading progress reporting, second argument of load():
+// {
+//     // stage is "download", "compile" or "done", loaded and total are in bytes,
+//     // total is 0 when the server did not send Content-Length
+//     on_progress: function (stage, loaded, total) { ... },
+//     // <progress> element, or any element to put the percentage text in
+//     progress_element: document.getElementById("progress"),
+//     // called right after the first frame was drawn, a good moment to hide the splash screen
+//     on_first_frame: function () { ... }
+// }
+// "miniquad_first_frame" event is dispatched on window as well.
+var loader_options = {};
+var first_frame_done = false;
+
+function report_progress(stage, loaded, total) {
+    if (loader_options.on_progress) {
+        loader_options.on_progress(stage, loaded, total);
+    }
+    var element = loader_options.progress_element;
+    if (element) {
+        if (element instanceof HTMLProgressElement) {
+            if (total > 0) {
+                element.max = total;
+                element.value = loaded;
+            } else {
+                element.removeAttribute("value");
+            }
+        } else {
+            var percent = total > 0 ? Math.floor(loaded * 100 / total) + "%" : "";
+            element.textContent = stage == "download" ? "Loading " + percent : stage == "compile" ? "Starting" : "";
+        }
+    }
+}
+
+function report_first_frame() {
+    if (loader_options.on_first_frame) {
+        loader_options.on_first_frame();
+    }
+    window.dispatchEvent(new Event("miniquad_first_frame"));
+}
+
+// fetch with download progress, needs streams support and Content-Length to be useful
+function fetch_with_progress(response) {
+    var total = parseInt(response.headers.get("Content-Length")) || 0;
+    if (!response.body || !response.body.getReader) {
+        return response.arrayBuffer();
+    }
+    var reader = response.body.getReader();
+    var chunks = [];
+    var loaded = 0;
+    function read() {
+        return reader.read().then(function (result) {
+            if (result.done) {
+                var bytes = new Uint8Array(loaded);
+                var offset = 0;
+                for (var i = 0; i < chunks.length; i++) {
+                    bytes.set(chunks[i], offset);
+                    offset += chunks[i].length;
+                }
+                return bytes.buffer;
+            }
+            chunks.push(result.value);
+            loaded += result.value.length;
+            report_progress("download", loaded, total);
+            return read();
+        });
+    }
+    report_progress("download", 0, total);
+    return read();
+}
+
+function load(wasm_path, options) {
     var req = fetch(wasm_path);
 
-    if (typeof WebAssembly.instantiateStreaming === 'function') {
+    loader_options = options || {};
+    register_plugins(plugins);
+
+    if (loader_options.on_progress || loader_options.progress_element) {
+        // instantiateStreaming would hide the download progress
+        req
+            .then(fetch_with_progress)
+            .then(function (bytes) {
+                report_progress("compile", bytes.byteLength, bytes.byteLength);
+                return WebAssembly.instantiate(bytes, importObject);
+            })
+            .then(function (obj) {
+                memory = obj.instance.exports.memory;
+                wasm_exports = obj.instance.exports;
+
+                report_progress("done", 0, 0);
+                init_plugins(plugins);
+                obj.instance.exports.main();
+            });
+    } else if (typeof WebAssembly.instantiateStreaming === 'function') {
         WebAssembly.instantiateStreaming(req, importObject)
             .then(obj => {
                 memory = obj.instance.exports.memory;
                 wasm_exports = obj.instance.exports;
 
+                init_plugins(plugins);
                 obj.instance.exports.main();
             });
     } else {
@@ -714,6 +1622,7 @@ function load(wasm_path) {
                 memory = obj.instance.exports.memory;
                 wasm_exports = obj.instance.exports;
 
+                init_plugins(plugins);
                 obj.instance.exports.main();
             });
     }
@@ -1607,7 +1607,28 @@ extern "C" {
     pub fn glClearBufferfi(buffer: GLenum, drawbuffer: GLint, depth: GLfloat, stencil: GLint);
 }
 extern "C" {
-    pub fn glGetStringi(name: GLenum, index: GLuint) -> *const GLubyte;
+    fn webgl_extension_name(index: GLuint, buf: *mut GLubyte, max_len: u32) -> u32;
+}
+
+/// WebGL has no glGetStringi, so extension names are copied out of JS
+/// and kept alive forever, just like GL does with its strings.
+/// Querying an extension also enables it, WebGL extensions do nothing until requested.
+pub unsafe fn glGetStringi(name: GLenum, index: GLuint) -> *const GLubyte {
+    static mut NAMES: Vec<Vec<u8>> = Vec::new();
+
+    if name != GL_EXTENSIONS {
+        return std::ptr::null();
+    }
+    let mut buf = vec![0; 128];
+    // one byte is kept for the terminating zero
+    let len = webgl_extension_name(index, buf.as_mut_ptr(), buf.len() as u32 - 1);
+    if len == 0 {
+        return std::ptr::null();
+    }
+    buf.truncate(len as usize + 1);
+    let ptr = buf.as_ptr();
+    NAMES.push(buf);
+    ptr
 }
 extern "C" {
     pub fn glCopyBufferSubData(
@@ -40,7 +40,14 @@ pub const sapp_event_type_SAPP_EVENTTYPE_SUSPENDED: sapp_event_type = 17;
 pub const sapp_event_type_SAPP_EVENTTYPE_RESUMED: sapp_event_type = 18;
 pub const sapp_event_type_SAPP_EVENTTYPE_UPDATE_CURSOR: sapp_event_type = 19;
 pub const sapp_event_type_SAPP_EVENTTYPE_QUIT_REQUESTED: sapp_event_type = 20;
-pub const sapp_event_type__SAPP_EVENTTYPE_NUM: sapp_event_type = 21;
+pub const sapp_event_type_SAPP_EVENTTYPE_THEME_CHANGED: sapp_event_type = 21;
+pub const sapp_event_type_SAPP_EVENTTYPE_SAFE_AREA_CHANGED: sapp_event_type = 22;
+pub const sapp_event_type_SAPP_EVENTTYPE_ORIENTATION_CHANGED: sapp_event_type = 23;
+pub const sapp_event_type_SAPP_EVENTTYPE_CONTEXT_RESTORED: sapp_event_type = 24;
+pub const sapp_event_type_SAPP_EVENTTYPE_EXPOSED: sapp_event_type = 25;
+pub const sapp_event_type_SAPP_EVENTTYPE_DPI_CHANGED: sapp_event_type = 26;
+pub const sapp_event_type_SAPP_EVENTTYPE_CONTEXT_LOST: sapp_event_type = 27;
+pub const sapp_event_type__SAPP_EVENTTYPE_NUM: sapp_event_type = 28;
 pub const sapp_event_type__SAPP_EVENTTYPE_FORCE_U32: sapp_event_type = 2147483647;
 
 pub const sapp_keycode_SAPP_KEYCODE_INVALID: sapp_keycode = 0;
@@ -196,6 +203,7 @@ pub struct sapp_event {
     pub window_height: ::std::os::raw::c_int,
     pub framebuffer_width: ::std::os::raw::c_int,
     pub framebuffer_height: ::std::os::raw::c_int,
+    pub timestamp: f64,
 }
 
 #[repr(C)]
@@ -238,6 +246,11 @@ pub struct sapp_desc {
     pub html5_ask_leave_site: bool,
     pub ios_keyboard_resizes_canvas: bool,
     pub gl_force_gles2: bool,
+    pub depth_bits: ::std::os::raw::c_int,
+    pub stencil_bits: ::std::os::raw::c_int,
+    pub html5_antialias: bool,
+    pub html5_desynchronized: bool,
+    pub robust_buffer_access: bool,
 }
 
 static mut SAPP_DESC: Option<sapp_desc> = None;
@@ -256,7 +269,19 @@ pub unsafe fn sapp_run(desc: *const sapp_desc) -> ::std::os::raw::c_int {
         }));
     }
 
-    init_opengl();
+    // WebGL only knows whether there is a depth or stencil buffer at all,
+    // the browser picks the bits
+    init_opengl(
+        (*desc).depth_bits > 0,
+        (*desc).stencil_bits > 0,
+        (*desc).alpha,
+        (*desc).html5_premultiplied_alpha,
+        (*desc).html5_preserve_drawing_buffer,
+        (*desc).html5_antialias,
+        (*desc).html5_desynchronized,
+    );
+
+    set_high_dpi((*desc).high_dpi);
 
     USER_DATA = (&*desc).user_data;
 
@@ -269,6 +294,11 @@ pub unsafe fn sapp_run(desc: *const sapp_desc) -> ::std::os::raw::c_int {
     0
 }
 
+/// Canvas pixels per CSS pixel, `window.devicePixelRatio` with `high_dpi`, 1 otherwise
+pub unsafe fn sapp_dpi_scale() -> f32 {
+    dpi_scale()
+}
+
 pub unsafe fn sapp_width() -> ::std::os::raw::c_int {
     canvas_width()
 }
@@ -276,10 +306,321 @@ pub unsafe fn sapp_height() -> ::std::os::raw::c_int {
     canvas_height()
 }
 
+pub unsafe fn sapp_set_window_opacity(opacity: f32) {
+    set_window_opacity(opacity);
+}
+
+pub unsafe fn sapp_set_input_passthrough(passthrough: bool) {
+    set_input_passthrough(passthrough);
+}
+
+static mut LOCALE: [u8; 64] = [0; 64];
+
+pub unsafe fn sapp_system_locale() -> *const ::std::os::raw::c_char {
+    // one byte is kept for the terminating zero
+    let len = system_locale(LOCALE.as_mut_ptr() as _, LOCALE.len() as u32 - 1);
+    LOCALE[len as usize] = 0;
+    LOCALE.as_ptr() as _
+}
+
+pub unsafe fn sapp_prefers_dark_theme() -> bool {
+    prefers_dark_theme()
+}
+
+/// CSS AccentColor system color, not supported by all browsers. Some report a fixed
+/// color instead of the user's one, to not expose it to every page.
+pub unsafe fn sapp_accent_color(r: *mut f32, g: *mut f32, b: *mut f32) -> bool {
+    let mut rgb = [0f32; 3];
+    if !accent_color(rgb.as_mut_ptr()) {
+        return false;
+    }
+    *r = rgb[0];
+    *g = rgb[1];
+    *b = rgb[2];
+    true
+}
+
+/// prefers-contrast: more, or forced-colors: active for windows high contrast themes.
+pub unsafe fn sapp_high_contrast() -> bool {
+    high_contrast()
+}
+
+/// Screen Orientation API, browsers allow locking only in fullscreen.
+pub unsafe fn sapp_set_orientation_lock(orientation: ::std::os::raw::c_int) {
+    set_orientation_lock(orientation);
+}
+
+/// Browsers only confine the cursor with pointer lock, which also hides it, this is a no-op.
+pub unsafe fn sapp_confine_cursor(
+    _confinement: ::std::os::raw::c_int,
+    _x: f32,
+    _y: f32,
+    _width: f32,
+    _height: f32,
+) {
+}
+
+/// CSS env(safe-area-inset-*), non zero only on devices with notches
+/// and with "viewport-fit=cover" in the page's viewport meta tag.
+pub unsafe fn sapp_safe_area_insets(
+    top: *mut f32,
+    right: *mut f32,
+    bottom: *mut f32,
+    left: *mut f32,
+) {
+    let mut insets = [0.0f32; 4];
+    safe_area_insets(insets.as_mut_ptr());
+    *top = insets[0];
+    *right = insets[1];
+    *bottom = insets[2];
+    *left = insets[3];
+}
+
+/// Focuses a hidden text field, which makes mobile browsers show their on-screen keyboard.
+/// Browsers do that only while handling a user input event.
+pub unsafe fn sapp_show_keyboard(shown: bool) {
+    show_keyboard(shown);
+}
+
+pub unsafe fn sapp_keyboard_shown() -> bool {
+    keyboard_shown()
+}
+
+/// performance.now() in seconds, DOM events' timeStamp use the same clock.
+pub unsafe fn sapp_timestamp() -> f64 {
+    timestamp()
+}
+
+/// Browsers do not let pages change the display mode.
+pub unsafe fn sapp_display_mode_count() -> ::std::os::raw::c_int {
+    0
+}
+
+pub unsafe fn sapp_display_mode(
+    _index: ::std::os::raw::c_int,
+    _width: *mut ::std::os::raw::c_int,
+    _height: *mut ::std::os::raw::c_int,
+    _refresh_rate: *mut ::std::os::raw::c_int,
+) -> bool {
+    false
+}
+
+pub unsafe fn sapp_set_exclusive_fullscreen(
+    width: ::std::os::raw::c_int,
+    _height: ::std::os::raw::c_int,
+    _refresh_rate: ::std::os::raw::c_int,
+) -> bool {
+    width <= 0
+}
+
+pub unsafe fn sapp_depth_bits() -> ::std::os::raw::c_int {
+    let mut bits = 0;
+    glGetIntegerv(GL_DEPTH_BITS, &mut bits);
+    bits
+}
+
+pub unsafe fn sapp_stencil_bits() -> ::std::os::raw::c_int {
+    let mut bits = 0;
+    glGetIntegerv(GL_STENCIL_BITS, &mut bits);
+    bits
+}
+
+/// Browsers do not expose the refresh rate, gl.js estimates it
+/// from requestAnimationFrame intervals.
+pub unsafe fn sapp_refresh_rate() -> ::std::os::raw::c_int {
+    refresh_rate()
+}
+
+/// Browsers deliver events between animation frames only.
+pub unsafe fn sapp_poll_events() {}
+
+/// Browsers always pace requestAnimationFrame with the display.
+pub unsafe fn sapp_set_swap_interval(_interval: ::std::os::raw::c_int) {}
+
+/// WebGL contexts have no native handles.
+pub unsafe fn sapp_gl_context_handles(
+    _display: *mut *mut ::std::os::raw::c_void,
+    _context: *mut *mut ::std::os::raw::c_void,
+    _config: *mut *mut ::std::os::raw::c_void,
+    _drawable: *mut u64,
+    _visual_id: *mut u64,
+) -> ::std::os::raw::c_int {
+    0
+}
+
+/// Context losses come as `SAPP_EVENTTYPE_CONTEXT_LOST` events, the browser
+/// restores the context by itself.
+pub unsafe fn sapp_graphics_reset_status() -> ::std::os::raw::c_int {
+    0
+}
+
+pub unsafe fn sapp_recreate_context() {}
+
+/// Browsers give pages no say over what screen sharing captures.
+pub unsafe fn sapp_set_capture_protection(_protect: bool) -> bool {
+    false
+}
+
+/// A screen wake lock, on browsers with the Screen Wake Lock API.
+pub unsafe fn sapp_keep_awake(awake: bool) -> bool {
+    keep_awake(awake)
+}
+
+/// The Keyboard Lock API, which only takes effect in fullscreen: Escape is then
+/// kept too, holding it leaves fullscreen.
+pub unsafe fn sapp_capture_shortcuts(capture: bool) -> bool {
+    capture_shortcuts(capture)
+}
+
+/// Pages can not be closed by scripts, the frames stop.
+pub unsafe fn sapp_quit() {
+    stop_frames();
+}
+
+/// Browsers do not tell when a frame reached the display. The requestAnimationFrame
+/// timestamp is the closest: the vsync the browser began the frame at, when the
+/// previous one was shown.
+pub unsafe fn sapp_present_stats(
+    present_count: *mut u64,
+    _refresh_count: *mut u64,
+    time: *mut f64,
+) -> ::std::os::raw::c_int {
+    let count = presented_frames();
+    if count == 0 {
+        return 0;
+    }
+    *present_count = count as u64;
+    *time = present_timestamp();
+    2
+}
+
+/// Battery API is asynchronous, gl.js keeps the last known state.
+/// Not available in firefox and safari.
+pub unsafe fn sapp_battery_status(
+    level: *mut f32,
+    charging: *mut bool,
+    power_saving: *mut bool,
+) -> bool {
+    let battery_level = battery_level();
+    if battery_level < 0. {
+        return false;
+    }
+    *level = battery_level;
+    *charging = battery_charging();
+    *power_saving = false;
+    true
+}
+
+/// PNG data URL of the canvas, from canvas.toDataURL().
+/// Without preserveDrawingBuffer the canvas is readable only until the browser presents it,
+/// at the end of the current animation frame.
+pub unsafe fn sapp_canvas_snapshot() -> String {
+    let mut url = vec![0u8; canvas_snapshot() as usize];
+    canvas_take_snapshot(url.as_mut_ptr() as _);
+    String::from_utf8_lossy(&url).into_owned()
+}
+
+/// Browsers do not expose the OS setting, 500 ms is the usual default.
+pub unsafe fn sapp_double_click_time() -> ::std::os::raw::c_int {
+    500
+}
+
+/// From navigator.keyboard.getLayoutMap() where available (chrome), otherwise
+/// learned from the keys pressed so far, 0 for keys not seen yet.
+pub unsafe fn sapp_key_char(key: sapp_keycode) -> u32 {
+    key_char(key)
+}
+
+/// Async Clipboard API with a PNG ClipboardItem, the write itself completes later
+/// and needs a recent user gesture. False if the browser has no image clipboard.
+pub unsafe fn sapp_set_clipboard_image(
+    width: ::std::os::raw::c_int,
+    height: ::std::os::raw::c_int,
+    pixels: *const u8,
+) -> bool {
+    set_clipboard_image(width, height, pixels)
+}
+
+/// Browsers have no taskbar to flash, this is a no-op.
+pub unsafe fn sapp_request_user_attention() {}
+
+/// Browsers have no taskbar progress, this is a no-op.
+pub unsafe fn sapp_set_taskbar_progress(_progress: f32) {}
+
+/// navigator.vibrate, supported only by mobile browsers and not by safari.
+pub unsafe fn sapp_vibrate(duration_ms: ::std::os::raw::c_int) {
+    vibrate(duration_ms);
+}
+
 extern "C" {
-    pub fn init_opengl();
+    pub fn init_opengl(
+        depth: bool,
+        stencil: bool,
+        alpha: bool,
+        premultiplied_alpha: bool,
+        preserve_drawing_buffer: bool,
+        antialias: bool,
+        desynchronized: bool,
+    );
     pub fn canvas_width() -> i32;
     pub fn canvas_height() -> i32;
+    pub fn set_high_dpi(high_dpi: bool);
+    pub fn dpi_scale() -> f32;
+    pub fn set_window_opacity(opacity: f32);
+    pub fn set_input_passthrough(passthrough: bool);
+    pub fn keep_awake(awake: bool) -> bool;
+    pub fn capture_shortcuts(capture: bool) -> bool;
+    pub fn system_locale(buf: *mut ::std::os::raw::c_char, max_len: u32) -> u32;
+    pub fn prefers_dark_theme() -> bool;
+    pub fn accent_color(rgb: *mut f32) -> bool;
+    pub fn high_contrast() -> bool;
+    pub fn battery_level() -> f32;
+    pub fn refresh_rate() -> i32;
+    pub fn safe_area_insets(insets: *mut f32);
+    pub fn vibrate(duration_ms: i32);
+    pub fn show_keyboard(shown: bool);
+    pub fn keyboard_shown() -> bool;
+    pub fn timestamp() -> f64;
+    pub fn event_timestamp() -> f64;
+    pub fn presented_frames() -> u32;
+    pub fn stop_frames();
+    pub fn present_timestamp() -> f64;
+    pub fn canvas_snapshot() -> u32;
+    pub fn canvas_take_snapshot(data: *mut ::std::os::raw::c_char);
+    pub fn set_clipboard_image(width: i32, height: i32, pixels: *const u8) -> bool;
+    pub fn key_char(key: u32) -> u32;
+    pub fn set_orientation_lock(orientation: i32);
+    pub fn battery_charging() -> bool;
+    pub fn ws_connect(url: *const ::std::os::raw::c_char, len: u32) -> i32;
+    pub fn ws_state(id: i32) -> i32;
+    pub fn ws_send(id: i32, data: *const ::std::os::raw::c_char, len: u32, text: bool);
+    pub fn ws_next_message_len(id: i32) -> i32;
+    pub fn ws_next_message_is_text(id: i32) -> bool;
+    pub fn ws_take_message(id: i32, data: *mut ::std::os::raw::c_char);
+    pub fn ws_close(id: i32);
+    pub fn http_get(url: *const ::std::os::raw::c_char, len: u32) -> i32;
+    pub fn http_take_response(id: i32, data: *mut ::std::os::raw::c_char);
+    pub fn camera_device_count() -> u32;
+    pub fn camera_device_name(index: u32, name: *mut ::std::os::raw::c_char, max_len: u32) -> u32;
+    pub fn camera_open(index: u32, width: u32, height: u32) -> i32;
+    pub fn camera_state(id: i32) -> i32;
+    pub fn camera_width(id: i32) -> u32;
+    pub fn camera_height(id: i32) -> u32;
+    pub fn camera_update(id: i32, texture: u32) -> bool;
+    pub fn camera_close(id: i32);
+    pub fn storage_set(
+        key: *const ::std::os::raw::c_char,
+        key_len: u32,
+        data: *const ::std::os::raw::c_char,
+        len: u32,
+    ) -> i32;
+    pub fn storage_len(key: *const ::std::os::raw::c_char, key_len: u32) -> i32;
+    pub fn storage_get(
+        key: *const ::std::os::raw::c_char,
+        key_len: u32,
+        data: *mut ::std::os::raw::c_char,
+    );
+    pub fn storage_remove(key: *const ::std::os::raw::c_char, key_len: u32) -> i32;
     pub fn test_log(msg: *const ::std::os::raw::c_char);
 }
 
@@ -306,6 +647,7 @@ pub extern "C" fn mouse_move(x: i32, y: i32) {
     let mut event: sapp_event = unsafe { std::mem::zeroed() };
 
     event.type_ = sapp_event_type_SAPP_EVENTTYPE_MOUSE_MOVE;
+    event.timestamp = unsafe { event_timestamp() };
     event.mouse_x = x as f32;
     event.mouse_y = y as f32;
     unsafe {
@@ -321,6 +663,7 @@ pub extern "C" fn mouse_down(x: i32, y: i32, _btn: i32) {
     let mut event: sapp_event = unsafe { std::mem::zeroed() };
 
     event.type_ = sapp_event_type_SAPP_EVENTTYPE_MOUSE_DOWN;
+    event.timestamp = unsafe { event_timestamp() };
     event.mouse_x = x as f32;
     event.mouse_y = y as f32;
     unsafe {
@@ -336,6 +679,7 @@ pub extern "C" fn mouse_up(x: i32, y: i32, _btn: i32) {
     let mut event: sapp_event = unsafe { std::mem::zeroed() };
 
     event.type_ = sapp_event_type_SAPP_EVENTTYPE_MOUSE_UP;
+    event.timestamp = unsafe { event_timestamp() };
     event.mouse_x = x as f32;
     event.mouse_y = y as f32;
     unsafe {
@@ -346,11 +690,53 @@ pub extern "C" fn mouse_up(x: i32, y: i32, _btn: i32) {
     }
 }
 
+#[no_mangle]
+pub extern "C" fn mouse_wheel(dx: f32, dy: f32, ctrl: bool) {
+    let mut event: sapp_event = unsafe { std::mem::zeroed() };
+
+    event.type_ = sapp_event_type_SAPP_EVENTTYPE_MOUSE_SCROLL;
+    event.timestamp = unsafe { event_timestamp() };
+    event.scroll_x = dx;
+    event.scroll_y = dy;
+    if ctrl {
+        event.modifiers = SAPP_MODIFIER_CTRL;
+    }
+    unsafe {
+        SAPP_DESC
+            .unwrap_or_else(|| panic!())
+            .event_userdata_cb
+            .unwrap_or_else(|| panic!())(&event as *const _, USER_DATA);
+    }
+}
+
+/// One changed touch, `phase` is the TOUCHES_* event type.
+#[no_mangle]
+pub extern "C" fn touch(phase: u32, id: u32, x: f32, y: f32) {
+    let mut event: sapp_event = unsafe { std::mem::zeroed() };
+
+    event.type_ = phase;
+    event.timestamp = unsafe { event_timestamp() };
+    event.num_touches = 1;
+    event.touches[0] = sapp_touchpoint {
+        identifier: id as usize,
+        pos_x: x,
+        pos_y: y,
+        changed: true,
+    };
+    unsafe {
+        SAPP_DESC
+            .unwrap_or_else(|| panic!())
+            .event_userdata_cb
+            .unwrap_or_else(|| panic!())(&event as *const _, USER_DATA);
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn key_down(key: u32) {
     let mut event: sapp_event = unsafe { std::mem::zeroed() };
 
     event.type_ = sapp_event_type_SAPP_EVENTTYPE_KEY_DOWN;
+    event.timestamp = unsafe { event_timestamp() };
     event.key_code = key;
     unsafe {
         SAPP_DESC
@@ -360,11 +746,27 @@ pub extern "C" fn key_down(key: u32) {
     }
 }
 
+#[no_mangle]
+pub extern "C" fn char_input(char_code: u32) {
+    let mut event: sapp_event = unsafe { std::mem::zeroed() };
+
+    event.type_ = sapp_event_type_SAPP_EVENTTYPE_CHAR;
+    event.timestamp = unsafe { event_timestamp() };
+    event.char_code = char_code;
+    unsafe {
+        SAPP_DESC
+            .unwrap_or_else(|| panic!())
+            .event_userdata_cb
+            .unwrap_or_else(|| panic!())(&event as *const _, USER_DATA);
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn key_up(key: u32) {
     let mut event: sapp_event = unsafe { std::mem::zeroed() };
 
     event.type_ = sapp_event_type_SAPP_EVENTTYPE_KEY_UP;
+    event.timestamp = unsafe { event_timestamp() };
     event.key_code = key;
     unsafe {
         SAPP_DESC
@@ -379,8 +781,95 @@ pub extern "C" fn resize(width: i32, height: i32) {
     let mut event: sapp_event = unsafe { std::mem::zeroed() };
 
     event.type_ = sapp_event_type_SAPP_EVENTTYPE_RESIZED;
+    event.timestamp = unsafe { sapp_timestamp() };
     event.window_width = width;
     event.window_height = height;
+    event.framebuffer_width = width;
+    event.framebuffer_height = height;
+    unsafe {
+        SAPP_DESC
+            .unwrap_or_else(|| panic!())
+            .event_userdata_cb
+            .unwrap_or_else(|| panic!())(&event as *const _, USER_DATA);
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn theme_changed() {
+    let mut event: sapp_event = unsafe { std::mem::zeroed() };
+
+    event.type_ = sapp_event_type_SAPP_EVENTTYPE_THEME_CHANGED;
+    event.timestamp = unsafe { sapp_timestamp() };
+    unsafe {
+        SAPP_DESC
+            .unwrap_or_else(|| panic!())
+            .event_userdata_cb
+            .unwrap_or_else(|| panic!())(&event as *const _, USER_DATA);
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn safe_area_changed() {
+    let mut event: sapp_event = unsafe { std::mem::zeroed() };
+
+    event.type_ = sapp_event_type_SAPP_EVENTTYPE_SAFE_AREA_CHANGED;
+    event.timestamp = unsafe { sapp_timestamp() };
+    unsafe {
+        SAPP_DESC
+            .unwrap_or_else(|| panic!())
+            .event_userdata_cb
+            .unwrap_or_else(|| panic!())(&event as *const _, USER_DATA);
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn orientation_changed() {
+    let mut event: sapp_event = unsafe { std::mem::zeroed() };
+
+    event.type_ = sapp_event_type_SAPP_EVENTTYPE_ORIENTATION_CHANGED;
+    event.timestamp = unsafe { sapp_timestamp() };
+    unsafe {
+        SAPP_DESC
+            .unwrap_or_else(|| panic!())
+            .event_userdata_cb
+            .unwrap_or_else(|| panic!())(&event as *const _, USER_DATA);
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn dpi_changed() {
+    let mut event: sapp_event = unsafe { std::mem::zeroed() };
+
+    event.type_ = sapp_event_type_SAPP_EVENTTYPE_DPI_CHANGED;
+    event.timestamp = unsafe { sapp_timestamp() };
+    unsafe {
+        SAPP_DESC
+            .unwrap_or_else(|| panic!())
+            .event_userdata_cb
+            .unwrap_or_else(|| panic!())(&event as *const _, USER_DATA);
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn context_lost() {
+    let mut event: sapp_event = unsafe { std::mem::zeroed() };
+
+    event.type_ = sapp_event_type_SAPP_EVENTTYPE_CONTEXT_LOST;
+    event.timestamp = unsafe { sapp_timestamp() };
+    unsafe {
+        SAPP_DESC
+            .unwrap_or_else(|| panic!())
+            .event_userdata_cb
+            .unwrap_or_else(|| panic!())(&event as *const _, USER_DATA);
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn context_restored() {
+    let mut event: sapp_event = unsafe { std::mem::zeroed() };
+
+    event.type_ = sapp_event_type_SAPP_EVENTTYPE_CONTEXT_RESTORED;
+    event.timestamp = unsafe { sapp_timestamp() };
     unsafe {
         SAPP_DESC
             .unwrap_or_else(|| panic!())
@@ -527,6 +527,13 @@ typedef enum sapp_event_type {
     SAPP_EVENTTYPE_RESUMED,
     SAPP_EVENTTYPE_UPDATE_CURSOR,
     SAPP_EVENTTYPE_QUIT_REQUESTED,
+    SAPP_EVENTTYPE_THEME_CHANGED,
+    SAPP_EVENTTYPE_SAFE_AREA_CHANGED,
+    SAPP_EVENTTYPE_ORIENTATION_CHANGED,
+    SAPP_EVENTTYPE_CONTEXT_RESTORED,    /* the GL context was lost and a new one is current, all GL objects are gone */
+    SAPP_EVENTTYPE_EXPOSED,             /* part of the window was uncovered and has been drawn again */
+    SAPP_EVENTTYPE_DPI_CHANGED,         /* sapp_dpi_scale() changed, e.g. the window moved to another monitor */
+    SAPP_EVENTTYPE_CONTEXT_LOST,        /* the GL context was lost, nothing can be drawn until CONTEXT_RESTORED */
     _SAPP_EVENTTYPE_NUM,
     _SAPP_EVENTTYPE_FORCE_U32 = 0x7FFFFFFF
 } sapp_event_type;
@@ -695,6 +702,7 @@ typedef struct sapp_event {
     int window_height;
     int framebuffer_width;
     int framebuffer_height;
+    double timestamp;                   /* seconds of the sapp_timestamp() clock, taken from the OS event when available */
 } sapp_event;
 
 typedef struct sapp_desc {
@@ -728,6 +736,9 @@ typedef struct sapp_desc {
     bool html5_ask_leave_site;          /* initial state of the internal html5_ask_leave_site flag (see sapp_html5_ask_leave_site()) */
     bool ios_keyboard_resizes_canvas;   /* if true, showing the iOS keyboard shrinks the canvas */
     bool gl_force_gles2;                /* if true, setup GLES2/WebGL even if GLES3/WebGL2 is available */
+    int depth_bits;                     /* preferred depth buffer bits of the default framebuffer, 0 for none */
+    int stencil_bits;                   /* preferred stencil buffer bits of the default framebuffer, 0 for none */
+    bool robust_buffer_access;          /* out of range buffer accesses read zeros or values of the buffer, where the driver supports it */
 } sapp_desc;
 
 /* user-provided functions */
@@ -798,6 +809,79 @@ SOKOL_API_DECL const void* sapp_win32_get_hwnd(void);
 /* Android: get native activity handle */
 SOKOL_API_DECL const void* sapp_android_get_native_activity(void);
 
+/* set the opacity of the whole window, 0.0 is fully transparent, 1.0 is opaque */
+SOKOL_API_DECL void sapp_set_window_opacity(float opacity);
+/* if true, mouse input goes through the window to whatever is below it */
+SOKOL_API_DECL void sapp_set_input_passthrough(bool passthrough);
+/* flash the taskbar button until the window gets focus */
+SOKOL_API_DECL void sapp_request_user_attention(void);
+/* show progress (0.0 .. 1.0) on the taskbar button, a negative value removes it */
+SOKOL_API_DECL void sapp_set_taskbar_progress(float progress);
+/* vibrate the device for the given milliseconds, only on mobile platforms */
+SOKOL_API_DECL void sapp_vibrate(int duration_ms);
+/* longest time in milliseconds between the clicks of a double click, as set in the OS */
+SOKOL_API_DECL int sapp_double_click_time(void);
+/* character a key types without modifiers on the current keyboard layout, 0 for keys not typing one */
+SOKOL_API_DECL uint32_t sapp_key_char(sapp_keycode key);
+/* put an image on the clipboard, RGBA8 rows from top to bottom, false if the clipboard could not be set */
+SOKOL_API_DECL bool sapp_set_clipboard_image(int width, int height, const uint8_t* pixels);
+/* user's preferred locale as a BCP 47 tag, like "en-US" */
+SOKOL_API_DECL const char* sapp_system_locale(void);
+/* true if the OS is set to a dark color scheme, SAPP_EVENTTYPE_THEME_CHANGED is sent when it changes */
+SOKOL_API_DECL bool sapp_prefers_dark_theme(void);
+/* the user's accent color, components from 0 to 1, false if the OS has none; SAPP_EVENTTYPE_THEME_CHANGED is sent when it changes */
+SOKOL_API_DECL bool sapp_accent_color(float* r, float* g, float* b);
+/* true in high contrast mode; SAPP_EVENTTYPE_THEME_CHANGED is sent when it changes */
+SOKOL_API_DECL bool sapp_high_contrast(void);
+/* battery level from 0.0 to 1.0, returns false if there is no battery */
+SOKOL_API_DECL bool sapp_battery_status(float* level, bool* charging, bool* power_saving);
+/* refresh rate in Hz of the monitor the window is on, 0 if unknown */
+SOKOL_API_DECL int sapp_refresh_rate(void);
+/* insets in pixels of the area not covered by notches and rounded display corners */
+SOKOL_API_DECL void sapp_safe_area_insets(float* top, float* right, float* bottom, float* left);
+/* 0: any orientation, 1: landscape only, 2: portrait only */
+SOKOL_API_DECL void sapp_set_orientation_lock(int orientation);
+/* 0: cursor moves freely, 1: cursor kept inside the window, 2: inside the rect, in mouse event coordinates */
+SOKOL_API_DECL void sapp_confine_cursor(int confinement, float x, float y, float width, float height);
+/* depth buffer bits of the default framebuffer actually obtained, may differ from sapp_desc.depth_bits */
+SOKOL_API_DECL int sapp_depth_bits(void);
+/* stencil buffer bits of the default framebuffer actually obtained */
+SOKOL_API_DECL int sapp_stencil_bits(void);
+/* seconds of a monotonic clock, the one of sapp_event.timestamp */
+SOKOL_API_DECL double sapp_timestamp(void);
+/* block the calling thread for about the given seconds, as close to it as the OS timers allow */
+SOKOL_API_DECL void sapp_sleep(double seconds);
+/* number of 32 bits per pixel display modes of the monitor the window is on */
+SOKOL_API_DECL int sapp_display_mode_count(void);
+/* size and refresh rate of a display mode, false if index is out of range */
+SOKOL_API_DECL bool sapp_display_mode(int index, int* width, int* height, int* refresh_rate);
+/* switch the monitor to a display mode and cover it with the window, width 0 goes back to windowed mode;
+   refresh_rate 0 picks the highest rate of the size */
+SOKOL_API_DECL bool sapp_set_exclusive_fullscreen(int width, int height, int refresh_rate);
+/* change the swap interval while running, 0 to stop waiting for vsync */
+SOKOL_API_DECL void sapp_set_swap_interval(int interval);
+/* process the pending keyboard and mouse events now, e.g. between drawing and presenting a frame */
+SOKOL_API_DECL void sapp_poll_events(void);
+/* native handles of the GL context, e.g. for an OpenXR graphics binding: returns 1 for WGL,
+   with the HDC as display and the HGLRC as context, 2 for GLX, with the Display*, the GLXContext,
+   the GLXFBConfig, the GLXWindow as drawable and the visual id, 0 when there are none */
+SOKOL_API_DECL int sapp_gl_context_handles(void** display, void** context, void** config, uint64_t* drawable, uint64_t* visual_id);
+/* when the last presented frame reached the display, as the platform reports it: the number of
+   frames presented, the display refresh count and the time in seconds of the sapp_timestamp clock.
+   Returns 1 with all of them, 2 without the refresh count, 0 when the platform does not tell */
+SOKOL_API_DECL int sapp_present_stats(uint64_t* present_count, uint64_t* refresh_count, double* time);
+/* GL_NO_ERROR, or GL_GUILTY_CONTEXT_RESET, GL_INNOCENT_CONTEXT_RESET or GL_UNKNOWN_CONTEXT_RESET once
+   the GPU was reset and the context is lost, always GL_NO_ERROR without robust context support */
+SOKOL_API_DECL int sapp_graphics_reset_status(void);
+/* replace a lost GL context with a new one, made current, all GL objects are gone */
+SOKOL_API_DECL void sapp_recreate_context(void);
+/* keep the window out of screenshots, recordings and screen sharing, false when the platform can not */
+SOKOL_API_DECL bool sapp_set_capture_protection(bool protect);
+/* keep the display and the system from sleeping and the screensaver off, false when the platform can not */
+SOKOL_API_DECL bool sapp_keep_awake(bool awake);
+/* swallow system shortcuts like the Windows key or Alt+Tab while the window is in front, false when the platform can not */
+SOKOL_API_DECL bool sapp_capture_shortcuts(bool capture);
+
 #ifdef __cplusplus
 } /* extern "C" */
 #endif
@@ -927,6 +1011,8 @@ typedef struct {
     int framebuffer_height;
     int sample_count;
     int swap_interval;
+    int depth_bits;
+    int stencil_bits;
     float dpi_scale;
     bool gles2_fallback;
     bool first_frame;
@@ -1044,6 +1130,9 @@ _SOKOL_PRIVATE void _sapp_init_state(const sapp_desc* desc) {
     _sapp.dpi_scale = 1.0f;
 }
 
+/* time of the OS event being processed, defined by each platform */
+_SOKOL_PRIVATE double _sapp_event_timestamp(void);
+
 _SOKOL_PRIVATE void _sapp_init_event(sapp_event_type type) {
     memset(&_sapp.event, 0, sizeof(_sapp.event));
     _sapp.event.type = type;
@@ -1053,6 +1142,7 @@ _SOKOL_PRIVATE void _sapp_init_event(sapp_event_type type) {
     _sapp.event.window_height = _sapp.window_height;
     _sapp.event.framebuffer_width = _sapp.framebuffer_width;
     _sapp.event.framebuffer_height = _sapp.framebuffer_height;
+    _sapp.event.timestamp = _sapp_event_timestamp();
 }
 
 _SOKOL_PRIVATE bool _sapp_events_enabled(void) {
@@ -2742,7 +2832,11 @@ _SOKOL_PRIVATE const _sapp_gl_fbconfig* _sapp_gl_choose_fbconfig(const _sapp_gl_
 #include <windows.h>
 #include <windowsx.h>
 #include <shellapi.h>
+#include <objbase.h>
+#include <shobjidl.h>
 #pragma comment (lib, "Shell32.lib")
+#pragma comment (lib, "Ole32.lib")
+#pragma comment (lib, "Advapi32.lib")
 
 #if defined(SOKOL_D3D11)
 #ifndef D3D11_NO_HELPERS
@@ -2786,6 +2880,9 @@ typedef enum MONITOR_DPI_TYPE {
     MDT_DEFAULT = MDT_EFFECTIVE_DPI
 } MONITOR_DPI_TYPE;
 #endif /*DPI_ENUMS_DECLARED*/
+#ifndef WM_DPICHANGED
+#define WM_DPICHANGED 0x02E0
+#endif
 
 static HWND _sapp_win32_hwnd;
 static HDC _sapp_win32_dc;
@@ -2795,6 +2892,31 @@ static float _sapp_win32_content_scale;
 static float _sapp_win32_window_scale;
 static float _sapp_win32_mouse_scale;
 static bool _sapp_win32_iconified;
+static bool _sapp_win32_in_size_move;
+static int _sapp_win32_confinement;
+static RECT _sapp_win32_confine_rect;
+static bool _sapp_win32_exclusive;
+static DEVMODEW _sapp_win32_exclusive_mode;
+static WCHAR _sapp_win32_exclusive_device[CCHDEVICENAME];
+static DWORD _sapp_win32_windowed_style;
+static RECT _sapp_win32_windowed_rect;
+static bool _sapp_win32_layered;
+static ITaskbarList3* _sapp_win32_taskbar;
+static char _sapp_win32_locale[LOCALE_NAME_MAX_LENGTH * 4];
+static bool _sapp_win32_portrait;
+static HHOOK _sapp_win32_keyboard_hook;
+typedef BOOL(WINAPI * SETDISPLAYAUTOROTATIONPREFERENCES_T)(DWORD);
+typedef BOOL(WINAPI * SETWINDOWDISPLAYAFFINITY_T)(HWND, DWORD);
+typedef UINT(WINAPI * TIMEPERIOD_T)(UINT);
+static HANDLE _sapp_win32_sleep_timer;
+static bool _sapp_win32_sleep_timer_checked;
+static TIMEPERIOD_T _sapp_win32_time_begin_period;
+static TIMEPERIOD_T _sapp_win32_time_end_period;
+#ifndef CREATE_WAITABLE_TIMER_HIGH_RESOLUTION
+#define CREATE_WAITABLE_TIMER_HIGH_RESOLUTION 0x00000002
+#endif
+static const CLSID _sapp_CLSID_TaskbarList = { 0x56fdf344, 0xfd6d, 0x11d0, {0x95, 0x8a, 0x00, 0x60, 0x97, 0xc9, 0xa0, 0x90} };
+static const IID _sapp_IID_ITaskbarList3 = { 0xea1afb91, 0x9e28, 0x4b86, {0x90, 0xe9, 0x9e, 0x9f, 0x8a, 0x5e, 0xef, 0xaf} };
 typedef BOOL(WINAPI * SETPROCESSDPIAWARE_T)(void);
 typedef HRESULT(WINAPI * SETPROCESSDPIAWARENESS_T)(PROCESS_DPI_AWARENESS);
 typedef HRESULT(WINAPI * GETDPIFORMONITOR_T)(HMONITOR, MONITOR_DPI_TYPE, UINT*, UINT*);
@@ -2885,6 +3007,7 @@ static bool _sapp_arb_multisample;
 static bool _sapp_arb_pixel_format;
 static bool _sapp_arb_create_context;
 static bool _sapp_arb_create_context_profile;
+static bool _sapp_arb_create_context_robustness;
 static HWND _sapp_win32_msg_hwnd;
 static HDC _sapp_win32_msg_dc;
 
@@ -3146,6 +3269,9 @@ typedef void  (GL_APIENTRY *PFN_glBindRenderbuffer)(GLenum target, GLuint render
 static PFN_glBindRenderbuffer _sapp_glBindRenderbuffer;
 typedef const GLubyte * (GL_APIENTRY *PFN_glGetStringi)(GLenum name, GLuint index);
 static PFN_glGetStringi _sapp_glGetStringi;
+const GLubyte * glGetStringi(GLenum name, GLuint index) {
+    return _sapp_glGetStringi(name, index);
+}
 typedef void  (GL_APIENTRY *PFN_glClearBufferfi)(GLenum buffer, GLint drawbuffer, GLfloat depth, GLint stencil);
 static PFN_glClearBufferfi _sapp_glClearBufferfi;
 typedef void  (GL_APIENTRY *PFN_glClearBufferfv)(GLenum buffer, GLint drawbuffer, const GLfloat * value);
@@ -3211,6 +3337,24 @@ void glDeleteShader(GLuint shader) {
 }
 typedef void  (GL_APIENTRY *PFN_glDeleteProgram)(GLuint program);
 static PFN_glDeleteProgram _sapp_glDeleteProgram;
+void glDeleteProgram(GLuint program) {
+    _sapp_glDeleteProgram(program);
+}
+typedef void  (GL_APIENTRY *PFN_glBindBufferRange)(GLenum target, GLuint index, GLuint buffer, GLintptr offset, GLsizeiptr size);
+static PFN_glBindBufferRange _sapp_glBindBufferRange;
+void glBindBufferRange(GLenum target, GLuint index, GLuint buffer, GLintptr offset, GLsizeiptr size) {
+    _sapp_glBindBufferRange(target, index, buffer, offset, size);
+}
+typedef GLuint (GL_APIENTRY *PFN_glGetUniformBlockIndex)(GLuint program, const GLchar * uniformBlockName);
+static PFN_glGetUniformBlockIndex _sapp_glGetUniformBlockIndex;
+GLuint glGetUniformBlockIndex(GLuint program, const GLchar * uniformBlockName) {
+    return _sapp_glGetUniformBlockIndex(program, uniformBlockName);
+}
+typedef void  (GL_APIENTRY *PFN_glUniformBlockBinding)(GLuint program, GLuint uniformBlockIndex, GLuint uniformBlockBinding);
+static PFN_glUniformBlockBinding _sapp_glUniformBlockBinding;
+void glUniformBlockBinding(GLuint program, GLuint uniformBlockIndex, GLuint uniformBlockBinding) {
+    _sapp_glUniformBlockBinding(program, uniformBlockIndex, uniformBlockBinding);
+}
 typedef void  (GL_APIENTRY *PFN_glCompileShader)(GLuint shader);
 static PFN_glCompileShader _sapp_glCompileShader;
 void glCompileShader(GLuint shader) {
@@ -3245,6 +3389,9 @@ typedef void  (GL_APIENTRY *PFN_glFramebufferRenderbuffer)(GLenum target, GLenum
 static PFN_glFramebufferRenderbuffer _sapp_glFramebufferRenderbuffer;
 typedef void  (GL_APIENTRY *PFN_glCompressedTexImage2D)(GLenum target, GLint level, GLenum internalformat, GLsizei width, GLsizei height, GLint border, GLsizei imageSize, const void * data);
 static PFN_glCompressedTexImage2D _sapp_glCompressedTexImage2D;
+void glCompressedTexImage2D(GLenum target, GLint level, GLenum internalformat, GLsizei width, GLsizei height, GLint border, GLsizei imageSize, const void * data) {
+    _sapp_glCompressedTexImage2D(target, level, internalformat, width, height, border, imageSize, data);
+}
 typedef void  (GL_APIENTRY *PFN_glCompressedTexImage3D)(GLenum target, GLint level, GLenum internalformat, GLsizei width, GLsizei height, GLsizei depth, GLint border, GLsizei imageSize, const void * data);
 static PFN_glCompressedTexImage3D _sapp_glCompressedTexImage3D;
 typedef void  (GL_APIENTRY *PFN_glActiveTexture)(GLenum texture);
@@ -3268,6 +3415,9 @@ void glGenTextures(GLsizei n, GLuint * textures) {
 }
 typedef void  (GL_APIENTRY *PFN_glPolygonOffset)(GLfloat factor, GLfloat units);
 static PFN_glPolygonOffset _sapp_glPolygonOffset;
+void glPolygonOffset(GLfloat factor, GLfloat units) {
+    _sapp_glPolygonOffset(factor, units);
+}
 typedef void  (GL_APIENTRY *PFN_glDrawElements)(GLenum mode, GLsizei count, GLenum type, const void * indices);
 static PFN_glDrawElements _sapp_glDrawElements;
 void glDrawElements(GLenum mode, GLsizei count, GLenum type, const void * indices) {
@@ -3275,6 +3425,9 @@ void glDrawElements(GLenum mode, GLsizei count, GLenum type, const void * indice
 }
 typedef void  (GL_APIENTRY *PFN_glDeleteFramebuffers)(GLsizei n, const GLuint * framebuffers);
 static PFN_glDeleteFramebuffers _sapp_glDeleteFramebuffers;
+void glDeleteFramebuffers(GLsizei n, const GLuint * framebuffers) {
+    _sapp_glDeleteFramebuffers(n, framebuffers);
+}
 typedef void  (GL_APIENTRY *PFN_glBlendEquationSeparate)(GLenum modeRGB, GLenum modeAlpha);
 static PFN_glBlendEquationSeparate _sapp_glBlendEquationSeparate;
 typedef void  (GL_APIENTRY *PFN_glBlendEquationSeparate)(GLenum modeRGB, GLenum modeAlpha);
@@ -3284,6 +3437,14 @@ void glBlendEquationSeparate(GLenum modeRGB, GLenum modeAlpha) {
 static PFN_glBlendEquationSeparate _sapp_glBlendEquationSeparate;
 typedef void  (GL_APIENTRY *PFN_glDeleteTextures)(GLsizei n, const GLuint * textures);
 static PFN_glDeleteTextures _sapp_glDeleteTextures;
+void glDeleteTextures(GLsizei n, const GLuint * textures) {
+    _sapp_glDeleteTextures(n, textures);
+}
+typedef void  (GL_APIENTRY *PFN_glReadPixels)(GLint x, GLint y, GLsizei width, GLsizei height, GLenum format, GLenum type, void * pixels);
+static PFN_glReadPixels _sapp_glReadPixels;
+void glReadPixels(GLint x, GLint y, GLsizei width, GLsizei height, GLenum format, GLenum type, void * pixels) {
+    _sapp_glReadPixels(x, y, width, height, format, type, pixels);
+}
 typedef void  (GL_APIENTRY *PFN_glGetProgramiv)(GLuint program, GLenum pname, GLint * params);
 static PFN_glGetProgramiv _sapp_glGetProgramiv;
 void glGetProgramiv(GLuint program, GLenum pname, GLint * params) {
@@ -3296,6 +3457,9 @@ void glBindTexture(GLenum target, GLuint texture) {
 }
 typedef void  (GL_APIENTRY *PFN_glTexImage3D)(GLenum target, GLint level, GLint internalformat, GLsizei width, GLsizei height, GLsizei depth, GLint border, GLenum format, GLenum type, const void * pixels);
 static PFN_glTexImage3D _sapp_glTexImage3D;
+void glTexImage3D(GLenum target, GLint level, GLint internalformat, GLsizei width, GLsizei height, GLsizei depth, GLint border, GLenum format, GLenum type, const void * pixels) {
+    _sapp_glTexImage3D(target, level, internalformat, width, height, depth, border, format, type, pixels);
+}
 typedef GLuint (GL_APIENTRY *PFN_glCreateShader)(GLenum type);
 static PFN_glCreateShader _sapp_glCreateShader;
 GLuint glCreateShader(GLenum type) {
@@ -3303,6 +3467,9 @@ GLuint glCreateShader(GLenum type) {
 }
 typedef void  (GL_APIENTRY *PFN_glTexSubImage2D)(GLenum target, GLint level, GLint xoffset, GLint yoffset, GLsizei width, GLsizei height, GLenum format, GLenum type, const void * pixels);
 static PFN_glTexSubImage2D _sapp_glTexSubImage2D;
+void glTexSubImage2D(GLenum target, GLint level, GLint xoffset, GLint yoffset, GLsizei width, GLsizei height, GLenum format, GLenum type, const void * pixels) {
+    _sapp_glTexSubImage2D(target, level, xoffset, yoffset, width, height, format, type, pixels);
+}
 typedef void  (GL_APIENTRY *PFN_glClearDepth)(GLdouble depth);
 static PFN_glClearDepth _sapp_glClearDepth;
 void glClearDepth(GLdouble depth) {
@@ -3358,6 +3525,9 @@ void glDisable(GLenum cap) {
 }
 typedef void  (GL_APIENTRY *PFN_glColorMask)(GLboolean red, GLboolean green, GLboolean blue, GLboolean alpha);
 static PFN_glColorMask _sapp_glColorMask;
+void glColorMask(GLboolean red, GLboolean green, GLboolean blue, GLboolean alpha) {
+    _sapp_glColorMask(red, green, blue, alpha);
+}
 typedef void  (GL_APIENTRY *PFN_glBindBuffer)(GLenum target, GLuint buffer);
 static PFN_glBindBuffer _sapp_glBindBuffer;
 void glBindBuffer(GLenum target, GLuint buffer) {
@@ -3408,6 +3578,41 @@ static PFN_glGetIntegerv _sapp_glGetIntegerv;
 void glGetIntegerv(GLenum pname, GLint * data) {
     _sapp_glGetIntegerv(pname, data);
 }
+typedef void  (GL_APIENTRY *PFN_glGenQueries)(GLsizei n, GLuint * ids);
+static PFN_glGenQueries _sapp_glGenQueries;
+void glGenQueries(GLsizei n, GLuint * ids) {
+    _sapp_glGenQueries(n, ids);
+}
+typedef void  (GL_APIENTRY *PFN_glDeleteQueries)(GLsizei n, const GLuint * ids);
+static PFN_glDeleteQueries _sapp_glDeleteQueries;
+void glDeleteQueries(GLsizei n, const GLuint * ids) {
+    _sapp_glDeleteQueries(n, ids);
+}
+typedef void  (GL_APIENTRY *PFN_glBeginQuery)(GLenum target, GLuint id);
+static PFN_glBeginQuery _sapp_glBeginQuery;
+void glBeginQuery(GLenum target, GLuint id) {
+    _sapp_glBeginQuery(target, id);
+}
+typedef void  (GL_APIENTRY *PFN_glEndQuery)(GLenum target);
+static PFN_glEndQuery _sapp_glEndQuery;
+void glEndQuery(GLenum target) {
+    _sapp_glEndQuery(target);
+}
+typedef void  (GL_APIENTRY *PFN_glClipControl)(GLenum origin, GLenum depth);
+static PFN_glClipControl _sapp_glClipControl;
+void glClipControl(GLenum origin, GLenum depth) {
+    _sapp_glClipControl(origin, depth);
+}
+typedef void  (GL_APIENTRY *PFN_glGetQueryObjectuiv)(GLuint id, GLenum pname, GLuint * params);
+static PFN_glGetQueryObjectuiv _sapp_glGetQueryObjectuiv;
+void glGetQueryObjectuiv(GLuint id, GLenum pname, GLuint * params) {
+    _sapp_glGetQueryObjectuiv(id, pname, params);
+}
+typedef void  (GL_APIENTRY *PFN_glFramebufferTextureMultiviewOVR)(GLenum target, GLenum attachment, GLuint texture, GLint level, GLint baseViewIndex, GLsizei numViews);
+static PFN_glFramebufferTextureMultiviewOVR _sapp_glFramebufferTextureMultiviewOVR;
+void glFramebufferTextureMultiviewOVR(GLenum target, GLenum attachment, GLuint texture, GLint level, GLint baseViewIndex, GLsizei numViews) {
+    _sapp_glFramebufferTextureMultiviewOVR(target, attachment, texture, level, baseViewIndex, numViews);
+}
 typedef void  (GL_APIENTRY *PFN_glEnable)(GLenum cap);
 static PFN_glEnable _sapp_glEnable;
 void  glEnable(GLenum cap) {
@@ -3424,6 +3629,12 @@ void glAttachShader(GLuint program, GLuint shader) {
 }
 typedef GLenum (GL_APIENTRY *PFN_glGetError)();
 static PFN_glGetError _sapp_glGetError;
+GLenum glGetError() {
+    return _sapp_glGetError();
+}
+typedef GLenum (GL_APIENTRY *PFN_glGetGraphicsResetStatusARB)(void);
+/* set when the context was created with reset notification */
+static PFN_glGetGraphicsResetStatusARB _sapp_glGetGraphicsResetStatusARB;
 typedef void  (GL_APIENTRY *PFN_glClearColor)(GLfloat red, GLfloat green, GLfloat blue, GLfloat alpha);
 static PFN_glClearColor _sapp_glClearColor;
 void glClearColor(GLfloat red, GLfloat green, GLfloat blue, GLfloat alpha) {
@@ -3550,6 +3761,7 @@ _SOKOL_PRIVATE  void _sapp_win32_gl_loadfuncs(void) {
     _SAPP_GLPROC(glDeleteFramebuffers);
     _SAPP_GLPROC(glBlendEquationSeparate);
     _SAPP_GLPROC(glDeleteTextures);
+    _SAPP_GLPROC(glReadPixels);
     _SAPP_GLPROC(glGetProgramiv);
     _SAPP_GLPROC(glBindTexture);
     _SAPP_GLPROC(glTexImage3D);
@@ -3579,11 +3791,21 @@ _SOKOL_PRIVATE  void _sapp_win32_gl_loadfuncs(void) {
     _SAPP_GLPROC(glBlendFuncSeparate);
     _SAPP_GLPROC(glTexParameteri);
     _SAPP_GLPROC(glGetIntegerv);
+    _SAPP_GLPROC(glGenQueries);
+    _SAPP_GLPROC(glDeleteQueries);
+    _SAPP_GLPROC(glBeginQuery);
+    _SAPP_GLPROC(glEndQuery);
+    _SAPP_GLPROC(glClipControl);
+    _SAPP_GLPROC(glGetQueryObjectuiv);
+    _SAPP_GLPROC(glFramebufferTextureMultiviewOVR);
     _SAPP_GLPROC(glEnable);
     _SAPP_GLPROC(glBlitFramebuffer);
     _SAPP_GLPROC(glStencilMask);
     _SAPP_GLPROC(glAttachShader);
     _SAPP_GLPROC(glGetError);
+    _SAPP_GLPROC(glBindBufferRange);
+    _SAPP_GLPROC(glGetUniformBlockIndex);
+    _SAPP_GLPROC(glUniformBlockBinding);
     _SAPP_GLPROC(glClearColor);
     _SAPP_GLPROC(glBlendColor);
     _SAPP_GLPROC(glTexParameterf);
@@ -3647,6 +3869,7 @@ _SOKOL_PRIVATE  void _sapp_win32_gl_loadfuncs(void) {
 #define glDeleteFramebuffers _sapp_glDeleteFramebuffers
 #define glBlendEquationSeparate _sapp_glBlendEquationSeparate
 #define glDeleteTextures _sapp_glDeleteTextures
+#define glReadPixels _sapp_glReadPixels
 #define glGetProgramiv _sapp_glGetProgramiv
 #define glBindTexture _sapp_glBindTexture
 #define glTexImage3D _sapp_glTexImage3D
@@ -3675,11 +3898,21 @@ _SOKOL_PRIVATE  void _sapp_win32_gl_loadfuncs(void) {
 #define glBlendFuncSeparate _sapp_glBlendFuncSeparate
 #define glTexParameteri _sapp_glTexParameteri
 #define glGetIntegerv _sapp_glGetIntegerv
+#define glGenQueries _sapp_glGenQueries
+#define glDeleteQueries _sapp_glDeleteQueries
+#define glBeginQuery _sapp_glBeginQuery
+#define glEndQuery _sapp_glEndQuery
+#define glClipControl _sapp_glClipControl
+#define glGetQueryObjectuiv _sapp_glGetQueryObjectuiv
+#define glFramebufferTextureMultiviewOVR _sapp_glFramebufferTextureMultiviewOVR
 #define glEnable _sapp_glEnable
 #define glBlitFramebuffer _sapp_glBlitFramebuffer
 #define glStencilMask _sapp_glStencilMask
 #define glAttachShader _sapp_glAttachShader
 #define glGetError _sapp_glGetError
+#define glBindBufferRange _sapp_glBindBufferRange
+#define glGetUniformBlockIndex _sapp_glGetUniformBlockIndex
+#define glUniformBlockBinding _sapp_glUniformBlockBinding
 #define glClearColor _sapp_glClearColor
 #define glBlendColor _sapp_glBlendColor
 #define glTexParameterf _sapp_glTexParameterf
@@ -3906,6 +4139,7 @@ _SOKOL_PRIVATE void _sapp_wgl_load_extensions(void) {
     _sapp_arb_multisample = _sapp_wgl_ext_supported("WGL_ARB_multisample");
     _sapp_arb_create_context = _sapp_wgl_ext_supported("WGL_ARB_create_context");
     _sapp_arb_create_context_profile = _sapp_wgl_ext_supported("WGL_ARB_create_context_profile");
+    _sapp_arb_create_context_robustness = _sapp_wgl_ext_supported("WGL_ARB_create_context_robustness");
     _sapp_ext_swap_control = _sapp_wgl_ext_supported("WGL_EXT_swap_control");
     _sapp_arb_pixel_format = _sapp_wgl_ext_supported("WGL_ARB_pixel_format");
     _sapp_wglMakeCurrent(_sapp_win32_msg_dc, 0);
@@ -3964,30 +4198,35 @@ _SOKOL_PRIVATE int _sapp_wgl_find_pixel_format(void) {
     desired.green_bits = 8;
     desired.blue_bits = 8;
     desired.alpha_bits = 8;
-    desired.depth_bits = 24;
-    desired.stencil_bits = 8;
+    desired.depth_bits = _sapp.desc.depth_bits;
+    desired.stencil_bits = _sapp.desc.stencil_bits;
     desired.doublebuffer = true;
     desired.samples = _sapp.sample_count > 1 ? _sapp.sample_count : 0;
     closest = _sapp_gl_choose_fbconfig(&desired, usable_configs, usable_count);
     int pixel_format = 0;
     if (closest) {
         pixel_format = (int) closest->handle;
+        _sapp.depth_bits = closest->depth_bits;
+        _sapp.stencil_bits = closest->stencil_bits;
     }
     SOKOL_FREE(usable_configs);
     return pixel_format;
 }
 
 _SOKOL_PRIVATE void _sapp_wgl_create_context(void) {
-    int pixel_format = _sapp_wgl_find_pixel_format();
-    if (0 == pixel_format) {
-        _sapp_fail("WGL: Didn't find matching pixel format.\n");
-    }
-    PIXELFORMATDESCRIPTOR pfd;
-    if (!DescribePixelFormat(_sapp_win32_dc, pixel_format, sizeof(pfd), &pfd)) {
-        _sapp_fail("WGL: Failed to retrieve PFD for selected pixel format!\n");
-    }
-    if (!SetPixelFormat(_sapp_win32_dc, pixel_format, &pfd)) {
-        _sapp_fail("WGL: Failed to set selected pixel format!\n");
+    /* the pixel format of a window is set once, a context created again after a GPU reset keeps it */
+    if (0 == GetPixelFormat(_sapp_win32_dc)) {
+        int pixel_format = _sapp_wgl_find_pixel_format();
+        if (0 == pixel_format) {
+            _sapp_fail("WGL: Didn't find matching pixel format.\n");
+        }
+        PIXELFORMATDESCRIPTOR pfd;
+        if (!DescribePixelFormat(_sapp_win32_dc, pixel_format, sizeof(pfd), &pfd)) {
+            _sapp_fail("WGL: Failed to retrieve PFD for selected pixel format!\n");
+        }
+        if (!SetPixelFormat(_sapp_win32_dc, pixel_format, &pfd)) {
+            _sapp_fail("WGL: Failed to set selected pixel format!\n");
+        }
     }
     if (!_sapp_arb_create_context) {
         _sapp_fail("WGL: ARB_create_context required!\n");
@@ -3995,14 +4234,31 @@ _SOKOL_PRIVATE void _sapp_wgl_create_context(void) {
     if (!_sapp_arb_create_context_profile) {
         _sapp_fail("WGL: ARB_create_context_profile required!\n");
     }
-    const int attrs[] = {
+    /* robust contexts report GPU resets instead of hanging or drawing garbage after them */
+    int attrs[] = {
         WGL_CONTEXT_MAJOR_VERSION_ARB, 3,
         WGL_CONTEXT_MINOR_VERSION_ARB, 3,
         WGL_CONTEXT_FLAGS_ARB, WGL_CONTEXT_FORWARD_COMPATIBLE_BIT_ARB,
         WGL_CONTEXT_PROFILE_MASK_ARB, WGL_CONTEXT_CORE_PROFILE_BIT_ARB,
+        WGL_CONTEXT_RESET_NOTIFICATION_STRATEGY_ARB, WGL_LOSE_CONTEXT_ON_RESET_ARB,
         0, 0
     };
+    if (!_sapp_arb_create_context_robustness) {
+        attrs[8] = 0;
+        attrs[9] = 0;
+    }
+    else if (_sapp.desc.robust_buffer_access) {
+        attrs[5] |= WGL_CONTEXT_ROBUST_ACCESS_BIT_ARB;
+    }
     _sapp_gl_ctx = _sapp_CreateContextAttribsARB(_sapp_win32_dc, 0, attrs);
+    bool robust = _sapp_arb_create_context_robustness;
+    if (!_sapp_gl_ctx && robust) {
+        attrs[5] = WGL_CONTEXT_FORWARD_COMPATIBLE_BIT_ARB;
+        attrs[8] = 0;
+        attrs[9] = 0;
+        robust = false;
+        _sapp_gl_ctx = _sapp_CreateContextAttribsARB(_sapp_win32_dc, 0, attrs);
+    }
     if (!_sapp_gl_ctx) {
         const DWORD err = GetLastError();
         if (err == (0xc0070000 | ERROR_INVALID_VERSION_ARB)) {
@@ -4019,6 +4275,7 @@ _SOKOL_PRIVATE void _sapp_wgl_create_context(void) {
         }
     }
     _sapp_wglMakeCurrent(_sapp_win32_dc, _sapp_gl_ctx);
+    _sapp_glGetGraphicsResetStatusARB = robust ? (PFN_glGetGraphicsResetStatusARB) _sapp_wglGetProcAddress("glGetGraphicsResetStatusARB") : 0;
     if (_sapp_ext_swap_control) {
         /* FIXME: DwmIsCompositionEnabled() (see GLFW) */
         _sapp_SwapIntervalEXT(_sapp.swap_interval);
@@ -4065,6 +4322,487 @@ _SOKOL_PRIVATE bool _sapp_win32_mouse_shown(void) {
     return (cursor_info.flags & CURSOR_SHOWING) != 0;
 }
 
+/* WS_EX_LAYERED without SetLayeredWindowAttributes() makes the window invisible,
+   so the layered style is only ever set together with the alpha value */
+_SOKOL_PRIVATE void _sapp_win32_set_layered(BYTE alpha) {
+    LONG ex_style = GetWindowLongW(_sapp_win32_hwnd, GWL_EXSTYLE);
+    SetWindowLongW(_sapp_win32_hwnd, GWL_EXSTYLE, ex_style | WS_EX_LAYERED);
+    SetLayeredWindowAttributes(_sapp_win32_hwnd, 0, alpha, LWA_ALPHA);
+    _sapp_win32_layered = true;
+}
+
+_SOKOL_PRIVATE void _sapp_win32_set_window_opacity(float opacity) {
+    if (opacity < 0.0f) {
+        opacity = 0.0f;
+    }
+    if (opacity > 1.0f) {
+        opacity = 1.0f;
+    }
+    _sapp_win32_set_layered((BYTE)(opacity * 255.0f));
+}
+
+_SOKOL_PRIVATE void _sapp_win32_set_input_passthrough(bool passthrough) {
+    if (passthrough && !_sapp_win32_layered) {
+        _sapp_win32_set_layered(255);
+    }
+    LONG ex_style = GetWindowLongW(_sapp_win32_hwnd, GWL_EXSTYLE);
+    if (passthrough) {
+        ex_style |= WS_EX_TRANSPARENT;
+    }
+    else {
+        ex_style &= ~WS_EX_TRANSPARENT;
+    }
+    SetWindowLongW(_sapp_win32_hwnd, GWL_EXSTYLE, ex_style);
+}
+
+/* WDA_EXCLUDEFROMCAPTURE leaves the window out of captures from windows 10 2004 on,
+   older versions only know WDA_MONITOR, which captures the window black */
+_SOKOL_PRIVATE bool _sapp_win32_set_capture_protection(bool protect) {
+    HINSTANCE user32 = GetModuleHandleW(L"user32.dll");
+    if (0 == user32) {
+        return false;
+    }
+    SETWINDOWDISPLAYAFFINITY_T set_affinity =
+        (SETWINDOWDISPLAYAFFINITY_T) GetProcAddress(user32, "SetWindowDisplayAffinity");
+    if (0 == set_affinity) {
+        return false;
+    }
+    /* WDA_NONE, WDA_EXCLUDEFROMCAPTURE and WDA_MONITOR */
+    if (!protect) {
+        return set_affinity(_sapp_win32_hwnd, 0x0) != 0;
+    }
+    return set_affinity(_sapp_win32_hwnd, 0x11) || set_affinity(_sapp_win32_hwnd, 0x1);
+}
+
+_SOKOL_PRIVATE bool _sapp_win32_keep_awake(bool awake) {
+    EXECUTION_STATE state = ES_CONTINUOUS;
+    if (awake) {
+        state |= ES_DISPLAY_REQUIRED | ES_SYSTEM_REQUIRED;
+    }
+    return SetThreadExecutionState(state) != 0;
+}
+
+/* Ctrl+Alt+Del is out of reach of any hook, the others would reach the shell first */
+_SOKOL_PRIVATE LRESULT CALLBACK _sapp_win32_keyboard_hook_proc(int code, WPARAM wParam, LPARAM lParam) {
+    if ((code == HC_ACTION) && (GetForegroundWindow() == _sapp_win32_hwnd)) {
+        const KBDLLHOOKSTRUCT* key = (const KBDLLHOOKSTRUCT*) lParam;
+        const bool alt = 0 != (key->flags & LLKHF_ALTDOWN);
+        const bool ctrl = 0 != (GetAsyncKeyState(VK_CONTROL) & 0x8000);
+        switch (key->vkCode) {
+            case VK_LWIN:
+            case VK_RWIN:
+            case VK_APPS:
+                return 1;
+            case VK_TAB:
+            case VK_F4:
+                if (alt) {
+                    return 1;
+                }
+                break;
+            case VK_ESCAPE:
+                if (alt || ctrl) {
+                    return 1;
+                }
+                break;
+            default:
+                break;
+        }
+    }
+    return CallNextHookEx(_sapp_win32_keyboard_hook, code, wParam, lParam);
+}
+
+_SOKOL_PRIVATE bool _sapp_win32_capture_shortcuts(bool capture) {
+    if (capture == (0 != _sapp_win32_keyboard_hook)) {
+        return true;
+    }
+    if (capture) {
+        _sapp_win32_keyboard_hook = SetWindowsHookExW(WH_KEYBOARD_LL, _sapp_win32_keyboard_hook_proc, GetModuleHandleW(NULL), 0);
+        return 0 != _sapp_win32_keyboard_hook;
+    }
+    UnhookWindowsHookEx(_sapp_win32_keyboard_hook);
+    _sapp_win32_keyboard_hook = 0;
+    return true;
+}
+
+_SOKOL_PRIVATE void _sapp_win32_request_user_attention(void) {
+    FLASHWINFO info;
+    memset(&info, 0, sizeof(info));
+    info.cbSize = sizeof(info);
+    info.hwnd = _sapp_win32_hwnd;
+    info.dwFlags = FLASHW_TRAY | FLASHW_TIMERNOFG;
+    FlashWindowEx(&info);
+}
+
+_SOKOL_PRIVATE void _sapp_win32_set_taskbar_progress(float progress) {
+    if (0 == _sapp_win32_taskbar) {
+        /* S_FALSE if COM was already initialized on this thread, which is fine */
+        CoInitialize(NULL);
+        HRESULT hr = CoCreateInstance(&_sapp_CLSID_TaskbarList, NULL, CLSCTX_INPROC_SERVER,
+            &_sapp_IID_ITaskbarList3, (void**)&_sapp_win32_taskbar);
+        if (FAILED(hr) || (0 == _sapp_win32_taskbar)) {
+            _sapp_win32_taskbar = 0;
+            return;
+        }
+        _sapp_win32_taskbar->lpVtbl->HrInit(_sapp_win32_taskbar);
+    }
+    if (progress < 0.0f) {
+        _sapp_win32_taskbar->lpVtbl->SetProgressState(_sapp_win32_taskbar, _sapp_win32_hwnd, TBPF_NOPROGRESS);
+        return;
+    }
+    if (progress > 1.0f) {
+        progress = 1.0f;
+    }
+    _sapp_win32_taskbar->lpVtbl->SetProgressState(_sapp_win32_taskbar, _sapp_win32_hwnd, TBPF_NORMAL);
+    _sapp_win32_taskbar->lpVtbl->SetProgressValue(_sapp_win32_taskbar, _sapp_win32_hwnd, (ULONGLONG)(progress * 1000.0f), 1000);
+}
+
+_SOKOL_PRIVATE const char* _sapp_win32_system_locale(void) {
+    WCHAR name[LOCALE_NAME_MAX_LENGTH];
+    if ((0 == GetUserDefaultLocaleName(name, LOCALE_NAME_MAX_LENGTH)) ||
+        (0 == WideCharToMultiByte(CP_UTF8, 0, name, -1, _sapp_win32_locale, sizeof(_sapp_win32_locale), NULL, NULL)))
+    {
+        strcpy(_sapp_win32_locale, "en-US");
+    }
+    return _sapp_win32_locale;
+}
+
+_SOKOL_PRIVATE bool _sapp_win32_prefers_dark_theme(void) {
+    DWORD light_theme = 1;
+    DWORD size = sizeof(light_theme);
+    if (ERROR_SUCCESS != RegGetValueW(HKEY_CURRENT_USER,
+        L"Software\\Microsoft\\Windows\\CurrentVersion\\Themes\\Personalize",
+        L"AppsUseLightTheme", RRF_RT_REG_DWORD, NULL, &light_theme, &size))
+    {
+        /* no such setting before windows 10 */
+        return false;
+    }
+    return 0 == light_theme;
+}
+
+_SOKOL_PRIVATE bool _sapp_win32_accent_color(float* r, float* g, float* b) {
+    /* 0xAABBGGRR, the color of title bars and the start menu when enabled */
+    DWORD color = 0;
+    DWORD size = sizeof(color);
+    if (ERROR_SUCCESS != RegGetValueW(HKEY_CURRENT_USER,
+        L"Software\\Microsoft\\Windows\\DWM",
+        L"AccentColor", RRF_RT_REG_DWORD, NULL, &color, &size))
+    {
+        return false;
+    }
+    *r = (float)(color & 0xFF) / 255.0f;
+    *g = (float)((color >> 8) & 0xFF) / 255.0f;
+    *b = (float)((color >> 16) & 0xFF) / 255.0f;
+    return true;
+}
+
+_SOKOL_PRIVATE bool _sapp_win32_high_contrast(void) {
+    HIGHCONTRASTW high_contrast;
+    memset(&high_contrast, 0, sizeof(high_contrast));
+    high_contrast.cbSize = sizeof(high_contrast);
+    if (!SystemParametersInfoW(SPI_GETHIGHCONTRAST, sizeof(high_contrast), &high_contrast, 0)) {
+        return false;
+    }
+    return 0 != (high_contrast.dwFlags & HCF_HIGHCONTRASTON);
+}
+
+_SOKOL_PRIVATE bool _sapp_win32_battery_status(float* level, bool* charging, bool* power_saving) {
+    SYSTEM_POWER_STATUS status;
+    if (!GetSystemPowerStatus(&status)) {
+        return false;
+    }
+    /* 128: no system battery, 255: unknown status */
+    if ((status.BatteryFlag & 128) || (status.BatteryFlag == 255) || (status.BatteryLifePercent == 255)) {
+        return false;
+    }
+    *level = status.BatteryLifePercent / 100.0f;
+    *charging = status.ACLineStatus == 1;
+    /* SystemStatusFlag is "Reserved1" in older SDK headers, 1 means battery saver is on */
+    *power_saving = ((BYTE*)&status)[3] == 1;
+    return true;
+}
+
+/* ClipCursor is system wide: the cursor is only confined while the window is active,
+   and the screen rect is updated when the window moves */
+_SOKOL_PRIVATE void _sapp_win32_apply_confinement(void) {
+    if ((0 == _sapp_win32_confinement) || (GetActiveWindow() != _sapp_win32_hwnd)) {
+        ClipCursor(NULL);
+        return;
+    }
+    RECT rect;
+    if (1 == _sapp_win32_confinement) {
+        GetClientRect(_sapp_win32_hwnd, &rect);
+    }
+    else {
+        rect = _sapp_win32_confine_rect;
+    }
+    POINT corners[2] = { { rect.left, rect.top }, { rect.right, rect.bottom } };
+    ClientToScreen(_sapp_win32_hwnd, &corners[0]);
+    ClientToScreen(_sapp_win32_hwnd, &corners[1]);
+    const RECT screen_rect = { corners[0].x, corners[0].y, corners[1].x, corners[1].y };
+    ClipCursor(&screen_rect);
+}
+
+_SOKOL_PRIVATE void _sapp_win32_confine_cursor(int confinement, float x, float y, float width, float height) {
+    _sapp_win32_confinement = confinement;
+    /* mouse event coordinates to client pixels */
+    _sapp_win32_confine_rect.left = (LONG)(x / _sapp_win32_mouse_scale);
+    _sapp_win32_confine_rect.top = (LONG)(y / _sapp_win32_mouse_scale);
+    _sapp_win32_confine_rect.right = (LONG)((x + width) / _sapp_win32_mouse_scale);
+    _sapp_win32_confine_rect.bottom = (LONG)((y + height) / _sapp_win32_mouse_scale);
+    _sapp_win32_apply_confinement();
+}
+
+/* key codes are scan codes, i.e. positions, ask the layout what the position types */
+_SOKOL_PRIVATE uint32_t _sapp_win32_key_char(sapp_keycode key) {
+    for (int scan_code = 0; scan_code < SAPP_MAX_KEYCODES; scan_code++) {
+        if (_sapp.keycodes[scan_code] == key) {
+            const UINT vk = MapVirtualKeyW((UINT)scan_code, MAPVK_VSC_TO_VK);
+            /* the top bit marks dead keys */
+            return MapVirtualKeyW(vk, MAPVK_VK_TO_CHAR) & 0x7FFFFFFF;
+        }
+    }
+    return 0;
+}
+
+/* CF_DIB is a BITMAPINFOHEADER followed by BGR(A) rows from bottom to top */
+_SOKOL_PRIVATE bool _sapp_win32_set_clipboard_image(int width, int height, const uint8_t* pixels) {
+    const SIZE_T row_size = (SIZE_T)width * 4;
+    const SIZE_T pixels_size = row_size * (SIZE_T)height;
+    HGLOBAL object = GlobalAlloc(GMEM_MOVEABLE, sizeof(BITMAPINFOHEADER) + pixels_size);
+    if (!object) {
+        return false;
+    }
+    BITMAPINFOHEADER* header = (BITMAPINFOHEADER*) GlobalLock(object);
+    memset(header, 0, sizeof(BITMAPINFOHEADER));
+    header->biSize = sizeof(BITMAPINFOHEADER);
+    header->biWidth = width;
+    header->biHeight = height;
+    header->biPlanes = 1;
+    header->biBitCount = 32;
+    header->biCompression = BI_RGB;
+    header->biSizeImage = (DWORD)pixels_size;
+    uint8_t* dst = (uint8_t*)(header + 1);
+    for (int y = 0; y < height; y++) {
+        const uint8_t* src = pixels + (SIZE_T)(height - 1 - y) * row_size;
+        for (int x = 0; x < width; x++, src += 4, dst += 4) {
+            dst[0] = src[2];
+            dst[1] = src[1];
+            dst[2] = src[0];
+            dst[3] = src[3];
+        }
+    }
+    GlobalUnlock(object);
+
+    if (!OpenClipboard(_sapp_win32_hwnd)) {
+        GlobalFree(object);
+        return false;
+    }
+    EmptyClipboard();
+    /* the clipboard owns the memory once SetClipboardData succeeds */
+    const bool result = SetClipboardData(CF_DIB, object) != NULL;
+    CloseClipboard();
+    if (!result) {
+        GlobalFree(object);
+    }
+    return result;
+}
+
+_SOKOL_PRIVATE int _sapp_win32_refresh_rate(void) {
+    MONITORINFOEXW info;
+    memset(&info, 0, sizeof(info));
+    info.cbSize = sizeof(info);
+    if (!GetMonitorInfoW(MonitorFromWindow(_sapp_win32_hwnd, MONITOR_DEFAULTTONEAREST), (MONITORINFO*)&info)) {
+        return 0;
+    }
+    DEVMODEW dm;
+    memset(&dm, 0, sizeof(dm));
+    dm.dmSize = sizeof(dm);
+    if (!EnumDisplaySettingsW(info.szDevice, ENUM_CURRENT_SETTINGS, &dm)) {
+        return 0;
+    }
+    /* 0 and 1 mean "default hardware rate" */
+    return dm.dmDisplayFrequency > 1 ? (int)dm.dmDisplayFrequency : 0;
+}
+
+_SOKOL_PRIVATE bool _sapp_win32_monitor_info(MONITORINFOEXW* info) {
+    memset(info, 0, sizeof(*info));
+    info->cbSize = sizeof(*info);
+    return GetMonitorInfoW(MonitorFromWindow(_sapp_win32_hwnd, MONITOR_DEFAULTTONEAREST), (MONITORINFO*)info);
+}
+
+/* the index-th 32 bits per pixel mode of the window's monitor */
+_SOKOL_PRIVATE bool _sapp_win32_display_mode(int index, DEVMODEW* dm) {
+    MONITORINFOEXW info;
+    if (!_sapp_win32_monitor_info(&info)) {
+        return false;
+    }
+    for (DWORD i = 0; ; i++) {
+        memset(dm, 0, sizeof(*dm));
+        dm->dmSize = sizeof(*dm);
+        if (!EnumDisplaySettingsW(info.szDevice, i, dm)) {
+            return false;
+        }
+        if ((dm->dmBitsPerPel == 32) && (index-- == 0)) {
+            return true;
+        }
+    }
+}
+
+_SOKOL_PRIVATE int _sapp_win32_display_mode_count(void) {
+    DEVMODEW dm;
+    int count = 0;
+    while (_sapp_win32_display_mode(count, &dm)) {
+        count++;
+    }
+    return count;
+}
+
+/* CDS_FULLSCREEN makes the change temporary: Windows puts the desktop mode
+   back by itself when the process exits, even when it crashes */
+_SOKOL_PRIVATE bool _sapp_win32_apply_exclusive_mode(void) {
+    if (ChangeDisplaySettingsExW(_sapp_win32_exclusive_device, &_sapp_win32_exclusive_mode, NULL, CDS_FULLSCREEN, NULL) != DISP_CHANGE_SUCCESSFUL) {
+        return false;
+    }
+    MONITORINFOEXW info;
+    if (_sapp_win32_monitor_info(&info)) {
+        SetWindowPos(_sapp_win32_hwnd, HWND_TOPMOST, info.rcMonitor.left, info.rcMonitor.top,
+            (int)_sapp_win32_exclusive_mode.dmPelsWidth, (int)_sapp_win32_exclusive_mode.dmPelsHeight,
+            SWP_FRAMECHANGED | SWP_SHOWWINDOW);
+    }
+    return true;
+}
+
+_SOKOL_PRIVATE void _sapp_win32_restore_display_mode(void) {
+    ChangeDisplaySettingsExW(_sapp_win32_exclusive_device, NULL, NULL, 0, NULL);
+}
+
+_SOKOL_PRIVATE bool _sapp_win32_set_exclusive_fullscreen(int width, int height, int refresh_rate) {
+    if (width <= 0) {
+        if (_sapp_win32_exclusive) {
+            _sapp_win32_exclusive = false;
+            _sapp_win32_restore_display_mode();
+            const RECT rect = _sapp_win32_windowed_rect;
+            SetWindowLongW(_sapp_win32_hwnd, GWL_STYLE, (LONG)_sapp_win32_windowed_style);
+            SetWindowPos(_sapp_win32_hwnd, HWND_NOTOPMOST, rect.left, rect.top,
+                rect.right - rect.left, rect.bottom - rect.top,
+                SWP_FRAMECHANGED | SWP_SHOWWINDOW);
+        }
+        return true;
+    }
+
+    DEVMODEW dm, found;
+    memset(&found, 0, sizeof(found));
+    bool match = false;
+    for (int i = 0; _sapp_win32_display_mode(i, &dm); i++) {
+        if (((int)dm.dmPelsWidth == width) && ((int)dm.dmPelsHeight == height) &&
+            ((refresh_rate == 0) ? (!match || (dm.dmDisplayFrequency > found.dmDisplayFrequency)) : ((int)dm.dmDisplayFrequency == refresh_rate)))
+        {
+            found = dm;
+            match = true;
+        }
+    }
+    if (!match) {
+        return false;
+    }
+    found.dmFields = DM_PELSWIDTH | DM_PELSHEIGHT | DM_BITSPERPEL | DM_DISPLAYFREQUENCY;
+
+    const DEVMODEW previous = _sapp_win32_exclusive_mode;
+    if (!_sapp_win32_exclusive) {
+        MONITORINFOEXW info;
+        if (!_sapp_win32_monitor_info(&info)) {
+            return false;
+        }
+        memcpy(_sapp_win32_exclusive_device, info.szDevice, sizeof(_sapp_win32_exclusive_device));
+    }
+    _sapp_win32_exclusive_mode = found;
+    if (!_sapp_win32_apply_exclusive_mode()) {
+        _sapp_win32_exclusive_mode = previous;
+        return false;
+    }
+    if (!_sapp_win32_exclusive) {
+        _sapp_win32_exclusive = true;
+        _sapp_win32_windowed_style = (DWORD)GetWindowLongW(_sapp_win32_hwnd, GWL_STYLE);
+        GetWindowRect(_sapp_win32_hwnd, &_sapp_win32_windowed_rect);
+        SetWindowLongW(_sapp_win32_hwnd, GWL_STYLE, WS_POPUP | WS_VISIBLE | WS_CLIPSIBLINGS | WS_CLIPCHILDREN);
+        /* apply the new style, the window was placed while it still had its frame */
+        _sapp_win32_apply_exclusive_mode();
+    }
+    return true;
+}
+
+_SOKOL_PRIVATE double _sapp_win32_timestamp(void) {
+    LARGE_INTEGER counter, frequency;
+    QueryPerformanceCounter(&counter);
+    QueryPerformanceFrequency(&frequency);
+    return (double)counter.QuadPart / (double)frequency.QuadPart;
+}
+
+/* high resolution waitable timers, from windows 10 1803 on, wake up within half a millisecond;
+   Sleep() rounds up to the scheduler tick, 15.6ms unless timeBeginPeriod asks for 1ms,
+   which is only done for the duration of the sleep as it costs power system wide */
+_SOKOL_PRIVATE void _sapp_win32_sleep(double seconds) {
+    if (!_sapp_win32_sleep_timer_checked) {
+        _sapp_win32_sleep_timer_checked = true;
+        _sapp_win32_sleep_timer = CreateWaitableTimerExW(NULL, NULL, CREATE_WAITABLE_TIMER_HIGH_RESOLUTION, TIMER_ALL_ACCESS);
+        if (0 == _sapp_win32_sleep_timer) {
+            HINSTANCE winmm = LoadLibraryW(L"winmm.dll");
+            if (winmm) {
+                _sapp_win32_time_begin_period = (TIMEPERIOD_T) GetProcAddress(winmm, "timeBeginPeriod");
+                _sapp_win32_time_end_period = (TIMEPERIOD_T) GetProcAddress(winmm, "timeEndPeriod");
+            }
+        }
+    }
+    if (seconds <= 0.0) {
+        return;
+    }
+    if (_sapp_win32_sleep_timer) {
+        LARGE_INTEGER due;
+        /* negative for a relative time, in 100ns units */
+        due.QuadPart = -(LONGLONG)(seconds * 1e7);
+        if (SetWaitableTimer(_sapp_win32_sleep_timer, &due, 0, NULL, NULL, FALSE)) {
+            WaitForSingleObject(_sapp_win32_sleep_timer, INFINITE);
+            return;
+        }
+    }
+    const bool period = _sapp_win32_time_begin_period && _sapp_win32_time_end_period;
+    if (period) {
+        _sapp_win32_time_begin_period(1);
+    }
+    Sleep((DWORD)(seconds * 1000.0));
+    if (period) {
+        _sapp_win32_time_end_period(1);
+    }
+}
+
+/* GetMessageTime() is the GetTickCount() of the message being processed, in milliseconds */
+_SOKOL_PRIVATE double _sapp_event_timestamp(void) {
+    double now = _sapp_win32_timestamp();
+    DWORD age = GetTickCount() - (DWORD)GetMessageTime();
+    return age < 1000 ? now - (double)age / 1000.0 : now;
+}
+
+/* only has an effect on tablets, and only on windows 8 and later */
+_SOKOL_PRIVATE void _sapp_win32_set_orientation_lock(int orientation) {
+    HINSTANCE user32 = GetModuleHandleW(L"user32.dll");
+    if (0 == user32) {
+        return;
+    }
+    SETDISPLAYAUTOROTATIONPREFERENCES_T set_preferences =
+        (SETDISPLAYAUTOROTATIONPREFERENCES_T) GetProcAddress(user32, "SetDisplayAutoRotationPreferences");
+    if (0 == set_preferences) {
+        return;
+    }
+    /* ORIENTATION_PREFERENCE_LANDSCAPE | ORIENTATION_PREFERENCE_LANDSCAPE_FLIPPED and the same for portrait */
+    DWORD preferences = 0;
+    if (orientation == 1) {
+        preferences = 0x1 | 0x4;
+    }
+    else if (orientation == 2) {
+        preferences = 0x2 | 0x8;
+    }
+    set_preferences(preferences);
+}
+
 _SOKOL_PRIVATE void _sapp_win32_init_keytable(void) {
     /* same as GLFW */
     _sapp.keycodes[0x00B] = SAPP_KEYCODE_0;
@@ -4280,8 +5018,27 @@ _SOKOL_PRIVATE void _sapp_win32_app_event(sapp_event_type type) {
     }
 }
 
+/* timer keeping frames going while the modal move/resize loop blocks the main loop */
+#define _SAPP_WIN32_SIZE_MOVE_TIMER (1)
+
+/* draws a frame from inside the window procedure, with a resize event first when needed */
+_SOKOL_PRIVATE void _sapp_win32_modal_frame(void) {
+    if (_sapp_win32_update_dimensions()) {
+        #if defined(SOKOL_D3D11)
+        _sapp_d3d11_resize_default_render_target();
+        #endif
+        _sapp_win32_app_event(SAPP_EVENTTYPE_RESIZED);
+    }
+    _sapp_frame();
+    #if defined(SOKOL_D3D11)
+        IDXGISwapChain_Present(_sapp_dxgi_swap_chain, _sapp.swap_interval, 0);
+    #endif
+    #if defined(SOKOL_GLCORE33)
+        _sapp_wgl_swap_buffers();
+    #endif
+}
+
 _SOKOL_PRIVATE LRESULT CALLBACK _sapp_win32_wndproc(HWND hWnd, UINT uMsg, WPARAM wParam, LPARAM lParam) {
-    /* FIXME: refresh rendering during resize with a WM_TIMER event */
     if (!_sapp_win32_in_create_window) {
         switch (uMsg) {
             case WM_CLOSE:
@@ -4317,7 +5074,71 @@ _SOKOL_PRIVATE LRESULT CALLBACK _sapp_win32_wndproc(HWND hWnd, UINT uMsg, WPARAM
                 break;
             case WM_ERASEBKGND:
                 return 1;
+            case WM_PAINT:
+                /* the main loop does not run while the window is moved or resized,
+                   draw right away so uncovered parts do not stay stale */
+                if (_sapp.valid) {
+                    _sapp_win32_app_event(SAPP_EVENTTYPE_EXPOSED);
+                    _sapp_win32_modal_frame();
+                }
+                break;
+            case WM_DPICHANGED:
+                if (_sapp_win32_dpi_aware && _sapp.desc.high_dpi) {
+                    _sapp_win32_window_scale = (float)HIWORD(wParam) / 96.0f;
+                    _sapp_win32_content_scale = _sapp_win32_window_scale;
+                    _sapp.dpi_scale = _sapp_win32_content_scale;
+                    /* the suggested rect keeps the window's logical size */
+                    const RECT* rect = (const RECT*)lParam;
+                    SetWindowPos(hWnd, NULL, rect->left, rect->top,
+                        rect->right - rect->left, rect->bottom - rect->top,
+                        SWP_NOZORDER | SWP_NOACTIVATE);
+                    _sapp_win32_app_event(SAPP_EVENTTYPE_DPI_CHANGED);
+                    if (_sapp_win32_update_dimensions()) {
+                        #if defined(SOKOL_D3D11)
+                        _sapp_d3d11_resize_default_render_target();
+                        #endif
+                        _sapp_win32_app_event(SAPP_EVENTTYPE_RESIZED);
+                    }
+                    return 0;
+                }
+                break;
+            case WM_ACTIVATEAPP:
+                /* alt-tab out of exclusive fullscreen: the desktop gets its mode back while away */
+                if (_sapp_win32_exclusive) {
+                    if (wParam) {
+                        _sapp_win32_apply_exclusive_mode();
+                    }
+                    else {
+                        _sapp_win32_restore_display_mode();
+                        ShowWindow(hWnd, SW_MINIMIZE);
+                    }
+                }
+                break;
+            case WM_ENTERSIZEMOVE:
+                _sapp_win32_in_size_move = true;
+                SetTimer(hWnd, _SAPP_WIN32_SIZE_MOVE_TIMER, USER_TIMER_MINIMUM, NULL);
+                break;
+            case WM_EXITSIZEMOVE:
+                _sapp_win32_in_size_move = false;
+                KillTimer(hWnd, _SAPP_WIN32_SIZE_MOVE_TIMER);
+                break;
+            case WM_TIMER:
+                if ((wParam == _SAPP_WIN32_SIZE_MOVE_TIMER) && _sapp_win32_in_size_move && _sapp.valid) {
+                    /* update and draw at the timer rate, with intermediate resize events */
+                    _sapp_win32_modal_frame();
+                    return 0;
+                }
+                break;
+            case WM_ACTIVATE:
+            case WM_MOVE:
+                if (_sapp_win32_confinement != 0) {
+                    _sapp_win32_apply_confinement();
+                }
+                break;
             case WM_SIZE:
+                if (_sapp_win32_confinement != 0) {
+                    _sapp_win32_apply_confinement();
+                }
                 {
                     const bool iconified = wParam == SIZE_MINIMIZED;
                     if (iconified != _sapp_win32_iconified) {
@@ -4331,6 +5152,27 @@ _SOKOL_PRIVATE LRESULT CALLBACK _sapp_win32_wndproc(HWND hWnd, UINT uMsg, WPARAM
                     }
                 }
                 break;
+            case WM_DISPLAYCHANGE:
+                {
+                    const bool portrait = HIWORD(lParam) > LOWORD(lParam);
+                    if (portrait != _sapp_win32_portrait) {
+                        _sapp_win32_portrait = portrait;
+                        _sapp_win32_app_event(SAPP_EVENTTYPE_ORIENTATION_CHANGED);
+                    }
+                }
+                break;
+            case WM_SETTINGCHANGE:
+                /* sent with "ImmersiveColorSet" when the user switches between light and dark mode
+                   or changes the accent color */
+                if (((0 != lParam) && (0 == lstrcmpW((LPCWSTR)lParam, L"ImmersiveColorSet"))) ||
+                    (SPI_SETHIGHCONTRAST == wParam))
+                {
+                    _sapp_win32_app_event(SAPP_EVENTTYPE_THEME_CHANGED);
+                }
+                break;
+            case WM_DWMCOLORIZATIONCOLORCHANGED:
+                _sapp_win32_app_event(SAPP_EVENTTYPE_THEME_CHANGED);
+                break;
             case WM_SETCURSOR:
                 if (_sapp.desc.user_cursor) {
                     if (LOWORD(lParam) == HTCLIENT) {
@@ -4467,8 +5309,9 @@ _SOKOL_PRIVATE void _sapp_win32_init_dpi(void) {
         _sapp_win32_getdpiformonitor = (GETDPIFORMONITOR_T) GetProcAddress(shcore, "GetDpiForMonitor");
     }
     if (_sapp_win32_setprocessdpiawareness) {
-        /* if the app didn't request HighDPI rendering, let Windows do the upscaling */
-        PROCESS_DPI_AWARENESS process_dpi_awareness = PROCESS_SYSTEM_DPI_AWARE;
+        /* if the app didn't request HighDPI rendering, let Windows do the upscaling,
+           otherwise get WM_DPICHANGED when the window moves to a monitor with another scale */
+        PROCESS_DPI_AWARENESS process_dpi_awareness = PROCESS_PER_MONITOR_DPI_AWARE;
         _sapp_win32_dpi_aware = true;
         if (!_sapp.desc.high_dpi) {
             process_dpi_awareness = PROCESS_DPI_UNAWARE;
@@ -4565,6 +5408,9 @@ _SOKOL_PRIVATE void _sapp_run(const sapp_desc* desc) {
             PostMessage(_sapp_win32_hwnd, WM_CLOSE, 0, 0);
         }
     }
+    if (_sapp_win32_exclusive) {
+        _sapp_win32_restore_display_mode();
+    }
     _sapp_call_cleanup();
 
     #if defined(SOKOL_D3D11)
@@ -6550,14 +7396,16 @@ _SOKOL_PRIVATE GLXFBConfig _sapp_glx_choosefbconfig() {
     desired.green_bits = 8;
     desired.blue_bits = 8;
     desired.alpha_bits = 8;
-    desired.depth_bits = 24;
-    desired.stencil_bits = 8;
+    desired.depth_bits = _sapp.desc.depth_bits;
+    desired.stencil_bits = _sapp.desc.stencil_bits;
     desired.doublebuffer = true;
     desired.samples = _sapp.sample_count > 1 ? _sapp.sample_count : 0;
     closest = _sapp_gl_choose_fbconfig(&desired, usable_configs, usable_count);
     GLXFBConfig result = 0;
     if (closest) {
         result = (GLXFBConfig) closest->handle;
+        _sapp.depth_bits = closest->depth_bits;
+        _sapp.stencil_bits = closest->stencil_bits;
     }
     XFree(native_configs);
     SOKOL_FREE(usable_configs);
@@ -7406,6 +8254,319 @@ SOKOL_API_IMPL void sapp_html5_ask_leave_site(bool ask) {
     _sapp.html5_ask_leave_site = ask;
 }
 
+SOKOL_API_IMPL void sapp_set_window_opacity(float opacity) {
+    #if defined(_WIN32)
+    _sapp_win32_set_window_opacity(opacity);
+    #else
+    _SOKOL_UNUSED(opacity);
+    #endif
+}
+
+SOKOL_API_IMPL void sapp_set_input_passthrough(bool passthrough) {
+    #if defined(_WIN32)
+    _sapp_win32_set_input_passthrough(passthrough);
+    #else
+    _SOKOL_UNUSED(passthrough);
+    #endif
+}
+
+SOKOL_API_IMPL bool sapp_set_capture_protection(bool protect) {
+    #if defined(_WIN32)
+    return _sapp_win32_set_capture_protection(protect);
+    #else
+    _SOKOL_UNUSED(protect);
+    return false;
+    #endif
+}
+
+SOKOL_API_IMPL bool sapp_keep_awake(bool awake) {
+    #if defined(_WIN32)
+    return _sapp_win32_keep_awake(awake);
+    #else
+    _SOKOL_UNUSED(awake);
+    return false;
+    #endif
+}
+
+SOKOL_API_IMPL bool sapp_capture_shortcuts(bool capture) {
+    #if defined(_WIN32)
+    return _sapp_win32_capture_shortcuts(capture);
+    #else
+    _SOKOL_UNUSED(capture);
+    return false;
+    #endif
+}
+
+SOKOL_API_IMPL void sapp_request_user_attention(void) {
+    #if defined(_WIN32)
+    _sapp_win32_request_user_attention();
+    #endif
+}
+
+SOKOL_API_IMPL void sapp_set_taskbar_progress(float progress) {
+    #if defined(_WIN32)
+    _sapp_win32_set_taskbar_progress(progress);
+    #else
+    _SOKOL_UNUSED(progress);
+    #endif
+}
+
+SOKOL_API_IMPL void sapp_vibrate(int duration_ms) {
+    _SOKOL_UNUSED(duration_ms);
+}
+
+SOKOL_API_IMPL int sapp_double_click_time(void) {
+    #if defined(_WIN32)
+    return (int)GetDoubleClickTime();
+    #else
+    return 500;
+    #endif
+}
+
+SOKOL_API_IMPL uint32_t sapp_key_char(sapp_keycode key) {
+    #if defined(_WIN32)
+    return _sapp_win32_key_char(key);
+    #else
+    _SOKOL_UNUSED(key);
+    return 0;
+    #endif
+}
+
+SOKOL_API_IMPL bool sapp_set_clipboard_image(int width, int height, const uint8_t* pixels) {
+    #if defined(_WIN32)
+    return _sapp_win32_set_clipboard_image(width, height, pixels);
+    #else
+    _SOKOL_UNUSED(width);
+    _SOKOL_UNUSED(height);
+    _SOKOL_UNUSED(pixels);
+    return false;
+    #endif
+}
+
+SOKOL_API_IMPL const char* sapp_system_locale(void) {
+    #if defined(_WIN32)
+    return _sapp_win32_system_locale();
+    #else
+    return "en-US";
+    #endif
+}
+
+SOKOL_API_IMPL bool sapp_prefers_dark_theme(void) {
+    #if defined(_WIN32)
+    return _sapp_win32_prefers_dark_theme();
+    #else
+    return false;
+    #endif
+}
+
+SOKOL_API_IMPL bool sapp_accent_color(float* r, float* g, float* b) {
+    #if defined(_WIN32)
+    return _sapp_win32_accent_color(r, g, b);
+    #else
+    _SOKOL_UNUSED(r);
+    _SOKOL_UNUSED(g);
+    _SOKOL_UNUSED(b);
+    return false;
+    #endif
+}
+
+SOKOL_API_IMPL bool sapp_high_contrast(void) {
+    #if defined(_WIN32)
+    return _sapp_win32_high_contrast();
+    #else
+    return false;
+    #endif
+}
+
+SOKOL_API_IMPL void sapp_confine_cursor(int confinement, float x, float y, float width, float height) {
+    #if defined(_WIN32)
+    _sapp_win32_confine_cursor(confinement, x, y, width, height);
+    #else
+    _SOKOL_UNUSED(confinement);
+    _SOKOL_UNUSED(x);
+    _SOKOL_UNUSED(y);
+    _SOKOL_UNUSED(width);
+    _SOKOL_UNUSED(height);
+    #endif
+}
+
+SOKOL_API_IMPL void sapp_set_orientation_lock(int orientation) {
+    #if defined(_WIN32)
+    _sapp_win32_set_orientation_lock(orientation);
+    #else
+    _SOKOL_UNUSED(orientation);
+    #endif
+}
+
+SOKOL_API_IMPL void sapp_safe_area_insets(float* top, float* right, float* bottom, float* left) {
+    /* only mobile platforms have unsafe areas, and those are not supported yet */
+    *top = 0.0f;
+    *right = 0.0f;
+    *bottom = 0.0f;
+    *left = 0.0f;
+}
+
+SOKOL_API_IMPL int sapp_depth_bits(void) {
+    return _sapp.depth_bits;
+}
+
+SOKOL_API_IMPL int sapp_stencil_bits(void) {
+    return _sapp.stencil_bits;
+}
+
+#if !defined(_WIN32)
+#include <time.h>
+
+_SOKOL_PRIVATE double _sapp_event_timestamp(void) {
+    return sapp_timestamp();
+}
+#endif
+
+SOKOL_API_IMPL double sapp_timestamp(void) {
+    #if defined(_WIN32)
+    return _sapp_win32_timestamp();
+    #else
+    struct timespec ts;
+    clock_gettime(CLOCK_MONOTONIC, &ts);
+    return (double)ts.tv_sec + (double)ts.tv_nsec / 1e9;
+    #endif
+}
+
+SOKOL_API_IMPL void sapp_sleep(double seconds) {
+    #if defined(_WIN32)
+    _sapp_win32_sleep(seconds);
+    #else
+    if (seconds > 0.0) {
+        struct timespec ts;
+        ts.tv_sec = (time_t)seconds;
+        ts.tv_nsec = (long)((seconds - (double)ts.tv_sec) * 1e9);
+        nanosleep(&ts, NULL);
+    }
+    #endif
+}
+
+SOKOL_API_IMPL int sapp_refresh_rate(void) {
+    #if defined(_WIN32)
+    return _sapp_win32_refresh_rate();
+    #else
+    return 0;
+    #endif
+}
+
+SOKOL_API_IMPL int sapp_display_mode_count(void) {
+    #if defined(_WIN32)
+    return _sapp_win32_display_mode_count();
+    #else
+    return 0;
+    #endif
+}
+
+SOKOL_API_IMPL bool sapp_display_mode(int index, int* width, int* height, int* refresh_rate) {
+    #if defined(_WIN32)
+    DEVMODEW dm;
+    if (!_sapp_win32_display_mode(index, &dm)) {
+        return false;
+    }
+    *width = (int)dm.dmPelsWidth;
+    *height = (int)dm.dmPelsHeight;
+    *refresh_rate = dm.dmDisplayFrequency > 1 ? (int)dm.dmDisplayFrequency : 0;
+    return true;
+    #else
+    _SOKOL_UNUSED(index);
+    _SOKOL_UNUSED(width);
+    _SOKOL_UNUSED(height);
+    _SOKOL_UNUSED(refresh_rate);
+    return false;
+    #endif
+}
+
+SOKOL_API_IMPL bool sapp_set_exclusive_fullscreen(int width, int height, int refresh_rate) {
+    #if defined(_WIN32)
+    return _sapp_win32_set_exclusive_fullscreen(width, height, refresh_rate);
+    #else
+    _SOKOL_UNUSED(height);
+    _SOKOL_UNUSED(refresh_rate);
+    return width <= 0;
+    #endif
+}
+
+SOKOL_API_IMPL bool sapp_battery_status(float* level, bool* charging, bool* power_saving) {
+    #if defined(_WIN32)
+    return _sapp_win32_battery_status(level, charging, power_saving);
+    #else
+    _SOKOL_UNUSED(level);
+    _SOKOL_UNUSED(charging);
+    _SOKOL_UNUSED(power_saving);
+    return false;
+    #endif
+}
+
+SOKOL_API_IMPL void sapp_set_swap_interval(int interval) {
+    #if defined(_WIN32)
+    _sapp.swap_interval = interval;
+    if (_sapp_ext_swap_control) {
+        _sapp_SwapIntervalEXT(interval);
+    }
+    #else
+    _SOKOL_UNUSED(interval);
+    #endif
+}
+
+SOKOL_API_IMPL void sapp_poll_events(void) {
+    #if defined(_WIN32)
+    MSG msg;
+    /* only input, WM_QUIT is left to the main loop; WM_CHAR from TranslateMessage is in the key range */
+    while (PeekMessageW(&msg, NULL, WM_KEYFIRST, WM_KEYLAST, PM_REMOVE)) {
+        TranslateMessage(&msg);
+        DispatchMessage(&msg);
+    }
+    while (PeekMessageW(&msg, NULL, WM_MOUSEFIRST, WM_MOUSELAST, PM_REMOVE)) {
+        DispatchMessage(&msg);
+    }
+    #endif
+}
+
+SOKOL_API_IMPL int sapp_gl_context_handles(void** display, void** context, void** config, uint64_t* drawable, uint64_t* visual_id) {
+    *config = 0;
+    *drawable = 0;
+    *visual_id = 0;
+    #if defined(_WIN32)
+    *display = (void*) _sapp_win32_dc;
+    *context = (void*) _sapp_gl_ctx;
+    return 1;
+    #else
+    *display = 0;
+    *context = 0;
+    return 0;
+    #endif
+}
+
+SOKOL_API_IMPL int sapp_graphics_reset_status(void) {
+    #if defined(_WIN32)
+    if (_sapp_glGetGraphicsResetStatusARB) {
+        return (int) _sapp_glGetGraphicsResetStatusARB();
+    }
+    #endif
+    return 0;
+}
+
+SOKOL_API_IMPL void sapp_recreate_context(void) {
+    #if defined(_WIN32)
+    _sapp_wglMakeCurrent(_sapp_win32_dc, 0);
+    _sapp_wgl_destroy_context();
+    _sapp_wgl_create_context();
+    _sapp_win32_gl_loadfuncs();
+    #endif
+}
+
+/* WGL has no present timestamps, DXGI frame statistics need a DXGI swapchain */
+SOKOL_API_IMPL int sapp_present_stats(uint64_t* present_count, uint64_t* refresh_count, double* time) {
+    _SOKOL_UNUSED(present_count);
+    _SOKOL_UNUSED(refresh_count);
+    _SOKOL_UNUSED(time);
+    return 0;
+}
+
 #undef _sapp_def
 
 #ifdef _MSC_VER
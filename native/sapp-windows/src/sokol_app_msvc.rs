@@ -8138,6 +8138,19 @@ pub const GL_STENCIL_BUFFER_BIT: u32 = 1024;
 pub const GL_REPEAT: u32 = 10497;
 pub const GL_RGBA: u32 = 6408;
 pub const GL_TEXTURE_CUBE_MAP_POSITIVE_X: u32 = 34069;
+pub const GL_TEXTURE_CUBE_MAP_SEAMLESS: u32 = 34895;
+pub const GL_TEXTURE_MAX_LEVEL: u32 = 33085;
+pub const GL_SAMPLES_PASSED: u32 = 35092;
+pub const GL_PRIMITIVES_GENERATED: u32 = 35975;
+pub const GL_CLIPPING_INPUT_PRIMITIVES: u32 = 33526;
+pub const GL_CLIPPING_OUTPUT_PRIMITIVES: u32 = 33527;
+pub const GL_QUERY_RESULT: u32 = 34918;
+pub const GL_QUERY_RESULT_AVAILABLE: u32 = 34919;
+pub const GL_DEPTH_CLAMP: u32 = 34383;
+pub const GL_LOWER_LEFT: u32 = 36001;
+pub const GL_NEGATIVE_ONE_TO_ONE: u32 = 37726;
+pub const GL_ZERO_TO_ONE: u32 = 37727;
+pub const GL_MAX_VIEWS_OVR: u32 = 38449;
 pub const GL_DECR: u32 = 7683;
 pub const GL_FRAGMENT_SHADER: u32 = 35632;
 pub const GL_FLOAT: u32 = 5126;
@@ -8264,6 +8277,8 @@ pub const GL_RGB16_SNORM: u32 = 36762;
 pub const GL_RGBA16_SNORM: u32 = 36763;
 pub const GL_RGBA16: u32 = 32859;
 pub const GL_MAX_TEXTURE_SIZE: u32 = 3379;
+pub const GL_MAX_TEXTURE_IMAGE_UNITS: u32 = 34930;
+pub const GL_MAX_VERTEX_TEXTURE_IMAGE_UNITS: u32 = 35660;
 pub const GL_MAX_CUBE_MAP_TEXTURE_SIZE: u32 = 34076;
 pub const GL_MAX_3D_TEXTURE_SIZE: u32 = 32883;
 pub const GL_MAX_ARRAY_TEXTURE_LAYERS: u32 = 35071;
@@ -8389,7 +8404,14 @@ pub const sapp_event_type_SAPP_EVENTTYPE_SUSPENDED: sapp_event_type = 17;
 pub const sapp_event_type_SAPP_EVENTTYPE_RESUMED: sapp_event_type = 18;
 pub const sapp_event_type_SAPP_EVENTTYPE_UPDATE_CURSOR: sapp_event_type = 19;
 pub const sapp_event_type_SAPP_EVENTTYPE_QUIT_REQUESTED: sapp_event_type = 20;
-pub const sapp_event_type__SAPP_EVENTTYPE_NUM: sapp_event_type = 21;
+pub const sapp_event_type_SAPP_EVENTTYPE_THEME_CHANGED: sapp_event_type = 21;
+pub const sapp_event_type_SAPP_EVENTTYPE_SAFE_AREA_CHANGED: sapp_event_type = 22;
+pub const sapp_event_type_SAPP_EVENTTYPE_ORIENTATION_CHANGED: sapp_event_type = 23;
+pub const sapp_event_type_SAPP_EVENTTYPE_CONTEXT_RESTORED: sapp_event_type = 24;
+pub const sapp_event_type_SAPP_EVENTTYPE_EXPOSED: sapp_event_type = 25;
+pub const sapp_event_type_SAPP_EVENTTYPE_DPI_CHANGED: sapp_event_type = 26;
+pub const sapp_event_type_SAPP_EVENTTYPE_CONTEXT_LOST: sapp_event_type = 27;
+pub const sapp_event_type__SAPP_EVENTTYPE_NUM: sapp_event_type = 28;
 pub const sapp_event_type__SAPP_EVENTTYPE_FORCE_U32: sapp_event_type = 2147483647;
 pub type sapp_event_type = u32;
 pub const sapp_keycode_SAPP_KEYCODE_INVALID: sapp_keycode = 0;
@@ -8552,6 +8574,7 @@ pub struct sapp_event {
     pub window_height: ::std::os::raw::c_int,
     pub framebuffer_width: ::std::os::raw::c_int,
     pub framebuffer_height: ::std::os::raw::c_int,
+    pub timestamp: f64,
 }
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
@@ -8593,6 +8616,9 @@ pub struct sapp_desc {
     pub html5_ask_leave_site: bool,
     pub ios_keyboard_resizes_canvas: bool,
     pub gl_force_gles2: bool,
+    pub depth_bits: ::std::os::raw::c_int,
+    pub stencil_bits: ::std::os::raw::c_int,
+    pub robust_buffer_access: bool,
 }
 extern "C" {
     pub fn sokol_main(
@@ -8687,6 +8713,135 @@ extern "C" {
 extern "C" {
     pub fn sapp_android_get_native_activity() -> *const ::std::os::raw::c_void;
 }
+extern "C" {
+    pub fn sapp_set_window_opacity(opacity: f32);
+}
+extern "C" {
+    pub fn sapp_set_input_passthrough(passthrough: bool);
+}
+extern "C" {
+    pub fn sapp_request_user_attention();
+}
+extern "C" {
+    pub fn sapp_set_taskbar_progress(progress: f32);
+}
+extern "C" {
+    pub fn sapp_vibrate(duration_ms: ::std::os::raw::c_int);
+}
+extern "C" {
+    pub fn sapp_double_click_time() -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn sapp_key_char(key: sapp_keycode) -> u32;
+}
+extern "C" {
+    pub fn sapp_set_clipboard_image(
+        width: ::std::os::raw::c_int,
+        height: ::std::os::raw::c_int,
+        pixels: *const u8,
+    ) -> bool;
+}
+extern "C" {
+    pub fn sapp_system_locale() -> *const ::std::os::raw::c_char;
+}
+extern "C" {
+    pub fn sapp_prefers_dark_theme() -> bool;
+}
+extern "C" {
+    pub fn sapp_accent_color(r: *mut f32, g: *mut f32, b: *mut f32) -> bool;
+}
+extern "C" {
+    pub fn sapp_high_contrast() -> bool;
+}
+extern "C" {
+    pub fn sapp_refresh_rate() -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn sapp_depth_bits() -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn sapp_stencil_bits() -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn sapp_timestamp() -> f64;
+}
+extern "C" {
+    pub fn sapp_sleep(seconds: f64);
+}
+extern "C" {
+    pub fn sapp_display_mode_count() -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn sapp_display_mode(
+        index: ::std::os::raw::c_int,
+        width: *mut ::std::os::raw::c_int,
+        height: *mut ::std::os::raw::c_int,
+        refresh_rate: *mut ::std::os::raw::c_int,
+    ) -> bool;
+}
+extern "C" {
+    pub fn sapp_set_exclusive_fullscreen(
+        width: ::std::os::raw::c_int,
+        height: ::std::os::raw::c_int,
+        refresh_rate: ::std::os::raw::c_int,
+    ) -> bool;
+}
+extern "C" {
+    pub fn sapp_set_orientation_lock(orientation: ::std::os::raw::c_int);
+}
+extern "C" {
+    pub fn sapp_confine_cursor(
+        confinement: ::std::os::raw::c_int,
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+    );
+}
+extern "C" {
+    pub fn sapp_safe_area_insets(top: *mut f32, right: *mut f32, bottom: *mut f32, left: *mut f32);
+}
+extern "C" {
+    pub fn sapp_battery_status(level: *mut f32, charging: *mut bool, power_saving: *mut bool)
+        -> bool;
+}
+extern "C" {
+    pub fn sapp_set_swap_interval(interval: ::std::os::raw::c_int);
+}
+extern "C" {
+    pub fn sapp_poll_events();
+}
+extern "C" {
+    pub fn sapp_gl_context_handles(
+        display: *mut *mut ::std::os::raw::c_void,
+        context: *mut *mut ::std::os::raw::c_void,
+        config: *mut *mut ::std::os::raw::c_void,
+        drawable: *mut u64,
+        visual_id: *mut u64,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn sapp_graphics_reset_status() -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn sapp_recreate_context();
+}
+extern "C" {
+    pub fn sapp_set_capture_protection(protect: bool) -> bool;
+}
+extern "C" {
+    pub fn sapp_keep_awake(awake: bool) -> bool;
+}
+extern "C" {
+    pub fn sapp_capture_shortcuts(capture: bool) -> bool;
+}
+extern "C" {
+    pub fn sapp_present_stats(
+        present_count: *mut u64,
+        refresh_count: *mut u64,
+        time: *mut f64,
+    ) -> ::std::os::raw::c_int;
+}
 extern "C" {
     pub fn _memccpy(
         _Dst: *mut ::std::os::raw::c_void,
@@ -41815,6 +41970,9 @@ pub type PFN_glGetStringi =
 extern "C" {
     pub static mut _sapp_glGetStringi: PFN_glGetStringi;
 }
+extern "C" {
+    pub fn glGetStringi(name: GLenum, index: GLuint) -> *const GLubyte;
+}
 pub type PFN_glClearBufferfi = ::std::option::Option<
     unsafe extern "C" fn(buffer: GLenum, drawbuffer: GLint, depth: GLfloat, stencil: GLint),
 >;
@@ -41951,6 +42109,52 @@ pub type PFN_glDeleteProgram = ::std::option::Option<unsafe extern "C" fn(progra
 extern "C" {
     pub static mut _sapp_glDeleteProgram: PFN_glDeleteProgram;
 }
+extern "C" {
+    pub fn glDeleteProgram(program: GLuint);
+}
+pub type PFN_glBindBufferRange = ::std::option::Option<
+    unsafe extern "C" fn(
+        target: GLenum,
+        index: GLuint,
+        buffer: GLuint,
+        offset: GLintptr,
+        size: GLsizeiptr,
+    ),
+>;
+extern "C" {
+    pub static mut _sapp_glBindBufferRange: PFN_glBindBufferRange;
+}
+extern "C" {
+    pub fn glBindBufferRange(
+        target: GLenum,
+        index: GLuint,
+        buffer: GLuint,
+        offset: GLintptr,
+        size: GLsizeiptr,
+    );
+}
+pub type PFN_glGetUniformBlockIndex = ::std::option::Option<
+    unsafe extern "C" fn(program: GLuint, uniformBlockName: *const GLchar) -> GLuint,
+>;
+extern "C" {
+    pub static mut _sapp_glGetUniformBlockIndex: PFN_glGetUniformBlockIndex;
+}
+extern "C" {
+    pub fn glGetUniformBlockIndex(program: GLuint, uniformBlockName: *const GLchar) -> GLuint;
+}
+pub type PFN_glUniformBlockBinding = ::std::option::Option<
+    unsafe extern "C" fn(program: GLuint, uniformBlockIndex: GLuint, uniformBlockBinding: GLuint),
+>;
+extern "C" {
+    pub static mut _sapp_glUniformBlockBinding: PFN_glUniformBlockBinding;
+}
+extern "C" {
+    pub fn glUniformBlockBinding(
+        program: GLuint,
+        uniformBlockIndex: GLuint,
+        uniformBlockBinding: GLuint,
+    );
+}
 pub type PFN_glCompileShader = ::std::option::Option<unsafe extern "C" fn(shader: GLuint)>;
 extern "C" {
     pub static mut _sapp_glCompileShader: PFN_glCompileShader;
@@ -42053,6 +42257,18 @@ pub type PFN_glCompressedTexImage2D = ::std::option::Option<
 extern "C" {
     pub static mut _sapp_glCompressedTexImage2D: PFN_glCompressedTexImage2D;
 }
+extern "C" {
+    pub fn glCompressedTexImage2D(
+        target: GLenum,
+        level: GLint,
+        internalformat: GLenum,
+        width: GLsizei,
+        height: GLsizei,
+        border: GLint,
+        imageSize: GLsizei,
+        data: *const ::std::os::raw::c_void,
+    );
+}
 pub type PFN_glCompressedTexImage3D = ::std::option::Option<
     unsafe extern "C" fn(
         target: GLenum,
@@ -42132,6 +42348,9 @@ pub type PFN_glPolygonOffset =
 extern "C" {
     pub static mut _sapp_glPolygonOffset: PFN_glPolygonOffset;
 }
+extern "C" {
+    pub fn glPolygonOffset(factor: GLfloat, units: GLfloat);
+}
 pub type PFN_glDrawElements = ::std::option::Option<
     unsafe extern "C" fn(
         mode: GLenum,
@@ -42156,6 +42375,9 @@ pub type PFN_glDeleteFramebuffers =
 extern "C" {
     pub static mut _sapp_glDeleteFramebuffers: PFN_glDeleteFramebuffers;
 }
+extern "C" {
+    pub fn glDeleteFramebuffers(n: GLsizei, framebuffers: *const GLuint);
+}
 pub type PFN_glBlendEquationSeparate =
     ::std::option::Option<unsafe extern "C" fn(modeRGB: GLenum, modeAlpha: GLenum)>;
 extern "C" {
@@ -42169,6 +42391,34 @@ pub type PFN_glDeleteTextures =
 extern "C" {
     pub static mut _sapp_glDeleteTextures: PFN_glDeleteTextures;
 }
+extern "C" {
+    pub fn glDeleteTextures(n: GLsizei, textures: *const GLuint);
+}
+pub type PFN_glReadPixels = ::std::option::Option<
+    unsafe extern "C" fn(
+        x: GLint,
+        y: GLint,
+        width: GLsizei,
+        height: GLsizei,
+        format: GLenum,
+        type_: GLenum,
+        pixels: *mut ::std::os::raw::c_void,
+    ),
+>;
+extern "C" {
+    pub static mut _sapp_glReadPixels: PFN_glReadPixels;
+}
+extern "C" {
+    pub fn glReadPixels(
+        x: GLint,
+        y: GLint,
+        width: GLsizei,
+        height: GLsizei,
+        format: GLenum,
+        type_: GLenum,
+        pixels: *mut ::std::os::raw::c_void,
+    );
+}
 pub type PFN_glGetProgramiv =
     ::std::option::Option<unsafe extern "C" fn(program: GLuint, pname: GLenum, params: *mut GLint)>;
 extern "C" {
@@ -42202,6 +42452,20 @@ pub type PFN_glTexImage3D = ::std::option::Option<
 extern "C" {
     pub static mut _sapp_glTexImage3D: PFN_glTexImage3D;
 }
+extern "C" {
+    pub fn glTexImage3D(
+        target: GLenum,
+        level: GLint,
+        internalformat: GLint,
+        width: GLsizei,
+        height: GLsizei,
+        depth: GLsizei,
+        border: GLint,
+        format: GLenum,
+        type_: GLenum,
+        pixels: *const ::std::os::raw::c_void,
+    );
+}
 pub type PFN_glCreateShader = ::std::option::Option<unsafe extern "C" fn(type_: GLenum) -> GLuint>;
 extern "C" {
     pub static mut _sapp_glCreateShader: PFN_glCreateShader;
@@ -42225,6 +42489,19 @@ pub type PFN_glTexSubImage2D = ::std::option::Option<
 extern "C" {
     pub static mut _sapp_glTexSubImage2D: PFN_glTexSubImage2D;
 }
+extern "C" {
+    pub fn glTexSubImage2D(
+        target: GLenum,
+        level: GLint,
+        xoffset: GLint,
+        yoffset: GLint,
+        width: GLsizei,
+        height: GLsizei,
+        format: GLenum,
+        type_: GLenum,
+        pixels: *const ::std::os::raw::c_void,
+    );
+}
 pub type PFN_glClearDepth = ::std::option::Option<unsafe extern "C" fn(depth: GLdouble)>;
 extern "C" {
     pub static mut _sapp_glClearDepth: PFN_glClearDepth;
@@ -42353,6 +42630,9 @@ pub type PFN_glColorMask = ::std::option::Option<
 extern "C" {
     pub static mut _sapp_glColorMask: PFN_glColorMask;
 }
+extern "C" {
+    pub fn glColorMask(red: GLboolean, green: GLboolean, blue: GLboolean, alpha: GLboolean);
+}
 pub type PFN_glBindBuffer =
     ::std::option::Option<unsafe extern "C" fn(target: GLenum, buffer: GLuint)>;
 extern "C" {
@@ -42460,6 +42740,71 @@ extern "C" {
 extern "C" {
     pub fn glGetIntegerv(pname: GLenum, data: *mut GLint);
 }
+pub type PFN_glGenQueries = ::std::option::Option<unsafe extern "C" fn(n: GLsizei, ids: *mut GLuint)>;
+extern "C" {
+    pub static mut _sapp_glGenQueries: PFN_glGenQueries;
+}
+extern "C" {
+    pub fn glGenQueries(n: GLsizei, ids: *mut GLuint);
+}
+pub type PFN_glDeleteQueries = ::std::option::Option<unsafe extern "C" fn(n: GLsizei, ids: *const GLuint)>;
+extern "C" {
+    pub static mut _sapp_glDeleteQueries: PFN_glDeleteQueries;
+}
+extern "C" {
+    pub fn glDeleteQueries(n: GLsizei, ids: *const GLuint);
+}
+pub type PFN_glBeginQuery = ::std::option::Option<unsafe extern "C" fn(target: GLenum, id: GLuint)>;
+extern "C" {
+    pub static mut _sapp_glBeginQuery: PFN_glBeginQuery;
+}
+extern "C" {
+    pub fn glBeginQuery(target: GLenum, id: GLuint);
+}
+pub type PFN_glEndQuery = ::std::option::Option<unsafe extern "C" fn(target: GLenum)>;
+extern "C" {
+    pub static mut _sapp_glEndQuery: PFN_glEndQuery;
+}
+extern "C" {
+    pub fn glEndQuery(target: GLenum);
+}
+pub type PFN_glClipControl = ::std::option::Option<unsafe extern "C" fn(origin: GLenum, depth: GLenum)>;
+extern "C" {
+    pub static mut _sapp_glClipControl: PFN_glClipControl;
+}
+extern "C" {
+    pub fn glClipControl(origin: GLenum, depth: GLenum);
+}
+pub type PFN_glGetQueryObjectuiv = ::std::option::Option<unsafe extern "C" fn(id: GLuint, pname: GLenum, params: *mut GLuint)>;
+extern "C" {
+    pub static mut _sapp_glGetQueryObjectuiv: PFN_glGetQueryObjectuiv;
+}
+extern "C" {
+    pub fn glGetQueryObjectuiv(id: GLuint, pname: GLenum, params: *mut GLuint);
+}
+pub type PFN_glFramebufferTextureMultiviewOVR = ::std::option::Option<
+    unsafe extern "C" fn(
+        target: GLenum,
+        attachment: GLenum,
+        texture: GLuint,
+        level: GLint,
+        baseViewIndex: GLint,
+        numViews: GLsizei,
+    ),
+>;
+extern "C" {
+    pub static mut _sapp_glFramebufferTextureMultiviewOVR: PFN_glFramebufferTextureMultiviewOVR;
+}
+extern "C" {
+    pub fn glFramebufferTextureMultiviewOVR(
+        target: GLenum,
+        attachment: GLenum,
+        texture: GLuint,
+        level: GLint,
+        baseViewIndex: GLint,
+        numViews: GLsizei,
+    );
+}
 pub type PFN_glEnable = ::std::option::Option<unsafe extern "C" fn(cap: GLenum)>;
 extern "C" {
     pub static mut _sapp_glEnable: PFN_glEnable;
@@ -42500,6 +42845,9 @@ pub type PFN_glGetError = ::std::option::Option<unsafe extern "C" fn() -> GLenum
 extern "C" {
     pub static mut _sapp_glGetError: PFN_glGetError;
 }
+extern "C" {
+    pub fn glGetError() -> GLenum;
+}
 pub type PFN_glClearColor = ::std::option::Option<
     unsafe extern "C" fn(red: GLfloat, green: GLfloat, blue: GLfloat, alpha: GLfloat),
 >;
@@ -0,0 +1,111 @@
+use crate::{Context, Texture};
+
+/// Empty pixels kept on the right and bottom of every rect, so linear filtering
+/// at rect borders does not pick up the neighbours.
+const PADDING: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AtlasRect {
+    /// Position and size in pixels
+    pub x: u32,
+    pub y: u32,
+    pub w: u32,
+    pub h: u32,
+    /// Texture coordinates of the top-left corner
+    pub uv_min: (f32, f32),
+    /// Texture coordinates of the bottom-right corner
+    pub uv_max: (f32, f32),
+}
+
+#[derive(Debug)]
+struct Shelf {
+    y: u32,
+    height: u32,
+    /// Where the next rect on this shelf goes
+    x: u32,
+}
+
+/// RGBA8 texture with rectangles packed into it at runtime, for glyph caches
+/// and dynamically generated sprites.
+/// Rects are packed on horizontal shelves: fast and good enough when
+/// most rects have similar heights, like glyphs of one font.
+pub struct Atlas {
+    texture: Texture,
+    size: u32,
+    shelves: Vec<Shelf>,
+}
+
+impl Atlas {
+    /// Square atlas `size` pixels wide, initially transparent.
+    pub fn new(_ctx: &mut Context, size: u16) -> Atlas {
+        let pixels = vec![0; size as usize * size as usize * 4];
+        Atlas {
+            texture: Texture::from_rgba8(size, size, &pixels),
+            size: size as u32,
+            shelves: vec![],
+        }
+    }
+
+    pub fn texture(&self) -> Texture {
+        self.texture
+    }
+
+    /// Reserve a `w`x`h` rect, `None` if the atlas is full.
+    pub fn allocate(&mut self, w: u32, h: u32) -> Option<AtlasRect> {
+        let (padded_w, padded_h) = (w.checked_add(PADDING)?, h.checked_add(PADDING)?);
+        if padded_w > self.size {
+            return None;
+        }
+
+        // the shelf wasting the least height
+        let size = self.size;
+        let best = self
+            .shelves
+            .iter_mut()
+            .filter(|shelf| shelf.height >= padded_h && shelf.x + padded_w <= size)
+            .min_by_key(|shelf| shelf.height - padded_h);
+
+        let shelf = match best {
+            Some(shelf) => shelf,
+            None => {
+                let y = self
+                    .shelves
+                    .last()
+                    .map_or(0, |shelf| shelf.y + shelf.height);
+                if y + padded_h > self.size {
+                    return None;
+                }
+                self.shelves.push(Shelf {
+                    y,
+                    height: padded_h,
+                    x: 0,
+                });
+                self.shelves.last_mut().unwrap()
+            }
+        };
+
+        let (x, y) = (shelf.x, shelf.y);
+        shelf.x += padded_w;
+
+        let size = self.size as f32;
+        Some(AtlasRect {
+            x,
+            y,
+            w,
+            h,
+            uv_min: (x as f32 / size, y as f32 / size),
+            uv_max: ((x + w) as f32 / size, (y + h) as f32 / size),
+        })
+    }
+
+    /// Fill an allocated rect with RGBA8 pixels, `rect.w * rect.h * 4` bytes.
+    pub fn upload(&self, rect: &AtlasRect, bytes: &[u8]) {
+        self.texture
+            .update_part(rect.x, rect.y, rect.w, rect.h, bytes);
+    }
+
+    /// Forget all allocations, the texture keeps its pixels until they are overwritten.
+    pub fn clear(&mut self) {
+        self.shelves.clear();
+    }
+}
@@ -0,0 +1,1179 @@
+//! Basis Universal textures: ETC1S compressed `.basis` files and KTX2 files with BasisLZ
+//! supercompression, one asset for desktop, mobile and web.
+//!
+//! The textures are transcoded at load time to a format the GPU samples, from
+//! `Context::features`: BC1, or BC3 with alpha, where S3TC is supported, ETC2 RGB where
+//! ETC2 is, RGBA8 otherwise. ETC1S blocks are ETC1 blocks, so the ETC2 transcoding is
+//! lossless, the others decode them first. Textures with alpha fall back to RGBA8
+//! without S3TC.
+//!
+//! UASTC textures, Zstandard supercompressed KTX2 files and video `.basis` files are
+//! not supported, nor textures larger than 16384x16384.
+//!
+//! `Texture::from_container` recognizes these files with the `basis` feature.
+
+use crate::{
+    container::{Container, Error, TextureFormat},
+    graphics::Features,
+};
+
+const BASIS_MAGIC: &[u8] = b"sB";
+/// Largest width and height, the Basis Universal encoder's own limit. Runs of blocks
+/// cost a few bits, the slice data doesn't bound the size of the image.
+const MAX_SIZE: u32 = 16384;
+const KTX2_MAGIC: &[u8] = &[
+    0xab, b'K', b'T', b'X', b' ', b'2', b'0', 0xbb, 0x0d, 0x0a, 0x1a, 0x0a,
+];
+
+/// Whether `bytes` looks like a `.basis` file or a KTX2 file of a Basis Universal texture
+pub fn is_basis(bytes: &[u8]) -> bool {
+    bytes.starts_with(BASIS_MAGIC)
+        || bytes.starts_with(KTX2_MAGIC) && bytes.get(12..16) == Some(&[0, 0, 0, 0])
+}
+
+/// Format the textures are transcoded to on a GPU with `features`
+pub fn target_format(features: &Features, alpha: bool) -> TextureFormat {
+    if features.texture_compression_s3tc {
+        if alpha {
+            TextureFormat::BC3
+        } else {
+            TextureFormat::BC1
+        }
+    } else if features.texture_compression_etc2 && !alpha {
+        TextureFormat::ETC2RGB
+    } else {
+        TextureFormat::RGBA8
+    }
+}
+
+/// Transcode a `.basis` or KTX2 file to `target_format`, mip levels and cubemap faces
+/// included.
+pub fn transcode(bytes: &[u8], features: &Features) -> Result<Container, Error> {
+    let file = if bytes.starts_with(BASIS_MAGIC) {
+        parse_basis(bytes)?
+    } else if bytes.starts_with(KTX2_MAGIC) {
+        parse_ktx2(bytes)?
+    } else {
+        return Err(Error::UnknownContainer);
+    };
+    if file.width == 0 || file.height == 0 || file.width > MAX_SIZE || file.height > MAX_SIZE {
+        return Err(Error::Corrupted);
+    }
+
+    let codebooks = Codebooks {
+        endpoints: decode_endpoints(file.endpoints, file.endpoint_count)?,
+        selectors: decode_selectors(file.selectors, file.selector_count)?,
+        tables: decode_tables(file.tables)?,
+    };
+    let alpha = file
+        .levels
+        .iter()
+        .flatten()
+        .any(|slices| slices.1.is_some());
+    let format = target_format(features, alpha);
+
+    let mut levels = Vec::with_capacity(file.levels.len());
+    for (level, faces) in file.levels.chunks(file.faces).enumerate() {
+        let width = (file.width >> level).max(1);
+        let height = (file.height >> level).max(1);
+        let (blocks_x, blocks_y) = ((width + 3) / 4, (height + 3) / 4);
+        let mut images = Vec::with_capacity(file.faces);
+        for (rgb, alpha) in faces.iter().flatten() {
+            let rgb = codebooks.decode_slice(rgb, blocks_x, blocks_y)?;
+            let alpha = match alpha {
+                Some(alpha) => Some(codebooks.decode_slice(alpha, blocks_x, blocks_y)?),
+                None => None,
+            };
+            let slices = Slices {
+                codebooks: &codebooks,
+                rgb: &rgb,
+                alpha: alpha.as_deref(),
+                blocks_x,
+            };
+            images.push(slices.image(format, width, height));
+        }
+        if images.len() != file.faces {
+            return Err(Error::Corrupted);
+        }
+        levels.push(images);
+    }
+
+    Ok(Container {
+        width: file.width,
+        height: file.height,
+        format,
+        faces: file.faces,
+        levels,
+    })
+}
+
+/// Color slice and optional alpha slice of one face of one mip level
+type SlicePair<'a> = (&'a [u8], Option<&'a [u8]>);
+
+/// The parts of either kind of file
+struct BasisFile<'a> {
+    width: u32,
+    height: u32,
+    faces: usize,
+    /// `levels[mip * faces + face]`, `None` for images missing in the file
+    levels: Vec<Option<SlicePair<'a>>>,
+    endpoint_count: usize,
+    endpoints: &'a [u8],
+    selector_count: usize,
+    selectors: &'a [u8],
+    tables: &'a [u8],
+}
+
+fn uint_at(bytes: &[u8], offset: usize, len: usize) -> Result<usize, Error> {
+    let b = slice(bytes, offset, len)?;
+    Ok(b.iter()
+        .rev()
+        .fold(0, |value, byte| value << 8 | *byte as usize))
+}
+
+fn slice(bytes: &[u8], offset: usize, len: usize) -> Result<&[u8], Error> {
+    bytes
+        .get(offset..offset.checked_add(len).ok_or(Error::Corrupted)?)
+        .ok_or(Error::Corrupted)
+}
+
+fn parse_basis(bytes: &[u8]) -> Result<BasisFile, Error> {
+    const FLAG_USES_GLOBAL_CODEBOOK: usize = 8;
+    const SLICE_HAS_ALPHA: usize = 1;
+    const TEX_TYPE_2D: usize = 0;
+    const TEX_TYPE_CUBEMAP_ARRAY: usize = 2;
+
+    let total_slices = uint_at(bytes, 14, 3)?;
+    let total_images = uint_at(bytes, 17, 3)?;
+    if uint_at(bytes, 20, 1)? != 0 {
+        return Err(Error::Unsupported("UASTC textures"));
+    }
+    if uint_at(bytes, 21, 2)? & FLAG_USES_GLOBAL_CODEBOOK != 0 {
+        return Err(Error::Unsupported("global codebooks"));
+    }
+    let faces = match (uint_at(bytes, 23, 1)?, total_images) {
+        (TEX_TYPE_2D, 1) => 1,
+        (TEX_TYPE_CUBEMAP_ARRAY, 6) => 6,
+        (TEX_TYPE_2D, _) | (TEX_TYPE_CUBEMAP_ARRAY, _) => {
+            return Err(Error::Unsupported("texture arrays"))
+        }
+        _ => return Err(Error::Unsupported("video and volume textures")),
+    };
+    let section = |offset, size_offset, size_len| -> Result<&[u8], Error> {
+        let offset = uint_at(bytes, offset, 4)?;
+        slice(bytes, offset, uint_at(bytes, size_offset, size_len)?)
+    };
+    let endpoints = section(41, 45, 3)?;
+    let selectors = section(50, 54, 3)?;
+    let tables = section(57, 61, 4)?;
+    let slice_descs = uint_at(bytes, 65, 4)?;
+
+    let mut width = 0;
+    let mut height = 0;
+    let mut levels: Vec<Option<SlicePair>> = vec![];
+    for n in 0..total_slices {
+        let desc = slice(bytes, slice_descs + n * 23, 23)?;
+        let image = uint_at(desc, 0, 3)?;
+        let level = uint_at(desc, 3, 1)?;
+        let flags = uint_at(desc, 4, 1)?;
+        if image >= faces || level >= 32 {
+            return Err(Error::Corrupted);
+        }
+        if image == 0 && level == 0 {
+            width = uint_at(desc, 5, 2)? as u32;
+            height = uint_at(desc, 7, 2)? as u32;
+        }
+        let data = slice(bytes, uint_at(desc, 13, 4)?, uint_at(desc, 17, 4)?)?;
+
+        let index = level * faces + image;
+        if levels.len() <= index {
+            levels.resize((level + 1) * faces, None);
+        }
+        match (&mut levels[index], flags & SLICE_HAS_ALPHA != 0) {
+            (slot @ None, false) => *slot = Some((data, None)),
+            (Some((_, alpha @ None)), true) => *alpha = Some(data),
+            _ => return Err(Error::Corrupted),
+        }
+    }
+
+    Ok(BasisFile {
+        width,
+        height,
+        faces,
+        levels,
+        endpoint_count: uint_at(bytes, 39, 2)?,
+        endpoints,
+        selector_count: uint_at(bytes, 48, 2)?,
+        selectors,
+        tables,
+    })
+}
+
+fn parse_ktx2(bytes: &[u8]) -> Result<BasisFile, Error> {
+    const BASIS_LZ: usize = 1;
+    const IS_P_FRAME: usize = 2;
+
+    let width = uint_at(bytes, 20, 4)? as u32;
+    let height = uint_at(bytes, 24, 4)? as u32;
+    if uint_at(bytes, 28, 4)? > 1 {
+        return Err(Error::Unsupported("3D textures"));
+    }
+    if uint_at(bytes, 32, 4)? > 1 {
+        return Err(Error::Unsupported("texture arrays"));
+    }
+    let faces = match uint_at(bytes, 36, 4)? {
+        1 => 1,
+        6 => 6,
+        _ => return Err(Error::Corrupted),
+    };
+    let mip_count = uint_at(bytes, 40, 4)?.max(1);
+    if mip_count > 32 {
+        return Err(Error::Corrupted);
+    }
+    if uint_at(bytes, 44, 4)? != BASIS_LZ {
+        return Err(Error::Unsupported(
+            "UASTC textures, or other supercompression than BasisLZ",
+        ));
+    }
+
+    let global = slice(bytes, uint_at(bytes, 64, 8)?, uint_at(bytes, 72, 8)?)?;
+    let image_count = mip_count * faces;
+    let mut offset = 20 + image_count * 20;
+    let mut section = |size_offset| -> Result<&[u8], Error> {
+        let len = uint_at(global, size_offset, 4)?;
+        offset += len;
+        slice(global, offset - len, len)
+    };
+    let endpoints = section(4)?;
+    let selectors = section(8)?;
+    let tables = section(12)?;
+
+    let mut levels = Vec::with_capacity(image_count);
+    for level in 0..mip_count {
+        // level index starts right after the 80 bytes header
+        let level_offset = uint_at(bytes, 80 + level * 24, 8)?;
+        for face in 0..faces {
+            let desc = slice(global, 20 + (level * faces + face) * 20, 20)?;
+            if uint_at(desc, 0, 4)? & IS_P_FRAME != 0 {
+                return Err(Error::Unsupported("video textures"));
+            }
+            let part = |at| -> Result<&[u8], Error> {
+                slice(
+                    bytes,
+                    level_offset + uint_at(desc, at, 4)?,
+                    uint_at(desc, at + 4, 4)?,
+                )
+            };
+            let alpha = part(12)?;
+            levels.push(Some((part(4)?, Some(alpha).filter(|a| !a.is_empty()))));
+        }
+    }
+
+    Ok(BasisFile {
+        width,
+        height,
+        faces,
+        levels,
+        endpoint_count: uint_at(global, 0, 2)?,
+        endpoints,
+        selector_count: uint_at(global, 2, 2)?,
+        selectors,
+        tables,
+    })
+}
+
+/// Reads bits from the least significant bit of each byte, past the end reads zeros
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    buf: u64,
+    len: u32,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(bytes: &'a [u8]) -> BitReader<'a> {
+        BitReader {
+            bytes,
+            buf: 0,
+            len: 0,
+        }
+    }
+
+    fn bits(&mut self, count: u32) -> usize {
+        while self.len < count {
+            let (byte, rest) = self.bytes.split_first().unwrap_or((&0, &[]));
+            self.buf |= (*byte as u64) << self.len;
+            self.bytes = rest;
+            self.len += 8;
+        }
+        let value = self.buf & ((1 << count) - 1);
+        self.buf >>= count;
+        self.len -= count;
+        value as usize
+    }
+
+    /// Variable length number in chunks of `chunk_bits`, each followed by a "more" bit
+    fn vlc(&mut self, chunk_bits: u32) -> Result<usize, Error> {
+        let mut value = 0;
+        for shift in (0..32).step_by(chunk_bits as usize) {
+            let chunk = self.bits(chunk_bits + 1);
+            value |= (chunk & ((1 << chunk_bits) - 1)) << shift;
+            if chunk >> chunk_bits == 0 {
+                return Ok(value);
+            }
+        }
+        Err(Error::Corrupted)
+    }
+
+    fn huffman(&mut self, table: &Huffman) -> Result<usize, Error> {
+        let mut code = 0;
+        let mut first = 0;
+        let mut index = 0;
+        for len in 1..=MAX_CODE_SIZE {
+            code |= self.bits(1);
+            let count = table.counts[len];
+            if code < first + count {
+                return Ok(table.symbols[index + code - first] as usize);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(Error::Corrupted)
+    }
+
+    /// A Huffman table, its code sizes themselves Huffman coded
+    fn huffman_table(&mut self) -> Result<Huffman, Error> {
+        // code length codes, in the order their sizes are stored
+        const ORDER: [usize; 21] = [
+            17, 18, 19, 20, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15, 16,
+        ];
+
+        let symbol_count = self.bits(14);
+        if symbol_count == 0 {
+            return Huffman::new(&[]);
+        }
+        let mut code_length_sizes = [0u8; 21];
+        let stored = self.bits(5);
+        if stored == 0 || stored > 21 {
+            return Err(Error::Corrupted);
+        }
+        for symbol in &ORDER[..stored] {
+            code_length_sizes[*symbol] = self.bits(3) as u8;
+        }
+        let code_lengths = Huffman::new(&code_length_sizes)?;
+
+        let mut sizes = Vec::with_capacity(symbol_count);
+        while sizes.len() < symbol_count {
+            let (size, repeat) = match self.huffman(&code_lengths)? {
+                size @ 0..=16 => (size as u8, 1),
+                17 => (0, self.bits(3) + 3),
+                18 => (0, self.bits(7) + 11),
+                code => {
+                    let repeat = if code == 19 {
+                        self.bits(2) + 3
+                    } else {
+                        self.bits(7) + 7
+                    };
+                    match sizes.last() {
+                        Some(&previous) if previous != 0 => (previous, repeat),
+                        _ => return Err(Error::Corrupted),
+                    }
+                }
+            };
+            if sizes.len() + repeat > symbol_count {
+                return Err(Error::Corrupted);
+            }
+            sizes.extend(std::iter::repeat(size).take(repeat));
+        }
+        Huffman::new(&sizes)
+    }
+}
+
+const MAX_CODE_SIZE: usize = 16;
+
+/// Canonical Huffman code: shorter codes first, then by symbol
+struct Huffman {
+    /// Codes of each size
+    counts: [usize; MAX_CODE_SIZE + 1],
+    /// Symbols by code size, then by value
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(code_sizes: &[u8]) -> Result<Huffman, Error> {
+        let mut counts = [0; MAX_CODE_SIZE + 1];
+        let mut symbols = vec![];
+        for size in 1..=MAX_CODE_SIZE {
+            for (symbol, _) in code_sizes
+                .iter()
+                .enumerate()
+                .filter(|(_, s)| **s as usize == size)
+            {
+                counts[size] += 1;
+                symbols.push(symbol as u16);
+            }
+        }
+        if code_sizes.iter().any(|size| *size as usize > MAX_CODE_SIZE) {
+            return Err(Error::Corrupted);
+        }
+        Ok(Huffman { counts, symbols })
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Endpoint {
+    /// 5 bit RGB
+    color: [u8; 3],
+    /// Row of `INTENSITIES`
+    intensity: u8,
+}
+
+/// 4 rows of 4 pixels, 2 bits per pixel from the lowest, brighter pixels with
+/// higher values
+type Selector = [u8; 4];
+
+/// How much brighter or darker than the base color of a block each selector is
+const INTENSITIES: [[i32; 4]; 8] = [
+    [-8, -2, 2, 8],
+    [-17, -5, 5, 17],
+    [-29, -9, 9, 29],
+    [-42, -13, 13, 42],
+    [-60, -18, 18, 60],
+    [-80, -24, 24, 80],
+    [-106, -33, 33, 106],
+    [-183, -47, 47, 183],
+];
+
+fn decode_endpoints(data: &[u8], count: usize) -> Result<Vec<Endpoint>, Error> {
+    let mut reader = BitReader::new(data);
+    // deltas from the previous color, the model depending on the previous value
+    let color_models = [
+        reader.huffman_table()?,
+        reader.huffman_table()?,
+        reader.huffman_table()?,
+    ];
+    let intensity_model = reader.huffman_table()?;
+    let grayscale = reader.bits(1) == 1;
+
+    let mut endpoints = Vec::with_capacity(count);
+    let mut previous = Endpoint {
+        color: [16, 16, 16],
+        intensity: 0,
+    };
+    for _ in 0..count {
+        let intensity_delta = reader.huffman(&intensity_model)?;
+        let mut endpoint = Endpoint {
+            color: previous.color,
+            intensity: ((previous.intensity as usize + intensity_delta) & 7) as u8,
+        };
+        let channels = if grayscale { 1 } else { 3 };
+        for c in 0..channels {
+            let model = match previous.color[c] {
+                0..=9 => &color_models[0],
+                10..=21 => &color_models[1],
+                _ => &color_models[2],
+            };
+            let delta = reader.huffman(model)?;
+            endpoint.color[c] = ((previous.color[c] as usize + delta) & 31) as u8;
+        }
+        if grayscale {
+            endpoint.color = [endpoint.color[0]; 3];
+        }
+        endpoints.push(endpoint);
+        previous = endpoint;
+    }
+    Ok(endpoints)
+}
+
+fn decode_selectors(data: &[u8], count: usize) -> Result<Vec<Selector>, Error> {
+    let mut reader = BitReader::new(data);
+    if reader.bits(1) == 1 {
+        return Err(Error::Unsupported("global selector codebooks"));
+    }
+    if reader.bits(1) == 1 {
+        return Err(Error::Unsupported("hybrid selector codebooks"));
+    }
+    let raw = reader.bits(1) == 1;
+    let delta_model = if raw {
+        None
+    } else {
+        Some(reader.huffman_table()?)
+    };
+
+    let mut selectors = Vec::with_capacity(count);
+    let mut previous = [0u8; 4];
+    for n in 0..count {
+        let mut selector = [0u8; 4];
+        for (row, previous) in selector.iter_mut().zip(previous.iter()) {
+            *row = match &delta_model {
+                Some(model) if n > 0 => (reader.huffman(model)? as u8) ^ previous,
+                _ => reader.bits(8) as u8,
+            };
+        }
+        selectors.push(selector);
+        previous = selector;
+    }
+    Ok(selectors)
+}
+
+/// Models of the slices
+struct Tables {
+    endpoint_pred: Huffman,
+    delta_endpoint: Huffman,
+    selector: Huffman,
+    selector_history_rle: Huffman,
+    selector_history_size: usize,
+}
+
+fn decode_tables(data: &[u8]) -> Result<Tables, Error> {
+    let mut reader = BitReader::new(data);
+    Ok(Tables {
+        endpoint_pred: reader.huffman_table()?,
+        delta_endpoint: reader.huffman_table()?,
+        selector: reader.huffman_table()?,
+        selector_history_rle: reader.huffman_table()?,
+        selector_history_size: reader.bits(13),
+    })
+}
+
+struct Codebooks {
+    endpoints: Vec<Endpoint>,
+    selectors: Vec<Selector>,
+    tables: Tables,
+}
+
+impl Codebooks {
+    /// Endpoint and selector indices of every block of a slice, row by row
+    fn decode_slice(
+        &self,
+        data: &[u8],
+        blocks_x: u32,
+        blocks_y: u32,
+    ) -> Result<Vec<(u16, u16)>, Error> {
+        const REPEAT_LAST_PRED: usize = 256;
+        const MIN_PRED_REPEAT: usize = 3;
+        const RLE_THRESHOLD: usize = 3;
+        const RLE_TOTAL: usize = 64;
+
+        let (blocks_x, blocks_y) = (blocks_x as usize, blocks_y as usize);
+        let tables = &self.tables;
+        let endpoint_count = self.endpoints.len();
+        let selector_count = self.selectors.len();
+        let history_rle_symbol = selector_count + tables.selector_history_size;
+
+        let mut reader = BitReader::new(data);
+        let mut history = SelectorHistory::new(tables.selector_history_size);
+        let mut blocks: Vec<(u16, u16)> = Vec::with_capacity(blocks_x * blocks_y);
+        // endpoint predictions of the next row, 2 bits per block for 2x2 blocks
+        let mut next_row_preds = vec![0u8; blocks_x];
+        let mut pred_bits = 0;
+        let mut previous_pred = 0;
+        let mut pred_repeat = 0;
+        let mut previous_endpoint = 0;
+        let mut selector_rle = 0;
+
+        for y in 0..blocks_y {
+            for x in 0..blocks_x {
+                if x % 2 == 0 {
+                    if y % 2 == 0 {
+                        if pred_repeat > 0 {
+                            pred_repeat -= 1;
+                            pred_bits = previous_pred;
+                        } else {
+                            pred_bits = reader.huffman(&tables.endpoint_pred)?;
+                            if pred_bits == REPEAT_LAST_PRED {
+                                pred_repeat = reader.vlc(4)? + MIN_PRED_REPEAT - 1;
+                                pred_bits = previous_pred;
+                            } else {
+                                previous_pred = pred_bits;
+                            }
+                        }
+                        next_row_preds[x] = (pred_bits >> 4) as u8;
+                    } else {
+                        pred_bits = next_row_preds[x] as usize;
+                    }
+                }
+
+                let endpoint = match pred_bits & 3 {
+                    // left
+                    0 if x > 0 => previous_endpoint,
+                    // above
+                    1 if y > 0 => blocks[(y - 1) * blocks_x + x].0 as usize,
+                    // above left
+                    2 if x > 0 && y > 0 => blocks[(y - 1) * blocks_x + x - 1].0 as usize,
+                    3 => {
+                        let endpoint =
+                            previous_endpoint + reader.huffman(&tables.delta_endpoint)?;
+                        if endpoint >= endpoint_count {
+                            endpoint - endpoint_count
+                        } else {
+                            endpoint
+                        }
+                    }
+                    _ => return Err(Error::Corrupted),
+                };
+                pred_bits >>= 2;
+                previous_endpoint = endpoint;
+
+                let symbol = if selector_rle > 0 {
+                    selector_rle -= 1;
+                    selector_count
+                } else {
+                    let symbol = reader.huffman(&tables.selector)?;
+                    if symbol == history_rle_symbol {
+                        let run = reader.huffman(&tables.selector_history_rle)?;
+                        selector_rle = if run == RLE_TOTAL - 1 {
+                            reader.vlc(7)? + RLE_THRESHOLD
+                        } else {
+                            run + RLE_THRESHOLD
+                        };
+                        if selector_rle > blocks_x * blocks_y {
+                            return Err(Error::Corrupted);
+                        }
+                        selector_rle -= 1;
+                        selector_count
+                    } else {
+                        symbol
+                    }
+                };
+                let selector = if symbol >= selector_count {
+                    history.get(symbol - selector_count)?
+                } else {
+                    history.add(symbol);
+                    symbol
+                };
+
+                if endpoint >= endpoint_count || selector >= selector_count {
+                    return Err(Error::Corrupted);
+                }
+                blocks.push((endpoint as u16, selector as u16));
+            }
+        }
+        Ok(blocks)
+    }
+}
+
+/// Recently used selectors, approximately most recent first
+struct SelectorHistory {
+    selectors: Vec<usize>,
+    next: usize,
+}
+
+impl SelectorHistory {
+    fn new(size: usize) -> SelectorHistory {
+        SelectorHistory {
+            selectors: vec![0; size],
+            next: size / 2,
+        }
+    }
+
+    fn add(&mut self, selector: usize) {
+        if self.selectors.is_empty() {
+            return;
+        }
+        self.selectors[self.next] = selector;
+        self.next += 1;
+        if self.next == self.selectors.len() {
+            self.next = self.selectors.len() / 2;
+        }
+    }
+
+    /// Selector at `index`, moved halfway to the front
+    fn get(&mut self, index: usize) -> Result<usize, Error> {
+        let selector = *self.selectors.get(index).ok_or(Error::Corrupted)?;
+        self.selectors.swap(index / 2, index);
+        Ok(selector)
+    }
+}
+
+/// Decoded slices of one image
+struct Slices<'a> {
+    codebooks: &'a Codebooks,
+    rgb: &'a [(u16, u16)],
+    alpha: Option<&'a [(u16, u16)]>,
+    blocks_x: u32,
+}
+
+impl<'a> Slices<'a> {
+    fn image(&self, format: TextureFormat, width: u32, height: u32) -> Vec<u8> {
+        let mut image = Vec::with_capacity(format.image_size(width, height));
+        if format == TextureFormat::RGBA8 {
+            for y in 0..height {
+                for x in 0..width {
+                    let block = (y / 4 * self.blocks_x + x / 4) as usize;
+                    let (x, y) = (x % 4, y % 4);
+                    let (colors, selector) = self.block(self.rgb, block);
+                    let color = colors[pixel(selector, x, y)];
+                    let alpha = match self.alpha {
+                        Some(alpha) => {
+                            let (alphas, selector) = self.block(alpha, block);
+                            alphas[pixel(selector, x, y)][1]
+                        }
+                        None => 255,
+                    };
+                    image.extend_from_slice(&[color[0], color[1], color[2], alpha]);
+                }
+            }
+            return image;
+        }
+
+        for block in 0..self.rgb.len() {
+            let (colors, selector) = self.block(self.rgb, block);
+            match format {
+                TextureFormat::ETC2RGB => {
+                    let (endpoint, _) = self.rgb[block];
+                    etc1_block(
+                        &self.codebooks.endpoints[endpoint as usize],
+                        &selector,
+                        &mut image,
+                    )
+                }
+                TextureFormat::BC1 => bc1_block(&colors, &selector, &mut image),
+                _ => {
+                    let (alphas, alpha_selector) = self.block(self.alpha.unwrap(), block);
+                    let alphas = [alphas[0][1], alphas[1][1], alphas[2][1], alphas[3][1]];
+                    bc4_block(&alphas, &alpha_selector, &mut image);
+                    bc1_block(&colors, &selector, &mut image);
+                }
+            }
+        }
+        image
+    }
+
+    /// The 4 colors of a block, and its selector
+    fn block(&self, slice: &[(u16, u16)], block: usize) -> ([[u8; 3]; 4], Selector) {
+        let (endpoint, selector) = slice[block];
+        let endpoint = &self.codebooks.endpoints[endpoint as usize];
+        let base = endpoint.color.map5();
+        let mut colors = [[0; 3]; 4];
+        for (color, modifier) in colors
+            .iter_mut()
+            .zip(INTENSITIES[endpoint.intensity as usize].iter())
+        {
+            for c in 0..3 {
+                color[c] = (base[c] as i32 + modifier).max(0).min(255) as u8;
+            }
+        }
+        (colors, self.codebooks.selectors[selector as usize])
+    }
+}
+
+trait Expand5 {
+    fn map5(&self) -> [u8; 3];
+}
+
+impl Expand5 for [u8; 3] {
+    /// 5 bit channels to 8 bits
+    fn map5(&self) -> [u8; 3] {
+        let expand = |c: u8| c << 3 | c >> 2;
+        [expand(self[0]), expand(self[1]), expand(self[2])]
+    }
+}
+
+fn pixel(selector: Selector, x: u32, y: u32) -> usize {
+    (selector[y as usize] >> (x * 2) & 3) as usize
+}
+
+/// The ETC1 block ETC1S blocks are: differential mode without a difference, both halves
+/// with the same intensity
+fn etc1_block(endpoint: &Endpoint, selector: &Selector, out: &mut Vec<u8>) {
+    // ETC1 index of each selector: +small, +large, -small, -large
+    const ETC1_INDEX: [u16; 4] = [3, 2, 0, 1];
+
+    let [r, g, b] = endpoint.color;
+    let intensity = endpoint.intensity;
+    out.extend_from_slice(&[r << 3, g << 3, b << 3, intensity << 5 | intensity << 2 | 2]);
+    let (mut msb, mut lsb) = (0u16, 0u16);
+    for x in 0..4 {
+        for y in 0..4 {
+            // column by column
+            let index = ETC1_INDEX[pixel(*selector, x, y)];
+            let bit = x * 4 + y;
+            msb |= (index >> 1) << bit;
+            lsb |= (index & 1) << bit;
+        }
+    }
+    out.extend_from_slice(&msb.to_be_bytes());
+    out.extend_from_slice(&lsb.to_be_bytes());
+}
+
+/// BC1 block between the darkest and brightest color used, each pixel with the
+/// nearest of the 4 BC1 colors
+fn bc1_block(colors: &[[u8; 3]; 4], selector: &Selector, out: &mut Vec<u8>) {
+    let to_565 = |c: [u8; 3]| {
+        let scale = |v: u8, max: u32| (v as u32 * max + 127) / 255;
+        (scale(c[0], 31) << 11 | scale(c[1], 63) << 5 | scale(c[2], 31)) as u16
+    };
+    let from_565 = |c: u16| {
+        let (r, g, b) = ((c >> 11) as u32, (c >> 5 & 63) as u32, (c & 31) as u32);
+        [r * 255 / 31, g * 255 / 63, b * 255 / 31]
+    };
+
+    let used: u32 = selector.iter().fold(0, |used, row| {
+        (0..4).fold(used, |used, x| used | 1 << (row >> (x * 2) & 3))
+    });
+    let darkest = used.trailing_zeros() as usize;
+    let brightest = 31 - used.leading_zeros() as usize;
+    let mut color0 = to_565(colors[brightest]);
+    let mut color1 = to_565(colors[darkest]);
+    if color0 < color1 {
+        std::mem::swap(&mut color0, &mut color1);
+    }
+
+    // BC1 index for each selector
+    let mut indices = [0u32; 4];
+    if color0 != color1 {
+        let (c0, c1) = (from_565(color0), from_565(color1));
+        let mut palette = [c0, c1, [0; 3], [0; 3]];
+        for c in 0..3 {
+            palette[2][c] = (2 * c0[c] + c1[c]) / 3;
+            palette[3][c] = (c0[c] + 2 * c1[c]) / 3;
+        }
+        for (index, color) in indices.iter_mut().zip(colors.iter()) {
+            let distance = |p: &[u32; 3]| -> i32 {
+                (0..3).map(|c| (p[c] as i32 - color[c] as i32).pow(2)).sum()
+            };
+            *index = (0..4).min_by_key(|i| distance(&palette[*i])).unwrap() as u32;
+        }
+    }
+
+    let mut bits = 0u32;
+    for y in 0..4 {
+        for x in 0..4 {
+            bits |= indices[pixel(*selector, x, y)] << (2 * (y * 4 + x));
+        }
+    }
+    out.extend_from_slice(&color0.to_le_bytes());
+    out.extend_from_slice(&color1.to_le_bytes());
+    out.extend_from_slice(&bits.to_le_bytes());
+}
+
+/// BC4 block, the alpha half of BC3, between the lowest and highest alpha
+fn bc4_block(alphas: &[u8; 4], selector: &Selector, out: &mut Vec<u8>) {
+    let alpha0 = *alphas.iter().max().unwrap();
+    let alpha1 = *alphas.iter().min().unwrap();
+
+    // BC4 index for each selector, from alpha0, alpha1 and 6 steps between them
+    let mut indices = [0u64; 4];
+    if alpha0 != alpha1 {
+        let palette = |i: u32| match i {
+            0 => alpha0 as u32,
+            1 => alpha1 as u32,
+            _ => ((8 - i) * alpha0 as u32 + (i - 1) * alpha1 as u32) / 7,
+        };
+        for (index, alpha) in indices.iter_mut().zip(alphas.iter()) {
+            let distance = |i: &u32| (palette(*i) as i32 - *alpha as i32).abs();
+            *index = (0..8).min_by_key(distance).unwrap() as u64;
+        }
+    }
+
+    let mut bits = 0u64;
+    for y in 0..4 {
+        for x in 0..4 {
+            bits |= indices[pixel(*selector, x, y)] << (3 * (y * 4 + x));
+        }
+    }
+    out.extend_from_slice(&[alpha0, alpha1]);
+    out.extend_from_slice(&bits.to_le_bytes()[..6]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 8x4, the left block from endpoint 0 and selector 0, the right one from
+    /// endpoint 1 and selector 1
+    const BASIS_RGB: &[u8] = include_bytes!("../testdata/8x4_etc1s.basis");
+    /// The same texture in a KTX2 file with BasisLZ supercompression
+    const KTX2_RGB: &[u8] = include_bytes!("../testdata/8x4_etc1s.ktx2");
+    /// 4x4, colors from endpoint 0 and selector 0, alpha from endpoint 1 and selector 1
+    const BASIS_ALPHA: &[u8] = include_bytes!("../testdata/4x4_etc1s_alpha.basis");
+
+    /// Colors of the 2 endpoints of the fixtures, by selector: the 5 bit colors
+    /// [20, 10, 5] with intensity 3, and [3, 30, 12] with intensity 6
+    const COLORS: [[[u8; 3]; 4]; 2] = [
+        [[123, 40, 0], [152, 69, 28], [178, 95, 54], [207, 124, 83]],
+        [[0, 141, 0], [0, 214, 66], [57, 255, 132], [130, 255, 205]],
+    ];
+    const SELECTORS: [[u8; 4]; 2] = [[0xe4, 0x1b, 0x00, 0xff], [0x1b, 0xe4, 0x55, 0xaa]];
+
+    fn s3tc() -> Features {
+        Features {
+            texture_compression_s3tc: true,
+            ..Default::default()
+        }
+    }
+
+    fn etc2() -> Features {
+        Features {
+            texture_compression_etc2: true,
+            ..Default::default()
+        }
+    }
+
+    /// Color of a pixel of the 4x4 block from `endpoint` and `selector`
+    fn color(endpoint: usize, selector: usize, x: usize, y: usize) -> [u8; 3] {
+        COLORS[endpoint][(SELECTORS[selector][y] >> (x * 2) & 3) as usize]
+    }
+
+    /// RGBA8 pixels of a block, row by row
+    fn etc1_decode(block: &[u8]) -> Vec<[u8; 3]> {
+        const MODIFIERS: [[i32; 2]; 8] = [
+            [2, 8],
+            [5, 17],
+            [9, 29],
+            [13, 42],
+            [18, 60],
+            [24, 80],
+            [33, 106],
+            [47, 183],
+        ];
+        // differential mode, no flip, the same intensity and a zero difference
+        assert_eq!(block[3] & 3, 2);
+        assert_eq!(block[3] >> 5, block[3] >> 2 & 7);
+        assert!(block[..3].iter().all(|c| c & 7 == 0));
+        let base = [block[0] >> 3, block[1] >> 3, block[2] >> 3].map5();
+        let modifiers = MODIFIERS[(block[3] >> 5) as usize];
+        let msb = u16::from_be_bytes([block[4], block[5]]);
+        let lsb = u16::from_be_bytes([block[6], block[7]]);
+        let mut pixels = vec![];
+        for y in 0..4 {
+            for x in 0..4 {
+                let bit = x * 4 + y;
+                let modifier = modifiers[(lsb >> bit & 1) as usize];
+                let modifier = if msb >> bit & 1 == 1 {
+                    -modifier
+                } else {
+                    modifier
+                };
+                let channel = |c: u8| (c as i32 + modifier).max(0).min(255) as u8;
+                pixels.push([channel(base[0]), channel(base[1]), channel(base[2])]);
+            }
+        }
+        pixels
+    }
+
+    fn bc1_decode(block: &[u8]) -> Vec<[u8; 3]> {
+        let color0 = u16::from_le_bytes([block[0], block[1]]);
+        let color1 = u16::from_le_bytes([block[2], block[3]]);
+        assert!(color0 > color1, "4 color mode");
+        let rgb = |c: u16| {
+            let (r, g, b) = ((c >> 11) as u32, (c >> 5 & 63) as u32, (c & 31) as u32);
+            [r * 255 / 31, g * 255 / 63, b * 255 / 31]
+        };
+        let (c0, c1) = (rgb(color0), rgb(color1));
+        let mut palette = [c0, c1, [0; 3], [0; 3]];
+        for c in 0..3 {
+            palette[2][c] = (2 * c0[c] + c1[c]) / 3;
+            palette[3][c] = (c0[c] + 2 * c1[c]) / 3;
+        }
+        let bits = u32::from_le_bytes([block[4], block[5], block[6], block[7]]);
+        (0..16)
+            .map(|i| {
+                let color = palette[(bits >> (2 * i) & 3) as usize];
+                [color[0] as u8, color[1] as u8, color[2] as u8]
+            })
+            .collect()
+    }
+
+    fn bc4_decode(block: &[u8]) -> Vec<u8> {
+        let (alpha0, alpha1) = (block[0] as u32, block[1] as u32);
+        assert!(alpha0 > alpha1, "8 alpha mode");
+        let mut bits = [0; 8];
+        bits[..6].copy_from_slice(&block[2..8]);
+        let bits = u64::from_le_bytes(bits);
+        (0..16)
+            .map(|i| match bits >> (3 * i) & 7 {
+                0 => alpha0 as u8,
+                1 => alpha1 as u8,
+                i => (((8 - i as u32) * alpha0 + (i as u32 - 1) * alpha1) / 7) as u8,
+            })
+            .collect()
+    }
+
+    /// Pixels of the 4x4 block at `block_x` of an RGBA8 image `width` wide
+    fn rgba8_block(image: &[u8], width: usize, block_x: usize) -> Vec<[u8; 4]> {
+        let mut pixels = vec![];
+        for y in 0..4 {
+            for x in block_x * 4..block_x * 4 + 4 {
+                let i = (y * width + x) * 4;
+                pixels.push([image[i], image[i + 1], image[i + 2], image[i + 3]]);
+            }
+        }
+        pixels
+    }
+
+    fn assert_close(a: u8, b: u8, tolerance: u8) {
+        assert!(
+            (a as i32 - b as i32).abs() <= tolerance as i32,
+            "{} and {}",
+            a,
+            b
+        );
+    }
+
+    #[test]
+    fn rgba8() {
+        let texture = transcode(BASIS_RGB, &Default::default()).unwrap();
+        assert_eq!((texture.width, texture.height), (8, 4));
+        assert_eq!(texture.format, TextureFormat::RGBA8);
+        assert_eq!(texture.faces, 1);
+        assert_eq!(texture.levels.len(), 1);
+        let image = &texture.levels[0][0];
+        for block in 0..2 {
+            for (n, pixel) in rgba8_block(image, 8, block).iter().enumerate() {
+                let [r, g, b] = color(block, block, n % 4, n / 4);
+                assert_eq!(*pixel, [r, g, b, 255]);
+            }
+        }
+    }
+
+    /// ETC1S blocks are ETC1 blocks, transcoded to ETC2 without loss
+    #[test]
+    fn etc2_lossless() {
+        let rgba8 = transcode(BASIS_RGB, &Default::default()).unwrap();
+        let texture = transcode(BASIS_RGB, &etc2()).unwrap();
+        assert_eq!(texture.format, TextureFormat::ETC2RGB);
+        let blocks = &texture.levels[0][0];
+        assert_eq!(blocks.len(), 16);
+        for (block, data) in blocks.chunks(8).enumerate() {
+            let expected = rgba8_block(&rgba8.levels[0][0], 8, block);
+            for (pixel, expected) in etc1_decode(data).iter().zip(&expected) {
+                assert_eq!(pixel[..], expected[..3]);
+            }
+        }
+    }
+
+    #[test]
+    fn bc1() {
+        let texture = transcode(BASIS_RGB, &s3tc()).unwrap();
+        assert_eq!(texture.format, TextureFormat::BC1);
+        let blocks = &texture.levels[0][0];
+        assert_eq!(blocks.len(), 16);
+        // the colors of the first endpoint are on a line, the BC1 ones close to them
+        for (n, pixel) in bc1_decode(&blocks[..8]).iter().enumerate() {
+            let expected = color(0, 0, n % 4, n / 4);
+            for c in 0..3 {
+                assert_close(pixel[c], expected[c], 8);
+            }
+        }
+        // the brightest and darkest colors of the second one are its end points
+        let pixels = bc1_decode(&blocks[8..]);
+        assert_close(pixels[3][1], 141, 4);
+        assert_close(pixels[0][2], 205, 4);
+    }
+
+    #[test]
+    fn alpha() {
+        let texture = transcode(BASIS_ALPHA, &Default::default()).unwrap();
+        assert_eq!(texture.format, TextureFormat::RGBA8);
+        let pixels = rgba8_block(&texture.levels[0][0], 4, 0);
+        for (n, pixel) in pixels.iter().enumerate() {
+            let [r, g, b] = color(0, 0, n % 4, n / 4);
+            // alpha from the green channel
+            assert_eq!(*pixel, [r, g, b, color(1, 1, n % 4, n / 4)[1]]);
+        }
+
+        // no ETC2 with alpha
+        let texture = transcode(BASIS_ALPHA, &etc2()).unwrap();
+        assert_eq!(texture.format, TextureFormat::RGBA8);
+
+        let texture = transcode(BASIS_ALPHA, &s3tc()).unwrap();
+        assert_eq!(texture.format, TextureFormat::BC3);
+        let block = &texture.levels[0][0];
+        assert_eq!(block.len(), 16);
+        for (alpha, pixel) in bc4_decode(&block[..8]).iter().zip(&pixels) {
+            assert_close(*alpha, pixel[3], 8);
+        }
+        for (color, pixel) in bc1_decode(&block[8..]).iter().zip(&pixels) {
+            for c in 0..3 {
+                assert_close(color[c], pixel[c], 8);
+            }
+        }
+    }
+
+    #[test]
+    fn ktx2_same_as_basis() {
+        assert!(is_basis(KTX2_RGB));
+        assert!(matches!(
+            crate::container::parse(KTX2_RGB),
+            Err(Error::Unsupported(_))
+        ));
+        for features in &[Default::default(), s3tc(), etc2()] {
+            let basis = transcode(BASIS_RGB, features).unwrap();
+            let ktx2 = transcode(KTX2_RGB, features).unwrap();
+            assert_eq!((ktx2.width, ktx2.height), (8, 4));
+            assert_eq!(ktx2.format, basis.format);
+            assert_eq!(ktx2.levels, basis.levels);
+        }
+    }
+
+    #[test]
+    fn not_basis() {
+        assert!(!is_basis(b"DDS "));
+        assert!(!is_basis(include_bytes!("../testdata/2x2_rgba_mips.ktx2")));
+        assert!(matches!(
+            transcode(b"GIF89a", &Default::default()),
+            Err(Error::UnknownContainer)
+        ));
+    }
+
+    #[test]
+    fn truncated() {
+        for data in &[BASIS_RGB, KTX2_RGB, BASIS_ALPHA] {
+            for len in 0..data.len() {
+                assert!(
+                    transcode(&data[..len], &Default::default()).is_err(),
+                    "{} bytes",
+                    len
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn corrupted() {
+        let transcode = |data: &[u8]| transcode(data, &Default::default());
+        // UASTC texture format
+        let mut data = BASIS_RGB.to_vec();
+        data[20] = 1;
+        assert!(matches!(transcode(&data), Err(Error::Unsupported(_))));
+        // slice data offset past the end of the file
+        let mut data = BASIS_RGB.to_vec();
+        data[77 + 16] = 0xff;
+        assert!(matches!(transcode(&data), Err(Error::Corrupted)));
+        // one selector only, the second block's selector index is out of range
+        let mut data = BASIS_RGB.to_vec();
+        data[48] = 1;
+        assert!(matches!(transcode(&data), Err(Error::Corrupted)));
+        // second alpha slice of the same image
+        let mut data = BASIS_ALPHA.to_vec();
+        data[77 + 4] = 1;
+        assert!(matches!(transcode(&data), Err(Error::Corrupted)));
+        // KTX2 without BasisLZ supercompression
+        let mut data = KTX2_RGB.to_vec();
+        data[44] = 0;
+        assert!(matches!(transcode(&data), Err(Error::Unsupported(_))));
+        // KTX2 global data past the end of the file
+        let mut data = KTX2_RGB.to_vec();
+        data[72] = 0xff;
+        assert!(matches!(transcode(&data), Err(Error::Corrupted)));
+    }
+
+    #[test]
+    fn oversized() {
+        let transcode = |data: &[u8]| transcode(data, &Default::default());
+        // 65535x65535 .basis, 16 GiB as RGBA8
+        let mut data = BASIS_RGB.to_vec();
+        data[77 + 5..77 + 9].copy_from_slice(&[0xff; 4]);
+        assert!(matches!(transcode(&data), Err(Error::Corrupted)));
+        // 131072x131072 KTX2, 64 GiB as RGBA8
+        let mut data = KTX2_RGB.to_vec();
+        data[20..28].copy_from_slice(&[0, 0, 2, 0, 0, 0, 2, 0]);
+        assert!(matches!(transcode(&data), Err(Error::Corrupted)));
+        // a size of 0
+        let mut data = KTX2_RGB.to_vec();
+        data[20] = 0;
+        assert!(matches!(transcode(&data), Err(Error::Corrupted)));
+    }
+}
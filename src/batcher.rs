@@ -0,0 +1,114 @@
+use crate::graphics::*;
+
+/// What draws must share to be merged into one: the pipeline and the images.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchKey {
+    pub pipeline: Pipeline,
+    pub images: Vec<Texture>,
+}
+
+/// Merges many small meshes into big streaming buffers drawn with one draw call,
+/// for UI and other things made of lots of little dynamic pieces.
+///
+/// Meshes are accumulated until the key changes, the buffers are full or `flush`
+/// is called, so `flush` should be called before the end of the render pass.
+/// The vertex buffer is the first and only one of the pipeline.
+pub struct Batcher<V> {
+    bindings: Bindings,
+    vertices: Vec<V>,
+    indices: Vec<u16>,
+    max_vertices: usize,
+    max_indices: usize,
+    key: Option<BatchKey>,
+    /// Applied before every draw, see `set_uniforms`
+    uniforms: Vec<f32>,
+}
+
+impl<V: Copy + std::fmt::Debug> Batcher<V> {
+    /// `max_vertices` is at most 65536, indices are 16 bits.
+    pub fn new(ctx: &mut Context, max_vertices: usize, max_indices: usize) -> Batcher<V> {
+        assert!(max_vertices <= u16::max_value() as usize + 1);
+
+        let vertex_buffer = Buffer::stream(
+            ctx,
+            BufferType::VertexBuffer,
+            max_vertices * std::mem::size_of::<V>(),
+        );
+        let index_buffer = Buffer::stream(
+            ctx,
+            BufferType::IndexBuffer,
+            max_indices * std::mem::size_of::<u16>(),
+        );
+
+        Batcher {
+            bindings: Bindings {
+                vertex_buffers: vec![vertex_buffer],
+                index_buffer,
+                images: vec![],
+            },
+            vertices: Vec::with_capacity(max_vertices),
+            indices: Vec::with_capacity(max_indices),
+            max_vertices,
+            max_indices,
+            key: None,
+            uniforms: vec![],
+        }
+    }
+
+    /// Queue a mesh, `indices` refer to `vertices`, starting from 0.
+    pub fn push(&mut self, ctx: &mut Context, vertices: &[V], indices: &[u16], key: &BatchKey) {
+        assert!(vertices.len() <= self.max_vertices && indices.len() <= self.max_indices);
+
+        if self.key.as_ref() != Some(key)
+            || self.vertices.len() + vertices.len() > self.max_vertices
+            || self.indices.len() + indices.len() > self.max_indices
+        {
+            self.flush(ctx);
+            self.key = Some(key.clone());
+        }
+
+        let base = self.vertices.len() as u16;
+        self.vertices.extend_from_slice(vertices);
+        self.indices
+            .extend(indices.iter().map(|index| base + index));
+    }
+
+    /// Uniforms for the following meshes, as given to `apply_uniforms`.
+    /// Meshes queued before are drawn with the previous ones.
+    pub fn set_uniforms<U>(&mut self, ctx: &mut Context, uniforms: &U) {
+        self.flush(ctx);
+
+        let floats = unsafe {
+            std::slice::from_raw_parts(
+                uniforms as *const _ as *const f32,
+                std::mem::size_of::<U>() / 4,
+            )
+        };
+        self.uniforms.clear();
+        self.uniforms.extend_from_slice(floats);
+    }
+
+    /// Draw all queued meshes into the current render pass.
+    pub fn flush(&mut self, ctx: &mut Context) {
+        let key = match &self.key {
+            Some(key) if !self.indices.is_empty() => key,
+            _ => return,
+        };
+
+        self.bindings.vertex_buffers[0].update(ctx, &self.vertices);
+        self.bindings.index_buffer.update(ctx, &self.indices);
+        self.bindings.images = key.images.clone();
+
+        ctx.apply_pipeline(&key.pipeline);
+        ctx.apply_bindings(&self.bindings);
+        if !self.uniforms.is_empty() {
+            unsafe {
+                ctx.apply_uniforms_raw(self.uniforms.as_ptr(), self.uniforms.len() * 4);
+            }
+        }
+        ctx.draw(0, self.indices.len() as i32, 1);
+
+        self.vertices.clear();
+        self.indices.clear();
+    }
+}
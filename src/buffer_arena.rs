@@ -0,0 +1,139 @@
+use crate::graphics::*;
+
+/// Allocations start on multiples of this, as WebGL wants for vertex attribute offsets
+const ALIGN: usize = 4;
+
+struct Page {
+    buffer: Buffer,
+    /// Free ranges as offset and size, sorted by offset, never adjacent
+    free: Vec<(usize, usize)>,
+}
+
+/// Packs many small meshes into a few big GL buffers, pages, for static geometry
+/// like the chunks of a tile world: fewer buffers to create and to switch between.
+///
+/// `allocate` returns a slice of a page, see `BufferSlice`, to put in `Bindings`.
+/// Freed ranges are merged with their free neighbours so the space can be reused by
+/// bigger meshes. Meshes are never moved, the slices stay valid until freed.
+///
+/// The pages are ordinary buffers: after a context loss they are among the lost
+/// resources and the arena has to be created again.
+pub struct BufferArena {
+    buffer_type: BufferType,
+    index_type: Option<IndexType>,
+    page_size: usize,
+    pages: Vec<Page>,
+}
+
+impl BufferArena {
+    /// Arena of `page_size` bytes pages, index buffer pages hold `u16`s.
+    pub fn new(buffer_type: BufferType, page_size: usize) -> BufferArena {
+        let index_type = match buffer_type {
+            BufferType::IndexBuffer => Some(IndexType::Short),
+            BufferType::VertexBuffer => None,
+        };
+        BufferArena {
+            buffer_type,
+            index_type,
+            page_size,
+            pages: vec![],
+        }
+    }
+
+    /// Arena of index buffers with indices of `index_type`.
+    pub fn with_index_type(index_type: IndexType, page_size: usize) -> BufferArena {
+        BufferArena {
+            index_type: Some(index_type),
+            ..BufferArena::new(BufferType::IndexBuffer, page_size)
+        }
+    }
+
+    /// Copy `data` into a page and return the slice holding it. A mesh bigger
+    /// than the page size gets a page of its own.
+    pub fn allocate<T: std::fmt::Debug>(&mut self, ctx: &mut Context, data: &[T]) -> Buffer {
+        let size = std::mem::size_of_val(data);
+        let aligned = (size.max(1) + ALIGN - 1) / ALIGN * ALIGN;
+
+        let found = self.pages.iter_mut().enumerate().find_map(|(n, page)| {
+            let free = page.free.iter().position(|(_, free)| *free >= aligned)?;
+            Some((n, free))
+        });
+        let (n, free) = match found {
+            Some(found) => found,
+            None => {
+                let page_size = self.page_size.max(aligned);
+                let buffer = Buffer::empty(
+                    ctx,
+                    self.buffer_type,
+                    self.index_type,
+                    Usage::Immutable,
+                    page_size,
+                );
+                self.pages.push(Page {
+                    buffer,
+                    free: vec![(0, page_size)],
+                });
+                (self.pages.len() - 1, 0)
+            }
+        };
+
+        let page = &mut self.pages[n];
+        let (offset, free_size) = page.free[free];
+        if free_size == aligned {
+            page.free.remove(free);
+        } else {
+            page.free[free] = (offset + aligned, free_size - aligned);
+        }
+
+        let slice = page.buffer.slice(offset, size);
+        if size != 0 {
+            slice.update(ctx, data);
+        }
+        slice
+    }
+
+    /// Give the space of a slice from `allocate` back, for other meshes.
+    pub fn free(&mut self, slice: Buffer) {
+        let page = self
+            .pages
+            .iter_mut()
+            .find(|page| page.buffer.same_buffer(&slice))
+            .expect("Buffer not allocated from this arena");
+        let offset = slice.offset();
+        let size = (slice.size().max(1) + ALIGN - 1) / ALIGN * ALIGN;
+
+        let n = page
+            .free
+            .iter()
+            .position(|(free, _)| *free > offset)
+            .unwrap_or_else(|| page.free.len());
+        debug_assert!(
+            n == 0 || page.free[n - 1].0 + page.free[n - 1].1 <= offset,
+            "Buffer slice freed twice"
+        );
+        page.free.insert(n, (offset, size));
+
+        // merge with the next free range, then with the previous one
+        if n + 1 < page.free.len() && offset + size == page.free[n + 1].0 {
+            page.free[n].1 += page.free[n + 1].1;
+            page.free.remove(n + 1);
+        }
+        if n > 0 && page.free[n - 1].0 + page.free[n - 1].1 == offset {
+            page.free[n - 1].1 += page.free[n].1;
+            page.free.remove(n);
+        }
+    }
+
+    /// The buffers meshes are allocated in, e.g. to group draws by page.
+    pub fn pages(&self) -> impl Iterator<Item = Buffer> + '_ {
+        self.pages.iter().map(|page| page.buffer)
+    }
+
+    /// Bytes allocated to meshes, counting alignment, over all pages.
+    pub fn used(&self) -> usize {
+        self.pages
+            .iter()
+            .map(|page| page.buffer.size() - page.free.iter().map(|(_, size)| size).sum::<usize>())
+            .sum()
+    }
+}
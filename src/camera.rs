@@ -0,0 +1,605 @@
+//! Webcam capture, with the `camera` feature: the frames of a camera in a texture,
+//! e.g. for AR or for video effects.
+//!
+//! On Linux frames come from V4L2, in YUYV, and are converted to RGBA on a background
+//! thread. On the web the browser's getUserMedia asks the user for the permission,
+//! and the frames are copied to the texture on the GPU.
+//!
+//! Windows and Apple platforms are not implemented yet: there is no Media Foundation
+//! or AVFoundation backend, `devices` finds no camera and `Camera::open` returns
+//! `Error::Unsupported` there, as on Android.
+//!
+//! ```ignore
+//! // once
+//! let device = camera::devices().into_iter().next().unwrap();
+//! let mut camera = Camera::open(&device, 1280, 720).unwrap();
+//! // every frame, in EventHandler::update
+//! camera.update(ctx);
+//! // in EventHandler::draw, from the first frame on
+//! if let Some(texture) = camera.texture() {
+//!     // draw with the texture
+//! }
+//! ```
+
+use crate::{graphics::*, Context};
+
+#[derive(Debug)]
+pub enum Error {
+    /// No camera capture on this platform: Windows, macOS, iOS and Android for now
+    Unsupported,
+    /// The camera has no frame format miniquad converts, YUYV with V4L2
+    UnsupportedFormat,
+    IOError(std::io::Error),
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Error {
+        Error::IOError(error)
+    }
+}
+
+/// A camera `devices` found
+#[derive(Debug, Clone, PartialEq)]
+pub struct Device {
+    /// Name shown to users. On the web browsers only tell it once the page was
+    /// allowed to use a camera, until then it is "Camera 1", "Camera 2"...
+    pub name: String,
+    /// N of /dev/videoN, or index among the browser's video inputs
+    id: u32,
+}
+
+/// Cameras plugged in.
+pub fn devices() -> Vec<Device> {
+    backend::devices()
+}
+
+/// A camera capturing frames, see the module documentation.
+pub struct Camera {
+    capture: backend::Capture,
+    texture: Option<Texture>,
+}
+
+impl Camera {
+    /// Start capturing frames of about `width`x`height`, the camera picks the closest
+    /// size it has. `texture` gives the actual size once the first frame came.
+    pub fn open(device: &Device, width: u32, height: u32) -> Result<Camera, Error> {
+        Ok(Camera {
+            capture: backend::Capture::open(device.id, width, height)?,
+            texture: None,
+        })
+    }
+
+    /// Copy the newest frame to the texture if one came since the last call, true then.
+    /// Meant to be called once per frame.
+    pub fn update(&mut self, _ctx: &mut Context) -> bool {
+        let (width, height) = match self.capture.frame_size() {
+            Some(size) => size,
+            None => return false,
+        };
+        let texture = match self.texture {
+            Some(texture) if texture.width == width && texture.height == height => texture,
+            _ => {
+                if let Some(texture) = self.texture.take() {
+                    texture.delete();
+                }
+                let texture = Texture::new_render_texture(RenderTextureParams {
+                    width,
+                    height,
+                    ..Default::default()
+                });
+                self.texture = Some(texture);
+                texture
+            }
+        };
+        self.capture.copy_frame(texture)
+    }
+
+    /// Texture of the last frame `update` copied, `None` until the first one
+    pub fn texture(&self) -> Option<Texture> {
+        self.texture
+    }
+
+    /// False once the camera stopped: unplugged, or, on the web, the user didn't
+    /// allow the page to use it.
+    pub fn is_running(&self) -> bool {
+        self.capture.is_running()
+    }
+
+    /// Stop capturing and free the texture.
+    pub fn close(self) {
+        self.capture.close();
+        if let Some(texture) = self.texture {
+            texture.delete();
+        }
+    }
+}
+
+/// RGBA of YUYV pixels, BT.601 with video range, `stride` bytes per source row
+#[cfg(target_os = "linux")]
+fn yuyv_to_rgba(yuyv: &[u8], width: u32, height: u32, stride: usize, rgba: &mut Vec<u8>) {
+    rgba.clear();
+    for row in yuyv.chunks(stride).take(height as usize) {
+        for pair in row[..width as usize * 2].chunks_exact(4) {
+            let d = pair[1] as i32 - 128;
+            let e = pair[3] as i32 - 128;
+            for &y in [pair[0], pair[2]].iter() {
+                let c = 298 * (y as i32 - 16);
+                let clamp = |value: i32| (value >> 8).max(0).min(255) as u8;
+                rgba.extend_from_slice(&[
+                    clamp(c + 409 * e + 128),
+                    clamp(c - 100 * d - 208 * e + 128),
+                    clamp(c + 516 * d + 128),
+                    255,
+                ]);
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+use v4l2 as backend;
+
+#[cfg(target_arch = "wasm32")]
+use web as backend;
+
+#[cfg(not(any(target_os = "linux", target_arch = "wasm32")))]
+use unsupported as backend;
+
+#[cfg(target_os = "linux")]
+mod v4l2 {
+    use super::{yuyv_to_rgba, Device, Error};
+    use crate::graphics::Texture;
+
+    use std::fs::{File, OpenOptions};
+    use std::mem::size_of;
+    use std::os::raw::{c_int, c_long, c_ulong, c_void};
+    use std::os::unix::io::AsRawFd;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    extern "C" {
+        fn ioctl(fd: c_int, request: c_ulong, ...) -> c_int;
+        fn mmap(
+            addr: *mut c_void,
+            len: usize,
+            prot: c_int,
+            flags: c_int,
+            fd: c_int,
+            offset: c_long,
+        ) -> *mut c_void;
+        fn munmap(addr: *mut c_void, len: usize) -> c_int;
+    }
+
+    const PROT_READ: c_int = 1;
+    const MAP_SHARED: c_int = 1;
+
+    const V4L2_CAP_VIDEO_CAPTURE: u32 = 0x1;
+    const V4L2_CAP_STREAMING: u32 = 0x0400_0000;
+    const V4L2_CAP_DEVICE_CAPS: u32 = 0x8000_0000;
+    const V4L2_BUF_TYPE_VIDEO_CAPTURE: u32 = 1;
+    const V4L2_MEMORY_MMAP: u32 = 1;
+    const V4L2_FIELD_NONE: u32 = 1;
+    const V4L2_PIX_FMT_YUYV: u32 = u32::from_le_bytes(*b"YUYV");
+
+    const BUFFER_COUNT: u32 = 4;
+
+    #[repr(C)]
+    struct Capability {
+        driver: [u8; 16],
+        card: [u8; 32],
+        bus_info: [u8; 32],
+        version: u32,
+        capabilities: u32,
+        device_caps: u32,
+        reserved: [u32; 3],
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    struct PixFormat {
+        width: u32,
+        height: u32,
+        pixelformat: u32,
+        field: u32,
+        bytesperline: u32,
+        sizeimage: u32,
+        colorspace: u32,
+        priv_: u32,
+        flags: u32,
+        ycbcr_enc: u32,
+        quantization: u32,
+        xfer_func: u32,
+    }
+
+    /// The kernel's union has pointers, aligning it like them
+    #[repr(C)]
+    union FormatUnion {
+        pix: PixFormat,
+        raw: [u8; 200],
+        align: [usize; 0],
+    }
+
+    #[repr(C)]
+    struct Format {
+        type_: u32,
+        fmt: FormatUnion,
+    }
+
+    #[repr(C)]
+    struct RequestBuffers {
+        count: u32,
+        type_: u32,
+        memory: u32,
+        capabilities: u32,
+        reserved: u32,
+    }
+
+    #[repr(C)]
+    struct Timecode {
+        type_: u32,
+        flags: u32,
+        frames: u8,
+        seconds: u8,
+        minutes: u8,
+        hours: u8,
+        userbits: [u8; 4],
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    union BufferLocation {
+        offset: u32,
+        userptr: c_ulong,
+    }
+
+    #[repr(C)]
+    struct Buffer {
+        index: u32,
+        type_: u32,
+        bytesused: u32,
+        flags: u32,
+        field: u32,
+        timestamp: [c_long; 2],
+        timecode: Timecode,
+        sequence: u32,
+        memory: u32,
+        m: BufferLocation,
+        length: u32,
+        reserved2: u32,
+        request_fd: u32,
+    }
+
+    const fn ioc(dir: c_ulong, nr: c_ulong, size: usize) -> c_ulong {
+        (dir << 30) | ((size as c_ulong) << 16) | ((b'V' as c_ulong) << 8) | nr
+    }
+    const READ: c_ulong = 2;
+    const WRITE: c_ulong = 1;
+    const VIDIOC_QUERYCAP: c_ulong = ioc(READ, 0, size_of::<Capability>());
+    const VIDIOC_S_FMT: c_ulong = ioc(READ | WRITE, 5, size_of::<Format>());
+    const VIDIOC_REQBUFS: c_ulong = ioc(READ | WRITE, 8, size_of::<RequestBuffers>());
+    const VIDIOC_QUERYBUF: c_ulong = ioc(READ | WRITE, 9, size_of::<Buffer>());
+    const VIDIOC_QBUF: c_ulong = ioc(READ | WRITE, 15, size_of::<Buffer>());
+    const VIDIOC_DQBUF: c_ulong = ioc(READ | WRITE, 17, size_of::<Buffer>());
+    const VIDIOC_STREAMON: c_ulong = ioc(WRITE, 18, size_of::<c_int>());
+    const VIDIOC_STREAMOFF: c_ulong = ioc(WRITE, 19, size_of::<c_int>());
+
+    /// `ioctl` on a zeroed `T` set up by `f`, retried when interrupted by a signal
+    fn query<T>(file: &File, request: c_ulong, f: impl FnOnce(&mut T)) -> Result<T, Error> {
+        let mut arg: T = unsafe { std::mem::zeroed() };
+        f(&mut arg);
+        loop {
+            if unsafe { ioctl(file.as_raw_fd(), request, &mut arg as *mut T) } != -1 {
+                return Ok(arg);
+            }
+            let error = std::io::Error::last_os_error();
+            if error.kind() != std::io::ErrorKind::Interrupted {
+                return Err(Error::IOError(error));
+            }
+        }
+    }
+
+    fn open_device(id: u32) -> Result<File, Error> {
+        Ok(OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(format!("/dev/video{}", id))?)
+    }
+
+    pub fn devices() -> Vec<Device> {
+        let mut devices = vec![];
+        let entries = match std::fs::read_dir("/dev") {
+            Ok(entries) => entries,
+            Err(_) => return devices,
+        };
+        for entry in entries.flatten() {
+            let name = entry.file_name();
+            let id = match name.to_str().and_then(|name| name.strip_prefix("video")) {
+                Some(id) => match id.parse() {
+                    Ok(id) => id,
+                    Err(_) => continue,
+                },
+                None => continue,
+            };
+            let caps = match open_device(id)
+                .and_then(|file| query::<Capability>(&file, VIDIOC_QUERYCAP, |_| {}))
+            {
+                Ok(caps) => caps,
+                Err(_) => continue,
+            };
+            // devices also list their metadata nodes, with no frames
+            let capabilities = if caps.capabilities & V4L2_CAP_DEVICE_CAPS != 0 {
+                caps.device_caps
+            } else {
+                caps.capabilities
+            };
+            if capabilities & V4L2_CAP_VIDEO_CAPTURE == 0 || capabilities & V4L2_CAP_STREAMING == 0
+            {
+                continue;
+            }
+            let len = caps.card.iter().position(|&b| b == 0).unwrap_or(32);
+            devices.push(Device {
+                name: String::from_utf8_lossy(&caps.card[..len]).into_owned(),
+                id,
+            });
+        }
+        devices.sort_by_key(|device| device.id);
+        devices
+    }
+
+    /// Newest frame converted by the capture thread
+    #[derive(Default)]
+    struct Frame {
+        rgba: Vec<u8>,
+        fresh: bool,
+    }
+
+    pub struct Capture {
+        width: u32,
+        height: u32,
+        frame: Arc<Mutex<Frame>>,
+        running: Arc<AtomicBool>,
+    }
+
+    /// The mapped buffers, moved to the capture thread
+    struct Mapping(Vec<(*mut c_void, usize)>);
+
+    unsafe impl Send for Mapping {}
+
+    impl Drop for Mapping {
+        fn drop(&mut self) {
+            for &(ptr, len) in &self.0 {
+                unsafe {
+                    munmap(ptr, len);
+                }
+            }
+        }
+    }
+
+    impl Capture {
+        pub fn open(id: u32, width: u32, height: u32) -> Result<Capture, Error> {
+            let file = open_device(id)?;
+            let format = query::<Format>(&file, VIDIOC_S_FMT, |format| {
+                format.type_ = V4L2_BUF_TYPE_VIDEO_CAPTURE;
+                format.fmt.pix = PixFormat {
+                    width,
+                    height,
+                    pixelformat: V4L2_PIX_FMT_YUYV,
+                    field: V4L2_FIELD_NONE,
+                    ..unsafe { std::mem::zeroed() }
+                };
+            })?;
+            // the driver picks the closest format it has
+            let pix = unsafe { format.fmt.pix };
+            if pix.pixelformat != V4L2_PIX_FMT_YUYV {
+                return Err(Error::UnsupportedFormat);
+            }
+            let stride = (pix.bytesperline as usize).max(pix.width as usize * 2);
+
+            let requested = query::<RequestBuffers>(&file, VIDIOC_REQBUFS, |request| {
+                request.count = BUFFER_COUNT;
+                request.type_ = V4L2_BUF_TYPE_VIDEO_CAPTURE;
+                request.memory = V4L2_MEMORY_MMAP;
+            })?;
+            let mut mapping = Mapping(vec![]);
+            for index in 0..requested.count {
+                let buffer = query::<Buffer>(&file, VIDIOC_QUERYBUF, |buffer| {
+                    buffer.index = index;
+                    buffer.type_ = V4L2_BUF_TYPE_VIDEO_CAPTURE;
+                    buffer.memory = V4L2_MEMORY_MMAP;
+                })?;
+                let len = buffer.length as usize;
+                let ptr = unsafe {
+                    mmap(
+                        std::ptr::null_mut(),
+                        len,
+                        PROT_READ,
+                        MAP_SHARED,
+                        file.as_raw_fd(),
+                        buffer.m.offset as c_long,
+                    )
+                };
+                if ptr as isize == -1 {
+                    return Err(Error::IOError(std::io::Error::last_os_error()));
+                }
+                mapping.0.push((ptr, len));
+                query::<Buffer>(&file, VIDIOC_QBUF, |buffer| {
+                    buffer.index = index;
+                    buffer.type_ = V4L2_BUF_TYPE_VIDEO_CAPTURE;
+                    buffer.memory = V4L2_MEMORY_MMAP;
+                })?;
+            }
+            query::<c_int>(&file, VIDIOC_STREAMON, |type_| {
+                *type_ = V4L2_BUF_TYPE_VIDEO_CAPTURE as c_int;
+            })?;
+
+            let capture = Capture {
+                width: pix.width,
+                height: pix.height,
+                frame: Arc::new(Mutex::new(Frame::default())),
+                running: Arc::new(AtomicBool::new(true)),
+            };
+            let frame = capture.frame.clone();
+            let running = capture.running.clone();
+            let (width, height) = (pix.width, pix.height);
+            std::thread::spawn(move || {
+                let mut rgba = vec![];
+                while running.load(Ordering::Relaxed) {
+                    let buffer = match query::<Buffer>(&file, VIDIOC_DQBUF, |buffer| {
+                        buffer.type_ = V4L2_BUF_TYPE_VIDEO_CAPTURE;
+                        buffer.memory = V4L2_MEMORY_MMAP;
+                    }) {
+                        Ok(buffer) => buffer,
+                        Err(_) => break,
+                    };
+                    let (ptr, len) = mapping.0[buffer.index as usize];
+                    let used = (buffer.bytesused as usize).min(len);
+                    let yuyv = unsafe { std::slice::from_raw_parts(ptr as *const u8, used) };
+                    if used >= stride * (height as usize - 1) + width as usize * 2 {
+                        yuyv_to_rgba(yuyv, width, height, stride, &mut rgba);
+                        let mut frame = frame.lock().unwrap();
+                        std::mem::swap(&mut frame.rgba, &mut rgba);
+                        frame.fresh = true;
+                    }
+                    let index = buffer.index;
+                    if query::<Buffer>(&file, VIDIOC_QBUF, |buffer| {
+                        buffer.index = index;
+                        buffer.type_ = V4L2_BUF_TYPE_VIDEO_CAPTURE;
+                        buffer.memory = V4L2_MEMORY_MMAP;
+                    })
+                    .is_err()
+                    {
+                        break;
+                    }
+                }
+                running.store(false, Ordering::Relaxed);
+                let _ = query::<c_int>(&file, VIDIOC_STREAMOFF, |type_| {
+                    *type_ = V4L2_BUF_TYPE_VIDEO_CAPTURE as c_int;
+                });
+                // unmapped before the file closes
+                drop(mapping);
+            });
+            Ok(capture)
+        }
+
+        pub fn frame_size(&self) -> Option<(u32, u32)> {
+            if self.frame.lock().unwrap().fresh {
+                Some((self.width, self.height))
+            } else {
+                None
+            }
+        }
+
+        pub fn copy_frame(&mut self, texture: Texture) -> bool {
+            let mut frame = self.frame.lock().unwrap();
+            if !frame.fresh {
+                return false;
+            }
+            frame.fresh = false;
+            texture.update_part(0, 0, self.width, self.height, &frame.rgba);
+            true
+        }
+
+        pub fn is_running(&self) -> bool {
+            self.running.load(Ordering::Relaxed)
+        }
+
+        /// The thread stops once the frame it waits for comes.
+        pub fn close(self) {
+            self.running.store(false, Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod web {
+    use super::{Device, Error};
+    use crate::graphics::Texture;
+
+    pub fn devices() -> Vec<Device> {
+        let count = unsafe { sapp::camera_device_count() };
+        (0..count)
+            .map(|id| {
+                let mut name = [0u8; 256];
+                let len = unsafe {
+                    sapp::camera_device_name(id, name.as_mut_ptr() as _, name.len() as u32)
+                };
+                Device {
+                    name: String::from_utf8_lossy(&name[..len as usize]).into_owned(),
+                    id,
+                }
+            })
+            .collect()
+    }
+
+    pub struct Capture {
+        id: i32,
+    }
+
+    impl Capture {
+        /// The permission is asked now, the frames come once the user answered.
+        pub fn open(id: u32, width: u32, height: u32) -> Result<Capture, Error> {
+            Ok(Capture {
+                id: unsafe { sapp::camera_open(id, width, height) },
+            })
+        }
+
+        pub fn frame_size(&self) -> Option<(u32, u32)> {
+            if unsafe { sapp::camera_state(self.id) } != 1 {
+                return None;
+            }
+            let width = unsafe { sapp::camera_width(self.id) };
+            let height = unsafe { sapp::camera_height(self.id) };
+            if width == 0 || height == 0 {
+                return None;
+            }
+            Some((width, height))
+        }
+
+        pub fn copy_frame(&mut self, texture: Texture) -> bool {
+            unsafe { sapp::camera_update(self.id, texture.gl_internal_id()) }
+        }
+
+        pub fn is_running(&self) -> bool {
+            let state = unsafe { sapp::camera_state(self.id) };
+            state != -1
+        }
+
+        pub fn close(self) {
+            unsafe { sapp::camera_close(self.id) }
+        }
+    }
+}
+
+/// Windows (Media Foundation), macOS and iOS (AVFoundation) and Android backends
+/// still to be written
+#[cfg(not(any(target_os = "linux", target_arch = "wasm32")))]
+mod unsupported {
+    use super::{Device, Error};
+    use crate::graphics::Texture;
+
+    pub fn devices() -> Vec<Device> {
+        vec![]
+    }
+
+    pub struct Capture;
+
+    impl Capture {
+        pub fn open(_id: u32, _width: u32, _height: u32) -> Result<Capture, Error> {
+            Err(Error::Unsupported)
+        }
+
+        pub fn frame_size(&self) -> Option<(u32, u32)> {
+            None
+        }
+
+        pub fn copy_frame(&mut self, _texture: Texture) -> bool {
+            false
+        }
+
+        pub fn is_running(&self) -> bool {
+            false
+        }
+
+        pub fn close(self) {}
+    }
+}
@@ -0,0 +1,150 @@
+use crate::{gl::*, graphics::*, Color};
+
+/// How depths go from clip space to the depth buffer, from `Context::clip_space`,
+/// with the projections, depth test and depth clear to match.
+///
+/// With reversed Z the near plane is at depth 1 and the far one at 0: floats are
+/// most precise near 0, where perspective crowds the distant depths, so distant
+/// surfaces stop fighting. The gain is for float depth buffers, and needs
+/// `depth_zero_to_one`: GL's usual -1..1 clip space range loses the precision again
+/// in the remapping to 0..1.
+///
+/// ```ignore
+/// let clip = ctx.clip_space();
+/// let proj = clip.perspective(60f32.to_radians(), aspect, 0.1, f32::INFINITY);
+/// let params = PipelineParams {
+///     depth_test: clip.depth_test(),
+///     depth_write: true,
+///     ..Default::default()
+/// };
+/// ctx.begin_default_pass(clip.clear(Color::new(0., 0., 0., 1.)));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ClipSpace {
+    /// Near plane at depth 1, far plane at 0
+    pub reversed_z: bool,
+    /// Clip space depths go from 0 to 1 rather than GL's usual -1 to 1,
+    /// with `glClipControl`
+    pub depth_zero_to_one: bool,
+}
+
+impl ClipSpace {
+    /// Depth buffer value of the far plane, what depths are cleared to
+    pub fn far_depth(&self) -> f32 {
+        if self.reversed_z {
+            0.
+        } else {
+            1.
+        }
+    }
+
+    /// Comparison of the depth test keeping the nearest surfaces
+    pub fn depth_test(&self) -> Comparison {
+        if self.reversed_z {
+            Comparison::GreaterOrEqual
+        } else {
+            Comparison::LessOrEqual
+        }
+    }
+
+    /// Clear color to `color` and depth to `far_depth`
+    pub fn clear(&self, color: Color) -> PassAction {
+        PassAction::Clear {
+            color: Some(color.into()),
+            depth: Some(self.far_depth()),
+            stencil: None,
+        }
+    }
+
+    /// Clip space depths of the near and far planes
+    fn depth_range(&self) -> (f32, f32) {
+        match (self.reversed_z, self.depth_zero_to_one) {
+            (false, false) => (-1., 1.),
+            (false, true) => (0., 1.),
+            (true, false) => (1., -1.),
+            (true, true) => (1., 0.),
+        }
+    }
+
+    /// Right handed perspective projection looking along -Z, column major, like
+    /// `gluPerspective` for this clip space. `fov_y` in radians. `far` may be
+    /// `f32::INFINITY`, best with reversed Z.
+    pub fn perspective(&self, fov_y: f32, aspect: f32, near: f32, far: f32) -> [f32; 16] {
+        let f = 1. / (fov_y / 2.).tan();
+        let (near_z, far_z) = self.depth_range();
+        // clip z = a * view z + b, w = -view z
+        let a = if far.is_infinite() {
+            -far_z
+        } else {
+            (near_z * near - far_z * far) / (far - near)
+        };
+        let b = (near_z + a) * near;
+        #[rustfmt::skip]
+        let proj = [
+            f / aspect, 0., 0., 0.,
+            0., f, 0., 0.,
+            0., 0., a, -1.,
+            0., 0., b, 0.,
+        ];
+        proj
+    }
+
+    /// Right handed orthographic projection looking along -Z, column major, like
+    /// `glOrtho` for this clip space.
+    pub fn orthographic(
+        &self,
+        left: f32,
+        right: f32,
+        bottom: f32,
+        top: f32,
+        near: f32,
+        far: f32,
+    ) -> [f32; 16] {
+        let (near_z, far_z) = self.depth_range();
+        // clip z = a * view z + b
+        let a = (near_z - far_z) / (far - near);
+        let b = near_z + a * near;
+        #[rustfmt::skip]
+        let proj = [
+            2. / (right - left), 0., 0., 0.,
+            0., 2. / (top - bottom), 0., 0.,
+            0., 0., a, 0.,
+            -(right + left) / (right - left), -(top + bottom) / (top - bottom), b, 1.,
+        ];
+        proj
+    }
+}
+
+impl Context {
+    /// Switch to reversed Z, or back, see `ClipSpace`. Also sets clip space depths
+    /// to 0..1 with `Features::clip_control`, on desktop GL 4.5 mostly, not on WebGL.
+    /// Projections, depth tests and depth clears are the app's, made for
+    /// `clip_space`; miniquad's own helpers, like `ShadowMap`, follow it when created
+    /// afterwards, so this is best set once, with `Conf::reversed_z`.
+    pub fn set_reversed_z(&mut self, reversed_z: bool) {
+        self.reversed_z = reversed_z;
+        self.apply_clip_control();
+    }
+
+    pub fn clip_space(&self) -> ClipSpace {
+        ClipSpace {
+            reversed_z: self.reversed_z,
+            depth_zero_to_one: self.reversed_z && self.features().clip_control,
+        }
+    }
+
+    /// Set the GL clip control matching `clip_space`, again after a context loss
+    pub(crate) fn apply_clip_control(&self) {
+        #[cfg(not(target_arch = "wasm32"))]
+        if self.features().clip_control {
+            let depth = if self.clip_space().depth_zero_to_one {
+                GL_ZERO_TO_ONE
+            } else {
+                GL_NEGATIVE_ONE_TO_ONE
+            };
+            unsafe {
+                glClipControl(GL_LOWER_LEFT, depth);
+            }
+        }
+    }
+}
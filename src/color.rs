@@ -0,0 +1,122 @@
+/// RGBA color, every component from 0.0 to 1.0.
+///
+/// Components are in the space of the framebuffer they end up in: sRGB encoded, as long
+/// as framebuffers are not sRGB ones, and just like the bytes of textures loaded from images.
+/// `to_linear`/`from_linear` convert for lighting or blending math done in linear space.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Color {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+impl Color {
+    pub const WHITE: Color = Color::new(1., 1., 1., 1.);
+    pub const BLACK: Color = Color::new(0., 0., 0., 1.);
+    pub const TRANSPARENT: Color = Color::new(0., 0., 0., 0.);
+
+    pub const fn new(r: f32, g: f32, b: f32, a: f32) -> Color {
+        Color { r, g, b, a }
+    }
+
+    pub fn from_rgba8(r: u8, g: u8, b: u8, a: u8) -> Color {
+        Color::new(
+            r as f32 / 255.,
+            g as f32 / 255.,
+            b as f32 / 255.,
+            a as f32 / 255.,
+        )
+    }
+
+    pub fn to_rgba8(&self) -> [u8; 4] {
+        let byte = |value: f32| (value.max(0.).min(1.) * 255.).round() as u8;
+        [byte(self.r), byte(self.g), byte(self.b), byte(self.a)]
+    }
+
+    /// Opaque color from a 0xRRGGBB number
+    pub fn from_hex(hex: u32) -> Color {
+        Color::from_rgba8((hex >> 16) as u8, (hex >> 8) as u8, hex as u8, 255)
+    }
+
+    /// Parse "#rgb", "#rgba", "#rrggbb" or "#rrggbbaa", the "#" is optional
+    pub fn parse_hex(hex: &str) -> Option<Color> {
+        let hex = hex.trim_start_matches('#');
+        let digit = |i: usize| -> Option<u8> {
+            hex.get(i..i + 1)
+                .and_then(|digit| u8::from_str_radix(digit, 16).ok())
+        };
+        let short = |i: usize| digit(i).map(|value| value * 17);
+        let long = |i: usize| Some(digit(i * 2)? * 16 + digit(i * 2 + 1)?);
+
+        let (r, g, b, a) = match hex.len() {
+            3 => (short(0)?, short(1)?, short(2)?, 255),
+            4 => (short(0)?, short(1)?, short(2)?, short(3)?),
+            6 => (long(0)?, long(1)?, long(2)?, 255),
+            8 => (long(0)?, long(1)?, long(2)?, long(3)?),
+            _ => return None,
+        };
+        Some(Color::from_rgba8(r, g, b, a))
+    }
+
+    /// Decode sRGB components to linear ones, alpha is left as is
+    pub fn to_linear(&self) -> Color {
+        Color::new(
+            srgb_to_linear(self.r),
+            srgb_to_linear(self.g),
+            srgb_to_linear(self.b),
+            self.a,
+        )
+    }
+
+    /// Encode linear components to sRGB ones, alpha is left as is
+    pub fn from_linear(linear: Color) -> Color {
+        Color::new(
+            linear_to_srgb(linear.r),
+            linear_to_srgb(linear.g),
+            linear_to_srgb(linear.b),
+            linear.a,
+        )
+    }
+}
+
+impl From<(f32, f32, f32, f32)> for Color {
+    fn from((r, g, b, a): (f32, f32, f32, f32)) -> Color {
+        Color::new(r, g, b, a)
+    }
+}
+
+impl From<Color> for (f32, f32, f32, f32) {
+    fn from(color: Color) -> (f32, f32, f32, f32) {
+        (color.r, color.g, color.b, color.a)
+    }
+}
+
+impl From<[f32; 4]> for Color {
+    fn from([r, g, b, a]: [f32; 4]) -> Color {
+        Color::new(r, g, b, a)
+    }
+}
+
+impl From<Color> for [f32; 4] {
+    fn from(color: Color) -> [f32; 4] {
+        [color.r, color.g, color.b, color.a]
+    }
+}
+
+/// sRGB transfer function, exact piecewise version
+pub fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+pub fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1. / 2.4) - 0.055
+    }
+}
@@ -1,5 +1,7 @@
 //use crate::goodies::loading_page::LoadingPage;
 
+use crate::Color;
+
 #[derive(Debug)]
 pub enum Cache {
     /// No preloading at all, filesystem::open will always panic.
@@ -22,10 +24,163 @@ pub enum Loading {
     //Custom(Box<dyn LoadingPage>),
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Orientation {
+    /// Follow the device rotation
+    Any,
+    Landscape,
+    Portrait,
+}
+
+/// Where the mouse cursor may go, see `Context::confine_cursor`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum CursorConfinement {
+    /// Anywhere on the screen
+    None,
+    /// Inside the window
+    Window,
+    /// Inside a rectangle of the window, in the coordinates of mouse events
+    Rect { x: f32, y: f32, w: f32, h: f32 },
+}
+
+/// Resolution and refresh rate of a monitor, see `Context::display_modes`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct DisplayMode {
+    pub width: u32,
+    pub height: u32,
+    /// In Hz, 0 when the driver does not say
+    pub refresh_rate: u32,
+}
+
+/// Fixed internal resolution for pixel art. `Context::begin_default_pass` then draws into
+/// a `width`x`height` render target, scaled up to the window at the end of the frame,
+/// centered between bars. `Context::canvas_size` is the size to lay out for, and
+/// `Context::screen_to_canvas` maps mouse and touch positions into the canvas.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PixelArt {
+    pub width: u32,
+    pub height: u32,
+    pub scaling: PixelScaling,
+    /// Color of the bars around the canvas, may be changed later with
+    /// `Context::set_letterbox_color`
+    pub letterbox_color: Color,
+}
+
+/// Unattended installations, like museum exhibits: the screen is kept on, see
+/// `Context::set_keep_awake`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Kiosk {
+    /// Swallow system shortcuts leaving the app, see `Context::set_shortcut_capture`
+    pub capture_shortcuts: bool,
+}
+
+/// How the `PixelArt` canvas is scaled up to the window. When the window is smaller
+/// than the canvas all of them shrink it like `Fit`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PixelScaling {
+    /// The largest whole number scale fitting both axes: square pixels, all of the same
+    /// size, at the cost of wider bars.
+    Integer,
+    /// The largest whole number scale of each axis on its own: pixels may get stretched,
+    /// e.g. 2x3, but are all of the same size and the bars are thinner.
+    IntegerPerAxis,
+    /// The largest scale fitting both axes, fractional: square pixels, but some one screen
+    /// pixel wider or taller than others.
+    Fit,
+}
+
+/// Which row of a render target texture an offscreen pass fills with the top of the picture.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RenderTargetOrigin {
+    /// Row 0 is the top, just like textures loaded from images, so render targets are
+    /// sampled with the same texture coordinates as any other texture.
+    /// Offscreen passes flip clip space Y to get there.
+    TopLeft,
+    /// Plain GL behaviour: row 0 is the bottom, sampling a render target with
+    /// image texture coordinates shows it upside down.
+    BottomLeft,
+}
+
+/// WebGL context creation attributes, ignored on other platforms.
+/// Defaults are the browser ones, except depth and stencil which follow `Conf`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct WebGlAttributes {
+    /// Whether the canvas has an alpha channel. When false the canvas is opaque
+    /// and covers the page content behind it, whatever alpha the app draws.
+    pub alpha: bool,
+    /// Whether the page treats the canvas colors as already multiplied by alpha.
+    /// Set to false when drawing with straight alpha blending to get the right colors
+    /// where the canvas is composited over HTML content.
+    pub premultiplied_alpha: bool,
+    /// Keep the canvas content after it was presented instead of clearing it,
+    /// needed for reading the canvas back outside of the frame callback.
+    /// May cost performance on some browsers.
+    pub preserve_drawing_buffer: bool,
+    /// Multisampled default framebuffer, if the browser supports it
+    pub antialias: bool,
+    /// Low latency hint: the canvas may be presented without waiting for the page
+    /// compositor, at the cost of possible tearing.
+    pub desynchronized: bool,
+}
+
+impl Default for WebGlAttributes {
+    fn default() -> WebGlAttributes {
+        WebGlAttributes {
+            alpha: true,
+            premultiplied_alpha: true,
+            preserve_drawing_buffer: false,
+            antialias: true,
+            desynchronized: false,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Conf {
     pub cache: Cache,
     pub loading: Loading,
+    /// Orientation lock applied on start, may be changed later with `Context::set_orientation_lock`
+    pub orientation: Orientation,
+    /// Y convention of offscreen passes, see `Context::render_target_origin`
+    pub render_target_origin: RenderTargetOrigin,
+    /// Start with reversed Z depths, see `Context::set_reversed_z`
+    pub reversed_z: bool,
+    /// Depth buffer bits of the default framebuffer, 0 for none, e.g. for pure 2D.
+    /// The closest available format is picked, `Features::depth_bits` tells what was obtained.
+    pub depth_bits: u8,
+    /// Stencil buffer bits of the default framebuffer, 0 for none.
+    pub stencil_bits: u8,
+    pub webgl_attributes: WebGlAttributes,
+    /// Render at the full resolution of high dpi displays, see `Context::dpi_scale`.
+    /// Otherwise the OS or browser upscales the picture.
+    pub high_dpi: bool,
+    /// Draw at a fixed resolution, scaled up to the window, see `PixelArt`.
+    pub pixel_art: Option<PixelArt>,
+    /// Wait for the display refresh to present frames. Browsers always do.
+    pub vsync: bool,
+    /// Check for GL errors after each miniquad call and at the end of each frame,
+    /// and print them. Slow, as it waits for the GPU.
+    pub debug_gl: bool,
+    /// Start with the HUD of `Context::set_debug_overlay` shown
+    pub debug_overlay: bool,
+    /// Ask for a GL context where reading buffers out of range, like a vertex index past
+    /// the end of a buffer, returns zeros or values within the buffer instead of crashing
+    /// the GPU or reading other memory, for shaders and meshes coming from users.
+    /// Costs a little performance. Drivers without `ARB_robustness` ignore it,
+    /// `Features::robust_buffer_access` tells. WebGL always works this way.
+    pub robust_buffer_access: bool,
+    /// Start with the window kept out of screen captures, see
+    /// `Context::set_capture_protection`. Not among the runtime overrides.
+    pub capture_protection: bool,
+    /// Start in kiosk mode, see `Kiosk`. Not among the runtime overrides.
+    pub kiosk: Option<Kiosk>,
+    /// Send Ctrl + mouse wheel only as `magnify_event`, not as `mouse_wheel_event` too,
+    /// for apps where the wheel scrolls and pinches zoom. Not among the runtime overrides.
+    pub ctrl_wheel_only_magnifies: bool,
+    /// Let `MINIQUAD_*` environment variables and `--miniquad-*` arguments override
+    /// this configuration, see `Conf::apply_overrides`. Apps parsing their command line
+    /// strictly, or shipping with fixed settings, turn it off.
+    pub runtime_overrides: bool,
 }
 
 impl Default for Conf {
@@ -33,7 +188,82 @@ impl Default for Conf {
         Conf {
             cache: Cache::No,
             loading: Loading::No,
+            orientation: Orientation::Any,
+            render_target_origin: RenderTargetOrigin::TopLeft,
+            reversed_z: false,
+            depth_bits: 24,
+            stencil_bits: 8,
+            webgl_attributes: WebGlAttributes::default(),
+            high_dpi: false,
+            pixel_art: None,
+            vsync: true,
+            debug_gl: false,
+            debug_overlay: false,
+            robust_buffer_access: false,
+            capture_protection: false,
+            kiosk: None,
+            ctrl_wheel_only_magnifies: false,
+            runtime_overrides: true,
+        }
+    }
+}
+
+impl Conf {
+    /// Override fields with environment variables and command line arguments, to debug
+    /// a build in the field without rebuilding it. `miniquad::start` calls it when
+    /// `runtime_overrides` is set.
+    ///
+    /// A field is set by `MINIQUAD_` and its name in capitals, or by `--miniquad-` and its
+    /// name with dashes, which wins: `MINIQUAD_VSYNC=0` or `--miniquad-vsync=0`.
+    /// The fields are `vsync`, `debug_gl`, `debug_overlay`, `robust_buffer_access`,
+    /// `high_dpi`, `reversed_z`, switched with `1`, `true`, `on` or `0`, `false`, `off`, a bare argument switching on,
+    /// `depth_bits`, `stencil_bits`, and `render_target_origin` as `top_left` or
+    /// `bottom_left`. Unknown options and values are reported and ignored.
+    /// There are neither on the web.
+    pub fn apply_overrides(&mut self) {
+        let variables = std::env::vars_os().filter_map(|(name, value)| {
+            let option = name.to_str()?.strip_prefix("MINIQUAD_")?.to_lowercase();
+            Some((option, value.into_string().ok()?))
+        });
+        let arguments = std::env::args_os().skip(1).filter_map(|argument| {
+            let option = argument.to_str()?.strip_prefix("--miniquad-")?;
+            let mut parts = option.splitn(2, '=');
+            let name = parts.next()?.replace('-', "_");
+            Some((name, parts.next().unwrap_or("1").to_string()))
+        });
+        let options: Vec<(String, String)> = variables.chain(arguments).collect();
+        for (name, value) in options {
+            if self.apply_override(&name, &value).is_none() {
+                eprintln!("Ignoring miniquad option {}={}", name, value);
+            }
+        }
+    }
+
+    fn apply_override(&mut self, name: &str, value: &str) -> Option<()> {
+        let switch = || match value.to_lowercase().as_str() {
+            "1" | "true" | "on" => Some(true),
+            "0" | "false" | "off" => Some(false),
+            _ => None,
+        };
+        match name {
+            "vsync" => self.vsync = switch()?,
+            "debug_gl" => self.debug_gl = switch()?,
+            "debug_overlay" => self.debug_overlay = switch()?,
+            "robust_buffer_access" => self.robust_buffer_access = switch()?,
+            "high_dpi" => self.high_dpi = switch()?,
+            "depth_bits" => self.depth_bits = value.parse().ok()?,
+            "stencil_bits" => self.stencil_bits = value.parse().ok()?,
+            "reversed_z" => self.reversed_z = switch()?,
+            "render_target_origin" => {
+                self.render_target_origin = match value {
+                    "top_left" => RenderTargetOrigin::TopLeft,
+                    "bottom_left" => RenderTargetOrigin::BottomLeft,
+                    _ => return None,
+                }
+            }
+            _ => return None,
         }
+        Some(())
     }
 }
 
@@ -0,0 +1,462 @@
+//! DDS and KTX2 texture containers: GPU-ready data with mip chains and cubemap faces.
+//!
+//! Only the container is parsed, the data is uploaded as is, so block compressed
+//! formats need GPU support, see `Context::features`.
+//! sRGB formats are loaded as their UNORM counterparts, the same way `Texture::from_rgba8`
+//! treats its bytes.
+
+use crate::graphics::Features;
+
+#[derive(Debug)]
+pub enum Error {
+    /// Neither a DDS nor a KTX2 file
+    UnknownContainer,
+    Corrupted,
+    /// Valid container, but uses a feature or format this loader does not support
+    Unsupported(&'static str),
+    /// The GPU can't sample this format
+    FormatNotSupported(TextureFormat),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextureFormat {
+    RGBA8,
+    /// BC1 (DXT1) without alpha
+    BC1,
+    /// BC1 (DXT1) with 1-bit alpha
+    BC1RGBA,
+    /// BC2 (DXT3)
+    BC2,
+    /// BC3 (DXT5)
+    BC3,
+    BC4,
+    BC5,
+    BC7,
+    ETC2RGB,
+    /// ETC2 with 1-bit alpha
+    ETC2RGBA1,
+    /// ETC2 with EAC alpha
+    ETC2RGBA,
+    ASTC4x4,
+}
+
+impl TextureFormat {
+    /// Bytes per 4x4 block, `None` for uncompressed formats
+    pub fn block_size(&self) -> Option<usize> {
+        use TextureFormat::*;
+
+        match self {
+            RGBA8 => None,
+            BC1 | BC1RGBA | BC4 | ETC2RGB | ETC2RGBA1 => Some(8),
+            BC2 | BC3 | BC5 | BC7 | ETC2RGBA | ASTC4x4 => Some(16),
+        }
+    }
+
+    /// Size in bytes of one image of the given size
+    pub fn image_size(&self, width: u32, height: u32) -> usize {
+        let (width, height) = (width as usize, height as usize);
+        match self.block_size() {
+            None => width * height * 4,
+            Some(block_size) => ((width + 3) / 4) * ((height + 3) / 4) * block_size,
+        }
+    }
+
+    pub fn is_supported(&self, features: &Features) -> bool {
+        use TextureFormat::*;
+
+        match self {
+            RGBA8 => true,
+            BC1 | BC1RGBA | BC2 | BC3 => features.texture_compression_s3tc,
+            BC4 | BC5 => features.texture_compression_rgtc,
+            BC7 => features.texture_compression_bptc,
+            ETC2RGB | ETC2RGBA1 | ETC2RGBA => features.texture_compression_etc2,
+            ASTC4x4 => features.texture_compression_astc,
+        }
+    }
+
+    /// Internal format for glCompressedTexImage2D
+    pub(crate) fn gl_compressed_format(&self) -> Option<u32> {
+        use TextureFormat::*;
+
+        match self {
+            RGBA8 => None,
+            BC1 => Some(0x83f0),
+            BC1RGBA => Some(0x83f1),
+            BC2 => Some(0x83f2),
+            BC3 => Some(0x83f3),
+            BC4 => Some(0x8dbb),
+            BC5 => Some(0x8dbd),
+            BC7 => Some(0x8e8c),
+            ETC2RGB => Some(0x9274),
+            ETC2RGBA1 => Some(0x9276),
+            ETC2RGBA => Some(0x9278),
+            ASTC4x4 => Some(0x93b0),
+        }
+    }
+}
+
+pub struct Container {
+    pub width: u32,
+    pub height: u32,
+    pub format: TextureFormat,
+    /// 1 for plain textures, 6 for cubemaps, in +X, -X, +Y, -Y, +Z, -Z order
+    pub faces: usize,
+    /// `levels[mip][face]`, largest mip level first
+    pub levels: Vec<Vec<Vec<u8>>>,
+}
+
+impl Container {
+    /// Number of mip levels a full chain down to 1x1 would have
+    pub fn full_mip_chain_len(&self) -> usize {
+        32 - self.width.max(self.height).leading_zeros() as usize
+    }
+}
+
+const DDS_MAGIC: &[u8] = b"DDS ";
+const KTX2_MAGIC: &[u8] = &[
+    0xab, b'K', b'T', b'X', b' ', b'2', b'0', 0xbb, 0x0d, 0x0a, 0x1a, 0x0a,
+];
+
+pub fn is_container(bytes: &[u8]) -> bool {
+    bytes.starts_with(DDS_MAGIC) || bytes.starts_with(KTX2_MAGIC)
+}
+
+/// Parse a DDS or KTX2 file, the container type is guessed from the header
+pub fn parse(bytes: &[u8]) -> Result<Container, Error> {
+    let container = if bytes.starts_with(DDS_MAGIC) {
+        parse_dds(bytes)?
+    } else if bytes.starts_with(KTX2_MAGIC) {
+        parse_ktx2(bytes)?
+    } else {
+        return Err(Error::UnknownContainer);
+    };
+
+    if container.width == 0 || container.height == 0 || container.levels.is_empty() {
+        return Err(Error::Corrupted);
+    }
+    if container.levels.len() > container.full_mip_chain_len() {
+        return Err(Error::Corrupted);
+    }
+    Ok(container)
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> Result<u32, Error> {
+    let b = bytes.get(offset..offset + 4).ok_or(Error::Corrupted)?;
+    Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+fn u64_at(bytes: &[u8], offset: usize) -> Result<usize, Error> {
+    let low = u32_at(bytes, offset)? as u64;
+    let high = u32_at(bytes, offset + 4)? as u64;
+    Ok(((high << 32) | low) as usize)
+}
+
+fn slice(bytes: &[u8], offset: usize, len: usize) -> Result<&[u8], Error> {
+    bytes
+        .get(offset..offset.checked_add(len).ok_or(Error::Corrupted)?)
+        .ok_or(Error::Corrupted)
+}
+
+fn parse_dds(bytes: &[u8]) -> Result<Container, Error> {
+    const DDSD_MIPMAPCOUNT: u32 = 0x20000;
+    const DDPF_ALPHAPIXELS: u32 = 0x1;
+    const DDPF_FOURCC: u32 = 0x4;
+    const DDPF_RGB: u32 = 0x40;
+    const DDSCAPS2_CUBEMAP: u32 = 0x200;
+    const DDSCAPS2_CUBEMAP_ALLFACES: u32 = 0xfc00;
+    const DDSCAPS2_VOLUME: u32 = 0x200000;
+    const DDS_RESOURCE_MISC_TEXTURECUBE: u32 = 0x4;
+
+    if u32_at(bytes, 4)? != 124 {
+        return Err(Error::Corrupted);
+    }
+    let flags = u32_at(bytes, 8)?;
+    let height = u32_at(bytes, 12)?;
+    let width = u32_at(bytes, 16)?;
+    let mip_count = if flags & DDSD_MIPMAPCOUNT != 0 {
+        u32_at(bytes, 28)?.max(1) as usize
+    } else {
+        1
+    };
+    if mip_count > 32 {
+        return Err(Error::Corrupted);
+    }
+    let pf_flags = u32_at(bytes, 80)?;
+    let four_cc = slice(bytes, 84, 4)?;
+    let caps2 = u32_at(bytes, 112)?;
+
+    if caps2 & DDSCAPS2_VOLUME != 0 {
+        return Err(Error::Unsupported("volume textures"));
+    }
+    let mut cubemap = caps2 & DDSCAPS2_CUBEMAP != 0;
+    if cubemap && caps2 & DDSCAPS2_CUBEMAP_ALLFACES != DDSCAPS2_CUBEMAP_ALLFACES {
+        return Err(Error::Unsupported("cubemaps with missing faces"));
+    }
+
+    // BGRA data has to be swizzled, RGBX needs its alpha filled
+    let mut swizzle = false;
+    let mut opaque = false;
+    let mut offset = 128;
+    let format = if pf_flags & DDPF_FOURCC != 0 {
+        match four_cc {
+            b"DXT1" => TextureFormat::BC1RGBA,
+            b"DXT2" | b"DXT3" => TextureFormat::BC2,
+            b"DXT4" | b"DXT5" => TextureFormat::BC3,
+            b"ATI1" | b"BC4U" => TextureFormat::BC4,
+            b"ATI2" | b"BC5U" => TextureFormat::BC5,
+            b"DX10" => {
+                offset += 20;
+                let misc_flags = u32_at(bytes, 136)?;
+                if u32_at(bytes, 140)? > 1 {
+                    return Err(Error::Unsupported("texture arrays"));
+                }
+                cubemap = misc_flags & DDS_RESOURCE_MISC_TEXTURECUBE != 0;
+                match u32_at(bytes, 128)? {
+                    28 | 29 => TextureFormat::RGBA8,
+                    87 | 91 => {
+                        swizzle = true;
+                        TextureFormat::RGBA8
+                    }
+                    71 | 72 => TextureFormat::BC1RGBA,
+                    74 | 75 => TextureFormat::BC2,
+                    77 | 78 => TextureFormat::BC3,
+                    80 => TextureFormat::BC4,
+                    83 => TextureFormat::BC5,
+                    98 | 99 => TextureFormat::BC7,
+                    _ => return Err(Error::Unsupported("DXGI format")),
+                }
+            }
+            _ => return Err(Error::Unsupported("FourCC format")),
+        }
+    } else if pf_flags & DDPF_RGB != 0 && u32_at(bytes, 88)? == 32 {
+        let masks = (u32_at(bytes, 92)?, u32_at(bytes, 96)?, u32_at(bytes, 100)?);
+        swizzle = match masks {
+            (0xff, 0xff00, 0xff0000) => false,
+            (0xff0000, 0xff00, 0xff) => true,
+            _ => return Err(Error::Unsupported("RGB channel masks")),
+        };
+        opaque = pf_flags & DDPF_ALPHAPIXELS == 0 || u32_at(bytes, 104)? != 0xff000000;
+        TextureFormat::RGBA8
+    } else {
+        return Err(Error::Unsupported("pixel format"));
+    };
+
+    let faces = if cubemap { 6 } else { 1 };
+    let mut levels = vec![Vec::with_capacity(faces); mip_count];
+    // DDS stores every mip level of a face, then the next face
+    for _ in 0..faces {
+        for (level, images) in levels.iter_mut().enumerate() {
+            let size = format.image_size((width >> level).max(1), (height >> level).max(1));
+            let mut image = slice(bytes, offset, size)?.to_vec();
+            offset += size;
+            if format == TextureFormat::RGBA8 {
+                for pixel in image.chunks_mut(4) {
+                    if swizzle {
+                        pixel.swap(0, 2);
+                    }
+                    if opaque {
+                        pixel[3] = 255;
+                    }
+                }
+            }
+            images.push(image);
+        }
+    }
+
+    Ok(Container {
+        width,
+        height,
+        format,
+        faces,
+        levels,
+    })
+}
+
+fn parse_ktx2(bytes: &[u8]) -> Result<Container, Error> {
+    let format = match u32_at(bytes, 12)? {
+        37 | 43 => TextureFormat::RGBA8,
+        131 | 132 => TextureFormat::BC1,
+        133 | 134 => TextureFormat::BC1RGBA,
+        135 | 136 => TextureFormat::BC2,
+        137 | 138 => TextureFormat::BC3,
+        139 => TextureFormat::BC4,
+        141 => TextureFormat::BC5,
+        145 | 146 => TextureFormat::BC7,
+        147 | 148 => TextureFormat::ETC2RGB,
+        149 | 150 => TextureFormat::ETC2RGBA1,
+        151 | 152 => TextureFormat::ETC2RGBA,
+        157 | 158 => TextureFormat::ASTC4x4,
+        // transcoded by the basis module, with the basis feature
+        0 => return Err(Error::Unsupported("Basis Universal textures")),
+        _ => return Err(Error::Unsupported("Vulkan format")),
+    };
+    let width = u32_at(bytes, 20)?;
+    let height = u32_at(bytes, 24)?;
+    if u32_at(bytes, 28)? > 1 {
+        return Err(Error::Unsupported("3D textures"));
+    }
+    if u32_at(bytes, 32)? > 1 {
+        return Err(Error::Unsupported("texture arrays"));
+    }
+    let faces = match u32_at(bytes, 36)? {
+        1 => 1,
+        6 => 6,
+        _ => return Err(Error::Corrupted),
+    };
+    // 0 means "generate mips at runtime", only the base level is stored then
+    let mip_count = u32_at(bytes, 40)?.max(1) as usize;
+    if mip_count > 32 {
+        return Err(Error::Corrupted);
+    }
+    if u32_at(bytes, 44)? != 0 {
+        return Err(Error::Unsupported("supercompression"));
+    }
+
+    let image_size =
+        |level: usize| format.image_size((width >> level).max(1), (height >> level).max(1));
+    let mut levels = Vec::with_capacity(mip_count);
+    for level in 0..mip_count {
+        // level index starts right after the 80 bytes header
+        let index = 80 + level * 24;
+        let offset = u64_at(bytes, index)?;
+        let len = u64_at(bytes, index + 8)?;
+        if len != image_size(level) * faces {
+            return Err(Error::Corrupted);
+        }
+        let data = slice(bytes, offset, len)?;
+        levels.push(data.chunks(len / faces).map(|face| face.to_vec()).collect());
+    }
+
+    Ok(Container {
+        width,
+        height,
+        format,
+        faces,
+        levels,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DDS_BGRX: &[u8] = include_bytes!("../testdata/2x2_bgrx_mips.dds");
+    const DDS_CUBE: &[u8] = include_bytes!("../testdata/4x4_bc1_cube_dx10.dds");
+    const KTX2_RGBA: &[u8] = include_bytes!("../testdata/2x2_rgba_mips.ktx2");
+
+    #[test]
+    fn dds_bgrx_swizzled_and_opaque() {
+        let container = parse(DDS_BGRX).unwrap();
+        assert_eq!((container.width, container.height), (2, 2));
+        assert_eq!(container.format, TextureFormat::RGBA8);
+        assert_eq!(container.faces, 1);
+        assert_eq!(
+            container.levels,
+            [
+                vec![vec![
+                    1, 2, 3, 255, 11, 12, 13, 255, 21, 22, 23, 255, 31, 32, 33, 255
+                ]],
+                vec![vec![41, 42, 43, 255]],
+            ]
+        );
+    }
+
+    #[test]
+    fn dds_dx10_cubemap() {
+        let container = parse(DDS_CUBE).unwrap();
+        assert_eq!((container.width, container.height), (4, 4));
+        assert_eq!(container.format, TextureFormat::BC1RGBA);
+        assert_eq!(container.faces, 6);
+        assert_eq!(container.levels.len(), 1);
+        for (n, face) in container.levels[0].iter().enumerate() {
+            assert_eq!(face, &[n as u8; 8]);
+        }
+    }
+
+    /// Levels are found through the level index, the smallest is stored first
+    #[test]
+    fn ktx2_mips() {
+        let container = parse(KTX2_RGBA).unwrap();
+        assert_eq!((container.width, container.height), (2, 2));
+        assert_eq!(container.format, TextureFormat::RGBA8);
+        assert_eq!(container.faces, 1);
+        assert_eq!(
+            container.levels,
+            [
+                vec![(0..16).collect::<Vec<u8>>()],
+                vec![vec![100, 101, 102, 103]]
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_container() {
+        assert!(!is_container(b"GIF89a"));
+        assert!(matches!(parse(b"GIF89a"), Err(Error::UnknownContainer)));
+        assert!(matches!(parse(&[]), Err(Error::UnknownContainer)));
+    }
+
+    #[test]
+    fn truncated() {
+        for data in &[DDS_BGRX, DDS_CUBE, KTX2_RGBA] {
+            assert!(is_container(data));
+            for len in 0..data.len() {
+                assert!(parse(&data[..len]).is_err(), "{} bytes", len);
+            }
+        }
+    }
+
+    #[test]
+    fn corrupted() {
+        // DDS header size
+        let mut data = DDS_BGRX.to_vec();
+        data[4] = 100;
+        assert!(matches!(parse(&data), Err(Error::Corrupted)));
+        // more mip levels than a 2x2 texture has
+        let mut data = DDS_BGRX.to_vec();
+        data[28] = 3;
+        assert!(matches!(parse(&data), Err(Error::Corrupted)));
+        // KTX2 level 0 length not matching the size
+        let mut data = KTX2_RGBA.to_vec();
+        data[88] = 15;
+        assert!(matches!(parse(&data), Err(Error::Corrupted)));
+        // KTX2 level 1 past the end of the file
+        let mut data = KTX2_RGBA.to_vec();
+        data[104] = 0xff;
+        assert!(matches!(parse(&data), Err(Error::Corrupted)));
+    }
+
+    #[test]
+    fn unsupported() {
+        // DDS volume texture
+        let mut data = DDS_BGRX.to_vec();
+        data[114] = 0x20;
+        assert!(matches!(parse(&data), Err(Error::Unsupported(_))));
+        // DX10 texture array
+        let mut data = DDS_CUBE.to_vec();
+        data[140] = 2;
+        assert!(matches!(parse(&data), Err(Error::Unsupported(_))));
+        // KTX2 Basis Universal texture, left to the basis module
+        let mut data = KTX2_RGBA.to_vec();
+        data[12] = 0;
+        assert!(matches!(parse(&data), Err(Error::Unsupported(_))));
+        // KTX2 Zstandard supercompression
+        let mut data = KTX2_RGBA.to_vec();
+        data[44] = 2;
+        assert!(matches!(parse(&data), Err(Error::Unsupported(_))));
+    }
+
+    /// Every single byte change of a valid file fails or parses, without panics
+    #[test]
+    fn mutations() {
+        for data in &[DDS_BGRX, DDS_CUBE, KTX2_RGBA] {
+            for i in 0..data.len() {
+                for &value in &[0x00, 0x01, 0x0f, 0x7f, 0x80, 0xfe, 0xff] {
+                    let mut mutated = data.to_vec();
+                    mutated[i] = value;
+                    let _ = parse(&mutated);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,259 @@
+use std::cell::Cell;
+
+use crate::{graphics::*, Color, SpriteBatch, STATS_WINDOW};
+
+/// What a frame asked of the GPU, from the start of `EventHandler::update` to the end of
+/// `EventHandler::draw`, or `late_latch_event` when late latching
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RenderCounters {
+    pub draw_calls: u32,
+    pub passes: u32,
+    /// `apply_pipeline` calls
+    pub pipelines: u32,
+    /// `apply_bindings` calls
+    pub bindings: u32,
+    /// `apply_uniforms` calls and uploads of shared uniforms
+    pub uniforms: u32,
+}
+
+/// GPU memory taken by miniquad's textures and buffers, estimated from their sizes:
+/// drivers add padding and mip levels, and the formats of textures are not taken into
+/// account, each texel counts for 4 bytes. Helpers like `SpriteBatch` are included.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GpuMemoryEstimate {
+    pub textures: usize,
+    pub texture_bytes: usize,
+    pub buffers: usize,
+    pub buffer_bytes: usize,
+}
+
+#[derive(Default)]
+pub(crate) struct DebugOverlay {
+    enabled: bool,
+    /// Created on first use
+    hud: Option<Hud>,
+    /// Counted so far this frame, `Cell` as `Context::draw` takes `&self`
+    counting: Cell<RenderCounters>,
+    /// Counted in the previous frame
+    counted: RenderCounters,
+}
+
+struct Hud {
+    batch: SpriteBatch,
+    /// Single white texel, everything is drawn with tinted quads
+    white: Texture,
+}
+
+/// Screen pixels per font pixel
+const SCALE: f32 = 2.;
+const MARGIN: f32 = 8.;
+const LINE_HEIGHT: f32 = 7. * SCALE;
+const GRAPH_HEIGHT: f32 = 60.;
+/// One bar per frame of `STATS_WINDOW`
+const BAR_WIDTH: f32 = 2.;
+
+impl Context {
+    /// Draw a HUD in the top left corner with the frame time history, the draw call
+    /// and state change counts of the previous frame and an estimate of the GPU memory
+    /// used, over everything, `debug_show_texture` views included. Off by default.
+    ///
+    /// The frame time graph is scaled for two refresh intervals, the frames missing
+    /// their deadline are red.
+    pub fn set_debug_overlay(&mut self, enabled: bool) {
+        self.debug_overlay.enabled = enabled;
+    }
+
+    pub fn debug_overlay(&self) -> bool {
+        self.debug_overlay.enabled
+    }
+
+    /// Counts of the previous frame, all zeros before the first one is done
+    pub fn render_counters(&self) -> RenderCounters {
+        self.debug_overlay.counted
+    }
+
+    pub fn gpu_memory_estimate(&self) -> GpuMemoryEstimate {
+        let live = live_resources();
+        GpuMemoryEstimate {
+            textures: live.textures.len(),
+            texture_bytes: live.textures.iter().map(Texture::estimated_size).sum(),
+            buffers: live.buffers.len(),
+            buffer_bytes: live.buffers.iter().map(Buffer::size).sum(),
+        }
+    }
+
+    pub(crate) fn count<F: FnOnce(&mut RenderCounters)>(&self, f: F) {
+        let mut counters = self.debug_overlay.counting.get();
+        f(&mut counters);
+        self.debug_overlay.counting.set(counters);
+    }
+
+    pub(crate) fn begin_render_counters(&mut self) {
+        self.debug_overlay.counting.set(RenderCounters::default());
+    }
+
+    pub(crate) fn end_render_counters(&mut self) {
+        self.debug_overlay.counted = self.debug_overlay.counting.get();
+    }
+
+    /// Draw the HUD to the screen when enabled, called once per frame
+    pub(crate) fn present_debug_overlay(&mut self) {
+        if !self.debug_overlay.enabled {
+            return;
+        }
+        let mut hud = match self.debug_overlay.hud.take() {
+            Some(hud) => hud,
+            None => Hud {
+                batch: SpriteBatch::new(self),
+                white: Texture::from_rgba8(1, 1, &[255; 4]),
+            },
+        };
+
+        let target = self.frame_timing().target;
+        let counters = self.render_counters();
+        let memory = self.gpu_memory_estimate();
+        let megabytes = |bytes: usize| bytes as f64 / (1024. * 1024.);
+        let mut lines = vec![];
+        if let Some(stats) = self.frame_stats() {
+            lines.push(format!(
+                "AVG {:.1} MS  P95 {:.1}  MAX {:.1}",
+                stats.average * 1000.,
+                stats.percentile_95 * 1000.,
+                stats.max * 1000.
+            ));
+        }
+        lines.push(format!(
+            "DRAWS {}  PASSES {}",
+            counters.draw_calls, counters.passes
+        ));
+        lines.push(format!(
+            "PIPELINES {}  BINDINGS {}  UNIFORMS {}",
+            counters.pipelines, counters.bindings, counters.uniforms
+        ));
+        lines.push(format!(
+            "TEXTURES {}: {:.1} MB",
+            memory.textures,
+            megabytes(memory.texture_bytes)
+        ));
+        lines.push(format!(
+            "BUFFERS {}: {:.1} MB",
+            memory.buffers,
+            megabytes(memory.buffer_bytes)
+        ));
+
+        let graph_width = STATS_WINDOW as f32 * BAR_WIDTH;
+        let columns = lines.iter().map(|line| line.len()).max().unwrap_or(0);
+        let text_width = columns as f32 * 4. * SCALE;
+        let width = graph_width.max(text_width) + MARGIN * 2.;
+        let height = GRAPH_HEIGHT + lines.len() as f32 * LINE_HEIGHT + MARGIN * 3.;
+
+        self.begin_pass(None, PassAction::Nothing);
+        let white = hud.white;
+        let mut rect = |ctx: &mut Context, x: f32, y: f32, w: f32, h: f32, color: Color| {
+            hud.batch
+                .draw(ctx, white, x, y, w, h, (0., 0.), (1., 1.), color)
+        };
+        rect(self, 0., 0., width, height, Color::new(0., 0., 0., 0.7));
+
+        // a refresh interval is half the graph
+        let graph_bottom = MARGIN + GRAPH_HEIGHT;
+        let frame_times: Vec<f64> = self.frame_pacer.frame_times().collect();
+        for (n, time) in frame_times.into_iter().enumerate() {
+            let h = (time / (target * 2.)).min(1.) as f32 * GRAPH_HEIGHT;
+            // same slack as `FrameTiming::missed_deadline`
+            let color = if time > target * 1.5 {
+                Color::new(1., 0.3, 0.3, 1.)
+            } else {
+                Color::new(0.3, 1., 0.3, 1.)
+            };
+            let x = MARGIN + n as f32 * BAR_WIDTH;
+            rect(self, x, graph_bottom - h, BAR_WIDTH, h, color);
+        }
+        let target_y = graph_bottom - GRAPH_HEIGHT / 2.;
+        rect(
+            self,
+            MARGIN,
+            target_y,
+            graph_width,
+            1.,
+            Color::new(1., 1., 1., 0.5),
+        );
+
+        for (n, line) in lines.iter().enumerate() {
+            let y = graph_bottom + MARGIN + n as f32 * LINE_HEIGHT;
+            for (column, character) in line.chars().enumerate() {
+                let x = MARGIN + column as f32 * 4. * SCALE;
+                for (row, bits) in glyph(character).iter().enumerate() {
+                    for bit in 0..3 {
+                        if bits & (0b100 >> bit) != 0 {
+                            let (px, py) = (x + bit as f32 * SCALE, y + row as f32 * SCALE);
+                            rect(self, px, py, SCALE, SCALE, Color::new(1., 1., 1., 1.));
+                        }
+                    }
+                }
+            }
+        }
+        hud.batch.flush(self);
+        self.end_render_pass();
+
+        self.debug_overlay.hud = Some(hud);
+    }
+
+    /// Forget the HUD's resources of the lost GL context, they are created again when
+    /// needed and are not among the app's lost resources.
+    pub(crate) fn restore_debug_overlay(&mut self, lost: &mut LostResources) {
+        if let Some(hud) = self.debug_overlay.hud.take() {
+            lost.buffers.retain(|buffer| !hud.batch.owns_buffer(buffer));
+            lost.textures.retain(|texture| *texture != hud.white);
+        }
+    }
+}
+
+/// Rows of a 3x5 pixel glyph, top to bottom, the leftmost pixel in the highest bit.
+/// Lowercase is drawn as uppercase, unknown characters as spaces.
+fn glyph(character: char) -> [u8; 5] {
+    match character.to_ascii_uppercase() {
+        '0' => [7, 5, 5, 5, 7],
+        '1' => [2, 6, 2, 2, 7],
+        '2' => [7, 1, 7, 4, 7],
+        '3' => [7, 1, 7, 1, 7],
+        '4' => [5, 5, 7, 1, 1],
+        '5' => [7, 4, 7, 1, 7],
+        '6' => [7, 4, 7, 5, 7],
+        '7' => [7, 1, 1, 1, 1],
+        '8' => [7, 5, 7, 5, 7],
+        '9' => [7, 5, 7, 1, 7],
+        'A' => [2, 5, 7, 5, 5],
+        'B' => [6, 5, 6, 5, 6],
+        'C' => [3, 4, 4, 4, 3],
+        'D' => [6, 5, 5, 5, 6],
+        'E' => [7, 4, 6, 4, 7],
+        'F' => [7, 4, 6, 4, 4],
+        'G' => [3, 4, 5, 5, 3],
+        'H' => [5, 5, 7, 5, 5],
+        'I' => [7, 2, 2, 2, 7],
+        'J' => [1, 1, 1, 5, 2],
+        'K' => [5, 5, 6, 5, 5],
+        'L' => [4, 4, 4, 4, 7],
+        'M' => [5, 7, 7, 5, 5],
+        'N' => [6, 5, 5, 5, 5],
+        'O' => [2, 5, 5, 5, 2],
+        'P' => [6, 5, 6, 4, 4],
+        'Q' => [2, 5, 5, 6, 3],
+        'R' => [6, 5, 6, 5, 5],
+        'S' => [3, 4, 2, 1, 6],
+        'T' => [7, 2, 2, 2, 2],
+        'U' => [5, 5, 5, 5, 7],
+        'V' => [5, 5, 5, 5, 2],
+        'W' => [5, 5, 7, 7, 5],
+        'X' => [5, 5, 2, 5, 5],
+        'Y' => [5, 5, 2, 2, 2],
+        'Z' => [7, 1, 2, 4, 7],
+        '.' => [0, 0, 0, 0, 2],
+        ':' => [0, 2, 0, 2, 0],
+        '/' => [1, 1, 2, 4, 4],
+        '-' => [0, 0, 7, 0, 0],
+        '%' => [5, 1, 2, 4, 5],
+        _ => [0; 5],
+    }
+}
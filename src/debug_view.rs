@@ -0,0 +1,138 @@
+use crate::{graphics::*, PostProcess};
+
+/// Textures shown over the frame with `Context::debug_show_texture`
+pub(crate) struct DebugView {
+    /// Created on first use
+    blits: Option<Blits>,
+    /// Texture and rect from the top left corner, in screen pixels
+    queue: Vec<(Texture, (f32, f32, f32, f32))>,
+    /// Depths shown from black to white
+    depth_range: (f32, f32),
+}
+
+struct Blits {
+    color: PostProcess,
+    depth: PostProcess,
+}
+
+impl Default for DebugView {
+    fn default() -> DebugView {
+        DebugView {
+            blits: None,
+            queue: vec![],
+            depth_range: (0., 1.),
+        }
+    }
+}
+
+#[repr(C)]
+struct DepthUniforms {
+    depth_range: [f32; 2],
+}
+
+impl Context {
+    /// Show a 2D texture, like a G-buffer attachment or a shadow map, in `screen_rect`:
+    /// x, y from the top left corner, width and height, in screen pixels.
+    /// It is drawn over everything once the frame's `EventHandler::draw` returns,
+    /// so this can be called anywhere, inside other passes too, for this frame only.
+    ///
+    /// Depth attachments of render passes are shown in grayscale, remapped with
+    /// `set_debug_depth_range`. WebGL only samples depth textures set to
+    /// `FilterMode::Nearest`.
+    pub fn debug_show_texture(&mut self, texture: Texture, screen_rect: (f32, f32, f32, f32)) {
+        self.debug_view.queue.push((texture, screen_rect));
+    }
+
+    /// Depths `debug_show_texture` shows black and white, between them the grays.
+    /// Perspective depths crowd near 1, a range like 0.99..1 shows them better
+    /// than the default 0..1.
+    pub fn set_debug_depth_range(&mut self, black: f32, white: f32) {
+        self.debug_view.depth_range = (black, white);
+    }
+
+    /// Draw the textures queued this frame to the screen, called once per frame
+    pub(crate) fn present_debug_views(&mut self) {
+        if self.debug_view.queue.is_empty() {
+            return;
+        }
+        let mut blits = match self.debug_view.blits.take() {
+            Some(blits) => blits,
+            None => Blits {
+                color: PostProcess::new(self, COLOR_FRAGMENT, COLOR_META),
+                depth: PostProcess::new(self, DEPTH_FRAGMENT, DEPTH_META),
+            },
+        };
+        let (black, white) = self.debug_view.depth_range;
+        let (_, screen_height) = self.screen_size();
+
+        self.begin_pass(None, PassAction::Nothing);
+        for (texture, (x, y, w, h)) in std::mem::take(&mut self.debug_view.queue) {
+            // the default framebuffer viewport goes from the bottom
+            self.apply_viewport(x as i32, (screen_height - y - h) as i32, w as i32, h as i32);
+            if self.is_depth_attachment(texture) {
+                let uniforms = DepthUniforms {
+                    depth_range: [black, white],
+                };
+                blits.depth.draw_with_uniforms(self, texture, &uniforms);
+            } else {
+                blits.color.draw(self, texture);
+            }
+        }
+        self.end_render_pass();
+
+        self.debug_view.blits = Some(blits);
+    }
+
+    /// Forget the blits of the lost GL context, they are created again when needed and
+    /// are not among the app's lost resources.
+    pub(crate) fn restore_debug_view(&mut self, lost: &mut LostResources) {
+        if let Some(blits) = self.debug_view.blits.take() {
+            lost.buffers.retain(|buffer| {
+                !blits.color.owns_buffer(buffer) && !blits.depth.owns_buffer(buffer)
+            });
+        }
+    }
+}
+
+const COLOR_FRAGMENT: &str = r#"#version 100
+precision mediump float;
+
+varying vec2 uv;
+
+uniform sampler2D tex;
+
+void main() {
+    gl_FragColor = texture2D(tex, uv);
+}"#;
+
+const COLOR_META: ShaderMeta = ShaderMeta {
+    images: &["tex"],
+    uniforms: UniformBlockLayout { uniforms: &[] },
+    shared_uniforms: &[],
+};
+
+const DEPTH_FRAGMENT: &str = r#"#version 100
+#ifdef GL_FRAGMENT_PRECISION_HIGH
+precision highp float;
+#else
+precision mediump float;
+#endif
+
+varying mediump vec2 uv;
+
+uniform sampler2D depth;
+uniform vec2 depth_range;
+
+void main() {
+    float d = texture2D(depth, uv).r;
+    float gray = clamp((d - depth_range.x) / (depth_range.y - depth_range.x), 0.0, 1.0);
+    gl_FragColor = vec4(vec3(gray), 1.0);
+}"#;
+
+const DEPTH_META: ShaderMeta = ShaderMeta {
+    images: &["depth"],
+    uniforms: UniformBlockLayout {
+        uniforms: &[("depth_range", UniformType::Float2)],
+    },
+    shared_uniforms: &[],
+};
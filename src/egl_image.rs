@@ -0,0 +1,79 @@
+use std::os::raw::c_void;
+
+use crate::{gl::*, graphics::*};
+
+/// Texture sampling an `EGLImage` in place, e.g. a hardware decoded video frame or
+/// a camera frame in a DMA-BUF, imported by the app with `eglCreateImageKHR` and
+/// `EGL_LINUX_DMA_BUF_EXT`: the GPU reads the frame where the decoder put it, there is
+/// no copy through the CPU.
+///
+/// Needs `Features::egl_image`, and an EGL GL context, which miniquad's own windows
+/// are not: this is for `Context::new_external` on a context of the app's EGL display,
+/// the one of the images. Multi-planar formats, like NV12, are imported plane by plane,
+/// one image per plane, and converted to RGB by the app's shaders.
+///
+/// ```ignore
+/// // once per decoded frame
+/// let image = egl_create_image_khr(display, EGL_NO_CONTEXT, EGL_LINUX_DMA_BUF_EXT, ...);
+/// unsafe { video.set_image(image) };
+/// // draw with video.texture(), then free the previous frame's image
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EglImageTexture {
+    texture: Texture,
+}
+
+impl EglImageTexture {
+    /// Texture of `width`x`height` showing `image`, `None` without `Features::egl_image`.
+    ///
+    /// # Safety
+    ///
+    /// `image` is an `EGLImageKHR` of the EGL display of the current GL context, of
+    /// `width`x`height`, and stays alive while the texture shows it.
+    pub unsafe fn new(
+        ctx: &Context,
+        image: *mut c_void,
+        width: u32,
+        height: u32,
+    ) -> Option<EglImageTexture> {
+        if !ctx.features().egl_image {
+            return None;
+        }
+        let mut texture: GLuint = 0;
+        glGenTextures(1, &mut texture as *mut _);
+        glActiveTexture(GL_TEXTURE0);
+        glBindTexture(GL_TEXTURE_2D, texture);
+        glTexParameteri(GL_TEXTURE_2D, GL_TEXTURE_WRAP_S, GL_CLAMP_TO_EDGE as i32);
+        glTexParameteri(GL_TEXTURE_2D, GL_TEXTURE_WRAP_T, GL_CLAMP_TO_EDGE as i32);
+        let params = RawTextureParams {
+            filter: Some(FilterMode::Linear),
+            ..Default::default()
+        };
+        let texture = EglImageTexture {
+            texture: Texture::from_raw_gl(texture, width, height, params),
+        };
+        texture.set_image(image);
+        Some(texture)
+    }
+
+    /// Show another image of the same size, like the next frame of a video.
+    ///
+    /// # Safety
+    ///
+    /// Same as for `new`.
+    pub unsafe fn set_image(&self, image: *mut c_void) {
+        glBindTexture(GL_TEXTURE_2D, self.texture.gl_internal_id());
+        #[cfg(not(any(windows, target_arch = "wasm32")))]
+        glEGLImageTargetTexture2DOES(GL_TEXTURE_2D, image);
+    }
+
+    /// The texture to bind, sampled with `sampler2D`
+    pub fn texture(&self) -> Texture {
+        self.texture
+    }
+
+    /// Free the texture, the image is left to the app.
+    pub fn delete(&self) {
+        self.texture.delete();
+    }
+}
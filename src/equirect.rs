@@ -0,0 +1,113 @@
+use crate::{graphics::*, PostProcess};
+
+/// Turns equirectangular panoramas, like the HDR environment maps of PBR pipelines,
+/// into cubemaps by rendering each face from the panorama.
+///
+/// ```ignore
+/// let panorama = Texture::new_render_texture(RenderTextureParams {
+///     width: 2048,
+///     height: 1024,
+///     format: PixelFormat::RGBA32F,
+///     ..Default::default()
+/// });
+/// panorama.update_part_rgba32f(0, 0, 2048, 1024, &hdr_pixels);
+/// let environment = converter.convert(ctx, panorama, 512, PixelFormat::RGBA32F);
+/// ```
+///
+/// The panorama's top row looks straight up, its left and right edges meet at -X,
+/// and its center looks along +X. Float cubemaps need `Features::texture_float`, and
+/// on WebGL `WEBGL_color_buffer_float` to be rendered to; WebGL also only samples
+/// float panoramas with `FilterMode::Nearest`.
+pub struct EquirectToCubemap {
+    post_process: PostProcess,
+}
+
+#[repr(C)]
+struct Uniforms {
+    face_s: [f32; 3],
+    face_t: [f32; 3],
+    face_normal: [f32; 3],
+}
+
+impl EquirectToCubemap {
+    pub fn new(ctx: &mut Context) -> EquirectToCubemap {
+        EquirectToCubemap {
+            post_process: PostProcess::new(ctx, FRAGMENT, META),
+        }
+    }
+
+    /// New `size`x`size` cubemap of `format`, without mip levels, drawn from `equirect`.
+    /// Leaves the default pass bound, so call it outside of other passes.
+    pub fn convert(
+        &mut self,
+        ctx: &mut Context,
+        equirect: Texture,
+        size: u32,
+        format: PixelFormat,
+    ) -> Texture {
+        let cubemap = Texture::new_cubemap(size, format, 1);
+        for face in CubeFace::ALL.iter() {
+            let pass = RenderPass::new_cubemap_face(ctx, cubemap, *face, 0, None);
+            ctx.begin_pass(pass, PassAction::Nothing);
+            self.post_process
+                .draw_with_uniforms(ctx, equirect, &face_basis(*face));
+            ctx.end_render_pass();
+            pass.delete(ctx);
+        }
+        cubemap
+    }
+}
+
+/// Directions of the face's s and t texture coordinates and of its center,
+/// as the GL spec maps directions to cubemap faces
+fn face_basis(face: CubeFace) -> Uniforms {
+    let (face_s, face_t, face_normal) = match face {
+        CubeFace::PositiveX => ([0., 0., -1.], [0., -1., 0.], [1., 0., 0.]),
+        CubeFace::NegativeX => ([0., 0., 1.], [0., -1., 0.], [-1., 0., 0.]),
+        CubeFace::PositiveY => ([1., 0., 0.], [0., 0., 1.], [0., 1., 0.]),
+        CubeFace::NegativeY => ([1., 0., 0.], [0., 0., -1.], [0., -1., 0.]),
+        CubeFace::PositiveZ => ([1., 0., 0.], [0., -1., 0.], [0., 0., 1.]),
+        CubeFace::NegativeZ => ([-1., 0., 0.], [0., -1., 0.], [0., 0., -1.]),
+    };
+    Uniforms {
+        face_s,
+        face_t,
+        face_normal,
+    }
+}
+
+const FRAGMENT: &str = r#"#version 100
+#ifdef GL_FRAGMENT_PRECISION_HIGH
+precision highp float;
+#else
+precision mediump float;
+#endif
+
+varying mediump vec2 uv;
+
+uniform sampler2D equirect;
+uniform vec3 face_s;
+uniform vec3 face_t;
+uniform vec3 face_normal;
+
+void main() {
+    // uv follows the texel rows of the face, whatever the render target origin
+    vec2 st = uv * 2.0 - 1.0;
+    vec3 dir = normalize(face_normal + st.x * face_s + st.y * face_t);
+    vec2 equirect_uv = vec2(
+        atan(dir.z, dir.x) / 6.2831853 + 0.5,
+        acos(clamp(dir.y, -1.0, 1.0)) / 3.1415927);
+    gl_FragColor = texture2D(equirect, equirect_uv);
+}"#;
+
+const META: ShaderMeta = ShaderMeta {
+    images: &["equirect"],
+    uniforms: UniformBlockLayout {
+        uniforms: &[
+            ("face_s", UniformType::Float3),
+            ("face_t", UniformType::Float3),
+            ("face_normal", UniformType::Float3),
+        ],
+    },
+    shared_uniforms: &[],
+};
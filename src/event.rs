@@ -1,4 +1,5 @@
-use crate::Context;
+use crate::conf::Orientation;
+use crate::{Color, Context, DeviceLostReason, LostResources, ResetStrategy};
 
 use crate::sapp::{self, sapp_keycode};
 
@@ -8,6 +9,10 @@ pub enum MouseButton {
     Left,
 }
 
+/// Physical key, named after what it types on a US QWERTY layout whatever the user's
+/// layout is: `KeyCode::W` is the key right of Q, which types "Z" on AZERTY.
+/// Good for movement keys, see `Context::logical_key` and `Context::key_name`
+/// for shortcuts and for showing keys to the user.
 #[derive(Debug, Copy, Clone, PartialEq, Hash, Eq)]
 #[repr(u32)]
 pub enum KeyCode {
@@ -262,6 +267,84 @@ impl From<sapp_keycode> for KeyCode {
     }
 }
 
+impl KeyCode {
+    /// sapp keycode of the keys typing a character, what they type depends on the layout.
+    /// Apart from the world keys, these keycodes are the character typed on a US layout.
+    pub(crate) fn character_key_code(self) -> Option<sapp_keycode> {
+        match self {
+            KeyCode::Space => None,
+            KeyCode::World1 => Some(sapp::sapp_keycode_SAPP_KEYCODE_WORLD_1),
+            KeyCode::World2 => Some(sapp::sapp_keycode_SAPP_KEYCODE_WORLD_2),
+            key if (key as u32) < KeyCode::World1 as u32 => {
+                key.us_name().chars().next().map(|c| c as sapp_keycode)
+            }
+            _ => None,
+        }
+    }
+
+    /// Key typing `character` on a US layout.
+    pub(crate) fn from_character(character: char) -> Option<KeyCode> {
+        if !character.is_ascii() {
+            return None;
+        }
+        let key = KeyCode::from(character.to_ascii_uppercase() as sapp_keycode);
+        key.character_key_code().map(|_| key)
+    }
+
+    /// Label of the key on a US keyboard.
+    pub(crate) fn us_name(self) -> String {
+        let name = match self {
+            KeyCode::Apostrophe => "'",
+            KeyCode::Comma => ",",
+            KeyCode::Minus => "-",
+            KeyCode::Period => ".",
+            KeyCode::Slash => "/",
+            KeyCode::Semicolon => ";",
+            KeyCode::Equal => "=",
+            KeyCode::LeftBracket => "[",
+            KeyCode::Backslash => "\\",
+            KeyCode::RightBracket => "]",
+            KeyCode::GraveAccent => "`",
+            KeyCode::World1 => "World 1",
+            KeyCode::World2 => "World 2",
+            KeyCode::Escape => "Esc",
+            KeyCode::Backspace => "Backspace",
+            KeyCode::PageUp => "Page Up",
+            KeyCode::PageDown => "Page Down",
+            KeyCode::CapsLock => "Caps Lock",
+            KeyCode::ScrollLock => "Scroll Lock",
+            KeyCode::NumLock => "Num Lock",
+            KeyCode::PrintScreen => "Print Screen",
+            KeyCode::KpDecimal => "Keypad .",
+            KeyCode::KpDivide => "Keypad /",
+            KeyCode::KpMultiply => "Keypad *",
+            KeyCode::KpSubtract => "Keypad -",
+            KeyCode::KpAdd => "Keypad +",
+            KeyCode::KpEnter => "Keypad Enter",
+            KeyCode::KpEqual => "Keypad =",
+            KeyCode::LeftShift => "Left Shift",
+            KeyCode::LeftControl => "Left Ctrl",
+            KeyCode::LeftAlt => "Left Alt",
+            KeyCode::LeftSuper => "Left Super",
+            KeyCode::RightShift => "Right Shift",
+            KeyCode::RightControl => "Right Ctrl",
+            KeyCode::RightAlt => "Right Alt",
+            KeyCode::RightSuper => "Right Super",
+            key => {
+                let name = format!("{:?}", key);
+                return if name.starts_with("Kp") {
+                    format!("Keypad {}", &name[2..])
+                } else if name.starts_with("Key") {
+                    name[3..].to_string()
+                } else {
+                    name
+                };
+            }
+        };
+        name.to_string()
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Default)]
 pub struct KeyMods {
     pub shift: bool,
@@ -291,9 +374,21 @@ impl From<u32> for KeyMods {
     }
 }
 
+/// Insets, in pixels, of the part of the window not hidden by notches
+/// and rounded display corners. All zeros on desktop.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct SafeAreaInsets {
+    pub top: f32,
+    pub right: f32,
+    pub bottom: f32,
+    pub left: f32,
+}
+
 pub trait EventHandler {
     fn update(&mut self, _ctx: &mut Context);
     fn draw(&mut self, _ctx: &mut Context);
+    /// New framebuffer size, in pixels, see `Context::dpi_scale`.
+    /// Also sent during an interactive resize, with frames still drawn meanwhile.
     fn resize_event(&mut self, _ctx: &mut Context, _width: f32, _height: f32) {}
     fn mouse_motion_event(&mut self, _ctx: &mut Context, _x: f32, _y: f32, _dx: f32, _dy: f32) {}
     fn mouse_wheel_event(&mut self, _ctx: &mut Context, _x: f32, _y: f32) {}
@@ -333,4 +428,60 @@ pub trait EventHandler {
     }
 
     fn key_up_event(&mut self, _ctx: &mut Context, _keycode: KeyCode, _keymods: KeyMods) {}
+    /// OS switched between light and dark color scheme.
+    /// Not sent on linux, where there is no common way to watch the theme.
+    fn theme_changed_event(&mut self, _ctx: &mut Context, _dark: bool) {}
+    /// The user picked another accent color, see `Context::accent_color`.
+    /// Sent on windows and web.
+    fn accent_color_changed_event(&mut self, _ctx: &mut Context, _color: Option<Color>) {}
+    /// High contrast mode was turned on or off, see `Context::high_contrast`.
+    /// Sent on windows and web.
+    fn high_contrast_changed_event(&mut self, _ctx: &mut Context, _high_contrast: bool) {}
+    /// Safe area changed, usually because of an orientation change.
+    fn safe_area_changed_event(&mut self, _ctx: &mut Context, _insets: SafeAreaInsets) {}
+    /// Device was rotated, `orientation` is either `Landscape` or `Portrait`.
+    fn orientation_changed_event(&mut self, _ctx: &mut Context, _orientation: Orientation) {}
+    /// The GPU was reset, after a driver crash or update, or a shader running too long,
+    /// and the GL context with it: nothing can be drawn, all GL objects are gone. Sent on
+    /// web, and on desktop where the driver supports robust contexts, at the start of a
+    /// frame there. Recreating the context, the default, is followed by
+    /// `resources_lost_event`.
+    fn device_lost_event(
+        &mut self,
+        _ctx: &mut Context,
+        _reason: DeviceLostReason,
+    ) -> ResetStrategy {
+        ResetStrategy::Recreate
+    }
+    /// The GL context was lost and a new one was created, see `device_lost_event`.
+    /// Shaders and pipelines were recreated, everything in `lost` has to be created again
+    /// before drawing with it.
+    fn resources_lost_event(&mut self, _ctx: &mut Context, _lost: &LostResources) {}
+    /// The previous frame took `frame_time` seconds, more than the budget
+    /// set with `Context::set_frame_budget`. Sent before `update`.
+    fn frame_budget_exceeded_event(&mut self, _ctx: &mut Context, _frame_time: f64) {}
+    /// Last moment to draw before the frame is presented, with `Context::set_late_latch`:
+    /// the input events that came while `draw` ran were just sent, so this can draw what
+    /// has to follow the input most closely, like a cursor or the final camera transform,
+    /// with the latest input. Keep it short, the frame waits for it.
+    fn late_latch_event(&mut self, _ctx: &mut Context) {}
+    /// Window was minimized. Not sent on web.
+    fn window_minimized_event(&mut self, _ctx: &mut Context) {}
+    /// Window is back from being minimized. Not sent on web.
+    fn window_restored_event(&mut self, _ctx: &mut Context) {}
+    /// Part of the window was uncovered, e.g. by moving another window away.
+    /// A frame is drawn right after. Not sent on web.
+    fn window_exposed_event(&mut self, _ctx: &mut Context) {}
+    /// `Context::dpi_scale` changed, e.g. the window was moved to a monitor with another
+    /// scale or the page was zoomed. The logical window size is kept, a `resize_event`
+    /// with the new framebuffer size follows. Sent on windows and web, with `Conf::high_dpi`.
+    fn dpi_changed_event(&mut self, _ctx: &mut Context, _scale: f32) {}
+    /// Zoom by `magnification` times around `x`, `y`, above 1 to zoom in.
+    /// From two finger pinches on touch screens and from Ctrl + mouse wheel, which is how
+    /// browsers and windows precision touchpads report trackpad pinches.
+    /// Ctrl + wheel is reported as `mouse_wheel_event` too, unless
+    /// `Conf::ctrl_wheel_only_magnifies` is set.
+    fn magnify_event(&mut self, _ctx: &mut Context, _magnification: f32, _x: f32, _y: f32) {}
+    /// Two finger rotation on touch screens, by `angle` radians clockwise around `x`, `y`.
+    fn rotate_event(&mut self, _ctx: &mut Context, _angle: f32, _x: f32, _y: f32) {}
 }
@@ -0,0 +1,107 @@
+//! Tiny single-threaded executor, polled once per frame before `EventHandler::update`.
+//!
+//! Meant for sequences of asynchronous steps, e.g. loading several files one after
+//! the other, written as plain `async` code instead of nested callbacks:
+//!
+//! ```ignore
+//! let level = Rc::new(RefCell::new(None));
+//! let result = level.clone();
+//! ctx.spawn(async move {
+//!     let index = fs::load_file_async("levels/index.txt").await;
+//!     let first = parse_index(index.unwrap())[0].clone();
+//!     *result.borrow_mut() = Some(fs::load_file_async(&first).await);
+//! });
+//! ```
+//!
+//! Tasks have no access to the `Context`, results are handed over to `update`
+//! through shared state, as above, and GPU resources are created there.
+
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context as TaskContext, Poll, RawWaker, RawWakerVTable, Waker},
+};
+
+type Task = Pin<Box<dyn Future<Output = ()>>>;
+
+#[derive(Default)]
+pub(crate) struct Executor {
+    tasks: Vec<Task>,
+}
+
+impl Executor {
+    pub fn spawn(&mut self, task: Task) {
+        self.tasks.push(task);
+    }
+
+    /// Poll every task once, dropping the finished ones.
+    /// Tasks are polled every frame, so wakers do nothing.
+    pub fn poll(&mut self) {
+        if self.tasks.is_empty() {
+            return;
+        }
+        let waker = noop_waker();
+        let mut task_context = TaskContext::from_waker(&waker);
+        let mut n = 0;
+        while n < self.tasks.len() {
+            if self.tasks[n].as_mut().poll(&mut task_context).is_ready() {
+                self.tasks.swap_remove(n);
+            } else {
+                n += 1;
+            }
+        }
+    }
+}
+
+fn noop_waker() -> Waker {
+    fn clone(_: *const ()) -> RawWaker {
+        RawWaker::new(std::ptr::null(), &VTABLE)
+    }
+    fn noop(_: *const ()) {}
+    static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+
+    unsafe { Waker::from_raw(clone(std::ptr::null())) }
+}
+
+/// Completes a `CallbackFuture`, may be sent to another thread.
+pub struct Resolver<T> {
+    slot: Arc<Mutex<Option<T>>>,
+}
+
+impl<T> Resolver<T> {
+    pub fn resolve(self, value: T) {
+        *self.slot.lock().unwrap() = Some(value);
+    }
+}
+
+/// Future of a value delivered to a callback, see `callback`.
+pub struct CallbackFuture<T> {
+    slot: Arc<Mutex<Option<T>>>,
+}
+
+impl<T> Future for CallbackFuture<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, _: &mut TaskContext) -> Poll<T> {
+        match self.slot.lock().unwrap().take() {
+            Some(value) => Poll::Ready(value),
+            None => Poll::Pending,
+        }
+    }
+}
+
+/// Turn a callback based API into a future:
+/// `exec::callback(|done| net::http_get(url, move |response| done.resolve(response)))`
+pub fn callback<T, F: FnOnce(Resolver<T>)>(f: F) -> CallbackFuture<T> {
+    let slot = Arc::new(Mutex::new(None));
+    f(Resolver { slot: slot.clone() });
+    CallbackFuture { slot }
+}
+
+impl crate::Context {
+    /// Run `future` on the frame loop, it is polled once per frame until it completes.
+    pub fn spawn<F: Future<Output = ()> + 'static>(&mut self, future: F) {
+        self.executor.spawn(Box::pin(future));
+    }
+}
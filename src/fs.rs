@@ -0,0 +1,39 @@
+//! Loading files with the same code on native and wasm.
+//!
+//! On native the file is read from disk right away, on wasm it is fetched
+//! relative to the page url and the callback is called from the event loop later.
+
+#[derive(Debug)]
+pub enum Error {
+    IOError(std::io::Error),
+    DownloadFailed(crate::net::Error),
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Error {
+        Error::IOError(error)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load_file<F>(path: &str, f: F)
+where
+    F: FnOnce(Result<Vec<u8>, Error>) + 'static,
+{
+    f(std::fs::read(path).map_err(Error::from));
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn load_file<F>(path: &str, f: F)
+where
+    F: FnOnce(Result<Vec<u8>, Error>) + 'static,
+{
+    crate::net::wasm::fetch(path, move |response| {
+        f(response.map_err(Error::DownloadFailed))
+    });
+}
+
+/// `load_file` as a future, for tasks run with `Context::spawn`.
+pub fn load_file_async(path: &str) -> crate::exec::CallbackFuture<Result<Vec<u8>, Error>> {
+    crate::exec::callback(|done| load_file(path, move |bytes| done.resolve(bytes)))
+}
@@ -0,0 +1,125 @@
+//! Gestures built from plain input events: multi-clicks, and pinch and rotate
+//! from two finger touches and from Ctrl + mouse wheel.
+
+use crate::sapp;
+
+/// Magnification per unit of wheel scroll with Ctrl held.
+const WHEEL_MAGNIFICATION: f32 = 0.1;
+
+/// How far, in pixels, the mouse may move between the clicks of a double click
+const CLICK_DISTANCE: f32 = 4.;
+
+/// Change of a two finger gesture since the previous touch event.
+pub(crate) struct Pinch {
+    pub magnification: f32,
+    /// Radians, clockwise on screen
+    pub rotation: f32,
+    /// Between the two fingers
+    pub x: f32,
+    pub y: f32,
+}
+
+/// First two fingers down, their distance, angle and center
+struct Shape {
+    ids: (u64, u64),
+    distance: f32,
+    angle: f32,
+    center: (f32, f32),
+}
+
+#[derive(Default)]
+pub(crate) struct Gestures {
+    /// Touches down as (identifier, x, y), in the order they started
+    touches: Vec<(u64, f32, f32)>,
+    /// Where Ctrl + wheel zooms around
+    pub mouse_position: (f32, f32),
+    /// Ctrl + wheel is not reported as `mouse_wheel_event`, see `Conf::ctrl_wheel_only_magnifies`
+    pub ctrl_wheel_only_magnifies: bool,
+    /// Clicks in the current series, see `Context::click_count`
+    pub click_count: u32,
+    /// Time and position of the last mouse press
+    last_click: Option<(f64, f32, f32)>,
+}
+
+impl Gestures {
+    /// Track a touch event, returns the pinch step when the two first fingers moved.
+    pub fn touch(
+        &mut self,
+        event_type: sapp::sapp_event_type,
+        touches: &[sapp::sapp_touchpoint],
+    ) -> Option<Pinch> {
+        let before = self.shape();
+
+        let lifted = event_type == sapp::sapp_event_type_SAPP_EVENTTYPE_TOUCHES_ENDED
+            || event_type == sapp::sapp_event_type_SAPP_EVENTTYPE_TOUCHES_CANCELLED;
+        for touch in touches.iter().filter(|touch| touch.changed) {
+            let id = touch.identifier as u64;
+            let n = self
+                .touches
+                .iter()
+                .position(|(touch_id, _, _)| *touch_id == id);
+            match n {
+                Some(n) if lifted => {
+                    self.touches.remove(n);
+                }
+                Some(n) => self.touches[n] = (id, touch.pos_x, touch.pos_y),
+                None if !lifted => self.touches.push((id, touch.pos_x, touch.pos_y)),
+                None => {}
+            }
+        }
+
+        let after = self.shape();
+        match (before, after) {
+            (Some(before), Some(after)) if before.ids == after.ids && before.distance >= 1. => {
+                let mut rotation = after.angle - before.angle;
+                if rotation > std::f32::consts::PI {
+                    rotation -= 2. * std::f32::consts::PI;
+                } else if rotation < -std::f32::consts::PI {
+                    rotation += 2. * std::f32::consts::PI;
+                }
+                Some(Pinch {
+                    magnification: after.distance / before.distance,
+                    rotation,
+                    x: after.center.0,
+                    y: after.center.1,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Count a mouse press, it continues the series of clicks when close enough in time,
+    /// `interval` seconds, and space to the previous one.
+    pub fn click(&mut self, time: f64, x: f32, y: f32, interval: f64) {
+        let in_series = self
+            .last_click
+            .map_or(false, |(last_time, last_x, last_y)| {
+                time - last_time <= interval
+                    && (x - last_x).abs() <= CLICK_DISTANCE
+                    && (y - last_y).abs() <= CLICK_DISTANCE
+            });
+        self.click_count = if in_series { self.click_count + 1 } else { 1 };
+        self.last_click = Some((time, x, y));
+    }
+
+    fn shape(&self) -> Option<Shape> {
+        if self.touches.len() < 2 {
+            return None;
+        }
+        let (id0, x0, y0) = self.touches[0];
+        let (id1, x1, y1) = self.touches[1];
+        let (dx, dy) = (x1 - x0, y1 - y0);
+        Some(Shape {
+            ids: (id0, id1),
+            distance: (dx * dx + dy * dy).sqrt(),
+            angle: dy.atan2(dx),
+            center: ((x0 + x1) / 2., (y0 + y1) / 2.),
+        })
+    }
+}
+
+/// Ctrl + wheel zooms in when scrolling up. Trackpad pinches arrive this way in browsers
+/// and from windows precision touchpads, in many small steps.
+pub(crate) fn wheel_magnification(scroll_y: f32) -> f32 {
+    (scroll_y * WHEEL_MAGNIFICATION).exp()
+}
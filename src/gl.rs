@@ -0,0 +1,142 @@
+//! Bindings of the platform backend, for `Context::with_raw_gl`: OpenGL on desktop,
+//! on the web only the WebGL 1 functions `gl.js` implements.
+//!
+//! With the `gl-loader` feature, the GL functions miniquad calls can come from another
+//! loader than the backend's, see `set_loader`: libglvnd, EGL, or the windowing library
+//! owning the GL context. On the web the browser provides them and the feature does nothing.
+
+pub use crate::sapp::*;
+
+#[cfg(all(feature = "gl-loader", not(target_arch = "wasm32")))]
+use std::{ffi::c_void, ptr};
+
+/// Where GL function pointers come from, like `eglGetProcAddress` or the
+/// `get_proc_address` of SDL, glutin or GTK.
+#[cfg(all(feature = "gl-loader", not(target_arch = "wasm32")))]
+pub trait GlLoader {
+    /// Address of the GL function `name`, null when there is none.
+    fn get_proc_address(&self, name: &str) -> *const c_void;
+}
+
+#[cfg(all(feature = "gl-loader", not(target_arch = "wasm32")))]
+impl<F: Fn(&str) -> *const c_void> GlLoader for F {
+    fn get_proc_address(&self, name: &str) -> *const c_void {
+        self(name)
+    }
+}
+
+macro_rules! gl_functions {
+    ($(fn $name:ident($($arg:ident: $ty:ty),*) $(-> $ret:ty)?;)*) => {
+        #[allow(non_snake_case)]
+        struct Functions {
+            $($name: *const c_void,)*
+        }
+
+        static mut FUNCTIONS: Functions = Functions {
+            $($name: ptr::null(),)*
+        };
+
+        /// Take the GL functions miniquad calls from `loader`, before creating the
+        /// `Context`, on the thread using GL. Functions the loader has no address for,
+        /// and all of them until this is called, are the backend's own.
+        pub fn set_loader(loader: &dyn GlLoader) {
+            unsafe {
+                FUNCTIONS = Functions {
+                    $($name: loader.get_proc_address(stringify!($name)),)*
+                };
+            }
+        }
+
+        $(
+            #[allow(non_snake_case)]
+            pub unsafe fn $name($($arg: $ty),*) $(-> $ret)? {
+                let f = FUNCTIONS.$name;
+                if f.is_null() {
+                    crate::sapp::$name($($arg),*)
+                } else {
+                    let f: unsafe extern "system" fn($($ty),*) $(-> $ret)? = std::mem::transmute(f);
+                    f($($arg),*)
+                }
+            }
+        )*
+    };
+}
+
+// the functions miniquad calls, shadowing the ones of the backend
+#[cfg(all(feature = "gl-loader", not(target_arch = "wasm32")))]
+gl_functions! {
+    fn glActiveTexture(texture: GLenum);
+    fn glAttachShader(program: GLuint, shader: GLuint);
+    fn glBeginQuery(target: GLenum, id: GLuint);
+    fn glBindBuffer(target: GLenum, buffer: GLuint);
+    fn glBindBufferRange(target: GLenum, index: GLuint, buffer: GLuint, offset: GLintptr, size: GLsizeiptr);
+    fn glBindFramebuffer(target: GLenum, framebuffer: GLuint);
+    fn glBindTexture(target: GLenum, texture: GLuint);
+    fn glBindVertexArray(array: GLuint);
+    fn glBlendEquationSeparate(modeRGB: GLenum, modeAlpha: GLenum);
+    fn glBlendFunc(sfactor: GLenum, dfactor: GLenum);
+    fn glBufferData(target: GLenum, size: GLsizeiptr, data: *const c_void, usage: GLenum);
+    fn glBufferSubData(target: GLenum, offset: GLintptr, size: GLsizeiptr, data: *const c_void);
+    fn glClear(mask: GLbitfield);
+    fn glClearColor(red: GLclampf, green: GLclampf, blue: GLclampf, alpha: GLclampf);
+    fn glClearDepthf(d: GLfloat);
+    fn glClearStencil(s: GLint);
+    fn glClipControl(origin: GLenum, depth: GLenum);
+    fn glColorMask(red: GLboolean, green: GLboolean, blue: GLboolean, alpha: GLboolean);
+    fn glCompileShader(shader: GLuint);
+    fn glCompressedTexImage2D(target: GLenum, level: GLint, internalformat: GLenum, width: GLsizei, height: GLsizei, border: GLint, imageSize: GLsizei, data: *const c_void);
+    fn glCreateProgram() -> GLuint;
+    fn glCreateShader(type_: GLenum) -> GLuint;
+    fn glDeleteFramebuffers(n: GLsizei, framebuffers: *const GLuint);
+    fn glDeleteProgram(program: GLuint);
+    fn glDeleteQueries(n: GLsizei, ids: *const GLuint);
+    fn glDeleteShader(shader: GLuint);
+    fn glDeleteTextures(n: GLsizei, textures: *const GLuint);
+    fn glDepthFunc(func: GLenum);
+    fn glDisable(cap: GLenum);
+    fn glDisableVertexAttribArray(index: GLuint);
+    fn glDrawElementsInstanced(mode: GLenum, count: GLsizei, type_: GLenum, indices: *const c_void, instancecount: GLsizei);
+    fn glEGLImageTargetTexture2DOES(target: GLenum, image: *mut c_void);
+    fn glEnable(cap: GLenum);
+    fn glEnableVertexAttribArray(index: GLuint);
+    fn glEndQuery(target: GLenum);
+    fn glFramebufferTexture2D(target: GLenum, attachment: GLenum, textarget: GLenum, texture: GLuint, level: GLint);
+    fn glFramebufferTextureMultiviewOVR(target: GLenum, attachment: GLenum, texture: GLuint, level: GLint, baseViewIndex: GLint, numViews: GLsizei);
+    fn glGenBuffers(n: GLsizei, buffers: *mut GLuint);
+    fn glGenFramebuffers(n: GLsizei, framebuffers: *mut GLuint);
+    fn glGenQueries(n: GLsizei, ids: *mut GLuint);
+    fn glGenTextures(n: GLsizei, textures: *mut GLuint);
+    fn glGenVertexArrays(n: GLsizei, arrays: *mut GLuint);
+    fn glGetAttribLocation(program: GLuint, name: *const GLchar) -> GLint;
+    fn glGetError() -> GLenum;
+    fn glGetIntegerv(pname: GLenum, params: *mut GLint);
+    fn glGetProgramInfoLog(program: GLuint, bufSize: GLsizei, length: *mut GLsizei, infoLog: *mut GLchar);
+    fn glGetProgramiv(program: GLuint, pname: GLenum, params: *mut GLint);
+    fn glGetQueryObjectuiv(id: GLuint, pname: GLenum, params: *mut GLuint);
+    fn glGetShaderInfoLog(shader: GLuint, bufSize: GLsizei, length: *mut GLsizei, infoLog: *mut GLchar);
+    fn glGetShaderiv(shader: GLuint, pname: GLenum, params: *mut GLint);
+    fn glGetStringi(name: GLenum, index: GLuint) -> *const GLubyte;
+    fn glGetUniformBlockIndex(program: GLuint, uniformBlockName: *const GLchar) -> GLuint;
+    fn glGetUniformLocation(program: GLuint, name: *const GLchar) -> GLint;
+    fn glLinkProgram(program: GLuint);
+    fn glPolygonOffset(factor: GLfloat, units: GLfloat);
+    fn glReadPixels(x: GLint, y: GLint, width: GLsizei, height: GLsizei, format: GLenum, type_: GLenum, pixels: *mut c_void);
+    fn glScissor(x: GLint, y: GLint, width: GLsizei, height: GLsizei);
+    fn glShaderSource(shader: GLuint, count: GLsizei, string: *const *const GLchar, length: *const GLint);
+    fn glTexImage2D(target: GLenum, level: GLint, internalFormat: GLint, width: GLsizei, height: GLsizei, border: GLint, format: GLenum, type_: GLenum, pixels: *const c_void);
+    fn glTexImage3D(target: GLenum, level: GLint, internalFormat: GLint, width: GLsizei, height: GLsizei, depth: GLsizei, border: GLint, format: GLenum, type_: GLenum, pixels: *const c_void);
+    fn glTexParameteri(target: GLenum, pname: GLenum, param: GLint);
+    fn glTexSubImage2D(target: GLenum, level: GLint, xoffset: GLint, yoffset: GLint, width: GLsizei, height: GLsizei, format: GLenum, type_: GLenum, pixels: *const c_void);
+    fn glUniform1f(location: GLint, v0: GLfloat);
+    fn glUniform1fv(location: GLint, count: GLsizei, value: *const GLfloat);
+    fn glUniform1i(location: GLint, v0: GLint);
+    fn glUniform2fv(location: GLint, count: GLsizei, value: *const GLfloat);
+    fn glUniform3fv(location: GLint, count: GLsizei, value: *const GLfloat);
+    fn glUniform4fv(location: GLint, count: GLsizei, value: *const GLfloat);
+    fn glUniformBlockBinding(program: GLuint, uniformBlockIndex: GLuint, uniformBlockBinding: GLuint);
+    fn glUniformMatrix4fv(location: GLint, count: GLsizei, transpose: GLboolean, value: *const GLfloat);
+    fn glUseProgram(program: GLuint);
+    fn glVertexAttribDivisor(index: GLuint, divisor: GLuint);
+    fn glVertexAttribPointer(index: GLuint, size: GLint, type_: GLenum, normalized: GLboolean, stride: GLsizei, pointer: *const c_void);
+    fn glViewport(x: GLint, y: GLint, width: GLsizei, height: GLsizei);
+}
@@ -0,0 +1,833 @@
+//! glTF 2.0 import: meshes become vertex and index buffers with their layout,
+//! images become textures.
+//!
+//! Reads binary `.glb` files and `.gltf` files whose buffers and images are embedded
+//! as data URIs, or loaded by the caller for external URIs. Every vertex attribute is
+//! converted to floats, normalized integers to 0..1 or -1..1, and gets a vertex buffer
+//! of its own, so a pipeline may use only some of them:
+//!
+//! ```ignore
+//! let gltf = gltf::load_embedded(ctx, &bytes).unwrap();
+//! let primitive = &gltf.meshes[0].primitives[0];
+//! let attributes = primitive.attributes_named(&["position", "texcoord0"]);
+//! let pipeline = Pipeline::new(ctx, &primitive.buffer_layouts(), &attributes, shader);
+//! let bindings = primitive.bindings(vec![gltf.images[0]]);
+//! ```
+//!
+//! Scenes, nodes, animations, cameras and samplers are not imported, nor are sparse
+//! accessors and primitives other than triangle lists.
+
+use crate::graphics::*;
+use crate::Color;
+
+#[derive(Debug)]
+pub enum Error {
+    /// Neither a .glb file nor glTF JSON
+    NotGltf,
+    Corrupted(&'static str),
+    /// Valid glTF, but uses a feature this loader does not support
+    Unsupported(&'static str),
+    /// External file the callback given to `load` did not provide
+    MissingFile(String),
+    Image(TextureLoadError),
+}
+
+pub struct Gltf {
+    pub meshes: Vec<Mesh>,
+    /// One per glTF image, in the file's order
+    pub images: Vec<Texture>,
+    pub materials: Vec<Material>,
+}
+
+pub struct Mesh {
+    pub name: Option<String>,
+    pub primitives: Vec<Primitive>,
+}
+
+/// Part of a mesh drawn with one material.
+pub struct Primitive {
+    /// One per attribute, attribute `n` reads buffer `n`
+    pub vertex_buffers: Vec<Buffer>,
+    /// Named after the glTF semantics: `position`, `normal`, `tangent`, `texcoord0`,
+    /// `texcoord1`, `color0`, `joints0` and `weights0`, for the ones the primitive has
+    pub attributes: Vec<VertexAttribute>,
+    pub index_buffer: Buffer,
+    /// Number of indices, to give to `Context::draw`
+    pub num_elements: i32,
+    /// Index in `Gltf::materials`
+    pub material: Option<usize>,
+}
+
+impl Primitive {
+    /// Layout of the vertex buffers for `Pipeline::new`, whichever attributes it uses.
+    pub fn buffer_layouts(&self) -> Vec<BufferLayout> {
+        vec![BufferLayout::default(); self.vertex_buffers.len()]
+    }
+
+    /// The attributes a shader reads, in the order of `names`, missing ones skipped.
+    pub fn attributes_named(&self, names: &[&str]) -> Vec<VertexAttribute> {
+        names
+            .iter()
+            .filter_map(|name| {
+                self.attributes
+                    .iter()
+                    .find(|attribute| attribute.name == *name)
+                    .cloned()
+            })
+            .collect()
+    }
+
+    pub fn bindings(&self, images: Vec<Texture>) -> Bindings {
+        Bindings {
+            vertex_buffers: self.vertex_buffers.clone(),
+            index_buffer: self.index_buffer,
+            images,
+        }
+    }
+}
+
+/// The base color of the metallic-roughness material, the rest is not imported.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Material {
+    pub base_color: Color,
+    /// Index in `Gltf::images`
+    pub base_color_image: Option<usize>,
+}
+
+const GLB_MAGIC: &[u8] = b"glTF";
+const CHUNK_JSON: u32 = 0x4E4F_534A;
+const CHUNK_BIN: u32 = 0x004E_4942;
+
+/// Load a .glb or .gltf file, `external` provides the content of the files it refers to,
+/// by their URI relative to the file.
+pub fn load<F>(ctx: &mut Context, bytes: &[u8], mut external: F) -> Result<Gltf, Error>
+where
+    F: FnMut(&str) -> Option<Vec<u8>>,
+{
+    let (text, bin) = if bytes.starts_with(GLB_MAGIC) {
+        split_glb(bytes)?
+    } else {
+        (bytes, None)
+    };
+    let text = std::str::from_utf8(text).map_err(|_| Error::NotGltf)?;
+    let root = json::parse(text).ok_or(Error::NotGltf)?;
+    if root.get("asset").is_none() {
+        return Err(Error::NotGltf);
+    }
+
+    let mut buffers = vec![];
+    for buffer in root.get("buffers").as_array() {
+        let data = match buffer.get("uri").as_str() {
+            Some(uri) => load_uri(uri, &mut external)?,
+            None => bin.ok_or(Error::Corrupted("buffer without data"))?.to_vec(),
+        };
+        buffers.push(data);
+    }
+    let document = Document {
+        root: &root,
+        buffers,
+    };
+
+    let mut images = vec![];
+    for image in root.get("images").as_array() {
+        let view = image.get("bufferView").as_usize();
+        let bytes = match (image.get("uri").as_str(), view) {
+            (Some(uri), _) => load_uri(uri, &mut external)?,
+            (None, Some(view)) => document.buffer_view(view)?.0.to_vec(),
+            (None, None) => return Err(Error::Corrupted("image without data")),
+        };
+        images.push(Texture::from_encoded(&bytes).map_err(Error::Image)?);
+    }
+
+    let materials = root
+        .get("materials")
+        .as_array()
+        .iter()
+        .map(|material| {
+            let pbr = material.get("pbrMetallicRoughness");
+            let factor = pbr.get("baseColorFactor").as_array();
+            let factor = |n: usize| factor.get(n).and_then(Json::as_f64).unwrap_or(1.) as f32;
+            let base_color_image = pbr
+                .get("baseColorTexture")
+                .get("index")
+                .as_usize()
+                .and_then(|texture| root.get("textures").as_array().get(texture))
+                .and_then(|texture| texture.get("source").as_usize());
+            Material {
+                base_color: Color::new(factor(0), factor(1), factor(2), factor(3)),
+                base_color_image,
+            }
+        })
+        .collect();
+
+    let mut meshes = vec![];
+    for mesh in root.get("meshes").as_array() {
+        let mut primitives = vec![];
+        for primitive in mesh.get("primitives").as_array() {
+            primitives.push(document.primitive(ctx, primitive)?);
+        }
+        meshes.push(Mesh {
+            name: mesh.get("name").as_str().map(str::to_owned),
+            primitives,
+        });
+    }
+
+    Ok(Gltf {
+        meshes,
+        images,
+        materials,
+    })
+}
+
+/// Load a .glb file, or a .gltf file with all its data in data URIs.
+pub fn load_embedded(ctx: &mut Context, bytes: &[u8]) -> Result<Gltf, Error> {
+    load(ctx, bytes, |_| None)
+}
+
+/// JSON and binary chunks of a .glb file
+fn split_glb(bytes: &[u8]) -> Result<(&[u8], Option<&[u8]>), Error> {
+    let u32_at = |pos: usize| {
+        bytes
+            .get(pos..pos + 4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .ok_or(Error::Corrupted("truncated .glb"))
+    };
+    if u32_at(4)? != 2 {
+        return Err(Error::Unsupported("glTF version other than 2"));
+    }
+    let len = (u32_at(8)? as usize).min(bytes.len());
+
+    let (mut json, mut bin) = (None, None);
+    let mut pos = 12;
+    while pos + 8 <= len {
+        let chunk_len = u32_at(pos)? as usize;
+        let chunk = bytes
+            .get(pos + 8..pos + 8 + chunk_len)
+            .ok_or(Error::Corrupted("truncated .glb chunk"))?;
+        match u32_at(pos + 4)? {
+            CHUNK_JSON if json.is_none() => json = Some(chunk),
+            CHUNK_BIN if bin.is_none() => bin = Some(chunk),
+            _ => {}
+        }
+        pos += 8 + chunk_len;
+    }
+    Ok((json.ok_or(Error::Corrupted(".glb without JSON"))?, bin))
+}
+
+fn load_uri<F>(uri: &str, external: &mut F) -> Result<Vec<u8>, Error>
+where
+    F: FnMut(&str) -> Option<Vec<u8>>,
+{
+    if uri.starts_with("data:") {
+        let comma = uri.find(',').ok_or(Error::Corrupted("data URI"))?;
+        if !uri[..comma].ends_with(";base64") {
+            return Err(Error::Unsupported("data URI not in base64"));
+        }
+        return base64(&uri[comma + 1..]).ok_or(Error::Corrupted("data URI"));
+    }
+    external(uri).ok_or_else(|| Error::MissingFile(uri.to_owned()))
+}
+
+fn base64(text: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(text.len() * 3 / 4);
+    let (mut bits, mut nbits) = (0u32, 0);
+    for c in text.bytes().take_while(|c| *c != b'=') {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+        bits = bits << 6 | value as u32;
+        nbits += 6;
+        if nbits >= 8 {
+            nbits -= 8;
+            bytes.push((bits >> nbits) as u8);
+        }
+    }
+    Some(bytes)
+}
+
+struct Document<'a> {
+    root: &'a Json,
+    buffers: Vec<Vec<u8>>,
+}
+
+/// glTF semantics and the names of their attributes
+const SEMANTICS: &[(&str, &str)] = &[
+    ("POSITION", "position"),
+    ("NORMAL", "normal"),
+    ("TANGENT", "tangent"),
+    ("TEXCOORD_0", "texcoord0"),
+    ("TEXCOORD_1", "texcoord1"),
+    ("COLOR_0", "color0"),
+    ("JOINTS_0", "joints0"),
+    ("WEIGHTS_0", "weights0"),
+];
+
+impl<'a> Document<'a> {
+    /// Bytes of a buffer view, from its start, and its stride, 0 when tightly packed
+    fn buffer_view(&self, index: usize) -> Result<(&[u8], usize), Error> {
+        let view = self
+            .root
+            .get("bufferViews")
+            .as_array()
+            .get(index)
+            .ok_or(Error::Corrupted("buffer view index"))?;
+        let buffer = view
+            .get("buffer")
+            .as_usize()
+            .and_then(|buffer| self.buffers.get(buffer))
+            .ok_or(Error::Corrupted("buffer index"))?;
+        let offset = view.get("byteOffset").as_usize().unwrap_or(0);
+        let len = view
+            .get("byteLength")
+            .as_usize()
+            .ok_or(Error::Corrupted("buffer view length"))?;
+        let bytes = offset
+            .checked_add(len)
+            .and_then(|end| buffer.get(offset..end))
+            .ok_or(Error::Corrupted("buffer view out of its buffer"))?;
+        Ok((bytes, view.get("byteStride").as_usize().unwrap_or(0)))
+    }
+
+    /// Components per element and values of an accessor, converted to floats
+    fn read_floats(&self, index: usize) -> Result<(usize, Vec<f32>), Error> {
+        let accessor = self.accessor(index)?;
+        let mut floats = Vec::with_capacity(accessor.count * accessor.components);
+        self.read(&accessor, |value, _| floats.push(value))?;
+        Ok((accessor.components, floats))
+    }
+
+    fn read_indices(&self, index: usize) -> Result<Vec<u32>, Error> {
+        let accessor = self.accessor(index)?;
+        let mut indices = Vec::with_capacity(accessor.count);
+        self.read(&accessor, |_, raw| indices.push(raw))?;
+        Ok(indices)
+    }
+
+    fn accessor(&self, index: usize) -> Result<Accessor, Error> {
+        let accessor = self
+            .root
+            .get("accessors")
+            .as_array()
+            .get(index)
+            .ok_or(Error::Corrupted("accessor index"))?;
+        if accessor.get("sparse").is_some() {
+            return Err(Error::Unsupported("sparse accessors"));
+        }
+        let components = match accessor.get("type").as_str() {
+            Some("SCALAR") => 1,
+            Some("VEC2") => 2,
+            Some("VEC3") => 3,
+            Some("VEC4") => 4,
+            Some("MAT2") | Some("MAT3") | Some("MAT4") => {
+                return Err(Error::Unsupported("matrix vertex attributes"))
+            }
+            _ => return Err(Error::Corrupted("accessor type")),
+        };
+        let component_type = accessor
+            .get("componentType")
+            .as_usize()
+            .ok_or(Error::Corrupted("accessor component type"))?;
+        let component_size = match component_type {
+            5120 | 5121 => 1,
+            5122 | 5123 => 2,
+            5125 | 5126 => 4,
+            _ => return Err(Error::Corrupted("accessor component type")),
+        };
+        let accessor = Accessor {
+            view: accessor.get("bufferView").as_usize(),
+            offset: accessor.get("byteOffset").as_usize().unwrap_or(0),
+            count: accessor
+                .get("count")
+                .as_usize()
+                .ok_or(Error::Corrupted("accessor count"))?,
+            components,
+            component_type,
+            component_size,
+            normalized: accessor.get("normalized").as_bool(),
+        };
+        self.check_bounds(&accessor)?;
+        Ok(accessor)
+    }
+
+    /// Accessors must stay within their buffer view, checked before anything is allocated
+    /// for their values: the numbers come from the file and may be anything.
+    fn check_bounds(&self, accessor: &Accessor) -> Result<(), Error> {
+        let values = accessor
+            .count
+            .checked_mul(accessor.components)
+            .ok_or(Error::Corrupted("accessor count"))?;
+        let view = match accessor.view {
+            Some(view) => view,
+            // zeros, no more than the buffers could have held
+            None => {
+                let buffers_len: usize = self.buffers.iter().map(Vec::len).sum();
+                if values > buffers_len {
+                    return Err(Error::Corrupted("accessor count"));
+                }
+                return Ok(());
+            }
+        };
+        if accessor.count == 0 {
+            return Ok(());
+        }
+        let (bytes, stride) = self.buffer_view(view)?;
+        let element_size = accessor.components * accessor.component_size;
+        let stride = if stride == 0 { element_size } else { stride };
+        let end = (accessor.count - 1)
+            .checked_mul(stride)
+            .and_then(|len| len.checked_add(accessor.offset))
+            .and_then(|len| len.checked_add(element_size));
+        match end {
+            Some(end) if end <= bytes.len() => Ok(()),
+            _ => Err(Error::Corrupted("accessor out of its buffer view")),
+        }
+    }
+
+    /// Call `f` with every component of the accessor, as a float, normalized if asked,
+    /// and as the raw integer for integer types.
+    fn read<F: FnMut(f32, u32)>(&self, accessor: &Accessor, mut f: F) -> Result<(), Error> {
+        let view = match accessor.view {
+            Some(view) => view,
+            // no buffer view: all zeros
+            None => {
+                for _ in 0..accessor.count * accessor.components {
+                    f(0., 0);
+                }
+                return Ok(());
+            }
+        };
+        // within the view, see check_bounds
+        let (bytes, stride) = self.buffer_view(view)?;
+        let element_size = accessor.components * accessor.component_size;
+        let stride = if stride == 0 { element_size } else { stride };
+
+        for n in 0..accessor.count {
+            let element = &bytes[accessor.offset + n * stride..];
+            for c in 0..accessor.components {
+                let b = &element[c * accessor.component_size..];
+                let (value, raw) = match accessor.component_type {
+                    5120 => {
+                        let v = b[0] as i8;
+                        let norm = (v as f32 / 127.).max(-1.);
+                        (if accessor.normalized { norm } else { v as f32 }, v as u32)
+                    }
+                    5121 => {
+                        let v = b[0];
+                        let norm = v as f32 / 255.;
+                        (if accessor.normalized { norm } else { v as f32 }, v as u32)
+                    }
+                    5122 => {
+                        let v = i16::from_le_bytes([b[0], b[1]]);
+                        let norm = (v as f32 / 32767.).max(-1.);
+                        (if accessor.normalized { norm } else { v as f32 }, v as u32)
+                    }
+                    5123 => {
+                        let v = u16::from_le_bytes([b[0], b[1]]);
+                        let norm = v as f32 / 65535.;
+                        (if accessor.normalized { norm } else { v as f32 }, v as u32)
+                    }
+                    5125 => {
+                        let v = u32::from_le_bytes([b[0], b[1], b[2], b[3]]);
+                        (v as f32, v)
+                    }
+                    _ => (f32::from_le_bytes([b[0], b[1], b[2], b[3]]), 0),
+                };
+                f(value, raw);
+            }
+        }
+        Ok(())
+    }
+
+    fn primitive(&self, ctx: &mut Context, primitive: &Json) -> Result<Primitive, Error> {
+        if primitive.get("mode").as_usize().unwrap_or(4) != 4 {
+            return Err(Error::Unsupported("primitives other than triangle lists"));
+        }
+
+        let mut vertex_buffers = vec![];
+        let mut attributes = vec![];
+        let mut vertex_count = 0;
+        for (semantic, name) in SEMANTICS {
+            let accessor = match primitive.get("attributes").get(semantic).as_usize() {
+                Some(accessor) => accessor,
+                None => continue,
+            };
+            let (components, floats) = self.read_floats(accessor)?;
+            let format = match components {
+                1 => VertexFormat::Float1,
+                2 => VertexFormat::Float2,
+                3 => VertexFormat::Float3,
+                _ => VertexFormat::Float4,
+            };
+            vertex_count = floats.len() / components;
+            attributes.push(VertexAttribute::with_buffer(
+                name,
+                format,
+                vertex_buffers.len(),
+            ));
+            vertex_buffers.push(Buffer::immutable(ctx, BufferType::VertexBuffer, &floats));
+        }
+        if attributes.is_empty() {
+            return Err(Error::Corrupted("primitive without attributes"));
+        }
+
+        let indices = match primitive.get("indices").as_usize() {
+            Some(accessor) => self.read_indices(accessor)?,
+            None => (0..vertex_count as u32).collect(),
+        };
+        let max = indices.iter().max().copied().unwrap_or(0);
+        let index_buffer = if max <= u16::max_value() as u32 {
+            let indices: Vec<u16> = indices.iter().map(|index| *index as u16).collect();
+            Buffer::immutable(ctx, BufferType::IndexBuffer, &indices)
+        } else {
+            Buffer::immutable(ctx, BufferType::IndexBuffer, &indices)
+        };
+
+        Ok(Primitive {
+            vertex_buffers,
+            attributes,
+            index_buffer,
+            num_elements: indices.len() as i32,
+            material: primitive.get("material").as_usize(),
+        })
+    }
+}
+
+struct Accessor {
+    view: Option<usize>,
+    offset: usize,
+    count: usize,
+    components: usize,
+    component_type: usize,
+    component_size: usize,
+    normalized: bool,
+}
+
+use json::Json;
+
+/// Just enough JSON for glTF: no validation of what follows the document,
+/// numbers as f64.
+mod json {
+    pub enum Json {
+        Null,
+        Bool(bool),
+        Number(f64),
+        String(String),
+        Array(Vec<Json>),
+        Object(Vec<(String, Json)>),
+    }
+
+    static NULL: Json = Json::Null;
+
+    impl Json {
+        /// Member of an object, `Null` when missing so lookups can be chained
+        pub fn get(&self, key: &str) -> &Json {
+            match self {
+                Json::Object(members) => members
+                    .iter()
+                    .find(|(name, _)| name == key)
+                    .map_or(&NULL, |(_, value)| value),
+                _ => &NULL,
+            }
+        }
+
+        pub fn is_none(&self) -> bool {
+            matches!(self, Json::Null)
+        }
+
+        pub fn is_some(&self) -> bool {
+            !self.is_none()
+        }
+
+        /// Elements of an array, none for anything else
+        pub fn as_array(&self) -> &[Json] {
+            match self {
+                Json::Array(elements) => elements,
+                _ => &[],
+            }
+        }
+
+        pub fn as_str(&self) -> Option<&str> {
+            match self {
+                Json::String(string) => Some(string),
+                _ => None,
+            }
+        }
+
+        pub fn as_f64(&self) -> Option<f64> {
+            match self {
+                Json::Number(number) => Some(*number),
+                _ => None,
+            }
+        }
+
+        pub fn as_usize(&self) -> Option<usize> {
+            self.as_f64()
+                .filter(|number| *number >= 0. && number.fract() == 0.)
+                .map(|number| number as usize)
+        }
+
+        pub fn as_bool(&self) -> bool {
+            matches!(self, Json::Bool(true))
+        }
+    }
+
+    pub fn parse(text: &str) -> Option<Json> {
+        let mut parser = Parser {
+            bytes: text.as_bytes(),
+            pos: 0,
+        };
+        parser.value()
+    }
+
+    struct Parser<'a> {
+        bytes: &'a [u8],
+        pos: usize,
+    }
+
+    impl<'a> Parser<'a> {
+        fn skip_whitespace(&mut self) {
+            while let Some(b' ') | Some(b'\t') | Some(b'\n') | Some(b'\r') = self.peek() {
+                self.pos += 1;
+            }
+        }
+
+        fn peek(&self) -> Option<u8> {
+            self.bytes.get(self.pos).copied()
+        }
+
+        fn eat(&mut self, expected: u8) -> Option<()> {
+            self.skip_whitespace();
+            if self.peek()? == expected {
+                self.pos += 1;
+                Some(())
+            } else {
+                None
+            }
+        }
+
+        fn literal(&mut self, word: &str, value: Json) -> Option<Json> {
+            if self.bytes[self.pos..].starts_with(word.as_bytes()) {
+                self.pos += word.len();
+                Some(value)
+            } else {
+                None
+            }
+        }
+
+        fn value(&mut self) -> Option<Json> {
+            self.skip_whitespace();
+            match self.peek()? {
+                b'{' => {
+                    self.pos += 1;
+                    let mut members = vec![];
+                    if self.eat(b'}').is_some() {
+                        return Some(Json::Object(members));
+                    }
+                    loop {
+                        self.skip_whitespace();
+                        let key = self.string()?;
+                        self.eat(b':')?;
+                        members.push((key, self.value()?));
+                        if self.eat(b',').is_none() {
+                            self.eat(b'}')?;
+                            return Some(Json::Object(members));
+                        }
+                    }
+                }
+                b'[' => {
+                    self.pos += 1;
+                    let mut elements = vec![];
+                    if self.eat(b']').is_some() {
+                        return Some(Json::Array(elements));
+                    }
+                    loop {
+                        elements.push(self.value()?);
+                        if self.eat(b',').is_none() {
+                            self.eat(b']')?;
+                            return Some(Json::Array(elements));
+                        }
+                    }
+                }
+                b'"' => self.string().map(Json::String),
+                b't' => self.literal("true", Json::Bool(true)),
+                b'f' => self.literal("false", Json::Bool(false)),
+                b'n' => self.literal("null", Json::Null),
+                _ => self.number(),
+            }
+        }
+
+        fn number(&mut self) -> Option<Json> {
+            let start = self.pos;
+            while let Some(b'0'..=b'9') | Some(b'-') | Some(b'+') | Some(b'.') | Some(b'e')
+            | Some(b'E') = self.peek()
+            {
+                self.pos += 1;
+            }
+            let text = std::str::from_utf8(&self.bytes[start..self.pos]).ok()?;
+            text.parse().ok().map(Json::Number)
+        }
+
+        fn string(&mut self) -> Option<String> {
+            if self.peek()? != b'"' {
+                return None;
+            }
+            self.pos += 1;
+            let mut string = vec![];
+            loop {
+                let c = self.peek()?;
+                self.pos += 1;
+                match c {
+                    b'"' => return String::from_utf8(string).ok(),
+                    b'\\' => {
+                        let escaped = self.peek()?;
+                        self.pos += 1;
+                        let c = match escaped {
+                            b'b' => '\u{8}',
+                            b'f' => '\u{c}',
+                            b'n' => '\n',
+                            b'r' => '\r',
+                            b't' => '\t',
+                            b'u' => {
+                                let mut code = self.hex4()?;
+                                // surrogate pair
+                                if (0xd800..0xdc00).contains(&code)
+                                    && self.bytes[self.pos..].starts_with(b"\\u")
+                                {
+                                    self.pos += 2;
+                                    let low = self.hex4()?;
+                                    code = 0x10000 + ((code - 0xd800) << 10) + (low - 0xdc00);
+                                }
+                                std::char::from_u32(code).unwrap_or('\u{fffd}')
+                            }
+                            c => c as char,
+                        };
+                        let mut utf8 = [0; 4];
+                        string.extend_from_slice(c.encode_utf8(&mut utf8).as_bytes());
+                    }
+                    c => string.push(c),
+                }
+            }
+        }
+
+        fn hex4(&mut self) -> Option<u32> {
+            let hex = self.bytes.get(self.pos..self.pos + 4)?;
+            self.pos += 4;
+            u32::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Document over one buffer holding `bytes`, with `views` and `accessors` as JSON
+    fn read(views: &str, accessors: &str, bytes: &[u8]) -> Result<(usize, Vec<f32>), Error> {
+        let text = format!(
+            r#"{{"asset": {{}}, "bufferViews": [{}], "accessors": [{}]}}"#,
+            views, accessors
+        );
+        let root = json::parse(&text).unwrap();
+        let document = Document {
+            root: &root,
+            buffers: vec![bytes.to_vec()],
+        };
+        document.read_floats(0)
+    }
+
+    fn corrupted<T>(result: Result<T, Error>) -> bool {
+        matches!(result, Err(Error::Corrupted(_)))
+    }
+
+    #[test]
+    fn strided_normalized_values() {
+        let bytes = [0, 255, 9, 9, 128, 0, 9, 9];
+        let (components, floats) = read(
+            r#"{"buffer": 0, "byteLength": 8, "byteStride": 4}"#,
+            r#"{"bufferView": 0, "componentType": 5121, "normalized": true,
+                "count": 2, "type": "VEC2"}"#,
+            &bytes,
+        )
+        .unwrap();
+        assert_eq!(components, 2);
+        assert_eq!(floats, [0., 1., 128. / 255., 0.]);
+    }
+
+    #[test]
+    fn accessor_past_its_view() {
+        let view = r#"{"buffer": 0, "byteLength": 8}"#;
+        let bytes = [0; 8];
+        let accessor = |fields: &str| {
+            format!(
+                r#"{{"bufferView": 0, "componentType": 5126, "type": "SCALAR", {}}}"#,
+                fields
+            )
+        };
+        assert!(read(view, &accessor(r#""count": 2"#), &bytes).is_ok());
+        assert!(corrupted(read(view, &accessor(r#""count": 3"#), &bytes)));
+        assert!(corrupted(read(
+            view,
+            &accessor(r#""count": 1, "byteOffset": 5"#),
+            &bytes
+        )));
+        // overflowing usize
+        assert!(corrupted(read(
+            view,
+            &accessor(r#""count": 1, "byteOffset": 18446744073709549568"#),
+            &bytes
+        )));
+        assert!(corrupted(read(
+            view,
+            &accessor(r#""count": 4611686018427387904"#),
+            &bytes
+        )));
+        assert!(corrupted(read(
+            r#"{"buffer": 0, "byteLength": 8, "byteStride": 9223372036854775808}"#,
+            &accessor(r#""count": 3"#),
+            &bytes
+        )));
+    }
+
+    #[test]
+    fn view_past_its_buffer() {
+        let accessor = r#"{"bufferView": 0, "componentType": 5121, "count": 1, "type": "SCALAR"}"#;
+        let bytes = [0; 8];
+        assert!(corrupted(read(
+            r#"{"buffer": 0, "byteLength": 9}"#,
+            accessor,
+            &bytes
+        )));
+        assert!(corrupted(read(
+            r#"{"buffer": 0, "byteOffset": 18446744073709549568, "byteLength": 4096}"#,
+            accessor,
+            &bytes
+        )));
+    }
+
+    #[test]
+    fn zeros_without_a_view() {
+        let bytes = [0; 8];
+        let (_, floats) = read(
+            "",
+            r#"{"componentType": 5126, "count": 2, "type": "VEC3"}"#,
+            &bytes,
+        )
+        .unwrap();
+        assert_eq!(floats, [0.; 6]);
+        assert!(corrupted(read(
+            "",
+            r#"{"componentType": 5126, "count": 1000000000000, "type": "VEC3"}"#,
+            &bytes
+        )));
+        assert!(corrupted(read(
+            "",
+            r#"{"componentType": 5126, "count": 9223372036854775808, "type": "VEC4"}"#,
+            &bytes
+        )));
+    }
+}
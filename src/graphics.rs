@@ -1,6 +1,6 @@
-use std::{ffi::CString, mem};
+use std::{cell::RefCell, collections::HashMap, ffi::CString, mem};
 
-use crate::sapp::*;
+use crate::gl::*;
 
 use std::option::Option::None;
 
@@ -10,6 +10,8 @@ pub const NEAREST_FILTER: i32 = GL_NEAREST as i32;
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Texture {
     texture: GLuint,
+    /// GL_TEXTURE_2D, GL_TEXTURE_CUBE_MAP or GL_TEXTURE_2D_ARRAY
+    target: GLenum,
     pub width: u32,
     pub height: u32,
 }
@@ -18,6 +20,10 @@ pub struct Texture {
 pub enum PixelFormat {
     RGBA8,
     Depth,
+    /// Four 32 bit floats per pixel, for data read by shaders rather than images.
+    /// Needs `Features::texture_float`, and is only sampled with `FilterMode::Nearest`
+    /// on WebGL.
+    RGBA32F,
 }
 
 impl From<PixelFormat> for (GLenum, GLenum, GLenum) {
@@ -25,10 +31,41 @@ impl From<PixelFormat> for (GLenum, GLenum, GLenum) {
         match format {
             PixelFormat::RGBA8 => (GL_RGBA, GL_RGBA, GL_UNSIGNED_BYTE),
             PixelFormat::Depth => (GL_DEPTH_COMPONENT, GL_DEPTH_COMPONENT, GL_UNSIGNED_SHORT),
+            // WebGL 1 takes the internal format from the type
+            #[cfg(target_arch = "wasm32")]
+            PixelFormat::RGBA32F => (GL_RGBA, GL_RGBA, GL_FLOAT),
+            #[cfg(not(target_arch = "wasm32"))]
+            PixelFormat::RGBA32F => (GL_RGBA32F, GL_RGBA, GL_FLOAT),
         }
     }
 }
 
+/// Faces of a cubemap, in the order of GL and of the faces in containers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CubeFace {
+    PositiveX,
+    NegativeX,
+    PositiveY,
+    NegativeY,
+    PositiveZ,
+    NegativeZ,
+}
+
+impl CubeFace {
+    pub const ALL: [CubeFace; 6] = [
+        CubeFace::PositiveX,
+        CubeFace::NegativeX,
+        CubeFace::PositiveY,
+        CubeFace::NegativeY,
+        CubeFace::PositiveZ,
+        CubeFace::NegativeZ,
+    ];
+
+    fn gl_target(self) -> GLenum {
+        GL_TEXTURE_CUBE_MAP_POSITIVE_X + self as GLenum
+    }
+}
+
 /// Sets the wrap parameter for texture.
 #[repr(u8)]
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
@@ -70,7 +107,60 @@ impl Default for RenderTextureParams {
     }
 }
 
+/// How miniquad binds a texture from `Texture::from_raw_gl`
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RawTextureParams {
+    /// `GL_TEXTURE_2D`, `GL_TEXTURE_CUBE_MAP`, `GL_TEXTURE_2D_ARRAY`, or another target
+    /// the shaders sample it with, like `GL_TEXTURE_EXTERNAL_OES` on Android
+    pub target: GLenum,
+    /// Filter set on the texture, `None` leaves the one of its creator
+    pub filter: Option<FilterMode>,
+}
+
+impl Default for RawTextureParams {
+    fn default() -> Self {
+        RawTextureParams {
+            target: GL_TEXTURE_2D,
+            filter: None,
+        }
+    }
+}
+
 impl Texture {
+    /// Name of the GL texture object
+    pub fn gl_internal_id(&self) -> GLuint {
+        self.texture
+    }
+
+    /// Rough GPU memory of the texture: 4 bytes per texel, one level, one layer
+    pub(crate) fn estimated_size(&self) -> usize {
+        let faces = if self.target == GL_TEXTURE_CUBE_MAP { 6 } else { 1 };
+        self.width as usize * self.height as usize * 4 * faces
+    }
+
+    /// Texture created by some other library on miniquad's GL context, like a video
+    /// decoder or a camera capture API, to bind and render into like miniquad's own.
+    /// Its creator keeps it: it is not among the resources reported by
+    /// `EventHandler::resources_lost_event`, and should only be freed by `delete`
+    /// when its creator won't.
+    pub fn from_raw_gl(
+        texture: GLuint,
+        width: u32,
+        height: u32,
+        params: RawTextureParams,
+    ) -> Texture {
+        let texture = Texture {
+            texture,
+            target: params.target,
+            width,
+            height,
+        };
+        if let Some(filter) = params.filter {
+            texture.set_filter(filter as i32);
+        }
+        texture
+    }
+
     pub fn new_render_texture(params: RenderTextureParams) -> Texture {
         let mut texture: GLuint = 0;
 
@@ -98,11 +188,57 @@ impl Texture {
             glTexParameteri(GL_TEXTURE_2D, GL_TEXTURE_MAG_FILTER, GL_LINEAR as i32);
         }
 
-        Texture {
+        track_texture(Texture {
             texture,
+            target: GL_TEXTURE_2D,
             width: params.width,
             height: params.height,
+        })
+    }
+
+    /// Render texture with `layers` layers of `params.width`x`params.height`, for
+    /// `RenderPass::new_multiview`, one layer per view. Sampled with `sampler2DArray`
+    /// in GLSL 130 and later shaders. Not on WebGL 1, which has no array textures.
+    pub fn new_render_texture_array(params: RenderTextureParams, layers: u32) -> Texture {
+        assert!(
+            cfg!(not(target_arch = "wasm32")),
+            "No array textures on WebGL 1"
+        );
+        let mut texture: GLuint = 0;
+
+        let (internal_format, format, pixel_type) = params.format.into();
+        let target = GL_TEXTURE_2D_ARRAY;
+
+        unsafe {
+            glGenTextures(1, &mut texture as *mut _);
+            glActiveTexture(GL_TEXTURE0);
+            glBindTexture(target, texture);
+            #[cfg(not(target_arch = "wasm32"))]
+            glTexImage3D(
+                target,
+                0,
+                internal_format as i32,
+                params.width as i32,
+                params.height as i32,
+                layers as i32,
+                0,
+                format,
+                pixel_type,
+                std::ptr::null(),
+            );
+
+            glTexParameteri(target, GL_TEXTURE_WRAP_S, GL_CLAMP_TO_EDGE as i32);
+            glTexParameteri(target, GL_TEXTURE_WRAP_T, GL_CLAMP_TO_EDGE as i32);
+            glTexParameteri(target, GL_TEXTURE_MIN_FILTER, GL_LINEAR as i32);
+            glTexParameteri(target, GL_TEXTURE_MAG_FILTER, GL_LINEAR as i32);
         }
+
+        track_texture(Texture {
+            texture,
+            target,
+            width: params.width,
+            height: params.height,
+        })
     }
 
     pub fn from_rgba8(width: u16, height: u16, bytes: &[u8]) -> Texture {
@@ -128,24 +264,352 @@ impl Texture {
             glTexParameteri(GL_TEXTURE_2D, GL_TEXTURE_MIN_FILTER, GL_LINEAR as i32);
             glTexParameteri(GL_TEXTURE_2D, GL_TEXTURE_MAG_FILTER, GL_LINEAR as i32);
 
-            Texture {
+            track_texture(Texture {
                 texture,
+                target: GL_TEXTURE_2D,
                 width: width as u32,
                 height: height as u32,
-            }
+            })
         }
     }
 
     pub fn set_filter(&self, filter: i32) {
         unsafe {
+            glBindTexture(self.target, self.texture);
+
+            glTexParameteri(self.target, GL_TEXTURE_MIN_FILTER, filter);
+            glTexParameteri(self.target, GL_TEXTURE_MAG_FILTER, filter);
+        }
+    }
+
+    /// Overwrite a `width`x`height` part of the texture at `x`,`y` with RGBA8 `bytes`.
+    /// Only for textures created from RGBA8 data or RGBA8 render textures.
+    pub fn update_part(&self, x: u32, y: u32, width: u32, height: u32, bytes: &[u8]) {
+        crate::zone!("texture upload");
+        assert_eq!(self.target, GL_TEXTURE_2D);
+        assert!(x + width <= self.width && y + height <= self.height);
+        assert_eq!(bytes.len(), (width * height * 4) as usize);
+
+        unsafe {
+            glActiveTexture(GL_TEXTURE0);
+            glBindTexture(GL_TEXTURE_2D, self.texture);
+            glTexSubImage2D(
+                GL_TEXTURE_2D,
+                0,
+                x as i32,
+                y as i32,
+                width as i32,
+                height as i32,
+                GL_RGBA,
+                GL_UNSIGNED_BYTE,
+                bytes.as_ptr() as *const _,
+            );
+        }
+    }
+
+    /// Overwrite a `width`x`height` part of the texture at `x`,`y` with 4 floats per pixel.
+    /// Only for `PixelFormat::RGBA32F` render textures.
+    pub fn update_part_rgba32f(&self, x: u32, y: u32, width: u32, height: u32, floats: &[f32]) {
+        crate::zone!("texture upload");
+        assert_eq!(self.target, GL_TEXTURE_2D);
+        assert!(x + width <= self.width && y + height <= self.height);
+        assert_eq!(floats.len(), (width * height * 4) as usize);
+
+        unsafe {
+            glActiveTexture(GL_TEXTURE0);
             glBindTexture(GL_TEXTURE_2D, self.texture);
+            glTexSubImage2D(
+                GL_TEXTURE_2D,
+                0,
+                x as i32,
+                y as i32,
+                width as i32,
+                height as i32,
+                GL_RGBA,
+                GL_FLOAT,
+                floats.as_ptr() as *const _,
+            );
+        }
+    }
+
+    /// Empty cubemap with `size`x`size` faces and `levels` mip levels, 1 for none,
+    /// to fill face by face and level by level with `update_cubemap_face`, e.g. a
+    /// prefiltered environment map. Sampled with `samplerCube` in shaders, trilinearly
+    /// with several levels. WebGL needs the levels down to 1x1, and on desktop GL
+    /// the filtering is seamless across the edges of the faces.
+    pub fn new_cubemap(size: u32, format: PixelFormat, levels: u32) -> Texture {
+        assert!(levels >= 1 && size >> (levels - 1) >= 1);
+        let (internal_format, gl_format, pixel_type) = format.into();
+        let target = GL_TEXTURE_CUBE_MAP;
+
+        unsafe {
+            let mut texture: GLuint = 0;
+            glGenTextures(1, &mut texture as *mut _);
+            glActiveTexture(GL_TEXTURE0);
+            glBindTexture(target, texture);
+            for level in 0..levels {
+                let level_size = (size >> level) as i32;
+                for face in CubeFace::ALL.iter() {
+                    glTexImage2D(
+                        face.gl_target(),
+                        level as i32,
+                        internal_format as i32,
+                        level_size,
+                        level_size,
+                        0,
+                        gl_format,
+                        pixel_type,
+                        std::ptr::null(),
+                    );
+                }
+            }
+
+            let min_filter = if levels > 1 {
+                GL_LINEAR_MIPMAP_LINEAR
+            } else {
+                GL_LINEAR
+            };
+            // complete without all the levels down to 1x1, GLES 2 has no max level
+            #[cfg(not(target_arch = "wasm32"))]
+            glTexParameteri(target, GL_TEXTURE_MAX_LEVEL, levels as i32 - 1);
+            glTexParameteri(target, GL_TEXTURE_WRAP_S, GL_CLAMP_TO_EDGE as i32);
+            glTexParameteri(target, GL_TEXTURE_WRAP_T, GL_CLAMP_TO_EDGE as i32);
+            glTexParameteri(target, GL_TEXTURE_MIN_FILTER, min_filter as i32);
+            glTexParameteri(target, GL_TEXTURE_MAG_FILTER, GL_LINEAR as i32);
+
+            track_texture(Texture {
+                texture,
+                target,
+                width: size,
+                height: size,
+            })
+        }
+    }
+
+    /// Overwrite mip `level` of a `face` of a cubemap from `new_cubemap` with RGBA8 `bytes`,
+    /// `(width >> level)` squared pixels.
+    pub fn update_cubemap_face(&self, face: CubeFace, level: u32, bytes: &[u8]) {
+        let size = self.cubemap_level_size(level);
+        assert_eq!(bytes.len(), (size * size * 4) as usize);
+        unsafe { self.update_cubemap_face_raw(face, level, GL_UNSIGNED_BYTE, bytes.as_ptr()) }
+    }
+
+    /// `update_cubemap_face` of a `PixelFormat::RGBA32F` cubemap, 4 floats per pixel.
+    pub fn update_cubemap_face_rgba32f(&self, face: CubeFace, level: u32, floats: &[f32]) {
+        let size = self.cubemap_level_size(level);
+        assert_eq!(floats.len(), (size * size * 4) as usize);
+        unsafe { self.update_cubemap_face_raw(face, level, GL_FLOAT, floats.as_ptr() as *const u8) }
+    }
+
+    fn cubemap_level_size(&self, level: u32) -> u32 {
+        assert_eq!(self.target, GL_TEXTURE_CUBE_MAP, "Not a cubemap");
+        assert!(self.width >> level >= 1, "No mip level {}", level);
+        self.width >> level
+    }
+
+    unsafe fn update_cubemap_face_raw(
+        &self,
+        face: CubeFace,
+        level: u32,
+        pixel_type: GLenum,
+        data: *const u8,
+    ) {
+        crate::zone!("texture upload");
+        let size = (self.width >> level) as i32;
+        glActiveTexture(GL_TEXTURE0);
+        glBindTexture(GL_TEXTURE_CUBE_MAP, self.texture);
+        glTexSubImage2D(
+            face.gl_target(),
+            level as i32,
+            0,
+            0,
+            size,
+            size,
+            GL_RGBA,
+            pixel_type,
+            data as *const _,
+        );
+    }
 
-            glTexParameteri(GL_TEXTURE_2D, GL_TEXTURE_MIN_FILTER, filter);
-            glTexParameteri(GL_TEXTURE_2D, GL_TEXTURE_MAG_FILTER, filter);
+    /// Free the texture, it must not be used afterwards, neither directly
+    /// nor through a `RenderPass` it is attached to.
+    pub fn delete(&self) {
+        unsafe {
+            glDeleteTextures(1, &self.texture as *const _);
         }
+        LIVE_RESOURCES.with(|resources| {
+            resources
+                .borrow_mut()
+                .textures
+                .retain(|texture| texture != self)
+        });
     }
 }
 
+impl Texture {
+    /// Create a texture from a DDS or KTX2 container, see `container` module.
+    /// Mip levels and cubemap faces are uploaded as they are in the file,
+    /// a cubemap texture should be sampled with `samplerCube` in shaders.
+    /// With the `basis` feature, Basis Universal textures are transcoded first,
+    /// see `basis` module.
+    pub fn from_container(ctx: &Context, bytes: &[u8]) -> Result<Texture, crate::container::Error> {
+        use crate::container::Error;
+
+        #[cfg(feature = "basis")]
+        let container = if crate::basis::is_basis(bytes) {
+            crate::basis::transcode(bytes, ctx.features())?
+        } else {
+            crate::container::parse(bytes)?
+        };
+        #[cfg(not(feature = "basis"))]
+        let container = crate::container::parse(bytes)?;
+        if !container.format.is_supported(ctx.features()) {
+            return Err(Error::FormatNotSupported(container.format));
+        }
+
+        let target = if container.faces == 6 {
+            GL_TEXTURE_CUBE_MAP
+        } else {
+            GL_TEXTURE_2D
+        };
+        // without all the levels down to 1x1 the texture is incomplete with mipmap filtering
+        let min_filter = if container.levels.len() == container.full_mip_chain_len()
+            && container.levels.len() > 1
+        {
+            GL_LINEAR_MIPMAP_LINEAR
+        } else {
+            GL_LINEAR
+        };
+
+        unsafe {
+            let mut texture: GLuint = 0;
+            glGenTextures(1, &mut texture as *mut _);
+            glActiveTexture(GL_TEXTURE0);
+            glBindTexture(target, texture);
+
+            for (level, faces) in container.levels.iter().enumerate() {
+                let width = (container.width >> level).max(1) as i32;
+                let height = (container.height >> level).max(1) as i32;
+                for (face, data) in faces.iter().enumerate() {
+                    let face_target = if container.faces == 6 {
+                        CubeFace::ALL[face].gl_target()
+                    } else {
+                        GL_TEXTURE_2D
+                    };
+                    match container.format.gl_compressed_format() {
+                        Some(format) => glCompressedTexImage2D(
+                            face_target,
+                            level as i32,
+                            format,
+                            width,
+                            height,
+                            0,
+                            data.len() as i32,
+                            data.as_ptr() as *const _,
+                        ),
+                        None => glTexImage2D(
+                            face_target,
+                            level as i32,
+                            GL_RGBA as i32,
+                            width,
+                            height,
+                            0,
+                            GL_RGBA,
+                            GL_UNSIGNED_BYTE,
+                            data.as_ptr() as *const _,
+                        ),
+                    }
+                }
+            }
+
+            glTexParameteri(target, GL_TEXTURE_WRAP_S, GL_CLAMP_TO_EDGE as i32);
+            glTexParameteri(target, GL_TEXTURE_WRAP_T, GL_CLAMP_TO_EDGE as i32);
+            glTexParameteri(target, GL_TEXTURE_MIN_FILTER, min_filter as i32);
+            glTexParameteri(target, GL_TEXTURE_MAG_FILTER, GL_LINEAR as i32);
+
+            Ok(track_texture(Texture {
+                texture,
+                target,
+                width: container.width,
+                height: container.height,
+            }))
+        }
+    }
+}
+
+#[cfg(any(feature = "png", feature = "jpeg"))]
+#[derive(Debug)]
+pub enum TextureLoadError {
+    File(crate::fs::Error),
+    #[cfg(feature = "png")]
+    Png(crate::png::Error),
+    #[cfg(feature = "jpeg")]
+    Jpeg(crate::jpeg::Error),
+    /// Not a format any of the enabled decoders understands
+    UnknownFormat,
+}
+
+#[cfg(feature = "png")]
+impl Texture {
+    pub fn from_png_bytes(bytes: &[u8]) -> Result<Texture, crate::png::Error> {
+        let image = crate::png::decode(bytes)?;
+        Ok(Texture::from_rgba8(image.width, image.height, &image.bytes))
+    }
+}
+
+#[cfg(feature = "jpeg")]
+impl Texture {
+    pub fn from_jpeg_bytes(bytes: &[u8]) -> Result<Texture, crate::jpeg::Error> {
+        let image = crate::jpeg::decode(bytes)?;
+        Ok(Texture::from_rgba8(image.width, image.height, &image.bytes))
+    }
+}
+
+#[cfg(any(feature = "png", feature = "jpeg"))]
+impl Texture {
+    /// Decode a PNG or JPEG image, the format is guessed from the file header.
+    /// Only formats with their cargo feature enabled are recognized.
+    pub fn from_encoded(bytes: &[u8]) -> Result<Texture, TextureLoadError> {
+        let (width, height, pixels) = decode_encoded(bytes)?;
+        Ok(Texture::from_rgba8(width, height, &pixels))
+    }
+
+    /// Load and decode an image file, see `fs::load_file` and `Texture::from_encoded`.
+    /// On wasm `f` is called later, from the event loop, so the texture
+    /// is always created on the main thread.
+    pub fn from_file_async<F>(path: &str, f: F)
+    where
+        F: FnOnce(Result<Texture, TextureLoadError>) + 'static,
+    {
+        crate::fs::load_file(path, move |bytes| {
+            f(bytes
+                .map_err(TextureLoadError::File)
+                .and_then(|bytes| Texture::from_encoded(&bytes)))
+        });
+    }
+}
+
+/// Width, height and RGBA8 pixels of a PNG or JPEG image, no GL involved so any
+/// thread can decode
+#[cfg(any(feature = "png", feature = "jpeg"))]
+pub(crate) fn decode_encoded(bytes: &[u8]) -> Result<(u16, u16, Vec<u8>), TextureLoadError> {
+    #[cfg(feature = "png")]
+    {
+        if bytes.starts_with(b"\x89PNG") {
+            let image = crate::png::decode(bytes).map_err(TextureLoadError::Png)?;
+            return Ok((image.width, image.height, image.bytes));
+        }
+    }
+    #[cfg(feature = "jpeg")]
+    {
+        if bytes.starts_with(&[0xff, 0xd8, 0xff]) {
+            let image = crate::jpeg::decode(bytes).map_err(TextureLoadError::Jpeg)?;
+            return Ok((image.width, image.height, image.bytes));
+        }
+    }
+    Err(TextureLoadError::UnknownFormat)
+}
+
 fn get_uniform_location(program: GLuint, name: &str) -> i32 {
     let cname = CString::new(name).unwrap_or_else(|e| panic!(e));
     let location = unsafe { glGetUniformLocation(program, cname.as_ptr()) };
@@ -158,7 +622,7 @@ fn get_uniform_location(program: GLuint, name: &str) -> i32 {
     location
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum UniformType {
     Float1,
     Float2,
@@ -168,7 +632,18 @@ pub enum UniformType {
 }
 
 impl UniformType {
-    fn size(&self, count: usize) -> usize {
+    /// Alignment and size of one element in a std140 uniform block, in floats
+    fn std140(&self) -> (usize, usize) {
+        match self {
+            UniformType::Float1 => (1, 1),
+            UniformType::Float2 => (2, 2),
+            UniformType::Float3 => (4, 3),
+            UniformType::Float4 => (4, 4),
+            UniformType::Mat4 => (4, 16),
+        }
+    }
+
+    pub(crate) fn size(&self, count: usize) -> usize {
         match self {
             UniformType::Float1 => 4 * count,
             UniformType::Float2 => 8 * count,
@@ -179,13 +654,238 @@ impl UniformType {
     }
 }
 
+/// Uniforms in the order of the struct given to `apply_uniforms`. An array is
+/// declared with its length, `("bones[32]", UniformType::Mat4)`, and takes
+/// that many consecutive values of the struct.
+#[derive(Clone, Copy)]
 pub struct UniformBlockLayout {
     pub uniforms: &'static [(&'static str, UniformType)],
 }
 
+/// Bytes `Context::apply_push_constants` takes at most, as Vulkan guarantees.
+pub const MAX_PUSH_CONSTANTS_SIZE: usize = 128;
+
+/// Declares the `vec4 push_constants[8]` uniform filled by `Context::apply_push_constants`,
+/// for the shader sources before `main`, in both stages when both read it.
+/// It is not listed in `ShaderMeta::uniforms`.
+pub const PUSH_CONSTANTS_GLSL: &str = r#"
+#ifdef GL_FRAGMENT_PRECISION_HIGH
+uniform highp vec4 push_constants[8];
+#else
+uniform mediump vec4 push_constants[8];
+#endif
+"#;
+
+/// Makes the depth and stencil tests run before the fragment shader, right after the
+/// `#version` line of fragment shaders. Drivers do so already, unless the shader
+/// discards fragments or writes `gl_FragDepth`; forced, the depth is written even for
+/// discarded fragments, fine for decals testing depth without writing it.
+/// Only desktop shaders of GLSL 420, or with `GL_ARB_shader_image_load_store`, take it,
+/// for others, GLSL 100 ones included, it expands to nothing.
+pub const EARLY_FRAGMENT_TESTS_GLSL: &str = r#"
+#ifdef GL_ARB_shader_image_load_store
+#extension GL_ARB_shader_image_load_store : enable
+layout(early_fragment_tests) in;
+#elif __VERSION__ >= 420
+layout(early_fragment_tests) in;
+#endif
+"#;
+
+/// Uniforms and images of a shader. Fields may be added, so write it with
+/// `ShaderMeta::new`, which works in constants, or end literals with
+/// `..Default::default()`.
+#[derive(Clone, Copy)]
 pub struct ShaderMeta {
     pub uniforms: UniformBlockLayout,
+    /// Samplers, in the order of `Bindings::images`, each one on its own texture unit.
+    /// Elements of a sampler array are listed one by one: `"layers[0]"`, `"layers[1]"`...
+    /// GLSL 100 only indexes sampler arrays with constants or loop counters, so a texture
+    /// picked per instance is sampled in a loop over the array, comparing the counter.
+    /// At most `Features::max_images`.
     pub images: &'static [&'static str],
+    /// Blocks of uniforms shared with other shaders, set once for all of them with
+    /// `Context::apply_shared_uniforms`, per-frame or per-view constants like the
+    /// camera. Not part of `uniforms`, nor of the struct given to `apply_uniforms`.
+    pub shared_uniforms: &'static [SharedUniformBlock],
+}
+
+impl Default for ShaderMeta {
+    fn default() -> ShaderMeta {
+        ShaderMeta::new(UniformBlockLayout { uniforms: &[] }, &[])
+    }
+}
+
+/// Slots of `Context::apply_shared_uniforms`
+pub const MAX_SHARED_UNIFORM_SLOTS: usize = 4;
+
+/// Name of the uniform block a shader reads a shared slot from, followed by the slot:
+///
+/// ```ignore
+/// #version 140
+/// //@shared 0 mat4 view_projection
+/// layout(std140) uniform SharedUniforms0 {
+///     mat4 view_projection;
+/// };
+/// ```
+///
+/// With `Features::uniform_buffers` the slot is then uploaded once to a uniform buffer
+/// all these shaders read. Shaders without the block, GLSL 100 ones included, get the
+/// values with `glUniform` calls, once per program.
+pub const SHARED_UNIFORMS_BLOCK: &str = "SharedUniforms";
+
+/// Uniform buffer binding of the `SHARED_UNIFORMS_BLOCK` of slot 0, the next slots
+/// following
+const SHARED_UNIFORMS_BINDING: GLuint = crate::uniform_ring::TRANSIENT_UNIFORMS_BINDING + 1;
+
+/// Uniforms a shader reads from a shared slot. Every shader declaring a slot should
+/// lay its uniforms out the same way, the data of a slot is the same for all of them.
+#[derive(Clone, Copy)]
+pub struct SharedUniformBlock {
+    /// Below `MAX_SHARED_UNIFORM_SLOTS`
+    pub slot: usize,
+    pub layout: UniformBlockLayout,
+}
+
+/// Bad `//@` annotation, with the line it was found on
+#[derive(Debug)]
+pub enum ShaderAnnotationError {
+    /// Neither `//@uniform <type> <name>`, `//@shared <slot> <type> <name>`
+    /// nor `//@image <name>`
+    Malformed(&'static str),
+    /// Uniform type other than float, vec2, vec3, vec4 or mat4
+    UnknownType(&'static str),
+    /// Uniform declared again with another type
+    Conflict(&'static str),
+}
+
+impl ShaderMeta {
+    /// Meta of a shader reading no shared uniforms
+    pub const fn new(uniforms: UniformBlockLayout, images: &'static [&'static str]) -> ShaderMeta {
+        ShaderMeta {
+            uniforms,
+            images,
+            shared_uniforms: &[],
+        }
+    }
+
+    pub const fn with_shared_uniforms(
+        self,
+        shared_uniforms: &'static [SharedUniformBlock],
+    ) -> ShaderMeta {
+        ShaderMeta {
+            shared_uniforms,
+            ..self
+        }
+    }
+
+    /// Build the meta from annotations in the shader sources, instead of declaring
+    /// every uniform and image both in GLSL and in Rust:
+    ///
+    /// ```ignore
+    /// //@uniform mat4 mvp
+    /// uniform mat4 mvp;
+    /// //@image tex
+    /// uniform sampler2D tex;
+    /// //@image layers[4]
+    /// uniform sampler2D layers[4];
+    /// //@uniform mat4 bones[32]
+    /// uniform mat4 bones[32];
+    /// //@shared 0 mat4 view_projection
+    /// uniform mat4 view_projection;
+    /// ```
+    ///
+    /// A sampler array takes as many images as it has elements, `layers[0]` to `layers[3]`.
+    /// A uniform array is a single uniform, see `UniformBlockLayout`.
+    /// `//@shared` uniforms go in the `ShaderMeta::shared_uniforms` block of their slot.
+    ///
+    /// Uniforms are laid out in the order they are annotated, vertex shader first,
+    /// and a uniform struct given to `apply_uniforms` should follow that order.
+    /// A name annotated in both shaders is declared once.
+    ///
+    /// The layout is allocated once and never freed, to fit in the `'static` slices
+    /// of `ShaderMeta`, so this is meant for shaders created at startup.
+    pub fn from_annotations(
+        vertex_shader: &'static str,
+        fragment_shader: &'static str,
+    ) -> Result<ShaderMeta, ShaderAnnotationError> {
+        let mut uniforms: Vec<(&'static str, UniformType)> = vec![];
+        let mut images: Vec<&'static str> = vec![];
+        let mut shared: Vec<Vec<(&'static str, UniformType)>> =
+            vec![vec![]; MAX_SHARED_UNIFORM_SLOTS];
+
+        let lines = vertex_shader.lines().chain(fragment_shader.lines());
+        for line in lines.map(str::trim) {
+            if !line.starts_with("//@") {
+                continue;
+            }
+            let words: Vec<&'static str> = line["//@".len()..].split_whitespace().collect();
+            let (block, ty, name) = match words[..] {
+                ["uniform", ty, name] => (&mut uniforms, ty, name),
+                ["shared", slot, ty, name] => match slot.parse::<usize>() {
+                    Ok(slot) if slot < MAX_SHARED_UNIFORM_SLOTS => (&mut shared[slot], ty, name),
+                    _ => return Err(ShaderAnnotationError::Malformed(line)),
+                },
+                ["image", name] => {
+                    let names = match glsl_array(name) {
+                        Some((array, len)) => (0..len)
+                            .map(|n| &*Box::leak(format!("{}[{}]", array, n).into_boxed_str()))
+                            .collect(),
+                        None => vec![name],
+                    };
+                    for name in names {
+                        if !images.contains(&name) {
+                            images.push(name);
+                        }
+                    }
+                    continue;
+                }
+                _ => return Err(ShaderAnnotationError::Malformed(line)),
+            };
+            let ty = match ty {
+                "float" => UniformType::Float1,
+                "vec2" => UniformType::Float2,
+                "vec3" => UniformType::Float3,
+                "vec4" => UniformType::Float4,
+                "mat4" => UniformType::Mat4,
+                _ => return Err(ShaderAnnotationError::UnknownType(line)),
+            };
+            match block.iter().find(|(other, _)| *other == name) {
+                Some((_, other_ty)) if *other_ty != ty => {
+                    return Err(ShaderAnnotationError::Conflict(line))
+                }
+                Some(_) => {}
+                None => block.push((name, ty)),
+            }
+        }
+
+        Ok(ShaderMeta {
+            uniforms: UniformBlockLayout {
+                uniforms: Box::leak(uniforms.into_boxed_slice()),
+            },
+            images: Box::leak(images.into_boxed_slice()),
+            shared_uniforms: Box::leak(
+                shared
+                    .into_iter()
+                    .enumerate()
+                    .filter(|(_, uniforms)| !uniforms.is_empty())
+                    .map(|(slot, uniforms)| SharedUniformBlock {
+                        slot,
+                        layout: UniformBlockLayout {
+                            uniforms: Box::leak(uniforms.into_boxed_slice()),
+                        },
+                    })
+                    .collect::<Vec<_>>()
+                    .into_boxed_slice(),
+            ),
+        })
+    }
+}
+
+/// `"layers[4]"` to `("layers", 4)`
+fn glsl_array(name: &str) -> Option<(&str, usize)> {
+    let open = name.find('[')?;
+    let len = name[open + 1..].strip_suffix(']')?.parse().ok()?;
+    Some((&name[..open], len))
 }
 
 #[derive(Clone, Copy, PartialEq, Debug)]
@@ -198,6 +898,11 @@ pub enum VertexFormat {
     Byte2,
     Byte3,
     Byte4,
+    /// `u16` values, read as floats without normalization, e.g. bone indices
+    Short1,
+    Short2,
+    Short3,
+    Short4,
     Mat4,
 }
 
@@ -212,6 +917,10 @@ impl VertexFormat {
             VertexFormat::Byte2 => 2,
             VertexFormat::Byte3 => 3,
             VertexFormat::Byte4 => 4,
+            VertexFormat::Short1 => 1,
+            VertexFormat::Short2 => 2,
+            VertexFormat::Short3 => 3,
+            VertexFormat::Short4 => 4,
             VertexFormat::Mat4 => 16,
         }
     }
@@ -226,6 +935,10 @@ impl VertexFormat {
             VertexFormat::Byte2 => 2,
             VertexFormat::Byte3 => 3,
             VertexFormat::Byte4 => 4,
+            VertexFormat::Short1 => 1 * 2,
+            VertexFormat::Short2 => 2 * 2,
+            VertexFormat::Short3 => 3 * 2,
+            VertexFormat::Short4 => 4 * 2,
             VertexFormat::Mat4 => 16 * 4,
         }
     }
@@ -240,6 +953,10 @@ impl VertexFormat {
             VertexFormat::Byte2 => GL_UNSIGNED_BYTE,
             VertexFormat::Byte3 => GL_UNSIGNED_BYTE,
             VertexFormat::Byte4 => GL_UNSIGNED_BYTE,
+            VertexFormat::Short1 => GL_UNSIGNED_SHORT,
+            VertexFormat::Short2 => GL_UNSIGNED_SHORT,
+            VertexFormat::Short3 => GL_UNSIGNED_SHORT,
+            VertexFormat::Short4 => GL_UNSIGNED_SHORT,
             VertexFormat::Mat4 => GL_FLOAT,
         }
     }
@@ -279,6 +996,10 @@ pub struct VertexAttribute {
     pub name: &'static str,
     pub format: VertexFormat,
     pub buffer_index: usize,
+    /// Other names the shader may use for this attribute, tried in order when
+    /// `name` is not found. Private, set with `with_aliases`: build attributes with
+    /// `new` or `with_buffer`.
+    aliases: &'static [&'static str],
 }
 
 impl VertexAttribute {
@@ -295,8 +1016,16 @@ impl VertexAttribute {
             name,
             format,
             buffer_index,
+            aliases: &[],
         }
     }
+
+    /// Bind by semantic rather than by exact name, so meshes and shaders with different
+    /// naming conventions fit together:
+    /// `VertexAttribute::new("position", VertexFormat::Float3).with_aliases(&["a_pos", "in_position"])`
+    pub fn with_aliases(self, aliases: &'static [&'static str]) -> VertexAttribute {
+        VertexAttribute { aliases, ..self }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -305,6 +1034,7 @@ pub struct PipelineLayout {
     pub attributes: &'static [VertexAttribute],
 }
 
+#[derive(Copy, Clone, Debug)]
 pub struct Shader(usize);
 
 impl Shader {
@@ -314,10 +1044,32 @@ impl Shader {
         fragment_shader: &str,
         meta: ShaderMeta,
     ) -> Shader {
+        crate::zone!("shader compile");
         let shader = load_shader_internal(vertex_shader, fragment_shader, meta);
         ctx.shaders.push(shader);
         Shader(ctx.shaders.len() - 1)
     }
+
+    /// `new` returning the driver's log instead of panicking
+    pub(crate) fn try_new(
+        ctx: &mut Context,
+        vertex_shader: &str,
+        fragment_shader: &str,
+        meta: ShaderMeta,
+    ) -> Result<Shader, ShaderCompileError> {
+        crate::zone!("shader compile");
+        let shader = try_load_shader_internal(vertex_shader, fragment_shader, meta)?;
+        ctx.shaders.push(shader);
+        Ok(Shader(ctx.shaders.len() - 1))
+    }
+}
+
+/// Info log of the step that failed
+#[derive(Debug)]
+pub(crate) enum ShaderCompileError {
+    Vertex(String),
+    Fragment(String),
+    Link(String),
 }
 
 pub struct ShaderImage {
@@ -330,12 +1082,134 @@ pub struct ShaderUniform {
     offset: usize,
     size: usize,
     uniform_type: UniformType,
+    /// Elements of a uniform array, 1 otherwise
+    count: usize,
+}
+
+impl ShaderUniform {
+    /// Start in a std140 block, size and distance between the elements, in floats,
+    /// for a uniform after `offset`
+    fn std140(&self, offset: usize) -> (usize, usize, usize) {
+        let (align, size) = self.uniform_type.std140();
+        // array elements are vec4 aligned
+        let (align, stride) = match self.count {
+            1 => (align, size),
+            _ => (4, (size + 3) / 4 * 4),
+        };
+        ((offset + align - 1) / align * align, size, stride)
+    }
+}
+
+/// `data` laid out like `uniforms`, rearranged by the std140 rules of uniform blocks
+fn std140_block(uniforms: &[ShaderUniform], data: &[f32]) -> Vec<f32> {
+    let mut block = vec![];
+    for uniform in uniforms {
+        assert!(uniform.offset + uniform.size <= data.len() * 4);
+        let (start, size, stride) = uniform.std140(block.len());
+        let values = &data[uniform.offset / 4..(uniform.offset + uniform.size) / 4];
+        for (element, values) in values.chunks(size).enumerate() {
+            block.resize(start + element * stride, 0.);
+            block.extend_from_slice(values);
+        }
+        block.resize(start + stride * uniform.count, 0.);
+    }
+    // whole vec4s
+    block.resize((block.len() + 3) / 4 * 4, 0.);
+    block
+}
+
+/// Handle to a shader uniform, resolved once from its name with `Context::uniform_id`
+/// so hot paths don't compare strings.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct UniformId {
+    shader: usize,
+    index: usize,
+}
+
+/// Handle to a shader image, resolved once from its name with `Context::image_id`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ImageId {
+    shader: usize,
+    index: usize,
+}
+
+impl ImageId {
+    /// Position of this image in `Bindings::images`
+    pub fn slot(&self) -> usize {
+        self.index
+    }
 }
 
 struct ShaderInternal {
     program: GLuint,
+    /// Kept to compile the shader again when the GL context is restored
+    vertex_source: String,
+    fragment_source: String,
+    meta: ShaderMeta,
     images: Vec<ShaderImage>,
     uniforms: Vec<ShaderUniform>,
+    /// `ShaderMeta` names to positions in `images` and `uniforms`
+    image_names: HashMap<&'static str, usize>,
+    uniform_names: HashMap<&'static str, usize>,
+    /// Location of the `_mq_flip_y` uniform added by `wrap_vertex_main`, -1 if the
+    /// vertex shader could not be wrapped
+    flip_y_loc: GLint,
+    /// Location of the `PUSH_CONSTANTS_GLSL` array, -1 if the shader doesn't use it
+    push_constants_loc: GLint,
+    shared_uniforms: Vec<ShaderSharedUniforms>,
+    /// Declares `TRANSIENT_UNIFORMS_BLOCK`
+    transient_block: bool,
+}
+
+/// A `SharedUniformBlock` of a shader
+struct ShaderSharedUniforms {
+    slot: usize,
+    uniforms: Vec<ShaderUniform>,
+    /// Declares the `SHARED_UNIFORMS_BLOCK` of the slot
+    block: bool,
+    /// `SharedUniforms::generation` of the data the program has, 0 for none
+    generation: u64,
+}
+
+/// Data of a slot of `Context::apply_shared_uniforms`
+#[derive(Clone, Default)]
+struct SharedUniforms {
+    data: Vec<f32>,
+    /// Counts the changes, 0 while the slot was never set
+    generation: u64,
+    /// Uniform buffer of the shaders declaring the slot's block, 0 until first used
+    buffer: GLuint,
+    /// In bytes
+    buffer_size: usize,
+    /// `generation` of the data in `buffer`
+    buffer_generation: u64,
+}
+
+impl SharedUniforms {
+    /// Copy the data to the slot's buffer, laid out by the std140 rules
+    unsafe fn upload_buffer(&mut self, slot: usize, uniforms: &[ShaderUniform]) {
+        use crate::uniform_ring::GL_UNIFORM_BUFFER;
+
+        let block = std140_block(uniforms, &self.data);
+        let size = block.len() * 4;
+        if self.buffer == 0 {
+            glGenBuffers(1, &mut self.buffer as *mut _);
+        }
+        glBindBuffer(GL_UNIFORM_BUFFER, self.buffer);
+        if size == self.buffer_size {
+            glBufferSubData(GL_UNIFORM_BUFFER, 0, size as _, block.as_ptr() as *const _);
+            return;
+        }
+        glBufferData(
+            GL_UNIFORM_BUFFER,
+            size as _,
+            block.as_ptr() as *const _,
+            GL_DYNAMIC_DRAW,
+        );
+        self.buffer_size = size;
+        let binding = SHARED_UNIFORMS_BINDING + slot as GLuint;
+        glBindBufferRange(GL_UNIFORM_BUFFER, binding, self.buffer, 0, size as _);
+    }
 }
 
 type BlendState = Option<(Equation, BlendFactor, BlendFactor)>;
@@ -351,12 +1225,38 @@ struct GlCache {
     stored_vertex_buffer: GLuint,
     index_buffer: GLuint,
     vertex_buffer: GLuint,
+    /// Of the bound index buffer
+    index_type: Option<IndexType>,
+    /// Byte offset of the bound index buffer slice
+    index_offset: usize,
     cur_pipeline: Option<Pipeline>,
     blend: BlendState,
-    attributes: [Option<CachedAttribute>; MAX_VERTEX_ATTRIBUTES],
+    /// One per attribute the driver has, `Features::max_vertex_attributes`
+    attributes: Vec<Option<CachedAttribute>>,
+    /// One past the highest enabled attribute, disabling the unused ones stops there
+    enabled_attributes: usize,
+    /// Pipeline and vertex buffers, with their byte offsets, the attributes were last
+    /// set up for, the same again skips comparing every attribute
+    vertex_state: Option<(Pipeline, Vec<(GLuint, usize)>)>,
 }
 
 impl GlCache {
+    fn new(max_attributes: usize) -> GlCache {
+        GlCache {
+            stored_index_buffer: 0,
+            stored_vertex_buffer: 0,
+            index_buffer: 0,
+            vertex_buffer: 0,
+            index_type: None,
+            index_offset: 0,
+            cur_pipeline: None,
+            blend: None,
+            attributes: vec![None; max_attributes],
+            enabled_attributes: 0,
+            vertex_state: None,
+        }
+    }
+
     fn bind_buffer(&mut self, target: GLenum, buffer: GLuint) {
         if target == GL_ARRAY_BUFFER {
             if self.vertex_buffer != buffer {
@@ -402,8 +1302,13 @@ pub enum PassAction {
 
 impl PassAction {
     pub fn clear_color(r: f32, g: f32, b: f32, a: f32) -> PassAction {
+        PassAction::clear(crate::Color::new(r, g, b, a))
+    }
+
+    /// Clear color to `color` and depth to 1, see `ClipSpace::clear` for reversed Z
+    pub fn clear(color: crate::Color) -> PassAction {
         PassAction::Clear {
-            color: Some((r, g, b, a)),
+            color: Some(color.into()),
             depth: Some(1.),
             stencil: None,
         }
@@ -423,9 +1328,37 @@ impl Default for PassAction {
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct RenderPass(usize);
 
+/// Rectangle of the current pass, in pixels from its bottom-left corner,
+/// see `Context::render_viewports`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Viewport {
+    pub x: i32,
+    pub y: i32,
+    pub w: i32,
+    pub h: i32,
+}
+
+impl Viewport {
+    pub fn new(x: i32, y: i32, w: i32, h: i32) -> Viewport {
+        Viewport { x, y, w, h }
+    }
+
+    /// Width over height, for the projection of a camera drawing into the viewport
+    pub fn aspect_ratio(&self) -> f32 {
+        self.w as f32 / self.h.max(1) as f32
+    }
+}
+
 struct RenderPassInternal {
     gl_fb: GLuint,
     texture: Texture,
+    /// Mip level of `texture` rendered to
+    level: u32,
+    depth_texture: Option<Texture>,
+    /// Layers of array textures rendered at once with multiview, 1 otherwise
+    views: u32,
+    /// Framebuffer of `RenderPass::from_raw_fbo`, left to its creator to delete
+    external: bool,
 }
 
 impl RenderPass {
@@ -434,20 +1367,134 @@ impl RenderPass {
         color_img: Texture,
         depth_img: impl Into<Option<Texture>>,
     ) -> RenderPass {
+        RenderPass::with_color_target(context, color_img, GL_TEXTURE_2D, 0, depth_img.into())
+    }
+
+    /// Pass rendering into mip `level` of a `face` of a cubemap from `Texture::new_cubemap`,
+    /// e.g. to draw an environment map. `depth_img` is a 2D depth texture of the size of
+    /// the level. WebGL 1 only renders to level 0, and to `RGBA32F` cubemaps with
+    /// `WEBGL_color_buffer_float`.
+    pub fn new_cubemap_face(
+        context: &mut Context,
+        color_img: Texture,
+        face: CubeFace,
+        level: u32,
+        depth_img: impl Into<Option<Texture>>,
+    ) -> RenderPass {
+        assert_eq!(color_img.target, GL_TEXTURE_CUBE_MAP, "Not a cubemap");
+        assert!(color_img.width >> level >= 1, "No mip level {}", level);
+        RenderPass::with_color_target(
+            context,
+            color_img,
+            face.gl_target(),
+            level,
+            depth_img.into(),
+        )
+    }
+
+    /// Pass rendering `views` views at once, e.g. both eyes of a VR headset, into
+    /// the first `views` layers of array textures from `Texture::new_render_texture_array`:
+    /// every draw call goes to every layer. Needs `Features::multiview`, and at most
+    /// `Features::max_views` views.
+    ///
+    /// Shaders of pipelines drawing in the pass are GLSL 330 with the `GL_OVR_multiview2`
+    /// extension, and pick the transforms of each view with `gl_ViewID_OVR`:
+    ///
+    /// ```ignore
+    /// #version 330
+    /// #extension GL_OVR_multiview2 : require
+    /// layout(num_views = 2) in;
+    ///
+    /// uniform mat4 view_proj[2];
+    /// in vec3 pos;
+    ///
+    /// void main() {
+    ///     gl_Position = view_proj[gl_ViewID_OVR] * vec4(pos, 1.0);
+    /// }
+    /// ```
+    pub fn new_multiview(
+        context: &mut Context,
+        color_img: Texture,
+        depth_img: impl Into<Option<Texture>>,
+        views: u32,
+    ) -> RenderPass {
+        let depth_img = depth_img.into();
+        let features = context.features();
+        assert!(features.multiview, "No multiview");
+        assert!(
+            views >= 1 && views <= features.max_views,
+            "{} views, at most {}",
+            views,
+            features.max_views
+        );
+        assert_eq!(color_img.target, GL_TEXTURE_2D_ARRAY, "Not an array texture");
+        if let Some(depth_img) = depth_img {
+            assert_eq!(depth_img.target, GL_TEXTURE_2D_ARRAY, "Not an array texture");
+        }
+
         let mut gl_fb = 0;
 
+        #[cfg(not(target_arch = "wasm32"))]
         unsafe {
             glGenFramebuffers(1, &mut gl_fb as *mut _);
-            glBindTexture(GL_TEXTURE_2D, color_img.texture);
             glBindFramebuffer(GL_FRAMEBUFFER, gl_fb);
-            glFramebufferTexture2D(
+            glFramebufferTextureMultiviewOVR(
                 GL_FRAMEBUFFER,
                 GL_COLOR_ATTACHMENT0,
-                GL_TEXTURE_2D,
                 color_img.texture,
                 0,
+                0,
+                views as i32,
+            );
+            if let Some(depth_img) = depth_img {
+                glFramebufferTextureMultiviewOVR(
+                    GL_FRAMEBUFFER,
+                    GL_DEPTH_ATTACHMENT,
+                    depth_img.texture,
+                    0,
+                    0,
+                    views as i32,
+                );
+            }
+            glBindFramebuffer(GL_FRAMEBUFFER, context.default_framebuffer);
+        }
+        let pass = RenderPassInternal {
+            gl_fb,
+            texture: color_img,
+            level: 0,
+            depth_texture: depth_img,
+            views,
+            external: false,
+        };
+
+        context.passes.push(pass);
+
+        RenderPass(context.passes.len() - 1)
+    }
+
+    /// Framebuffer with `level` of `color_img` as seen from `target`, the texture itself
+    /// or a cubemap face, as color attachment
+    fn with_color_target(
+        context: &mut Context,
+        color_img: Texture,
+        target: GLenum,
+        level: u32,
+        depth_img: Option<Texture>,
+    ) -> RenderPass {
+        let mut gl_fb = 0;
+
+        unsafe {
+            glGenFramebuffers(1, &mut gl_fb as *mut _);
+            glBindTexture(color_img.target, color_img.texture);
+            glBindFramebuffer(GL_FRAMEBUFFER, gl_fb);
+            glFramebufferTexture2D(
+                GL_FRAMEBUFFER,
+                GL_COLOR_ATTACHMENT0,
+                target,
+                color_img.texture,
+                level as i32,
             );
-            if let Some(depth_img) = depth_img.into() {
+            if let Some(depth_img) = depth_img {
                 glFramebufferTexture2D(
                     GL_FRAMEBUFFER,
                     GL_DEPTH_ATTACHMENT,
@@ -461,26 +1508,391 @@ impl RenderPass {
         let pass = RenderPassInternal {
             gl_fb,
             texture: color_img,
+            level,
+            depth_texture: depth_img,
+            views: 1,
+            external: false,
         };
 
         context.passes.push(pass);
 
         RenderPass(context.passes.len() - 1)
     }
+
+    /// Pass rendering into a framebuffer created by some other library on miniquad's
+    /// GL context, of `size` pixels. Its attachments are unknown to miniquad: `texture`
+    /// gives a texture named 0 of `size`, and `depth_texture` none. `delete` forgets the
+    /// framebuffer, its creator frees it.
+    pub fn from_raw_fbo(context: &mut Context, fbo: GLuint, size: (u32, u32)) -> RenderPass {
+        let pass = RenderPassInternal {
+            gl_fb: fbo,
+            texture: Texture::from_raw_gl(0, size.0, size.1, Default::default()),
+            level: 0,
+            depth_texture: None,
+            views: 1,
+            external: true,
+        };
+
+        context.passes.push(pass);
+
+        RenderPass(context.passes.len() - 1)
+    }
+
+    /// Name of the GL framebuffer object, 0 once deleted
+    pub fn gl_internal_id(&self, context: &Context) -> GLuint {
+        context.passes[self.0].gl_fb
+    }
+
+    /// Free the framebuffer, its textures are left alone and should be deleted separately.
+    pub fn delete(&self, context: &mut Context) {
+        let pass = &mut context.passes[self.0];
+        if !pass.external {
+            unsafe {
+                glDeleteFramebuffers(1, &pass.gl_fb as *const _);
+            }
+        }
+        pass.gl_fb = 0;
+    }
+
+    /// Color attachment, e.g. to look at it with `Context::debug_show_texture`.
+    pub fn texture(&self, context: &Context) -> Texture {
+        context.passes[self.0].texture
+    }
+
+    /// Depth attachment, if the pass has one.
+    pub fn depth_texture(&self, context: &Context) -> Option<Texture> {
+        context.passes[self.0].depth_texture
+    }
+
+    /// Views rendered at once, more than 1 for passes from `new_multiview`.
+    pub fn views(&self, context: &Context) -> u32 {
+        context.passes[self.0].views
+    }
 }
 
+/// Vertex attributes GL 3 guarantees, WebGL only guarantees 8.
+/// The actual limit is `Features::max_vertex_attributes`.
+#[deprecated(note = "use Features::max_vertex_attributes")]
 pub const MAX_VERTEX_ATTRIBUTES: usize = 16;
 
+/// GL names of miniquad's objects, inside `Context::with_raw_gl`.
+pub struct RawGl<'a> {
+    ctx: &'a Context,
+}
+
+impl<'a> RawGl<'a> {
+    pub fn buffer(&self, buffer: &Buffer) -> GLuint {
+        buffer.gl_buf
+    }
+
+    pub fn texture(&self, texture: Texture) -> GLuint {
+        texture.texture
+    }
+
+    /// Framebuffer of a render pass, `None` for the default framebuffer.
+    pub fn framebuffer(&self, pass: Option<RenderPass>) -> GLuint {
+        match pass {
+            Some(pass) => self.ctx.passes[pass.0].gl_fb,
+            None => self.ctx.default_framebuffer,
+        }
+    }
+
+    /// Program of a pipeline's shader.
+    pub fn program(&self, pipeline: &Pipeline) -> GLuint {
+        self.ctx.shaders[self.ctx.pipelines[pipeline.0].shader.0].program
+    }
+}
+
+/// Why the GL context was lost, see `EventHandler::device_lost_event`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeviceLostReason {
+    /// The app's own GL commands caused the GPU reset, e.g. a shader running too long
+    Guilty,
+    /// Something else caused the reset, another app or the driver
+    Innocent,
+    /// The driver or the browser does not tell
+    Unknown,
+}
+
+/// What to do after the GL context was lost
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResetStrategy {
+    /// Carry on with a new GL context, `EventHandler::resources_lost_event` comes once it
+    /// is there.
+    Recreate,
+    /// Quit the app, e.g. when it caused the reset and would cause it again.
+    /// On the web the frames stop, pages can not be closed.
+    Quit,
+}
+
+/// GPU resources invalidated by a GL context loss, see `EventHandler::resources_lost_event`.
+///
+/// Shaders and pipelines are not listed: they are compiled again from their sources and
+/// keep working. Everything listed here has to be created again, including what helpers
+/// like `SpriteBatch`, `Atlas` or `PostProcess` created.
+#[derive(Debug, Clone, Default)]
+pub struct LostResources {
+    pub textures: Vec<Texture>,
+    pub buffers: Vec<Buffer>,
+    pub render_passes: Vec<RenderPass>,
+}
+
+thread_local! {
+    /// Textures and buffers created since start or since the last context restore.
+    /// Not kept in `Context`, textures are created without it.
+    static LIVE_RESOURCES: RefCell<LostResources> = RefCell::new(LostResources::default());
+}
+
+fn track_texture(texture: Texture) -> Texture {
+    LIVE_RESOURCES.with(|resources| resources.borrow_mut().textures.push(texture));
+    texture
+}
+
+fn track_buffer(buffer: Buffer) -> Buffer {
+    LIVE_RESOURCES.with(|resources| resources.borrow_mut().buffers.push(buffer));
+    buffer
+}
+
+/// Textures and buffers alive now, as `EventHandler::resources_lost_event` would list them
+pub(crate) fn live_resources() -> LostResources {
+    LIVE_RESOURCES.with(|resources| resources.borrow().clone())
+}
+
+/// Optional GPU capabilities and default framebuffer formats, queried once at startup.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Features {
+    /// BC1, BC2 and BC3, also known as DXT1, DXT3 and DXT5
+    pub texture_compression_s3tc: bool,
+    /// BC4 and BC5
+    pub texture_compression_rgtc: bool,
+    /// BC7
+    pub texture_compression_bptc: bool,
+    pub texture_compression_etc2: bool,
+    /// ASTC LDR profile
+    pub texture_compression_astc: bool,
+    /// Depth buffer bits of the default framebuffer, may differ from `Conf::depth_bits`.
+    /// On the web only zero or non zero can be requested, the browser picks the rest.
+    /// 0 with `Context::new_external`, only the creator of the GL context knows.
+    pub depth_bits: u8,
+    /// Stencil buffer bits of the default framebuffer, may differ from `Conf::stencil_bits`.
+    /// 0 with `Context::new_external`.
+    pub stencil_bits: u8,
+    /// Texture units of fragment shaders, how many images a shader can have.
+    /// At least 8, commonly 16 or 32.
+    pub max_images: u32,
+    /// Texture units of vertex shaders, 0 when vertex shaders can't sample textures,
+    /// as allowed by GLES 2 and WebGL 1.
+    pub max_vertex_images: u32,
+    /// `PixelFormat::RGBA32F` textures
+    pub texture_float: bool,
+    /// Vertex attributes a pipeline can have, a `VertexFormat::Mat4` counting for 4.
+    /// At least 16 on desktop GL, 8 on WebGL.
+    pub max_vertex_attributes: u32,
+    /// `QueryKind::SamplesPassed` and `QueryKind::PrimitivesGenerated` queries,
+    /// core on desktop GL, missing on WebGL 1
+    pub queries: bool,
+    /// `QueryKind::ClippingInputPrimitives` and `QueryKind::ClippingOutputPrimitives`
+    /// queries, from GL 4.6 or `GL_ARB_pipeline_statistics_query`
+    pub pipeline_statistics: bool,
+    /// `PipelineParams::depth_clamp`, core on desktop GL, missing on WebGL 1
+    pub depth_clamp: bool,
+    /// Clip space depths from 0 to 1 for `Context::set_reversed_z`,
+    /// from GL 4.5 or `GL_ARB_clip_control`
+    pub clip_control: bool,
+    /// `RenderPass::new_multiview`, from `GL_OVR_multiview2`, not on WebGL 1
+    pub multiview: bool,
+    /// Views a multiview pass can render at once, at least 2 with `multiview`,
+    /// 0 without
+    pub max_views: u32,
+    /// `EglImageTexture`, from `GL_OES_EGL_image`, on Linux and Android
+    pub egl_image: bool,
+    /// Out of range buffer reads are safe, see `Conf::robust_buffer_access`.
+    /// Always on WebGL.
+    pub robust_buffer_access: bool,
+    /// `Context::apply_transient_uniforms` binds uniform buffer ranges, and shared
+    /// uniform slots are uniform buffers, core on desktop GL, missing on WebGL 1
+    pub uniform_buffers: bool,
+}
+
+impl Features {
+    fn query(external: bool) -> Features {
+        let mut extensions = vec![];
+        unsafe {
+            let mut count: GLint = 0;
+            glGetIntegerv(GL_NUM_EXTENSIONS, &mut count as *mut _);
+            for i in 0..count {
+                let name = glGetStringi(GL_EXTENSIONS, i as GLuint);
+                if !name.is_null() {
+                    extensions.push(
+                        std::ffi::CStr::from_ptr(name as *const _)
+                            .to_string_lossy()
+                            .into_owned(),
+                    );
+                }
+            }
+        }
+        // desktop GL and WebGL names of the same extensions
+        let has = |names: &[&str]| extensions.iter().any(|ext| names.contains(&ext.as_str()));
+
+        Features {
+            texture_compression_s3tc: has(&[
+                "GL_EXT_texture_compression_s3tc",
+                "WEBGL_compressed_texture_s3tc",
+            ]),
+            // core since GL 3.0
+            texture_compression_rgtc: cfg!(not(target_arch = "wasm32"))
+                || has(&["EXT_texture_compression_rgtc"]),
+            texture_compression_bptc: has(&[
+                "GL_ARB_texture_compression_bptc",
+                "EXT_texture_compression_bptc",
+            ]),
+            texture_compression_etc2: has(&[
+                "GL_ARB_ES3_compatibility",
+                "WEBGL_compressed_texture_etc",
+            ]),
+            texture_compression_astc: has(&[
+                "GL_KHR_texture_compression_astc_ldr",
+                "WEBGL_compressed_texture_astc",
+            ]),
+            depth_bits: if external {
+                0
+            } else {
+                unsafe { sapp_depth_bits() as u8 }
+            },
+            stencil_bits: if external {
+                0
+            } else {
+                unsafe { sapp_stencil_bits() as u8 }
+            },
+            max_images: unsafe {
+                let mut units: GLint = 0;
+                glGetIntegerv(GL_MAX_TEXTURE_IMAGE_UNITS, &mut units as *mut _);
+                units as u32
+            },
+            max_vertex_images: unsafe {
+                let mut units: GLint = 0;
+                glGetIntegerv(GL_MAX_VERTEX_TEXTURE_IMAGE_UNITS, &mut units as *mut _);
+                units as u32
+            },
+            // core since GL 3.0
+            texture_float: cfg!(not(target_arch = "wasm32")) || has(&["OES_texture_float"]),
+            max_vertex_attributes: unsafe {
+                let mut attributes: GLint = 0;
+                glGetIntegerv(GL_MAX_VERTEX_ATTRIBS, &mut attributes as *mut _);
+                attributes as u32
+            },
+            // core since GL 1.5 and 3.0
+            queries: cfg!(not(target_arch = "wasm32")),
+            pipeline_statistics: has(&["GL_ARB_pipeline_statistics_query"]),
+            // core since GL 3.2
+            depth_clamp: cfg!(not(target_arch = "wasm32")),
+            clip_control: has(&["GL_ARB_clip_control"]),
+            multiview: has(&["GL_OVR_multiview2"]),
+            max_views: if has(&["GL_OVR_multiview2"]) {
+                let mut views: GLint = 0;
+                #[cfg(not(target_arch = "wasm32"))]
+                unsafe {
+                    glGetIntegerv(GL_MAX_VIEWS_OVR, &mut views as *mut _);
+                }
+                views as u32
+            } else {
+                0
+            },
+            egl_image: cfg!(not(any(windows, target_arch = "wasm32")))
+                && has(&["GL_OES_EGL_image"]),
+            robust_buffer_access: cfg!(target_arch = "wasm32") || {
+                const GL_CONTEXT_FLAGS: GLenum = 0x821e;
+                const GL_CONTEXT_FLAG_ROBUST_ACCESS_BIT: GLint = 0x4;
+                let mut flags: GLint = 0;
+                unsafe { glGetIntegerv(GL_CONTEXT_FLAGS, &mut flags as *mut _) };
+                flags & GL_CONTEXT_FLAG_ROBUST_ACCESS_BIT != 0
+            },
+            // core since GL 3.1
+            uniform_buffers: cfg!(not(target_arch = "wasm32")),
+        }
+    }
+}
+
 pub struct Context {
     shaders: Vec<ShaderInternal>,
     pipelines: Vec<PipelineInternal>,
     passes: Vec<RenderPassInternal>,
+    named_shaders: HashMap<String, Shader>,
+    named_pipelines: HashMap<String, Pipeline>,
     default_framebuffer: GLuint,
     cache: GlCache,
+    features: Features,
+    pub(crate) render_target_origin: crate::conf::RenderTargetOrigin,
+    /// See `Context::set_reversed_z`
+    pub(crate) reversed_z: bool,
+    /// Current pass is offscreen and renders upside down, with the height of its target
+    flipped_pass: Option<i32>,
+    /// Width and height of the target of the current pass
+    pass_size: (i32, i32),
+    /// Framebuffer of the current pass, bound again after `with_raw_gl`
+    framebuffer: GLuint,
+    /// The vertex array object all the attributes are set on
+    vao: GLuint,
+    /// Size of the default framebuffer of a GL context created outside of miniquad,
+    /// `None` with miniquad's own window
+    pub(crate) external_size: Option<(i32, i32)>,
+    pub(crate) frame_limiter: crate::power::FrameLimiter,
+    pub(crate) frame_pacer: crate::timing::FramePacer,
+    pub(crate) event_timestamp: f64,
+    pub(crate) text_input_enabled: bool,
+    pub(crate) executor: crate::exec::Executor,
+    pub(crate) scheduler: crate::timer::Scheduler,
+    pub(crate) uploads: crate::upload::UploadQueue,
+    pub(crate) gestures: crate::gesture::Gestures,
+    /// Dark theme, accent color and high contrast, to tell which one changed
+    pub(crate) appearance: (bool, Option<crate::Color>, bool),
+    #[cfg(target_arch = "wasm32")]
+    pub(crate) canvas_snapshots: Vec<Box<dyn FnOnce(String)>>,
+    pub(crate) copy_frame_requested: bool,
+    pub(crate) recorder: Option<crate::recorder::Recorder>,
+    pub(crate) late_latch: bool,
+    /// Stands in for the default framebuffer with `Conf::pixel_art`
+    pub(crate) pixel_canvas: Option<crate::pixel_canvas::PixelCanvas>,
+    pub(crate) debug_view: crate::debug_view::DebugView,
+    pub(crate) debug_overlay: crate::debug_overlay::DebugOverlay,
+    /// `Conf::debug_gl`
+    pub(crate) debug_gl: bool,
+    /// One per slot, up to `MAX_SHARED_UNIFORM_SLOTS`
+    shared_uniforms: Vec<SharedUniforms>,
+    pub(crate) uniform_ring: crate::uniform_ring::UniformRing,
 }
 
 impl Context {
     pub fn new() -> Context {
+        Context::create(None)
+    }
+
+    /// Context drawing with the GL context current on this thread, created by another
+    /// windowing library: a GTK GL area, an SDL window, an offscreen EGL surface...
+    /// `width` and `height` are the size of its default framebuffer, in pixels, see
+    /// `set_external_size`.
+    ///
+    /// Only the rendering part of the `Context` works then. There is no event loop:
+    /// the app calls its drawing code and swaps buffers itself, `EventHandler`, timers
+    /// and futures are not run, and the window functions, like `dpi_scale` or
+    /// `show_mouse`, concern miniquad's window, which doesn't exist.
+    /// With the `gl-loader` feature the GL functions can come from the same library,
+    /// see `gl::set_loader`.
+    pub fn new_external(width: i32, height: i32) -> Context {
+        Context::create(Some((width, height)))
+    }
+
+    /// New size of the default framebuffer of a `new_external` context, e.g. after
+    /// its window was resized. Used by the next `begin_default_pass`.
+    pub fn set_external_size(&mut self, width: i32, height: i32) {
+        assert!(
+            self.external_size.is_some(),
+            "set_external_size on a context of miniquad's window"
+        );
+        self.external_size = Some((width, height));
+    }
+
+    fn create(external_size: Option<(i32, i32)>) -> Context {
         unsafe {
             let mut default_framebuffer: GLuint = 0;
             glGetIntegerv(
@@ -491,23 +1903,98 @@ impl Context {
 
             glGenVertexArrays(1, &mut vao as *mut _);
             glBindVertexArray(vao);
+            // core since GL 3.2, WebGL 1 filters the faces separately
+            #[cfg(not(target_arch = "wasm32"))]
+            glEnable(GL_TEXTURE_CUBE_MAP_SEAMLESS);
+            let features = Features::query(external_size.is_some());
             Context {
                 default_framebuffer,
                 shaders: vec![],
                 pipelines: vec![],
                 passes: vec![],
-                cache: GlCache {
-                    stored_index_buffer: 0,
-                    stored_vertex_buffer: 0,
-                    index_buffer: 0,
-                    vertex_buffer: 0,
-                    cur_pipeline: None,
-                    blend: None,
-                    attributes: [None; MAX_VERTEX_ATTRIBUTES],
-                },
+                named_shaders: HashMap::new(),
+                named_pipelines: HashMap::new(),
+                cache: GlCache::new(features.max_vertex_attributes as usize),
                 //attributes: [None; 16],
+                features,
+                render_target_origin: crate::conf::RenderTargetOrigin::TopLeft,
+                reversed_z: false,
+                flipped_pass: None,
+                pass_size: (0, 0),
+                framebuffer: default_framebuffer,
+                vao,
+                external_size,
+                frame_limiter: Default::default(),
+                frame_pacer: Default::default(),
+                event_timestamp: 0.,
+                text_input_enabled: true,
+                executor: Default::default(),
+                scheduler: Default::default(),
+                uploads: Default::default(),
+                gestures: Default::default(),
+                appearance: (false, None, false),
+                #[cfg(target_arch = "wasm32")]
+                canvas_snapshots: vec![],
+                copy_frame_requested: false,
+                recorder: None,
+                late_latch: false,
+                pixel_canvas: None,
+                debug_view: Default::default(),
+                debug_overlay: Default::default(),
+                debug_gl: false,
+                shared_uniforms: vec![Default::default(); MAX_SHARED_UNIFORM_SLOTS],
+                uniform_ring: Default::default(),
+            }
+        }
+    }
+
+    /// Set everything up again on a new GL context, after the previous one was lost.
+    /// Shaders and pipelines are recreated, what could not be is returned.
+    pub(crate) fn restore(&mut self) -> LostResources {
+        unsafe {
+            glGetIntegerv(
+                GL_FRAMEBUFFER_BINDING,
+                &mut self.default_framebuffer as *mut _ as *mut _,
+            );
+            glGenVertexArrays(1, &mut self.vao as *mut _);
+            glBindVertexArray(self.vao);
+            // core since GL 3.2, WebGL 1 filters the faces separately
+            #[cfg(not(target_arch = "wasm32"))]
+            glEnable(GL_TEXTURE_CUBE_MAP_SEAMLESS);
+        }
+        self.framebuffer = self.default_framebuffer;
+        self.features = Features::query(self.external_size.is_some());
+        self.cache = GlCache::new(self.features.max_vertex_attributes as usize);
+        self.flipped_pass = None;
+        self.uniform_ring.lost();
+        for shared in &mut self.shared_uniforms {
+            shared.buffer = 0;
+            shared.buffer_size = 0;
+            shared.buffer_generation = 0;
+        }
+        self.apply_clip_control();
+
+        for shader in &mut self.shaders {
+            *shader =
+                load_shader_internal(&shader.vertex_source, &shader.fragment_source, shader.meta);
+        }
+        let shaders = &self.shaders;
+        for pipeline in &mut self.pipelines {
+            pipeline.layout = vertex_layout(
+                shaders[pipeline.shader.0].program,
+                &pipeline.buffer_layout,
+                &pipeline.attributes,
+            );
+        }
+
+        let mut lost = LIVE_RESOURCES.with(|resources| resources.replace(LostResources::default()));
+        for (n, pass) in self.passes.iter_mut().enumerate() {
+            if pass.gl_fb != 0 {
+                lost.render_passes.push(RenderPass(n));
+                pass.gl_fb = 0;
             }
         }
+        lost
     }
 
     pub(crate) fn resize(&mut self, w: u32, h: u32) {
@@ -516,18 +2003,64 @@ impl Context {
         }
     }
 
+    /// Y convention of offscreen passes, set with `Conf::render_target_origin`.
+    /// With `TopLeft` the same shaders and texture coordinates work for the default framebuffer,
+    /// render targets and loaded textures; scissor rects are flipped along.
+    /// With `BottomLeft` render targets have to be sampled with `1 - v`.
+    pub fn render_target_origin(&self) -> crate::conf::RenderTargetOrigin {
+        self.render_target_origin
+    }
+
+    pub fn features(&self) -> &Features {
+        &self.features
+    }
+
     pub fn screen_size(&self) -> (f32, f32) {
-        unsafe { (sapp_width() as f32, sapp_height() as f32) }
+        let (width, height) = self.default_framebuffer_size();
+        (width as f32, height as f32)
+    }
+
+    /// In pixels, of the window or of the external GL context
+    fn default_framebuffer_size(&self) -> (i32, i32) {
+        match self.external_size {
+            Some(size) => size,
+            None => unsafe { (sapp_width(), sapp_height()) },
+        }
+    }
+
+    /// Make `shader` available by name, e.g. for materials loaded from data files.
+    /// Replaces any shader registered under the same name.
+    pub fn register_shader(&mut self, name: &str, shader: Shader) {
+        self.named_shaders.insert(name.to_owned(), shader);
+    }
+
+    pub fn shader(&self, name: &str) -> Option<Shader> {
+        self.named_shaders.get(name).copied()
+    }
+
+    /// Make `pipeline` available by name, e.g. `ctx.pipeline("sprite_lit")`.
+    /// Replaces any pipeline registered under the same name.
+    pub fn register_pipeline(&mut self, name: &str, pipeline: Pipeline) {
+        self.named_pipelines.insert(name.to_owned(), pipeline);
+    }
+
+    pub fn pipeline(&self, name: &str) -> Option<Pipeline> {
+        self.named_pipelines.get(name).copied()
     }
 
     pub fn apply_pipeline(&mut self, pipeline: &Pipeline) {
+        self.check_gl_errors("before apply_pipeline");
+        self.count(|counters| counters.pipelines += 1);
         self.cache.cur_pipeline = Some(*pipeline);
 
-        let pipeline = &mut self.pipelines[pipeline.0];
-        let shader = &mut self.shaders[pipeline.shader.0];
+        let shader = &self.shaders[self.pipelines[pipeline.0].shader.0];
         unsafe {
             glUseProgram(shader.program);
         }
+        self.upload_flip_y();
+        self.upload_shared_uniforms(*pipeline);
+
+        let pipeline = &mut self.pipelines[pipeline.0];
 
         unsafe {
             glEnable(GL_SCISSOR_TEST);
@@ -544,6 +2077,24 @@ impl Context {
             }
         }
 
+        unsafe {
+            match pipeline.params.depth_write_offset {
+                Some((factor, units)) => {
+                    glEnable(GL_POLYGON_OFFSET_FILL);
+                    glPolygonOffset(factor, units);
+                }
+                None => glDisable(GL_POLYGON_OFFSET_FILL),
+            }
+            let (r, g, b, a) = pipeline.params.color_write;
+            glColorMask(r as _, g as _, b as _, a as _);
+            #[cfg(not(target_arch = "wasm32"))]
+            if pipeline.params.depth_clamp {
+                glEnable(GL_DEPTH_CLAMP);
+            } else {
+                glDisable(GL_DEPTH_CLAMP);
+            }
+        }
+
         if self.cache.blend != pipeline.params.color_blend {
             unsafe {
                 if let Some((equation, src, dst)) = pipeline.params.color_blend {
@@ -563,12 +2114,50 @@ impl Context {
     }
 
     pub fn apply_scissor_rect(&mut self, x: i32, y: i32, w: i32, h: i32) {
+        let y = match self.flipped_pass {
+            Some(pass_height) => pass_height - y - h,
+            None => y,
+        };
         unsafe {
             glScissor(x, y, w, h);
         }
     }
 
+    /// Draw into the `w`x`h` rectangle at `x`,`y` of the current pass only, in the same
+    /// coordinates as `apply_scissor_rect`. Reset to the whole pass by `begin_pass`.
+    pub fn apply_viewport(&mut self, x: i32, y: i32, w: i32, h: i32) {
+        let y = match self.flipped_pass {
+            Some(pass_height) => pass_height - y - h,
+            None => y,
+        };
+        unsafe {
+            glViewport(x, y, w, h);
+        }
+    }
+
+    /// Call `f` once per viewport, with drawing limited to that viewport, e.g. for
+    /// split-screen or the panes of an editor. Viewport and scissor are set back to the
+    /// whole pass afterwards, and also between viewports, should `f` change them.
+    pub fn render_viewports<F>(&mut self, viewports: &[Viewport], mut f: F)
+    where
+        F: FnMut(&mut Context, &Viewport),
+    {
+        for viewport in viewports {
+            let Viewport { x, y, w, h } = *viewport;
+            self.apply_viewport(x, y, w, h);
+            self.apply_scissor_rect(x, y, w, h);
+            f(self, viewport);
+        }
+        let (w, h) = self.pass_size;
+        unsafe {
+            glViewport(0, 0, w, h);
+            glScissor(0, 0, w, h);
+        }
+    }
+
     pub fn apply_bindings(&mut self, bindings: &Bindings) {
+        self.check_gl_errors("before apply_bindings");
+        self.count(|counters| counters.bindings += 1);
         let pip = &self.pipelines[self.cache.cur_pipeline.unwrap().0];
         let shader = &self.shaders[pip.shader.0];
 
@@ -579,92 +2168,355 @@ impl Context {
                 .unwrap_or_else(|| panic!("Image count in bindings and shader did not match!"));
             unsafe {
                 glActiveTexture(GL_TEXTURE0 + n as u32);
-                glBindTexture(GL_TEXTURE_2D, bindings_image.texture);
+                glBindTexture(bindings_image.target, bindings_image.texture);
                 glUniform1i(shader_image.gl_loc, n as i32);
             }
         }
 
+        debug_assert!(
+            bindings.index_buffer.buffer_type == BufferType::IndexBuffer,
+            "Bindings::index_buffer is not an index buffer"
+        );
         self.cache
             .bind_buffer(GL_ELEMENT_ARRAY_BUFFER, bindings.index_buffer.gl_buf);
+        self.cache.index_type = bindings.index_buffer.index_type;
+        self.cache.index_offset = bindings.index_buffer.offset;
 
+        let cur_pipeline = self.cache.cur_pipeline.unwrap();
+        let vertex_buffers = bindings
+            .vertex_buffers
+            .iter()
+            .map(|vb| (vb.gl_buf, vb.offset));
+        if let Some((pipeline, applied)) = &self.cache.vertex_state {
+            if *pipeline == cur_pipeline && applied.iter().copied().eq(vertex_buffers.clone()) {
+                return;
+            }
+        }
+        self.cache.vertex_state = Some((cur_pipeline, vertex_buffers.collect()));
+
+        #[cfg(debug_assertions)]
+        self.report_unused_vertex_buffers(cur_pipeline, bindings);
+
+        let attributes_len = self.pipelines[cur_pipeline.0].layout.len();
+        for attr_index in 0..attributes_len {
+            let mut attribute = self.pipelines[cur_pipeline.0].layout[attr_index];
+            let vb = *bindings
+                .vertex_buffers
+                .get(attribute.buffer_index)
+                .unwrap_or_else(|| {
+                    panic!(
+                        "Pipeline reads vertex buffer {} but the bindings have {}",
+                        attribute.buffer_index,
+                        bindings.vertex_buffers.len()
+                    )
+                });
+            attribute.offset += vb.offset as i64;
+            self.apply_attribute(attr_index, attribute, vb.gl_buf);
+        }
+        for attr_index in attributes_len..self.cache.enabled_attributes {
+            let cached_attr = &mut self.cache.attributes[attr_index];
+            if cached_attr.is_some() {
+                unsafe {
+                    glDisableVertexAttribArray(attr_index as GLuint);
+                }
+                *cached_attr = None;
+            }
+        }
+        self.cache.enabled_attributes = self.cache.enabled_attributes.min(attributes_len);
+    }
+
+    /// Replace the vertex buffer at `slot` of the bindings applied last, e.g. to draw
+    /// the same mesh with another instance buffer. Only the attributes of the current
+    /// pipeline reading from `slot` are set up again, `apply_bindings` has to be
+    /// called for the pipeline first.
+    pub fn apply_vertex_buffer(&mut self, slot: usize, buffer: Buffer) {
+        self.apply_vertex_buffer_offset(slot, buffer, 0);
+    }
+
+    /// Like `apply_vertex_buffer`, with the vertices starting `offset` bytes into
+    /// `buffer`, so sub-meshes or ranges of instances can share one big buffer:
+    /// for instances `first..`, `offset` is `first * instance_size`.
+    pub fn apply_vertex_buffer_offset(&mut self, slot: usize, buffer: Buffer, offset: usize) {
+        debug_assert!(
+            buffer.buffer_type == BufferType::VertexBuffer,
+            "apply_vertex_buffer with an index buffer"
+        );
+        assert!(
+            offset < buffer.size,
+            "Vertex buffer offset {} past the end of the buffer, {} bytes",
+            offset,
+            buffer.size
+        );
+        let cur_pipeline = self.cache.cur_pipeline.unwrap();
+        match &mut self.cache.vertex_state {
+            Some((pipeline, vertex_buffers))
+                if *pipeline == cur_pipeline && slot < vertex_buffers.len() =>
+            {
+                vertex_buffers[slot] = (buffer.gl_buf, buffer.offset + offset)
+            }
+            _ => panic!("apply_vertex_buffer before apply_bindings, or to a slot not in them"),
+        }
+
+        for attr_index in 0..self.pipelines[cur_pipeline.0].layout.len() {
+            let mut attribute = self.pipelines[cur_pipeline.0].layout[attr_index];
+            if attribute.buffer_index == slot {
+                attribute.offset += (buffer.offset + offset) as i64;
+                self.apply_attribute(attr_index, attribute, buffer.gl_buf);
+            }
+        }
+    }
+
+    /// Replace the image at `slot` of the bindings applied last, see `ImageId::slot`.
+    pub fn apply_image(&mut self, slot: usize, texture: Texture) {
         let pip = &self.pipelines[self.cache.cur_pipeline.unwrap().0];
+        let shader = &self.shaders[pip.shader.0];
+        assert!(
+            slot < shader.images.len(),
+            "Image slot {} but the shader has {} images",
+            slot,
+            shader.images.len()
+        );
+        unsafe {
+            glActiveTexture(GL_TEXTURE0 + slot as u32);
+            glBindTexture(texture.target, texture.texture);
+        }
+    }
 
-        for attr_index in 0..MAX_VERTEX_ATTRIBUTES {
-            let cached_attr = &mut self.cache.attributes[attr_index];
+    /// Point the attribute at `attr_index` to `gl_vbuf`, unless it already is.
+    fn apply_attribute(
+        &mut self,
+        attr_index: usize,
+        attribute: VertexAttributeInternal,
+        gl_vbuf: GLuint,
+    ) {
+        let cached_attr = self.cache.attributes[attr_index];
+        if cached_attr.map_or(false, |cached_attr| {
+            attribute == cached_attr.attribute && cached_attr.gl_vbuf == gl_vbuf
+        }) {
+            return;
+        }
+        self.cache.bind_buffer(GL_ARRAY_BUFFER, gl_vbuf);
 
-            let pip_attribute = pip.layout.get(attr_index).copied();
-
-            if let Some(attribute) = pip_attribute {
-                let vb = bindings.vertex_buffers[attribute.buffer_index];
-
-                if cached_attr.map_or(true, |cached_attr| {
-                    attribute != cached_attr.attribute || cached_attr.gl_vbuf != vb.gl_buf
-                }) {
-                    self.cache.bind_buffer(GL_ARRAY_BUFFER, vb.gl_buf);
-
-                    unsafe {
-                        glVertexAttribPointer(
-                            attr_index as GLuint,
-                            attribute.size,
-                            attribute.type_,
-                            GL_FALSE as u8,
-                            attribute.stride,
-                            attribute.offset as *mut _,
-                        );
-                        glVertexAttribDivisor(attr_index as GLuint, attribute.divisor as u32);
-                        glEnableVertexAttribArray(attr_index as GLuint);
-                    };
+        unsafe {
+            glVertexAttribPointer(
+                attr_index as GLuint,
+                attribute.size,
+                attribute.type_,
+                GL_FALSE as u8,
+                attribute.stride,
+                attribute.offset as *mut _,
+            );
+            glVertexAttribDivisor(attr_index as GLuint, attribute.divisor as u32);
+            glEnableVertexAttribArray(attr_index as GLuint);
+        };
 
-                    let cached_attr = &mut self.cache.attributes[attr_index];
-                    *cached_attr = Some(CachedAttribute {
-                        attribute,
-                        gl_vbuf: vb.gl_buf,
-                    });
-                }
-            } else {
-                if cached_attr.is_some() {
-                    unsafe {
-                        glDisableVertexAttribArray(attr_index as GLuint);
-                    }
-                    *cached_attr = None;
+        self.cache.attributes[attr_index] = Some(CachedAttribute { attribute, gl_vbuf });
+        self.cache.enabled_attributes = self.cache.enabled_attributes.max(attr_index + 1);
+    }
+
+    /// Vertex buffers no attribute of the pipeline reads are fine, e.g. an instance
+    /// buffer shared with other pipelines, but may as well be a mistake: tell once per pipeline.
+    #[cfg(debug_assertions)]
+    fn report_unused_vertex_buffers(&mut self, pipeline: Pipeline, bindings: &Bindings) {
+        let pip = &mut self.pipelines[pipeline.0];
+        if pip.unused_buffers_reported {
+            return;
+        }
+        let unused: Vec<usize> = (0..bindings.vertex_buffers.len())
+            .filter(|n| !pip.layout.iter().any(|attr| attr.buffer_index == *n))
+            .collect();
+        if !unused.is_empty() {
+            eprintln!(
+                "Vertex buffers {:?} of the bindings are not used by pipeline {}",
+                unused, pipeline.0
+            );
+            pip.unused_buffers_reported = true;
+        }
+    }
+
+    /// Find a uniform of the pipeline's shader by its `ShaderMeta` name,
+    /// without the length for arrays: `"bones"` for `"bones[32]"`.
+    pub fn uniform_id(&self, pipeline: &Pipeline, name: &str) -> Option<UniformId> {
+        let shader = self.pipelines[pipeline.0].shader.0;
+        self.shaders[shader]
+            .uniform_names
+            .get(name)
+            .map(|index| UniformId {
+                shader,
+                index: *index,
+            })
+    }
+
+    /// Find an image of the pipeline's shader by its `ShaderMeta` name.
+    /// The name of a sampler array finds its first element, the others follow it.
+    pub fn image_id(&self, pipeline: &Pipeline, name: &str) -> Option<ImageId> {
+        let shader = self.pipelines[pipeline.0].shader.0;
+        let image_names = &self.shaders[shader].image_names;
+        image_names
+            .get(name)
+            .or_else(|| image_names.get(format!("{}[0]", name).as_str()))
+            .map(|index| ImageId {
+                shader,
+                index: *index,
+            })
+    }
+
+    /// Set a single uniform of the current pipeline, `value` should have the size
+    /// of the uniform type, like `[f32; 4]` for `Float4`, times the length of an array.
+    pub fn apply_uniform<T>(&mut self, id: UniformId, value: &T) {
+        let pip = &self.pipelines[self.cache.cur_pipeline.unwrap().0];
+        assert_eq!(
+            pip.shader.0, id.shader,
+            "Uniform does not belong to the current pipeline"
+        );
+        let uniform = &self.shaders[id.shader].uniforms[id.index];
+        assert_eq!(mem::size_of::<T>(), uniform.size);
+
+        let data = value as *const _ as *const f32;
+        let count = uniform.count as i32;
+        unsafe {
+            match uniform.uniform_type {
+                UniformType::Float1 => glUniform1fv(uniform.gl_loc, count, data),
+                UniformType::Float2 => glUniform2fv(uniform.gl_loc, count, data),
+                UniformType::Float3 => glUniform3fv(uniform.gl_loc, count, data),
+                UniformType::Float4 => glUniform4fv(uniform.gl_loc, count, data),
+                UniformType::Mat4 => glUniformMatrix4fv(uniform.gl_loc, count, 0, data),
+            }
+        }
+    }
+
+    /// Fill the start of the `push_constants` array of `PUSH_CONSTANTS_GLSL` with `data`,
+    /// for the small values changing on every draw, a model matrix, a tint...
+    /// One GL call, without going through the uniform layout like `apply_uniforms`.
+    /// `data` is a whole number of `vec4`s of floats, up to `MAX_PUSH_CONSTANTS_SIZE` bytes.
+    /// Nothing happens if the current pipeline's shader doesn't declare the array.
+    pub fn apply_push_constants<T>(&mut self, data: &T) {
+        let size = mem::size_of::<T>();
+        assert!(
+            size % 16 == 0 && size <= MAX_PUSH_CONSTANTS_SIZE,
+            "Push constants of {} bytes, not a multiple of 16 up to {}",
+            size,
+            MAX_PUSH_CONSTANTS_SIZE
+        );
+        let pip = &self.pipelines[self.cache.cur_pipeline.unwrap().0];
+        let loc = self.shaders[pip.shader.0].push_constants_loc;
+        if loc != -1 {
+            unsafe {
+                glUniform4fv(loc, (size / 16) as i32, data as *const _ as *const f32);
+            }
+        }
+    }
+
+    /// Set the uniforms of a shared slot, for every shader declaring it in
+    /// `ShaderMeta::shared_uniforms`, e.g. the camera once per frame rather than for
+    /// each of the shaders drawing with it. `uniforms` is laid out like the slot's
+    /// `SharedUniformBlock::layout`.
+    ///
+    /// A program gets the new values when a pipeline of its shader is applied, or right
+    /// away for the current one, and only once per change: switching between pipelines
+    /// doesn't upload them again. Shaders declaring the slot's `SHARED_UNIFORMS_BLOCK`
+    /// share a single upload to a uniform buffer, with `Features::uniform_buffers`.
+    pub fn apply_shared_uniforms<U>(&mut self, slot: usize, uniforms: &U) {
+        assert!(
+            slot < MAX_SHARED_UNIFORM_SLOTS,
+            "Shared uniform slot {}, there are {}",
+            slot,
+            MAX_SHARED_UNIFORM_SLOTS
+        );
+        let size = mem::size_of::<U>();
+        let shared = &mut self.shared_uniforms[slot];
+        shared.data.clear();
+        shared.data.resize((size + 3) / 4, 0.);
+        unsafe {
+            std::ptr::copy_nonoverlapping(
+                uniforms as *const U as *const u8,
+                shared.data.as_mut_ptr() as *mut u8,
+                size,
+            );
+        }
+        shared.generation += 1;
+        if let Some(pipeline) = self.cache.cur_pipeline {
+            self.upload_shared_uniforms(pipeline);
+        }
+    }
+
+    /// Shared uniforms the pipeline's program doesn't have yet, the program in use
+    fn upload_shared_uniforms(&mut self, pipeline: Pipeline) {
+        let uniform_buffers = self.features.uniform_buffers;
+        let shader = &mut self.shaders[self.pipelines[pipeline.0].shader.0];
+        let mut uploads = 0;
+        for block in &mut shader.shared_uniforms {
+            let shared = &mut self.shared_uniforms[block.slot];
+            if uniform_buffers && block.block {
+                if shared.buffer_generation != shared.generation {
+                    shared.buffer_generation = shared.generation;
+                    unsafe { shared.upload_buffer(block.slot, &block.uniforms) };
+                    uploads += 1;
                 }
+                continue;
+            }
+            if block.generation == shared.generation {
+                continue;
+            }
+            block.generation = shared.generation;
+            unsafe {
+                upload_uniforms(&block.uniforms, shared.data.as_ptr(), shared.data.len() * 4);
             }
+            uploads += 1;
         }
+        self.count(|counters| counters.uniforms += uploads);
     }
 
     pub fn apply_uniforms<U>(&mut self, uniforms: &U) {
+        unsafe {
+            self.apply_uniforms_raw(uniforms as *const _ as *const f32, std::mem::size_of::<U>());
+        }
+    }
+
+    /// `apply_uniforms` with the uniform data as `size` bytes at `uniforms`
+    pub(crate) unsafe fn apply_uniforms_raw(&mut self, uniforms: *const f32, size: usize) {
+        self.check_gl_errors("before apply_uniforms");
+        self.count(|counters| counters.uniforms += 1);
         let pip = &self.pipelines[self.cache.cur_pipeline.unwrap().0];
         let shader = &self.shaders[pip.shader.0];
 
-        let mut offset = 0;
+        upload_uniforms(&shader.uniforms, uniforms, size);
+    }
 
-        for (_, uniform) in shader.uniforms.iter().enumerate() {
-            use UniformType::*;
+    /// The current pipeline's shader declares `TRANSIENT_UNIFORMS_BLOCK`
+    pub(crate) fn transient_uniforms_block(&self) -> bool {
+        let pip = &self.pipelines[self.cache.cur_pipeline.unwrap().0];
+        self.shaders[pip.shader.0].transient_block
+    }
 
-            assert!(offset < std::mem::size_of::<U>() - 4);
+    /// `apply_uniforms` with `data` laid out by the std140 rules of uniform blocks
+    pub(crate) fn apply_uniforms_std140(&mut self, data: &[f32]) {
+        self.check_gl_errors("before apply_uniforms");
+        self.count(|counters| counters.uniforms += 1);
+        let pip = &self.pipelines[self.cache.cur_pipeline.unwrap().0];
+        let shader = &self.shaders[pip.shader.0];
 
+        // in floats
+        let mut offset = 0;
+        let mut packed = vec![];
+        for uniform in &shader.uniforms {
+            let (start, size, stride) = uniform.std140(offset);
+            offset = start;
+            let end = offset + stride * (uniform.count - 1) + size;
+            assert!(end <= data.len(), "Uniforms smaller than the std140 layout");
             unsafe {
-                let data = (uniforms as *const _ as *const f32).offset(offset as isize);
-
-                match uniform.uniform_type {
-                    Float1 => {
-                        glUniform1fv(uniform.gl_loc, 1, data);
-                    }
-                    Float2 => {
-                        glUniform2fv(uniform.gl_loc, 1, data);
-                    }
-                    Float3 => {
-                        glUniform3fv(uniform.gl_loc, 1, data);
-                    }
-                    Float4 => {
-                        glUniform4fv(uniform.gl_loc, 1, data);
-                    }
-                    Mat4 => {
-                        glUniformMatrix4fv(uniform.gl_loc, 1, 0, data);
+                if stride == size {
+                    upload_uniform(uniform, data[offset..].as_ptr());
+                } else {
+                    packed.clear();
+                    for element in 0..uniform.count {
+                        let start = offset + element * stride;
+                        packed.extend_from_slice(&data[start..start + size]);
                     }
+                    upload_uniform(uniform, packed.as_ptr());
                 }
             }
-            offset += uniform.uniform_type.size(1) / 4;
+            offset += stride * uniform.count;
         }
     }
 
@@ -697,39 +2549,80 @@ impl Context {
         }
 
         if bits != 0 {
+            // the color mask applies to clears too, they clear every channel
+            let masked = color.is_some() && self.color_write() != (true, true, true, true);
             unsafe {
+                if masked {
+                    glColorMask(1, 1, 1, 1);
+                }
                 glClear(bits);
+                if masked {
+                    let (r, g, b, a) = self.color_write();
+                    glColorMask(r as _, g as _, b as _, a as _);
+                }
+            }
+        }
+    }
+
+    /// `PipelineParams::color_write` of the current pipeline
+    fn color_write(&self) -> (bool, bool, bool, bool) {
+        match self.cache.cur_pipeline {
+            Some(pipeline) => self.pipelines[pipeline.0].params.color_write,
+            None => (true, true, true, true),
+        }
+    }
+
+    /// `_mq_flip_y` of the current pipeline's shader, for the current pass. Set again
+    /// when the pass changes, the pipeline may have been applied before it.
+    fn upload_flip_y(&self) {
+        if let Some(pipeline) = self.cache.cur_pipeline {
+            let shader = &self.shaders[self.pipelines[pipeline.0].shader.0];
+            if shader.flip_y_loc != -1 {
+                let flip_y = if self.flipped_pass.is_some() { -1. } else { 1. };
+                unsafe {
+                    glUniform1f(shader.flip_y_loc, flip_y);
+                }
             }
         }
     }
 
-    /// start rendering to the default frame buffer
+    /// start rendering to the default frame buffer, or to the canvas with `Conf::pixel_art`
     pub fn begin_default_pass(&mut self, action: PassAction) {
-        self.begin_pass(None, action);
+        let pass = self.pixel_canvas.as_ref().map(|canvas| canvas.pass());
+        self.begin_pass(pass, action);
     }
 
     /// start rendering to an offscreen framebuffer
     pub fn begin_pass(&mut self, pass: impl Into<Option<RenderPass>>, action: PassAction) {
-        let (framebuffer, w, h) = match pass.into() {
-            None => (
-                self.default_framebuffer,
-                unsafe { sapp_width() } as i32,
-                unsafe { sapp_height() } as i32,
-            ),
+        self.check_gl_errors("before begin_pass");
+        self.count(|counters| counters.passes += 1);
+        let pass = pass.into();
+        let (framebuffer, w, h) = match pass {
+            None => {
+                let (w, h) = self.default_framebuffer_size();
+                (self.default_framebuffer, w, h)
+            }
             Some(pass) => {
                 let pass = &self.passes[pass.0];
                 (
                     pass.gl_fb,
-                    pass.texture.width as i32,
-                    pass.texture.height as i32,
+                    (pass.texture.width >> pass.level).max(1) as i32,
+                    (pass.texture.height >> pass.level).max(1) as i32,
                 )
             }
         };
+        self.flipped_pass = match (pass, self.render_target_origin) {
+            (Some(_), crate::conf::RenderTargetOrigin::TopLeft) => Some(h),
+            _ => None,
+        };
+        self.pass_size = (w, h);
+        self.framebuffer = framebuffer;
         unsafe {
             glBindFramebuffer(GL_FRAMEBUFFER, framebuffer);
             glViewport(0, 0, w, h);
             glScissor(0, 0, w, h);
         }
+        self.upload_flip_y();
         match action {
             PassAction::Nothing => {}
             PassAction::Clear {
@@ -743,36 +2636,247 @@ impl Context {
     }
 
     pub fn end_render_pass(&mut self) {
+        self.flipped_pass = None;
+        self.framebuffer = self.default_framebuffer;
         unsafe {
             glBindFramebuffer(GL_FRAMEBUFFER, self.default_framebuffer);
             self.cache.bind_buffer(GL_ARRAY_BUFFER, 0);
             self.cache.bind_buffer(GL_ELEMENT_ARRAY_BUFFER, 0);
         }
+        self.upload_flip_y();
     }
 
     pub fn commit_frame(&self) {}
 
+    /// Whether `texture` is the depth attachment of a render pass not deleted yet
+    pub(crate) fn is_depth_attachment(&self, texture: Texture) -> bool {
+        self.passes
+            .iter()
+            .any(|pass| pass.gl_fb != 0 && pass.depth_texture == Some(texture))
+    }
+
+    /// Run `f` making GL calls of its own, with the functions of `miniquad::gl`,
+    /// for what miniquad doesn't cover. `gl` gives the GL names of miniquad's objects.
+    ///
+    /// miniquad makes its GL calls right away, there is nothing pending when `f` runs.
+    /// Afterwards the state miniquad keeps track of is reset: its vertex array object,
+    /// the framebuffer of the current pass with a viewport and scissor rect covering it,
+    /// buffer bindings, vertex attributes, and the current pipeline, applied again.
+    /// Bindings have to be applied again before drawing. Other state `f` changes,
+    /// like the stencil test or pixel store parameters, it has to restore itself.
+    pub fn with_raw_gl<R>(&mut self, f: impl FnOnce(&RawGl) -> R) -> R {
+        let result = f(&RawGl { ctx: self });
+
+        let (w, h) = self.pass_size;
+        unsafe {
+            glBindVertexArray(self.vao);
+            #[cfg(not(target_arch = "wasm32"))]
+            glEnable(GL_TEXTURE_CUBE_MAP_SEAMLESS);
+            glBindFramebuffer(GL_FRAMEBUFFER, self.framebuffer);
+            glViewport(0, 0, w, h);
+            glScissor(0, 0, w, h);
+            glBindBuffer(GL_ARRAY_BUFFER, 0);
+            glBindBuffer(GL_ELEMENT_ARRAY_BUFFER, 0);
+            for attr_index in 0..self.features.max_vertex_attributes {
+                glDisableVertexAttribArray(attr_index);
+            }
+            glDisable(GL_BLEND);
+            glActiveTexture(GL_TEXTURE0);
+        }
+        let pipeline = self.cache.cur_pipeline;
+        self.cache = GlCache::new(self.features.max_vertex_attributes as usize);
+        if let Some(pipeline) = pipeline {
+            self.apply_pipeline(&pipeline);
+        }
+
+        result
+    }
+
+    /// RGBA8 pixels of the default framebuffer, rows from top to bottom.
+    pub(crate) fn read_default_framebuffer(&mut self) -> (i32, i32, Vec<u8>) {
+        let (width, height) = self.default_framebuffer_size();
+        let row = width as usize * 4;
+        let mut pixels = vec![0u8; row * height as usize];
+        unsafe {
+            glBindFramebuffer(GL_FRAMEBUFFER, self.default_framebuffer);
+            glReadPixels(
+                0,
+                0,
+                width,
+                height,
+                GL_RGBA,
+                GL_UNSIGNED_BYTE,
+                pixels.as_mut_ptr() as *mut _,
+            );
+        }
+        // GL rows go from bottom to top
+        let mut flipped = Vec::with_capacity(pixels.len());
+        for line in pixels.chunks(row).rev() {
+            flipped.extend_from_slice(line);
+        }
+        (width, height, flipped)
+    }
+
+    /// Draw `num_elements` indices of the bound index buffer, from `base_element`,
+    /// `num_instances` times. Offsets follow the index buffer's `IndexType`.
     pub fn draw(&self, base_element: i32, num_elements: i32, num_instances: i32) {
+        self.check_gl_errors("before draw");
+        self.count(|counters| counters.draw_calls += 1);
+        let index_type = self.cache.index_type.unwrap_or(IndexType::Short);
         unsafe {
             glDrawElementsInstanced(
                 GL_TRIANGLES,
                 num_elements,
-                GL_UNSIGNED_SHORT,
-                (2 * base_element) as *mut _,
+                index_type.gl_type(),
+                (self.cache.index_offset + index_type.size() as usize * base_element as usize)
+                    as *mut _,
                 num_instances,
             );
         }
+        self.check_gl_errors("in draw");
+    }
+
+    /// Whether the GPU was reset since the context was created, checked once per frame.
+    /// Only robust contexts tell, when the driver supports them.
+    pub(crate) fn graphics_reset(&self) -> Option<DeviceLostReason> {
+        match unsafe { sapp::sapp_graphics_reset_status() } {
+            0 => None,
+            0x8253 => Some(DeviceLostReason::Guilty),
+            0x8254 => Some(DeviceLostReason::Innocent),
+            _ => Some(DeviceLostReason::Unknown),
+        }
+    }
+
+    /// Print the GL errors raised since the last check, with `Conf::debug_gl`.
+    /// Errors are not tied to the call raising them, `during` tells what ran since.
+    pub(crate) fn check_gl_errors(&self, during: &str) {
+        if !self.debug_gl {
+            return;
+        }
+        // a lost context may report errors forever
+        for _ in 0..16 {
+            let error = unsafe { glGetError() };
+            let name = match error {
+                0 => return,
+                0x500 => "GL_INVALID_ENUM",
+                0x501 => "GL_INVALID_VALUE",
+                0x502 => "GL_INVALID_OPERATION",
+                0x505 => "GL_OUT_OF_MEMORY",
+                0x506 => "GL_INVALID_FRAMEBUFFER_OPERATION",
+                0x507 => "GL_CONTEXT_LOST",
+                _ => "unknown GL error",
+            };
+            eprintln!("{} (0x{:x}) {}", name, error, during);
+        }
+    }
+}
+
+/// Offscreen passes with `RenderTargetOrigin::TopLeft` render upside down: the user's `main`
+/// is renamed and called from a new `main` that flips gl_Position.y when `_mq_flip_y` is -1.
+/// This also reverses the triangle winding in those passes.
+fn wrap_vertex_main(source: &str) -> String {
+    let is_ident = |c: char| c.is_ascii_alphanumeric() || c == '_';
+
+    let mut search_from = 0;
+    while let Some(found) = source[search_from..].find("main") {
+        let start = search_from + found;
+        let end = start + "main".len();
+        search_from = end;
+
+        let before = source[..start].trim_end();
+        let after = source[end..].trim_start();
+        let word_start = source[..start]
+            .chars()
+            .next_back()
+            .map_or(true, |c| !is_ident(c));
+        let word_end = source[end..].chars().next().map_or(true, |c| !is_ident(c));
+        if word_start && word_end && before.ends_with("void") && after.starts_with('(') {
+            return format!(
+                "{}_mq_main{}\n\
+                 uniform float _mq_flip_y;\n\
+                 void main() {{\n\
+                 \x20   _mq_main();\n\
+                 \x20   gl_Position.y *= _mq_flip_y;\n\
+                 }}\n",
+                &source[..start],
+                &source[end..]
+            );
+        }
+    }
+    source.to_string()
+}
+
+/// `uniforms`, laid out by `ShaderUniform::offset`, from the `size` bytes at `data`
+unsafe fn upload_uniforms(uniforms: &[ShaderUniform], data: *const f32, size: usize) {
+    for uniform in uniforms {
+        assert!(uniform.offset + uniform.size <= size);
+        upload_uniform(uniform, data.add(uniform.offset / 4));
+    }
+}
+
+/// All the elements of `uniform`, from `data`
+unsafe fn upload_uniform(uniform: &ShaderUniform, data: *const f32) {
+    use UniformType::*;
+
+    let count = uniform.count as i32;
+    match uniform.uniform_type {
+        Float1 => glUniform1fv(uniform.gl_loc, count, data),
+        Float2 => glUniform2fv(uniform.gl_loc, count, data),
+        Float3 => glUniform3fv(uniform.gl_loc, count, data),
+        Float4 => glUniform4fv(uniform.gl_loc, count, data),
+        Mat4 => glUniformMatrix4fv(uniform.gl_loc, count, 0, data),
     }
 }
 
+/// Locations and offsets of the uniforms of `layout`
+unsafe fn shader_uniforms(program: GLuint, layout: &UniformBlockLayout) -> Vec<ShaderUniform> {
+    #[rustfmt::skip]
+    let uniforms = layout.uniforms.iter().scan(0, |offset, uniform| {
+        let (name, count) = glsl_array(uniform.0).unwrap_or((uniform.0, 1));
+        let res = ShaderUniform {
+            gl_loc: get_uniform_location(program, name),
+            offset: *offset,
+            size: uniform.1.size(count),
+            uniform_type: uniform.1,
+            count,
+        };
+        *offset += uniform.1.size(count);
+        Some(res)
+    }).collect();
+    uniforms
+}
+
 fn load_shader_internal(
-    vertex_shader: &str,
-    fragment_shader: &str,
+    vertex_source: &str,
+    fragment_source: &str,
     meta: ShaderMeta,
 ) -> ShaderInternal {
+    match try_load_shader_internal(vertex_source, fragment_source, meta) {
+        Ok(shader) => shader,
+        Err(ShaderCompileError::Link(error_message)) => panic!("{}", error_message),
+        Err(ShaderCompileError::Vertex(error_message))
+        | Err(ShaderCompileError::Fragment(error_message)) => {
+            eprintln!("{:?}", error_message);
+            panic!("cant compile shader!");
+        }
+    }
+}
+
+fn try_load_shader_internal(
+    vertex_source: &str,
+    fragment_source: &str,
+    meta: ShaderMeta,
+) -> Result<ShaderInternal, ShaderCompileError> {
     unsafe {
-        let vertex_shader = load_shader(GL_VERTEX_SHADER, vertex_shader);
-        let fragment_shader = load_shader(GL_FRAGMENT_SHADER, fragment_shader);
+        let vertex_shader = compile_shader(GL_VERTEX_SHADER, &wrap_vertex_main(vertex_source))
+            .map_err(ShaderCompileError::Vertex)?;
+        let fragment_shader = match compile_shader(GL_FRAGMENT_SHADER, fragment_source) {
+            Ok(shader) => shader,
+            Err(error_message) => {
+                glDeleteShader(vertex_shader);
+                return Err(ShaderCompileError::Fragment(error_message));
+            }
+        };
 
         let program = glCreateProgram();
         glAttachShader(program, vertex_shader);
@@ -782,7 +2886,8 @@ fn load_shader_internal(
         let mut link_status = 0;
         glGetProgramiv(program, GL_LINK_STATUS, &mut link_status as *mut _);
         if link_status == 0 {
-            let mut max_length = 100;
+            let mut max_length: i32 = 0;
+            glGetProgramiv(program, GL_INFO_LOG_LENGTH, &mut max_length as *mut _);
             let mut error_message = vec![0u8; max_length as usize + 1];
             glGetProgramInfoLog(
                 program,
@@ -790,9 +2895,14 @@ fn load_shader_internal(
                 &mut max_length as *mut _,
                 error_message.as_mut_ptr() as *mut _,
             );
-
-            let error_message = std::string::String::from_utf8_lossy(&error_message);
-            panic!("{}", error_message);
+            error_message.truncate(max_length as usize);
+
+            glDeleteProgram(program);
+            glDeleteShader(vertex_shader);
+            glDeleteShader(fragment_shader);
+            return Err(ShaderCompileError::Link(
+                String::from_utf8_lossy(&error_message).into_owned(),
+            ));
         }
 
         glUseProgram(program);
@@ -801,32 +2911,90 @@ fn load_shader_internal(
         let images = meta.images.iter().map(|name| ShaderImage {
                 gl_loc: get_uniform_location(program, name),
             }).collect();
-        #[rustfmt::skip]
-        let uniforms = meta.uniforms.uniforms.iter().scan(0, |offset, uniform| {
-            let res = ShaderUniform {
-                gl_loc: get_uniform_location(program, uniform.0),
-                offset: *offset,
-                size: uniform.1.size(1),
-                uniform_type: uniform.1
-            };
-            *offset += uniform.1.size(1);
-            Some(res)
-        }).collect();
-        ShaderInternal {
+        let uniforms = shader_uniforms(program, &meta.uniforms);
+        const GL_INVALID_INDEX: GLuint = 0xffff_ffff;
+        let shared_uniforms = meta
+            .shared_uniforms
+            .iter()
+            .map(|block| {
+                let name = format!("{}{}", SHARED_UNIFORMS_BLOCK, block.slot);
+                let name = CString::new(name).unwrap();
+                let index = glGetUniformBlockIndex(program, name.as_ptr());
+                if index != GL_INVALID_INDEX {
+                    let binding = SHARED_UNIFORMS_BINDING + block.slot as GLuint;
+                    glUniformBlockBinding(program, index, binding);
+                }
+                ShaderSharedUniforms {
+                    slot: block.slot,
+                    uniforms: shader_uniforms(program, &block.layout),
+                    block: index != GL_INVALID_INDEX,
+                    generation: 0,
+                }
+            })
+            .collect();
+        let transient_block = CString::new(crate::uniform_ring::TRANSIENT_UNIFORMS_BLOCK).unwrap();
+        let transient_block = glGetUniformBlockIndex(program, transient_block.as_ptr());
+        if transient_block != GL_INVALID_INDEX {
+            let binding = crate::uniform_ring::TRANSIENT_UNIFORMS_BINDING;
+            glUniformBlockBinding(program, transient_block, binding);
+        }
+        let flip_y_loc = glGetUniformLocation(program, b"_mq_flip_y\0".as_ptr() as *const _);
+        let push_constants_loc =
+            glGetUniformLocation(program, b"push_constants\0".as_ptr() as *const _);
+        let image_names = meta
+            .images
+            .iter()
+            .enumerate()
+            .map(|(index, name)| (*name, index))
+            .collect();
+        let uniform_names = meta
+            .uniforms
+            .uniforms
+            .iter()
+            .enumerate()
+            .map(|(index, (name, _))| (glsl_array(name).map_or(*name, |(name, _)| name), index))
+            .collect();
+        Ok(ShaderInternal {
             program,
+            vertex_source: vertex_source.to_string(),
+            fragment_source: fragment_source.to_string(),
+            meta,
             images,
             uniforms,
-        }
+            image_names,
+            uniform_names,
+            flip_y_loc,
+            push_constants_loc,
+            shared_uniforms,
+            transient_block: transient_block != GL_INVALID_INDEX,
+        })
     }
 }
 
 pub fn load_shader(shader_type: GLenum, source: &str) -> GLuint {
+    match compile_shader(shader_type, source) {
+        Ok(shader) => shader,
+        Err(error_message) => {
+            eprintln!("{:?}", error_message);
+            panic!("cant compile shader!");
+        }
+    }
+}
+
+/// The shader, or its info log when it does not compile
+fn compile_shader(shader_type: GLenum, source: &str) -> Result<GLuint, String> {
     unsafe {
         let shader = glCreateShader(shader_type);
 
         assert!(shader != 0);
 
-        let cstring = CString::new(source).unwrap_or_else(|e| panic!(e));
+        let cstring = match CString::new(source) {
+            Ok(cstring) => cstring,
+            Err(e) => {
+                glDeleteShader(shader);
+                return Err(e.to_string());
+            }
+        };
         let csource = [cstring];
         glShaderSource(shader, 1, csource.as_ptr() as *const _, std::ptr::null());
         glCompileShader(shader);
@@ -848,13 +3016,12 @@ pub fn load_shader(shader_type: GLenum, source: &str) -> GLuint {
             #[cfg(target_arch = "wasm32")]
             test_log(error_message.as_ptr() as *const _);
 
-            let error_message = std::string::String::from_utf8_lossy(&error_message);
-            eprintln!("{} {:?}", max_length, error_message);
+            error_message.truncate(max_length as usize);
             glDeleteShader(shader);
-            panic!("cant compile shader!");
+            return Err(String::from_utf8_lossy(&error_message).into_owned());
         }
 
-        shader
+        Ok(shader)
     }
 }
 
@@ -967,13 +3134,19 @@ pub struct PipelineParams {
     pub front_face_order: FrontFaceOrder,
     pub depth_test: Comparison,
     pub depth_write: bool,
+    /// Polygon offset `(factor, units)` added to written depths, e.g. against shadow acne
     pub depth_write_offset: Option<(f32, f32)>,
+    /// Clamp depths to the depth range rather than clipping at the near and far planes,
+    /// e.g. for shadow pancaking: casters between the light and its near plane still
+    /// cast shadows. Ignored without `Features::depth_clamp`.
+    pub depth_clamp: bool,
     pub color_blend: BlendState,
+    /// Red, green, blue and alpha channels drawing writes, clears write them all
     pub color_write: (bool, bool, bool, bool),
 }
 
-#[derive(Copy, Clone, Debug)]
-pub struct Pipeline(usize);
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Pipeline(pub(crate) usize);
 
 impl Default for PipelineParams {
     fn default() -> PipelineParams {
@@ -983,6 +3156,7 @@ impl Default for PipelineParams {
             depth_test: Comparison::Always, // no depth test,
             depth_write: false,             // no depth write,
             depth_write_offset: None,
+            depth_clamp: false,
             color_blend: None,
             color_write: (true, true, true, true),
         }
@@ -1006,116 +3180,138 @@ impl Pipeline {
         shader: Shader,
         params: PipelineParams,
     ) -> Pipeline {
-        #[derive(Clone, Copy, Default)]
-        struct BufferCacheData {
-            stride: i32,
-            offset: i64,
-        }
+        let program = ctx.shaders[shader.0].program;
+        let layout = vertex_layout(program, buffer_layout, attributes);
+        assert!(
+            layout.len() <= ctx.features.max_vertex_attributes as usize,
+            "Pipeline with {} vertex attributes, the driver supports {}",
+            layout.len(),
+            ctx.features.max_vertex_attributes
+        );
+        let pipeline = PipelineInternal {
+            layout,
+            buffer_layout: buffer_layout.to_vec(),
+            attributes: attributes.to_vec(),
+            shader,
+            params,
+            unused_buffers_reported: false,
+        };
 
-        let mut buffer_cache: Vec<BufferCacheData> =
-            vec![BufferCacheData::default(); buffer_layout.len()];
+        ctx.pipelines.push(pipeline);
+        Pipeline(ctx.pipelines.len() - 1)
+    }
+}
 
-        for VertexAttribute {
-            format,
-            buffer_index,
-            ..
-        } in attributes
-        {
-            let layout = buffer_layout.get(*buffer_index).unwrap_or_else(|| panic!());
-            let mut cache = buffer_cache
-                .get_mut(*buffer_index)
-                .unwrap_or_else(|| panic!());
+/// Where every attribute is in the vertex buffers, with the attribute locations of `program`
+fn vertex_layout(
+    program: GLuint,
+    buffer_layout: &[BufferLayout],
+    attributes: &[VertexAttribute],
+) -> Vec<VertexAttributeInternal> {
+    #[derive(Clone, Copy, Default)]
+    struct BufferCacheData {
+        stride: i32,
+        offset: i64,
+    }
 
-            if layout.stride == 0 {
-                cache.stride += format.byte_len();
-            } else {
-                cache.stride = layout.stride;
-            }
+    let mut buffer_cache: Vec<BufferCacheData> =
+        vec![BufferCacheData::default(); buffer_layout.len()];
+
+    for VertexAttribute {
+        format,
+        buffer_index,
+        ..
+    } in attributes
+    {
+        let layout = buffer_layout.get(*buffer_index).unwrap_or_else(|| panic!());
+        let mut cache = buffer_cache
+            .get_mut(*buffer_index)
+            .unwrap_or_else(|| panic!());
+
+        if layout.stride == 0 {
+            cache.stride += format.byte_len();
+        } else {
+            cache.stride = layout.stride;
         }
+    }
 
-        let program = ctx.shaders[shader.0].program;
-
-        let attributes_len = attributes
-            .iter()
-            .map(|layout| match layout.format {
-                VertexFormat::Mat4 => 4,
-                _ => 1,
+    let attributes_len = attributes
+        .iter()
+        .map(|layout| match layout.format {
+            VertexFormat::Mat4 => 4,
+            _ => 1,
+        })
+        .sum();
+
+    let mut vertex_layout: Vec<VertexAttributeInternal> =
+        vec![VertexAttributeInternal::default(); attributes_len];
+
+    for VertexAttribute {
+        name,
+        format,
+        buffer_index,
+        aliases,
+    } in attributes
+    {
+        let mut buffer_data = &mut buffer_cache
+            .get_mut(*buffer_index)
+            .unwrap_or_else(|| panic!());
+        let layout = buffer_layout.get(*buffer_index).unwrap_or_else(|| panic!());
+
+        let attr_loc = std::iter::once(name)
+            .chain(aliases.iter())
+            .map(|name| {
+                let cname = CString::new(*name).unwrap_or_else(|e| panic!(e));
+                unsafe { glGetAttribLocation(program, cname.as_ptr() as *const _) }
             })
-            .sum();
+            .find(|attr_loc| *attr_loc != -1)
+            .unwrap_or_else(|| panic!("Cant get \"{}\" attribute location", name));
+        let divisor = if layout.step_func == VertexStep::PerVertex {
+            0
+        } else {
+            layout.step_rate
+        };
 
-        let mut vertex_layout: Vec<VertexAttributeInternal> =
-            vec![VertexAttributeInternal::default(); attributes_len];
+        let mut attributes_count: usize = 1;
+        let mut format = *format;
 
-        for VertexAttribute {
-            name,
-            format,
-            buffer_index,
-        } in attributes
-        {
-            let mut buffer_data = &mut buffer_cache
-                .get_mut(*buffer_index)
-                .unwrap_or_else(|| panic!());
-            let layout = buffer_layout.get(*buffer_index).unwrap_or_else(|| panic!());
-
-            let cname = CString::new(*name).unwrap_or_else(|e| panic!(e));
-            let attr_loc = unsafe { glGetAttribLocation(program, cname.as_ptr() as *const _) };
-            if attr_loc == -1 {
-                panic!();
-            }
-            let divisor = if layout.step_func == VertexStep::PerVertex {
-                0
-            } else {
-                layout.step_rate
+        if format == VertexFormat::Mat4 {
+            format = VertexFormat::Float4;
+            attributes_count = 4;
+        }
+        for i in 0..attributes_count {
+            let attr_loc = attr_loc as GLuint + i as GLuint;
+
+            let attr = VertexAttributeInternal {
+                attr_loc,
+                size: format.size(),
+                type_: format.type_(),
+                offset: buffer_data.offset,
+                stride: buffer_data.stride,
+                buffer_index: *buffer_index,
+                divisor,
             };
+            //println!("{}: {:?}", name, attr);
+
+            assert!(
+                attr_loc < vertex_layout.len() as u32,
+                format!(
+                    "attribute: {} outside of allocated attributes array len: {}",
+                    name,
+                    vertex_layout.len()
+                )
+            );
+            vertex_layout[attr_loc as usize] = attr;
 
-            let mut attributes_count: usize = 1;
-            let mut format = *format;
-
-            if format == VertexFormat::Mat4 {
-                format = VertexFormat::Float4;
-                attributes_count = 4;
-            }
-            for i in 0..attributes_count {
-                let attr_loc = attr_loc as GLuint + i as GLuint;
-
-                let attr = VertexAttributeInternal {
-                    attr_loc,
-                    size: format.size(),
-                    type_: format.type_(),
-                    offset: buffer_data.offset,
-                    stride: buffer_data.stride,
-                    buffer_index: *buffer_index,
-                    divisor,
-                };
-                //println!("{}: {:?}", name, attr);
-
-                assert!(
-                    attr_loc < vertex_layout.len() as u32,
-                    format!(
-                        "attribute: {} outside of allocated attributes array len: {}",
-                        name,
-                        vertex_layout.len()
-                    )
-                );
-                vertex_layout[attr_loc as usize] = attr;
-
-                buffer_data.offset += (std::mem::size_of::<f32>() as i32 * format.size()) as i64
-            }
+            buffer_data.offset += format.byte_len() as i64
         }
+    }
 
-        // TODO: it should be possible to express a "holes" in the attribute layout in the api
-        // so empty attributes will be fine. But right now empty attribute is always a bug
-        assert!(vertex_layout.iter().any(|attr| attr.size == 0) == false);
-
-        let pipeline = PipelineInternal {
-            layout: vertex_layout,
-            shader,
-            params,
-        };
+    // TODO: it should be possible to express a "holes" in the attribute layout in the api
+    // so empty attributes will be fine. But right now empty attribute is always a bug
+    assert!(vertex_layout.iter().any(|attr| attr.size == 0) == false);
 
-        ctx.pipelines.push(pipeline);
-        Pipeline(ctx.pipelines.len() - 1)
-    }
+    vertex_layout
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Default)]
@@ -1131,23 +3327,65 @@ struct VertexAttributeInternal {
 
 struct PipelineInternal {
     layout: Vec<VertexAttributeInternal>,
+    /// Kept to find the attribute locations again when the GL context is restored
+    buffer_layout: Vec<BufferLayout>,
+    attributes: Vec<VertexAttribute>,
     shader: Shader,
     params: PipelineParams,
+    /// Debug builds tell once about bindings with vertex buffers the pipeline does not read
+    unused_buffers_reported: bool,
 }
 
-#[derive(Clone, Debug)]
+/// Buffers and images for the draws of a pipeline. `vertex_buffers` is indexed by
+/// `VertexAttribute::buffer_index` and may hold more buffers than the pipeline reads,
+/// so one `Bindings` can serve pipelines with and without, say, an instance buffer.
+#[derive(Clone, Debug, PartialEq)]
 pub struct Bindings {
     pub vertex_buffers: Vec<Buffer>,
     pub index_buffer: Buffer,
     pub images: Vec<Texture>,
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum BufferType {
     VertexBuffer,
     IndexBuffer,
 }
 
+/// Width of the elements of an index buffer, given by the type of the indices
+/// it is created with: `u16` or `u32`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum IndexType {
+    Short,
+    /// Needs OES_element_index_uint on WebGL 1, available nearly everywhere
+    Int,
+}
+
+impl IndexType {
+    pub fn for_type<T>() -> IndexType {
+        match mem::size_of::<T>() {
+            2 => IndexType::Short,
+            4 => IndexType::Int,
+            size => panic!("Index buffers hold u16 or u32 indices, not {} bytes", size),
+        }
+    }
+
+    /// Bytes per index
+    pub fn size(self) -> u8 {
+        match self {
+            IndexType::Short => 2,
+            IndexType::Int => 4,
+        }
+    }
+
+    fn gl_type(self) -> GLenum {
+        match self {
+            IndexType::Short => GL_UNSIGNED_SHORT,
+            IndexType::Int => GL_UNSIGNED_INT,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Usage {
     Immutable,
@@ -1170,16 +3408,72 @@ fn gl_usage(usage: &Usage) -> GLenum {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Buffer {
     gl_buf: GLuint,
     buffer_type: BufferType,
+    /// Bytes from `offset`
     size: usize,
+    /// `None` for vertex buffers
+    index_type: Option<IndexType>,
+    /// Start in the GL buffer, not 0 for the views made by `BufferSlice`
+    offset: usize,
+}
+
+/// `size` bytes of `buffer` from `offset`, e.g. one mesh of a big buffer shared by many.
+/// Converts into a `Buffer` that can be used anywhere a buffer goes, in `Bindings`
+/// or to `update` just that part:
+///
+/// ```ignore
+/// let mesh = BufferSlice { buffer: arena, offset: 4096, size: 1024 };
+/// bindings.vertex_buffers[0] = mesh.into();
+/// ```
+///
+/// Offsets of index buffer slices are a multiple of the index size, and WebGL wants
+/// vertex buffer offsets to be multiples of 4.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BufferSlice {
+    pub buffer: Buffer,
+    pub offset: usize,
+    pub size: usize,
+}
+
+impl From<BufferSlice> for Buffer {
+    fn from(slice: BufferSlice) -> Buffer {
+        let BufferSlice {
+            buffer,
+            offset,
+            size,
+        } = slice;
+        assert!(
+            offset + size <= buffer.size,
+            "Buffer slice {}..{} past the end of the buffer, {} bytes",
+            offset,
+            offset + size,
+            buffer.size
+        );
+        if let Some(index_type) = buffer.index_type {
+            assert!(
+                offset % index_type.size() as usize == 0,
+                "Index buffer slice at {} does not start on an index",
+                offset
+            );
+        }
+        Buffer {
+            offset: buffer.offset + offset,
+            size,
+            ..buffer
+        }
+    }
 }
 
 impl Buffer {
     /// Create an immutable buffer resource object.
     /// ```no_run
+    /// # use miniquad::*;
+    /// # fn f(ctx: &mut Context) {
+    /// # #[repr(C)]
+    /// # struct Vec2 { x: f32, y: f32 }
     /// #[repr(C)]
     /// struct Vertex {
     ///     pos: Vec2,
@@ -1192,9 +3486,14 @@ impl Buffer {
     ///     Vertex { pos : Vec2 { x: -0.5, y:  0.5 }, uv: Vec2 { x: 0., y: 1. } },
     /// ];
     /// let buffer = Buffer::immutable(ctx, BufferType::VertexBuffer, &vertices);
+    /// # }
     /// ```
     pub fn immutable<T>(ctx: &mut Context, buffer_type: BufferType, data: &[T]) -> Buffer {
         //println!("{} {}", mem::size_of::<T>(), mem::size_of_val(data));
+        let index_type = match buffer_type {
+            BufferType::IndexBuffer => Some(IndexType::for_type::<T>()),
+            BufferType::VertexBuffer => None,
+        };
         let gl_target = gl_buffer_target(&buffer_type);
         let gl_usage = gl_usage(&Usage::Immutable);
         let size = mem::size_of_val(data) as i64;
@@ -1209,16 +3508,44 @@ impl Buffer {
             ctx.cache.restore_buffer_binding(gl_target);
         }
 
-        Buffer {
+        track_buffer(Buffer {
             gl_buf,
             buffer_type,
             size: size as usize,
-        }
+            index_type,
+            offset: 0,
+        })
     }
 
+    /// `size` bytes to be filled with `update`, index buffers made this way hold `u16`s.
     pub fn stream(ctx: &mut Context, buffer_type: BufferType, size: usize) -> Buffer {
+        let index_type = match buffer_type {
+            BufferType::IndexBuffer => Some(IndexType::Short),
+            BufferType::VertexBuffer => None,
+        };
+        Buffer::empty(ctx, buffer_type, index_type, Usage::Stream, size)
+    }
+
+    /// Index buffer of `size` bytes to be filled with `update`, with indices of `index_type`.
+    pub fn index_stream(ctx: &mut Context, index_type: IndexType, size: usize) -> Buffer {
+        Buffer::empty(
+            ctx,
+            BufferType::IndexBuffer,
+            Some(index_type),
+            Usage::Stream,
+            size,
+        )
+    }
+
+    pub(crate) fn empty(
+        ctx: &mut Context,
+        buffer_type: BufferType,
+        index_type: Option<IndexType>,
+        usage: Usage,
+        size: usize,
+    ) -> Buffer {
         let gl_target = gl_buffer_target(&buffer_type);
-        let gl_usage = gl_usage(&Usage::Stream);
+        let gl_usage = gl_usage(&usage);
         let mut gl_buf: u32 = 0;
 
         unsafe {
@@ -1229,24 +3556,307 @@ impl Buffer {
             ctx.cache.restore_buffer_binding(gl_target);
         }
 
-        Buffer {
+        track_buffer(Buffer {
             gl_buf,
             buffer_type,
             size,
+            index_type,
+            offset: 0,
+        })
+    }
+
+    /// Width of the indices, `None` for vertex buffers.
+    pub fn index_type(&self) -> Option<IndexType> {
+        self.index_type
+    }
+
+    /// Bytes in the buffer, or in the slice for buffers made from a `BufferSlice`.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Start of a slice in its buffer, 0 for whole buffers.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Whether both are the same GL buffer, or slices of it.
+    pub fn same_buffer(&self, other: &Buffer) -> bool {
+        self.gl_buf == other.gl_buf
+    }
+
+    pub fn buffer_type(&self) -> BufferType {
+        self.buffer_type
+    }
+
+    /// `size` bytes from `offset`, see `BufferSlice`.
+    pub fn slice(&self, offset: usize, size: usize) -> Buffer {
+        BufferSlice {
+            buffer: *self,
+            offset,
+            size,
         }
+        .into()
     }
 
     pub fn update<T: std::fmt::Debug>(&self, ctx: &mut Context, data: &[T]) {
+        crate::zone!("buffer update");
         //println!("{} {}", mem::size_of::<T>(), mem::size_of_val(data));
 
         let size = mem::size_of_val(data);
 
         assert!(size <= self.size);
+        if let Some(index_type) = self.index_type {
+            debug_assert_eq!(
+                index_type.size() as usize,
+                mem::size_of::<T>(),
+                "Index buffer updated with indices of another size"
+            );
+        }
 
+        unsafe { self.update_raw(ctx, 0, data.as_ptr() as *const u8, size) };
+    }
+
+    /// Copy `size` bytes at `data` to `offset` bytes into the buffer
+    pub(crate) unsafe fn update_raw(
+        &self,
+        ctx: &mut Context,
+        offset: usize,
+        data: *const u8,
+        size: usize,
+    ) {
+        assert!(offset + size <= self.size);
         let gl_target = gl_buffer_target(&self.buffer_type);
 
         ctx.cache.bind_buffer(gl_target, self.gl_buf);
-        unsafe { glBufferSubData(gl_target, 0, size as _, data.as_ptr() as *const _) };
+        glBufferSubData(
+            gl_target,
+            (self.offset + offset) as _,
+            size as _,
+            data as *const _,
+        );
         ctx.cache.restore_buffer_binding(gl_target);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn uniforms(layout: &'static [(&'static str, UniformType)]) -> Vec<ShaderUniform> {
+        let mut offset = 0;
+        layout
+            .iter()
+            .map(|(name, uniform_type)| {
+                let count = glsl_array(name).map_or(1, |(_, count)| count);
+                let size = uniform_type.size(count);
+                offset += size;
+                ShaderUniform {
+                    gl_loc: -1,
+                    offset: offset - size,
+                    size,
+                    uniform_type: *uniform_type,
+                    count,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn std140_alignment() {
+        use UniformType::*;
+
+        let uniforms = uniforms(&[
+            ("a", Float1),
+            ("b", Float3),
+            ("c[2]", Float1),
+            ("d", Float2),
+            ("e", Mat4),
+        ]);
+        let data: Vec<f32> = (1..=24).map(|v| v as f32).collect();
+        let block = std140_block(&uniforms, &data);
+        let mut expected = vec![0.; 36];
+        // a, b on the next vec4
+        expected[0] = 1.;
+        expected[4..7].copy_from_slice(&[2., 3., 4.]);
+        // array elements a vec4 apart
+        expected[8] = 5.;
+        expected[12] = 6.;
+        // d after the whole last element
+        expected[16..18].copy_from_slice(&[7., 8.]);
+        expected[20..36].copy_from_slice(&data[8..24]);
+        assert_eq!(block, expected);
+    }
+
+    #[test]
+    fn std140_vec3_then_float() {
+        use UniformType::*;
+
+        let uniforms = uniforms(&[("a", Float3), ("b", Float1), ("c", Float2)]);
+        let block = std140_block(&uniforms, &[1., 2., 3., 4., 5., 6.]);
+        // the float fills the vec3's vec4, the block is whole vec4s
+        assert_eq!(block, [1., 2., 3., 4., 5., 6., 0., 0.]);
+    }
+
+    /// Surfaceless EGL context current on this thread, None without Mesa's EGL.
+    /// libEGL is opened at runtime, the tests still link where it is missing.
+    #[cfg(target_os = "linux")]
+    fn egl_context() -> Option<()> {
+        use std::ffi::c_void;
+        use std::os::raw::{c_char, c_int};
+
+        extern "C" {
+            fn dlopen(filename: *const c_char, flag: c_int) -> *mut c_void;
+            fn dlsym(handle: *mut c_void, symbol: *const c_char) -> *mut c_void;
+        }
+        const RTLD_NOW: c_int = 2;
+        const EGL_PLATFORM_SURFACELESS_MESA: u32 = 0x31dd;
+        const EGL_SURFACE_TYPE: i32 = 0x3033;
+        const EGL_PBUFFER_BIT: i32 = 0x1;
+        const EGL_RENDERABLE_TYPE: i32 = 0x3040;
+        const EGL_OPENGL_BIT: i32 = 0x8;
+        const EGL_NONE: i32 = 0x3038;
+        const EGL_OPENGL_API: u32 = 0x30a2;
+
+        type GetPlatformDisplay = extern "C" fn(u32, *mut c_void, *const isize) -> *mut c_void;
+        type Initialize = extern "C" fn(*mut c_void, *mut i32, *mut i32) -> u32;
+        type ChooseConfig =
+            extern "C" fn(*mut c_void, *const i32, *mut *mut c_void, i32, *mut i32) -> u32;
+        type BindApi = extern "C" fn(u32) -> u32;
+        type CreateContext =
+            extern "C" fn(*mut c_void, *mut c_void, *mut c_void, *const i32) -> *mut c_void;
+        type MakeCurrent = extern "C" fn(*mut c_void, *mut c_void, *mut c_void, *mut c_void) -> u32;
+
+        unsafe {
+            let lib = dlopen(b"libEGL.so.1\0".as_ptr() as _, RTLD_NOW);
+            if lib.is_null() {
+                return None;
+            }
+            let symbol = |name: &[u8]| {
+                let f = dlsym(lib, name.as_ptr() as _);
+                if f.is_null() {
+                    None
+                } else {
+                    Some(f)
+                }
+            };
+            let get_platform_display: GetPlatformDisplay =
+                std::mem::transmute(symbol(b"eglGetPlatformDisplay\0")?);
+            let initialize: Initialize = std::mem::transmute(symbol(b"eglInitialize\0")?);
+            let choose_config: ChooseConfig = std::mem::transmute(symbol(b"eglChooseConfig\0")?);
+            let bind_api: BindApi = std::mem::transmute(symbol(b"eglBindAPI\0")?);
+            let create_context: CreateContext = std::mem::transmute(symbol(b"eglCreateContext\0")?);
+            let make_current: MakeCurrent = std::mem::transmute(symbol(b"eglMakeCurrent\0")?);
+
+            let null = std::ptr::null_mut();
+            let display =
+                get_platform_display(EGL_PLATFORM_SURFACELESS_MESA, null, std::ptr::null());
+            if display.is_null() || initialize(display, null as _, null as _) == 0 {
+                return None;
+            }
+            // surfaceless displays have no window configs, the default surface type
+            let attributes = [
+                EGL_SURFACE_TYPE,
+                EGL_PBUFFER_BIT,
+                EGL_RENDERABLE_TYPE,
+                EGL_OPENGL_BIT,
+                EGL_NONE,
+            ];
+            let mut config = null;
+            let mut configs = 0;
+            if choose_config(display, attributes.as_ptr(), &mut config, 1, &mut configs) == 0
+                || configs == 0
+                || bind_api(EGL_OPENGL_API) == 0
+            {
+                return None;
+            }
+            let context = create_context(display, config, null, [EGL_NONE].as_ptr());
+            if context.is_null() || make_current(display, null, null, context) == 0 {
+                return None;
+            }
+        }
+        Some(())
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn flip_y_follows_the_pass() {
+        if egl_context().is_none() {
+            eprintln!("no surfaceless EGL, skipped");
+            return;
+        }
+        let mut ctx = Context::new_external(4, 4);
+        assert_eq!(
+            ctx.render_target_origin(),
+            crate::conf::RenderTargetOrigin::TopLeft
+        );
+
+        const VERTEX: &str = "#version 100
+        attribute vec2 pos;
+        void main() {
+            gl_Position = vec4(pos, 0, 1);
+        }";
+        const FRAGMENT: &str = "#version 100
+        void main() {
+            gl_FragColor = vec4(1, 0, 0, 1);
+        }";
+        let shader = Shader::new(
+            &mut ctx,
+            VERTEX,
+            FRAGMENT,
+            ShaderMeta::new(UniformBlockLayout { uniforms: &[] }, &[]),
+        );
+        let pipeline = Pipeline::new(
+            &mut ctx,
+            &[BufferLayout::default()],
+            &[VertexAttribute::new("pos", VertexFormat::Float2)],
+            shader,
+        );
+        // upper half of clip space, the top rows of the texture
+        let vertices: [f32; 8] = [-1., 0., 1., 0., 1., 1., -1., 1.];
+        let bindings = Bindings {
+            vertex_buffers: vec![Buffer::immutable(
+                &mut ctx,
+                BufferType::VertexBuffer,
+                &vertices,
+            )],
+            index_buffer: Buffer::immutable(
+                &mut ctx,
+                BufferType::IndexBuffer,
+                &[0u16, 1, 2, 0, 2, 3],
+            ),
+            images: vec![],
+        };
+        let texture = Texture::new_render_texture(RenderTextureParams {
+            width: 4,
+            height: 4,
+            ..Default::default()
+        });
+        let pass = RenderPass::new(&mut ctx, texture, None);
+
+        // applied before the pass, while the default framebuffer isn't flipped
+        ctx.apply_pipeline(&pipeline);
+        ctx.begin_pass(pass, PassAction::clear_color(0., 0., 0., 1.));
+        ctx.apply_bindings(&bindings);
+        ctx.draw(0, 6, 1);
+        ctx.end_render_pass();
+
+        let mut pixels = [0u8; 4 * 4 * 4];
+        unsafe {
+            glBindFramebuffer(GL_FRAMEBUFFER, ctx.passes[pass.0].gl_fb);
+            glReadPixels(
+                0,
+                0,
+                4,
+                4,
+                GL_RGBA,
+                GL_UNSIGNED_BYTE,
+                pixels.as_mut_ptr() as *mut _,
+            );
+            glBindFramebuffer(GL_FRAMEBUFFER, ctx.default_framebuffer);
+        }
+        // with TopLeft, row 0 of the texture is the top of the image
+        let red: Vec<bool> = pixels.chunks(16).map(|row| row[0] == 255).collect();
+        assert_eq!(red, [true, true, false, false]);
+    }
+}
@@ -0,0 +1,564 @@
+//! Small baseline JPEG decoder, just enough for game textures.
+//!
+//! Supports baseline and extended huffman-coded images, grayscale or YCbCr with any
+//! chroma subsampling, and restart markers. Progressive and arithmetic-coded images
+//! are not supported.
+
+#[derive(Debug)]
+pub enum Error {
+    NotJpeg,
+    Corrupted,
+    /// Valid JPEG, but uses a feature this decoder does not support
+    Unsupported(&'static str),
+}
+
+pub struct Image {
+    pub width: u16,
+    pub height: u16,
+    /// RGBA8 pixels, row by row
+    pub bytes: Vec<u8>,
+}
+
+const ZIGZAG: [usize; 64] = [
+    0, 1, 8, 16, 9, 2, 3, 10, 17, 24, 32, 25, 18, 11, 4, 5, 12, 19, 26, 33, 40, 48, 41, 34, 27, 20,
+    13, 6, 7, 14, 21, 28, 35, 42, 49, 56, 57, 50, 43, 36, 29, 22, 15, 23, 30, 37, 44, 51, 58, 59,
+    52, 45, 38, 31, 39, 46, 53, 60, 61, 54, 47, 55, 62, 63,
+];
+
+#[derive(Default, Clone)]
+struct Huffman {
+    counts: [u16; 17],
+    symbols: Vec<u8>,
+}
+
+#[derive(Default, Clone)]
+struct Component {
+    id: u8,
+    h: usize,
+    v: usize,
+    quant: usize,
+    dc_table: usize,
+    ac_table: usize,
+    dc_pred: i32,
+    /// blocks per line and per column, including padding blocks up to the MCU size
+    blocks_w: usize,
+    blocks_h: usize,
+    pixels: Vec<u8>,
+}
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    acc: u32,
+    bits: u32,
+}
+
+impl<'a> BitReader<'a> {
+    fn bit(&mut self) -> u32 {
+        if self.bits == 0 {
+            let mut byte = 0;
+            if self.pos < self.data.len() {
+                byte = self.data[self.pos];
+                if byte == 0xff {
+                    match self.data.get(self.pos + 1) {
+                        // stuffed zero byte
+                        Some(0) => self.pos += 2,
+                        // marker, feed zeros until the caller deals with it
+                        _ => byte = 0,
+                    }
+                } else {
+                    self.pos += 1;
+                }
+            }
+            self.acc = byte as u32;
+            self.bits = 8;
+        }
+        self.bits -= 1;
+        (self.acc >> self.bits) & 1
+    }
+
+    fn receive(&mut self, n: u32) -> u32 {
+        let mut value = 0;
+        for _ in 0..n {
+            value = (value << 1) | self.bit();
+        }
+        value
+    }
+
+    /// Read a n-bit value and extend its sign as described in F.2.2.1
+    fn receive_extend(&mut self, n: u32) -> i32 {
+        if n == 0 {
+            return 0;
+        }
+        let value = self.receive(n) as i32;
+        if value < 1 << (n - 1) {
+            value - (1 << n) + 1
+        } else {
+            value
+        }
+    }
+
+    fn decode(&mut self, table: &Huffman) -> Result<u8, Error> {
+        let mut code: i32 = 0;
+        let mut first: i32 = 0;
+        let mut index: i32 = 0;
+        for len in 1..17 {
+            code |= self.bit() as i32;
+            let count = table.counts[len] as i32;
+            if code - count < first {
+                return table
+                    .symbols
+                    .get((index + code - first) as usize)
+                    .cloned()
+                    .ok_or(Error::Corrupted);
+            }
+            index += count;
+            first += count;
+            first <<= 1;
+            code <<= 1;
+        }
+        Err(Error::Corrupted)
+    }
+
+    /// Skip to the RSTn marker and past it
+    fn restart(&mut self) {
+        self.bits = 0;
+        while self.pos + 1 < self.data.len() {
+            if self.data[self.pos] == 0xff && (0xd0..=0xd7).contains(&self.data[self.pos + 1]) {
+                self.pos += 2;
+                return;
+            }
+            self.pos += 1;
+        }
+    }
+}
+
+fn read_u16(data: &[u8], pos: usize) -> Result<usize, Error> {
+    if pos + 2 > data.len() {
+        return Err(Error::Corrupted);
+    }
+    Ok(((data[pos] as usize) << 8) | data[pos + 1] as usize)
+}
+
+pub fn decode(data: &[u8]) -> Result<Image, Error> {
+    if !data.starts_with(&[0xff, 0xd8]) {
+        return Err(Error::NotJpeg);
+    }
+
+    let mut quant = [[0u16; 64]; 4];
+    let mut dc_tables = vec![Huffman::default(); 4];
+    let mut ac_tables = vec![Huffman::default(); 4];
+    let mut components: Vec<Component> = vec![];
+    let mut width = 0;
+    let mut height = 0;
+    let mut restart_interval = 0;
+    let mut decoded = false;
+
+    let mut pos = 2;
+    while pos + 4 <= data.len() && !decoded {
+        if data[pos] != 0xff {
+            return Err(Error::Corrupted);
+        }
+        let marker = data[pos + 1];
+        if marker == 0xff {
+            // fill byte
+            pos += 1;
+            continue;
+        }
+        let len = read_u16(data, pos + 2)?;
+        let segment = data.get(pos + 4..pos + 2 + len).ok_or(Error::Corrupted)?;
+        pos += 2 + len;
+
+        match marker {
+            // DQT
+            0xdb => {
+                let mut i = 0;
+                while i < segment.len() {
+                    let precision = segment[i] >> 4;
+                    let table = (segment[i] & 3) as usize;
+                    i += 1;
+                    for k in 0..64 {
+                        quant[table][ZIGZAG[k]] = if precision == 0 {
+                            *segment.get(i + k).ok_or(Error::Corrupted)? as u16
+                        } else {
+                            read_u16(segment, i + k * 2)? as u16
+                        };
+                    }
+                    i += if precision == 0 { 64 } else { 128 };
+                }
+            }
+            // DHT
+            0xc4 => {
+                let mut i = 0;
+                while i < segment.len() {
+                    let class = segment[i] >> 4;
+                    let id = (segment[i] & 3) as usize;
+                    let mut table = Huffman::default();
+                    let mut total = 0;
+                    for len in 1..17 {
+                        table.counts[len] = *segment.get(i + len).ok_or(Error::Corrupted)? as u16;
+                        total += table.counts[len] as usize;
+                    }
+                    i += 17;
+                    table.symbols = segment.get(i..i + total).ok_or(Error::Corrupted)?.to_vec();
+                    i += total;
+                    if class == 0 {
+                        dc_tables[id] = table;
+                    } else {
+                        ac_tables[id] = table;
+                    }
+                }
+            }
+            // baseline and extended sequential huffman
+            0xc0 | 0xc1 => {
+                if segment.len() < 6 || segment[0] != 8 {
+                    return Err(Error::Unsupported("12 bit precision"));
+                }
+                height = read_u16(segment, 1)?;
+                width = read_u16(segment, 3)?;
+                let count = segment[5] as usize;
+                if width == 0 || height == 0 {
+                    return Err(Error::Unsupported("image size"));
+                }
+                if count != 1 && count != 3 {
+                    return Err(Error::Unsupported("CMYK images"));
+                }
+                for c in 0..count {
+                    let spec = segment.get(6 + c * 3..9 + c * 3).ok_or(Error::Corrupted)?;
+                    let (h, v) = ((spec[1] >> 4) as usize, (spec[1] & 15) as usize);
+                    if h == 0 || v == 0 || h > 4 || v > 4 {
+                        return Err(Error::Corrupted);
+                    }
+                    components.push(Component {
+                        id: spec[0],
+                        h,
+                        v,
+                        quant: (spec[2] & 3) as usize,
+                        ..Default::default()
+                    });
+                }
+            }
+            0xc2 | 0xc3 | 0xc5..=0xc7 | 0xc9..=0xcb | 0xcd..=0xcf => {
+                return Err(Error::Unsupported(
+                    "progressive, lossless or arithmetic coding",
+                ));
+            }
+            // DRI
+            0xdd => restart_interval = read_u16(segment, 0)?,
+            // SOS
+            0xda => {
+                if components.is_empty() {
+                    return Err(Error::Corrupted);
+                }
+                let count = *segment.get(0).ok_or(Error::Corrupted)? as usize;
+                if count != components.len() {
+                    return Err(Error::Unsupported("multiple scans"));
+                }
+                for i in 0..count {
+                    let spec = segment.get(1 + i * 2..3 + i * 2).ok_or(Error::Corrupted)?;
+                    let component = components
+                        .iter_mut()
+                        .find(|c| c.id == spec[0])
+                        .ok_or(Error::Corrupted)?;
+                    component.dc_table = (spec[1] >> 4) as usize & 3;
+                    component.ac_table = (spec[1] & 15) as usize & 3;
+                }
+                let mut reader = BitReader {
+                    data,
+                    pos,
+                    acc: 0,
+                    bits: 0,
+                };
+                decode_scan(
+                    &mut reader,
+                    &mut components,
+                    &quant,
+                    &dc_tables,
+                    &ac_tables,
+                    width,
+                    height,
+                    restart_interval,
+                )?;
+                decoded = true;
+            }
+            // EOI
+            0xd9 => break,
+            _ => {}
+        }
+    }
+
+    if !decoded {
+        return Err(Error::Corrupted);
+    }
+
+    let h_max = components.iter().map(|c| c.h).max().unwrap();
+    let v_max = components.iter().map(|c| c.v).max().unwrap();
+    let mut bytes = Vec::with_capacity(width * height * 4);
+    // chroma upsampling is nearest neighbour
+    let sample = |c: &Component, x: usize, y: usize| -> f32 {
+        let (x, y) = if components.len() == 1 {
+            (x, y)
+        } else {
+            (x * c.h / h_max, y * c.v / v_max)
+        };
+        c.pixels[y * c.blocks_w * 8 + x] as f32
+    };
+    for y in 0..height {
+        for x in 0..width {
+            if components.len() == 1 {
+                let l = sample(&components[0], x, y) as u8;
+                bytes.extend_from_slice(&[l, l, l, 255]);
+            } else {
+                let luma = sample(&components[0], x, y);
+                let cb = sample(&components[1], x, y) - 128.0;
+                let cr = sample(&components[2], x, y) - 128.0;
+                let clamp = |v: f32| v.max(0.0).min(255.0).round() as u8;
+                bytes.extend_from_slice(&[
+                    clamp(luma + 1.402 * cr),
+                    clamp(luma - 0.344136 * cb - 0.714136 * cr),
+                    clamp(luma + 1.772 * cb),
+                    255,
+                ]);
+            }
+        }
+    }
+
+    Ok(Image {
+        width: width as u16,
+        height: height as u16,
+        bytes,
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+fn decode_scan(
+    reader: &mut BitReader,
+    components: &mut [Component],
+    quant: &[[u16; 64]; 4],
+    dc_tables: &[Huffman],
+    ac_tables: &[Huffman],
+    width: usize,
+    height: usize,
+    restart_interval: usize,
+) -> Result<(), Error> {
+    let single = components.len() == 1;
+    let h_max = components.iter().map(|c| c.h).max().unwrap();
+    let v_max = components.iter().map(|c| c.v).max().unwrap();
+    // a non-interleaved scan is made of single 8x8 blocks, whatever the sampling factors
+    let (mcu_w, mcu_h) = if single {
+        (8, 8)
+    } else {
+        (8 * h_max, 8 * v_max)
+    };
+    let mcus_x = (width + mcu_w - 1) / mcu_w;
+    let mcus_y = (height + mcu_h - 1) / mcu_h;
+
+    for c in components.iter_mut() {
+        let (h, v) = if single { (1, 1) } else { (c.h, c.v) };
+        c.blocks_w = mcus_x * h;
+        c.blocks_h = mcus_y * v;
+        c.pixels = vec![0; c.blocks_w * c.blocks_h * 64];
+        c.dc_pred = 0;
+    }
+
+    let mut block = [0u8; 64];
+    for mcu in 0..mcus_x * mcus_y {
+        if restart_interval != 0 && mcu != 0 && mcu % restart_interval == 0 {
+            reader.restart();
+            for c in components.iter_mut() {
+                c.dc_pred = 0;
+            }
+        }
+        let (mcu_x, mcu_y) = (mcu % mcus_x, mcu / mcus_x);
+        for c in components.iter_mut() {
+            let (h, v) = if single { (1, 1) } else { (c.h, c.v) };
+            for by in 0..v {
+                for bx in 0..h {
+                    let mut coefficients = [0i32; 64];
+
+                    // differences of 8 bit samples take at most 11 bits, ac values 10
+                    let dc_len = reader.decode(&dc_tables[c.dc_table])? as u32;
+                    if dc_len > 11 {
+                        return Err(Error::Corrupted);
+                    }
+                    // garbage in a corrupted file may add up to anything
+                    c.dc_pred = c.dc_pred.wrapping_add(reader.receive_extend(dc_len));
+                    coefficients[0] = c.dc_pred.wrapping_mul(quant[c.quant][0] as i32);
+
+                    let mut k = 1;
+                    while k < 64 {
+                        let rs = reader.decode(&ac_tables[c.ac_table])?;
+                        let (run, size) = ((rs >> 4) as usize, (rs & 15) as u32);
+                        if size == 0 {
+                            if run != 15 {
+                                // end of block
+                                break;
+                            }
+                            k += 16;
+                            continue;
+                        }
+                        k += run;
+                        if k > 63 || size > 10 {
+                            return Err(Error::Corrupted);
+                        }
+                        coefficients[ZIGZAG[k]] =
+                            reader.receive_extend(size) * quant[c.quant][ZIGZAG[k]] as i32;
+                        k += 1;
+                    }
+
+                    idct(&coefficients, &mut block);
+
+                    let block_x = mcu_x * h + bx;
+                    let block_y = mcu_y * v + by;
+                    let stride = c.blocks_w * 8;
+                    for y in 0..8 {
+                        let row = (block_y * 8 + y) * stride + block_x * 8;
+                        c.pixels[row..row + 8].copy_from_slice(&block[y * 8..y * 8 + 8]);
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Straightforward separable float IDCT, with level shift and clamping
+fn idct(input: &[i32; 64], output: &mut [u8; 64]) {
+    let mut cos_table = [[0f32; 8]; 8];
+    for x in 0..8 {
+        for u in 0..8 {
+            let cu = if u == 0 {
+                std::f32::consts::FRAC_1_SQRT_2
+            } else {
+                1.0
+            };
+            cos_table[x][u] =
+                cu * (((2 * x + 1) as f32 * u as f32 * std::f32::consts::PI) / 16.0).cos();
+        }
+    }
+
+    let mut tmp = [0f32; 64];
+    for y in 0..8 {
+        for x in 0..8 {
+            let mut sum = 0.0;
+            for u in 0..8 {
+                sum += cos_table[x][u] * input[y * 8 + u] as f32;
+            }
+            tmp[y * 8 + x] = sum / 2.0;
+        }
+    }
+    for x in 0..8 {
+        for y in 0..8 {
+            let mut sum = 0.0;
+            for v in 0..8 {
+                sum += cos_table[y][v] * tmp[v * 8 + x];
+            }
+            output[y * 8 + x] = (sum / 2.0 + 128.0).round().max(0.0).min(255.0) as u8;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pixel(image: &Image, x: usize, y: usize) -> [u8; 4] {
+        let i = (y * image.width as usize + x) * 4;
+        [
+            image.bytes[i],
+            image.bytes[i + 1],
+            image.bytes[i + 2],
+            image.bytes[i + 3],
+        ]
+    }
+
+    fn assert_close(actual: [u8; 4], expected: [u8; 4]) {
+        for (a, e) in actual.iter().zip(expected.iter()) {
+            assert!(
+                (*a as i32 - *e as i32).abs() <= 4,
+                "{:?} != {:?}",
+                actual,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn color_4_2_0() {
+        let image = decode(include_bytes!("../testdata/2x2.jpg")).unwrap();
+        assert_eq!((image.width, image.height), (2, 2));
+        assert_eq!(image.bytes.len(), 2 * 2 * 4);
+        // the chroma of the 2x2 pixels is averaged by the subsampling
+        let expected = [
+            [200, 40, 40, 255],
+            [210, 50, 50, 255],
+            [190, 30, 30, 255],
+            [220, 60, 60, 255],
+        ];
+        for (i, expected) in expected.iter().enumerate() {
+            assert_close(pixel(&image, i % 2, i / 2), *expected);
+        }
+    }
+
+    #[test]
+    fn grayscale_with_restarts() {
+        let image = decode(include_bytes!("../testdata/9x3_gray_restart.jpg")).unwrap();
+        assert_eq!((image.width, image.height), (9, 3));
+        for y in 0..3 {
+            for x in 0..9 {
+                let l = x as u8 * 28;
+                assert_close(pixel(&image, x, y), [l, l, l, 255]);
+            }
+        }
+    }
+
+    #[test]
+    fn not_jpeg() {
+        assert!(matches!(decode(b"GIF89a"), Err(Error::NotJpeg)));
+        assert!(matches!(decode(&[]), Err(Error::NotJpeg)));
+    }
+
+    #[test]
+    fn truncated() {
+        let data = include_bytes!("../testdata/2x2.jpg");
+        for len in 2..data.len() - 2 {
+            // the entropy coded data reads as zeros past the end, that is not an error
+            let _ = decode(&data[..len]);
+        }
+        assert!(decode(&data[..200]).is_err());
+    }
+
+    /// Huffman tables with symbols out of range for their class
+    #[test]
+    fn oversized_symbols() {
+        let data = include_bytes!("../testdata/2x2.jpg");
+        let dht = data.windows(2).position(|w| w == [0xff, 0xc4]).unwrap();
+        // first table is the luma dc one, make every category of it 31 bits
+        let mut corrupted = data.to_vec();
+        for b in &mut corrupted[dht + 5 + 16..dht + 5 + 16 + 12] {
+            *b = 31;
+        }
+        assert!(matches!(decode(&corrupted), Err(Error::Corrupted)));
+
+        // every luma ac symbol 0xff: run 15, size 15
+        let mut corrupted = data.to_vec();
+        let ac = dht + 5 + 16 + 12 + 2 + 2 + 1 + 16;
+        for b in &mut corrupted[ac..ac + 162] {
+            *b = 0xff;
+        }
+        assert!(matches!(decode(&corrupted), Err(Error::Corrupted)));
+    }
+
+    /// Every single byte change of a valid file fails or decodes, without panics
+    #[test]
+    fn mutations() {
+        let data = include_bytes!("../testdata/2x2.jpg");
+        for i in 0..data.len() {
+            for &value in &[0x00, 0x01, 0x0f, 0x7f, 0x80, 0xfe, 0xff] {
+                let mut mutated = data.to_vec();
+                mutated[i] = value;
+                let _ = decode(&mutated);
+            }
+        }
+    }
+}
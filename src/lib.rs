@@ -9,14 +9,87 @@ extern crate sapp_windows as sapp;
 #[cfg(not(any(target_os="linux", target_arch="wasm32", windows)))]
 extern crate sapp_dummy as sapp;
 
+mod atlas;
+mod batcher;
+#[cfg(feature = "basis")]
+pub mod basis;
+mod buffer_arena;
+#[cfg(feature = "camera")]
+pub mod camera;
+pub mod color;
+mod clip_space;
 pub mod conf;
+pub mod container;
+mod debug_overlay;
+mod debug_view;
+mod egl_image;
+mod equirect;
+pub mod fs;
+pub mod gl;
 mod event;
+pub mod exec;
 pub mod graphics;
+#[cfg(feature = "gltf")]
+pub mod gltf;
+#[cfg(feature = "jpeg")]
+pub mod jpeg;
+mod mesh;
+pub mod net;
+#[cfg(feature = "png")]
+pub mod png;
+pub mod profiler;
+mod post_process;
+mod query;
+mod recorder;
+mod power;
+mod pixel_canvas;
+mod render_queue;
+mod render_scale;
+mod shader_sandbox;
+mod shadow_map;
+pub mod skinning;
+mod sprite_batch;
+#[cfg(any(feature = "png", feature = "jpeg"))]
+mod texture_loader;
+mod gesture;
+mod timer;
+mod timing;
+mod uniform_ring;
+mod upload;
+mod xr;
+pub mod storage;
+#[cfg(feature = "text")]
+pub mod text;
 
 pub use event::*;
 
 pub use graphics::*;
 
+pub use atlas::{Atlas, AtlasRect};
+pub use batcher::{BatchKey, Batcher};
+pub use buffer_arena::BufferArena;
+pub use clip_space::ClipSpace;
+pub use color::Color;
+pub use debug_overlay::{GpuMemoryEstimate, RenderCounters};
+pub use egl_image::EglImageTexture;
+pub use equirect::EquirectToCubemap;
+pub use mesh::Mesh;
+pub use post_process::PostProcess;
+pub use query::{Query, QueryKind};
+pub use recorder::{FrameFormat, RecordedFrame};
+pub use power::BatteryStatus;
+pub use render_queue::RenderQueue;
+pub use render_scale::RenderScale;
+pub use shader_sandbox::{DiagnosticSeverity, ShaderDiagnostic, ShaderSandbox, ShaderStage};
+pub use shadow_map::ShadowMap;
+pub use sprite_batch::SpriteBatch;
+#[cfg(any(feature = "png", feature = "jpeg"))]
+pub use texture_loader::{TextureLoadId, TextureLoader};
+pub use timer::TimerId;
+pub use timing::{FrameStats, FrameTiming, PresentStats, STATS_WINDOW};
+pub use uniform_ring::{TransientUniforms, TRANSIENT_UNIFORMS_BLOCK};
+pub use xr::GlContextHandles;
+
 use std::ffi::CString;
 
 pub use sapp::{rand, RAND_MAX};
@@ -36,6 +109,322 @@ pub mod date {
     pub fn now() -> f64 {
         unsafe { sapp::time() as f64 }
     }
+
+    /// Seconds of a monotonic clock with an unspecified origin, the clock of
+    /// `Context::event_timestamp`. Unlike `now`, never jumps when the system time is changed.
+    pub fn monotonic() -> f64 {
+        unsafe { sapp::sapp_timestamp() }
+    }
+}
+
+impl Context {
+    /// Set the opacity of the whole window, from 0.0 (invisible) to 1.0 (opaque).
+    /// On wasm this is the opacity of the canvas element.
+    pub fn set_window_opacity(&self, opacity: f32) {
+        unsafe {
+            sapp::sapp_set_window_opacity(opacity);
+        }
+    }
+
+    /// When enabled, mouse input is not captured by the window and goes to
+    /// whatever is below it. Useful for overlays drawn on top of other applications.
+    pub fn set_input_passthrough(&self, passthrough: bool) {
+        unsafe {
+            sapp::sapp_set_input_passthrough(passthrough);
+        }
+    }
+
+    /// Keep the window's content out of screenshots, recordings and screen sharing, for
+    /// privacy-sensitive or protected content. False when the platform can't: only
+    /// Windows can, where captures leave the window out from Windows 10 2004 on and
+    /// show it black before. X11 and browsers have no such control.
+    pub fn set_capture_protection(&self, protect: bool) -> bool {
+        unsafe { sapp::sapp_set_capture_protection(protect) }
+    }
+
+    /// Keep the display on and the system awake, without input for hours, and the
+    /// screensaver off. False when the platform can't: Windows asks for it with
+    /// `SetThreadExecutionState`, X11 restarts the screensaver timer every 30 seconds
+    /// and browsers need the Screen Wake Lock API.
+    pub fn set_keep_awake(&self, awake: bool) -> bool {
+        unsafe { sapp::sapp_keep_awake(awake) }
+    }
+
+    /// Keep system shortcuts leaving the app, like Alt+Tab or the Windows key, from
+    /// working while the window is in front. False when the platform can't.
+    /// Windows swallows the Windows and menu keys, Alt+Tab, Alt+F4, Alt+Esc and Ctrl+Esc,
+    /// the app doesn't get them either, and nothing keeps Ctrl+Alt+Del from working.
+    /// X11 sends all keys to the window instead of the window manager. Browsers need
+    /// the Keyboard Lock API and fullscreen.
+    pub fn set_shortcut_capture(&self, capture: bool) -> bool {
+        unsafe { sapp::sapp_capture_shortcuts(capture) }
+    }
+
+    /// Ask the window manager to draw the user's attention to the window,
+    /// usually by flashing or highlighting its taskbar entry until it gets focus.
+    /// Does nothing on wasm.
+    pub fn request_user_attention(&self) {
+        unsafe {
+            sapp::sapp_request_user_attention();
+        }
+    }
+
+    /// Show a progress bar, from 0.0 to 1.0, on the window's taskbar button.
+    /// `None` removes it. Only implemented on Windows.
+    pub fn set_taskbar_progress(&self, progress: Option<f32>) {
+        unsafe {
+            sapp::sapp_set_taskbar_progress(progress.unwrap_or(-1.0));
+        }
+    }
+
+    /// Vibrate the device, 0 stops an ongoing vibration.
+    /// Only on mobile web for now, does nothing on desktop.
+    pub fn vibrate(&self, duration_ms: u32) {
+        unsafe {
+            sapp::sapp_vibrate(duration_ms as _);
+        }
+    }
+
+    /// User's preferred language and region as a BCP 47 tag, like "en-US" or "pt-BR".
+    pub fn system_locale(&self) -> String {
+        unsafe { std::ffi::CStr::from_ptr(sapp::sapp_system_locale()) }
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    /// True if the OS (or browser) is set to a dark color scheme.
+    pub fn prefers_dark_theme(&self) -> bool {
+        unsafe { sapp::sapp_prefers_dark_theme() }
+    }
+
+    /// The user's accent color, as picked in the OS personalization settings.
+    /// `None` where there is no such setting, like on linux.
+    pub fn accent_color(&self) -> Option<Color> {
+        let (mut r, mut g, mut b) = (0., 0., 0.);
+        if unsafe { sapp::sapp_accent_color(&mut r, &mut g, &mut b) } {
+            Some(Color::new(r, g, b, 1.))
+        } else {
+            None
+        }
+    }
+
+    /// True when the OS, or browser, asks for high contrast, e.g. with a windows
+    /// high contrast theme. UI should then use stronger colors and outlines.
+    pub fn high_contrast(&self) -> bool {
+        unsafe { sapp::sapp_high_contrast() }
+    }
+
+    /// Allow only landscape or portrait orientation, or `Orientation::Any` to unlock.
+    /// On web this works only in fullscreen, on desktop only on windows tablets.
+    pub fn set_orientation_lock(&self, orientation: conf::Orientation) {
+        let orientation = match orientation {
+            conf::Orientation::Any => 0,
+            conf::Orientation::Landscape => 1,
+            conf::Orientation::Portrait => 2,
+        };
+        unsafe {
+            sapp::sapp_set_orientation_lock(orientation);
+        }
+    }
+
+    /// Keep the mouse cursor inside the window, or a part of it, e.g. for edge
+    /// scrolling in windowed mode. Unlike a grab, the cursor stays visible and moves as usual.
+    /// The cursor is released while the window is not focused and confined again after.
+    /// Windows and X11 only.
+    pub fn confine_cursor(&self, confinement: conf::CursorConfinement) {
+        let (confinement, x, y, w, h) = match confinement {
+            conf::CursorConfinement::None => (0, 0., 0., 0., 0.),
+            conf::CursorConfinement::Window => (1, 0., 0., 0., 0.),
+            conf::CursorConfinement::Rect { x, y, w, h } => (2, x, y, w, h),
+        };
+        unsafe {
+            sapp::sapp_confine_cursor(confinement, x, y, w, h);
+        }
+    }
+
+    /// Framebuffer pixels per logical window pixel, above 1 on high dpi displays
+    /// with `Conf::high_dpi`. May change at runtime, see `EventHandler::dpi_changed_event`.
+    pub fn dpi_scale(&self) -> f32 {
+        unsafe { sapp::sapp_dpi_scale() }
+    }
+
+    /// Key that `key` stands for on the user's keyboard layout, for shortcuts like Ctrl+Z
+    /// that follow the letter rather than the position: `KeyCode::W` on AZERTY is `KeyCode::Z`.
+    /// Keys typing nothing, or a character that has no `KeyCode`, are returned as they are.
+    pub fn logical_key(&self, key: KeyCode) -> KeyCode {
+        self.key_character(key)
+            .and_then(KeyCode::from_character)
+            .unwrap_or(key)
+    }
+
+    /// Label of `key` on the user's keyboard layout, for keybinding menus:
+    /// `KeyCode::Z` is "Z" on QWERTY and "W" on AZERTY.
+    /// Keys not typing a character have English names, like "Left Shift".
+    pub fn key_name(&self, key: KeyCode) -> String {
+        match self.key_character(key) {
+            Some(character) => character.to_uppercase().collect(),
+            None => key.us_name(),
+        }
+    }
+
+    fn key_character(&self, key: KeyCode) -> Option<char> {
+        let sapp_key = key.character_key_code()?;
+        let character = std::char::from_u32(unsafe { sapp::sapp_key_char(sapp_key) })?;
+        if character.is_control() || character.is_whitespace() {
+            return None;
+        }
+        Some(character)
+    }
+
+    /// Display modes of the monitor the window is on, for `set_exclusive_fullscreen`.
+    /// Only Windows switches display modes, the list is empty elsewhere.
+    pub fn display_modes(&self) -> Vec<conf::DisplayMode> {
+        let mut modes: Vec<conf::DisplayMode> = vec![];
+        for index in 0..unsafe { sapp::sapp_display_mode_count() } {
+            let (mut width, mut height, mut refresh_rate) = (0, 0, 0);
+            if unsafe { !sapp::sapp_display_mode(index, &mut width, &mut height, &mut refresh_rate) } {
+                break;
+            }
+            let mode = conf::DisplayMode {
+                width: width as u32,
+                height: height as u32,
+                refresh_rate: refresh_rate as u32,
+            };
+            // drivers list the same mode once per scaling and orientation setting
+            if !modes.contains(&mode) {
+                modes.push(mode);
+            }
+        }
+        modes
+    }
+
+    /// Switch the monitor to `mode` and cover it with the window, `None` goes back
+    /// to the desktop mode and the previous window. The desktop mode is also restored
+    /// while the app is alt-tabbed away and when it exits, crashes included.
+    /// Returns false if the mode could not be set, always on platforms without
+    /// display modes, see `display_modes`.
+    pub fn set_exclusive_fullscreen(&mut self, mode: Option<conf::DisplayMode>) -> bool {
+        let (width, height, refresh_rate) = mode.map_or((0, 0, 0), |mode| {
+            (mode.width as _, mode.height as _, mode.refresh_rate as _)
+        });
+        unsafe { sapp::sapp_set_exclusive_fullscreen(width, height, refresh_rate) }
+    }
+
+    /// Current orientation of the window, either `Landscape` or `Portrait`.
+    pub fn orientation(&self) -> conf::Orientation {
+        if unsafe { sapp::sapp_height() > sapp::sapp_width() } {
+            conf::Orientation::Portrait
+        } else {
+            conf::Orientation::Landscape
+        }
+    }
+
+    /// Whether `char_event` is delivered, like SDL's StartTextInput/StopTextInput.
+    /// Games may disable it during gameplay and enable it while a text field is focused.
+    /// Enabled by default, `key_down_event` and `key_up_event` are delivered either way.
+    pub fn set_text_input_enabled(&mut self, enabled: bool) {
+        self.text_input_enabled = enabled;
+    }
+
+    pub fn text_input_enabled(&self) -> bool {
+        self.text_input_enabled
+    }
+
+    /// Show or hide the on-screen keyboard. Showing it also enables text input,
+    /// the keyboard's text comes as `char_event`.
+    /// Only has an effect on web, where a hidden text field gets focused: browsers show
+    /// the keyboard only when asked from an input event, e.g. `mouse_button_down_event`.
+    pub fn show_keyboard(&mut self, shown: bool) {
+        if shown {
+            self.text_input_enabled = true;
+        }
+        unsafe {
+            sapp::sapp_show_keyboard(shown);
+        }
+    }
+
+    pub fn keyboard_shown(&self) -> bool {
+        unsafe { sapp::sapp_keyboard_shown() }
+    }
+
+    /// When the input event being handled happened, in seconds of `date::monotonic()`.
+    /// Taken from the OS event when it has a usable time, otherwise the time the event
+    /// was received at. `date::monotonic() - ctx.event_timestamp()` is the input latency so far.
+    pub fn event_timestamp(&self) -> f64 {
+        self.event_timestamp
+    }
+
+    /// Clicks in a row of the mouse button event being handled, and of the following
+    /// button up: 1 for a single click, 2 for a double click, 3 for a triple click...
+    /// Clicks are in a row when within the OS double click time and a few pixels
+    /// of the previous one.
+    pub fn click_count(&self) -> u32 {
+        self.gestures.click_count
+    }
+
+    /// Area of the window HUD elements should stay within.
+    /// On web this requires "viewport-fit=cover" in the page's viewport meta tag.
+    pub fn safe_area_insets(&self) -> SafeAreaInsets {
+        let mut insets = SafeAreaInsets::default();
+        unsafe {
+            sapp::sapp_safe_area_insets(
+                &mut insets.top as *mut _,
+                &mut insets.right as *mut _,
+                &mut insets.bottom as *mut _,
+                &mut insets.left as *mut _,
+            );
+        }
+        insets
+    }
+
+    /// Get the canvas content as a PNG data URL, from `canvas.toDataURL()`.
+    ///
+    /// The snapshot is taken once the current frame is fully drawn and before the browser
+    /// presents it, so `callback` runs at the end of this frame, or of the next one when
+    /// called outside of `draw`. The canvas can be read at any other time, e.g. by page
+    /// scripts or with `glReadPixels` from events, only with
+    /// `WebGlAttributes::preserve_drawing_buffer` enabled.
+    #[cfg(target_arch = "wasm32")]
+    pub fn web_canvas_snapshot<F>(&mut self, callback: F)
+    where
+        F: 'static + FnOnce(String),
+    {
+        self.canvas_snapshots.push(Box::new(callback));
+    }
+
+    /// Put the window content on the clipboard as an image, e.g. for a "copy screenshot"
+    /// button. Like `web_canvas_snapshot` the frame is captured once it is fully drawn,
+    /// and alpha is dropped so the image looks as it does on screen.
+    /// Implemented on Windows (CF_DIB) and on the web (PNG ClipboardItem, which browsers
+    /// only accept shortly after user input, e.g. from a key or mouse event).
+    pub fn copy_frame_to_clipboard(&mut self) {
+        self.copy_frame_requested = true;
+    }
+
+    fn copy_frame_if_requested(&mut self) {
+        if !self.copy_frame_requested {
+            return;
+        }
+        self.copy_frame_requested = false;
+        let (width, height, mut pixels) = self.read_default_framebuffer();
+        for pixel in pixels.chunks_mut(4) {
+            pixel[3] = 255;
+        }
+        if unsafe { !sapp::sapp_set_clipboard_image(width, height, pixels.as_ptr()) } {
+            eprintln!("Could not copy the frame to the clipboard");
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn take_canvas_snapshots(&mut self) {
+        if self.canvas_snapshots.is_empty() {
+            return;
+        }
+        let url = unsafe { sapp::sapp_canvas_snapshot() };
+        for callback in self.canvas_snapshots.drain(..) {
+            callback(url.clone());
+        }
+    }
 }
 
 struct UserData {
@@ -60,6 +449,11 @@ extern "C" fn init(user_data: *mut ::std::os::raw::c_void) {
         panic!();
     };
     let mut context = graphics::Context::new();
+    context.appearance = (
+        context.prefers_dark_theme(),
+        context.accent_color(),
+        context.high_contrast(),
+    );
 
     let user_data = UserData {
         event_handler: f(&mut context),
@@ -77,8 +471,77 @@ extern "C" fn frame(user_data: *mut ::std::os::raw::c_void) {
         panic!()
     };
 
-    data.event_handler.update(&mut data.context);
-    data.event_handler.draw(&mut data.context);
+    if !data.context.frame_limiter.begin_frame() {
+        return;
+    }
+    #[cfg(feature = "profiling")]
+    profiler::frame_mark();
+    if let Some(reason) = data.context.graphics_reset() {
+        match data.event_handler.device_lost_event(&mut data.context, reason) {
+            ResetStrategy::Recreate => {
+                unsafe { sapp::sapp_recreate_context() };
+                restore_context(data);
+            }
+            ResetStrategy::Quit => {
+                unsafe { sapp::sapp_quit() };
+                return;
+            }
+        }
+    }
+    let max_fps = data.context.frame_limiter.active_limit;
+    data.context.frame_pacer.begin_frame(max_fps);
+    if let Some(frame_time) = data.context.frame_pacer.over_budget() {
+        data.event_handler.frame_budget_exceeded_event(&mut data.context, frame_time);
+    }
+
+    data.context.begin_render_counters();
+    data.context.reset_transient_uniforms();
+    data.context.executor.poll();
+    data.context.run_uploads();
+    data.context.run_timers();
+    {
+        zone!("update");
+        data.event_handler.update(&mut data.context);
+    }
+    {
+        zone!("draw");
+        data.event_handler.draw(&mut data.context);
+    }
+
+    if data.context.late_latch {
+        // the event callback borrows the user data too, `data` is taken again afterwards
+        unsafe { sapp::sapp_poll_events() };
+    }
+    let data: &mut UserDataState = unsafe { &mut *(user_data as *mut UserDataState) };
+    let data = if let UserDataState::Intialized(ref mut data) = data {
+        data
+    } else {
+        panic!()
+    };
+    if data.context.late_latch {
+        zone!("late latch");
+        data.event_handler.late_latch_event(&mut data.context);
+    }
+    data.context.end_render_counters();
+
+    data.context.present_pixel_canvas();
+    data.context.record_frame();
+    data.context.present_debug_views();
+    data.context.present_debug_overlay();
+    data.context.copy_frame_if_requested();
+    #[cfg(target_arch = "wasm32")]
+    data.context.take_canvas_snapshots();
+    data.context.check_gl_errors("at the end of the frame");
+}
+
+/// Set miniquad up again on the new GL context, and tell the app what it has to create again
+fn restore_context(data: &mut UserData) {
+    let mut lost = data.context.restore();
+    data.context.restore_pixel_canvas(&mut lost);
+    data.context.restore_debug_view(&mut lost);
+    data.context.restore_debug_overlay(&mut lost);
+    data.event_handler
+        .resources_lost_event(&mut data.context, &lost);
 }
 
 extern "C" fn event(event: *const sapp::sapp_event, user_data: *mut ::std::os::raw::c_void) {
@@ -90,9 +553,12 @@ extern "C" fn event(event: *const sapp::sapp_event, user_data: *mut ::std::os::r
     } else {
         panic!()
     };
+    data.context.event_timestamp = event.timestamp;
+    zone!("event");
 
     match event.type_ {
         sapp::sapp_event_type_SAPP_EVENTTYPE_MOUSE_MOVE => {
+            data.context.gestures.mouse_position = (event.mouse_x, event.mouse_y);
             data.event_handler.mouse_motion_event(
                 &mut data.context,
                 event.mouse_x,
@@ -102,6 +568,10 @@ extern "C" fn event(event: *const sapp::sapp_event, user_data: *mut ::std::os::r
             );
         }
         sapp::sapp_event_type_SAPP_EVENTTYPE_MOUSE_DOWN => {
+            let interval = unsafe { sapp::sapp_double_click_time() } as f64 / 1000.;
+            data.context
+                .gestures
+                .click(event.timestamp, event.mouse_x, event.mouse_y, interval);
             data.event_handler.mouse_button_down_event(
                 &mut data.context,
                 MouseButton::Left,
@@ -117,7 +587,36 @@ extern "C" fn event(event: *const sapp::sapp_event, user_data: *mut ::std::os::r
                 event.mouse_y,
             );
         }
-        sapp::sapp_event_type_SAPP_EVENTTYPE_CHAR => {
+        sapp::sapp_event_type_SAPP_EVENTTYPE_MOUSE_SCROLL => {
+            let ctrl = KeyMods::from(event.modifiers).ctrl;
+            if !ctrl || !data.context.gestures.ctrl_wheel_only_magnifies {
+                data.event_handler
+                    .mouse_wheel_event(&mut data.context, event.scroll_x, event.scroll_y);
+            }
+            if ctrl {
+                let (x, y) = data.context.gestures.mouse_position;
+                let magnification = gesture::wheel_magnification(event.scroll_y);
+                data.event_handler
+                    .magnify_event(&mut data.context, magnification, x, y);
+            }
+        }
+        sapp::sapp_event_type_SAPP_EVENTTYPE_TOUCHES_BEGAN
+        | sapp::sapp_event_type_SAPP_EVENTTYPE_TOUCHES_MOVED
+        | sapp::sapp_event_type_SAPP_EVENTTYPE_TOUCHES_ENDED
+        | sapp::sapp_event_type_SAPP_EVENTTYPE_TOUCHES_CANCELLED => {
+            let touches = &event.touches[..event.num_touches as usize];
+            if let Some(pinch) = data.context.gestures.touch(event.type_, touches) {
+                data.event_handler.magnify_event(
+                    &mut data.context,
+                    pinch.magnification,
+                    pinch.x,
+                    pinch.y,
+                );
+                data.event_handler
+                    .rotate_event(&mut data.context, pinch.rotation, pinch.x, pinch.y);
+            }
+        }
+        sapp::sapp_event_type_SAPP_EVENTTYPE_CHAR if data.context.text_input_enabled => {
             if let Some(character) = std::char::from_u32(event.char_code) {
                 let mut key_mods = KeyMods::from(event.modifiers);
 
@@ -143,32 +642,142 @@ extern "C" fn event(event: *const sapp::sapp_event, user_data: *mut ::std::os::r
             data.event_handler
                 .key_up_event(&mut data.context, keycode, key_mods)
         }
+        sapp::sapp_event_type_SAPP_EVENTTYPE_THEME_CHANGED => {
+            // sent for any appearance setting, tell only about the ones that changed
+            let (old_dark, old_accent_color, old_high_contrast) = data.context.appearance;
+            let dark = data.context.prefers_dark_theme();
+            let accent_color = data.context.accent_color();
+            let high_contrast = data.context.high_contrast();
+            data.context.appearance = (dark, accent_color, high_contrast);
+            if dark != old_dark {
+                data.event_handler.theme_changed_event(&mut data.context, dark);
+            }
+            if accent_color != old_accent_color {
+                data.event_handler
+                    .accent_color_changed_event(&mut data.context, accent_color);
+            }
+            if high_contrast != old_high_contrast {
+                data.event_handler
+                    .high_contrast_changed_event(&mut data.context, high_contrast);
+            }
+        }
+        sapp::sapp_event_type_SAPP_EVENTTYPE_SAFE_AREA_CHANGED => {
+            let insets = data.context.safe_area_insets();
+            data.event_handler
+                .safe_area_changed_event(&mut data.context, insets);
+        }
+        sapp::sapp_event_type_SAPP_EVENTTYPE_ORIENTATION_CHANGED => {
+            let orientation = data.context.orientation();
+            data.event_handler
+                .orientation_changed_event(&mut data.context, orientation);
+        }
+        sapp::sapp_event_type_SAPP_EVENTTYPE_ICONIFIED => {
+            data.event_handler.window_minimized_event(&mut data.context);
+        }
+        sapp::sapp_event_type_SAPP_EVENTTYPE_RESTORED => {
+            data.event_handler.window_restored_event(&mut data.context);
+        }
+        sapp::sapp_event_type_SAPP_EVENTTYPE_DPI_CHANGED => {
+            let scale = data.context.dpi_scale();
+            data.event_handler.dpi_changed_event(&mut data.context, scale);
+        }
+        sapp::sapp_event_type_SAPP_EVENTTYPE_EXPOSED => {
+            data.event_handler.window_exposed_event(&mut data.context);
+        }
+        sapp::sapp_event_type_SAPP_EVENTTYPE_CONTEXT_LOST => {
+            let reason = DeviceLostReason::Unknown;
+            if data.event_handler.device_lost_event(&mut data.context, reason)
+                == ResetStrategy::Quit
+            {
+                unsafe { sapp::sapp_quit() };
+            }
+        }
+        sapp::sapp_event_type_SAPP_EVENTTYPE_CONTEXT_RESTORED => {
+            restore_context(data);
+        }
         sapp::sapp_event_type_SAPP_EVENTTYPE_RESIZED => {
-            data.context
-                .resize(event.window_width as u32, event.window_height as u32);
+            // framebuffer pixels, same as window ones unless with high dpi
+            data.context.resize(
+                event.framebuffer_width as u32,
+                event.framebuffer_height as u32,
+            );
             data.event_handler.resize_event(
                 &mut data.context,
-                event.window_width as f32,
-                event.window_height as f32,
+                event.framebuffer_width as f32,
+                event.framebuffer_height as f32,
             );
         }
         _ => {}
     }
 }
 
-pub fn start<F>(_conf: conf::Conf, f: F)
+pub fn start<F>(mut conf: conf::Conf, f: F)
 where
     F: 'static + FnOnce(&mut Context) -> Box<dyn event::EventHandler>,
 {
+    if conf.runtime_overrides {
+        conf.apply_overrides();
+    }
     let mut desc: sapp::sapp_desc = unsafe { std::mem::zeroed() };
 
     let title = CString::new("").unwrap_or_else(|e| panic!(e));
 
-    let mut user_data = Box::new(UserDataState::Uninitialized(Box::new(f)));
+    let orientation = conf.orientation;
+    let render_target_origin = conf.render_target_origin;
+    let reversed_z = conf.reversed_z;
+    let pixel_art = conf.pixel_art;
+    let vsync = conf.vsync;
+    let debug_gl = conf.debug_gl;
+    let debug_overlay = conf.debug_overlay;
+    let capture_protection = conf.capture_protection;
+    let kiosk = conf.kiosk;
+    let ctrl_wheel_only_magnifies = conf.ctrl_wheel_only_magnifies;
+    let mut user_data = Box::new(UserDataState::Uninitialized(Box::new(
+        move |context: &mut Context| {
+            if !vsync {
+                unsafe { sapp::sapp_set_swap_interval(0) };
+            }
+            context.debug_gl = debug_gl;
+            context.set_debug_overlay(debug_overlay);
+            if capture_protection && !context.set_capture_protection(true) {
+                eprintln!("Conf::capture_protection is not supported on this platform");
+            }
+            if let Some(kiosk) = kiosk {
+                if !context.set_keep_awake(true) {
+                    eprintln!("Conf::kiosk can not keep the screen awake on this platform");
+                }
+                if kiosk.capture_shortcuts && !context.set_shortcut_capture(true) {
+                    eprintln!("Conf::kiosk can not capture shortcuts on this platform");
+                }
+            }
+            context.set_orientation_lock(orientation);
+            context.render_target_origin = render_target_origin;
+            context.gestures.ctrl_wheel_only_magnifies = ctrl_wheel_only_magnifies;
+            if reversed_z {
+                context.set_reversed_z(true);
+            }
+            context.pixel_canvas =
+                pixel_art.map(|conf| pixel_canvas::PixelCanvas::new(context, conf));
+            f(context)
+        },
+    )));
 
     desc.width = 800;
     desc.height = 600;
     desc.window_title = title.as_ptr();
+    desc.depth_bits = conf.depth_bits as _;
+    desc.stencil_bits = conf.stencil_bits as _;
+    desc.high_dpi = conf.high_dpi;
+    desc.robust_buffer_access = conf.robust_buffer_access;
+    #[cfg(target_arch = "wasm32")]
+    {
+        let attributes = conf.webgl_attributes;
+        desc.alpha = attributes.alpha;
+        desc.html5_premultiplied_alpha = attributes.premultiplied_alpha;
+        desc.html5_preserve_drawing_buffer = attributes.preserve_drawing_buffer;
+        desc.html5_antialias = attributes.antialias;
+        desc.html5_desynchronized = attributes.desynchronized;
+    }
     desc.user_data = &mut *user_data as *mut _ as *mut _;
     desc.init_userdata_cb = Some(init);
     desc.frame_userdata_cb = Some(frame);
@@ -0,0 +1,84 @@
+use crate::graphics::*;
+
+/// Vertex and index buffers with the layout of the vertices, ready to draw:
+///
+/// ```ignore
+/// let mesh = Mesh::new(ctx, &vertices, &indices, &[
+///     VertexAttribute::new("pos", VertexFormat::Float3),
+///     VertexAttribute::new("uv0", VertexFormat::Float2),
+/// ]);
+/// let pipeline = mesh.pipeline(ctx, shader, PipelineParams::default());
+/// // in draw
+/// ctx.apply_pipeline(&pipeline);
+/// mesh.draw(ctx, 1);
+/// ```
+///
+/// The vertices are the first vertex buffer of `bindings`. Instance buffers, with
+/// attributes of `buffer_index` 1 and up, and images are added to `bindings` directly.
+pub struct Mesh {
+    pub bindings: Bindings,
+    attributes: Vec<VertexAttribute>,
+    /// Range of indices drawn, as for `Context::draw`, all of them initially
+    pub base_element: i32,
+    pub num_elements: i32,
+}
+
+impl Mesh {
+    /// Mesh of immutable buffers, `attributes` describe the fields of `V` in order.
+    pub fn new<V, I>(
+        ctx: &mut Context,
+        vertices: &[V],
+        indices: &[I],
+        attributes: &[VertexAttribute],
+    ) -> Mesh {
+        let vertex_size: i32 = attributes
+            .iter()
+            .filter(|attribute| attribute.buffer_index == 0)
+            .map(|attribute| attribute.format.byte_len())
+            .sum();
+        debug_assert_eq!(
+            vertex_size as usize,
+            std::mem::size_of::<V>(),
+            "Vertex attributes and vertex type have different sizes"
+        );
+
+        Mesh {
+            bindings: Bindings {
+                vertex_buffers: vec![Buffer::immutable(ctx, BufferType::VertexBuffer, vertices)],
+                index_buffer: Buffer::immutable(ctx, BufferType::IndexBuffer, indices),
+                images: vec![],
+            },
+            attributes: attributes.to_vec(),
+            base_element: 0,
+            num_elements: indices.len() as i32,
+        }
+    }
+
+    pub fn attributes(&self) -> &[VertexAttribute] {
+        &self.attributes
+    }
+
+    /// Pipeline reading the vertices of this mesh, and of meshes with the same attributes.
+    /// Buffers other than the vertices are laid out per vertex, tightly packed.
+    pub fn pipeline(&self, ctx: &mut Context, shader: Shader, params: PipelineParams) -> Pipeline {
+        let buffers = self
+            .attributes
+            .iter()
+            .map(|attribute| attribute.buffer_index + 1)
+            .max()
+            .unwrap_or(1);
+        Pipeline::with_params(
+            ctx,
+            &vec![BufferLayout::default(); buffers],
+            &self.attributes,
+            shader,
+            params,
+        )
+    }
+
+    /// Draw the mesh `num_instances` times with the current pipeline.
+    pub fn draw(&self, ctx: &mut Context, num_instances: i32) {
+        ctx.apply_bindings(&self.bindings);
+        ctx.draw(self.base_element, self.num_elements, num_instances);
+    }
+}
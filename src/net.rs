@@ -0,0 +1,783 @@
+//! Minimal non-blocking WebSocket client and HTTP GET.
+//!
+//! On wasm this is a thin wrapper over the browser's WebSocket and fetch, on native platforms
+//! the same protocols are spoken over a plain `TcpStream` driven by background threads:
+//! sending only queues the message, it never waits for the network.
+//! Only `ws://` and `http://` urls are supported on native, there is no TLS.
+//!
+//! WebSocket messages are queued as they arrive. They are polled with `try_recv` from
+//! `EventHandler::update`, once per frame, or awaited with `recv` from a task of
+//! `Context::spawn`, which the event loop polls right before `update`:
+//!
+//! ```ignore
+//! ctx.spawn(async move {
+//!     let mut socket = WebSocket::connect("ws://localhost:8080/").unwrap();
+//!     while let Some(message) = socket.recv().await {
+//!         // ...
+//!     }
+//! });
+//! ```
+//!
+//! There is no `EventHandler` method for them: the socket belongs to the app, not to the
+//! `Context`, and a handler method would need the context to own every socket and tell
+//! them apart by some id.
+
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context as TaskContext, Poll},
+};
+
+#[derive(Debug)]
+pub enum Error {
+    InvalidUrl,
+    NotConnected,
+    HandshakeFailed,
+    /// Server responded with non-2xx status code
+    HttpStatus(u16),
+    IOError(std::io::Error),
+    /// A frame or message longer than `MAX_MESSAGE_SIZE`
+    MessageTooLarge,
+    /// A text message that is not utf-8, or frames out of order
+    Protocol,
+    /// The browser reports WebSocket errors without a reason
+    ConnectionFailed,
+}
+
+/// Largest WebSocket message received, the connection fails with `Error::MessageTooLarge`
+/// on longer ones instead of allocating whatever length the peer announces.
+pub const MAX_MESSAGE_SIZE: usize = 16 * 1024 * 1024;
+
+/// Where a `WebSocket` is in its lifetime, see `WebSocket::state`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum State {
+    Connecting,
+    Open,
+    /// Closed by either side. Messages received before are still returned.
+    Closed,
+    /// Could not connect, or the connection broke, see `WebSocket::take_error`.
+    /// Messages received before are still returned.
+    Failed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Message {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+/// Future of the next message of a `WebSocket`, see `WebSocket::recv`.
+pub struct Recv<'a> {
+    socket: &'a mut WebSocket,
+}
+
+impl<'a> Future for Recv<'a> {
+    type Output = Option<Message>;
+
+    fn poll(mut self: Pin<&mut Self>, _: &mut TaskContext) -> Poll<Option<Message>> {
+        if let Some(message) = self.socket.try_recv() {
+            return Poll::Ready(Some(message));
+        }
+        match self.socket.state() {
+            State::Connecting | State::Open => Poll::Pending,
+            State::Closed | State::Failed => Poll::Ready(None),
+        }
+    }
+}
+
+impl WebSocket {
+    /// Next message, as a future for tasks run with `Context::spawn`.
+    /// `None` once the socket is closed or failed and every message was received.
+    pub fn recv(&mut self) -> Recv {
+        Recv { socket: self }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Error {
+        Error::IOError(error)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use native::{http_get, WebSocket};
+
+#[cfg(target_arch = "wasm32")]
+pub use wasm::{http_get, WebSocket};
+
+/// `http_get` as a future, for tasks run with `Context::spawn`.
+pub fn http_get_async(url: &str) -> crate::exec::CallbackFuture<Result<Vec<u8>, Error>> {
+    crate::exec::callback(|done| http_get(url, move |response| done.resolve(response)))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod native {
+    use super::{Error, Message, State, MAX_MESSAGE_SIZE};
+
+    use std::io::{Read, Write};
+    use std::net::{Shutdown, TcpStream};
+    use std::sync::mpsc::{self, Receiver, Sender};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    const OPCODE_CONTINUATION: u8 = 0x0;
+    const OPCODE_TEXT: u8 = 0x1;
+    const OPCODE_BINARY: u8 = 0x2;
+    const OPCODE_CLOSE: u8 = 0x8;
+    const OPCODE_PING: u8 = 0x9;
+    const OPCODE_PONG: u8 = 0xa;
+
+    /// A peer not reading for that long fails the connection, instead of keeping the
+    /// writer thread and the queued messages forever
+    const WRITE_TIMEOUT: Duration = Duration::from_secs(10);
+
+    /// Opcode and payload, for the writer thread
+    type Frame = (u8, Vec<u8>);
+
+    pub struct WebSocket {
+        shared: Arc<Mutex<Shared>>,
+        rx: Receiver<Message>,
+    }
+
+    /// What the reader and writer threads tell the socket
+    struct Shared {
+        /// Frames to send, while open
+        writer: Option<Sender<Frame>>,
+        /// The connection, to shut it down, never read or written under the lock
+        stream: Option<TcpStream>,
+        state: State,
+        error: Option<Error>,
+    }
+
+    struct Url {
+        host: String,
+        port: u16,
+        path: String,
+    }
+
+    fn parse_url(url: &str, scheme: &str) -> Result<Url, Error> {
+        // no TLS on native, so "wss://" and "https://" are rejected here as well
+        if !url.starts_with(scheme) {
+            return Err(Error::InvalidUrl);
+        }
+        let rest = &url[scheme.len()..];
+        let (authority, path) = match rest.find('/') {
+            Some(n) => (&rest[..n], &rest[n..]),
+            None => (rest, "/"),
+        };
+        let (host, port) = match authority.rfind(':') {
+            Some(n) => (
+                &authority[..n],
+                authority[n + 1..].parse().map_err(|_| Error::InvalidUrl)?,
+            ),
+            None => (authority, 80),
+        };
+        if host.is_empty() {
+            return Err(Error::InvalidUrl);
+        }
+
+        Ok(Url {
+            host: host.to_string(),
+            port,
+            path: path.to_string(),
+        })
+    }
+
+    fn base64(data: &[u8]) -> String {
+        const TABLE: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+        let mut res = String::new();
+        for chunk in data.chunks(3) {
+            let b = [
+                chunk[0],
+                *chunk.get(1).unwrap_or(&0),
+                *chunk.get(2).unwrap_or(&0),
+            ];
+            let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+            for i in 0..4 {
+                if i <= chunk.len() {
+                    res.push(TABLE[(n >> (18 - i * 6)) as usize & 63] as char);
+                } else {
+                    res.push('=');
+                }
+            }
+        }
+        res
+    }
+
+    fn random_bytes(buf: &mut [u8]) {
+        for b in buf {
+            *b = unsafe { crate::rand() } as u8;
+        }
+    }
+
+    fn handshake(url: &Url) -> Result<TcpStream, Error> {
+        let mut stream = TcpStream::connect((url.host.as_str(), url.port))?;
+
+        let mut key = [0; 16];
+        random_bytes(&mut key);
+        let request = format!(
+            "GET {} HTTP/1.1\r\nHost: {}:{}\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Key: {}\r\nSec-WebSocket-Version: 13\r\n\r\n",
+            url.path,
+            url.host,
+            url.port,
+            base64(&key)
+        );
+        stream.write_all(request.as_bytes())?;
+
+        // read byte by byte to not consume the beginning of the first frame
+        let mut response = vec![];
+        let mut byte = [0];
+        while !response.ends_with(b"\r\n\r\n") {
+            if stream.read(&mut byte)? == 0 {
+                return Err(Error::HandshakeFailed);
+            }
+            response.push(byte[0]);
+        }
+        if !response.starts_with(b"HTTP/1.1 101") {
+            return Err(Error::HandshakeFailed);
+        }
+
+        Ok(stream)
+    }
+
+    fn write_frame(stream: &mut TcpStream, opcode: u8, data: &[u8]) -> std::io::Result<()> {
+        let mut frame = Vec::with_capacity(data.len() + 14);
+        frame.push(0x80 | opcode);
+        // client to server frames are always masked
+        if data.len() < 126 {
+            frame.push(0x80 | data.len() as u8);
+        } else if data.len() <= 0xffff {
+            frame.push(0x80 | 126);
+            frame.extend_from_slice(&(data.len() as u16).to_be_bytes());
+        } else {
+            frame.push(0x80 | 127);
+            frame.extend_from_slice(&(data.len() as u64).to_be_bytes());
+        }
+        let mut mask = [0; 4];
+        random_bytes(&mut mask);
+        frame.extend_from_slice(&mask);
+        frame.extend(data.iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+
+        stream.write_all(&frame)
+    }
+
+    fn read_frame(stream: &mut TcpStream) -> Result<(bool, u8, Vec<u8>), Error> {
+        let mut header = [0; 2];
+        stream.read_exact(&mut header)?;
+        let fin = header[0] & 0x80 != 0;
+        let opcode = header[0] & 0x0f;
+        let masked = header[1] & 0x80 != 0;
+        let len = match header[1] & 0x7f {
+            126 => {
+                let mut len = [0; 2];
+                stream.read_exact(&mut len)?;
+                u16::from_be_bytes(len) as usize
+            }
+            127 => {
+                let mut len = [0; 8];
+                stream.read_exact(&mut len)?;
+                u64::from_be_bytes(len) as usize
+            }
+            len => len as usize,
+        };
+        if len > MAX_MESSAGE_SIZE {
+            return Err(Error::MessageTooLarge);
+        }
+        let mut mask = [0; 4];
+        if masked {
+            stream.read_exact(&mut mask)?;
+        }
+        let mut data = vec![0; len];
+        stream.read_exact(&mut data)?;
+        if masked {
+            for (i, b) in data.iter_mut().enumerate() {
+                *b ^= mask[i % 4];
+            }
+        }
+        Ok((fin, opcode, data))
+    }
+
+    fn reader_thread(url: Url, shared: Arc<Mutex<Shared>>, tx: Sender<Message>) {
+        let result = handshake(&url)
+            .and_then(|s| Ok((s.try_clone()?, s.try_clone()?, s)))
+            .and_then(|(mut reader, writer, stream)| {
+                writer.set_write_timeout(Some(WRITE_TIMEOUT))?;
+                let (frames, frame_receiver) = mpsc::channel();
+                {
+                    let mut shared = shared.lock().unwrap();
+                    // dropped while connecting
+                    if shared.state == State::Closed {
+                        return Ok(());
+                    }
+                    shared.writer = Some(frames.clone());
+                    shared.stream = Some(stream);
+                    shared.state = State::Open;
+                }
+                std::thread::spawn({
+                    let shared = shared.clone();
+                    move || writer_thread(writer, frame_receiver, shared)
+                });
+                read_messages(&mut reader, &frames, &tx)
+            });
+
+        let mut shared = shared.lock().unwrap();
+        shared.writer = None;
+        if let Some(stream) = shared.stream.take() {
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+        match result {
+            Ok(()) => shared.state = State::Closed,
+            Err(error) => {
+                shared.state = State::Failed;
+                // the writer's error, if it failed first
+                shared.error.get_or_insert(error);
+            }
+        }
+    }
+
+    /// Send the queued frames until a close frame, or until every sender is gone
+    fn writer_thread(mut writer: TcpStream, frames: Receiver<Frame>, shared: Arc<Mutex<Shared>>) {
+        for (opcode, data) in frames {
+            if let Err(error) = write_frame(&mut writer, opcode, &data) {
+                shared.lock().unwrap().error.get_or_insert(error.into());
+                break;
+            }
+            if opcode == OPCODE_CLOSE {
+                break;
+            }
+        }
+        // ends the reader thread as well
+        let _ = writer.shutdown(Shutdown::Both);
+    }
+
+    /// Until the server closes the connection, or the socket is dropped
+    fn read_messages(
+        reader: &mut TcpStream,
+        frames: &Sender<Frame>,
+        tx: &Sender<Message>,
+    ) -> Result<(), Error> {
+        // opcode of the first frame, while in the middle of a message
+        let mut message_opcode = None;
+        let mut message = vec![];
+        loop {
+            let (fin, opcode, data) = read_frame(reader)?;
+            match opcode {
+                OPCODE_TEXT | OPCODE_BINARY | OPCODE_CONTINUATION => {
+                    match (opcode, message_opcode) {
+                        (OPCODE_CONTINUATION, Some(_)) => {}
+                        (OPCODE_TEXT, None) | (OPCODE_BINARY, None) => {
+                            message_opcode = Some(opcode)
+                        }
+                        _ => return Err(Error::Protocol),
+                    }
+                    if message.len() + data.len() > MAX_MESSAGE_SIZE {
+                        return Err(Error::MessageTooLarge);
+                    }
+                    message.extend_from_slice(&data);
+                    if !fin {
+                        continue;
+                    }
+                    let data = std::mem::replace(&mut message, vec![]);
+                    let message = if message_opcode.take() == Some(OPCODE_TEXT) {
+                        Message::Text(String::from_utf8(data).map_err(|_| Error::Protocol)?)
+                    } else {
+                        Message::Binary(data)
+                    };
+                    if tx.send(message).is_err() {
+                        return Ok(());
+                    }
+                }
+                OPCODE_PING => {
+                    let _ = frames.send((OPCODE_PONG, data));
+                }
+                OPCODE_CLOSE => return Ok(()),
+                _ => {}
+            }
+        }
+    }
+
+    impl WebSocket {
+        /// Start connecting to `url`, "ws://host:port/path".
+        /// Returns immediately, `state` becomes `Open` once the handshake is done.
+        pub fn connect(url: &str) -> Result<WebSocket, Error> {
+            let url = parse_url(url, "ws://")?;
+            let shared = Arc::new(Mutex::new(Shared {
+                writer: None,
+                stream: None,
+                state: State::Connecting,
+                error: None,
+            }));
+            let (tx, rx) = mpsc::channel();
+
+            std::thread::spawn({
+                let shared = shared.clone();
+                move || reader_thread(url, shared, tx)
+            });
+
+            Ok(WebSocket { shared, rx })
+        }
+
+        pub fn state(&self) -> State {
+            self.shared.lock().unwrap().state
+        }
+
+        pub fn connected(&self) -> bool {
+            self.state() == State::Open
+        }
+
+        /// Why the socket is `State::Failed`, returned once
+        pub fn take_error(&mut self) -> Option<Error> {
+            self.shared.lock().unwrap().error.take()
+        }
+
+        pub fn send_text(&self, text: &str) -> Result<(), Error> {
+            self.send(OPCODE_TEXT, text.as_bytes())
+        }
+
+        pub fn send_bytes(&self, data: &[u8]) -> Result<(), Error> {
+            self.send(OPCODE_BINARY, data)
+        }
+
+        /// Queue the message for the writer thread
+        fn send(&self, opcode: u8, data: &[u8]) -> Result<(), Error> {
+            let writer = self.shared.lock().unwrap().writer.clone();
+            writer
+                .ok_or(Error::NotConnected)?
+                .send((opcode, data.to_vec()))
+                .map_err(|_| Error::NotConnected)
+        }
+
+        /// Next received message, if any
+        pub fn try_recv(&mut self) -> Option<Message> {
+            self.rx.try_recv().ok()
+        }
+    }
+
+    fn http_request(url: &Url) -> Result<Vec<u8>, Error> {
+        let mut stream = TcpStream::connect((url.host.as_str(), url.port))?;
+
+        // HTTP/1.0 to get a plain body terminated by connection close, without chunked encoding
+        let request = format!(
+            "GET {} HTTP/1.0\r\nHost: {}:{}\r\nConnection: close\r\n\r\n",
+            url.path, url.host, url.port
+        );
+        stream.write_all(request.as_bytes())?;
+
+        let mut response = vec![];
+        stream.read_to_end(&mut response)?;
+
+        let header_end = response
+            .windows(4)
+            .position(|w| w == b"\r\n\r\n")
+            .ok_or(Error::HandshakeFailed)?;
+        let status = std::str::from_utf8(&response[..header_end])
+            .ok()
+            .and_then(|headers| headers.split(' ').nth(1))
+            .and_then(|status| status.parse::<u16>().ok())
+            .ok_or(Error::HandshakeFailed)?;
+        if status < 200 || status >= 300 {
+            return Err(Error::HttpStatus(status));
+        }
+
+        Ok(response.split_off(header_end + 4))
+    }
+
+    /// Download `url`, "http://host:port/path", on a background thread.
+    /// `f` is called from that thread with the response body.
+    pub fn http_get<F>(url: &str, f: F)
+    where
+        F: FnOnce(Result<Vec<u8>, Error>) + Send + 'static,
+    {
+        match parse_url(url, "http://") {
+            Ok(url) => {
+                std::thread::spawn(move || f(http_request(&url)));
+            }
+            Err(err) => f(Err(err)),
+        }
+    }
+
+    impl Drop for WebSocket {
+        /// The writer thread sends the queued messages and a close frame, then shuts the
+        /// connection down. The reader stops right away.
+        fn drop(&mut self) {
+            let mut shared = self.shared.lock().unwrap();
+            shared.state = State::Closed;
+            if let Some(writer) = shared.writer.take() {
+                let _ = writer.send((OPCODE_CLOSE, vec![]));
+            }
+            if let Some(stream) = shared.stream.take() {
+                let _ = stream.shutdown(Shutdown::Read);
+            }
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) mod wasm {
+    use super::{Error, Message, State};
+
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+
+    type HttpCallback = Box<dyn FnOnce(Result<Vec<u8>, Error>)>;
+
+    thread_local! {
+        static HTTP_REQUESTS: RefCell<HashMap<i32, HttpCallback>> = RefCell::new(HashMap::new());
+    }
+
+    /// Download `url` with the browser's fetch.
+    /// `f` is called from the event loop once the response is ready.
+    pub fn http_get<F>(url: &str, f: F)
+    where
+        F: FnOnce(Result<Vec<u8>, Error>) + Send + 'static,
+    {
+        fetch(url, f);
+    }
+
+    /// Same as `http_get`, but the callback does not have to be `Send`,
+    /// since on wasm it is always called on the main thread.
+    pub(crate) fn fetch<F>(url: &str, f: F)
+    where
+        F: FnOnce(Result<Vec<u8>, Error>) + 'static,
+    {
+        let id = unsafe { sapp::http_get(url.as_ptr() as _, url.len() as u32) };
+        HTTP_REQUESTS.with(|requests| requests.borrow_mut().insert(id, Box::new(f)));
+    }
+
+    /// Called by gl.js when fetch is finished. Status 0 means a network error.
+    #[no_mangle]
+    pub extern "C" fn http_on_complete(id: i32, status: i32, len: u32) {
+        let f = match HTTP_REQUESTS.with(|requests| requests.borrow_mut().remove(&id)) {
+            Some(f) => f,
+            None => return,
+        };
+        if status < 200 || status >= 300 {
+            f(Err(Error::HttpStatus(status as u16)));
+            return;
+        }
+        let mut data = vec![0u8; len as usize];
+        unsafe { sapp::http_take_response(id, data.as_mut_ptr() as _) };
+        f(Ok(data));
+    }
+
+    pub struct WebSocket {
+        id: i32,
+        error_taken: bool,
+    }
+
+    impl WebSocket {
+        /// Start connecting to `url`, "ws://host:port/path" or "wss://...".
+        /// Returns immediately, `state` becomes `Open` once the socket is open.
+        pub fn connect(url: &str) -> Result<WebSocket, Error> {
+            let id = unsafe { sapp::ws_connect(url.as_ptr() as _, url.len() as u32) };
+            if id < 0 {
+                return Err(Error::InvalidUrl);
+            }
+            Ok(WebSocket {
+                id,
+                error_taken: false,
+            })
+        }
+
+        pub fn state(&self) -> State {
+            match unsafe { sapp::ws_state(self.id) } {
+                0 => State::Connecting,
+                1 => State::Open,
+                2 => State::Closed,
+                _ => State::Failed,
+            }
+        }
+
+        pub fn connected(&self) -> bool {
+            self.state() == State::Open
+        }
+
+        /// Why the socket is `State::Failed`, returned once.
+        /// Always `Error::ConnectionFailed`, browsers do not tell more.
+        pub fn take_error(&mut self) -> Option<Error> {
+            if self.state() != State::Failed || self.error_taken {
+                return None;
+            }
+            self.error_taken = true;
+            Some(Error::ConnectionFailed)
+        }
+
+        pub fn send_text(&self, text: &str) -> Result<(), Error> {
+            self.send(text.as_bytes(), true)
+        }
+
+        pub fn send_bytes(&self, data: &[u8]) -> Result<(), Error> {
+            self.send(data, false)
+        }
+
+        fn send(&self, data: &[u8], text: bool) -> Result<(), Error> {
+            if !self.connected() {
+                return Err(Error::NotConnected);
+            }
+            unsafe { sapp::ws_send(self.id, data.as_ptr() as _, data.len() as u32, text) };
+            Ok(())
+        }
+
+        /// Next received message, if any
+        pub fn try_recv(&mut self) -> Option<Message> {
+            let len = unsafe { sapp::ws_next_message_len(self.id) };
+            if len < 0 {
+                return None;
+            }
+            let text = unsafe { sapp::ws_next_message_is_text(self.id) };
+            let mut data = vec![0u8; len as usize];
+            unsafe { sapp::ws_take_message(self.id, data.as_mut_ptr() as _) };
+            // text comes out of TextEncoder, always utf-8
+            Some(if text {
+                Message::Text(String::from_utf8(data).unwrap())
+            } else {
+                Message::Binary(data)
+            })
+        }
+    }
+
+    impl Drop for WebSocket {
+        fn drop(&mut self) {
+            unsafe { sapp::ws_close(self.id) };
+        }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+
+    use std::io::{Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::time::{Duration, Instant};
+
+    /// Accept one client, and hand the connection to `f` after the handshake
+    fn serve_with<F: FnOnce(TcpStream) + Send + 'static>(f: F) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("ws://{}/", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = vec![];
+            let mut byte = [0];
+            while !request.ends_with(b"\r\n\r\n") {
+                stream.read_exact(&mut byte).unwrap();
+                request.push(byte[0]);
+            }
+            stream
+                .write_all(b"HTTP/1.1 101 Switching Protocols\r\n\r\n")
+                .unwrap();
+            f(stream)
+        });
+        url
+    }
+
+    /// Accept one client and send it `frames` after the handshake
+    fn serve(frames: &'static [u8]) -> String {
+        serve_with(move |mut stream| {
+            stream.write_all(frames).unwrap();
+            // keep the connection open until the client gives up on it
+            let _ = stream.read(&mut [0]);
+        })
+    }
+
+    fn wait_until_open(socket: &WebSocket) {
+        let start = Instant::now();
+        while socket.state() == State::Connecting {
+            assert!(start.elapsed() < Duration::from_secs(5), "still connecting");
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(socket.state(), State::Open);
+    }
+
+    fn wait_for_end(socket: &WebSocket) -> State {
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_secs(5) {
+            match socket.state() {
+                State::Connecting | State::Open => std::thread::sleep(Duration::from_millis(1)),
+                state => return state,
+            }
+        }
+        panic!("socket still open");
+    }
+
+    #[test]
+    fn messages_keep_their_type() {
+        let url = serve(&[
+            0x01, 2, b'h', b'e', // text, continued
+            0x80, 2, b'l', b'o', // continuation, final
+            0x82, 1, 7, // binary
+            0x88, 0, // close
+        ]);
+        let mut socket = WebSocket::connect(&url).unwrap();
+        assert_eq!(wait_for_end(&socket), State::Closed);
+        assert_eq!(socket.try_recv(), Some(Message::Text("helo".to_string())));
+        assert_eq!(socket.try_recv(), Some(Message::Binary(vec![7])));
+        assert_eq!(socket.try_recv(), None);
+        assert!(socket.take_error().is_none());
+    }
+
+    #[test]
+    fn huge_frames_fail_the_connection() {
+        let url = serve(&[0x82, 127, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]);
+        let mut socket = WebSocket::connect(&url).unwrap();
+        assert_eq!(wait_for_end(&socket), State::Failed);
+        assert!(matches!(socket.take_error(), Some(Error::MessageTooLarge)));
+        assert!(socket.take_error().is_none());
+    }
+
+    #[test]
+    fn invalid_text_fails_the_connection() {
+        let url = serve(&[0x81, 2, 0xc3, 0x28]);
+        let mut socket = WebSocket::connect(&url).unwrap();
+        assert_eq!(wait_for_end(&socket), State::Failed);
+        assert!(matches!(socket.take_error(), Some(Error::Protocol)));
+    }
+
+    #[test]
+    fn refused_connections_fail() {
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let mut socket = WebSocket::connect(&format!("ws://127.0.0.1:{}/", port)).unwrap();
+        assert_eq!(wait_for_end(&socket), State::Failed);
+        assert!(matches!(socket.take_error(), Some(Error::IOError(_))));
+    }
+
+    /// Sending queues the message even when the peer stopped reading and the TCP
+    /// buffers are full
+    #[test]
+    fn send_does_not_wait_for_the_peer() {
+        let (done, finished) = std::sync::mpsc::channel::<()>();
+        let url = serve_with(move |_stream| {
+            let _ = finished.recv();
+        });
+        let socket = WebSocket::connect(&url).unwrap();
+        wait_until_open(&socket);
+        let message = vec![0; 1024 * 1024];
+        let start = Instant::now();
+        for _ in 0..64 {
+            socket.send_bytes(&message).unwrap();
+        }
+        assert!(start.elapsed() < Duration::from_secs(2));
+        drop(done);
+    }
+
+    #[test]
+    fn pings_are_answered() {
+        let (pong, pong_receiver) = std::sync::mpsc::channel();
+        let url = serve_with(move |mut stream| {
+            stream.write_all(&[0x89, 2, 1, 2]).unwrap();
+            let mut frame = [0; 8];
+            stream.read_exact(&mut frame).unwrap();
+            let mask = [frame[2], frame[3], frame[4], frame[5]];
+            pong.send((frame[0], frame[1], [frame[6] ^ mask[0], frame[7] ^ mask[1]]))
+                .unwrap();
+        });
+        let _socket = WebSocket::connect(&url).unwrap();
+        let pong = pong_receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(pong, (0x8a, 0x82, [1, 2]));
+    }
+}
@@ -0,0 +1,160 @@
+use crate::{
+    conf::{PixelArt, PixelScaling},
+    graphics::*,
+    Color, PostProcess,
+};
+
+/// Fixed resolution render target standing in for the default framebuffer when
+/// `Conf::pixel_art` is set: `begin_default_pass` draws into it, and at the end of
+/// the frame it is scaled up to the window, with bars around.
+///
+/// Scales are computed in framebuffer pixels, so integer scaling stays crisp
+/// with `Conf::high_dpi` too.
+pub(crate) struct PixelCanvas {
+    conf: PixelArt,
+    color_img: Texture,
+    depth_img: Texture,
+    pass: RenderPass,
+    blit: PostProcess,
+}
+
+impl PixelCanvas {
+    pub fn new(ctx: &mut Context, conf: PixelArt) -> PixelCanvas {
+        assert!(conf.width > 0 && conf.height > 0);
+
+        let color_img = Texture::new_render_texture(RenderTextureParams {
+            width: conf.width,
+            height: conf.height,
+            format: PixelFormat::RGBA8,
+            ..Default::default()
+        });
+        color_img.set_filter(FilterMode::Nearest as i32);
+        let depth_img = Texture::new_render_texture(RenderTextureParams {
+            width: conf.width,
+            height: conf.height,
+            format: PixelFormat::Depth,
+            ..Default::default()
+        });
+        let pass = RenderPass::new(ctx, color_img, depth_img);
+
+        PixelCanvas {
+            conf,
+            color_img,
+            depth_img,
+            pass,
+            blit: PostProcess::new(ctx, FRAGMENT, META),
+        }
+    }
+
+    pub fn pass(&self) -> RenderPass {
+        self.pass
+    }
+
+    /// Where the canvas lands on a `screen_width`x`screen_height` screen:
+    /// x, y from the top left corner, width and height, in framebuffer pixels.
+    pub fn screen_rect(&self, screen_width: f32, screen_height: f32) -> (i32, i32, i32, i32) {
+        let (width, height) = (self.conf.width as f32, self.conf.height as f32);
+        let fit = (screen_width / width).min(screen_height / height);
+        let (scale_x, scale_y) = match self.conf.scaling {
+            // a window smaller than the canvas still shows all of it, unevenly
+            PixelScaling::Integer if fit >= 1. => (fit.floor(), fit.floor()),
+            PixelScaling::IntegerPerAxis if fit >= 1. => (
+                (screen_width / width).floor(),
+                (screen_height / height).floor(),
+            ),
+            _ => (fit, fit),
+        };
+        let (w, h) = (
+            (width * scale_x).round() as i32,
+            (height * scale_y).round() as i32,
+        );
+        (
+            (screen_width as i32 - w) / 2,
+            (screen_height as i32 - h) / 2,
+            w,
+            h,
+        )
+    }
+}
+
+impl Context {
+    /// Size of what `begin_default_pass` draws to: `Conf::pixel_art`'s resolution
+    /// when set, otherwise the same as `screen_size`.
+    pub fn canvas_size(&self) -> (f32, f32) {
+        match &self.pixel_canvas {
+            Some(canvas) => (canvas.conf.width as f32, canvas.conf.height as f32),
+            None => self.screen_size(),
+        }
+    }
+
+    /// Position in the `Conf::pixel_art` canvas, from its top left corner and in canvas
+    /// pixels, of a mouse or touch event position. Positions over the bars are outside
+    /// of `0..canvas_size`. Without `Conf::pixel_art` `x` and `y` are returned as they are.
+    pub fn screen_to_canvas(&self, x: f32, y: f32) -> (f32, f32) {
+        let canvas = match &self.pixel_canvas {
+            Some(canvas) => canvas,
+            None => return (x, y),
+        };
+        let (screen_width, screen_height) = self.screen_size();
+        let (left, top, w, h) = canvas.screen_rect(screen_width, screen_height);
+        (
+            (x - left as f32) * canvas.conf.width as f32 / w as f32,
+            (y - top as f32) * canvas.conf.height as f32 / h as f32,
+        )
+    }
+
+    /// Color of the bars around the `Conf::pixel_art` canvas.
+    pub fn set_letterbox_color(&mut self, color: Color) {
+        if let Some(canvas) = &mut self.pixel_canvas {
+            canvas.conf.letterbox_color = color;
+        }
+    }
+
+    pub(crate) fn present_pixel_canvas(&mut self) {
+        let mut canvas = match self.pixel_canvas.take() {
+            Some(canvas) => canvas,
+            None => return,
+        };
+        let (screen_width, screen_height) = self.screen_size();
+        let (x, y, w, h) = canvas.screen_rect(screen_width, screen_height);
+
+        let Color { r, g, b, a } = canvas.conf.letterbox_color;
+        self.begin_pass(None, PassAction::clear_color(r, g, b, a));
+        // the default framebuffer viewport goes from the bottom
+        self.apply_viewport(x, screen_height as i32 - y - h, w, h);
+        canvas.blit.draw(self, canvas.color_img);
+        self.end_render_pass();
+
+        self.pixel_canvas = Some(canvas);
+    }
+
+    /// Recreate the canvas on a new GL context, it is not one of the app's lost resources.
+    pub(crate) fn restore_pixel_canvas(&mut self, lost: &mut LostResources) {
+        let old = match self.pixel_canvas.take() {
+            Some(canvas) => canvas,
+            None => return,
+        };
+        lost.textures
+            .retain(|texture| *texture != old.color_img && *texture != old.depth_img);
+        lost.render_passes.retain(|pass| *pass != old.pass);
+        lost.buffers.retain(|buffer| !old.blit.owns_buffer(buffer));
+        self.pixel_canvas = Some(PixelCanvas::new(self, old.conf));
+    }
+}
+
+const FRAGMENT: &str = r#"#version 100
+precision mediump float;
+
+varying vec2 uv;
+
+uniform sampler2D canvas;
+
+void main() {
+    gl_FragColor = texture2D(canvas, uv);
+}"#;
+
+const META: ShaderMeta = ShaderMeta {
+    images: &["canvas"],
+    uniforms: UniformBlockLayout { uniforms: &[] },
+    shared_uniforms: &[],
+};
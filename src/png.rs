@@ -0,0 +1,522 @@
+//! Small PNG decoder, just enough for game textures.
+//!
+//! Supports all color types with 8 bit channels, palette and grayscale images
+//! with 1/2/4 bit depth and 16 bit images (truncated to 8 bit).
+//! Interlaced images are not supported.
+
+#[derive(Debug)]
+pub enum Error {
+    NotPng,
+    Corrupted,
+    /// Valid PNG, but uses a feature this decoder does not support
+    Unsupported(&'static str),
+}
+
+pub struct Image {
+    pub width: u16,
+    pub height: u16,
+    /// RGBA8 pixels, row by row
+    pub bytes: Vec<u8>,
+}
+
+const SIGNATURE: &[u8] = &[0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+
+pub fn decode(data: &[u8]) -> Result<Image, Error> {
+    if !data.starts_with(SIGNATURE) {
+        return Err(Error::NotPng);
+    }
+
+    let mut header = None;
+    let mut palette: &[u8] = &[];
+    let mut transparency: &[u8] = &[];
+    let mut compressed = vec![];
+
+    let mut pos = SIGNATURE.len();
+    while pos + 8 <= data.len() {
+        let len = u32::from_be_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]]);
+        let kind = &data[pos + 4..pos + 8];
+        let start = pos + 8;
+        let end = start
+            .checked_add(len as usize)
+            .filter(|end| end + 4 <= data.len())
+            .ok_or(Error::Corrupted)?;
+        let chunk = &data[start..end];
+        pos = end + 4; // skip CRC
+
+        match kind {
+            b"IHDR" => {
+                if chunk.len() != 13 {
+                    return Err(Error::Corrupted);
+                }
+                header = Some(chunk);
+            }
+            b"PLTE" => palette = chunk,
+            b"tRNS" => transparency = chunk,
+            b"IDAT" => compressed.extend_from_slice(chunk),
+            b"IEND" => break,
+            _ => {}
+        }
+    }
+
+    let header = header.ok_or(Error::Corrupted)?;
+    let width = u32::from_be_bytes([header[0], header[1], header[2], header[3]]);
+    let height = u32::from_be_bytes([header[4], header[5], header[6], header[7]]);
+    let depth = header[8] as usize;
+    let color_type = header[9];
+    if header[12] != 0 {
+        return Err(Error::Unsupported("interlaced images"));
+    }
+    if width == 0
+        || height == 0
+        || width > u16::max_value() as u32
+        || height > u16::max_value() as u32
+    {
+        return Err(Error::Unsupported("image size"));
+    }
+    let channels = match color_type {
+        0 => 1,
+        2 => 3,
+        3 => 1,
+        4 => 2,
+        6 => 4,
+        _ => return Err(Error::Corrupted),
+    };
+    if ![1, 2, 4, 8, 16].contains(&depth) {
+        return Err(Error::Corrupted);
+    }
+
+    let (width, height) = (width as usize, height as usize);
+    let bits_per_pixel = channels * depth;
+    let stride = (width * bits_per_pixel + 7) / 8;
+    // filters work on whole bytes, for sub-byte depths the previous "pixel" is the previous byte
+    let filter_step = (bits_per_pixel + 7) / 8;
+
+    if compressed.len() < 2 {
+        return Err(Error::Corrupted);
+    }
+    // skip zlib header, adler32 checksum at the end is ignored
+    let raw = inflate(&compressed[2..])?;
+    if raw.len() < (stride + 1) * height {
+        return Err(Error::Corrupted);
+    }
+
+    let mut pixels = vec![0u8; stride * height];
+    for y in 0..height {
+        let filter = raw[y * (stride + 1)];
+        let src = &raw[y * (stride + 1) + 1..(y + 1) * (stride + 1)];
+        let (prev_rows, rows) = pixels.split_at_mut(y * stride);
+        let prev = if y > 0 {
+            &prev_rows[(y - 1) * stride..]
+        } else {
+            &[][..]
+        };
+        let row = &mut rows[..stride];
+        for x in 0..stride {
+            let a = if x >= filter_step {
+                row[x - filter_step]
+            } else {
+                0
+            };
+            let b = if y > 0 { prev[x] } else { 0 };
+            let c = if y > 0 && x >= filter_step {
+                prev[x - filter_step]
+            } else {
+                0
+            };
+            row[x] = src[x].wrapping_add(match filter {
+                0 => 0,
+                1 => a,
+                2 => b,
+                3 => ((a as u16 + b as u16) / 2) as u8,
+                4 => paeth(a, b, c),
+                _ => return Err(Error::Corrupted),
+            });
+        }
+    }
+
+    // channel value of n-th sample in a row, scaled to 8 bits
+    let sample = |row: &[u8], n: usize| -> u8 {
+        match depth {
+            8 => row[n],
+            16 => row[n * 2],
+            _ => {
+                let bit = n * depth;
+                let value = (row[bit / 8] >> (8 - depth - bit % 8)) & ((1 << depth) - 1) as u8;
+                if color_type == 3 {
+                    value
+                } else {
+                    // scale gray to 0..255
+                    (value as u32 * 255 / ((1 << depth) - 1)) as u8
+                }
+            }
+        }
+    };
+
+    let mut bytes = Vec::with_capacity(width * height * 4);
+    for y in 0..height {
+        let row = &pixels[y * stride..(y + 1) * stride];
+        for x in 0..width {
+            let rgba = match color_type {
+                0 => {
+                    let v = sample(row, x);
+                    [v, v, v, 255]
+                }
+                2 => [
+                    sample(row, x * 3),
+                    sample(row, x * 3 + 1),
+                    sample(row, x * 3 + 2),
+                    255,
+                ],
+                3 => {
+                    let index = sample(row, x) as usize;
+                    if index * 3 + 2 >= palette.len() {
+                        return Err(Error::Corrupted);
+                    }
+                    [
+                        palette[index * 3],
+                        palette[index * 3 + 1],
+                        palette[index * 3 + 2],
+                        *transparency.get(index).unwrap_or(&255),
+                    ]
+                }
+                4 => {
+                    let v = sample(row, x * 2);
+                    [v, v, v, sample(row, x * 2 + 1)]
+                }
+                _ => [
+                    sample(row, x * 4),
+                    sample(row, x * 4 + 1),
+                    sample(row, x * 4 + 2),
+                    sample(row, x * 4 + 3),
+                ],
+            };
+            bytes.extend_from_slice(&rgba);
+        }
+    }
+
+    Ok(Image {
+        width: width as u16,
+        height: height as u16,
+        bytes,
+    })
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let pa = (p - a as i16).abs();
+    let pb = (p - b as i16).abs();
+    let pc = (p - c as i16).abs();
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    bit: u32,
+}
+
+impl<'a> BitReader<'a> {
+    fn bits(&mut self, n: u32) -> Result<u32, Error> {
+        let mut value = 0;
+        for i in 0..n {
+            let byte = *self.data.get(self.pos).ok_or(Error::Corrupted)?;
+            value |= ((byte as u32 >> self.bit) & 1) << i;
+            self.bit += 1;
+            if self.bit == 8 {
+                self.bit = 0;
+                self.pos += 1;
+            }
+        }
+        Ok(value)
+    }
+
+    fn align(&mut self) {
+        if self.bit != 0 {
+            self.bit = 0;
+            self.pos += 1;
+        }
+    }
+}
+
+/// Canonical Huffman code, decoded bit by bit as in zlib's "puff".
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Huffman {
+        let mut counts = [0u16; 16];
+        for &len in lengths {
+            counts[len as usize] += 1;
+        }
+        counts[0] = 0;
+
+        let mut offsets = [0u16; 16];
+        for i in 1..15 {
+            offsets[i + 1] = offsets[i] + counts[i];
+        }
+        let mut symbols = vec![0; lengths.len()];
+        for (symbol, &len) in lengths.iter().enumerate() {
+            if len != 0 {
+                symbols[offsets[len as usize] as usize] = symbol as u16;
+                offsets[len as usize] += 1;
+            }
+        }
+        Huffman { counts, symbols }
+    }
+
+    fn decode(&self, reader: &mut BitReader) -> Result<u16, Error> {
+        let mut code: i32 = 0;
+        let mut first: i32 = 0;
+        let mut index: i32 = 0;
+        for len in 1..16 {
+            code |= reader.bits(1)? as i32;
+            let count = self.counts[len] as i32;
+            if code - count < first {
+                return self
+                    .symbols
+                    .get((index + code - first) as usize)
+                    .cloned()
+                    .ok_or(Error::Corrupted);
+            }
+            index += count;
+            first += count;
+            first <<= 1;
+            code <<= 1;
+        }
+        Err(Error::Corrupted)
+    }
+}
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+fn inflate(data: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut reader = BitReader {
+        data,
+        pos: 0,
+        bit: 0,
+    };
+    let mut out = vec![];
+
+    loop {
+        let last = reader.bits(1)? == 1;
+        match reader.bits(2)? {
+            0 => {
+                reader.align();
+                let pos = reader.pos;
+                if pos + 4 > data.len() {
+                    return Err(Error::Corrupted);
+                }
+                let len = u16::from_le_bytes([data[pos], data[pos + 1]]) as usize;
+                let block = data.get(pos + 4..pos + 4 + len).ok_or(Error::Corrupted)?;
+                out.extend_from_slice(block);
+                reader.pos = pos + 4 + len;
+            }
+            1 => {
+                let mut lengths = [0u8; 288];
+                for (i, len) in lengths.iter_mut().enumerate() {
+                    *len = match i {
+                        0..=143 => 8,
+                        144..=255 => 9,
+                        256..=279 => 7,
+                        _ => 8,
+                    };
+                }
+                let lit = Huffman::new(&lengths);
+                let dist = Huffman::new(&[5; 30]);
+                inflate_block(&mut reader, &mut out, &lit, &dist)?;
+            }
+            2 => {
+                let hlit = reader.bits(5)? as usize + 257;
+                let hdist = reader.bits(5)? as usize + 1;
+                let hclen = reader.bits(4)? as usize + 4;
+                const ORDER: [usize; 19] = [
+                    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+                ];
+                let mut code_lengths = [0u8; 19];
+                for &i in ORDER.iter().take(hclen) {
+                    code_lengths[i] = reader.bits(3)? as u8;
+                }
+                let code = Huffman::new(&code_lengths);
+
+                let mut lengths = vec![0u8; hlit + hdist];
+                let mut i = 0;
+                while i < hlit + hdist {
+                    let symbol = code.decode(&mut reader)?;
+                    let (value, repeat) = match symbol {
+                        0..=15 => (symbol as u8, 1),
+                        16 => {
+                            if i == 0 {
+                                return Err(Error::Corrupted);
+                            }
+                            (lengths[i - 1], 3 + reader.bits(2)? as usize)
+                        }
+                        17 => (0, 3 + reader.bits(3)? as usize),
+                        _ => (0, 11 + reader.bits(7)? as usize),
+                    };
+                    if i + repeat > lengths.len() {
+                        return Err(Error::Corrupted);
+                    }
+                    for len in &mut lengths[i..i + repeat] {
+                        *len = value;
+                    }
+                    i += repeat;
+                }
+                let lit = Huffman::new(&lengths[..hlit]);
+                let dist = Huffman::new(&lengths[hlit..]);
+                inflate_block(&mut reader, &mut out, &lit, &dist)?;
+            }
+            _ => return Err(Error::Corrupted),
+        }
+        if last {
+            break;
+        }
+    }
+
+    Ok(out)
+}
+
+fn inflate_block(
+    reader: &mut BitReader,
+    out: &mut Vec<u8>,
+    lit: &Huffman,
+    dist: &Huffman,
+) -> Result<(), Error> {
+    loop {
+        let symbol = lit.decode(reader)? as usize;
+        if symbol < 256 {
+            out.push(symbol as u8);
+        } else if symbol == 256 {
+            return Ok(());
+        } else {
+            let symbol = symbol - 257;
+            if symbol >= 29 {
+                return Err(Error::Corrupted);
+            }
+            let len =
+                LENGTH_BASE[symbol] as usize + reader.bits(LENGTH_EXTRA[symbol] as u32)? as usize;
+            let symbol = dist.decode(reader)? as usize;
+            if symbol >= 30 {
+                return Err(Error::Corrupted);
+            }
+            let distance =
+                DIST_BASE[symbol] as usize + reader.bits(DIST_EXTRA[symbol] as u32)? as usize;
+            if distance > out.len() {
+                return Err(Error::Corrupted);
+            }
+            let start = out.len() - distance;
+            for i in 0..len {
+                let byte = out[start + i];
+                out.push(byte);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rgba_with_filters() {
+        let image = decode(include_bytes!("../testdata/3x2_rgba.png")).unwrap();
+        assert_eq!((image.width, image.height), (3, 2));
+        assert_eq!(
+            image.bytes,
+            [
+                255, 0, 0, 255, 0, 255, 0, 128, 0, 0, 255, 0, //
+                10, 20, 30, 40, 50, 60, 70, 80, 90, 100, 110, 120,
+            ]
+        );
+    }
+
+    #[test]
+    fn palette_2_bit_with_transparency() {
+        let image = decode(include_bytes!("../testdata/5x1_palette.png")).unwrap();
+        assert_eq!((image.width, image.height), (5, 1));
+        assert_eq!(
+            image.bytes,
+            [0, 0, 0, 0, 255, 255, 255, 255, 255, 0, 0, 255, 255, 255, 255, 255, 0, 0, 0, 0]
+        );
+    }
+
+    /// Big enough for zlib to use dynamic huffman codes
+    #[test]
+    fn gray_16_bit() {
+        let image = decode(include_bytes!("../testdata/32x32_gray16.png")).unwrap();
+        assert_eq!((image.width, image.height), (32, 32));
+        for y in 0..32 {
+            for x in 0..32 {
+                let v = ((x * y * 64) & 0xffff) >> 8;
+                let i = (y * 32 + x) * 4;
+                assert_eq!(image.bytes[i..i + 4], [v as u8, v as u8, v as u8, 255]);
+            }
+        }
+    }
+
+    #[test]
+    fn not_png() {
+        assert!(matches!(decode(b"GIF89a"), Err(Error::NotPng)));
+        assert!(matches!(decode(&[]), Err(Error::NotPng)));
+    }
+
+    #[test]
+    fn truncated() {
+        let data = include_bytes!("../testdata/32x32_gray16.png");
+        for len in 0..data.len() - 12 {
+            // IEND and the end of IDAT missing
+            assert!(decode(&data[..len]).is_err(), "{} bytes", len);
+        }
+    }
+
+    #[test]
+    fn corrupted_header() {
+        let mut data = include_bytes!("../testdata/3x2_rgba.png").to_vec();
+        // color type
+        data[8 + 8 + 9] = 5;
+        assert!(matches!(decode(&data), Err(Error::Corrupted)));
+        // chunk length past the end of the file
+        let mut data = include_bytes!("../testdata/3x2_rgba.png").to_vec();
+        data[8] = 0xff;
+        assert!(matches!(decode(&data), Err(Error::Corrupted)));
+    }
+
+    /// Every single byte change of a valid file fails or decodes, without panics
+    #[test]
+    fn mutations() {
+        for data in &[
+            &include_bytes!("../testdata/3x2_rgba.png")[..],
+            &include_bytes!("../testdata/5x1_palette.png")[..],
+            &include_bytes!("../testdata/32x32_gray16.png")[..],
+        ] {
+            for i in 0..data.len() {
+                for &value in &[0x00, 0x01, 0x0f, 0x7f, 0x80, 0xfe, 0xff] {
+                    let mut mutated = data.to_vec();
+                    mutated[i] = value;
+                    let _ = decode(&mutated);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use crate::{conf::RenderTargetOrigin, graphics::*};
+
+/// Fullscreen pass: draws a texture, usually the color attachment of an offscreen
+/// `RenderPass`, through a user fragment shader into the current pass.
+///
+/// The fragment shader gets `varying mediump vec2 uv;`, the coordinates to sample the source
+/// texture with, and the source texture is the first image of its `ShaderMeta`.
+/// `uv` follows `Context::render_target_origin`, so the picture keeps its orientation
+/// whether the pass draws to the screen or to another render target.
+pub struct PostProcess {
+    pipeline: Pipeline,
+    bindings: Bindings,
+}
+
+impl PostProcess {
+    pub fn new(ctx: &mut Context, fragment: &str, meta: ShaderMeta) -> PostProcess {
+        // one triangle covering the whole screen, no diagonal seam as with a quad
+        #[rustfmt::skip]
+        let vertices: [f32; 12] = match ctx.render_target_origin() {
+            /* pos         uv */
+            RenderTargetOrigin::TopLeft => [
+                -1., -1.,    0., 1.,
+                 3., -1.,    2., 1.,
+                -1.,  3.,    0., -1.,
+            ],
+            RenderTargetOrigin::BottomLeft => [
+                -1., -1.,    0., 0.,
+                 3., -1.,    2., 0.,
+                -1.,  3.,    0., 2.,
+            ],
+        };
+        let vertex_buffer = Buffer::immutable(ctx, BufferType::VertexBuffer, &vertices);
+        let index_buffer = Buffer::immutable(ctx, BufferType::IndexBuffer, &[0u16, 1, 2]);
+
+        let shader = Shader::new(ctx, VERTEX, fragment, meta);
+        let pipeline = Pipeline::new(
+            ctx,
+            &[BufferLayout::default()],
+            &[
+                VertexAttribute::new("pos", VertexFormat::Float2),
+                VertexAttribute::new("uv0", VertexFormat::Float2),
+            ],
+            shader,
+        );
+
+        PostProcess {
+            pipeline,
+            bindings: Bindings {
+                vertex_buffers: vec![vertex_buffer],
+                index_buffer,
+                images: vec![],
+            },
+        }
+    }
+
+    /// Draw `source` into the current pass, for shaders without uniforms.
+    pub fn draw(&mut self, ctx: &mut Context, source: Texture) {
+        self.apply(ctx, source);
+        ctx.draw(0, 3, 1);
+    }
+
+    /// Draw `source` into the current pass, `uniforms` should match
+    /// the layout given in the `ShaderMeta`.
+    pub fn draw_with_uniforms<U>(&mut self, ctx: &mut Context, source: Texture, uniforms: &U) {
+        self.apply(ctx, source);
+        ctx.apply_uniforms(uniforms);
+        ctx.draw(0, 3, 1);
+    }
+
+    /// Whether `buffer` is the triangle's vertex or index buffer.
+    pub(crate) fn owns_buffer(&self, buffer: &Buffer) -> bool {
+        self.bindings.vertex_buffers.contains(buffer) || self.bindings.index_buffer == *buffer
+    }
+
+    fn apply(&mut self, ctx: &mut Context, source: Texture) {
+        self.bindings.images = vec![source];
+        ctx.apply_pipeline(&self.pipeline);
+        ctx.apply_bindings(&self.bindings);
+    }
+}
+
+const VERTEX: &str = r#"#version 100
+attribute vec2 pos;
+attribute vec2 uv0;
+
+varying mediump vec2 uv;
+
+void main() {
+    gl_Position = vec4(pos, 0, 1);
+    uv = uv0;
+}"#;
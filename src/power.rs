@@ -0,0 +1,161 @@
+use crate::Context;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BatteryStatus {
+    /// Charge level, from 0.0 to 1.0
+    pub level: f32,
+    /// Connected to external power, either charging or already full
+    pub charging: bool,
+    /// OS asks applications to save power: battery saver, low power mode etc
+    pub power_saving: bool,
+}
+
+/// Frame rate caps, applied in the main loop.
+/// Battery status is polled only once in a while, it may be slow to query.
+#[derive(Debug, Default)]
+pub(crate) struct FrameLimiter {
+    pub max_fps: Option<u32>,
+    pub max_fps_on_battery: Option<u32>,
+    /// Limit applied to the last frame
+    pub active_limit: Option<u32>,
+    /// Frames paced by someone else, like an XR runtime, the limits are not applied
+    pub external_pacing: bool,
+    last_frame: f64,
+    battery_checked_at: f64,
+    on_battery: bool,
+    /// Recent lateness of `sapp_sleep` wake-ups, slowly forgotten
+    sleep_overshoot: f64,
+}
+
+const BATTERY_CHECK_INTERVAL: f64 = 5.0;
+/// Least and most of the end of a frame wait spun instead of slept, in seconds.
+/// The most keeps a single late wake-up, like a preempted thread, from spinning for long.
+#[cfg(not(target_arch = "wasm32"))]
+const MIN_SPIN: f64 = 0.000_2;
+#[cfg(not(target_arch = "wasm32"))]
+const MAX_SPIN: f64 = 0.004;
+
+impl FrameLimiter {
+    fn current_limit(&mut self, now: f64) -> Option<u32> {
+        if self.max_fps_on_battery.is_some() {
+            if now - self.battery_checked_at > BATTERY_CHECK_INTERVAL {
+                self.battery_checked_at = now;
+                self.on_battery = battery_status()
+                    .map_or(false, |status| !status.charging || status.power_saving);
+            }
+            if self.on_battery {
+                return self.max_fps_on_battery;
+            }
+        }
+        self.max_fps
+    }
+
+    /// Returns false if this frame should be skipped.
+    /// On native the limiter sleeps instead, so this is always true.
+    pub fn begin_frame(&mut self) -> bool {
+        let now = crate::date::monotonic();
+        self.active_limit = if self.external_pacing {
+            None
+        } else {
+            self.current_limit(now).filter(|limit| *limit > 0)
+        };
+        let limit = match self.active_limit {
+            Some(limit) => limit,
+            None => {
+                self.last_frame = now;
+                return true;
+            }
+        };
+        let frame_time = 1.0 / limit as f64;
+        let elapsed = now - self.last_frame;
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            // more than a frame since the last one, like on the first frame, isn't waited for
+            let deadline = self.last_frame + frame_time;
+            if (0. ..frame_time).contains(&elapsed) {
+                self.wait_until(deadline);
+            }
+            // frames keep their cadence instead of drifting by each wake-up's lateness,
+            // unless one took so long there is no catching up
+            let now = crate::date::monotonic();
+            self.last_frame = if (0. ..frame_time).contains(&(now - deadline)) {
+                deadline
+            } else {
+                now
+            };
+            true
+        }
+
+        // browsers call requestAnimationFrame at the display rate, so just skip some frames
+        #[cfg(target_arch = "wasm32")]
+        {
+            if elapsed < frame_time {
+                return false;
+            }
+            self.last_frame = now;
+            true
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl FrameLimiter {
+    /// Sleep most of the way, as OS timers wake up late, by up to a scheduler tick on
+    /// Windows without high resolution timers, and spin the rest: by how much is learnt
+    /// from how late the previous sleeps were.
+    fn wait_until(&mut self, deadline: f64) {
+        let spin = self.sleep_overshoot.max(MIN_SPIN).min(MAX_SPIN);
+        let now = crate::date::monotonic();
+        let sleep = deadline - now - spin;
+        if sleep > 0. {
+            unsafe { sapp::sapp_sleep(sleep) };
+            let overshoot = crate::date::monotonic() - now - sleep;
+            self.sleep_overshoot = overshoot.max(self.sleep_overshoot * 0.95);
+        }
+        while crate::date::monotonic() < deadline {
+            std::hint::spin_loop();
+        }
+    }
+}
+
+pub(crate) fn battery_status() -> Option<BatteryStatus> {
+    let mut status = BatteryStatus {
+        level: 0.,
+        charging: false,
+        power_saving: false,
+    };
+    let has_battery = unsafe {
+        sapp::sapp_battery_status(
+            &mut status.level as *mut _,
+            &mut status.charging as *mut _,
+            &mut status.power_saving as *mut _,
+        )
+    };
+    if has_battery {
+        Some(status)
+    } else {
+        None
+    }
+}
+
+impl Context {
+    /// Battery charge and power state, `None` on devices without a battery
+    /// or when the platform does not report it.
+    pub fn battery_status(&self) -> Option<BatteryStatus> {
+        battery_status()
+    }
+
+    /// Limit the frame rate, `None` to run as fast as vsync allows.
+    /// Native frames are waited for to within a fraction of a millisecond, the end
+    /// of the wait spinning; browsers skip frames of the display rate instead.
+    pub fn set_max_fps(&mut self, max_fps: Option<u32>) {
+        self.frame_limiter.max_fps = max_fps;
+    }
+
+    /// Frame rate limit used instead of `set_max_fps` one while running on battery
+    /// or when the OS asks to save power.
+    pub fn set_max_fps_on_battery(&mut self, max_fps: Option<u32>) {
+        self.frame_limiter.max_fps_on_battery = max_fps;
+    }
+}
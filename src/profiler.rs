@@ -0,0 +1,77 @@
+//! Scoped profiling zones, forwarded to an external profiler such as Tracy or Optick.
+//!
+//! The crate does not talk to any profiler itself: the application implements `Profiler`
+//! on top of its profiler client and installs it with `set_profiler`. Zones are then
+//! reported for the application's own `zone!`s and for the crate's work: event handling,
+//! update, draw, buffer and texture uploads and shader compilation. A frame mark is
+//! emitted at the start of every frame, so what happens between the end of `draw` and the
+//! next frame, presenting and the platform event loop, shows up between the zones.
+//!
+//! Without the `profiling` feature `zone!` expands to nothing.
+
+use std::cell::RefCell;
+
+pub trait Profiler {
+    /// Zones are strictly nested, `end_zone` closes the last zone begun.
+    fn begin_zone(&self, name: &'static str);
+    fn end_zone(&self);
+    fn frame_mark(&self) {}
+}
+
+thread_local! {
+    static PROFILER: RefCell<Option<Box<dyn Profiler>>> = RefCell::new(None);
+}
+
+/// Report zones to `profiler` from now on, replacing any previous one.
+pub fn set_profiler<P: Profiler + 'static>(profiler: P) {
+    PROFILER.with(|current| *current.borrow_mut() = Some(Box::new(profiler)));
+}
+
+/// Ends the zone when dropped, see `zone!`.
+pub struct Zone {
+    _private: (),
+}
+
+#[doc(hidden)]
+pub fn begin_zone(name: &'static str) -> Zone {
+    PROFILER.with(|profiler| {
+        if let Some(profiler) = &*profiler.borrow() {
+            profiler.begin_zone(name);
+        }
+    });
+    Zone { _private: () }
+}
+
+impl Drop for Zone {
+    fn drop(&mut self) {
+        PROFILER.with(|profiler| {
+            if let Some(profiler) = &*profiler.borrow() {
+                profiler.end_zone();
+            }
+        });
+    }
+}
+
+pub(crate) fn frame_mark() {
+    PROFILER.with(|profiler| {
+        if let Some(profiler) = &*profiler.borrow() {
+            profiler.frame_mark();
+        }
+    });
+}
+
+/// Profile the rest of the enclosing block as a zone named `$name`:
+/// `zone!("shadow pass");`
+#[cfg(feature = "profiling")]
+#[macro_export]
+macro_rules! zone {
+    ($name:expr) => {
+        let _zone = $crate::profiler::begin_zone($name);
+    };
+}
+
+#[cfg(not(feature = "profiling"))]
+#[macro_export]
+macro_rules! zone {
+    ($name:expr) => {};
+}
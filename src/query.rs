@@ -0,0 +1,133 @@
+use crate::{gl::*, graphics::Context};
+
+/// What a `Query` counts between `begin` and `end`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QueryKind {
+    /// Samples passing the depth and stencil tests, one per pixel without MSAA:
+    /// how much of what was drawn ends up visible
+    SamplesPassed,
+    /// Primitives the vertex stage output, before clipping and culling
+    PrimitivesGenerated,
+    /// Primitives reaching the clipping stage, needs `Features::pipeline_statistics`
+    ClippingInputPrimitives,
+    /// Primitives left after clipping against the view volume, the ones crossing its
+    /// planes possibly split in several. Back faces are culled after this. Needs
+    /// `Features::pipeline_statistics`
+    ClippingOutputPrimitives,
+}
+
+impl QueryKind {
+    #[cfg(not(target_arch = "wasm32"))]
+    fn gl_target(self) -> GLenum {
+        match self {
+            QueryKind::SamplesPassed => GL_SAMPLES_PASSED,
+            QueryKind::PrimitivesGenerated => GL_PRIMITIVES_GENERATED,
+            QueryKind::ClippingInputPrimitives => GL_CLIPPING_INPUT_PRIMITIVES,
+            QueryKind::ClippingOutputPrimitives => GL_CLIPPING_OUTPUT_PRIMITIVES,
+        }
+    }
+}
+
+/// GPU counter over the draw calls between `begin` and `end`, e.g. to measure how
+/// many of the primitives submitted a culling system lets through are actually visible.
+///
+/// ```ignore
+/// // once
+/// let query = Query::new(ctx, QueryKind::SamplesPassed).unwrap();
+/// // every frame, counting again once the last count came
+/// let count = match self.counting {
+///     true => query.result().map(|samples| println!("{} samples passed", samples)),
+///     false => Some(()),
+/// };
+/// if count.is_some() {
+///     query.begin();
+/// }
+/// // draw calls
+/// if count.is_some() {
+///     query.end();
+///     self.counting = true;
+/// }
+/// ```
+///
+/// The GPU runs behind the CPU, results come a frame or two later: `result` is `None`
+/// until then, and beginning the query again before its result came discards it. Only one query of each kind can be running at a time.
+///
+/// Queries are ordinary GL objects: after a context loss they have to be created again.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Query {
+    query: GLuint,
+    kind: QueryKind,
+}
+
+impl Query {
+    /// `None` when the GPU can't count `kind`: on WebGL, and for the clipping counts
+    /// without `Features::pipeline_statistics`.
+    pub fn new(ctx: &Context, kind: QueryKind) -> Option<Query> {
+        let features = ctx.features();
+        let supported = match kind {
+            QueryKind::SamplesPassed | QueryKind::PrimitivesGenerated => features.queries,
+            QueryKind::ClippingInputPrimitives | QueryKind::ClippingOutputPrimitives => {
+                features.pipeline_statistics
+            }
+        };
+        if !supported {
+            return None;
+        }
+
+        let mut query = 0;
+        #[cfg(not(target_arch = "wasm32"))]
+        unsafe {
+            glGenQueries(1, &mut query as *mut _);
+        }
+        Some(Query { query, kind })
+    }
+
+    pub fn kind(&self) -> QueryKind {
+        self.kind
+    }
+
+    /// Start counting, the count starts from 0.
+    pub fn begin(&self) {
+        #[cfg(not(target_arch = "wasm32"))]
+        unsafe {
+            glBeginQuery(self.kind.gl_target(), self.query);
+        }
+    }
+
+    pub fn end(&self) {
+        #[cfg(not(target_arch = "wasm32"))]
+        unsafe {
+            glEndQuery(self.kind.gl_target());
+        }
+    }
+
+    /// Count of the last `begin`, `end` pair once the GPU is done with it,
+    /// without waiting. Only valid after the first `end`.
+    pub fn result(&self) -> Option<u32> {
+        #[cfg(not(target_arch = "wasm32"))]
+        unsafe {
+            let mut available: GLuint = 0;
+            glGetQueryObjectuiv(
+                self.query,
+                GL_QUERY_RESULT_AVAILABLE,
+                &mut available as *mut _,
+            );
+            if available == 0 {
+                return None;
+            }
+            let mut result: GLuint = 0;
+            glGetQueryObjectuiv(self.query, GL_QUERY_RESULT, &mut result as *mut _);
+            Some(result)
+        }
+        #[cfg(target_arch = "wasm32")]
+        None
+    }
+
+    /// Free the query, it must not be used afterwards.
+    pub fn delete(&self) {
+        #[cfg(not(target_arch = "wasm32"))]
+        unsafe {
+            glDeleteQueries(1, &self.query as *const _);
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use crate::Context;
+
+/// Pixel format of the frames `Context::start_recording` hands over
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameFormat {
+    /// 4 bytes per pixel, as drawn
+    Rgba,
+    /// 4 bytes per pixel, what Windows and many capture APIs take
+    Bgra,
+    /// Planar YUV 4:2:0, BT.601 with video range: the Y plane, then the U and V planes
+    /// at half the width and height. What most video encoders take. Odd window sizes
+    /// lose their last row or column.
+    I420,
+}
+
+/// A frame of a recording, rows from top to bottom, alpha set to opaque
+#[derive(Debug)]
+pub struct RecordedFrame<'a> {
+    pub width: u32,
+    pub height: u32,
+    pub format: FrameFormat,
+    pub data: &'a [u8],
+    /// Frames handed over since the recording started, this one excluded
+    pub index: u64,
+    /// Presentation time of the frame in seconds from the start of the recording,
+    /// `index` over the recording's frame rate
+    pub time: f64,
+}
+
+pub(crate) struct Recorder {
+    sink: Box<dyn FnMut(&RecordedFrame)>,
+    fps: u32,
+    format: FrameFormat,
+    start: f64,
+    /// Frames handed over so far
+    emitted: u64,
+    converted: Vec<u8>,
+}
+
+impl Context {
+    /// Hand the frames drawn from now on to `sink`, e.g. to feed a video encoder for
+    /// gameplay recording, at a steady `fps`, whatever the rate of the app: frames are
+    /// repeated when the app draws slower and skipped when it draws faster, so the
+    /// recording keeps to real time. `sink` is called at the end of the frame, with the
+    /// window's content before the `debug_show_texture` overlays, in `format`.
+    ///
+    /// Reading frames back waits for the GPU to finish drawing them: keep `fps` at what
+    /// the video needs.
+    pub fn start_recording<F>(&mut self, fps: u32, format: FrameFormat, sink: F)
+    where
+        F: 'static + FnMut(&RecordedFrame),
+    {
+        assert!(fps > 0);
+        self.recorder = Some(Recorder {
+            sink: Box::new(sink),
+            fps,
+            format,
+            start: crate::date::now(),
+            emitted: 0,
+            converted: vec![],
+        });
+    }
+
+    pub fn stop_recording(&mut self) {
+        self.recorder = None;
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recorder.is_some()
+    }
+
+    /// Hand this frame to the recording as often as it is due, called once per frame
+    pub(crate) fn record_frame(&mut self) {
+        let mut recorder = match self.recorder.take() {
+            Some(recorder) => recorder,
+            None => return,
+        };
+        let due = ((crate::date::now() - recorder.start) * recorder.fps as f64) as u64 + 1;
+        if recorder.emitted < due {
+            let (width, height, mut pixels) = self.read_default_framebuffer();
+            for pixel in pixels.chunks_mut(4) {
+                pixel[3] = 255;
+            }
+            let (width, height) = (width as u32, height as u32);
+            let (width, height, data) = match recorder.format {
+                FrameFormat::Rgba => (width, height, &pixels[..]),
+                FrameFormat::Bgra => {
+                    for pixel in pixels.chunks_mut(4) {
+                        pixel.swap(0, 2);
+                    }
+                    (width, height, &pixels[..])
+                }
+                FrameFormat::I420 => {
+                    let stride = width as usize * 4;
+                    let (width, height) = (width & !1, height & !1);
+                    rgba_to_i420(&pixels, stride, width, height, &mut recorder.converted);
+                    (width, height, &recorder.converted[..])
+                }
+            };
+            while recorder.emitted < due {
+                (recorder.sink)(&RecordedFrame {
+                    width,
+                    height,
+                    format: recorder.format,
+                    data,
+                    index: recorder.emitted,
+                    time: recorder.emitted as f64 / recorder.fps as f64,
+                });
+                recorder.emitted += 1;
+            }
+        }
+        self.recorder = Some(recorder);
+    }
+}
+
+/// I420 of the top left `width`x`height` pixels, both even, of an RGBA image of
+/// `stride` bytes per row
+fn rgba_to_i420(rgba: &[u8], stride: usize, width: u32, height: u32, i420: &mut Vec<u8>) {
+    let (width, height) = (width as usize, height as usize);
+    i420.clear();
+    i420.resize(width * height * 3 / 2, 0);
+    let (y_plane, chroma) = i420.split_at_mut(width * height);
+    let (u_plane, v_plane) = chroma.split_at_mut(width * height / 4);
+
+    let pixel = |x: usize, y: usize| {
+        let i = y * stride + x * 4;
+        (rgba[i] as i32, rgba[i + 1] as i32, rgba[i + 2] as i32)
+    };
+    for y in 0..height {
+        for x in 0..width {
+            let (r, g, b) = pixel(x, y);
+            y_plane[y * width + x] = (((66 * r + 129 * g + 25 * b + 128) >> 8) + 16) as u8;
+        }
+    }
+    for y in 0..height / 2 {
+        for x in 0..width / 2 {
+            let (mut r, mut g, mut b) = (0, 0, 0);
+            for &(dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)].iter() {
+                let (pr, pg, pb) = pixel(x * 2 + dx, y * 2 + dy);
+                r += pr;
+                g += pg;
+                b += pb;
+            }
+            let (r, g, b) = (r / 4, g / 4, b / 4);
+            let i = y * width / 2 + x;
+            u_plane[i] = (((-38 * r - 74 * g + 112 * b + 128) >> 8) + 128) as u8;
+            v_plane[i] = (((112 * r - 94 * g - 18 * b + 128) >> 8) + 128) as u8;
+        }
+    }
+}
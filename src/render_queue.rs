@@ -0,0 +1,113 @@
+use std::ops::Range;
+
+use crate::graphics::*;
+
+/// Collects draws with a sort key and issues them sorted by it on `flush`,
+/// so draws sharing a pipeline, then a texture, end up next to each other.
+///
+/// Keys are compared as plain numbers, `sort_key` builds one from the usual criteria.
+/// Draws with equal keys keep their submission order.
+#[derive(Default)]
+pub struct RenderQueue {
+    draws: Vec<QueuedDraw>,
+    /// Uniforms of all the queued draws, one after the other
+    uniforms: Vec<f32>,
+}
+
+struct QueuedDraw {
+    key: u64,
+    pipeline: Pipeline,
+    bindings: Bindings,
+    uniforms: Range<usize>,
+    base_element: i32,
+    num_elements: i32,
+    num_instances: i32,
+}
+
+impl RenderQueue {
+    pub fn new() -> RenderQueue {
+        RenderQueue::default()
+    }
+
+    /// Key ordering draws by `layer` first, e.g. opaque before transparent, then by
+    /// pipeline, texture and `depth`, from 0 to 1. For back to front drawing
+    /// of transparent things pass `1 - depth`.
+    pub fn sort_key(layer: u8, pipeline: &Pipeline, texture: Option<Texture>, depth: f32) -> u64 {
+        let pipeline = pipeline.0 as u64 & 0xffff;
+        let texture = texture.map_or(0, |texture| texture.gl_internal_id() as u64 & 0xffff);
+        let depth = (depth.max(0.).min(1.) * 0xff_ffff as f32) as u64;
+        (layer as u64) << 56 | pipeline << 40 | texture << 24 | depth
+    }
+
+    /// Queue a draw, with arguments as for `apply_pipeline`, `apply_bindings`,
+    /// `apply_uniforms` and `draw`. `uniforms` is copied, `&()` for shaders without uniforms.
+    #[allow(clippy::too_many_arguments)]
+    pub fn submit<U>(
+        &mut self,
+        key: u64,
+        pipeline: &Pipeline,
+        bindings: &Bindings,
+        uniforms: &U,
+        base_element: i32,
+        num_elements: i32,
+        num_instances: i32,
+    ) {
+        let floats = unsafe {
+            std::slice::from_raw_parts(
+                uniforms as *const _ as *const f32,
+                std::mem::size_of::<U>() / 4,
+            )
+        };
+        let start = self.uniforms.len();
+        self.uniforms.extend_from_slice(floats);
+
+        self.draws.push(QueuedDraw {
+            key,
+            pipeline: *pipeline,
+            bindings: bindings.clone(),
+            uniforms: start..self.uniforms.len(),
+            base_element,
+            num_elements,
+            num_instances,
+        });
+    }
+
+    pub fn len(&self) -> usize {
+        self.draws.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.draws.is_empty()
+    }
+
+    /// Issue all queued draws into the current render pass, sorted by key.
+    /// Pipelines and bindings are only applied when they change.
+    pub fn flush(&mut self, ctx: &mut Context) {
+        self.draws.sort_by_key(|draw| draw.key);
+
+        let mut previous: Option<&QueuedDraw> = None;
+        for draw in &self.draws {
+            let same_pipeline =
+                previous.map_or(false, |previous| previous.pipeline.0 == draw.pipeline.0);
+            if !same_pipeline {
+                ctx.apply_pipeline(&draw.pipeline);
+            }
+            if !same_pipeline
+                || previous.map_or(true, |previous| previous.bindings != draw.bindings)
+            {
+                ctx.apply_bindings(&draw.bindings);
+            }
+            if !draw.uniforms.is_empty() {
+                let uniforms = &self.uniforms[draw.uniforms.clone()];
+                unsafe {
+                    ctx.apply_uniforms_raw(uniforms.as_ptr(), uniforms.len() * 4);
+                }
+            }
+            ctx.draw(draw.base_element, draw.num_elements, draw.num_instances);
+            previous = Some(draw);
+        }
+
+        self.draws.clear();
+        self.uniforms.clear();
+    }
+}
@@ -0,0 +1,178 @@
+use crate::{conf::RenderTargetOrigin, graphics::*, PostProcess};
+
+/// Renders the scene at a fraction of the screen resolution and upscales it on present,
+/// to trade sharpness for fill rate, e.g. lowering the scale when frames take too long.
+///
+/// The render target has the size of the screen and only its top-left `render_size`
+/// part is drawn to, so changing the scale every frame costs nothing. It is reallocated
+/// when the screen is resized.
+///
+/// ```ignore
+/// self.render_scale.begin_pass(ctx, PassAction::clear_color(0., 0., 0., 1.));
+/// // draw the scene
+/// self.render_scale.present(ctx);
+/// // draw the UI at full resolution
+/// ctx.end_render_pass();
+/// ```
+pub struct RenderScale {
+    scale: f32,
+    target: Option<Target>,
+    blit: PostProcess,
+}
+
+struct Target {
+    color_img: Texture,
+    depth_img: Texture,
+    pass: RenderPass,
+}
+
+impl RenderScale {
+    pub const MIN_SCALE: f32 = 0.1;
+
+    pub fn new(ctx: &mut Context, scale: f32) -> RenderScale {
+        let mut render_scale = RenderScale {
+            scale: 1.,
+            target: None,
+            blit: PostProcess::new(ctx, shader::FRAGMENT, shader::META),
+        };
+        render_scale.set_scale(scale);
+        render_scale
+    }
+
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    /// Fraction of the screen resolution the scene is rendered at,
+    /// clamped to `MIN_SCALE`..=1. Takes effect on the next `begin_pass`.
+    pub fn set_scale(&mut self, scale: f32) {
+        self.scale = scale.max(Self::MIN_SCALE).min(1.);
+    }
+
+    /// Size in pixels of the scene picture, what the projection aspect ratio and
+    /// `apply_scissor_rect` coordinates inside the scene pass should be based on.
+    pub fn render_size(&self, ctx: &Context) -> (i32, i32) {
+        let (width, height) = ctx.screen_size();
+        (
+            ((width * self.scale).round() as i32).max(1),
+            ((height * self.scale).round() as i32).max(1),
+        )
+    }
+
+    /// Start rendering the scene into the scaled down target.
+    pub fn begin_pass(&mut self, ctx: &mut Context, action: PassAction) {
+        let (screen_width, screen_height) = ctx.screen_size();
+        let (screen_width, screen_height) = (screen_width as u32, screen_height as u32);
+        let outdated = match &self.target {
+            Some(target) => {
+                target.color_img.width != screen_width || target.color_img.height != screen_height
+            }
+            None => true,
+        };
+        if outdated {
+            if let Some(target) = self.target.take() {
+                target.pass.delete(ctx);
+                target.color_img.delete();
+                target.depth_img.delete();
+            }
+            self.target = Some(Target::new(ctx, screen_width, screen_height));
+        }
+        let pass = self.target.as_ref().unwrap().pass;
+
+        let (width, height) = self.render_size(ctx);
+        ctx.begin_pass(pass, action);
+        // keep the picture in the texture rows sampled from by present, whatever the origin
+        match ctx.render_target_origin() {
+            RenderTargetOrigin::TopLeft => {
+                ctx.apply_viewport(0, screen_height as i32 - height, width, height)
+            }
+            RenderTargetOrigin::BottomLeft => ctx.apply_viewport(0, 0, width, height),
+        }
+    }
+
+    /// End the scene pass and draw it stretched over the screen. The default pass is
+    /// left open, to draw on top at full resolution, and should be ended by the caller.
+    pub fn present(&mut self, ctx: &mut Context) {
+        ctx.end_render_pass();
+        ctx.begin_default_pass(PassAction::Nothing);
+
+        let target = match &self.target {
+            Some(target) => target,
+            None => return,
+        };
+        let (width, height) = self.render_size(ctx);
+        let (texture_width, texture_height) = (
+            target.color_img.width as f32,
+            target.color_img.height as f32,
+        );
+        self.blit.draw_with_uniforms(
+            ctx,
+            target.color_img,
+            &shader::Uniforms {
+                uv_scale: (width as f32 / texture_width, height as f32 / texture_height),
+                // half a texel in, linear filtering would bleed the unused part of the texture
+                uv_max: (
+                    (width as f32 - 0.5) / texture_width,
+                    (height as f32 - 0.5) / texture_height,
+                ),
+            },
+        );
+    }
+}
+
+impl Target {
+    fn new(ctx: &mut Context, width: u32, height: u32) -> Target {
+        let color_img = Texture::new_render_texture(RenderTextureParams {
+            width,
+            height,
+            format: PixelFormat::RGBA8,
+            ..Default::default()
+        });
+        let depth_img = Texture::new_render_texture(RenderTextureParams {
+            width,
+            height,
+            format: PixelFormat::Depth,
+            ..Default::default()
+        });
+        let pass = RenderPass::new(ctx, color_img, depth_img);
+        Target {
+            color_img,
+            depth_img,
+            pass,
+        }
+    }
+}
+
+mod shader {
+    use crate::graphics::*;
+
+    pub const FRAGMENT: &str = r#"#version 100
+    precision mediump float;
+
+    varying vec2 uv;
+
+    uniform sampler2D source;
+    uniform vec2 uv_scale;
+    uniform vec2 uv_max;
+
+    void main() {
+        gl_FragColor = texture2D(source, min(uv * uv_scale, uv_max));
+    }"#;
+
+    pub const META: ShaderMeta = ShaderMeta {
+        images: &["source"],
+        uniforms: UniformBlockLayout {
+            uniforms: &[
+                ("uv_scale", UniformType::Float2),
+                ("uv_max", UniformType::Float2),
+            ],
+        },
+        shared_uniforms: &[],
+    };
+
+    #[repr(C)]
+    pub struct Uniforms {
+        pub uv_scale: (f32, f32),
+        pub uv_max: (f32, f32),
+    }
+}
@@ -0,0 +1,523 @@
+use crate::graphics::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShaderStage {
+    Vertex,
+    Fragment,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagnosticSeverity {
+    Error,
+    Warning,
+}
+
+/// A problem in user shaders, found by `ShaderSandbox` or reported by the driver
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShaderDiagnostic {
+    pub severity: DiagnosticSeverity,
+    /// `None` for problems of the program as a whole: link errors, `ShaderMeta` limits
+    pub stage: Option<ShaderStage>,
+    /// 1-based line of the stage's source, when known
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+/// Checks and limits for shaders from untrusted sources, mods or an in-game editor,
+/// run before the sources reach the driver by `Shader::new_sandboxed`.
+///
+/// The checks work on tokens, not on a full GLSL parse, and are strict rather than
+/// clever: `while` and `do` loops are rejected, `for` loops need the
+/// `for (int i = 0; i < 8; i++) { ... }` form with numbers as bounds, so the work
+/// per vertex or fragment is known, and macros, which would hide code from the
+/// checks, are not allowed. Loops in functions are counted where they are written,
+/// not where the functions are called.
+#[derive(Debug, Clone)]
+pub struct ShaderSandbox {
+    /// Bytes of each stage's source
+    pub max_source_len: usize,
+    /// Bytes of the uniforms of `ShaderMeta::uniforms` and `ShaderMeta::shared_uniforms`
+    pub max_uniform_bytes: usize,
+    /// Capped by `Features::max_images`
+    pub max_images: usize,
+    /// Runs of the innermost body of nested loops, the iterations of all the loops
+    /// around it multiplied
+    pub max_loop_iterations: u64,
+    /// Names accepted in `#extension` directives, none by default
+    pub extensions: Vec<String>,
+}
+
+impl Default for ShaderSandbox {
+    fn default() -> ShaderSandbox {
+        ShaderSandbox {
+            max_source_len: 16 * 1024,
+            // the 16 vec4 GLSL ES guarantees to fragment shaders
+            max_uniform_bytes: 256,
+            max_images: 8,
+            max_loop_iterations: 1024,
+            extensions: vec![],
+        }
+    }
+}
+
+impl ShaderSandbox {
+    /// Diagnostics of the sandbox's checks, without compiling anything. Errors make
+    /// `Shader::new_sandboxed` fail, warnings point at code that compiles but is
+    /// likely a mistake, like uniforms missing from `meta` that are never set.
+    pub fn validate(
+        &self,
+        ctx: &Context,
+        vertex_shader: &str,
+        fragment_shader: &str,
+        meta: &ShaderMeta,
+    ) -> Vec<ShaderDiagnostic> {
+        let mut diagnostics = vec![];
+
+        let shared = meta.shared_uniforms.iter().map(|block| &block.layout);
+        let uniforms: Vec<&(&str, UniformType)> = std::iter::once(&meta.uniforms)
+            .chain(shared)
+            .flat_map(|layout| layout.uniforms.iter())
+            .collect();
+        let uniform_bytes: usize = uniforms
+            .iter()
+            .map(|(name, ty)| ty.size(glsl_array_len(name)))
+            .sum();
+        if uniform_bytes > self.max_uniform_bytes {
+            diagnostics.push(error(
+                None,
+                None,
+                format!(
+                    "uniforms take {} bytes, more than the {} allowed",
+                    uniform_bytes, self.max_uniform_bytes
+                ),
+            ));
+        }
+        let max_images = self.max_images.min(ctx.features().max_images as usize);
+        if meta.images.len() > max_images {
+            diagnostics.push(error(
+                None,
+                None,
+                format!(
+                    "{} images, more than the {} allowed",
+                    meta.images.len(),
+                    max_images
+                ),
+            ));
+        }
+
+        let mut known: Vec<&str> = vec!["push_constants"];
+        let names = uniforms.iter().map(|(name, _)| *name);
+        known.extend(names.chain(meta.images.iter().copied()).map(glsl_base_name));
+
+        let stages = [
+            (ShaderStage::Vertex, vertex_shader),
+            (ShaderStage::Fragment, fragment_shader),
+        ];
+        for (stage, source) in stages.iter().copied() {
+            self.check_stage(stage, source, &known, &mut diagnostics);
+        }
+        diagnostics
+    }
+
+    fn check_stage(
+        &self,
+        stage: ShaderStage,
+        source: &str,
+        known: &[&str],
+        diagnostics: &mut Vec<ShaderDiagnostic>,
+    ) {
+        let stage = Some(stage);
+        if source.len() > self.max_source_len {
+            let message = format!(
+                "{} bytes of source, more than the {} allowed",
+                source.len(),
+                self.max_source_len
+            );
+            diagnostics.push(error(stage, None, message));
+            return;
+        }
+
+        let source = strip_comments(source);
+        let mut code = String::with_capacity(source.len());
+        for (n, line) in source.lines().enumerate() {
+            let directive = match line.trim_start().strip_prefix('#') {
+                Some(directive) => directive,
+                None => {
+                    code.push_str(line);
+                    code.push('\n');
+                    continue;
+                }
+            };
+            // kept as an empty line, so tokens keep their line numbers
+            code.push('\n');
+            let mut words = directive.split_whitespace();
+            match words.next().unwrap_or("") {
+                "" | "version" | "if" | "ifdef" | "ifndef" | "elif" | "else" | "endif" => {}
+                "extension" => {
+                    let name = words.next().unwrap_or("");
+                    if !self.extensions.iter().any(|allowed| allowed == name) {
+                        let message = format!("extension `{}` is not allowed", name);
+                        diagnostics.push(error(stage, Some(n + 1), message));
+                    }
+                }
+                other => {
+                    let message = format!("`#{}` is not allowed", other);
+                    diagnostics.push(error(stage, Some(n + 1), message));
+                }
+            }
+        }
+
+        let tokens = tokens(&code);
+        let words: Vec<&str> = tokens.iter().map(|token| token.text).collect();
+        let mut depth: usize = 0;
+        // body depth, iterations of the body and counter of the loops around the token
+        let mut loops: Vec<(usize, u64, &str)> = vec![];
+        let mut at = 0;
+        while at < tokens.len() {
+            let line = Some(tokens[at].line);
+            match tokens[at].text {
+                "{" => depth += 1,
+                "}" => {
+                    depth = depth.saturating_sub(1);
+                    while loops.last().map_or(false, |(body, _, _)| *body > depth) {
+                        loops.pop();
+                    }
+                }
+                "while" | "do" => {
+                    let message = "only `for` loops with numbers as bounds are allowed";
+                    diagnostics.push(error(stage, line, message.to_string()));
+                }
+                "for" => match for_loop(&words[at..]) {
+                    Ok((iterations, counter, header)) => {
+                        let outer = loops.last().map_or(1, |(_, iterations, _)| *iterations);
+                        let total = outer.saturating_mul(iterations);
+                        if total > self.max_loop_iterations {
+                            let message = format!(
+                                "the loop body runs {} times, more than the {} allowed",
+                                total, self.max_loop_iterations
+                            );
+                            diagnostics.push(error(stage, line, message));
+                        }
+                        loops.push((depth + 1, total, counter));
+                        // to the body's `{`
+                        at += header;
+                        continue;
+                    }
+                    Err(message) => diagnostics.push(error(stage, line, message.to_string())),
+                },
+                "uniform" => {
+                    for name in uniform_names(&words[at + 1..]) {
+                        if !known.contains(&name) {
+                            diagnostics.push(ShaderDiagnostic {
+                                severity: DiagnosticSeverity::Warning,
+                                stage,
+                                line,
+                                message: format!(
+                                    "uniform `{}` is not in the ShaderMeta and is never set",
+                                    name
+                                ),
+                            });
+                        }
+                    }
+                }
+                word if word.starts_with("_mq_") => {
+                    let message = format!("`{}` is reserved", word);
+                    diagnostics.push(error(stage, line, message));
+                }
+                word if loops.iter().any(|(_, _, counter)| *counter == word) => {
+                    let before = if at > 0 { words[at - 1] } else { "" };
+                    let after = words.get(at + 1).copied().unwrap_or("");
+                    let assigned = ["=", "+=", "-=", "*=", "/=", "++", "--"].contains(&after);
+                    if assigned || before == "++" || before == "--" {
+                        let message = format!("loop counter `{}` changed in the loop", word);
+                        diagnostics.push(error(stage, line, message));
+                    }
+                }
+                _ => {}
+            }
+            at += 1;
+        }
+    }
+}
+
+impl Shader {
+    /// `Shader::new` for user-supplied sources: checked by `sandbox`, then compiled and
+    /// linked without panicking, the driver's info log split into diagnostics.
+    /// Warnings don't fail, `ShaderSandbox::validate` lists them.
+    pub fn new_sandboxed(
+        ctx: &mut Context,
+        sandbox: &ShaderSandbox,
+        vertex_shader: &str,
+        fragment_shader: &str,
+        meta: ShaderMeta,
+    ) -> Result<Shader, Vec<ShaderDiagnostic>> {
+        let mut diagnostics = sandbox.validate(ctx, vertex_shader, fragment_shader, &meta);
+        let failed = diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.severity == DiagnosticSeverity::Error);
+        if failed {
+            return Err(diagnostics);
+        }
+        Shader::try_new(ctx, vertex_shader, fragment_shader, meta).map_err(|compile_error| {
+            let (stage, log) = match compile_error {
+                ShaderCompileError::Vertex(log) => (Some(ShaderStage::Vertex), log),
+                ShaderCompileError::Fragment(log) => (Some(ShaderStage::Fragment), log),
+                ShaderCompileError::Link(log) => (None, log),
+            };
+            diagnostics.extend(driver_diagnostics(stage, &log));
+            diagnostics
+        })
+    }
+}
+
+fn error(stage: Option<ShaderStage>, line: Option<usize>, message: String) -> ShaderDiagnostic {
+    ShaderDiagnostic {
+        severity: DiagnosticSeverity::Error,
+        stage,
+        line,
+        message,
+    }
+}
+
+/// `"bones[32]"` to `"bones"`
+fn glsl_base_name(name: &str) -> &str {
+    name.find('[').map_or(name, |open| &name[..open])
+}
+
+/// `"bones[32]"` to 32, 1 for other names
+fn glsl_array_len(name: &str) -> usize {
+    name.find('[')
+        .and_then(|open| name[open + 1..].strip_suffix(']'))
+        .and_then(|len| len.parse().ok())
+        .unwrap_or(1)
+}
+
+/// Comments replaced by a space, the line breaks of block comments kept
+fn strip_comments(source: &str) -> String {
+    let mut stripped = String::with_capacity(source.len());
+    let mut chars = source.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('/', Some('/')) => {
+                while chars.peek().map_or(false, |c| *c != '\n') {
+                    chars.next();
+                }
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut previous = ' ';
+                while let Some(c) = chars.next() {
+                    if c == '\n' {
+                        stripped.push('\n');
+                    }
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+                stripped.push(' ');
+            }
+            _ => stripped.push(c),
+        }
+    }
+    stripped
+}
+
+struct Token<'a> {
+    text: &'a str,
+    /// 1-based
+    line: usize,
+}
+
+const OPERATORS: &[&str] = &[
+    "++", "--", "+=", "-=", "*=", "/=", "<=", ">=", "==", "!=", "&&", "||", "^^",
+];
+
+/// Identifiers, keywords and numbers, swizzles and fields included: `v.xy` is one token
+fn tokens(code: &str) -> Vec<Token<'_>> {
+    let is_word = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '.';
+    let mut tokens = vec![];
+    for (n, line) in code.lines().enumerate() {
+        let mut rest = line.trim_start();
+        while let Some(c) = rest.chars().next() {
+            let len = if is_word(c) {
+                rest.find(|c| !is_word(c)).unwrap_or(rest.len())
+            } else if OPERATORS.iter().any(|op| rest.starts_with(op)) {
+                2
+            } else {
+                c.len_utf8()
+            };
+            tokens.push(Token {
+                text: &rest[..len],
+                line: n + 1,
+            });
+            rest = rest[len..].trim_start();
+        }
+    }
+    tokens
+}
+
+/// Iterations, counter and tokens up to the body of the loop starting at `words[0]`
+fn for_loop<'a>(words: &[&'a str]) -> Result<(u64, &'a str, usize), &'static str> {
+    const FORM: &str =
+        "loops need the form `for (int i = 0; i < 8; i++) { ... }`, with numbers as bounds";
+
+    let mut at = 1;
+    let mut next = || {
+        let word = words.get(at).copied().unwrap_or("");
+        at += 1;
+        word
+    };
+    let number = |word: &str| word.parse::<f64>().ok().filter(|n| n.is_finite());
+    let signed = |next: &mut dyn FnMut() -> &'a str| match next() {
+        "-" => number(next()).map(|n| -n),
+        word => number(word),
+    };
+
+    if next() != "(" {
+        return Err(FORM);
+    }
+    let mut ty = next();
+    if ty == "lowp" || ty == "mediump" || ty == "highp" {
+        ty = next();
+    }
+    let counter = next();
+    if (ty != "int" && ty != "float") || next() != "=" {
+        return Err(FORM);
+    }
+    let start = signed(&mut next).ok_or(FORM)?;
+    if next() != ";" || next() != counter {
+        return Err(FORM);
+    }
+    let comparison = next();
+    let end = signed(&mut next).ok_or(FORM)?;
+    if next() != ";" {
+        return Err(FORM);
+    }
+    let step = match (next(), next()) {
+        (word, "++") | ("++", word) if word == counter => 1.,
+        (word, "--") | ("--", word) if word == counter => -1.,
+        (word, "+=") if word == counter => signed(&mut next).ok_or(FORM)?,
+        (word, "-=") if word == counter => -signed(&mut next).ok_or(FORM)?,
+        _ => return Err(FORM),
+    };
+    if next() != ")" {
+        return Err(FORM);
+    }
+    if next() != "{" {
+        return Err("the loop body needs braces");
+    }
+    let header = at - 1;
+
+    const ENDLESS: &str = "the loop never reaches its bound";
+    let steps = (end - start) / step;
+    let iterations = match comparison {
+        "<" | "<=" if step < 0. => return Err(ENDLESS),
+        ">" | ">=" if step > 0. => return Err(ENDLESS),
+        "<" | ">" => steps.ceil(),
+        "<=" | ">=" => steps.floor() + 1.,
+        "!=" if steps.fract() == 0. && steps >= 0. => steps,
+        "!=" => return Err(ENDLESS),
+        _ => return Err(FORM),
+    };
+    Ok((iterations.max(0.) as u64, counter, header))
+}
+
+/// Names declared by a `uniform` declaration, `words` following the keyword
+fn uniform_names<'a>(words: &[&'a str]) -> Vec<&'a str> {
+    let declaration = words.split(|word| *word == ";").next().unwrap_or(&[]);
+    let precisions = ["lowp", "mediump", "highp"];
+    let mut words = declaration
+        .iter()
+        .copied()
+        .skip_while(|word| precisions.contains(word));
+    // the type
+    words.next();
+
+    let mut names = vec![];
+    let mut expect_name = true;
+    let mut brackets = 0;
+    for word in words {
+        match word {
+            "[" => brackets += 1,
+            "]" => brackets -= 1,
+            "," if brackets == 0 => expect_name = true,
+            name if expect_name => {
+                names.push(name);
+                expect_name = false;
+            }
+            _ => {}
+        }
+    }
+    names
+}
+
+/// One diagnostic per line of the driver's info log
+fn driver_diagnostics(stage: Option<ShaderStage>, log: &str) -> Vec<ShaderDiagnostic> {
+    let mut diagnostics: Vec<ShaderDiagnostic> = log
+        .lines()
+        .map(|line| line.trim_matches(|c: char| c == '\0' || c.is_whitespace()))
+        .filter(|line| !line.is_empty())
+        .map(|line| driver_diagnostic(stage, line))
+        .collect();
+    if diagnostics.is_empty() {
+        let message = "rejected by the driver, without a log".to_string();
+        diagnostics.push(error(stage, None, message));
+    }
+    diagnostics
+}
+
+/// A line of an info log, in the formats of the common drivers:
+/// `ERROR: 0:12: 'x' : undeclared identifier` (ANGLE, AMD, Intel),
+/// `0:12(5): error: ...` (Mesa) or `0(12) : error C0000: ...` (NVIDIA)
+fn driver_diagnostic(stage: Option<ShaderStage>, line: &str) -> ShaderDiagnostic {
+    let starts_with = |text: &str, prefix: &str| {
+        text.get(..prefix.len())
+            .map_or(false, |start| start.eq_ignore_ascii_case(prefix))
+    };
+    let digits = |text: &str| {
+        text.find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(text.len())
+    };
+
+    let mut severity = DiagnosticSeverity::Error;
+    let mut message = line;
+    if starts_with(message, "warning:") {
+        severity = DiagnosticSeverity::Warning;
+        message = message["warning:".len()..].trim_start();
+    } else if starts_with(message, "error:") {
+        message = message["error:".len()..].trim_start();
+    }
+
+    // source string, then line
+    let mut number = None;
+    let source_string = digits(message);
+    let separator = message[source_string..].chars().next();
+    if source_string > 0 && (separator == Some(':') || separator == Some('(')) {
+        let rest = &message[source_string + 1..];
+        let len = digits(rest);
+        if len > 0 {
+            number = rest[..len].parse().ok();
+            // past Mesa's `(column)` or NVIDIA's `)`
+            if let Some(colon) = rest[len..].find(':') {
+                message = rest[len + colon + 1..].trim_start();
+            }
+        }
+    }
+
+    if starts_with(message, "warning") {
+        severity = DiagnosticSeverity::Warning;
+    }
+    if starts_with(message, "warning") || starts_with(message, "error") {
+        if let Some(colon) = message.find(':') {
+            message = message[colon + 1..].trim_start();
+        }
+    }
+
+    ShaderDiagnostic {
+        severity,
+        stage,
+        line: number,
+        message: message.to_string(),
+    }
+}
@@ -0,0 +1,246 @@
+use crate::{graphics::*, ClipSpace};
+
+/// Depth render pass seen from a light, for shadows: draw the shadow casters into it
+/// with `begin_pass`, then sample it in the lit shaders with `SAMPLE_GLSL`.
+///
+/// ```ignore
+/// shadow_map.set_directional_light([-1., -2., -1.], [0., 0., 0.], 10.);
+/// shadow_map.begin_pass(ctx);
+/// // casters, with pipelines made with ShadowMap::pipeline_params and DEPTH_FRAGMENT,
+/// // their vertex shaders output light_view_proj * model * pos
+/// ctx.end_render_pass();
+///
+/// ctx.begin_default_pass(PassAction::default());
+/// // receivers, with shadow_map.texture() in their images, the uniforms
+/// // shadow_matrix * model and size, and shadow(shadow_pos) in the fragment shader
+/// ```
+///
+/// GLSL 100 has no shadow samplers, so depths are compared in the shader rather than
+/// by the texture unit, over 4 texels for softer edges.
+///
+/// The shadow map keeps the usual depth direction under `Context::set_reversed_z`,
+/// and follows its clip space depth range from the time it is created.
+///
+/// The textures and the pass are ordinary resources: after a context loss they are
+/// among the lost resources and the shadow map has to be created again.
+pub struct ShadowMap {
+    depth: Texture,
+    /// Unused, render passes need a color attachment
+    color: Texture,
+    pass: RenderPass,
+    /// Of the context at creation, never reversed Z: these depths are only compared
+    /// in `SAMPLE_GLSL`
+    clip_space: ClipSpace,
+    /// Light view and projection, column major, from `set_directional_light` or any
+    /// other projection, e.g. a perspective one for a spot light, made with
+    /// `ClipSpace::perspective` without reversed Z under `Context::set_reversed_z`
+    pub light_view_proj: [f32; 16],
+}
+
+impl ShadowMap {
+    /// Fragment shader for the pipelines drawing into the shadow map, only depth matters.
+    pub const DEPTH_FRAGMENT: &'static str = r#"#version 100
+void main() {
+    gl_FragColor = vec4(1.0);
+}"#;
+
+    /// Defines `float shadow(vec4 shadow_pos)` for fragment shaders, 0 in the shadow,
+    /// 1 in the light, for a position in the light's clip space transformed by
+    /// `shadow_matrix`. Reads the image `shadow_map` and the `UniformType::Float1`
+    /// uniform `shadow_map_size`, `ShadowMap::size`.
+    pub const SAMPLE_GLSL: &'static str = r#"
+#ifdef GL_FRAGMENT_PRECISION_HIGH
+uniform highp sampler2D shadow_map;
+#else
+uniform mediump sampler2D shadow_map;
+#endif
+uniform float shadow_map_size;
+
+float shadow(vec4 shadow_pos) {
+    vec3 pos = shadow_pos.xyz / shadow_pos.w;
+    // beyond the light's far plane
+    if (pos.z > 1.0) {
+        return 1.0;
+    }
+    float lit = 0.0;
+    for (int x = 0; x < 2; x++) {
+        for (int y = 0; y < 2; y++) {
+            vec2 offset = (vec2(float(x), float(y)) - 0.5) / shadow_map_size;
+            lit += step(pos.z, texture2D(shadow_map, pos.xy + offset).r);
+        }
+    }
+    return lit * 0.25;
+}
+"#;
+
+    /// Square shadow map of `size`x`size` texels.
+    pub fn new(ctx: &mut Context, size: u32) -> ShadowMap {
+        let depth = Texture::new_render_texture(RenderTextureParams {
+            width: size,
+            height: size,
+            format: PixelFormat::Depth,
+            ..Default::default()
+        });
+        // depths are compared before filtering, and WebGL only guarantees nearest
+        // sampling of depth textures
+        depth.set_filter(FilterMode::Nearest as i32);
+        let color = Texture::new_render_texture(RenderTextureParams {
+            width: size,
+            height: size,
+            format: PixelFormat::RGBA8,
+            ..Default::default()
+        });
+        let pass = RenderPass::new(ctx, color, depth);
+
+        ShadowMap {
+            depth,
+            color,
+            pass,
+            clip_space: ClipSpace {
+                reversed_z: false,
+                ..ctx.clip_space()
+            },
+            light_view_proj: IDENTITY,
+        }
+    }
+
+    /// Parameters for the pipelines of the casters: depth test and write, and a depth
+    /// offset against surfaces shadowing themselves, "shadow acne".
+    pub fn pipeline_params() -> PipelineParams {
+        PipelineParams {
+            depth_test: Comparison::LessOrEqual,
+            depth_write: true,
+            depth_write_offset: Some((2., 4.)),
+            ..Default::default()
+        }
+    }
+
+    /// Begin the pass drawing the casters, depths cleared to the farthest.
+    pub fn begin_pass(&self, ctx: &mut Context) {
+        ctx.begin_pass(
+            self.pass,
+            PassAction::Clear {
+                color: None,
+                depth: Some(1.),
+                stencil: None,
+            },
+        );
+    }
+
+    pub fn pass(&self) -> RenderPass {
+        self.pass
+    }
+
+    /// The depth texture, to put in `Bindings::images` for `shadow_map`.
+    pub fn texture(&self) -> Texture {
+        self.depth
+    }
+
+    pub fn size(&self) -> f32 {
+        self.depth.width as f32
+    }
+
+    /// Light shining along `direction` over a sphere of `radius` around `center`,
+    /// like the sun over the visible part of a scene.
+    pub fn set_directional_light(&mut self, direction: [f32; 3], center: [f32; 3], radius: f32) {
+        let direction = normalize(direction);
+        let eye = [
+            center[0] - direction[0] * radius,
+            center[1] - direction[1] * radius,
+            center[2] - direction[2] * radius,
+        ];
+        // any up vector not along the direction
+        let up = if direction[1].abs() > 0.99 {
+            [0., 0., 1.]
+        } else {
+            [0., 1., 0.]
+        };
+        let view = look_at(eye, center, up);
+        let proj = self
+            .clip_space
+            .orthographic(-radius, radius, -radius, radius, 0., radius * 2.);
+        self.light_view_proj = mul(&proj, &view);
+    }
+
+    /// `light_view_proj` followed by the mapping from clip space to shadow map
+    /// coordinates and depth, for the `shadow_pos` of `SAMPLE_GLSL`. Follows
+    /// `Context::render_target_origin`.
+    pub fn shadow_matrix(&self, ctx: &Context) -> [f32; 16] {
+        let y = match ctx.render_target_origin() {
+            crate::conf::RenderTargetOrigin::TopLeft => -0.5,
+            crate::conf::RenderTargetOrigin::BottomLeft => 0.5,
+        };
+        // clip space depths are already the depth buffer's with 0..1 clip control
+        let z = if self.clip_space.depth_zero_to_one {
+            (1., 0.)
+        } else {
+            (0.5, 0.5)
+        };
+        #[rustfmt::skip]
+        let bias = [
+            0.5, 0., 0., 0.,
+            0., y, 0., 0.,
+            0., 0., z.0, 0.,
+            0.5, 0.5, z.1, 1.,
+        ];
+        mul(&bias, &self.light_view_proj)
+    }
+
+    /// Free the textures and the pass.
+    pub fn delete(&self, ctx: &mut Context) {
+        self.pass.delete(ctx);
+        self.depth.delete();
+        self.color.delete();
+    }
+}
+
+#[rustfmt::skip]
+const IDENTITY: [f32; 16] = [
+    1., 0., 0., 0.,
+    0., 1., 0., 0.,
+    0., 0., 1., 0.,
+    0., 0., 0., 1.,
+];
+
+/// `a * b` of column major matrices
+fn mul(a: &[f32; 16], b: &[f32; 16]) -> [f32; 16] {
+    let mut m = [0.; 16];
+    for col in 0..4 {
+        for row in 0..4 {
+            m[col * 4 + row] = (0..4).map(|k| a[k * 4 + row] * b[col * 4 + k]).sum();
+        }
+    }
+    m
+}
+
+fn normalize(v: [f32; 3]) -> [f32; 3] {
+    let len = dot(v, v).sqrt();
+    [v[0] / len, v[1] / len, v[2] / len]
+}
+
+fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+/// Right handed view matrix, as `gluLookAt`
+fn look_at(eye: [f32; 3], target: [f32; 3], up: [f32; 3]) -> [f32; 16] {
+    let f = normalize([target[0] - eye[0], target[1] - eye[1], target[2] - eye[2]]);
+    let s = normalize(cross(f, up));
+    let u = cross(s, f);
+    #[rustfmt::skip]
+    let view = [
+        s[0], u[0], -f[0], 0.,
+        s[1], u[1], -f[1], 0.,
+        s[2], u[2], -f[2], 0.,
+        -dot(s, eye), -dot(u, eye), dot(f, eye), 1.,
+    ];
+    view
+}
@@ -0,0 +1,120 @@
+//! Skeletal animation: bone matrices for vertex shaders and the GLSL to blend them.
+//!
+//! Every vertex has up to 4 bone indices, `joints`, and the weights of those bones,
+//! `weights`, summing to 1. Indices can be `VertexFormat::Byte4` or `VertexFormat::Short4`,
+//! weights `VertexFormat::Float4`. The vertex shader moves the vertex by the
+//! weighted sum of the bone matrices, computed on the CPU for every frame.
+//!
+//! The matrices reach the shader in one of two ways, with the same `skin_matrix` function:
+//!
+//! - A uniform array, with `UNIFORMS_GLSL`. Works everywhere, but uniform space is small:
+//!   GLES 2 and WebGL 1 only guarantee 128 `vec4` to vertex shaders, about 28 bones
+//!   next to the other uniforms.
+//! - A float texture, with `BoneTexture` and `TEXTURE_GLSL`. Thousands of bones, but needs
+//!   `Features::texture_float` and `Features::max_vertex_images` above 0.
+//!
+//! The GLSL goes in the vertex shader source before `main`, e.g. with `format!`:
+//!
+//! ```ignore
+//! attribute vec3 pos;
+//! attribute vec4 joints;
+//! attribute vec4 weights;
+//! uniform mat4 mvp;
+//! #define MAX_BONES 24
+//! // skinning::UNIFORMS_GLSL here
+//! void main() {
+//!     gl_Position = mvp * skin_matrix(joints, weights) * vec4(pos, 1);
+//! }
+//! ```
+
+use crate::graphics::*;
+
+/// Defines `mat4 skin_matrix(vec4 joints, vec4 weights)` reading the uniform
+/// `mat4 bones[MAX_BONES]`, `MAX_BONES` has to be `#define`d before.
+/// In the `ShaderMeta` the array is `("bones[24]", UniformType::Mat4)` for 24 bones,
+/// and the uniform struct has a `[[f32; 16]; 24]` field at its place.
+pub const UNIFORMS_GLSL: &str = r#"
+uniform mat4 bones[MAX_BONES];
+
+mat4 skin_matrix(vec4 joints, vec4 weights) {
+    return weights.x * bones[int(joints.x)]
+         + weights.y * bones[int(joints.y)]
+         + weights.z * bones[int(joints.z)]
+         + weights.w * bones[int(joints.w)];
+}
+"#;
+
+/// Defines `mat4 skin_matrix(vec4 joints, vec4 weights)` reading the image
+/// `bone_texture` filled by `BoneTexture::update`, and the uniform `bone_texture_height`,
+/// `BoneTexture::height` as a `UniformType::Float1`.
+pub const TEXTURE_GLSL: &str = r#"
+// lowp by default in vertex shaders, and texture2D returns the precision of the sampler
+uniform highp sampler2D bone_texture;
+uniform float bone_texture_height;
+
+mat4 bone_matrix(float bone) {
+    float v = (bone + 0.5) / bone_texture_height;
+    return mat4(
+        texture2D(bone_texture, vec2(0.125, v)),
+        texture2D(bone_texture, vec2(0.375, v)),
+        texture2D(bone_texture, vec2(0.625, v)),
+        texture2D(bone_texture, vec2(0.875, v)));
+}
+
+mat4 skin_matrix(vec4 joints, vec4 weights) {
+    return weights.x * bone_matrix(joints.x)
+         + weights.y * bone_matrix(joints.y)
+         + weights.z * bone_matrix(joints.z)
+         + weights.w * bone_matrix(joints.w);
+}
+"#;
+
+/// Float texture holding bone matrices for `TEXTURE_GLSL`, one bone per row:
+/// row `n` is bone `n`, its 4 RGBA32F pixels the columns of the matrix.
+/// Several skeletons can share it by giving each a range of rows, their
+/// vertices' indices then start at their first row.
+///
+/// The texture is an ordinary texture: after a context loss it is among the
+/// lost resources and has to be created again.
+pub struct BoneTexture {
+    texture: Texture,
+}
+
+impl BoneTexture {
+    /// Texture for up to `max_bones` bones, `None` without `Features::texture_float`
+    /// or without texture units in vertex shaders.
+    pub fn new(ctx: &Context, max_bones: u32) -> Option<BoneTexture> {
+        let features = ctx.features();
+        if !features.texture_float || features.max_vertex_images == 0 {
+            return None;
+        }
+
+        let texture = Texture::new_render_texture(RenderTextureParams {
+            width: 4,
+            height: max_bones,
+            format: PixelFormat::RGBA32F,
+            ..Default::default()
+        });
+        // float textures are not filterable without another extension on WebGL
+        texture.set_filter(FilterMode::Nearest as i32);
+        Some(BoneTexture { texture })
+    }
+
+    /// Overwrite the matrices of bones `first` and up, column major as
+    /// `glam::Mat4::to_cols_array` gives them.
+    pub fn update(&self, first: u32, bones: &[[f32; 16]]) {
+        let floats: Vec<f32> = bones.iter().flatten().copied().collect();
+        self.texture
+            .update_part_rgba32f(0, first, 4, bones.len() as u32, &floats);
+    }
+
+    /// The texture to put in `Bindings::images` for `bone_texture`.
+    pub fn texture(&self) -> Texture {
+        self.texture
+    }
+
+    /// Value of the `bone_texture_height` uniform.
+    pub fn height(&self) -> f32 {
+        self.texture.height as f32
+    }
+}
@@ -0,0 +1,170 @@
+use crate::{graphics::*, Color};
+
+const MAX_SPRITES: usize = 4096;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+struct Vertex {
+    pos: (f32, f32),
+    uv: (f32, f32),
+    color: (f32, f32, f32, f32),
+}
+
+/// Draws lots of textured, tinted quads with few draw calls.
+/// Coordinates are in pixels, with the origin in the top-left corner of the screen.
+/// Quads are queued until `flush`, the texture changes or the batch is full,
+/// so `flush` should be called before the end of the render pass.
+pub struct SpriteBatch {
+    pipeline: Pipeline,
+    bindings: Bindings,
+    vertices: Vec<Vertex>,
+    texture: Option<Texture>,
+}
+
+impl SpriteBatch {
+    pub fn new(ctx: &mut Context) -> SpriteBatch {
+        let vertex_buffer = Buffer::stream(
+            ctx,
+            BufferType::VertexBuffer,
+            MAX_SPRITES * 4 * std::mem::size_of::<Vertex>(),
+        );
+        let indices: Vec<u16> = (0..MAX_SPRITES as u16)
+            .flat_map(|i| {
+                let i = i * 4;
+                vec![i, i + 1, i + 2, i, i + 2, i + 3]
+            })
+            .collect();
+        let index_buffer = Buffer::immutable(ctx, BufferType::IndexBuffer, &indices);
+
+        let shader = Shader::new(ctx, shader::VERTEX, shader::FRAGMENT, shader::META);
+        let pipeline = Pipeline::with_params(
+            ctx,
+            &[BufferLayout::default()],
+            &[
+                VertexAttribute::new("pos", VertexFormat::Float2),
+                VertexAttribute::new("uv", VertexFormat::Float2),
+                VertexAttribute::new("color", VertexFormat::Float4),
+            ],
+            shader,
+            PipelineParams {
+                color_blend: Some((
+                    Equation::Add,
+                    BlendFactor::Value(BlendValue::SourceAlpha),
+                    BlendFactor::OneMinusValue(BlendValue::SourceAlpha),
+                )),
+                ..Default::default()
+            },
+        );
+
+        SpriteBatch {
+            pipeline,
+            bindings: Bindings {
+                vertex_buffers: vec![vertex_buffer],
+                index_buffer,
+                images: vec![],
+            },
+            vertices: Vec::with_capacity(MAX_SPRITES * 4),
+            texture: None,
+        }
+    }
+
+    /// Queue a quad at `x`,`y` of `w`x`h` pixels, showing the `uv_min`..`uv_max`
+    /// part of `texture`, multiplied by `color`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw(
+        &mut self,
+        ctx: &mut Context,
+        texture: Texture,
+        x: f32,
+        y: f32,
+        w: f32,
+        h: f32,
+        uv_min: (f32, f32),
+        uv_max: (f32, f32),
+        color: Color,
+    ) {
+        if self.texture != Some(texture) || self.vertices.len() == MAX_SPRITES * 4 {
+            self.flush(ctx);
+            self.texture = Some(texture);
+        }
+
+        let (u0, v0) = uv_min;
+        let (u1, v1) = uv_max;
+        let color = color.into();
+        #[rustfmt::skip]
+        self.vertices.extend_from_slice(&[
+            Vertex { pos: (x, y), uv: (u0, v0), color },
+            Vertex { pos: (x + w, y), uv: (u1, v0), color },
+            Vertex { pos: (x + w, y + h), uv: (u1, v1), color },
+            Vertex { pos: (x, y + h), uv: (u0, v1), color },
+        ]);
+    }
+
+    pub(crate) fn owns_buffer(&self, buffer: &Buffer) -> bool {
+        self.bindings.vertex_buffers.contains(buffer) || self.bindings.index_buffer == *buffer
+    }
+
+    /// Draw all queued quads into the current render pass.
+    pub fn flush(&mut self, ctx: &mut Context) {
+        let texture = match self.texture {
+            Some(texture) if !self.vertices.is_empty() => texture,
+            _ => return,
+        };
+
+        self.bindings.vertex_buffers[0].update(ctx, &self.vertices);
+        self.bindings.images = vec![texture];
+
+        ctx.apply_pipeline(&self.pipeline);
+        ctx.apply_bindings(&self.bindings);
+        ctx.apply_uniforms(&shader::Uniforms {
+            screen_size: ctx.screen_size(),
+        });
+        ctx.draw(0, (self.vertices.len() / 4 * 6) as i32, 1);
+
+        self.vertices.clear();
+    }
+}
+
+mod shader {
+    use crate::graphics::*;
+
+    pub const VERTEX: &str = r#"#version 100
+    attribute vec2 pos;
+    attribute vec2 uv;
+    attribute vec4 color;
+
+    uniform vec2 screen_size;
+
+    varying mediump vec2 texcoord;
+    varying lowp vec4 tint;
+
+    void main() {
+        vec2 ndc = pos / screen_size * 2.0 - 1.0;
+        gl_Position = vec4(ndc.x, -ndc.y, 0, 1);
+        texcoord = uv;
+        tint = color;
+    }"#;
+
+    pub const FRAGMENT: &str = r#"#version 100
+    varying mediump vec2 texcoord;
+    varying lowp vec4 tint;
+
+    uniform sampler2D tex;
+
+    void main() {
+        gl_FragColor = texture2D(tex, texcoord) * tint;
+    }"#;
+
+    pub const META: ShaderMeta = ShaderMeta {
+        images: &["tex"],
+        uniforms: UniformBlockLayout {
+            uniforms: &[("screen_size", UniformType::Float2)],
+        },
+        shared_uniforms: &[],
+    };
+
+    #[repr(C)]
+    pub struct Uniforms {
+        pub screen_size: (f32, f32),
+    }
+}
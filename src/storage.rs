@@ -0,0 +1,194 @@
+//! Persistent key-value storage for settings and save games.
+//!
+//! On wasm values live in the browser's LocalStorage, on native platforms every key
+//! is a file in the OS data directory: `$XDG_DATA_HOME/<app>` (or `~/.local/share/<app>`)
+//! on linux, `%APPDATA%\<app>` on windows and `~/Library/Application Support/<app>` on macos.
+//! `<app>` is the executable name.
+
+#[derive(Debug)]
+pub enum Error {
+    /// Browser's storage quota exceeded
+    QuotaExceeded,
+    /// No storage available, e.g. LocalStorage disabled by privacy settings
+    /// or no home directory found
+    Unavailable,
+    IOError(std::io::Error),
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Error {
+        Error::IOError(error)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use native::{get, remove, set};
+
+#[cfg(target_arch = "wasm32")]
+pub use wasm::{get, remove, set};
+
+#[cfg(not(target_arch = "wasm32"))]
+mod native {
+    use super::Error;
+
+    use std::path::PathBuf;
+
+    fn storage_dir() -> Result<PathBuf, Error> {
+        let home = || std::env::var_os("HOME").map(PathBuf::from);
+
+        let base = if cfg!(windows) {
+            std::env::var_os("APPDATA").map(PathBuf::from)
+        } else if cfg!(target_os = "macos") {
+            home().map(|home| home.join("Library").join("Application Support"))
+        } else {
+            std::env::var_os("XDG_DATA_HOME")
+                .map(PathBuf::from)
+                .or_else(|| home().map(|home| home.join(".local").join("share")))
+        };
+        let app = std::env::current_exe()
+            .ok()
+            .and_then(|exe| exe.file_stem().map(|name| name.to_owned()))
+            .ok_or(Error::Unavailable)?;
+
+        Ok(base.ok_or(Error::Unavailable)?.join(app))
+    }
+
+    /// Devices names on windows, whatever the case and extension: "nul.txt" is the device too
+    const RESERVED_NAMES: &[&str] = &[
+        "con", "prn", "aux", "nul", "com0", "com1", "com2", "com3", "com4", "com5", "com6", "com7",
+        "com8", "com9", "lpt0", "lpt1", "lpt2", "lpt3", "lpt4", "lpt5", "lpt6", "lpt7", "lpt8",
+        "lpt9",
+    ];
+
+    /// Keys may contain anything, so everything except [a-z0-9_-.] is escaped to keep
+    /// them valid file names. Capitals are escaped too, as "Save" and "save" are the same
+    /// file on windows and macos, and so are the first letter of device names and
+    /// trailing dots, which windows drops.
+    pub(super) fn file_name(key: &str) -> String {
+        let mut name = String::new();
+        for b in key.bytes() {
+            match b {
+                b'a'..=b'z' | b'0'..=b'9' | b'_' | b'-' | b'.' => name.push(b as char),
+                _ => name.push_str(&format!("%{:02x}", b)),
+            }
+        }
+        let stem = name.split('.').next().unwrap();
+        if RESERVED_NAMES.contains(&stem) {
+            name = format!("%{:02x}{}", name.as_bytes()[0], &name[1..]);
+        }
+        let dots = name.len() - name.trim_end_matches('.').len();
+        name.truncate(name.len() - dots);
+        for _ in 0..dots {
+            name.push_str("%2e");
+        }
+        name
+    }
+
+    fn key_path(key: &str) -> Result<PathBuf, Error> {
+        Ok(storage_dir()?.join(file_name(key)))
+    }
+
+    pub fn set(key: &str, data: &[u8]) -> Result<(), Error> {
+        let path = key_path(key)?;
+        std::fs::create_dir_all(path.parent().unwrap())?;
+
+        // write to a temporary file first so a crash never leaves a half-written save,
+        // "~" is always escaped in keys so this can't clash with another key
+        let mut tmp = path.clone().into_os_string();
+        tmp.push("~");
+        std::fs::write(&tmp, data)?;
+        std::fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+
+    pub fn get(key: &str) -> Result<Option<Vec<u8>>, Error> {
+        match std::fs::read(key_path(key)?) {
+            Ok(data) => Ok(Some(data)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    pub fn remove(key: &str) -> Result<(), Error> {
+        match std::fs::remove_file(key_path(key)?) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::native::file_name;
+
+    #[test]
+    fn file_names_differ_by_more_than_case() {
+        assert_eq!(file_name("save"), "save");
+        assert_eq!(file_name("Save"), "%53ave");
+        assert_ne!(
+            file_name("Save").to_lowercase(),
+            file_name("save").to_lowercase()
+        );
+    }
+
+    #[test]
+    fn device_names_are_escaped() {
+        assert_eq!(file_name("nul"), "%6eul");
+        assert_eq!(file_name("con.json"), "%63on.json");
+        assert_eq!(file_name("COM1"), "%43%4f%4d1");
+        assert_eq!(file_name("console"), "console");
+    }
+
+    #[test]
+    fn dots_are_kept() {
+        assert_eq!(file_name("a."), "a%2e");
+        assert_eq!(file_name(".."), "%2e%2e");
+        assert_eq!(file_name("a.b"), "a.b");
+        assert_eq!(file_name("a/b~"), "a%2fb%7e");
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod wasm {
+    use super::Error;
+
+    // same codes as in gl.js, 0 or positive values are success
+    const STORAGE_QUOTA_EXCEEDED: i32 = -1;
+    const STORAGE_UNAVAILABLE: i32 = -2;
+    const STORAGE_NOT_FOUND: i32 = -3;
+
+    fn check(code: i32) -> Result<(), Error> {
+        match code {
+            STORAGE_QUOTA_EXCEEDED => Err(Error::QuotaExceeded),
+            STORAGE_UNAVAILABLE => Err(Error::Unavailable),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn set(key: &str, data: &[u8]) -> Result<(), Error> {
+        check(unsafe {
+            sapp::storage_set(
+                key.as_ptr() as _,
+                key.len() as u32,
+                data.as_ptr() as _,
+                data.len() as u32,
+            )
+        })
+    }
+
+    pub fn get(key: &str) -> Result<Option<Vec<u8>>, Error> {
+        let len = unsafe { sapp::storage_len(key.as_ptr() as _, key.len() as u32) };
+        if len == STORAGE_NOT_FOUND {
+            return Ok(None);
+        }
+        check(len)?;
+
+        let mut data = vec![0u8; len as usize];
+        unsafe { sapp::storage_get(key.as_ptr() as _, key.len() as u32, data.as_mut_ptr() as _) };
+        Ok(Some(data))
+    }
+
+    pub fn remove(key: &str) -> Result<(), Error> {
+        check(unsafe { sapp::storage_remove(key.as_ptr() as _, key.len() as u32) })
+    }
+}
@@ -0,0 +1,269 @@
+//! Bitmap font rendering: glyphs are packed into an `Atlas` and drawn with a `SpriteBatch`.
+//!
+//! Glyphs come either from an AngelCode BMFont file (text format) with its page images,
+//! or from any rasterizer, e.g. fontdue, through `Font::add_glyph`.
+
+use std::collections::HashMap;
+
+use crate::{Atlas, AtlasRect, Color, Context, SpriteBatch};
+
+#[derive(Debug)]
+pub enum Error {
+    /// Malformed BMFont line, with its line number
+    Parse(usize),
+    /// A glyph refers to a page image that was not given
+    MissingPage(u32),
+    /// The page image is smaller than the glyph rects in it
+    InvalidPage(u32),
+    /// Not enough room in the atlas for all the glyphs
+    AtlasFull,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Glyph {
+    pub rect: AtlasRect,
+    /// From the pen position, at the top of the line, to the top-left corner of the bitmap
+    pub offset: (f32, f32),
+    /// How far the pen moves after this glyph
+    pub advance: f32,
+}
+
+/// RGBA8 image of a BMFont page
+#[derive(Debug, Clone, Copy)]
+pub struct Page<'a> {
+    pub width: u32,
+    pub height: u32,
+    pub bytes: &'a [u8],
+}
+
+pub struct Font {
+    atlas: Atlas,
+    glyphs: HashMap<char, Glyph>,
+    kerning: HashMap<(char, char), f32>,
+    line_height: f32,
+}
+
+impl Font {
+    /// Empty font, glyphs are added later with `add_glyph`.
+    pub fn new(ctx: &mut Context, atlas_size: u16, line_height: f32) -> Font {
+        Font {
+            atlas: Atlas::new(ctx, atlas_size),
+            glyphs: HashMap::new(),
+            kerning: HashMap::new(),
+            line_height,
+        }
+    }
+
+    /// Load an AngelCode BMFont in text format. `pages` are the decoded page images,
+    /// in the order of their `page id`.
+    pub fn from_bmfont(
+        ctx: &mut Context,
+        fnt: &str,
+        pages: &[Page],
+        atlas_size: u16,
+    ) -> Result<Font, Error> {
+        let mut font = Font::new(ctx, atlas_size, 0.);
+
+        for (n, line) in fnt.lines().enumerate() {
+            let mut tokens = line.split_whitespace();
+            let tag = match tokens.next() {
+                Some(tag) => tag,
+                None => continue,
+            };
+            let attributes: HashMap<&str, &str> =
+                tokens.filter_map(|token| split_attribute(token)).collect();
+            let int = |key: &str| -> Result<i32, Error> {
+                attributes
+                    .get(key)
+                    .and_then(|value| value.parse().ok())
+                    .ok_or(Error::Parse(n + 1))
+            };
+            let uint = |key: &str| -> Result<u32, Error> {
+                let value = int(key)?;
+                if value < 0 {
+                    return Err(Error::Parse(n + 1));
+                }
+                Ok(value as u32)
+            };
+
+            match tag {
+                "common" => font.line_height = int("lineHeight")? as f32,
+                "char" => {
+                    let character = std::char::from_u32(uint("id")?).ok_or(Error::Parse(n + 1))?;
+                    let page_id = uint("page")?;
+                    let page = pages
+                        .get(page_id as usize)
+                        .ok_or(Error::MissingPage(page_id))?;
+                    let (x, y) = (uint("x")?, uint("y")?);
+                    let (w, h) = (uint("width")?, uint("height")?);
+                    let right = x.checked_add(w).ok_or(Error::Parse(n + 1))?;
+                    let bottom = y.checked_add(h).ok_or(Error::Parse(n + 1))?;
+                    let page_len = (page.width as usize)
+                        .checked_mul(page.height as usize)
+                        .and_then(|len| len.checked_mul(4))
+                        .ok_or(Error::Parse(n + 1))?;
+                    if right > page.width || bottom > page.height || page.bytes.len() < page_len {
+                        return Err(Error::InvalidPage(page_id));
+                    }
+
+                    // within the page bytes from here on, so these fit in usize
+                    let (x, w, stride) = (x as usize, w as usize, page.width as usize);
+                    let mut bytes = Vec::with_capacity(w * h as usize * 4);
+                    for row in y as usize..bottom as usize {
+                        let start = (row * stride + x) * 4;
+                        bytes.extend_from_slice(&page.bytes[start..start + w * 4]);
+                    }
+                    font.add_glyph_rgba(
+                        character,
+                        w as u32,
+                        h,
+                        &bytes,
+                        (int("xoffset")? as f32, int("yoffset")? as f32),
+                        int("xadvance")? as f32,
+                    )?;
+                }
+                "kerning" => {
+                    let first = std::char::from_u32(int("first")? as u32);
+                    let second = std::char::from_u32(int("second")? as u32);
+                    if let (Some(first), Some(second)) = (first, second) {
+                        font.set_kerning(first, second, int("amount")? as f32);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(font)
+    }
+
+    /// Add a glyph rasterized elsewhere, `coverage` is one alpha byte per pixel,
+    /// as produced by fontdue and most rasterizers.
+    pub fn add_glyph(
+        &mut self,
+        character: char,
+        width: u32,
+        height: u32,
+        coverage: &[u8],
+        offset: (f32, f32),
+        advance: f32,
+    ) -> Result<(), Error> {
+        let bytes: Vec<u8> = coverage
+            .iter()
+            .flat_map(|alpha| vec![255, 255, 255, *alpha])
+            .collect();
+        self.add_glyph_rgba(character, width, height, &bytes, offset, advance)
+    }
+
+    /// Same as `add_glyph`, but with colored RGBA8 pixels.
+    pub fn add_glyph_rgba(
+        &mut self,
+        character: char,
+        width: u32,
+        height: u32,
+        bytes: &[u8],
+        offset: (f32, f32),
+        advance: f32,
+    ) -> Result<(), Error> {
+        let rect = self.atlas.allocate(width, height).ok_or(Error::AtlasFull)?;
+        if width != 0 && height != 0 {
+            self.atlas.upload(&rect, bytes);
+        }
+        self.glyphs.insert(
+            character,
+            Glyph {
+                rect,
+                offset,
+                advance,
+            },
+        );
+        Ok(())
+    }
+
+    /// Extra space between two characters, usually negative
+    pub fn set_kerning(&mut self, first: char, second: char, amount: f32) {
+        self.kerning.insert((first, second), amount);
+    }
+
+    pub fn glyph(&self, character: char) -> Option<&Glyph> {
+        self.glyphs.get(&character)
+    }
+
+    pub fn line_height(&self) -> f32 {
+        self.line_height
+    }
+
+    /// Width and height of `text` in pixels, drawn with the given scale.
+    pub fn measure(&self, text: &str, scale: f32) -> (f32, f32) {
+        let mut width: f32 = 0.;
+        self.layout(text, scale, |_, _, pen_x| width = width.max(pen_x));
+        let lines = text.split('\n').count();
+        (width, lines as f32 * self.line_height * scale)
+    }
+
+    /// Walks through the glyphs of `text`, calling `f` with every glyph, its top-left corner
+    /// and the pen position after it.
+    fn layout<F>(&self, text: &str, scale: f32, mut f: F)
+    where
+        F: FnMut(&Glyph, (f32, f32), f32),
+    {
+        let mut x = 0.;
+        let mut y = 0.;
+        let mut previous = None;
+        for character in text.chars() {
+            if character == '\n' {
+                x = 0.;
+                y += self.line_height * scale;
+                previous = None;
+                continue;
+            }
+            let glyph = match self.glyphs.get(&character) {
+                Some(glyph) => glyph,
+                None => continue,
+            };
+            if let Some(previous) = previous {
+                x += self.kerning.get(&(previous, character)).unwrap_or(&0.) * scale;
+            }
+            let corner = (x + glyph.offset.0 * scale, y + glyph.offset.1 * scale);
+            x += glyph.advance * scale;
+            f(glyph, corner, x);
+            previous = Some(character);
+        }
+    }
+
+    /// Queue `text` into `batch`, with the top-left corner of the first line at `x`,`y`.
+    /// Glyphs missing from the font are skipped.
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_text(
+        &self,
+        ctx: &mut Context,
+        batch: &mut SpriteBatch,
+        text: &str,
+        x: f32,
+        y: f32,
+        scale: f32,
+        color: Color,
+    ) {
+        let texture = self.atlas.texture();
+        self.layout(text, scale, |glyph, (glyph_x, glyph_y), _| {
+            batch.draw(
+                ctx,
+                texture,
+                x + glyph_x,
+                y + glyph_y,
+                glyph.rect.w as f32 * scale,
+                glyph.rect.h as f32 * scale,
+                glyph.rect.uv_min,
+                glyph.rect.uv_max,
+                color,
+            )
+        });
+    }
+}
+
+/// `key=value` or `key="value"`
+fn split_attribute(token: &str) -> Option<(&str, &str)> {
+    let mut parts = token.splitn(2, '=');
+    let key = parts.next()?;
+    let value = parts.next()?.trim_matches('"');
+    Some((key, value))
+}
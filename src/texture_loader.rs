@@ -0,0 +1,230 @@
+use crate::{graphics::*, upload::chunk_rows, Context};
+
+use std::collections::VecDeque;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::{mpsc, Arc, Mutex};
+
+#[cfg(target_arch = "wasm32")]
+use std::{cell::RefCell, rc::Rc};
+
+/// Image queued with `TextureLoader::load` or `TextureLoader::load_file`, to tell the
+/// finished textures apart.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TextureLoadId(usize);
+
+enum Source {
+    Bytes(Vec<u8>),
+    #[cfg(not(target_arch = "wasm32"))]
+    File(String),
+}
+
+type Decoded = (TextureLoadId, Result<(u16, u16, Vec<u8>), TextureLoadError>);
+
+/// Texture being filled a few rows at a time
+struct Upload {
+    id: TextureLoadId,
+    texture: Texture,
+    pixels: Vec<u8>,
+    /// First row not uploaded yet
+    row: u32,
+}
+
+/// Loads many images without frame hitches: files are read and decoded on worker
+/// threads, and the pixels uploaded to the GPU on the main thread a few rows at a
+/// time, within the frame's upload budget, see `Context::set_upload_budget_ms`.
+///
+/// ```ignore
+/// // in EventHandler::update
+/// for (id, texture) in self.loader.update(ctx) {
+///     self.textures.insert(id, texture.unwrap());
+/// }
+/// let progress = 1. - self.loader.pending() as f32 / total as f32;
+/// ```
+///
+/// On wasm there are no threads: files are fetched asynchronously, and decoded on
+/// the main thread in `update`, one image per frame at most, then uploaded the same way.
+pub struct TextureLoader {
+    #[cfg(not(target_arch = "wasm32"))]
+    jobs: mpsc::Sender<(TextureLoadId, Source)>,
+    #[cfg(not(target_arch = "wasm32"))]
+    decoded: mpsc::Receiver<Decoded>,
+    /// Encoded images, or the errors fetching them, waiting to be decoded
+    #[cfg(target_arch = "wasm32")]
+    jobs: Rc<RefCell<VecDeque<(TextureLoadId, Result<Source, TextureLoadError>)>>>,
+    uploads: VecDeque<Upload>,
+    next_id: usize,
+    pending: usize,
+}
+
+impl TextureLoader {
+    /// Loader decoding with `workers` threads, ignored on wasm.
+    pub fn new(workers: usize) -> TextureLoader {
+        #[cfg(not(target_arch = "wasm32"))]
+        let (jobs, decoded) = {
+            let (jobs, job_receiver) = mpsc::channel::<(TextureLoadId, Source)>();
+            let (decoded_sender, decoded) = mpsc::channel();
+            let job_receiver = Arc::new(Mutex::new(job_receiver));
+            for _ in 0..workers.max(1) {
+                let job_receiver = job_receiver.clone();
+                let decoded_sender = decoded_sender.clone();
+                std::thread::spawn(move || loop {
+                    // ends when the loader, and with it the sender, is dropped
+                    let (id, source) = match job_receiver.lock().unwrap().recv() {
+                        Ok(job) => job,
+                        Err(_) => return,
+                    };
+                    let bytes = match source {
+                        Source::Bytes(bytes) => Ok(bytes),
+                        Source::File(path) => std::fs::read(&path)
+                            .map_err(|error| TextureLoadError::File(error.into())),
+                    };
+                    let image = bytes.and_then(|bytes| decode_encoded(&bytes));
+                    if decoded_sender.send((id, image)).is_err() {
+                        return;
+                    }
+                });
+            }
+            (jobs, decoded)
+        };
+
+        TextureLoader {
+            #[cfg(not(target_arch = "wasm32"))]
+            jobs,
+            #[cfg(not(target_arch = "wasm32"))]
+            decoded,
+            #[cfg(target_arch = "wasm32")]
+            jobs: Default::default(),
+            uploads: VecDeque::new(),
+            next_id: 0,
+            pending: 0,
+        }
+    }
+
+    /// Queue a PNG or JPEG image, see `Texture::from_encoded`.
+    pub fn load(&mut self, bytes: Vec<u8>) -> TextureLoadId {
+        let id = self.next_id();
+        #[cfg(not(target_arch = "wasm32"))]
+        let _ = self.jobs.send((id, Source::Bytes(bytes)));
+        #[cfg(target_arch = "wasm32")]
+        self.jobs
+            .borrow_mut()
+            .push_back((id, Ok(Source::Bytes(bytes))));
+        id
+    }
+
+    /// Queue an image file, read on a worker thread, or fetched on wasm,
+    /// see `fs::load_file`.
+    pub fn load_file(&mut self, path: &str) -> TextureLoadId {
+        let id = self.next_id();
+        #[cfg(not(target_arch = "wasm32"))]
+        let _ = self.jobs.send((id, Source::File(path.to_string())));
+        #[cfg(target_arch = "wasm32")]
+        {
+            let jobs = self.jobs.clone();
+            crate::fs::load_file(path, move |bytes| {
+                let source = bytes.map(Source::Bytes).map_err(TextureLoadError::File);
+                jobs.borrow_mut().push_back((id, source));
+            });
+        }
+        id
+    }
+
+    /// Images queued and not returned by `update` yet, for a progress bar.
+    pub fn pending(&self) -> usize {
+        self.pending
+    }
+
+    /// Upload decoded images with what is left of the frame's upload budget, and
+    /// return the ones finished since the last call, or the errors reading or
+    /// decoding them. Call once per frame, from `EventHandler::update` for example.
+    pub fn update(
+        &mut self,
+        ctx: &mut Context,
+    ) -> Vec<(TextureLoadId, Result<Texture, TextureLoadError>)> {
+        crate::zone!("texture loader");
+        let mut finished = vec![];
+
+        #[cfg(not(target_arch = "wasm32"))]
+        while let Ok(decoded) = self.decoded.try_recv() {
+            self.start_upload(decoded, &mut finished);
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            let job = self.jobs.borrow_mut().pop_front();
+            if let Some((id, source)) = job {
+                let image = source.and_then(|Source::Bytes(bytes)| decode_encoded(&bytes));
+                self.start_upload((id, image), &mut finished);
+            }
+        }
+
+        if !self.uploads.is_empty() {
+            let uploads = &mut self.uploads;
+            ctx.upload_chunks(|_| {
+                let upload = uploads.front_mut().unwrap();
+                let width = upload.texture.width;
+                let row_size = width as usize * 4;
+                let rows = chunk_rows(width).min(upload.texture.height - upload.row);
+                let offset = upload.row as usize * row_size;
+                let bytes = &upload.pixels[offset..offset + rows as usize * row_size];
+                upload
+                    .texture
+                    .update_part(0, upload.row, width, rows, bytes);
+                upload.row += rows;
+
+                if upload.row == upload.texture.height {
+                    let upload = uploads.pop_front().unwrap();
+                    finished.push((upload.id, Ok(upload.texture)));
+                }
+                !uploads.is_empty()
+            });
+        }
+
+        self.pending -= finished.len();
+        finished
+    }
+
+    fn next_id(&mut self) -> TextureLoadId {
+        self.next_id += 1;
+        self.pending += 1;
+        TextureLoadId(self.next_id - 1)
+    }
+
+    /// An empty texture for a decoded image, or the error
+    fn start_upload(
+        &mut self,
+        (id, image): Decoded,
+        finished: &mut Vec<(TextureLoadId, Result<Texture, TextureLoadError>)>,
+    ) {
+        match image {
+            Ok((width, height, _)) if width == 0 || height == 0 => {
+                finished.push((id, Ok(Texture::from_rgba8(width, height, &[]))))
+            }
+            Ok((width, height, pixels)) => {
+                let texture = Texture::new_render_texture(RenderTextureParams {
+                    width: width as u32,
+                    height: height as u32,
+                    format: PixelFormat::RGBA8,
+                    ..Default::default()
+                });
+                self.uploads.push_back(Upload {
+                    id,
+                    texture,
+                    pixels,
+                    row: 0,
+                });
+            }
+            Err(error) => finished.push((id, Err(error))),
+        }
+    }
+}
+
+impl Drop for TextureLoader {
+    /// Textures still being uploaded are deleted, the workers finish their current
+    /// image and stop.
+    fn drop(&mut self) {
+        for upload in &self.uploads {
+            upload.texture.delete();
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use crate::Context;
+
+/// Handle of a scheduled callback, to cancel it with `Context::cancel_timer`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TimerId(u64);
+
+struct Timer {
+    id: TimerId,
+    /// `date::now()` time to run at
+    due: f64,
+    interval: Option<f64>,
+    callback: Box<dyn FnMut(&mut Context)>,
+}
+
+#[derive(Default)]
+pub(crate) struct Scheduler {
+    timers: Vec<Timer>,
+    next_id: u64,
+    /// Timer whose callback is running, and whether it cancelled itself
+    running: Option<(TimerId, bool)>,
+}
+
+impl Scheduler {
+    fn add(
+        &mut self,
+        due: f64,
+        interval: Option<f64>,
+        callback: Box<dyn FnMut(&mut Context)>,
+    ) -> TimerId {
+        let id = TimerId(self.next_id);
+        self.next_id += 1;
+        self.timers.push(Timer {
+            id,
+            due,
+            interval,
+            callback,
+        });
+        id
+    }
+}
+
+impl Context {
+    /// Call `f` once, from the frame loop, `after` seconds from now.
+    pub fn schedule<F>(&mut self, after: f64, f: F) -> TimerId
+    where
+        F: FnOnce(&mut Context) + 'static,
+    {
+        let mut f = Some(f);
+        let callback = move |ctx: &mut Context| {
+            if let Some(f) = f.take() {
+                f(ctx)
+            }
+        };
+        self.scheduler
+            .add(crate::date::now() + after, None, Box::new(callback))
+    }
+
+    /// Call `f` every `interval` seconds, from the frame loop, until cancelled.
+    /// Calls missed while frames were not running, e.g. in the background, are skipped.
+    pub fn schedule_repeating<F>(&mut self, interval: f64, f: F) -> TimerId
+    where
+        F: FnMut(&mut Context) + 'static,
+    {
+        self.scheduler
+            .add(crate::date::now() + interval, Some(interval), Box::new(f))
+    }
+
+    /// Stop a scheduled callback, does nothing if it already ran.
+    pub fn cancel_timer(&mut self, id: TimerId) {
+        self.scheduler.timers.retain(|timer| timer.id != id);
+        if let Some((running, cancelled)) = &mut self.scheduler.running {
+            if *running == id {
+                *cancelled = true;
+            }
+        }
+    }
+
+    /// Run the callbacks that are due, oldest first. Timers due again, or scheduled,
+    /// by the callbacks run on the next frame at the earliest.
+    pub(crate) fn run_timers(&mut self) {
+        let now = crate::date::now();
+        let mut due: Vec<(f64, TimerId)> = self
+            .scheduler
+            .timers
+            .iter()
+            .filter(|timer| timer.due <= now)
+            .map(|timer| (timer.due, timer.id))
+            .collect();
+        due.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+        for (_, id) in due {
+            let n = match self
+                .scheduler
+                .timers
+                .iter()
+                .position(|timer| timer.id == id)
+            {
+                Some(n) => n,
+                None => continue,
+            };
+            let mut timer = self.scheduler.timers.swap_remove(n);
+
+            self.scheduler.running = Some((id, false));
+            (timer.callback)(self);
+            let cancelled = self
+                .scheduler
+                .running
+                .take()
+                .map_or(false, |(_, cancelled)| cancelled);
+
+            if let (Some(interval), false) = (timer.interval, cancelled) {
+                timer.due = (timer.due + interval).max(now);
+                self.scheduler.timers.push(timer);
+            }
+        }
+    }
+}
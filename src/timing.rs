@@ -0,0 +1,262 @@
+use std::collections::VecDeque;
+
+use crate::Context;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameTiming {
+    /// Seconds since the previous frame
+    pub delta: f64,
+    /// Expected seconds per frame: display refresh interval, or the frame limiter one when it is lower
+    pub target: f64,
+    /// Previous frame took noticeably longer than `target`
+    pub missed_deadline: bool,
+    /// Total number of missed deadlines since start
+    pub missed_frames: u64,
+}
+
+/// Frame times over the last `STATS_WINDOW` frames, in seconds
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameStats {
+    pub average: f64,
+    /// 95% of the frames took at most this long
+    pub percentile_95: f64,
+    pub max: f64,
+    /// Number of frames the stats are computed from, fewer than `STATS_WINDOW` right after start
+    pub frames: usize,
+}
+
+/// When frames actually reached the display, as reported by the platform, for
+/// latency and stutter measurements that CPU frame times miss
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PresentStats {
+    /// Frames shown since the window was created
+    pub present_count: u64,
+    /// Display refreshes counted by the platform, `None` where it does not count them.
+    /// Gaps larger than one between presents are refreshes showing a frame twice.
+    pub refresh_count: Option<u64>,
+    /// When the last of `present_count` frames was shown, in seconds of `date::monotonic`
+    pub present_time: f64,
+}
+
+/// Number of frames `FrameStats` are computed from, about two seconds at 60 Hz.
+pub const STATS_WINDOW: usize = 120;
+
+/// Refresh rate may change when the window is moved to another monitor,
+/// but querying it may be slow, so it is checked once in a while.
+const REFRESH_RATE_CHECK_INTERVAL: f64 = 1.0;
+
+/// Refresh rate assumed when the platform does not report it.
+const DEFAULT_REFRESH_RATE: u32 = 60;
+
+#[derive(Debug, Default)]
+pub(crate) struct FramePacer {
+    refresh_rate: Option<u32>,
+    refresh_rate_checked_at: Option<f64>,
+    last_frame: Option<f64>,
+    timing: Option<FrameTiming>,
+    missed_frames: u64,
+    /// Frame times, the most recent last
+    history: VecDeque<f64>,
+    budget: Option<f64>,
+}
+
+impl FramePacer {
+    pub fn begin_frame(&mut self, max_fps: Option<u32>) {
+        let now = crate::date::monotonic();
+
+        if self
+            .refresh_rate_checked_at
+            .map_or(true, |at| now - at > REFRESH_RATE_CHECK_INTERVAL)
+        {
+            self.refresh_rate_checked_at = Some(now);
+            self.refresh_rate = refresh_rate();
+        }
+
+        let mut fps = self.refresh_rate.unwrap_or(DEFAULT_REFRESH_RATE);
+        if let Some(max_fps) = max_fps {
+            fps = fps.min(max_fps);
+        }
+        self.record_frame(now, 1.0 / fps as f64);
+    }
+
+    /// A frame starting at `now`, `date::monotonic` seconds, expected to take `target`
+    fn record_frame(&mut self, now: f64, target: f64) {
+        // the clock never goes back, unless the platform's is broken: a frame of no time
+        // rather than a negative one skewing the stats
+        let delta = self
+            .last_frame
+            .map_or(target, |last_frame| (now - last_frame).max(0.));
+        if self.last_frame.is_some() {
+            if self.history.len() == STATS_WINDOW {
+                self.history.pop_front();
+            }
+            self.history.push_back(delta);
+        }
+        self.last_frame = Some(now);
+
+        // half a frame of slack, vsync timestamps are never exact
+        let missed_deadline = delta > target * 1.5;
+        if missed_deadline {
+            self.missed_frames += 1;
+        }
+        self.timing = Some(FrameTiming {
+            delta,
+            target,
+            missed_deadline,
+            missed_frames: self.missed_frames,
+        });
+    }
+
+    /// Time the previous frame took, when it was longer than the budget
+    pub fn over_budget(&self) -> Option<f64> {
+        let delta = *self.history.back()?;
+        match self.budget {
+            Some(budget) if delta > budget => Some(delta),
+            _ => None,
+        }
+    }
+
+    /// Times of the last `STATS_WINDOW` frames, the most recent last
+    pub fn frame_times(&self) -> impl Iterator<Item = f64> + '_ {
+        self.history.iter().copied()
+    }
+
+    fn stats(&self) -> Option<FrameStats> {
+        if self.history.is_empty() {
+            return None;
+        }
+        let mut sorted: Vec<f64> = self.history.iter().copied().collect();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let frames = sorted.len();
+        Some(FrameStats {
+            average: sorted.iter().sum::<f64>() / frames as f64,
+            percentile_95: sorted[((frames as f64 * 0.95).ceil() as usize).max(1) - 1],
+            max: sorted[frames - 1],
+            frames,
+        })
+    }
+}
+
+fn refresh_rate() -> Option<u32> {
+    let rate = unsafe { sapp::sapp_refresh_rate() };
+    if rate > 0 {
+        Some(rate as u32)
+    } else {
+        None
+    }
+}
+
+impl Context {
+    /// Refresh rate of the monitor the window is on, in Hz.
+    /// On wasm it is estimated from the browser's animation frame rate.
+    pub fn display_refresh_rate(&self) -> Option<u32> {
+        refresh_rate()
+    }
+
+    /// Timing of the current frame, meant to be checked from `EventHandler::update`.
+    pub fn frame_timing(&self) -> FrameTiming {
+        self.frame_pacer.timing.unwrap_or(FrameTiming {
+            delta: 1.0 / DEFAULT_REFRESH_RATE as f64,
+            target: 1.0 / DEFAULT_REFRESH_RATE as f64,
+            missed_deadline: false,
+            missed_frames: 0,
+        })
+    }
+
+    /// Rolling stats of the time between frames, `None` before the second frame.
+    /// Better suited than `frame_timing` to react to sustained drops rather than spikes.
+    pub fn frame_stats(&self) -> Option<FrameStats> {
+        self.frame_pacer.stats()
+    }
+
+    /// Stats of the last frame shown on the display, `None` where the platform does not
+    /// report them. Linux uses GLX_OML_sync_control, exact to the refresh. On the web it is
+    /// the requestAnimationFrame timestamp, when the browser began the current frame,
+    /// about when the previous one was shown, and there is no refresh count.
+    /// Windows GL contexts have no way to tell, DXGI frame statistics need a DXGI swapchain.
+    pub fn present_stats(&self) -> Option<PresentStats> {
+        let mut present_count = 0;
+        let mut refresh_count = 0;
+        let mut present_time = 0.;
+        let stats = unsafe {
+            sapp::sapp_present_stats(
+                &mut present_count as *mut _,
+                &mut refresh_count as *mut _,
+                &mut present_time as *mut _,
+            )
+        };
+        let refresh_count = match stats {
+            0 => return None,
+            1 => Some(refresh_count),
+            _ => None,
+        };
+        Some(PresentStats {
+            present_count,
+            refresh_count,
+            present_time,
+        })
+    }
+
+    /// Frames taking longer than `budget` seconds trigger
+    /// `EventHandler::frame_budget_exceeded_event`, `None` to turn it off, the default.
+    pub fn set_frame_budget(&mut self, budget: Option<f64>) {
+        self.frame_pacer.budget = budget;
+    }
+
+    pub fn frame_budget(&self) -> Option<f64> {
+        self.frame_pacer.budget
+    }
+
+    /// Send the input events that came while drawing right after `EventHandler::draw`,
+    /// followed by `EventHandler::late_latch_event`, for lower input to photon latency.
+    /// Off by default. On the web browsers only send events between frames, there
+    /// `late_latch_event` comes without new input.
+    pub fn set_late_latch(&mut self, late_latch: bool) {
+        self.late_latch = late_latch;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TARGET: f64 = 1. / 60.;
+
+    #[test]
+    fn stats_and_budget() {
+        let mut pacer = FramePacer {
+            budget: Some(0.02),
+            ..Default::default()
+        };
+        for now in &[10., 10.016, 10.032, 10.082] {
+            pacer.record_frame(*now, TARGET);
+        }
+        let timing = pacer.timing.unwrap();
+        assert!(timing.missed_deadline);
+        assert_eq!(timing.missed_frames, 1);
+        assert!((pacer.over_budget().unwrap() - 0.05).abs() < 1e-9);
+        let stats = pacer.stats().unwrap();
+        assert_eq!(stats.frames, 3);
+        assert!((stats.max - 0.05).abs() < 1e-9);
+    }
+
+    /// A timestamp before the previous one is a frame of no time, not a negative one
+    #[test]
+    fn backwards_step() {
+        let mut pacer = FramePacer {
+            budget: Some(0.02),
+            ..Default::default()
+        };
+        for now in &[100., 100.016, 40., 40.016] {
+            pacer.record_frame(*now, TARGET);
+        }
+        let times: Vec<f64> = pacer.frame_times().collect();
+        assert_eq!(times.len(), 3);
+        assert_eq!(times[1], 0.);
+        assert!(times.iter().all(|time| (0. ..=TARGET).contains(time)));
+        let stats = pacer.stats().unwrap();
+        assert!(stats.average >= 0. && stats.max <= TARGET);
+        assert_eq!(pacer.timing.unwrap().missed_frames, 0);
+        assert_eq!(pacer.over_budget(), None);
+    }
+}
@@ -0,0 +1,174 @@
+use crate::{gl::*, graphics::Context};
+
+/// Name of the uniform block a shader reads its `ShaderMeta::uniforms` from, to get
+/// them from `Context::apply_transient_uniforms` with a single bind:
+///
+/// ```ignore
+/// #version 140
+/// layout(std140) uniform TransientUniforms {
+///     mat4 mvp;
+///     vec4 tint;
+/// };
+/// ```
+///
+/// Shaders without it, GLSL 100 ones included, get the same data with a `glUniform`
+/// call per uniform.
+pub const TRANSIENT_UNIFORMS_BLOCK: &str = "TransientUniforms";
+
+/// Uniform buffer binding the `TRANSIENT_UNIFORMS_BLOCK` of every shader reads from
+pub(crate) const TRANSIENT_UNIFORMS_BINDING: GLuint = 0;
+
+pub(crate) const GL_UNIFORM_BUFFER: GLenum = 0x8a11;
+const GL_UNIFORM_BUFFER_OFFSET_ALIGNMENT: GLenum = 0x8a34;
+const GL_STREAM_DRAW: GLenum = 0x88e0;
+
+/// Frames the GPU may still be reading a buffer of the ring from
+const FRAMES: usize = 3;
+/// First size of each buffer, doubled when a frame needs more
+const MIN_CAPACITY: usize = 64 * 1024;
+
+/// Uniforms allocated for the current frame by `Context::alloc_transient_uniforms`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TransientUniforms {
+    frame: u64,
+    /// In bytes
+    offset: usize,
+    size: usize,
+}
+
+#[derive(Default)]
+pub(crate) struct UniformRing {
+    /// Buffer and capacity per frame in flight, created on first use
+    buffers: Vec<(GLuint, usize)>,
+    frame: u64,
+    /// Everything allocated this frame, each block at a multiple of `alignment`
+    staging: Vec<f32>,
+    /// Floats of `staging` already in this frame's buffer
+    flushed: usize,
+    /// `GL_UNIFORM_BUFFER_OFFSET_ALIGNMENT`, 0 until queried
+    alignment: usize,
+}
+
+impl UniformRing {
+    /// Forget the buffers of a lost GL context, the frame's blocks are uploaded again
+    pub(crate) fn lost(&mut self) {
+        self.buffers.clear();
+        self.flushed = 0;
+    }
+
+    /// This frame's buffer, with everything allocated so far
+    unsafe fn flush(&mut self) -> GLuint {
+        if self.buffers.is_empty() {
+            for _ in 0..FRAMES {
+                let mut buffer = 0;
+                glGenBuffers(1, &mut buffer as *mut _);
+                self.buffers.push((buffer, 0));
+            }
+        }
+        let (buffer, capacity) = &mut self.buffers[self.frame as usize % FRAMES];
+        glBindBuffer(GL_UNIFORM_BUFFER, *buffer);
+        let bytes = self.staging.len() * 4;
+        if bytes > *capacity {
+            *capacity = bytes.next_power_of_two().max(MIN_CAPACITY);
+            glBufferData(
+                GL_UNIFORM_BUFFER,
+                *capacity as _,
+                std::ptr::null(),
+                GL_STREAM_DRAW,
+            );
+            self.flushed = 0;
+        }
+        if self.flushed < self.staging.len() {
+            glBufferSubData(
+                GL_UNIFORM_BUFFER,
+                (self.flushed * 4) as _,
+                ((self.staging.len() - self.flushed) * 4) as _,
+                self.staging[self.flushed..].as_ptr() as *const _,
+            );
+            self.flushed = self.staging.len();
+        }
+        *buffer
+    }
+}
+
+impl Context {
+    /// Copy per-draw uniforms into this frame's uniform buffer, to bind them later
+    /// with `apply_transient_uniforms`. `uniforms` follows the std140 rules of uniform
+    /// blocks for the `ShaderMeta::uniforms` of the shaders it is applied to: `vec3`
+    /// aligned like `vec4`, array elements 16 bytes apart.
+    ///
+    /// All the blocks of a frame are packed in one buffer, uploaded at once before
+    /// the first draw reading them, instead of thousands of `glUniform` calls.
+    /// The handle is valid until the end of the frame.
+    pub fn alloc_transient_uniforms<U>(&mut self, uniforms: &U) -> TransientUniforms {
+        let uniform_buffers = self.features().uniform_buffers;
+        let ring = &mut self.uniform_ring;
+        if ring.alignment == 0 {
+            let mut alignment: GLint = 0;
+            if uniform_buffers {
+                unsafe {
+                    glGetIntegerv(GL_UNIFORM_BUFFER_OFFSET_ALIGNMENT, &mut alignment as *mut _);
+                }
+            }
+            // std140 blocks start on a vec4 anyway
+            ring.alignment = (alignment as usize).max(16);
+        }
+
+        let size = std::mem::size_of::<U>();
+        let offset =
+            (ring.staging.len() * 4 + ring.alignment - 1) / ring.alignment * ring.alignment;
+        ring.staging.resize((offset + size + 3) / 4, 0.);
+        unsafe {
+            std::ptr::copy_nonoverlapping(
+                uniforms as *const U as *const u8,
+                ring.staging.as_mut_ptr().add(offset / 4) as *mut u8,
+                size,
+            );
+        }
+        TransientUniforms {
+            frame: ring.frame,
+            offset,
+            size,
+        }
+    }
+
+    /// Set the uniforms of the current pipeline's shader from a block of this frame:
+    /// a bind of the buffer range for shaders declaring `TRANSIENT_UNIFORMS_BLOCK`
+    /// with `Features::uniform_buffers`, `glUniform` calls for the others.
+    pub fn apply_transient_uniforms(&mut self, uniforms: TransientUniforms) {
+        assert_eq!(
+            uniforms.frame, self.uniform_ring.frame,
+            "TransientUniforms of a previous frame"
+        );
+        if !self.features().uniform_buffers || !self.transient_uniforms_block() {
+            let start = uniforms.offset / 4;
+            let staging = std::mem::take(&mut self.uniform_ring.staging);
+            self.apply_uniforms_std140(&staging[start..start + (uniforms.size + 3) / 4]);
+            self.uniform_ring.staging = staging;
+            return;
+        }
+
+        self.check_gl_errors("before apply_transient_uniforms");
+        self.count(|counters| counters.uniforms += 1);
+        unsafe {
+            let buffer = self.uniform_ring.flush();
+            glBindBufferRange(
+                GL_UNIFORM_BUFFER,
+                TRANSIENT_UNIFORMS_BINDING,
+                buffer,
+                uniforms.offset as _,
+                uniforms.size as _,
+            );
+        }
+    }
+
+    /// Start a new frame of transient uniforms, on the next buffer of the ring: done
+    /// at the start of every frame of miniquad's window, a `Context::new_external`
+    /// calls it once per frame itself.
+    pub fn reset_transient_uniforms(&mut self) {
+        let ring = &mut self.uniform_ring;
+        ring.frame += 1;
+        ring.staging.clear();
+        ring.flushed = 0;
+    }
+}
@@ -0,0 +1,170 @@
+use crate::{graphics::*, Context};
+
+use std::collections::VecDeque;
+
+/// Bytes copied per GL call, for textures whole rows, at least one
+const CHUNK_SIZE: usize = 256 * 1024;
+
+/// Rows of a `width` pixels wide RGBA8 texture copied per GL call
+pub(crate) fn chunk_rows(width: u32) -> u32 {
+    (CHUNK_SIZE / (width as usize * 4)).max(1) as u32
+}
+
+enum Target {
+    Buffer(Buffer),
+    /// Rows of `width` RGBA8 pixels from `x`, `y`
+    Texture {
+        texture: Texture,
+        x: u32,
+        y: u32,
+        width: u32,
+    },
+}
+
+struct Upload {
+    target: Target,
+    bytes: Vec<u8>,
+    /// Bytes copied so far
+    done: usize,
+}
+
+pub(crate) struct UploadQueue {
+    uploads: VecDeque<Upload>,
+    /// Seconds per frame
+    budget: f64,
+    /// Seconds spent this frame, by the queue and by `TextureLoader`s
+    spent: f64,
+}
+
+impl Default for UploadQueue {
+    fn default() -> UploadQueue {
+        UploadQueue {
+            uploads: VecDeque::new(),
+            budget: 0.002,
+            spent: 0.,
+        }
+    }
+}
+
+impl Context {
+    /// Time spent per frame copying the data queued with `queue_buffer_update` and
+    /// `queue_texture_update`, before `EventHandler::update`, and the images of
+    /// `TextureLoader::update`, 2 ms by default, shared by all of them.
+    /// Each still copies at least one chunk every frame, however small the budget.
+    pub fn set_upload_budget_ms(&mut self, budget: f32) {
+        self.uploads.budget = budget as f64 / 1000.;
+    }
+
+    /// `Buffer::update` spread over the next frames, `data` is copied now.
+    /// Uploads happen in the order they were queued; updating or deleting the buffer
+    /// directly before this one is done races with it.
+    pub fn queue_buffer_update<T>(&mut self, buffer: &Buffer, data: &[T]) {
+        let size = std::mem::size_of_val(data);
+        assert!(size <= buffer.size());
+        let bytes = unsafe { std::slice::from_raw_parts(data.as_ptr() as *const u8, size) };
+        self.uploads.uploads.push_back(Upload {
+            target: Target::Buffer(*buffer),
+            bytes: bytes.to_vec(),
+            done: 0,
+        });
+    }
+
+    /// `Texture::update_part` spread over the next frames, a few rows at a time,
+    /// `bytes` is copied now. Same ordering as `queue_buffer_update`.
+    pub fn queue_texture_update(
+        &mut self,
+        texture: Texture,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        bytes: &[u8],
+    ) {
+        assert!(x + width <= texture.width && y + height <= texture.height);
+        assert_eq!(bytes.len(), (width * height * 4) as usize);
+        if bytes.is_empty() {
+            return;
+        }
+        self.uploads.uploads.push_back(Upload {
+            target: Target::Texture {
+                texture,
+                x,
+                y,
+                width,
+            },
+            bytes: bytes.to_vec(),
+            done: 0,
+        });
+    }
+
+    /// Queued updates not finished yet, for a loading screen.
+    pub fn upload_queue_len(&self) -> usize {
+        self.uploads.uploads.len()
+    }
+
+    /// Bytes of the queued updates still to copy, for a progress bar.
+    pub fn upload_queue_bytes(&self) -> usize {
+        self.uploads
+            .uploads
+            .iter()
+            .map(|upload| upload.bytes.len() - upload.done)
+            .sum()
+    }
+
+    /// Copy queued data until the budget is spent, called once per frame
+    pub(crate) fn run_uploads(&mut self) {
+        self.uploads.spent = 0.;
+        if self.uploads.uploads.is_empty() {
+            return;
+        }
+        crate::zone!("queued uploads");
+        self.upload_chunks(|ctx| {
+            if let Some(upload) = ctx.uploads.uploads.pop_front() {
+                if let Some(upload) = ctx.upload_chunk(upload) {
+                    ctx.uploads.uploads.push_front(upload);
+                }
+            }
+            !ctx.uploads.uploads.is_empty()
+        });
+    }
+
+    /// Call `chunk` until it returns false, or the frame's upload budget is spent.
+    /// Called at least once.
+    pub(crate) fn upload_chunks(&mut self, mut chunk: impl FnMut(&mut Context) -> bool) {
+        let start = crate::date::monotonic();
+        let spent = self.uploads.spent;
+        while chunk(self) && spent + crate::date::monotonic() - start < self.uploads.budget {}
+        self.uploads.spent = spent + crate::date::monotonic() - start;
+    }
+
+    /// The upload, if it isn't done after this chunk
+    fn upload_chunk(&mut self, mut upload: Upload) -> Option<Upload> {
+        let left = &upload.bytes[upload.done..];
+        let size = match upload.target {
+            Target::Buffer(buffer) => {
+                let size = left.len().min(CHUNK_SIZE);
+                unsafe { buffer.update_raw(self, upload.done, left.as_ptr(), size) };
+                size
+            }
+            Target::Texture {
+                texture,
+                x,
+                y,
+                width,
+            } => {
+                let row_size = width as usize * 4;
+                let rows = (chunk_rows(width) as usize).min(left.len() / row_size);
+                let row = (upload.done / row_size) as u32;
+                let size = rows * row_size;
+                texture.update_part(x, y + row, width, rows as u32, &left[..size]);
+                size
+            }
+        };
+        upload.done += size;
+        if upload.done < upload.bytes.len() {
+            Some(upload)
+        } else {
+            None
+        }
+    }
+}
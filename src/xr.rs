@@ -0,0 +1,88 @@
+use std::os::raw::c_void;
+
+use crate::{gl::*, graphics::*};
+
+/// Native handles of miniquad's GL context, the fields of the OpenXR graphics binding
+/// a session renders with it: `XrGraphicsBindingOpenGLWin32KHR` on Windows,
+/// `XrGraphicsBindingOpenGLXlibKHR` on Linux.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GlContextHandles {
+    Win32 {
+        h_dc: *mut c_void,
+        h_glrc: *mut c_void,
+    },
+    Xlib {
+        x_display: *mut c_void,
+        visualid: u32,
+        glx_fb_config: *mut c_void,
+        glx_drawable: u64,
+        glx_context: *mut c_void,
+    },
+}
+
+impl Context {
+    /// Handles of the GL context, `None` on the web and with `Context::new_external`,
+    /// whose creator has them already.
+    pub fn gl_context_handles(&self) -> Option<GlContextHandles> {
+        if self.external_size.is_some() {
+            return None;
+        }
+        let mut display = std::ptr::null_mut();
+        let mut context = std::ptr::null_mut();
+        let mut config = std::ptr::null_mut();
+        let mut drawable: u64 = 0;
+        let mut visual_id: u64 = 0;
+        let kind = unsafe {
+            sapp::sapp_gl_context_handles(
+                &mut display as *mut _,
+                &mut context as *mut _,
+                &mut config as *mut _,
+                &mut drawable as *mut _,
+                &mut visual_id as *mut _,
+            )
+        };
+        match kind {
+            1 => Some(GlContextHandles::Win32 {
+                h_dc: display,
+                h_glrc: context,
+            }),
+            2 => Some(GlContextHandles::Xlib {
+                x_display: display,
+                visualid: visual_id as u32,
+                glx_fb_config: config,
+                glx_drawable: drawable,
+                glx_context: context,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Let someone else pace the frames, like an XR runtime whose `xrWaitFrame` blocks
+    /// until the headset wants the next one: swapping buffers stops waiting for vsync and
+    /// `set_max_fps` limits are not applied, until this is set back to false.
+    /// The window is still presented each frame, e.g. with a mirror of the eyes.
+    pub fn set_external_pacing(&mut self, external: bool) {
+        self.frame_limiter.external_pacing = external;
+        unsafe {
+            sapp::sapp_set_swap_interval(if external { 0 } else { 1 });
+        }
+    }
+}
+
+impl RenderPass {
+    /// Pass rendering into an image of an OpenXR swapchain, the GL texture name of an
+    /// `XrSwapchainImageOpenGLKHR` of a 2D swapchain of `width`x`height`. The runtime
+    /// owns the image: make a pass per image once, after `xrEnumerateSwapchainImages`,
+    /// and begin the one `xrAcquireSwapchainImage` picks each frame. Deleting the pass
+    /// leaves the image alone.
+    pub fn new_xr_swapchain_image(
+        context: &mut Context,
+        image: GLuint,
+        width: u32,
+        height: u32,
+        depth_img: impl Into<Option<Texture>>,
+    ) -> RenderPass {
+        let image = Texture::from_raw_gl(image, width, height, Default::default());
+        RenderPass::new(context, image, depth_img)
+    }
+}